@@ -0,0 +1,54 @@
+use std::collections::BTreeMap;
+
+use acir::{
+    native_types::{Witness, WitnessMap},
+    FieldElement,
+};
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+
+const SIZES: [u32; 3] = [1 << 10, 1 << 16, 1 << 20];
+
+fn bench_insert(c: &mut Criterion) {
+    let mut group = c.benchmark_group("witness_map_insert");
+    for size in SIZES {
+        group.bench_with_input(BenchmarkId::new("btree_map", size), &size, |b, &size| {
+            b.iter(|| {
+                let mut map = BTreeMap::new();
+                for i in 0..size {
+                    map.insert(Witness(i), FieldElement::from(i as u128));
+                }
+                black_box(map)
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("witness_map", size), &size, |b, &size| {
+            b.iter(|| {
+                let mut map = WitnessMap::new();
+                for i in 0..size {
+                    map.insert(Witness(i), FieldElement::from(i as u128));
+                }
+                black_box(map)
+            })
+        });
+    }
+    group.finish();
+}
+
+fn bench_get(c: &mut Criterion) {
+    let mut group = c.benchmark_group("witness_map_get");
+    for size in SIZES {
+        let btree_map: BTreeMap<_, _> =
+            (0..size).map(|i| (Witness(i), FieldElement::from(i as u128))).collect();
+        let witness_map = WitnessMap::from(btree_map.clone());
+
+        group.bench_with_input(BenchmarkId::new("btree_map", size), &size, |b, &size| {
+            b.iter(|| (0..size).filter(|i| btree_map.contains_key(&Witness(*i))).count())
+        });
+        group.bench_with_input(BenchmarkId::new("witness_map", size), &size, |b, &size| {
+            b.iter(|| (0..size).filter(|i| witness_map.contains_key(&Witness(*i))).count())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_insert, bench_get);
+criterion_main!(benches);
@@ -20,8 +20,12 @@ pub enum BlackBoxFunc {
     RANGE,
     /// Calculates the SHA256 hash of the inputs.
     SHA256,
+    /// Calculates the SHA512 hash of the inputs.
+    Sha512,
     /// Calculates the Blake2s hash of the inputs.
     Blake2s,
+    /// Calculates the Blake3 hash of the inputs.
+    Blake3,
     /// Verifies a Schnorr signature over the embedded curve.
     SchnorrVerify,
     /// Calculates a Pedersen commitment to the inputs.
@@ -31,16 +35,66 @@ pub enum BlackBoxFunc {
     ///
     /// This is implemented using the `Blake2s` hash function.
     /// The "128" in the name specifies that this function should have 128 bits of security.
+    ///
+    /// Deprecated in favour of [`HashToField`][BlackBoxFunc::HashToField], which follows RFC 9380.
     HashToField128Security,
     /// Verifies a ECDSA signature over the secp256k1 curve.
     EcdsaSecp256k1,
     /// Performs scalar multiplication over the embedded curve on which [`FieldElement`][acir_field::FieldElement] is defined.
+    ///
+    /// Deprecated in favour of [`MultiScalarMul`][BlackBoxFunc::MultiScalarMul], which supports arbitrary bases.
     FixedBaseScalarMul,
     /// Calculates the Keccak256 hash of the inputs.
     Keccak256,
     /// Compute a recursive aggregation object when verifying a proof inside another circuit.
     /// This outputted aggregation object will then be either checked in a top-level verifier or aggregated upon again.
     RecursiveAggregation,
+    /// Applies the Poseidon2 permutation to a state of a given width.
+    Poseidon2Permutation,
+    /// Verifies an Ed25519 signature over a message of bytes.
+    EddsaEd25519,
+    /// Verifies an EdDSA signature over the Baby Jubjub curve, using the Poseidon hash function.
+    EddsaBabyJubjub,
+    /// Adds two points on the embedded curve.
+    EmbeddedCurveAdd,
+    /// Doubles a point on the embedded curve.
+    EmbeddedCurveDouble,
+    /// Computes a linear combination of points on the embedded curve.
+    MultiScalarMul,
+    /// Adds two big integers modulo their shared modulus.
+    BigIntAdd,
+    /// Multiplies two big integers modulo their shared modulus.
+    BigIntMul,
+    /// Divides two big integers modulo their shared modulus.
+    BigIntDiv,
+    /// Converts a little-endian array of bytes into a big integer with a given modulus.
+    BigIntFromLeBytes,
+    /// Converts a big integer into a little-endian array of bytes.
+    BigIntToLeBytes,
+    /// Applies the keccak-f[1600] permutation to a state of 25 lanes of 64 bits.
+    Keccakf1600,
+    /// Encrypts the inputs using AES-128 in CBC mode.
+    Aes128Encrypt,
+    /// Applies the SHA256 compression function to a single block of the message.
+    Sha256Compression,
+    /// Hashes the inputs to a [`FieldElement`][acir_field::FieldElement] following the `hash_to_field` procedure of RFC 9380.
+    HashToField,
+    /// Hashes the inputs to a point on the secp256k1 curve following RFC 9380.
+    HashToCurveSecp256k1,
+    /// Hashes the inputs to a point on the embedded curve following RFC 9380.
+    HashToEmbeddedCurve,
+    /// Recovers the secp256k1 public key which produced an ECDSA signature, as the `ecrecover` precompile of Ethereum does.
+    EcdsaSecp256k1Recover,
+    /// Range constraints on several [`FieldElement`][acir_field::FieldElement]s, each with its own number of bits.
+    RangeBatch,
+    /// Bitwise NOT.
+    NOT,
+    /// Logical left shift by a constant number of bits.
+    SHL,
+    /// Logical right shift by a constant number of bits.
+    SHR,
+    /// Calculates the Blake2b hash of the inputs.
+    Blake2b,
 }
 
 impl std::fmt::Display for BlackBoxFunc {
@@ -50,11 +104,54 @@ impl std::fmt::Display for BlackBoxFunc {
 }
 
 impl BlackBoxFunc {
+    /// Every black box function, in declaration order.
+    pub const ALL: [BlackBoxFunc; 37] = [
+        BlackBoxFunc::AND,
+        BlackBoxFunc::XOR,
+        BlackBoxFunc::RANGE,
+        BlackBoxFunc::SHA256,
+        BlackBoxFunc::Sha512,
+        BlackBoxFunc::Blake2s,
+        BlackBoxFunc::Blake3,
+        BlackBoxFunc::SchnorrVerify,
+        BlackBoxFunc::Pedersen,
+        BlackBoxFunc::HashToField128Security,
+        BlackBoxFunc::EcdsaSecp256k1,
+        BlackBoxFunc::FixedBaseScalarMul,
+        BlackBoxFunc::Keccak256,
+        BlackBoxFunc::RecursiveAggregation,
+        BlackBoxFunc::Poseidon2Permutation,
+        BlackBoxFunc::EddsaEd25519,
+        BlackBoxFunc::EddsaBabyJubjub,
+        BlackBoxFunc::EmbeddedCurveAdd,
+        BlackBoxFunc::EmbeddedCurveDouble,
+        BlackBoxFunc::MultiScalarMul,
+        BlackBoxFunc::BigIntAdd,
+        BlackBoxFunc::BigIntMul,
+        BlackBoxFunc::BigIntDiv,
+        BlackBoxFunc::BigIntFromLeBytes,
+        BlackBoxFunc::BigIntToLeBytes,
+        BlackBoxFunc::Keccakf1600,
+        BlackBoxFunc::Aes128Encrypt,
+        BlackBoxFunc::Sha256Compression,
+        BlackBoxFunc::HashToField,
+        BlackBoxFunc::HashToCurveSecp256k1,
+        BlackBoxFunc::HashToEmbeddedCurve,
+        BlackBoxFunc::EcdsaSecp256k1Recover,
+        BlackBoxFunc::RangeBatch,
+        BlackBoxFunc::NOT,
+        BlackBoxFunc::SHL,
+        BlackBoxFunc::SHR,
+        BlackBoxFunc::Blake2b,
+    ];
+
     pub fn name(&self) -> &'static str {
         match self {
             BlackBoxFunc::SHA256 => "sha256",
+            BlackBoxFunc::Sha512 => "sha512",
             BlackBoxFunc::SchnorrVerify => "schnorr_verify",
             BlackBoxFunc::Blake2s => "blake2s",
+            BlackBoxFunc::Blake3 => "blake3",
             BlackBoxFunc::Pedersen => "pedersen",
             BlackBoxFunc::HashToField128Security => "hash_to_field_128_security",
             BlackBoxFunc::EcdsaSecp256k1 => "ecdsa_secp256k1",
@@ -64,13 +161,38 @@ impl BlackBoxFunc {
             BlackBoxFunc::RANGE => "range",
             BlackBoxFunc::Keccak256 => "keccak256",
             BlackBoxFunc::RecursiveAggregation => "recursive_aggregation",
+            BlackBoxFunc::Poseidon2Permutation => "poseidon2_permutation",
+            BlackBoxFunc::EddsaEd25519 => "eddsa_ed25519",
+            BlackBoxFunc::EddsaBabyJubjub => "eddsa_baby_jubjub",
+            BlackBoxFunc::EmbeddedCurveAdd => "embedded_curve_add",
+            BlackBoxFunc::EmbeddedCurveDouble => "embedded_curve_double",
+            BlackBoxFunc::MultiScalarMul => "multi_scalar_mul",
+            BlackBoxFunc::BigIntAdd => "bigint_add",
+            BlackBoxFunc::BigIntMul => "bigint_mul",
+            BlackBoxFunc::BigIntDiv => "bigint_div",
+            BlackBoxFunc::BigIntFromLeBytes => "bigint_from_le_bytes",
+            BlackBoxFunc::BigIntToLeBytes => "bigint_to_le_bytes",
+            BlackBoxFunc::Keccakf1600 => "keccakf1600",
+            BlackBoxFunc::Aes128Encrypt => "aes128_encrypt",
+            BlackBoxFunc::Sha256Compression => "sha256_compression",
+            BlackBoxFunc::HashToField => "hash_to_field",
+            BlackBoxFunc::HashToCurveSecp256k1 => "hash_to_curve_secp256k1",
+            BlackBoxFunc::HashToEmbeddedCurve => "hash_to_embedded_curve",
+            BlackBoxFunc::EcdsaSecp256k1Recover => "ecdsa_secp256k1_recover",
+            BlackBoxFunc::RangeBatch => "range_batch",
+            BlackBoxFunc::NOT => "not",
+            BlackBoxFunc::SHL => "shl",
+            BlackBoxFunc::SHR => "shr",
+            BlackBoxFunc::Blake2b => "blake2b",
         }
     }
     pub fn lookup(op_name: &str) -> Option<BlackBoxFunc> {
         match op_name {
             "sha256" => Some(BlackBoxFunc::SHA256),
+            "sha512" => Some(BlackBoxFunc::Sha512),
             "schnorr_verify" => Some(BlackBoxFunc::SchnorrVerify),
             "blake2s" => Some(BlackBoxFunc::Blake2s),
+            "blake3" => Some(BlackBoxFunc::Blake3),
             "pedersen" => Some(BlackBoxFunc::Pedersen),
             "hash_to_field_128_security" => Some(BlackBoxFunc::HashToField128Security),
             "ecdsa_secp256k1" => Some(BlackBoxFunc::EcdsaSecp256k1),
@@ -80,6 +202,29 @@ impl BlackBoxFunc {
             "range" => Some(BlackBoxFunc::RANGE),
             "keccak256" => Some(BlackBoxFunc::Keccak256),
             "recursive_aggregation" => Some(BlackBoxFunc::RecursiveAggregation),
+            "poseidon2_permutation" => Some(BlackBoxFunc::Poseidon2Permutation),
+            "eddsa_ed25519" => Some(BlackBoxFunc::EddsaEd25519),
+            "eddsa_baby_jubjub" => Some(BlackBoxFunc::EddsaBabyJubjub),
+            "embedded_curve_add" => Some(BlackBoxFunc::EmbeddedCurveAdd),
+            "embedded_curve_double" => Some(BlackBoxFunc::EmbeddedCurveDouble),
+            "multi_scalar_mul" => Some(BlackBoxFunc::MultiScalarMul),
+            "bigint_add" => Some(BlackBoxFunc::BigIntAdd),
+            "bigint_mul" => Some(BlackBoxFunc::BigIntMul),
+            "bigint_div" => Some(BlackBoxFunc::BigIntDiv),
+            "bigint_from_le_bytes" => Some(BlackBoxFunc::BigIntFromLeBytes),
+            "bigint_to_le_bytes" => Some(BlackBoxFunc::BigIntToLeBytes),
+            "keccakf1600" => Some(BlackBoxFunc::Keccakf1600),
+            "aes128_encrypt" => Some(BlackBoxFunc::Aes128Encrypt),
+            "sha256_compression" => Some(BlackBoxFunc::Sha256Compression),
+            "hash_to_field" => Some(BlackBoxFunc::HashToField),
+            "hash_to_curve_secp256k1" => Some(BlackBoxFunc::HashToCurveSecp256k1),
+            "hash_to_embedded_curve" => Some(BlackBoxFunc::HashToEmbeddedCurve),
+            "ecdsa_secp256k1_recover" => Some(BlackBoxFunc::EcdsaSecp256k1Recover),
+            "range_batch" => Some(BlackBoxFunc::RangeBatch),
+            "not" => Some(BlackBoxFunc::NOT),
+            "shl" => Some(BlackBoxFunc::SHL),
+            "shr" => Some(BlackBoxFunc::SHR),
+            "blake2b" => Some(BlackBoxFunc::Blake2b),
             _ => None,
         }
     }
@@ -106,4 +251,9 @@ mod tests {
             )
         }
     }
+
+    #[test]
+    fn all_lists_every_function() {
+        assert_eq!(BlackBoxFunc::ALL.to_vec(), BlackBoxFunc::iter().collect::<Vec<_>>());
+    }
 }
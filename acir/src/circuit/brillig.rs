@@ -26,7 +26,130 @@ pub struct Brillig {
     // Each element of this vector corresponds to a single foreign call but may contain several values.
     pub foreign_call_results: Vec<ForeignCallResult>,
     /// The Brillig VM bytecode to be executed by this ACIR opcode.
+    #[serde(with = "compact_bytecode")]
     pub bytecode: Vec<brillig_vm::Opcode>,
     /// Predicate of the Brillig execution - indicates if it should be skipped
     pub predicate: Option<Expression>,
 }
+
+/// (De)serializes Brillig bytecode [compactly][brillig_vm::encode_bytecode] in binary formats, such as the
+/// one of [`Circuit::write`][super::Circuit::write], and as a list of opcodes in human readable formats.
+///
+/// Bytecode serialized as a list of opcodes, as in circuit format versions prior to 2, is still read
+/// from binary formats.
+mod compact_bytecode {
+    use brillig_vm::{decode_bytecode, encode_bytecode, Opcode};
+    use serde::{
+        de::{Error, SeqAccess, Visitor},
+        Deserialize, Deserializer, Serialize, Serializer,
+    };
+
+    pub(super) fn serialize<S: Serializer>(
+        bytecode: &[Opcode],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            bytecode.serialize(serializer)
+        } else {
+            serializer.serialize_bytes(&encode_bytecode(bytecode))
+        }
+    }
+
+    pub(super) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<Opcode>, D::Error> {
+        if deserializer.is_human_readable() {
+            Vec::deserialize(deserializer)
+        } else {
+            deserializer.deserialize_any(BytecodeVisitor)
+        }
+    }
+
+    struct BytecodeVisitor;
+
+    impl<'de> Visitor<'de> for BytecodeVisitor {
+        type Value = Vec<Opcode>;
+
+        fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+            write!(formatter, "compact Brillig bytecode or a list of Brillig opcodes")
+        }
+
+        fn visit_bytes<E: Error>(self, bytes: &[u8]) -> Result<Self::Value, E> {
+            decode_bytecode(bytes).map_err(E::custom)
+        }
+
+        fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+            let mut bytecode = Vec::new();
+            while let Some(opcode) = seq.next_element()? {
+                bytecode.push(opcode);
+            }
+            Ok(bytecode)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Brillig;
+    use brillig_vm::{BinaryIntOp, Opcode, RegisterIndex, Value};
+
+    fn brillig() -> Brillig {
+        let r = RegisterIndex::from;
+        let bytecode = (0..64)
+            .flat_map(|i| {
+                [
+                    Opcode::Const { destination: r(2), value: Value::from(i as u128) },
+                    Opcode::BinaryIntOp {
+                        destination: r(0),
+                        op: BinaryIntOp::Add,
+                        bit_size: 32,
+                        lhs: r(0),
+                        rhs: r(2),
+                    },
+                    Opcode::JumpIf { condition: r(1), location: 3 * i + 4 },
+                ]
+            })
+            .chain([Opcode::Stop])
+            .collect();
+        Brillig {
+            inputs: Vec::new(),
+            outputs: Vec::new(),
+            foreign_call_results: Vec::new(),
+            bytecode,
+            predicate: None,
+        }
+    }
+
+    #[test]
+    fn bytecode_is_serialized_compactly_in_binary_formats() {
+        let brillig = brillig();
+        let compact = rmp_serde::to_vec(&brillig).unwrap();
+        assert_eq!(rmp_serde::from_slice::<Brillig>(&compact).unwrap(), brillig);
+
+        let opcodes = rmp_serde::to_vec(&brillig.bytecode).unwrap();
+        assert!(
+            compact.len() * 4 < opcodes.len(),
+            "{} bytes against {}",
+            compact.len(),
+            opcodes.len()
+        );
+    }
+
+    #[test]
+    fn reads_bytecode_serialized_as_opcodes() {
+        let brillig = brillig();
+        let legacy = (
+            &brillig.inputs,
+            &brillig.outputs,
+            &brillig.foreign_call_results,
+            &brillig.bytecode,
+            &brillig.predicate,
+        );
+        let bytes = rmp_serde::to_vec(&legacy).unwrap();
+        assert_eq!(rmp_serde::from_slice::<Brillig>(&bytes).unwrap(), brillig);
+
+        let json = serde_json::to_value(&brillig).unwrap();
+        assert!(json["bytecode"].is_array());
+        assert_eq!(serde_json::from_str::<Brillig>(&json.to_string()).unwrap(), brillig);
+    }
+}
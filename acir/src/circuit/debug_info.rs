@@ -0,0 +1,116 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::OpcodeLocation;
+
+/// A span within a source file of the program from which a [`Circuit`][super::Circuit] was compiled.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct SourceLocation {
+    /// Identifier of the file containing this span, as listed in [`DebugInfo::files`].
+    pub file_id: u32,
+    /// Byte offset of the start of the span.
+    pub start: u32,
+    /// Byte offset of the end of the span.
+    pub end: u32,
+}
+
+/// Optional debug metadata attached to a [`Circuit`][super::Circuit].
+///
+/// Each [`OpcodeLocation`] may be mapped to the call stack of [`SourceLocation`]s which generated it,
+/// ordered from the outermost call to the innermost one.
+#[derive(Clone, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct DebugInfo {
+    /// Maps the file identifiers used by [`SourceLocation`]s to their paths.
+    pub files: BTreeMap<u32, String>,
+    /// Maps opcode locations to the call stacks of source locations which generated them.
+    pub locations: BTreeMap<OpcodeLocation, Vec<SourceLocation>>,
+}
+
+impl DebugInfo {
+    pub fn new(locations: BTreeMap<OpcodeLocation, Vec<SourceLocation>>) -> Self {
+        DebugInfo { files: BTreeMap::new(), locations }
+    }
+
+    /// Returns the call stack of source locations which generated the opcode at `location`.
+    ///
+    /// If no call stack was recorded for a Brillig location, the call stack of the
+    /// enclosing ACIR opcode is returned instead.
+    pub fn call_stack(&self, location: &OpcodeLocation) -> Option<&[SourceLocation]> {
+        if let Some(call_stack) = self.locations.get(location) {
+            return Some(call_stack);
+        }
+        match location {
+            OpcodeLocation::Acir(_) => None,
+            OpcodeLocation::Brillig { acir_index, .. } => {
+                self.locations.get(&OpcodeLocation::Acir(*acir_index)).map(Vec::as_slice)
+            }
+        }
+    }
+
+    /// Remaps the ACIR opcode indices of this debug information after a transformation of the circuit.
+    ///
+    /// `opcode_indices` holds, for each opcode of the transformed circuit, the index of the opcode
+    /// in the original circuit from which it was derived.
+    pub fn update_acir(&mut self, opcode_indices: &[usize]) {
+        let mut new_locations = BTreeMap::new();
+        for (new_index, old_index) in opcode_indices.iter().enumerate() {
+            let old_range = OpcodeLocation::Acir(*old_index)..=OpcodeLocation::Brillig {
+                acir_index: *old_index,
+                brillig_index: usize::MAX,
+            };
+            for (location, call_stack) in self.locations.range(old_range) {
+                let new_location = match *location {
+                    OpcodeLocation::Acir(_) => OpcodeLocation::Acir(new_index),
+                    OpcodeLocation::Brillig { brillig_index, .. } => {
+                        OpcodeLocation::Brillig { acir_index: new_index, brillig_index }
+                    }
+                };
+                new_locations.insert(new_location, call_stack.clone());
+            }
+        }
+        self.locations = new_locations;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::{DebugInfo, SourceLocation};
+    use crate::circuit::OpcodeLocation;
+
+    fn location(start: u32) -> SourceLocation {
+        SourceLocation { file_id: 0, start, end: start + 1 }
+    }
+
+    #[test]
+    fn brillig_locations_fall_back_to_acir_call_stack() {
+        let debug_info =
+            DebugInfo::new(BTreeMap::from([(OpcodeLocation::Acir(2), vec![location(10)])]));
+
+        let brillig_location = OpcodeLocation::Brillig { acir_index: 2, brillig_index: 5 };
+        assert_eq!(debug_info.call_stack(&brillig_location), Some([location(10)].as_slice()));
+        assert_eq!(debug_info.call_stack(&OpcodeLocation::Acir(1)), None);
+    }
+
+    #[test]
+    fn update_acir_remaps_opcode_indices() {
+        let mut debug_info = DebugInfo::new(BTreeMap::from([
+            (OpcodeLocation::Acir(0), vec![location(0)]),
+            (OpcodeLocation::Acir(1), vec![location(1)]),
+            (OpcodeLocation::Brillig { acir_index: 1, brillig_index: 3 }, vec![location(2)]),
+        ]));
+
+        // Opcode 0 was removed and opcode 1 was split into two opcodes.
+        debug_info.update_acir(&[1, 1]);
+
+        let expected = BTreeMap::from([
+            (OpcodeLocation::Acir(0), vec![location(1)]),
+            (OpcodeLocation::Brillig { acir_index: 0, brillig_index: 3 }, vec![location(2)]),
+            (OpcodeLocation::Acir(1), vec![location(1)]),
+            (OpcodeLocation::Brillig { acir_index: 1, brillig_index: 3 }, vec![location(2)]),
+        ]);
+        assert_eq!(debug_info.locations, expected);
+    }
+}
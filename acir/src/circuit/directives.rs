@@ -11,6 +11,8 @@ pub struct QuotientDirective {
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+// Expressions store their terms inline so variants holding them are expected to be large.
+#[allow(clippy::large_enum_variant)]
 /// Directives do not apply any constraints.
 /// You can think of them as opcodes that allow one to use non-determinism
 /// In the future, this can be replaced with asm non-determinism blocks
@@ -40,6 +42,27 @@ pub enum Directive {
         sort_by: Vec<u32>, // specify primary index to sort by, then the secondary,... For instance, if tuple is 2 and sort_by is [1,0], then a=[(a0,b0),..] is sorted by bi and then ai.
     },
     Log(LogInfo),
+
+    //Performs euclidian division of a / b for big integers given by their little endian limbs of limb_bits bits,
+    //and stores the limbs of the quotient in q and the limbs of the rest in r.
+    //The limbs of a may exceed limb_bits bits and are read as signed integers, so that a can hold the unreduced result of limb-wise arithmetic.
+    BigQuotient {
+        a: Vec<Expression>,
+        b: Vec<Expression>,
+        q: Vec<Witness>,
+        r: Vec<Witness>,
+        limb_bits: u32,
+    },
+
+    //Inverts the big integer a modulo the big integer modulus, both given by their little endian limbs of limb_bits bits,
+    //and stores the limbs of the inverse in result, which is zero if a is not invertible.
+    //The limbs of a are read as signed integers as for BigQuotient.
+    BigInvert {
+        a: Vec<Expression>,
+        modulus: Vec<Expression>,
+        result: Vec<Witness>,
+        limb_bits: u32,
+    },
 }
 
 impl Directive {
@@ -50,6 +73,21 @@ impl Directive {
             Directive::ToLeRadix { .. } => "to_le_radix",
             Directive::PermutationSort { .. } => "permutation_sort",
             Directive::Log { .. } => "log",
+            Directive::BigQuotient { .. } => "big_quotient",
+            Directive::BigInvert { .. } => "big_invert",
+        }
+    }
+
+    /// Returns the witnesses assigned by this directive.
+    pub fn outputs(&self) -> Vec<Witness> {
+        match self {
+            Directive::Invert { result, .. } => vec![*result],
+            Directive::Quotient(QuotientDirective { q, r, .. }) => vec![*q, *r],
+            Directive::ToLeRadix { b, .. } => b.clone(),
+            Directive::PermutationSort { bits, .. } => bits.clone(),
+            Directive::Log(_) => Vec::new(),
+            Directive::BigQuotient { q, r, .. } => q.iter().chain(r).copied().collect(),
+            Directive::BigInvert { result, .. } => result.clone(),
         }
     }
 }
@@ -1,17 +1,40 @@
 pub mod black_box_functions;
 pub mod brillig;
+pub mod debug_info;
 pub mod directives;
 pub mod opcodes;
+pub mod phases;
+mod serialization;
+pub mod visitor;
 
-use crate::native_types::Witness;
+use crate::native_types::{Expression, Witness};
+use acir_field::FieldElement;
 pub use opcodes::Opcode;
 
+use debug_info::DebugInfo;
 use flate2::{read::DeflateDecoder, write::DeflateEncoder, Compression};
-use serde::{Deserialize, Serialize};
-use std::collections::BTreeSet;
+use phases::Phase;
+use serde::{de::Error as _, Deserialize, Deserializer, Serialize, Serializer};
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet};
 use std::io::prelude::*;
+use std::str::FromStr;
+use thiserror::Error;
 
-#[derive(Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
+/// The version of the format in which circuits are serialized.
+///
+/// Version 1 renamed `Arithmetic` opcodes to [`Opcode::AssertZero`] and replaced the `Block`, `ROM` and `RAM`
+/// opcodes with [`Opcode::MemoryInit`] and [`Opcode::MemoryOp`]. Circuits serialized in version 0 are migrated
+/// when deserialized, although support for them is deprecated.
+///
+/// Version 2 serializes the bytecode of [`Opcode::Brillig`] compactly in binary formats. Bytecode serialized
+/// as a list of opcodes by earlier versions is still read.
+///
+/// Version 3 adds the [phases][Circuit::phases] of the circuit, which are empty when reading earlier versions.
+pub const CIRCUIT_FORMAT_VERSION: u32 = 3;
+
+// Serialized along with the format version, see the `serialization` module.
+#[derive(Clone, PartialEq, Eq, Default)]
 pub struct Circuit {
     // current_witness_index is the highest witness index in the circuit. The next witness to be added to this circuit
     // will take on this value. (The value is cached here as an optimization.)
@@ -25,6 +48,11 @@ pub struct Circuit {
     pub public_parameters: PublicInputs,
     /// The set of public inputs calculated within the circuit.
     pub return_values: PublicInputs,
+    /// Optional mapping from opcodes to the source locations which generated them.
+    pub debug_info: Option<DebugInfo>,
+    /// Groups of opcodes which may be solved apart from the others, in the order they are meant to be solved.
+    /// Opcodes belonging to no phase are solved along with any phase.
+    pub phases: Vec<Phase>,
 }
 
 impl Circuit {
@@ -32,6 +60,11 @@ impl Circuit {
         self.current_witness_index + 1
     }
 
+    /// Returns the phase named `name`, if any.
+    pub fn phase(&self, name: &str) -> Option<&Phase> {
+        self.phases.iter().find(|phase| phase.name == name)
+    }
+
     /// Returns all public inputs. This includes those provided as parameters to the circuit and those
     /// computed as return values.
     pub fn public_inputs(&self) -> PublicInputs {
@@ -40,6 +73,39 @@ impl Circuit {
         PublicInputs(public_inputs)
     }
 
+    /// Returns the witnesses which the arithmetic opcodes force to a constant whatever the inputs, along with their value.
+    ///
+    /// A witness is constant when an [`Opcode::AssertZero`] constrains it alone once the values of the constants
+    /// already found are substituted, such as `2 * x - 6 = 0` or, once `x` is known to be 3, `x * y - 12 = 0`.
+    /// Other opcodes are not analyzed, and the circuit is not checked to be satisfiable.
+    pub fn constant_witnesses(&self) -> BTreeMap<Witness, FieldElement> {
+        let mut constants = BTreeMap::new();
+        let mut unsolved: Vec<&Expression> = self
+            .opcodes
+            .iter()
+            .filter_map(|opcode| match opcode {
+                Opcode::AssertZero(expr) => Some(expr),
+                _ => None,
+            })
+            .collect();
+
+        // Finding a constant may reveal another one, so opcodes are revisited until no new constant is found.
+        let mut found_constant = true;
+        while found_constant {
+            found_constant = false;
+            unsolved.retain(|expr| match constant_of(expr, &constants) {
+                ConstantOf::Witness(witness, value) => {
+                    constants.insert(witness, value);
+                    found_constant = true;
+                    false
+                }
+                ConstantOf::None => false,
+                ConstantOf::Unknown => true,
+            });
+        }
+        constants
+    }
+
     pub fn write<W: std::io::Write>(&self, writer: W) -> std::io::Result<()> {
         let buf = rmp_serde::to_vec(&self).unwrap();
         let mut deflater = DeflateEncoder::new(writer, Compression::best());
@@ -47,12 +113,58 @@ impl Circuit {
 
         Ok(())
     }
+    /// Reads a circuit written by [`Circuit::write`] in any supported [format version][CIRCUIT_FORMAT_VERSION].
     pub fn read<R: std::io::Read>(reader: R) -> std::io::Result<Self> {
         let mut deflater = DeflateDecoder::new(reader);
         let mut buf_d = Vec::new();
-        deflater.read_to_end(&mut buf_d).unwrap();
-        let circuit = rmp_serde::from_slice(buf_d.as_slice()).unwrap();
-        Ok(circuit)
+        deflater.read_to_end(&mut buf_d)?;
+        rmp_serde::from_slice(buf_d.as_slice())
+            .map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidData, err))
+    }
+}
+
+/// What an arithmetic opcode constrains once the values of the known constants are substituted.
+enum ConstantOf {
+    /// A single witness, to the given value.
+    Witness(Witness, FieldElement),
+    /// No witness, all of them being constants.
+    None,
+    /// Several witnesses, which may still be constants once more of them are known.
+    Unknown,
+}
+
+fn constant_of(expr: &Expression, constants: &BTreeMap<Witness, FieldElement>) -> ConstantOf {
+    let mut q_c = expr.q_c;
+    let mut linear_combinations: BTreeMap<Witness, FieldElement> = BTreeMap::new();
+    for &(coefficient, lhs, rhs) in &expr.mul_terms {
+        match (constants.get(&lhs), constants.get(&rhs)) {
+            (Some(lhs), Some(rhs)) => q_c += coefficient * *lhs * *rhs,
+            (Some(value), None) => {
+                *linear_combinations.entry(rhs).or_insert_with(FieldElement::zero) +=
+                    coefficient * *value
+            }
+            (None, Some(value)) => {
+                *linear_combinations.entry(lhs).or_insert_with(FieldElement::zero) +=
+                    coefficient * *value
+            }
+            (None, None) => return ConstantOf::Unknown,
+        }
+    }
+    for &(coefficient, witness) in &expr.linear_combinations {
+        match constants.get(&witness) {
+            Some(value) => q_c += coefficient * *value,
+            None => {
+                *linear_combinations.entry(witness).or_insert_with(FieldElement::zero) +=
+                    coefficient
+            }
+        }
+    }
+
+    linear_combinations.retain(|_, coefficient| !coefficient.is_zero());
+    match linear_combinations.into_iter().collect::<Vec<_>>()[..] {
+        [] => ConstantOf::None,
+        [(witness, coefficient)] => ConstantOf::Witness(witness, -q_c / coefficient),
+        _ => ConstantOf::Unknown,
     }
 }
 
@@ -93,6 +205,93 @@ impl std::fmt::Debug for Circuit {
     }
 }
 
+/// The location of an opcode within a [`Circuit`].
+///
+/// Brillig opcodes are located by the index of the enclosing [`Opcode::Brillig`]
+/// along with the index of the opcode within its bytecode.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum OpcodeLocation {
+    Acir(usize),
+    Brillig { acir_index: usize, brillig_index: usize },
+}
+
+impl OpcodeLocation {
+    /// Returns the index of the ACIR opcode at this location.
+    pub fn acir_index(&self) -> usize {
+        match self {
+            OpcodeLocation::Acir(index) => *index,
+            OpcodeLocation::Brillig { acir_index, .. } => *acir_index,
+        }
+    }
+
+    fn brillig_index(&self) -> Option<usize> {
+        match self {
+            OpcodeLocation::Acir(_) => None,
+            OpcodeLocation::Brillig { brillig_index, .. } => Some(*brillig_index),
+        }
+    }
+}
+
+// Locations are ordered by ACIR index first so that all locations within an ACIR opcode are contiguous.
+impl Ord for OpcodeLocation {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.acir_index(), self.brillig_index()).cmp(&(other.acir_index(), other.brillig_index()))
+    }
+}
+
+impl PartialOrd for OpcodeLocation {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl std::fmt::Display for OpcodeLocation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OpcodeLocation::Acir(index) => write!(f, "{index}"),
+            OpcodeLocation::Brillig { acir_index, brillig_index } => {
+                write!(f, "{acir_index}.{brillig_index}")
+            }
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+#[error("invalid opcode location string: {0}")]
+pub struct OpcodeLocationFromStrError(String);
+
+/// The implementation of display and FromStr allows serializing and deserializing a OpcodeLocation to a string.
+/// This is useful when used as key in a map that has to be serialized to JSON/TOML.
+impl FromStr for OpcodeLocation {
+    type Err = OpcodeLocationFromStrError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parse_index = |index: &str| {
+            index.parse::<usize>().map_err(|_| OpcodeLocationFromStrError(s.to_owned()))
+        };
+        match s.split_once('.') {
+            None => Ok(OpcodeLocation::Acir(parse_index(s)?)),
+            Some((acir_index, brillig_index)) => Ok(OpcodeLocation::Brillig {
+                acir_index: parse_index(acir_index)?,
+                brillig_index: parse_index(brillig_index)?,
+            }),
+        }
+    }
+}
+
+impl Serialize for OpcodeLocation {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.to_string().serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for OpcodeLocation {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        OpcodeLocation::from_str(&s).map_err(D::Error::custom)
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Default)]
 pub struct PublicInputs(pub BTreeSet<Witness>);
 
@@ -109,13 +308,16 @@ impl PublicInputs {
 
 #[cfg(test)]
 mod tests {
-    use std::collections::BTreeSet;
+    use smallvec::smallvec;
+    use std::collections::{BTreeMap, BTreeSet};
 
     use super::{
+        debug_info::{DebugInfo, SourceLocation},
         opcodes::{BlackBoxFuncCall, FunctionInput},
-        Circuit, Opcode, PublicInputs,
+        phases::Phase,
+        Circuit, Opcode, OpcodeLocation, PublicInputs,
     };
-    use crate::native_types::Witness;
+    use crate::native_types::{Expression, Witness};
     use acir_field::FieldElement;
 
     fn directive_opcode() -> Opcode {
@@ -144,6 +346,8 @@ mod tests {
             opcodes: vec![and_opcode(), range_opcode(), directive_opcode()],
             public_parameters: PublicInputs(BTreeSet::from_iter(vec![Witness(2), Witness(12)])),
             return_values: PublicInputs(BTreeSet::from_iter(vec![Witness(4), Witness(12)])),
+            debug_info: None,
+            phases: vec![Phase::new("setup", [0]), Phase::new("main", [1, 2])],
         };
 
         fn read_write(circuit: Circuit) -> (Circuit, Circuit) {
@@ -162,9 +366,9 @@ mod tests {
         let circuit = Circuit {
             current_witness_index: 0,
             opcodes: vec![
-                Opcode::Arithmetic(crate::native_types::Expression {
-                    mul_terms: vec![],
-                    linear_combinations: vec![],
+                Opcode::AssertZero(Expression {
+                    mul_terms: smallvec![],
+                    linear_combinations: smallvec![],
                     q_c: FieldElement::from(8u128),
                 }),
                 range_opcode(),
@@ -172,6 +376,8 @@ mod tests {
             ],
             public_parameters: PublicInputs(BTreeSet::from_iter(vec![Witness(2)])),
             return_values: PublicInputs(BTreeSet::from_iter(vec![Witness(2)])),
+            debug_info: None,
+            phases: vec![Phase::new("main", [0, 1, 2])],
         };
 
         let json = serde_json::to_string_pretty(&circuit).unwrap();
@@ -179,4 +385,94 @@ mod tests {
         let deserialized = serde_json::from_str(&json).unwrap();
         assert_eq!(circuit, deserialized);
     }
+
+    #[test]
+    fn finds_constant_witnesses() {
+        let (x, y, z, w) = (Witness(1), Witness(2), Witness(3), Witness(4));
+        let circuit = Circuit {
+            current_witness_index: 4,
+            opcodes: vec![
+                // x * y - 12 = 0, which only constrains y once x is known to be 3.
+                Opcode::AssertZero(Expression {
+                    mul_terms: smallvec![(FieldElement::one(), x, y)],
+                    linear_combinations: smallvec![],
+                    q_c: -FieldElement::from(12_u128),
+                }),
+                // 2 * x - 6 = 0
+                Opcode::AssertZero(Expression {
+                    mul_terms: smallvec![],
+                    linear_combinations: smallvec![(FieldElement::from(2_u128), x)],
+                    q_c: -FieldElement::from(6_u128),
+                }),
+                // z - w = 0, which has no constant.
+                Opcode::AssertZero(Expression {
+                    mul_terms: smallvec![],
+                    linear_combinations: smallvec![
+                        (FieldElement::one(), z),
+                        (-FieldElement::one(), w)
+                    ],
+                    q_c: FieldElement::zero(),
+                }),
+                range_opcode(),
+            ],
+            ..Circuit::default()
+        };
+
+        assert_eq!(
+            circuit.constant_witnesses(),
+            BTreeMap::from([(x, FieldElement::from(3_u128)), (y, FieldElement::from(4_u128))])
+        );
+    }
+
+    #[test]
+    fn debug_info_serialization_roundtrip() {
+        let call_stack = vec![SourceLocation { file_id: 0, start: 12, end: 20 }];
+        let mut debug_info = DebugInfo::new(BTreeMap::from([
+            (OpcodeLocation::Acir(0), call_stack.clone()),
+            (OpcodeLocation::Brillig { acir_index: 1, brillig_index: 4 }, call_stack),
+        ]));
+        debug_info.files.insert(0, "src/main.nr".to_owned());
+
+        let circuit = Circuit {
+            current_witness_index: 3,
+            opcodes: vec![range_opcode(), and_opcode()],
+            public_parameters: PublicInputs::default(),
+            return_values: PublicInputs::default(),
+            debug_info: Some(debug_info),
+            phases: Vec::new(),
+        };
+
+        let mut bytes = Vec::new();
+        circuit.write(&mut bytes).unwrap();
+        assert_eq!(Circuit::read(&*bytes).unwrap(), circuit);
+
+        let json = serde_json::to_string(&circuit).unwrap();
+        assert_eq!(serde_json::from_str::<Circuit>(&json).unwrap(), circuit);
+    }
+
+    #[test]
+    fn read_rejects_malformed_circuits() {
+        use std::io::Write;
+
+        // A deflate block of the reserved type.
+        assert!(Circuit::read(&[0xff_u8; 8][..]).is_err());
+
+        let mut bytes = Vec::new();
+        let mut encoder =
+            flate2::write::DeflateEncoder::new(&mut bytes, flate2::Compression::default());
+        encoder.write_all(b"not a circuit").unwrap();
+        encoder.finish().unwrap();
+        let error = Circuit::read(&*bytes).unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn opcode_location_string_roundtrip() {
+        for location in
+            [OpcodeLocation::Acir(7), OpcodeLocation::Brillig { acir_index: 3, brillig_index: 9 }]
+        {
+            assert_eq!(location.to_string().parse::<OpcodeLocation>().unwrap(), location);
+        }
+        assert!("3.x".parse::<OpcodeLocation>().is_err());
+    }
 }
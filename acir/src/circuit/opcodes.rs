@@ -2,7 +2,7 @@ use super::{
     brillig::Brillig,
     directives::{Directive, LogInfo, QuotientDirective},
 };
-use crate::native_types::Expression;
+use crate::native_types::{Expression, Witness};
 use serde::{Deserialize, Serialize};
 
 mod black_box_function_call;
@@ -12,29 +12,34 @@ pub use black_box_function_call::{BlackBoxFuncCall, FunctionInput};
 pub use block::{BlockId, MemOp, MemoryBlock};
 
 #[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
+// Expressions store their terms inline so variants holding them are expected to be large.
+#[allow(clippy::large_enum_variant)]
 pub enum Opcode {
-    Arithmetic(Expression),
+    /// Constrains an expression to be equal to zero.
+    ///
+    /// Named `Arithmetic` in circuits serialized before [format version 1][super::CIRCUIT_FORMAT_VERSION].
+    AssertZero(Expression),
     /// Calls to "gadgets" which rely on backends implementing support for specialized constraints.
     ///
     /// Often used for exposing more efficient implementations of SNARK-unfriendly computations.  
     BlackBoxFuncCall(BlackBoxFuncCall),
     Directive(Directive),
-    /// Abstract read/write operations on a block of data. In particular;
-    /// - It does not require an initialization phase
-    /// - Operations do not need to be constant, they can be any expression which resolves to 0 or 1.
-    Block(MemoryBlock),
-    /// Same as Block, but it starts with an initialization phase and then have only read operation
-    /// - init: write operations with index from 0..MemoryBlock.len
-    /// - after MemoryBlock.len; all operations are read
+    /// Initializes the memory block `block_id` with the values of `init`, at indices `0..init.len()`.
+    ///
+    /// Every block is initialized once, before any [memory operation][Opcode::MemoryOp] is performed on it.
+    MemoryInit {
+        block_id: BlockId,
+        init: Vec<Witness>,
+    },
+    /// Performs a read or write operation on the memory block `block_id`.
     ///
-    /// ROM can be more efficiently handled because we do not need to check for the operation value (which is always 0).
-    ROM(MemoryBlock),
-    /// Same as ROM, but can have read or write operations
-    /// - init = write operations with index 0..MemoryBlock.len
-    /// - after MemoryBlock.len, all operations are constant expressions (0 or 1)
-    // TODO(#319): Review this comment and generalize it to be useful for other backends.
-    // RAM is required for acvm-backend-barretenberg as dynamic memory implementation in Barretenberg requires an initialization phase and can only handle constant values for operations.
-    RAM(MemoryBlock),
+    /// The operations on a block take effect in the order in which they appear in the circuit. Their `operation`
+    /// can be any expression which resolves to 0 (read) or 1 (write), although backends usually handle
+    /// blocks which are only read or whose operations are constant more efficiently.
+    MemoryOp {
+        block_id: BlockId,
+        op: MemOp,
+    },
     Brillig(Brillig),
 }
 
@@ -43,23 +48,44 @@ impl Opcode {
     // TODO concat!("directive:", directive.name)
     pub fn name(&self) -> &str {
         match self {
-            Opcode::Arithmetic(_) => "arithmetic",
+            Opcode::AssertZero(_) => "assert_zero",
             Opcode::Directive(directive) => directive.name(),
             Opcode::BlackBoxFuncCall(g) => g.name(),
-            Opcode::Block(_) => "block",
-            Opcode::RAM(_) => "ram",
-            Opcode::ROM(_) => "rom",
+            Opcode::MemoryInit { .. } => "memory_init",
+            Opcode::MemoryOp { .. } => "memory_op",
             Opcode::Brillig(_) => "brillig",
         }
     }
 
+    pub fn is_assert_zero(&self) -> bool {
+        matches!(self, Opcode::AssertZero(_))
+    }
+
+    pub fn assert_zero(self) -> Option<Expression> {
+        match self {
+            Opcode::AssertZero(expr) => Some(expr),
+            _ => None,
+        }
+    }
+
+    #[deprecated(
+        note = "arithmetic opcodes have been renamed, use `Opcode::is_assert_zero` instead"
+    )]
     pub fn is_arithmetic(&self) -> bool {
-        matches!(self, Opcode::Arithmetic(_))
+        self.is_assert_zero()
     }
 
+    #[deprecated(note = "arithmetic opcodes have been renamed, use `Opcode::assert_zero` instead")]
     pub fn arithmetic(self) -> Option<Expression> {
+        self.assert_zero()
+    }
+
+    /// Returns the id of the memory block accessed by this opcode, if any.
+    pub fn memory_block_id(&self) -> Option<BlockId> {
         match self {
-            Opcode::Arithmetic(expr) => Some(expr),
+            Opcode::MemoryInit { block_id, .. } | Opcode::MemoryOp { block_id, .. } => {
+                Some(*block_id)
+            }
             _ => None,
         }
     }
@@ -68,7 +94,7 @@ impl Opcode {
 impl std::fmt::Display for Opcode {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            Opcode::Arithmetic(expr) => {
+            Opcode::AssertZero(expr) => {
                 write!(f, "EXPR [ ")?;
                 for i in &expr.mul_terms {
                     write!(f, "({}, _{}, _{}) ", i.0, i.1.witness_index(), i.2.witness_index())?;
@@ -134,17 +160,48 @@ impl std::fmt::Display for Opcode {
                     witnesses.last().unwrap().witness_index()
                 ),
             },
-            Opcode::Block(block) => {
-                write!(f, "BLOCK ")?;
-                write!(f, "(id: {}, len: {}) ", block.id.0, block.trace.len())
+            Opcode::Directive(Directive::BigQuotient { a, b, q, r, limb_bits }) => {
+                write!(f, "DIR::BIGQUOTIENT ")?;
+                write!(
+                    f,
+                    "(limbs: {} / {} of {} bits, q: [_{}..._{}], r: [_{}..._{}])",
+                    a.len(),
+                    b.len(),
+                    limb_bits,
+                    q.first().unwrap().witness_index(),
+                    q.last().unwrap().witness_index(),
+                    r.first().unwrap().witness_index(),
+                    r.last().unwrap().witness_index(),
+                )
+            }
+            Opcode::Directive(Directive::BigInvert { a, modulus, result, limb_bits }) => {
+                write!(f, "DIR::BIGINVERT ")?;
+                write!(
+                    f,
+                    "(limbs: {} mod {} of {} bits, out: [_{}..._{}])",
+                    a.len(),
+                    modulus.len(),
+                    limb_bits,
+                    result.first().unwrap().witness_index(),
+                    result.last().unwrap().witness_index(),
+                )
             }
-            Opcode::ROM(block) => {
-                write!(f, "ROM ")?;
-                write!(f, "(id: {}, len: {}) ", block.id.0, block.trace.len())
+            Opcode::MemoryInit { block_id, init } => {
+                write!(f, "INIT ")?;
+                write!(f, "(id: {}, len: {}) ", block_id.0, init.len())
             }
-            Opcode::RAM(block) => {
-                write!(f, "RAM ")?;
-                write!(f, "(id: {}, len: {}) ", block.id.0, block.trace.len())
+            Opcode::MemoryOp { block_id, op } => {
+                write!(f, "MEM ")?;
+                let operation = match op.operation.to_const() {
+                    Some(operation) if operation.is_zero() => "read".to_owned(),
+                    Some(operation) if operation.is_one() => "write".to_owned(),
+                    _ => format!("op: {}", op.operation),
+                };
+                write!(
+                    f,
+                    "(id: {}, {operation} at: {}, value: {}) ",
+                    block_id.0, op.index, op.value
+                )
             }
             Opcode::Brillig(brillig) => {
                 write!(f, "BRILLIG: ")?;
@@ -4,7 +4,7 @@ use serde::{Deserialize, Serialize};
 
 // Note: Some functions will not use all of the witness
 // So we need to supply how many bits of the witness is needed
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct FunctionInput {
     pub witness: Witness,
     pub num_bits: u32,
@@ -16,7 +16,7 @@ impl FunctionInput {
     }
 }
 
-#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum BlackBoxFuncCall {
     AND {
         lhs: FunctionInput,
@@ -35,10 +35,19 @@ pub enum BlackBoxFuncCall {
         inputs: Vec<FunctionInput>,
         outputs: Vec<Witness>,
     },
+    /// Computes the SHA512 digest of `inputs`, writing its 64 bytes to `outputs`.
+    Sha512 {
+        inputs: Vec<FunctionInput>,
+        outputs: Vec<Witness>,
+    },
     Blake2s {
         inputs: Vec<FunctionInput>,
         outputs: Vec<Witness>,
     },
+    Blake3 {
+        inputs: Vec<FunctionInput>,
+        outputs: Vec<Witness>,
+    },
     SchnorrVerify {
         public_key_x: FunctionInput,
         public_key_y: FunctionInput,
@@ -54,6 +63,8 @@ pub enum BlackBoxFuncCall {
     },
     // 128 here specifies that this function
     // should have 128 bits of security
+    /// Deprecated in favour of [`HashToField`][BlackBoxFuncCall::HashToField], which follows RFC 9380.
+    #[deprecated(note = "use HashToField, which follows RFC 9380, instead")]
     HashToField128Security {
         inputs: Vec<FunctionInput>,
         output: Witness,
@@ -65,6 +76,8 @@ pub enum BlackBoxFuncCall {
         hashed_message: Vec<FunctionInput>,
         output: Witness,
     },
+    /// Deprecated in favour of [`MultiScalarMul`][BlackBoxFuncCall::MultiScalarMul], which supports arbitrary bases.
+    #[deprecated(note = "use MultiScalarMul with the generator of the embedded curve instead")]
     FixedBaseScalarMul {
         input: FunctionInput,
         outputs: (Witness, Witness),
@@ -79,6 +92,8 @@ pub enum BlackBoxFuncCall {
         /// from the input. Note: if `var_message_size`
         /// is more than the number of bytes in the input,
         /// then an error is returned.
+        ///
+        /// Each input contributes the `ceil(num_bits / 8)` bytes of its value in little-endian order.
         var_message_size: FunctionInput,
         outputs: Vec<Witness>,
     },
@@ -105,6 +120,219 @@ pub enum BlackBoxFuncCall {
         /// will be the input aggregation object of the next recursive aggregation.
         output_aggregation_object: Vec<Witness>,
     },
+    /// Applies the Poseidon2 permutation to the state `inputs`, writing the permuted state to `outputs`.
+    Poseidon2Permutation {
+        inputs: Vec<FunctionInput>,
+        outputs: Vec<Witness>,
+        /// The width of the permutation's state, which must match the number of inputs and outputs.
+        len: u32,
+    },
+    /// Verifies an Ed25519 signature over `message`, writing 1 to `output` if it is valid and 0 otherwise.
+    EddsaEd25519 {
+        /// The 32 byte compressed encoding of the public key.
+        public_key: Vec<FunctionInput>,
+        /// The 64 byte signature, made of the encoding of `R` followed by `s`.
+        signature: Vec<FunctionInput>,
+        message: Vec<FunctionInput>,
+        output: Witness,
+    },
+    /// Verifies an EdDSA-Poseidon signature over the Baby Jubjub curve, as specified by circomlib,
+    /// writing 1 to `output` if it is valid and 0 otherwise.
+    EddsaBabyJubjub {
+        public_key_x: FunctionInput,
+        public_key_y: FunctionInput,
+        signature_s: FunctionInput,
+        signature_r8_x: FunctionInput,
+        signature_r8_y: FunctionInput,
+        message: FunctionInput,
+        output: Witness,
+    },
+    /// Adds the embedded curve points `(input1_x, input1_y)` and `(input2_x, input2_y)`,
+    /// writing the coordinates of the sum to `outputs`.
+    ///
+    /// The point at infinity is represented by the coordinates `(0, 0)`.
+    EmbeddedCurveAdd {
+        input1_x: FunctionInput,
+        input1_y: FunctionInput,
+        input2_x: FunctionInput,
+        input2_y: FunctionInput,
+        outputs: (Witness, Witness),
+    },
+    /// Doubles the embedded curve point `(input_x, input_y)`, writing the coordinates of the result to `outputs`.
+    EmbeddedCurveDouble {
+        input_x: FunctionInput,
+        input_y: FunctionInput,
+        outputs: (Witness, Witness),
+    },
+    /// Computes the sum of the embedded curve points `points` multiplied by their respective `scalars`,
+    /// writing the coordinates of the result to `outputs`.
+    ///
+    /// Points are given as consecutive `x` and `y` coordinates, so `points` must be twice as long as `scalars`.
+    /// The point at infinity is represented by the coordinates `(0, 0)`.
+    MultiScalarMul {
+        points: Vec<FunctionInput>,
+        scalars: Vec<FunctionInput>,
+        outputs: (Witness, Witness),
+    },
+    /// Adds the big integers identified by `lhs` and `rhs`, storing the result under the identifier `output`.
+    ///
+    /// Big integers live outside of the witness map and are referenced by identifiers, which are assigned
+    /// by [`BigIntFromLeBytes`][BlackBoxFuncCall::BigIntFromLeBytes] and the arithmetic opcodes.
+    /// Both operands must share the same modulus, which is inherited by the result.
+    BigIntAdd {
+        lhs: u32,
+        rhs: u32,
+        output: u32,
+    },
+    /// Multiplies the big integers identified by `lhs` and `rhs`, storing the result under the identifier `output`.
+    BigIntMul {
+        lhs: u32,
+        rhs: u32,
+        output: u32,
+    },
+    /// Divides the big integer identified by `lhs` by the one identified by `rhs`,
+    /// storing the result under the identifier `output`.
+    ///
+    /// `rhs` must be invertible modulo the operands' modulus.
+    BigIntDiv {
+        lhs: u32,
+        rhs: u32,
+        output: u32,
+    },
+    /// Interprets the bytes `inputs` as a little-endian integer, reduced modulo the little-endian `modulus`,
+    /// and stores it under the identifier `output`.
+    BigIntFromLeBytes {
+        inputs: Vec<FunctionInput>,
+        modulus: Vec<u8>,
+        output: u32,
+    },
+    /// Writes the little-endian bytes of the big integer identified by `input` to `outputs`.
+    BigIntToLeBytes {
+        input: u32,
+        outputs: Vec<Witness>,
+    },
+    /// Applies the keccak-f[1600] permutation to the state `inputs`, writing the permuted state to `outputs`.
+    ///
+    /// The state is made of 25 lanes of 64 bits, indexed by `x + 5 * y`.
+    Keccakf1600 {
+        inputs: Vec<FunctionInput>,
+        outputs: Vec<Witness>,
+    },
+    /// Encrypts the bytes `inputs` using AES-128 in CBC mode, writing the ciphertext to `outputs`.
+    ///
+    /// The plaintext is padded following PKCS#7, so `outputs` holds the next multiple of 16 bytes above `inputs.len()`.
+    Aes128Encrypt {
+        inputs: Vec<FunctionInput>,
+        /// The 16 byte initialization vector.
+        iv: Vec<FunctionInput>,
+        /// The 16 byte key.
+        key: Vec<FunctionInput>,
+        outputs: Vec<Witness>,
+    },
+    /// Applies the SHA256 compression function to the intermediate hash `hash_values` and the message block `inputs`,
+    /// writing the updated hash to `outputs`.
+    ///
+    /// The message block is made of 16 words of 32 bits and the hash of 8 words of 32 bits, all in big-endian order.
+    /// This allows a digest to be computed one block at a time, reusing the hash of any common prefix.
+    Sha256Compression {
+        inputs: Vec<FunctionInput>,
+        hash_values: Vec<FunctionInput>,
+        outputs: Vec<Witness>,
+    },
+    /// Computes the SHA256 digest of the first `var_message_size` bytes of `inputs`.
+    ///
+    /// As with [`Keccak256VariableLength`][BlackBoxFuncCall::Keccak256VariableLength], the message size
+    /// counts the bytes of `inputs` rather than the inputs themselves.
+    Sha256VariableLength {
+        inputs: Vec<FunctionInput>,
+        var_message_size: FunctionInput,
+        outputs: Vec<Witness>,
+    },
+    /// Computes the SHA512 digest of the first `var_message_size` bytes of `inputs`.
+    Sha512VariableLength {
+        inputs: Vec<FunctionInput>,
+        var_message_size: FunctionInput,
+        outputs: Vec<Witness>,
+    },
+    /// Computes the Blake2s digest of the first `var_message_size` bytes of `inputs`.
+    Blake2sVariableLength {
+        inputs: Vec<FunctionInput>,
+        var_message_size: FunctionInput,
+        outputs: Vec<Witness>,
+    },
+    /// Computes the Blake3 digest of the first `var_message_size` bytes of `inputs`.
+    Blake3VariableLength {
+        inputs: Vec<FunctionInput>,
+        var_message_size: FunctionInput,
+        outputs: Vec<Witness>,
+    },
+    /// Hashes the bytes of `inputs` to a field element following the `hash_to_field` procedure of RFC 9380,
+    /// using `expand_message_xmd` with SHA256 and `domain_separator` as the domain separation tag.
+    HashToField {
+        inputs: Vec<FunctionInput>,
+        domain_separator: Vec<u8>,
+        output: Witness,
+    },
+    /// Hashes the bytes of `inputs` to a point on the secp256k1 curve using the `secp256k1_XMD:SHA-256_SSWU_RO_`
+    /// suite of RFC 9380, with `domain_separator` as the domain separation tag.
+    ///
+    /// `outputs` holds the 32 byte big-endian x coordinate of the point followed by its y coordinate.
+    HashToCurveSecp256k1 {
+        inputs: Vec<FunctionInput>,
+        domain_separator: Vec<u8>,
+        outputs: Vec<Witness>,
+    },
+    /// Hashes the bytes of `inputs` to a point on the embedded curve using `expand_message_xmd` with SHA256
+    /// and the Shallue-van de Woestijne method of RFC 9380, with `domain_separator` as the domain separation tag.
+    HashToEmbeddedCurve {
+        inputs: Vec<FunctionInput>,
+        domain_separator: Vec<u8>,
+        outputs: (Witness, Witness),
+    },
+    /// Recovers the public key which produced `signature` over `hashed_message`, writing the 32 byte big-endian
+    /// encodings of its coordinates to `public_key_x` and `public_key_y`.
+    ///
+    /// As with the `ecrecover` precompile of Ethereum, both coordinates are zero if no key can be recovered
+    /// and signatures with a high `s` value are accepted.
+    /// The Ethereum address of the key is given by the last 20 bytes of the keccak256 hash of its coordinates.
+    EcdsaSecp256k1Recover {
+        /// The 64 byte concatenation of the `r` and `s` values of the signature.
+        signature: Vec<FunctionInput>,
+        /// The parity of the y coordinate of the point whose x coordinate is `r`, either 0 or 1.
+        recovery_id: FunctionInput,
+        hashed_message: Vec<FunctionInput>,
+        public_key_x: Vec<Witness>,
+        public_key_y: Vec<Witness>,
+    },
+    /// Constrains each of `inputs` to fit within its number of bits, as a [`RANGE`][BlackBoxFuncCall::RANGE] opcode would.
+    ///
+    /// Grouping range constraints into a single opcode keeps circuits compact and lets backends
+    /// check them against shared lookup tables.
+    RangeBatch {
+        inputs: Vec<FunctionInput>,
+    },
+    /// Flips each of the `num_bits` low bits of `input`.
+    NOT {
+        input: FunctionInput,
+        output: Witness,
+    },
+    /// Shifts `input` left by `shift` bits, discarding the bits shifted past its `num_bits`.
+    SHL {
+        input: FunctionInput,
+        shift: u32,
+        output: Witness,
+    },
+    /// Shifts `input` right by `shift` bits.
+    SHR {
+        input: FunctionInput,
+        shift: u32,
+        output: Witness,
+    },
+    /// Computes the Blake2b digest of `inputs`, writing its 64 bytes to `outputs`.
+    Blake2b {
+        inputs: Vec<FunctionInput>,
+        outputs: Vec<Witness>,
+    },
 }
 
 impl BlackBoxFuncCall {
@@ -122,7 +350,9 @@ impl BlackBoxFuncCall {
             },
             BlackBoxFunc::RANGE => BlackBoxFuncCall::RANGE { input: FunctionInput::dummy() },
             BlackBoxFunc::SHA256 => BlackBoxFuncCall::SHA256 { inputs: vec![], outputs: vec![] },
+            BlackBoxFunc::Sha512 => BlackBoxFuncCall::Sha512 { inputs: vec![], outputs: vec![] },
             BlackBoxFunc::Blake2s => BlackBoxFuncCall::Blake2s { inputs: vec![], outputs: vec![] },
+            BlackBoxFunc::Blake3 => BlackBoxFuncCall::Blake3 { inputs: vec![], outputs: vec![] },
             BlackBoxFunc::SchnorrVerify => BlackBoxFuncCall::SchnorrVerify {
                 public_key_x: FunctionInput::dummy(),
                 public_key_y: FunctionInput::dummy(),
@@ -136,6 +366,7 @@ impl BlackBoxFuncCall {
                 domain_separator: 0,
                 outputs: (Witness(0), Witness(0)),
             },
+            #[allow(deprecated)]
             BlackBoxFunc::HashToField128Security => {
                 BlackBoxFuncCall::HashToField128Security { inputs: vec![], output: Witness(0) }
             }
@@ -146,6 +377,7 @@ impl BlackBoxFuncCall {
                 hashed_message: vec![],
                 output: Witness(0),
             },
+            #[allow(deprecated)]
             BlackBoxFunc::FixedBaseScalarMul => BlackBoxFuncCall::FixedBaseScalarMul {
                 input: FunctionInput::dummy(),
                 outputs: (Witness(0), Witness(0)),
@@ -161,6 +393,101 @@ impl BlackBoxFuncCall {
                 input_aggregation_object: None,
                 output_aggregation_object: vec![],
             },
+            BlackBoxFunc::Poseidon2Permutation => {
+                BlackBoxFuncCall::Poseidon2Permutation { inputs: vec![], outputs: vec![], len: 0 }
+            }
+            BlackBoxFunc::EddsaEd25519 => BlackBoxFuncCall::EddsaEd25519 {
+                public_key: vec![],
+                signature: vec![],
+                message: vec![],
+                output: Witness(0),
+            },
+            BlackBoxFunc::EddsaBabyJubjub => BlackBoxFuncCall::EddsaBabyJubjub {
+                public_key_x: FunctionInput::dummy(),
+                public_key_y: FunctionInput::dummy(),
+                signature_s: FunctionInput::dummy(),
+                signature_r8_x: FunctionInput::dummy(),
+                signature_r8_y: FunctionInput::dummy(),
+                message: FunctionInput::dummy(),
+                output: Witness(0),
+            },
+            BlackBoxFunc::EmbeddedCurveAdd => BlackBoxFuncCall::EmbeddedCurveAdd {
+                input1_x: FunctionInput::dummy(),
+                input1_y: FunctionInput::dummy(),
+                input2_x: FunctionInput::dummy(),
+                input2_y: FunctionInput::dummy(),
+                outputs: (Witness(0), Witness(0)),
+            },
+            BlackBoxFunc::EmbeddedCurveDouble => BlackBoxFuncCall::EmbeddedCurveDouble {
+                input_x: FunctionInput::dummy(),
+                input_y: FunctionInput::dummy(),
+                outputs: (Witness(0), Witness(0)),
+            },
+            BlackBoxFunc::MultiScalarMul => BlackBoxFuncCall::MultiScalarMul {
+                points: vec![],
+                scalars: vec![],
+                outputs: (Witness(0), Witness(0)),
+            },
+            BlackBoxFunc::BigIntAdd => BlackBoxFuncCall::BigIntAdd { lhs: 0, rhs: 0, output: 0 },
+            BlackBoxFunc::BigIntMul => BlackBoxFuncCall::BigIntMul { lhs: 0, rhs: 0, output: 0 },
+            BlackBoxFunc::BigIntDiv => BlackBoxFuncCall::BigIntDiv { lhs: 0, rhs: 0, output: 0 },
+            BlackBoxFunc::BigIntFromLeBytes => {
+                BlackBoxFuncCall::BigIntFromLeBytes { inputs: vec![], modulus: vec![], output: 0 }
+            }
+            BlackBoxFunc::BigIntToLeBytes => {
+                BlackBoxFuncCall::BigIntToLeBytes { input: 0, outputs: vec![] }
+            }
+            BlackBoxFunc::Keccakf1600 => {
+                BlackBoxFuncCall::Keccakf1600 { inputs: vec![], outputs: vec![] }
+            }
+            BlackBoxFunc::Aes128Encrypt => BlackBoxFuncCall::Aes128Encrypt {
+                inputs: vec![],
+                iv: vec![],
+                key: vec![],
+                outputs: vec![],
+            },
+            BlackBoxFunc::Sha256Compression => BlackBoxFuncCall::Sha256Compression {
+                inputs: vec![],
+                hash_values: vec![],
+                outputs: vec![],
+            },
+            BlackBoxFunc::HashToField => BlackBoxFuncCall::HashToField {
+                inputs: vec![],
+                domain_separator: vec![],
+                output: Witness(0),
+            },
+            BlackBoxFunc::HashToCurveSecp256k1 => BlackBoxFuncCall::HashToCurveSecp256k1 {
+                inputs: vec![],
+                domain_separator: vec![],
+                outputs: vec![],
+            },
+            BlackBoxFunc::HashToEmbeddedCurve => BlackBoxFuncCall::HashToEmbeddedCurve {
+                inputs: vec![],
+                domain_separator: vec![],
+                outputs: (Witness(0), Witness(0)),
+            },
+            BlackBoxFunc::RangeBatch => BlackBoxFuncCall::RangeBatch { inputs: vec![] },
+            BlackBoxFunc::EcdsaSecp256k1Recover => BlackBoxFuncCall::EcdsaSecp256k1Recover {
+                signature: vec![],
+                recovery_id: FunctionInput::dummy(),
+                hashed_message: vec![],
+                public_key_x: vec![],
+                public_key_y: vec![],
+            },
+            BlackBoxFunc::NOT => {
+                BlackBoxFuncCall::NOT { input: FunctionInput::dummy(), output: Witness(0) }
+            }
+            BlackBoxFunc::SHL => BlackBoxFuncCall::SHL {
+                input: FunctionInput::dummy(),
+                shift: 0,
+                output: Witness(0),
+            },
+            BlackBoxFunc::SHR => BlackBoxFuncCall::SHR {
+                input: FunctionInput::dummy(),
+                shift: 0,
+                output: Witness(0),
+            },
+            BlackBoxFunc::Blake2b => BlackBoxFuncCall::Blake2b { inputs: vec![], outputs: vec![] },
         }
     }
 
@@ -170,15 +497,46 @@ impl BlackBoxFuncCall {
             BlackBoxFuncCall::XOR { .. } => BlackBoxFunc::XOR,
             BlackBoxFuncCall::RANGE { .. } => BlackBoxFunc::RANGE,
             BlackBoxFuncCall::SHA256 { .. } => BlackBoxFunc::SHA256,
+            BlackBoxFuncCall::Sha512 { .. } => BlackBoxFunc::Sha512,
             BlackBoxFuncCall::Blake2s { .. } => BlackBoxFunc::Blake2s,
+            BlackBoxFuncCall::Blake3 { .. } => BlackBoxFunc::Blake3,
             BlackBoxFuncCall::SchnorrVerify { .. } => BlackBoxFunc::SchnorrVerify,
             BlackBoxFuncCall::Pedersen { .. } => BlackBoxFunc::Pedersen,
+            #[allow(deprecated)]
             BlackBoxFuncCall::HashToField128Security { .. } => BlackBoxFunc::HashToField128Security,
             BlackBoxFuncCall::EcdsaSecp256k1 { .. } => BlackBoxFunc::EcdsaSecp256k1,
+            #[allow(deprecated)]
             BlackBoxFuncCall::FixedBaseScalarMul { .. } => BlackBoxFunc::FixedBaseScalarMul,
             BlackBoxFuncCall::Keccak256 { .. } => BlackBoxFunc::Keccak256,
             BlackBoxFuncCall::Keccak256VariableLength { .. } => BlackBoxFunc::Keccak256,
             BlackBoxFuncCall::RecursiveAggregation { .. } => BlackBoxFunc::RecursiveAggregation,
+            BlackBoxFuncCall::Poseidon2Permutation { .. } => BlackBoxFunc::Poseidon2Permutation,
+            BlackBoxFuncCall::EddsaEd25519 { .. } => BlackBoxFunc::EddsaEd25519,
+            BlackBoxFuncCall::EddsaBabyJubjub { .. } => BlackBoxFunc::EddsaBabyJubjub,
+            BlackBoxFuncCall::EmbeddedCurveAdd { .. } => BlackBoxFunc::EmbeddedCurveAdd,
+            BlackBoxFuncCall::EmbeddedCurveDouble { .. } => BlackBoxFunc::EmbeddedCurveDouble,
+            BlackBoxFuncCall::MultiScalarMul { .. } => BlackBoxFunc::MultiScalarMul,
+            BlackBoxFuncCall::BigIntAdd { .. } => BlackBoxFunc::BigIntAdd,
+            BlackBoxFuncCall::BigIntMul { .. } => BlackBoxFunc::BigIntMul,
+            BlackBoxFuncCall::BigIntDiv { .. } => BlackBoxFunc::BigIntDiv,
+            BlackBoxFuncCall::BigIntFromLeBytes { .. } => BlackBoxFunc::BigIntFromLeBytes,
+            BlackBoxFuncCall::BigIntToLeBytes { .. } => BlackBoxFunc::BigIntToLeBytes,
+            BlackBoxFuncCall::Keccakf1600 { .. } => BlackBoxFunc::Keccakf1600,
+            BlackBoxFuncCall::Aes128Encrypt { .. } => BlackBoxFunc::Aes128Encrypt,
+            BlackBoxFuncCall::Sha256Compression { .. } => BlackBoxFunc::Sha256Compression,
+            BlackBoxFuncCall::Sha256VariableLength { .. } => BlackBoxFunc::SHA256,
+            BlackBoxFuncCall::Sha512VariableLength { .. } => BlackBoxFunc::Sha512,
+            BlackBoxFuncCall::Blake2sVariableLength { .. } => BlackBoxFunc::Blake2s,
+            BlackBoxFuncCall::Blake3VariableLength { .. } => BlackBoxFunc::Blake3,
+            BlackBoxFuncCall::HashToField { .. } => BlackBoxFunc::HashToField,
+            BlackBoxFuncCall::HashToCurveSecp256k1 { .. } => BlackBoxFunc::HashToCurveSecp256k1,
+            BlackBoxFuncCall::HashToEmbeddedCurve { .. } => BlackBoxFunc::HashToEmbeddedCurve,
+            BlackBoxFuncCall::EcdsaSecp256k1Recover { .. } => BlackBoxFunc::EcdsaSecp256k1Recover,
+            BlackBoxFuncCall::RangeBatch { .. } => BlackBoxFunc::RangeBatch,
+            BlackBoxFuncCall::NOT { .. } => BlackBoxFunc::NOT,
+            BlackBoxFuncCall::SHL { .. } => BlackBoxFunc::SHL,
+            BlackBoxFuncCall::SHR { .. } => BlackBoxFunc::SHR,
+            BlackBoxFuncCall::Blake2b { .. } => BlackBoxFunc::Blake2b,
         }
     }
 
@@ -188,15 +546,35 @@ impl BlackBoxFuncCall {
 
     pub fn get_inputs_vec(&self) -> Vec<FunctionInput> {
         match self {
+            #[allow(deprecated)]
             BlackBoxFuncCall::SHA256 { inputs, .. }
+            | BlackBoxFuncCall::Sha512 { inputs, .. }
+            | BlackBoxFuncCall::Blake2b { inputs, .. }
             | BlackBoxFuncCall::Blake2s { inputs, .. }
+            | BlackBoxFuncCall::Blake3 { inputs, .. }
             | BlackBoxFuncCall::Keccak256 { inputs, .. }
+            | BlackBoxFuncCall::Keccakf1600 { inputs, .. }
+            | BlackBoxFuncCall::Poseidon2Permutation { inputs, .. }
             | BlackBoxFuncCall::Pedersen { inputs, .. }
+            | BlackBoxFuncCall::BigIntFromLeBytes { inputs, .. }
+            | BlackBoxFuncCall::HashToField { inputs, .. }
+            | BlackBoxFuncCall::HashToCurveSecp256k1 { inputs, .. }
+            | BlackBoxFuncCall::HashToEmbeddedCurve { inputs, .. }
+            | BlackBoxFuncCall::RangeBatch { inputs }
             | BlackBoxFuncCall::HashToField128Security { inputs, .. } => inputs.to_vec(),
+            // Big integer operands are not stored in the witness map.
+            BlackBoxFuncCall::BigIntAdd { .. }
+            | BlackBoxFuncCall::BigIntMul { .. }
+            | BlackBoxFuncCall::BigIntDiv { .. }
+            | BlackBoxFuncCall::BigIntToLeBytes { .. } => vec![],
             BlackBoxFuncCall::AND { lhs, rhs, .. } | BlackBoxFuncCall::XOR { lhs, rhs, .. } => {
                 vec![*lhs, *rhs]
             }
+            #[allow(deprecated)]
             BlackBoxFuncCall::FixedBaseScalarMul { input, .. }
+            | BlackBoxFuncCall::NOT { input, .. }
+            | BlackBoxFuncCall::SHL { input, .. }
+            | BlackBoxFuncCall::SHR { input, .. }
             | BlackBoxFuncCall::RANGE { input } => vec![*input],
             BlackBoxFuncCall::SchnorrVerify {
                 public_key_x,
@@ -233,7 +611,71 @@ impl BlackBoxFuncCall {
                 inputs.extend(hashed_message.iter().copied());
                 inputs
             }
-            BlackBoxFuncCall::Keccak256VariableLength { inputs, var_message_size, .. } => {
+            BlackBoxFuncCall::EcdsaSecp256k1Recover {
+                signature,
+                recovery_id,
+                hashed_message,
+                ..
+            } => {
+                let mut inputs = Vec::with_capacity(signature.len() + 1 + hashed_message.len());
+                inputs.extend(signature.iter().copied());
+                inputs.push(*recovery_id);
+                inputs.extend(hashed_message.iter().copied());
+                inputs
+            }
+            BlackBoxFuncCall::EddsaEd25519 { public_key, signature, message, .. } => {
+                let mut inputs =
+                    Vec::with_capacity(public_key.len() + signature.len() + message.len());
+                inputs.extend(public_key.iter().copied());
+                inputs.extend(signature.iter().copied());
+                inputs.extend(message.iter().copied());
+                inputs
+            }
+            BlackBoxFuncCall::EddsaBabyJubjub {
+                public_key_x,
+                public_key_y,
+                signature_s,
+                signature_r8_x,
+                signature_r8_y,
+                message,
+                ..
+            } => vec![
+                *public_key_x,
+                *public_key_y,
+                *signature_s,
+                *signature_r8_x,
+                *signature_r8_y,
+                *message,
+            ],
+            BlackBoxFuncCall::EmbeddedCurveAdd {
+                input1_x, input1_y, input2_x, input2_y, ..
+            } => {
+                vec![*input1_x, *input1_y, *input2_x, *input2_y]
+            }
+            BlackBoxFuncCall::EmbeddedCurveDouble { input_x, input_y, .. } => {
+                vec![*input_x, *input_y]
+            }
+            BlackBoxFuncCall::Aes128Encrypt { inputs, iv, key, .. } => {
+                let mut all_inputs = Vec::with_capacity(inputs.len() + iv.len() + key.len());
+                all_inputs.extend(inputs.iter().copied());
+                all_inputs.extend(iv.iter().copied());
+                all_inputs.extend(key.iter().copied());
+                all_inputs
+            }
+            BlackBoxFuncCall::Sha256Compression { inputs, hash_values, .. } => {
+                inputs.iter().chain(hash_values).copied().collect()
+            }
+            BlackBoxFuncCall::MultiScalarMul { points, scalars, .. } => {
+                let mut inputs = Vec::with_capacity(points.len() + scalars.len());
+                inputs.extend(points.iter().copied());
+                inputs.extend(scalars.iter().copied());
+                inputs
+            }
+            BlackBoxFuncCall::Keccak256VariableLength { inputs, var_message_size, .. }
+            | BlackBoxFuncCall::Sha256VariableLength { inputs, var_message_size, .. }
+            | BlackBoxFuncCall::Sha512VariableLength { inputs, var_message_size, .. }
+            | BlackBoxFuncCall::Blake2sVariableLength { inputs, var_message_size, .. }
+            | BlackBoxFuncCall::Blake3VariableLength { inputs, var_message_size, .. } => {
                 let mut inputs = inputs.clone();
                 inputs.push(*var_message_size);
                 inputs
@@ -251,8 +693,7 @@ impl BlackBoxFuncCall {
                 inputs.extend(public_inputs.iter().copied());
                 inputs.push(*key_hash);
                 // NOTE: we do not return an input aggregation object as it will either be non-existent for the first recursive aggregation
-                // or the output aggregation object of a previous recursive aggregation. We do not simulate recursive aggregation
-                // thus the input aggregation object will always be unassigned until proving
+                // or the output aggregation object of a previous recursive aggregation, whose assignment is left to the solver to check.
                 inputs
             }
         }
@@ -261,20 +702,53 @@ impl BlackBoxFuncCall {
     pub fn get_outputs_vec(&self) -> Vec<Witness> {
         match self {
             BlackBoxFuncCall::SHA256 { outputs, .. }
+            | BlackBoxFuncCall::Sha512 { outputs, .. }
+            | BlackBoxFuncCall::Blake2b { outputs, .. }
             | BlackBoxFuncCall::Blake2s { outputs, .. }
+            | BlackBoxFuncCall::Blake3 { outputs, .. }
             | BlackBoxFuncCall::Keccak256 { outputs, .. }
+            | BlackBoxFuncCall::Keccakf1600 { outputs, .. }
+            | BlackBoxFuncCall::Aes128Encrypt { outputs, .. }
+            | BlackBoxFuncCall::Sha256Compression { outputs, .. }
+            | BlackBoxFuncCall::HashToCurveSecp256k1 { outputs, .. }
+            | BlackBoxFuncCall::Poseidon2Permutation { outputs, .. }
             | BlackBoxFuncCall::RecursiveAggregation {
                 output_aggregation_object: outputs, ..
             } => outputs.to_vec(),
+            #[allow(deprecated)]
             BlackBoxFuncCall::AND { output, .. }
             | BlackBoxFuncCall::XOR { output, .. }
+            | BlackBoxFuncCall::NOT { output, .. }
+            | BlackBoxFuncCall::SHL { output, .. }
+            | BlackBoxFuncCall::SHR { output, .. }
+            | BlackBoxFuncCall::HashToField { output, .. }
             | BlackBoxFuncCall::HashToField128Security { output, .. }
             | BlackBoxFuncCall::SchnorrVerify { output, .. }
-            | BlackBoxFuncCall::EcdsaSecp256k1 { output, .. } => vec![*output],
+            | BlackBoxFuncCall::EcdsaSecp256k1 { output, .. }
+            | BlackBoxFuncCall::EddsaEd25519 { output, .. }
+            | BlackBoxFuncCall::EddsaBabyJubjub { output, .. } => vec![*output],
+            #[allow(deprecated)]
             BlackBoxFuncCall::FixedBaseScalarMul { outputs, .. }
-            | BlackBoxFuncCall::Pedersen { outputs, .. } => vec![outputs.0, outputs.1],
-            BlackBoxFuncCall::RANGE { .. } => vec![],
-            BlackBoxFuncCall::Keccak256VariableLength { outputs, .. } => outputs.to_vec(),
+            | BlackBoxFuncCall::Pedersen { outputs, .. }
+            | BlackBoxFuncCall::EmbeddedCurveAdd { outputs, .. }
+            | BlackBoxFuncCall::EmbeddedCurveDouble { outputs, .. }
+            | BlackBoxFuncCall::HashToEmbeddedCurve { outputs, .. }
+            | BlackBoxFuncCall::MultiScalarMul { outputs, .. } => vec![outputs.0, outputs.1],
+            BlackBoxFuncCall::RANGE { .. }
+            | BlackBoxFuncCall::RangeBatch { .. }
+            | BlackBoxFuncCall::BigIntAdd { .. }
+            | BlackBoxFuncCall::BigIntMul { .. }
+            | BlackBoxFuncCall::BigIntDiv { .. }
+            | BlackBoxFuncCall::BigIntFromLeBytes { .. } => vec![],
+            BlackBoxFuncCall::Keccak256VariableLength { outputs, .. }
+            | BlackBoxFuncCall::Sha256VariableLength { outputs, .. }
+            | BlackBoxFuncCall::Sha512VariableLength { outputs, .. }
+            | BlackBoxFuncCall::Blake2sVariableLength { outputs, .. }
+            | BlackBoxFuncCall::Blake3VariableLength { outputs, .. }
+            | BlackBoxFuncCall::BigIntToLeBytes { outputs, .. } => outputs.to_vec(),
+            BlackBoxFuncCall::EcdsaSecp256k1Recover { public_key_x, public_key_y, .. } => {
+                public_key_x.iter().chain(public_key_y).copied().collect()
+            }
         }
     }
 }
@@ -364,6 +838,22 @@ impl std::fmt::Display for BlackBoxFuncCall {
             BlackBoxFuncCall::Pedersen { domain_separator, .. } => {
                 write!(f, " domain_separator: {domain_separator}")
             }
+            BlackBoxFuncCall::Poseidon2Permutation { len, .. } => write!(f, " len: {len}"),
+            BlackBoxFuncCall::SHL { shift, .. } | BlackBoxFuncCall::SHR { shift, .. } => {
+                write!(f, " shift: {shift}")
+            }
+            BlackBoxFuncCall::HashToField { domain_separator, .. }
+            | BlackBoxFuncCall::HashToCurveSecp256k1 { domain_separator, .. }
+            | BlackBoxFuncCall::HashToEmbeddedCurve { domain_separator, .. } => {
+                write!(f, " domain_separator: {:?}", String::from_utf8_lossy(domain_separator))
+            }
+            BlackBoxFuncCall::BigIntAdd { lhs, rhs, output }
+            | BlackBoxFuncCall::BigIntMul { lhs, rhs, output }
+            | BlackBoxFuncCall::BigIntDiv { lhs, rhs, output } => {
+                write!(f, " lhs: {lhs}, rhs: {rhs}, output: {output}")
+            }
+            BlackBoxFuncCall::BigIntFromLeBytes { output, .. } => write!(f, " output: {output}"),
+            BlackBoxFuncCall::BigIntToLeBytes { input, .. } => write!(f, " input: {input}"),
             _ => write!(f, ""),
         }
     }
@@ -2,7 +2,9 @@ use crate::native_types::Expression;
 use acir_field::FieldElement;
 use serde::{Deserialize, Serialize};
 
-#[derive(Clone, PartialEq, Eq, Serialize, Deserialize, Hash, Copy, Default)]
+#[derive(
+    Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, Hash, Copy, Default,
+)]
 pub struct BlockId(pub u32);
 
 /// Operation on a block
@@ -15,7 +17,12 @@ pub struct MemOp {
     pub value: Expression,
 }
 
-/// Represents operations on a block of length len of data
+/// All the operations performed on a block of length `len`, in order.
+///
+/// Circuits describe memory with [`MemoryInit`][super::Opcode::MemoryInit] and [`MemoryOp`][super::Opcode::MemoryOp]
+/// opcodes, which can be gathered into a `MemoryBlock` whose trace starts with the writes initializing the block.
+/// Circuits serialized before [format version 1][crate::circuit::CIRCUIT_FORMAT_VERSION] held whole blocks in
+/// `Block`, `ROM` and `RAM` opcodes.
 #[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct MemoryBlock {
     /// Id of the block
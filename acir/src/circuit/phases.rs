@@ -0,0 +1,49 @@
+use std::collections::BTreeSet;
+
+use serde::{Deserialize, Serialize};
+
+/// A named group of the opcodes of a [`Circuit`][super::Circuit], such as the opcodes setting up a computation,
+/// performing it or finalizing it.
+///
+/// Phases allow a witness to be solved in parts, for protocols committing to part of a witness before obtaining
+/// a challenge from which the rest is derived.
+#[derive(Clone, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct Phase {
+    /// The name of the phase, unique within its circuit.
+    pub name: String,
+    /// The indices of the opcodes belonging to the phase.
+    pub opcodes: BTreeSet<usize>,
+}
+
+impl Phase {
+    pub fn new(name: impl Into<String>, opcodes: impl IntoIterator<Item = usize>) -> Self {
+        Phase { name: name.into(), opcodes: opcodes.into_iter().collect() }
+    }
+
+    /// Remaps the opcode indices of this phase after a transformation of the circuit.
+    ///
+    /// `opcode_indices` holds, for each opcode of the transformed circuit, the index of the opcode
+    /// in the original circuit from which it was derived. An opcode belongs to the phase if the
+    /// opcode it was derived from did.
+    pub fn update_acir(&mut self, opcode_indices: &[usize]) {
+        self.opcodes = opcode_indices
+            .iter()
+            .enumerate()
+            .filter(|(_, old_index)| self.opcodes.contains(old_index))
+            .map(|(new_index, _)| new_index)
+            .collect();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Phase;
+
+    #[test]
+    fn update_acir_follows_derived_opcodes() {
+        let mut phase = Phase::new("main", [1, 3]);
+        // Opcode 1 was expanded into two opcodes, and opcode 2 was removed.
+        phase.update_acir(&[0, 1, 1, 3]);
+        assert_eq!(phase, Phase::new("main", [1, 2, 3]));
+    }
+}
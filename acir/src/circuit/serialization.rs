@@ -0,0 +1,358 @@
+//! Versioned (de)serialization of circuits.
+//!
+//! Circuits are serialized along with the [format version][CIRCUIT_FORMAT_VERSION] they were written in.
+//! Circuits written before the format was versioned are read as version 0, in which arithmetic opcodes are named
+//! `Arithmetic` and memory is described by whole `Block`, `ROM` and `RAM` opcodes. These are migrated to
+//! [`Opcode::AssertZero`] and to [`Opcode::MemoryInit`] and [`Opcode::MemoryOp`] opcodes when read.
+//!
+//! Support for reading version 0 circuits is deprecated and will be removed in a future release.
+//!
+//! Version 2 only changed how Brillig bytecode is serialized, which is handled by [`Brillig`] itself.
+//!
+//! Version 3 added the phases of the circuit, serialized after the format version so that earlier versions are
+//! read without them.
+use serde::{
+    de::Error as _, ser::SerializeStruct, Deserialize, Deserializer, Serialize, Serializer,
+};
+
+use super::{
+    brillig::Brillig,
+    debug_info::DebugInfo,
+    directives::Directive,
+    opcodes::{BlackBoxFuncCall, BlockId, MemOp, MemoryBlock},
+    phases::Phase,
+    Circuit, Opcode, PublicInputs, CIRCUIT_FORMAT_VERSION,
+};
+use crate::native_types::{Expression, Witness};
+use acir_field::FieldElement;
+
+impl Serialize for Circuit {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut circuit = serializer.serialize_struct("Circuit", 7)?;
+        circuit.serialize_field("current_witness_index", &self.current_witness_index)?;
+        circuit.serialize_field("opcodes", &self.opcodes)?;
+        circuit.serialize_field("public_parameters", &self.public_parameters)?;
+        circuit.serialize_field("return_values", &self.return_values)?;
+        circuit.serialize_field("debug_info", &self.debug_info)?;
+        circuit.serialize_field("format_version", &CIRCUIT_FORMAT_VERSION)?;
+        circuit.serialize_field("phases", &self.phases)?;
+        circuit.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for Circuit {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        SerializedCircuit::deserialize(deserializer)?.migrate().map_err(D::Error::custom)
+    }
+}
+
+/// A circuit as serialized in any supported format version.
+#[derive(Deserialize)]
+struct SerializedCircuit {
+    current_witness_index: u32,
+    opcodes: Vec<SerializedOpcode>,
+    public_parameters: PublicInputs,
+    return_values: PublicInputs,
+    #[serde(default)]
+    debug_info: Option<DebugInfo>,
+    #[serde(default)]
+    format_version: u32,
+    #[serde(default)]
+    phases: Vec<Phase>,
+}
+
+/// The opcodes of every supported format version, under the name they are serialized with.
+#[derive(Deserialize)]
+// Mirrors `Opcode`, the variants of which are expected to be large.
+#[allow(clippy::large_enum_variant)]
+enum SerializedOpcode {
+    AssertZero(Expression),
+    BlackBoxFuncCall(BlackBoxFuncCall),
+    Directive(Directive),
+    MemoryInit {
+        block_id: BlockId,
+        init: Vec<Witness>,
+    },
+    MemoryOp {
+        block_id: BlockId,
+        op: MemOp,
+    },
+    Brillig(Brillig),
+    // Version 0 opcodes.
+    Arithmetic(Expression),
+    Block(MemoryBlock),
+    #[serde(rename = "ROM")]
+    Rom(MemoryBlock),
+    #[serde(rename = "RAM")]
+    Ram(MemoryBlock),
+}
+
+impl SerializedCircuit {
+    fn migrate(self) -> Result<Circuit, String> {
+        if self.format_version > CIRCUIT_FORMAT_VERSION {
+            return Err(format!(
+                "circuit format version {} is not supported, the latest supported version is {CIRCUIT_FORMAT_VERSION}",
+                self.format_version
+            ));
+        }
+
+        let mut current_witness_index = self.current_witness_index;
+        let mut opcodes = Vec::with_capacity(self.opcodes.len());
+        // The index of the serialized opcode from which each opcode was migrated.
+        let mut opcode_indices = Vec::with_capacity(self.opcodes.len());
+        for (index, opcode) in self.opcodes.into_iter().enumerate() {
+            let migrated = match opcode {
+                SerializedOpcode::AssertZero(expr) => vec![Opcode::AssertZero(expr)],
+                SerializedOpcode::BlackBoxFuncCall(call) => vec![Opcode::BlackBoxFuncCall(call)],
+                SerializedOpcode::Directive(directive) => vec![Opcode::Directive(directive)],
+                SerializedOpcode::MemoryInit { block_id, init } => {
+                    vec![Opcode::MemoryInit { block_id, init }]
+                }
+                SerializedOpcode::MemoryOp { block_id, op } => {
+                    vec![Opcode::MemoryOp { block_id, op }]
+                }
+                SerializedOpcode::Brillig(brillig) => vec![Opcode::Brillig(brillig)],
+                _ if self.format_version > 0 => {
+                    return Err(format!(
+                        "opcode {index} is not valid in circuit format version {}",
+                        self.format_version
+                    ))
+                }
+                SerializedOpcode::Arithmetic(expr) => vec![Opcode::AssertZero(expr)],
+                SerializedOpcode::Block(block) => {
+                    migrate_memory_block(block, false, &mut current_witness_index)?
+                }
+                SerializedOpcode::Rom(block) | SerializedOpcode::Ram(block) => {
+                    migrate_memory_block(block, true, &mut current_witness_index)?
+                }
+            };
+            opcode_indices.extend(std::iter::repeat(index).take(migrated.len()));
+            opcodes.extend(migrated);
+        }
+
+        let mut debug_info = self.debug_info;
+        let mut phases = self.phases;
+        if opcode_indices.iter().enumerate().any(|(new_index, old_index)| new_index != *old_index) {
+            if let Some(debug_info) = &mut debug_info {
+                debug_info.update_acir(&opcode_indices);
+            }
+            for phase in &mut phases {
+                phase.update_acir(&opcode_indices);
+            }
+        }
+
+        Ok(Circuit {
+            current_witness_index,
+            opcodes,
+            public_parameters: self.public_parameters,
+            return_values: self.return_values,
+            debug_info,
+            phases,
+        })
+    }
+}
+
+/// Splits a version 0 memory block into the opcodes initializing it followed by an opcode for each of
+/// its remaining operations.
+///
+/// The initialization phase of a block is made of its first `len` operations, which write to each index in
+/// turn. The values they write which are not witnesses are assigned to new witnesses, allocated after
+/// `current_witness_index` and constrained by [`Opcode::AssertZero`] opcodes ahead of the [`Opcode::MemoryInit`].
+///
+/// `ROM` and `RAM` blocks must start with an initialization phase. A `Block` without one, as accepted by
+/// earlier releases, is migrated to an [`Opcode::MemoryOp`] for each of its operations.
+fn migrate_memory_block(
+    block: MemoryBlock,
+    requires_init: bool,
+    current_witness_index: &mut u32,
+) -> Result<Vec<Opcode>, String> {
+    let len = block.len as usize;
+    let block_id = block.id;
+    let has_init = block.trace.len() >= len
+        && block.trace[..len].iter().enumerate().all(|(index, op)| {
+            op.operation == Expression::one()
+                && op.index == Expression::from_field(FieldElement::from(index as u128))
+        });
+    if !has_init {
+        if requires_init {
+            return Err(format!(
+                "memory block {} cannot be migrated as its first {len} operations do not initialize it",
+                block_id.0
+            ));
+        }
+        return Ok(block.trace.into_iter().map(|op| Opcode::MemoryOp { block_id, op }).collect());
+    }
+
+    let mut opcodes = Vec::new();
+    let mut trace = block.trace.into_iter();
+    let init = trace
+        .by_ref()
+        .take(len)
+        .map(|op| match op.value.to_witness() {
+            Some(witness) => witness,
+            None => {
+                *current_witness_index += 1;
+                let witness = Witness(*current_witness_index);
+                opcodes.push(Opcode::AssertZero(&op.value - witness));
+                witness
+            }
+        })
+        .collect();
+    opcodes.push(Opcode::MemoryInit { block_id, init });
+    opcodes.extend(trace.map(|op| Opcode::MemoryOp { block_id, op }));
+    Ok(opcodes)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use serde_json::json;
+
+    use super::super::{
+        debug_info::{DebugInfo, SourceLocation},
+        opcodes::{BlockId, MemOp, MemoryBlock},
+        Circuit, Opcode, OpcodeLocation,
+    };
+    use crate::native_types::{Expression, Witness};
+    use acir_field::FieldElement;
+
+    fn op(operation: u128, index: Expression, value: u32) -> MemOp {
+        MemOp {
+            operation: Expression::from_field(FieldElement::from(operation)),
+            index,
+            value: Expression::from(Witness(value)),
+        }
+    }
+
+    fn constant(value: u128) -> Expression {
+        Expression::from_field(FieldElement::from(value))
+    }
+
+    /// Reads a circuit from the hex encoding of the bytes written by [`Circuit::write`].
+    fn read_hex(hex: &str) -> Circuit {
+        let bytes: Vec<u8> = (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+            .collect();
+        Circuit::read(&*bytes).unwrap()
+    }
+
+    #[test]
+    fn reads_version_0_circuits() {
+        // A `RAM` block written by an earlier release, initialized by witnesses 1 and 2.
+        let circuit = read_hex("dd925111c0200c4337b6a1071c6c02f683231c047a28c00836708388f6234705f45e5ed27cc93d7dbf1cae8900f30dba8b164f82a0543dca690042e324da3871244e8c2abe58401e9ead6de5f5e6d92b4d395e9f06180b");
+
+        let memory_op = |op| Opcode::MemoryOp { block_id: BlockId(0), op };
+        assert_eq!(
+            circuit.opcodes,
+            vec![
+                Opcode::MemoryInit { block_id: BlockId(0), init: vec![Witness(1), Witness(2)] },
+                memory_op(op(0, Witness(3).into(), 5)),
+                memory_op(op(1, Witness(3).into(), 4)),
+                memory_op(op(0, constant(1), 6)),
+            ]
+        );
+    }
+
+    #[test]
+    fn migrates_version_0_blocks_initialized_with_constants_and_expressions() {
+        // A `ROM` block written by an earlier release, initialized by 5 and `w1 + 1`, then read at `w2` into `w3`.
+        let circuit = read_hex("cd91490d00200c04390cc1833f0608098e70d0521ca00a37e0a1fbe80a98cc6477e479466fe2828810dd9a74cb084842408a9d9e0fe1a5a7788008e81e444eb02212f522440f");
+
+        // The values which are not witnesses are assigned to new witnesses.
+        assert_eq!(circuit.current_witness_index, 5);
+        let w1_plus_one = &Expression::from(Witness(1)) + &constant(1);
+        assert_eq!(
+            circuit.opcodes,
+            vec![
+                Opcode::AssertZero(&constant(5) - Witness(4)),
+                Opcode::AssertZero(&w1_plus_one - Witness(5)),
+                Opcode::MemoryInit { block_id: BlockId(0), init: vec![Witness(4), Witness(5)] },
+                Opcode::MemoryOp { block_id: BlockId(0), op: op(0, Witness(2).into(), 3) },
+            ]
+        );
+    }
+
+    #[test]
+    fn migrates_version_0_blocks_without_initialization() {
+        // A `Block` written by an earlier release, whose index 1 is written before index 0.
+        let circuit = read_hex("cdd1410d00200c03c00086c68b2fc10a4f48d08083c1b08028dc20627dac022e4d7bc29ab7f4519b387f44985f265d2208595baf3835417636210492c44c15d0c7dec8c70442109b047d11e60f");
+
+        let memory_op = |op| Opcode::MemoryOp { block_id: BlockId(1), op };
+        let write_constant =
+            MemOp { operation: constant(1), index: constant(0), value: constant(7) };
+        assert_eq!(circuit.current_witness_index, 3);
+        assert_eq!(
+            circuit.opcodes,
+            vec![
+                memory_op(op(1, constant(1), 1)),
+                memory_op(write_constant),
+                memory_op(op(0, constant(1), 2)),
+                memory_op(op(0, constant(0), 3)),
+            ]
+        );
+    }
+
+    #[test]
+    fn migrates_debug_info_of_version_0_circuits() {
+        let block = MemoryBlock {
+            id: BlockId(3),
+            len: 1,
+            trace: vec![op(1, constant(0), 1), op(0, constant(0), 2)],
+        };
+        let call_stack = vec![SourceLocation { file_id: 0, start: 1, end: 2 }];
+        let debug_info =
+            DebugInfo::new(BTreeMap::from([(OpcodeLocation::Acir(1), call_stack.clone())]));
+        let legacy = json!({
+            "current_witness_index": 2,
+            "opcodes": [{ "ROM": block }, { "Arithmetic": Expression::from(Witness(2)) }],
+            "public_parameters": [],
+            "return_values": [],
+            "debug_info": debug_info,
+        });
+
+        let circuit: Circuit = serde_json::from_str(&legacy.to_string()).unwrap();
+        assert_eq!(circuit.opcodes.len(), 3);
+        assert_eq!(circuit.opcodes[2], Opcode::AssertZero(Expression::from(Witness(2))));
+        assert_eq!(
+            circuit.debug_info.unwrap().locations,
+            BTreeMap::from([(OpcodeLocation::Acir(2), call_stack)])
+        );
+    }
+
+    #[test]
+    fn rejects_circuits_which_cannot_be_read_as_their_version() {
+        let circuit = |opcodes, format_version: Option<u32>| {
+            let mut circuit = json!({
+                "current_witness_index": 2,
+                "opcodes": opcodes,
+                "public_parameters": [],
+                "return_values": [],
+            });
+            if let Some(format_version) = format_version {
+                circuit["format_version"] = json!(format_version);
+            }
+            serde_json::from_str::<Circuit>(&circuit.to_string())
+        };
+        let arithmetic = json!([{ "Arithmetic": Expression::from(Witness(2)) }]);
+        assert!(circuit(arithmetic.clone(), None).is_ok());
+        assert!(circuit(arithmetic, Some(1)).is_err(), "version 1 renamed arithmetic opcodes");
+
+        let assert_zero = json!([{ "AssertZero": Expression::from(Witness(2)) }]);
+        assert!(circuit(assert_zero.clone(), Some(1)).is_ok());
+        assert!(circuit(assert_zero.clone(), Some(2)).is_ok());
+        assert!(circuit(assert_zero.clone(), Some(3)).is_ok());
+        assert!(circuit(assert_zero, Some(4)).is_err(), "version 4 does not exist yet");
+
+        let uninitialized =
+            MemoryBlock { id: BlockId(0), len: 1, trace: vec![op(0, constant(0), 1)] };
+        assert!(circuit(json!([{ "RAM": uninitialized }]), None).is_err());
+    }
+
+    #[test]
+    fn writes_the_current_format_version() {
+        let json = serde_json::to_value(Circuit::default()).unwrap();
+        assert_eq!(json["format_version"], json!(super::CIRCUIT_FORMAT_VERSION));
+    }
+}
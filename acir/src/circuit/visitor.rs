@@ -0,0 +1,665 @@
+//! Traversal of the opcodes of a [`Circuit`].
+//!
+//! The [`Visitor`] and [`VisitorMut`] traits provide a method for each node of a circuit, the default
+//! implementation of which recurses into its children through the matching `walk_*` function.
+//! Implementors only need to override the methods for the nodes they are interested in, calling
+//! back into the `walk_*` function if they want traversal to continue below that node.
+//!
+//! ```
+//! use acir::circuit::{visitor::Visitor, Circuit};
+//! use acir::native_types::Witness;
+//!
+//! #[derive(Default)]
+//! struct CountWitnesses(usize);
+//!
+//! impl Visitor for CountWitnesses {
+//!     fn visit_witness(&mut self, _witness: &Witness) {
+//!         self.0 += 1;
+//!     }
+//! }
+//!
+//! let mut counter = CountWitnesses::default();
+//! counter.visit_circuit(&Circuit::default());
+//! assert_eq!(counter.0, 0);
+//! ```
+
+use super::{
+    brillig::{Brillig, BrilligInputs, BrilligOutputs},
+    directives::{Directive, LogInfo, QuotientDirective},
+    opcodes::{BlackBoxFuncCall, BlockId, FunctionInput, MemOp},
+    Circuit, Opcode, PublicInputs,
+};
+use crate::native_types::{Expression, Witness};
+
+/// Immutable traversal over the contents of a [`Circuit`].
+pub trait Visitor {
+    fn visit_circuit(&mut self, circuit: &Circuit) {
+        walk_circuit(self, circuit)
+    }
+
+    fn visit_opcode(&mut self, opcode: &Opcode) {
+        walk_opcode(self, opcode)
+    }
+
+    fn visit_expression(&mut self, expr: &Expression) {
+        walk_expression(self, expr)
+    }
+
+    fn visit_witness(&mut self, _witness: &Witness) {}
+
+    fn visit_function_input(&mut self, input: &FunctionInput) {
+        self.visit_witness(&input.witness)
+    }
+
+    fn visit_black_box_func_call(&mut self, call: &BlackBoxFuncCall) {
+        walk_black_box_func_call(self, call)
+    }
+
+    fn visit_directive(&mut self, directive: &Directive) {
+        walk_directive(self, directive)
+    }
+
+    fn visit_memory_init(&mut self, block_id: &BlockId, init: &[Witness]) {
+        walk_memory_init(self, block_id, init)
+    }
+
+    fn visit_mem_op(&mut self, op: &MemOp) {
+        walk_mem_op(self, op)
+    }
+
+    fn visit_brillig(&mut self, brillig: &Brillig) {
+        walk_brillig(self, brillig)
+    }
+}
+
+pub fn walk_circuit<V: Visitor + ?Sized>(visitor: &mut V, circuit: &Circuit) {
+    for opcode in &circuit.opcodes {
+        visitor.visit_opcode(opcode);
+    }
+    for witness in circuit.public_parameters.0.iter().chain(&circuit.return_values.0) {
+        visitor.visit_witness(witness);
+    }
+}
+
+pub fn walk_opcode<V: Visitor + ?Sized>(visitor: &mut V, opcode: &Opcode) {
+    match opcode {
+        Opcode::AssertZero(expr) => visitor.visit_expression(expr),
+        Opcode::BlackBoxFuncCall(call) => visitor.visit_black_box_func_call(call),
+        Opcode::Directive(directive) => visitor.visit_directive(directive),
+        Opcode::MemoryInit { block_id, init } => visitor.visit_memory_init(block_id, init),
+        Opcode::MemoryOp { op, .. } => visitor.visit_mem_op(op),
+        Opcode::Brillig(brillig) => visitor.visit_brillig(brillig),
+    }
+}
+
+pub fn walk_expression<V: Visitor + ?Sized>(visitor: &mut V, expr: &Expression) {
+    for (_, lhs, rhs) in &expr.mul_terms {
+        visitor.visit_witness(lhs);
+        visitor.visit_witness(rhs);
+    }
+    for (_, witness) in &expr.linear_combinations {
+        visitor.visit_witness(witness);
+    }
+}
+
+pub fn walk_black_box_func_call<V: Visitor + ?Sized>(visitor: &mut V, call: &BlackBoxFuncCall) {
+    for input in call.get_inputs_vec() {
+        visitor.visit_function_input(&input);
+    }
+    // The input aggregation object is deliberately left out of `get_inputs_vec`.
+    if let BlackBoxFuncCall::RecursiveAggregation {
+        input_aggregation_object: Some(aggregation_object),
+        ..
+    } = call
+    {
+        for input in aggregation_object {
+            visitor.visit_function_input(input);
+        }
+    }
+    for output in call.get_outputs_vec() {
+        visitor.visit_witness(&output);
+    }
+}
+
+pub fn walk_directive<V: Visitor + ?Sized>(visitor: &mut V, directive: &Directive) {
+    match directive {
+        Directive::Invert { x, result } => {
+            visitor.visit_witness(x);
+            visitor.visit_witness(result);
+        }
+        Directive::Quotient(QuotientDirective { a, b, q, r, predicate }) => {
+            visitor.visit_expression(a);
+            visitor.visit_expression(b);
+            visitor.visit_witness(q);
+            visitor.visit_witness(r);
+            if let Some(predicate) = predicate {
+                visitor.visit_expression(predicate);
+            }
+        }
+        Directive::ToLeRadix { a, b, .. } => {
+            visitor.visit_expression(a);
+            for witness in b {
+                visitor.visit_witness(witness);
+            }
+        }
+        Directive::PermutationSort { inputs, bits, .. } => {
+            for expr in inputs.iter().flatten() {
+                visitor.visit_expression(expr);
+            }
+            for witness in bits {
+                visitor.visit_witness(witness);
+            }
+        }
+        Directive::Log(LogInfo::WitnessOutput(witnesses)) => {
+            for witness in witnesses {
+                visitor.visit_witness(witness);
+            }
+        }
+        Directive::Log(LogInfo::FinalizedOutput(_)) => (),
+        Directive::BigQuotient { a, b, q, r, .. } => {
+            for expr in a.iter().chain(b) {
+                visitor.visit_expression(expr);
+            }
+            for witness in q.iter().chain(r) {
+                visitor.visit_witness(witness);
+            }
+        }
+        Directive::BigInvert { a, modulus, result, .. } => {
+            for expr in a.iter().chain(modulus) {
+                visitor.visit_expression(expr);
+            }
+            for witness in result {
+                visitor.visit_witness(witness);
+            }
+        }
+    }
+}
+
+pub fn walk_memory_init<V: Visitor + ?Sized>(
+    visitor: &mut V,
+    _block_id: &BlockId,
+    init: &[Witness],
+) {
+    for witness in init {
+        visitor.visit_witness(witness);
+    }
+}
+
+pub fn walk_mem_op<V: Visitor + ?Sized>(visitor: &mut V, op: &MemOp) {
+    visitor.visit_expression(&op.operation);
+    visitor.visit_expression(&op.index);
+    visitor.visit_expression(&op.value);
+}
+
+pub fn walk_brillig<V: Visitor + ?Sized>(visitor: &mut V, brillig: &Brillig) {
+    for input in &brillig.inputs {
+        match input {
+            BrilligInputs::Single(expr) => visitor.visit_expression(expr),
+            BrilligInputs::Array(exprs) => {
+                for expr in exprs {
+                    visitor.visit_expression(expr);
+                }
+            }
+        }
+    }
+    for output in &brillig.outputs {
+        match output {
+            BrilligOutputs::Simple(witness) => visitor.visit_witness(witness),
+            BrilligOutputs::Array(witnesses) => {
+                for witness in witnesses {
+                    visitor.visit_witness(witness);
+                }
+            }
+        }
+    }
+    if let Some(predicate) = &brillig.predicate {
+        visitor.visit_expression(predicate);
+    }
+}
+
+/// Mutable traversal over the contents of a [`Circuit`].
+///
+/// This mirrors [`Visitor`] and allows passes to rewrite nodes in place,
+/// e.g. renumbering every witness of a circuit by overriding [`VisitorMut::visit_witness_mut`].
+pub trait VisitorMut {
+    fn visit_circuit_mut(&mut self, circuit: &mut Circuit) {
+        walk_circuit_mut(self, circuit)
+    }
+
+    fn visit_opcode_mut(&mut self, opcode: &mut Opcode) {
+        walk_opcode_mut(self, opcode)
+    }
+
+    fn visit_expression_mut(&mut self, expr: &mut Expression) {
+        walk_expression_mut(self, expr)
+    }
+
+    fn visit_witness_mut(&mut self, _witness: &mut Witness) {}
+
+    fn visit_function_input_mut(&mut self, input: &mut FunctionInput) {
+        self.visit_witness_mut(&mut input.witness)
+    }
+
+    fn visit_black_box_func_call_mut(&mut self, call: &mut BlackBoxFuncCall) {
+        walk_black_box_func_call_mut(self, call)
+    }
+
+    fn visit_directive_mut(&mut self, directive: &mut Directive) {
+        walk_directive_mut(self, directive)
+    }
+
+    fn visit_memory_init_mut(&mut self, block_id: &mut BlockId, init: &mut [Witness]) {
+        walk_memory_init_mut(self, block_id, init)
+    }
+
+    fn visit_mem_op_mut(&mut self, op: &mut MemOp) {
+        walk_mem_op_mut(self, op)
+    }
+
+    fn visit_brillig_mut(&mut self, brillig: &mut Brillig) {
+        walk_brillig_mut(self, brillig)
+    }
+}
+
+pub fn walk_circuit_mut<V: VisitorMut + ?Sized>(visitor: &mut V, circuit: &mut Circuit) {
+    for opcode in &mut circuit.opcodes {
+        visitor.visit_opcode_mut(opcode);
+    }
+    // Public inputs are stored in an ordered set so must be rebuilt after being visited.
+    for public_inputs in [&mut circuit.public_parameters, &mut circuit.return_values] {
+        let witnesses = std::mem::take(&mut public_inputs.0);
+        *public_inputs = PublicInputs(
+            witnesses
+                .into_iter()
+                .map(|mut witness| {
+                    visitor.visit_witness_mut(&mut witness);
+                    witness
+                })
+                .collect(),
+        );
+    }
+}
+
+pub fn walk_opcode_mut<V: VisitorMut + ?Sized>(visitor: &mut V, opcode: &mut Opcode) {
+    match opcode {
+        Opcode::AssertZero(expr) => visitor.visit_expression_mut(expr),
+        Opcode::BlackBoxFuncCall(call) => visitor.visit_black_box_func_call_mut(call),
+        Opcode::Directive(directive) => visitor.visit_directive_mut(directive),
+        Opcode::MemoryInit { block_id, init } => visitor.visit_memory_init_mut(block_id, init),
+        Opcode::MemoryOp { op, .. } => visitor.visit_mem_op_mut(op),
+        Opcode::Brillig(brillig) => visitor.visit_brillig_mut(brillig),
+    }
+}
+
+pub fn walk_expression_mut<V: VisitorMut + ?Sized>(visitor: &mut V, expr: &mut Expression) {
+    for (_, lhs, rhs) in &mut expr.mul_terms {
+        visitor.visit_witness_mut(lhs);
+        visitor.visit_witness_mut(rhs);
+    }
+    for (_, witness) in &mut expr.linear_combinations {
+        visitor.visit_witness_mut(witness);
+    }
+}
+
+pub fn walk_black_box_func_call_mut<V: VisitorMut + ?Sized>(
+    visitor: &mut V,
+    call: &mut BlackBoxFuncCall,
+) {
+    match call {
+        BlackBoxFuncCall::AND { lhs, rhs, output } | BlackBoxFuncCall::XOR { lhs, rhs, output } => {
+            visitor.visit_function_input_mut(lhs);
+            visitor.visit_function_input_mut(rhs);
+            visitor.visit_witness_mut(output);
+        }
+        BlackBoxFuncCall::NOT { input, output }
+        | BlackBoxFuncCall::SHL { input, output, .. }
+        | BlackBoxFuncCall::SHR { input, output, .. } => {
+            visitor.visit_function_input_mut(input);
+            visitor.visit_witness_mut(output);
+        }
+        BlackBoxFuncCall::RANGE { input } => visitor.visit_function_input_mut(input),
+        BlackBoxFuncCall::RangeBatch { inputs } => {
+            inputs.iter_mut().for_each(|input| visitor.visit_function_input_mut(input));
+        }
+        BlackBoxFuncCall::SHA256 { inputs, outputs }
+        | BlackBoxFuncCall::Sha512 { inputs, outputs }
+        | BlackBoxFuncCall::Blake2b { inputs, outputs }
+        | BlackBoxFuncCall::Blake2s { inputs, outputs }
+        | BlackBoxFuncCall::Blake3 { inputs, outputs }
+        | BlackBoxFuncCall::Keccak256 { inputs, outputs }
+        | BlackBoxFuncCall::Keccakf1600 { inputs, outputs }
+        | BlackBoxFuncCall::Poseidon2Permutation { inputs, outputs, .. } => {
+            inputs.iter_mut().for_each(|input| visitor.visit_function_input_mut(input));
+            outputs.iter_mut().for_each(|output| visitor.visit_witness_mut(output));
+        }
+        BlackBoxFuncCall::SchnorrVerify {
+            public_key_x,
+            public_key_y,
+            signature_s,
+            signature_e,
+            message,
+            output,
+        } => {
+            visitor.visit_function_input_mut(public_key_x);
+            visitor.visit_function_input_mut(public_key_y);
+            visitor.visit_function_input_mut(signature_s);
+            visitor.visit_function_input_mut(signature_e);
+            message.iter_mut().for_each(|input| visitor.visit_function_input_mut(input));
+            visitor.visit_witness_mut(output);
+        }
+        BlackBoxFuncCall::Pedersen { inputs, outputs, .. } => {
+            inputs.iter_mut().for_each(|input| visitor.visit_function_input_mut(input));
+            visitor.visit_witness_mut(&mut outputs.0);
+            visitor.visit_witness_mut(&mut outputs.1);
+        }
+        #[allow(deprecated)]
+        BlackBoxFuncCall::HashToField128Security { inputs, output }
+        | BlackBoxFuncCall::HashToField { inputs, output, .. } => {
+            inputs.iter_mut().for_each(|input| visitor.visit_function_input_mut(input));
+            visitor.visit_witness_mut(output);
+        }
+        BlackBoxFuncCall::EcdsaSecp256k1 {
+            public_key_x,
+            public_key_y,
+            signature,
+            hashed_message,
+            output,
+        } => {
+            for input in public_key_x
+                .iter_mut()
+                .chain(public_key_y.iter_mut())
+                .chain(signature.iter_mut())
+                .chain(hashed_message.iter_mut())
+            {
+                visitor.visit_function_input_mut(input);
+            }
+            visitor.visit_witness_mut(output);
+        }
+        BlackBoxFuncCall::EcdsaSecp256k1Recover {
+            signature,
+            recovery_id,
+            hashed_message,
+            public_key_x,
+            public_key_y,
+        } => {
+            signature.iter_mut().for_each(|input| visitor.visit_function_input_mut(input));
+            visitor.visit_function_input_mut(recovery_id);
+            hashed_message.iter_mut().for_each(|input| visitor.visit_function_input_mut(input));
+            for output in public_key_x.iter_mut().chain(public_key_y.iter_mut()) {
+                visitor.visit_witness_mut(output);
+            }
+        }
+        BlackBoxFuncCall::EddsaEd25519 { public_key, signature, message, output } => {
+            for input in public_key.iter_mut().chain(signature.iter_mut()).chain(message.iter_mut())
+            {
+                visitor.visit_function_input_mut(input);
+            }
+            visitor.visit_witness_mut(output);
+        }
+        BlackBoxFuncCall::EddsaBabyJubjub {
+            public_key_x,
+            public_key_y,
+            signature_s,
+            signature_r8_x,
+            signature_r8_y,
+            message,
+            output,
+        } => {
+            visitor.visit_function_input_mut(public_key_x);
+            visitor.visit_function_input_mut(public_key_y);
+            visitor.visit_function_input_mut(signature_s);
+            visitor.visit_function_input_mut(signature_r8_x);
+            visitor.visit_function_input_mut(signature_r8_y);
+            visitor.visit_function_input_mut(message);
+            visitor.visit_witness_mut(output);
+        }
+        BlackBoxFuncCall::EmbeddedCurveAdd { input1_x, input1_y, input2_x, input2_y, outputs } => {
+            visitor.visit_function_input_mut(input1_x);
+            visitor.visit_function_input_mut(input1_y);
+            visitor.visit_function_input_mut(input2_x);
+            visitor.visit_function_input_mut(input2_y);
+            visitor.visit_witness_mut(&mut outputs.0);
+            visitor.visit_witness_mut(&mut outputs.1);
+        }
+        BlackBoxFuncCall::HashToCurveSecp256k1 { inputs, outputs, .. } => {
+            inputs.iter_mut().for_each(|input| visitor.visit_function_input_mut(input));
+            outputs.iter_mut().for_each(|output| visitor.visit_witness_mut(output));
+        }
+        BlackBoxFuncCall::HashToEmbeddedCurve { inputs, outputs, .. } => {
+            inputs.iter_mut().for_each(|input| visitor.visit_function_input_mut(input));
+            visitor.visit_witness_mut(&mut outputs.0);
+            visitor.visit_witness_mut(&mut outputs.1);
+        }
+        BlackBoxFuncCall::EmbeddedCurveDouble { input_x, input_y, outputs } => {
+            visitor.visit_function_input_mut(input_x);
+            visitor.visit_function_input_mut(input_y);
+            visitor.visit_witness_mut(&mut outputs.0);
+            visitor.visit_witness_mut(&mut outputs.1);
+        }
+        BlackBoxFuncCall::BigIntFromLeBytes { inputs, .. } => {
+            inputs.iter_mut().for_each(|input| visitor.visit_function_input_mut(input));
+        }
+        BlackBoxFuncCall::BigIntToLeBytes { outputs, .. } => {
+            outputs.iter_mut().for_each(|output| visitor.visit_witness_mut(output));
+        }
+        BlackBoxFuncCall::BigIntAdd { .. }
+        | BlackBoxFuncCall::BigIntMul { .. }
+        | BlackBoxFuncCall::BigIntDiv { .. } => (),
+        BlackBoxFuncCall::Aes128Encrypt { inputs, iv, key, outputs } => {
+            for input in inputs.iter_mut().chain(iv.iter_mut()).chain(key.iter_mut()) {
+                visitor.visit_function_input_mut(input);
+            }
+            outputs.iter_mut().for_each(|output| visitor.visit_witness_mut(output));
+        }
+        BlackBoxFuncCall::Sha256Compression { inputs, hash_values, outputs } => {
+            for input in inputs.iter_mut().chain(hash_values.iter_mut()) {
+                visitor.visit_function_input_mut(input);
+            }
+            outputs.iter_mut().for_each(|output| visitor.visit_witness_mut(output));
+        }
+        BlackBoxFuncCall::MultiScalarMul { points, scalars, outputs } => {
+            for input in points.iter_mut().chain(scalars.iter_mut()) {
+                visitor.visit_function_input_mut(input);
+            }
+            visitor.visit_witness_mut(&mut outputs.0);
+            visitor.visit_witness_mut(&mut outputs.1);
+        }
+        #[allow(deprecated)]
+        BlackBoxFuncCall::FixedBaseScalarMul { input, outputs } => {
+            visitor.visit_function_input_mut(input);
+            visitor.visit_witness_mut(&mut outputs.0);
+            visitor.visit_witness_mut(&mut outputs.1);
+        }
+        BlackBoxFuncCall::Keccak256VariableLength { inputs, var_message_size, outputs }
+        | BlackBoxFuncCall::Sha256VariableLength { inputs, var_message_size, outputs }
+        | BlackBoxFuncCall::Sha512VariableLength { inputs, var_message_size, outputs }
+        | BlackBoxFuncCall::Blake2sVariableLength { inputs, var_message_size, outputs }
+        | BlackBoxFuncCall::Blake3VariableLength { inputs, var_message_size, outputs } => {
+            inputs.iter_mut().for_each(|input| visitor.visit_function_input_mut(input));
+            visitor.visit_function_input_mut(var_message_size);
+            outputs.iter_mut().for_each(|output| visitor.visit_witness_mut(output));
+        }
+        BlackBoxFuncCall::RecursiveAggregation {
+            verification_key,
+            proof,
+            public_inputs,
+            key_hash,
+            input_aggregation_object,
+            output_aggregation_object,
+        } => {
+            for input in verification_key
+                .iter_mut()
+                .chain(proof.iter_mut())
+                .chain(public_inputs.iter_mut())
+                .chain(std::iter::once(key_hash))
+                .chain(input_aggregation_object.iter_mut().flatten())
+            {
+                visitor.visit_function_input_mut(input);
+            }
+            output_aggregation_object
+                .iter_mut()
+                .for_each(|output| visitor.visit_witness_mut(output));
+        }
+    }
+}
+
+pub fn walk_directive_mut<V: VisitorMut + ?Sized>(visitor: &mut V, directive: &mut Directive) {
+    match directive {
+        Directive::Invert { x, result } => {
+            visitor.visit_witness_mut(x);
+            visitor.visit_witness_mut(result);
+        }
+        Directive::Quotient(QuotientDirective { a, b, q, r, predicate }) => {
+            visitor.visit_expression_mut(a);
+            visitor.visit_expression_mut(b);
+            visitor.visit_witness_mut(q);
+            visitor.visit_witness_mut(r);
+            if let Some(predicate) = predicate {
+                visitor.visit_expression_mut(predicate);
+            }
+        }
+        Directive::ToLeRadix { a, b, .. } => {
+            visitor.visit_expression_mut(a);
+            b.iter_mut().for_each(|witness| visitor.visit_witness_mut(witness));
+        }
+        Directive::PermutationSort { inputs, bits, .. } => {
+            inputs.iter_mut().flatten().for_each(|expr| visitor.visit_expression_mut(expr));
+            bits.iter_mut().for_each(|witness| visitor.visit_witness_mut(witness));
+        }
+        Directive::Log(LogInfo::WitnessOutput(witnesses)) => {
+            witnesses.iter_mut().for_each(|witness| visitor.visit_witness_mut(witness));
+        }
+        Directive::Log(LogInfo::FinalizedOutput(_)) => (),
+        Directive::BigQuotient { a, b, q, r, .. } => {
+            a.iter_mut().chain(b).for_each(|expr| visitor.visit_expression_mut(expr));
+            q.iter_mut().chain(r).for_each(|witness| visitor.visit_witness_mut(witness));
+        }
+        Directive::BigInvert { a, modulus, result, .. } => {
+            a.iter_mut().chain(modulus).for_each(|expr| visitor.visit_expression_mut(expr));
+            result.iter_mut().for_each(|witness| visitor.visit_witness_mut(witness));
+        }
+    }
+}
+
+pub fn walk_memory_init_mut<V: VisitorMut + ?Sized>(
+    visitor: &mut V,
+    _block_id: &mut BlockId,
+    init: &mut [Witness],
+) {
+    for witness in init {
+        visitor.visit_witness_mut(witness);
+    }
+}
+
+pub fn walk_mem_op_mut<V: VisitorMut + ?Sized>(visitor: &mut V, op: &mut MemOp) {
+    visitor.visit_expression_mut(&mut op.operation);
+    visitor.visit_expression_mut(&mut op.index);
+    visitor.visit_expression_mut(&mut op.value);
+}
+
+pub fn walk_brillig_mut<V: VisitorMut + ?Sized>(visitor: &mut V, brillig: &mut Brillig) {
+    for input in &mut brillig.inputs {
+        match input {
+            BrilligInputs::Single(expr) => visitor.visit_expression_mut(expr),
+            BrilligInputs::Array(exprs) => {
+                exprs.iter_mut().for_each(|expr| visitor.visit_expression_mut(expr))
+            }
+        }
+    }
+    for output in &mut brillig.outputs {
+        match output {
+            BrilligOutputs::Simple(witness) => visitor.visit_witness_mut(witness),
+            BrilligOutputs::Array(witnesses) => {
+                witnesses.iter_mut().for_each(|witness| visitor.visit_witness_mut(witness))
+            }
+        }
+    }
+    if let Some(predicate) = &mut brillig.predicate {
+        visitor.visit_expression_mut(predicate);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use smallvec::smallvec;
+
+    use super::{Visitor, VisitorMut};
+    use crate::{
+        circuit::{
+            brillig::{Brillig, BrilligInputs, BrilligOutputs},
+            directives::Directive,
+            opcodes::{BlackBoxFuncCall, FunctionInput},
+            Circuit, Opcode, PublicInputs,
+        },
+        native_types::{Expression, Witness},
+        FieldElement,
+    };
+
+    #[derive(Default)]
+    struct CollectWitnesses(BTreeSet<Witness>);
+
+    impl Visitor for CollectWitnesses {
+        fn visit_witness(&mut self, witness: &Witness) {
+            self.0.insert(*witness);
+        }
+    }
+
+    struct ShiftWitnesses(u32);
+
+    impl VisitorMut for ShiftWitnesses {
+        fn visit_witness_mut(&mut self, witness: &mut Witness) {
+            witness.0 += self.0;
+        }
+    }
+
+    fn test_circuit() -> Circuit {
+        let expr = Expression {
+            mul_terms: smallvec![(FieldElement::one(), Witness(1), Witness(2))],
+            linear_combinations: smallvec![(FieldElement::one(), Witness(3))],
+            q_c: FieldElement::zero(),
+        };
+        Circuit {
+            current_witness_index: 7,
+            opcodes: vec![
+                Opcode::AssertZero(expr.clone()),
+                Opcode::BlackBoxFuncCall(BlackBoxFuncCall::AND {
+                    lhs: FunctionInput { witness: Witness(1), num_bits: 8 },
+                    rhs: FunctionInput { witness: Witness(2), num_bits: 8 },
+                    output: Witness(4),
+                }),
+                Opcode::Directive(Directive::Invert { x: Witness(3), result: Witness(5) }),
+                Opcode::Brillig(Brillig {
+                    inputs: vec![BrilligInputs::Single(expr)],
+                    outputs: vec![BrilligOutputs::Array(vec![Witness(6)])],
+                    foreign_call_results: vec![],
+                    bytecode: vec![],
+                    predicate: Some(Expression::from(Witness(7))),
+                }),
+            ],
+            public_parameters: PublicInputs(BTreeSet::from([Witness(1)])),
+            return_values: PublicInputs(BTreeSet::from([Witness(6)])),
+            debug_info: None,
+            phases: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn visits_every_witness() {
+        let mut collector = CollectWitnesses::default();
+        collector.visit_circuit(&test_circuit());
+        assert_eq!(collector.0, (1..=7).map(Witness).collect());
+    }
+
+    #[test]
+    fn mutates_every_witness() {
+        let mut circuit = test_circuit();
+        ShiftWitnesses(10).visit_circuit_mut(&mut circuit);
+
+        let mut collector = CollectWitnesses::default();
+        collector.visit_circuit(&circuit);
+        assert_eq!(collector.0, (11..=17).map(Witness).collect());
+        assert_eq!(circuit.return_values, PublicInputs(BTreeSet::from([Witness(16)])));
+    }
+}
@@ -10,3 +10,8 @@ pub use acir_field;
 pub use acir_field::FieldElement;
 pub use brillig_vm;
 pub use circuit::black_box_functions::BlackBoxFunc;
+pub use smallvec;
+
+// Only used by the benchmarks.
+#[cfg(test)]
+use criterion as _;
@@ -1,11 +1,22 @@
 use crate::native_types::Witness;
 use acir_field::FieldElement;
 use serde::{Deserialize, Serialize};
+use smallvec::{smallvec, SmallVec};
 use std::cmp::Ordering;
 
 mod operators;
 mod ordering;
 
+/// Multiplication terms of an [`Expression`].
+///
+/// Arithmetic opcodes rarely hold more than a single multiplication term so this is stored inline.
+pub type MulTerms = SmallVec<[(FieldElement, Witness, Witness); 1]>;
+
+/// Linear terms of an [`Expression`].
+///
+/// Enough terms are stored inline to hold an opcode of the usual PLONK widths without allocating.
+pub type LinearCombinations = SmallVec<[(FieldElement, Witness); 3]>;
+
 // In the addition polynomial
 // We can have arbitrary fan-in/out, so we need more than wL,wR and wO
 // When looking at the arithmetic gate for the quotient polynomial in standard plonk
@@ -19,9 +30,9 @@ pub struct Expression {
     // We collect all of the multiplication terms in the arithmetic gate
     // A multiplication term if of the form q_M * wL * wR
     // Hence this vector represents the following sum: q_M1 * wL1 * wR1 + q_M2 * wL2 * wR2 + .. +
-    pub mul_terms: Vec<(FieldElement, Witness, Witness)>,
+    pub mul_terms: MulTerms,
 
-    pub linear_combinations: Vec<(FieldElement, Witness)>,
+    pub linear_combinations: LinearCombinations,
     // TODO: rename q_c to `constant` moreover q_X is not clear to those who
     // TODO are not familiar with PLONK
     pub q_c: FieldElement,
@@ -30,8 +41,8 @@ pub struct Expression {
 impl Default for Expression {
     fn default() -> Expression {
         Expression {
-            mul_terms: Vec::new(),
-            linear_combinations: Vec::new(),
+            mul_terms: SmallVec::new(),
+            linear_combinations: SmallVec::new(),
             q_c: FieldElement::zero(),
         }
     }
@@ -42,7 +53,7 @@ impl std::fmt::Display for Expression {
         if let Some(witness) = self.to_witness() {
             write!(f, "x{}", witness.witness_index())
         } else {
-            write!(f, "%{:?}%", crate::circuit::opcodes::Opcode::Arithmetic(self.clone()))
+            write!(f, "%{:?}%", crate::circuit::opcodes::Opcode::AssertZero(self.clone()))
         }
     }
 }
@@ -99,6 +110,7 @@ impl Expression {
     ///
     /// - `mul_term` in an expression contains degree-2 terms
     /// - `linear_combinations` contains degree-1 terms
+    ///
     /// Hence, it is sufficient to check that there are no `mul_terms`
     ///
     /// Examples:
@@ -112,11 +124,11 @@ impl Expression {
     /// Returns `true` if the expression can be seen as a degree-1 univariate polynomial
     ///
     /// - `mul_terms` in an expression can be univariate, however unless the coefficient
-    /// is zero, it is always degree-2.
+    ///   is zero, it is always degree-2.
     /// - `linear_combinations` contains the sum of degree-1 terms, these terms do not
-    /// need to contain the same variable and so it can be multivariate. However, we
-    /// have thus far only checked if `linear_combinations` contains one term, so this
-    /// method will return false, if the `Expression` has not been simplified.
+    ///   need to contain the same variable and so it can be multivariate. However, we
+    ///   have thus far only checked if `linear_combinations` contains one term, so this
+    ///   method will return false, if the `Expression` has not been simplified.
     ///
     /// Hence, we check in the simplest case if an expression is a degree-1 univariate,
     /// by checking if it contains no `mul_terms` and it contains one `linear_combination` term.
@@ -175,7 +187,7 @@ impl Expression {
     /// XXX: We can probably make this more efficient by sorting on each phase. We only care if it is deterministic
     pub fn sort(&mut self) {
         self.mul_terms.sort_by(|a, b| a.1.cmp(&b.1).then(a.2.cmp(&b.2)));
-        self.linear_combinations.sort_by(|a, b| a.1.cmp(&b.1));
+        self.linear_combinations.sort_by_key(|a| a.1);
     }
 
     /// Checks if this polynomial can fit into one arithmetic identity
@@ -243,10 +255,10 @@ impl Expression {
             return self.clone() + (k * b.q_c);
         }
 
-        let mut mul_terms: Vec<(FieldElement, Witness, Witness)> =
-            Vec::with_capacity(self.mul_terms.len() + b.mul_terms.len());
-        let mut linear_combinations: Vec<(FieldElement, Witness)> =
-            Vec::with_capacity(self.linear_combinations.len() + b.linear_combinations.len());
+        let mut mul_terms = MulTerms::with_capacity(self.mul_terms.len() + b.mul_terms.len());
+        let mut linear_combinations = LinearCombinations::with_capacity(
+            self.linear_combinations.len() + b.linear_combinations.len(),
+        );
         let q_c = self.q_c + k * b.q_c;
 
         //linear combinations
@@ -344,7 +356,11 @@ impl Expression {
 
 impl From<FieldElement> for Expression {
     fn from(constant: FieldElement) -> Expression {
-        Expression { q_c: constant, linear_combinations: Vec::new(), mul_terms: Vec::new() }
+        Expression {
+            q_c: constant,
+            linear_combinations: SmallVec::new(),
+            mul_terms: SmallVec::new(),
+        }
     }
 }
 
@@ -357,8 +373,8 @@ impl From<Witness> for Expression {
     fn from(wit: Witness) -> Expression {
         Expression {
             q_c: FieldElement::zero(),
-            linear_combinations: vec![(FieldElement::one(), wit)],
-            mul_terms: Vec::new(),
+            linear_combinations: smallvec![(FieldElement::one(), wit)],
+            mul_terms: SmallVec::new(),
         }
     }
 }
@@ -366,19 +382,19 @@ impl From<Witness> for Expression {
 #[test]
 fn add_mul_smoketest() {
     let a = Expression {
-        mul_terms: vec![(FieldElement::from(2u128), Witness(1), Witness(2))],
+        mul_terms: smallvec![(FieldElement::from(2u128), Witness(1), Witness(2))],
         ..Default::default()
     };
 
     let k = FieldElement::from(10u128);
 
     let b = Expression {
-        mul_terms: vec![
+        mul_terms: smallvec![
             (FieldElement::from(3u128), Witness(0), Witness(2)),
             (FieldElement::from(3u128), Witness(1), Witness(2)),
             (FieldElement::from(4u128), Witness(4), Witness(5)),
         ],
-        linear_combinations: vec![(FieldElement::from(4u128), Witness(4))],
+        linear_combinations: smallvec![(FieldElement::from(4u128), Witness(4))],
         q_c: FieldElement::one(),
     };
 
@@ -386,12 +402,12 @@ fn add_mul_smoketest() {
     assert_eq!(
         result,
         Expression {
-            mul_terms: vec![
+            mul_terms: smallvec![
                 (FieldElement::from(30u128), Witness(0), Witness(2)),
                 (FieldElement::from(32u128), Witness(1), Witness(2)),
                 (FieldElement::from(40u128), Witness(4), Witness(5)),
             ],
-            linear_combinations: vec![(FieldElement::from(40u128), Witness(4))],
+            linear_combinations: smallvec![(FieldElement::from(40u128), Witness(4))],
             q_c: FieldElement::from(10u128)
         }
     )
@@ -5,7 +5,7 @@ use std::{
     ops::{Add, Mul, Neg, Sub},
 };
 
-use super::Expression;
+use super::{Expression, LinearCombinations, MulTerms};
 
 // Negation
 
@@ -14,10 +14,10 @@ impl Neg for &Expression {
     fn neg(self) -> Self::Output {
         // XXX(med) : Implement an efficient way to do this
 
-        let mul_terms: Vec<_> =
+        let mul_terms: MulTerms =
             self.mul_terms.iter().map(|(q_m, w_l, w_r)| (-*q_m, *w_l, *w_r)).collect();
 
-        let linear_combinations: Vec<_> =
+        let linear_combinations: LinearCombinations =
             self.linear_combinations.iter().map(|(q_k, w_k)| (-*q_k, *w_k)).collect();
         let q_c = -self.q_c;
 
@@ -59,7 +59,7 @@ impl Sub<Expression> for FieldElement {
     type Output = Expression;
     #[inline]
     fn sub(self, rhs: Expression) -> Self::Output {
-        rhs - self
+        -&rhs + self
     }
 }
 
@@ -67,11 +67,11 @@ impl Mul<FieldElement> for &Expression {
     type Output = Expression;
     fn mul(self, rhs: FieldElement) -> Self::Output {
         // Scale the mul terms
-        let mul_terms: Vec<_> =
+        let mul_terms: MulTerms =
             self.mul_terms.iter().map(|(q_m, w_l, w_r)| (*q_m * rhs, *w_l, *w_r)).collect();
 
         // Scale the linear combinations terms
-        let lin_combinations: Vec<_> =
+        let lin_combinations: LinearCombinations =
             self.linear_combinations.iter().map(|(q_l, w_l)| (*q_l * rhs, *w_l)).collect();
 
         // Scale the constant
@@ -231,23 +231,25 @@ fn single_mul(w: Witness, b: &Expression) -> Expression {
 
 #[test]
 fn add_smoketest() {
+    use smallvec::smallvec;
+
     let a = Expression {
-        mul_terms: vec![],
-        linear_combinations: vec![(FieldElement::from(2u128), Witness(2))],
+        mul_terms: smallvec![],
+        linear_combinations: smallvec![(FieldElement::from(2u128), Witness(2))],
         q_c: FieldElement::from(2u128),
     };
 
     let b = Expression {
-        mul_terms: vec![],
-        linear_combinations: vec![(FieldElement::from(4u128), Witness(4))],
+        mul_terms: smallvec![],
+        linear_combinations: smallvec![(FieldElement::from(4u128), Witness(4))],
         q_c: FieldElement::one(),
     };
 
     assert_eq!(
         &a + &b,
         Expression {
-            mul_terms: vec![],
-            linear_combinations: vec![
+            mul_terms: smallvec![],
+            linear_combinations: smallvec![
                 (FieldElement::from(2u128), Witness(2)),
                 (FieldElement::from(4u128), Witness(4))
             ],
@@ -261,23 +263,25 @@ fn add_smoketest() {
 
 #[test]
 fn mul_smoketest() {
+    use smallvec::smallvec;
+
     let a = Expression {
-        mul_terms: vec![],
-        linear_combinations: vec![(FieldElement::from(2u128), Witness(2))],
+        mul_terms: smallvec![],
+        linear_combinations: smallvec![(FieldElement::from(2u128), Witness(2))],
         q_c: FieldElement::from(2u128),
     };
 
     let b = Expression {
-        mul_terms: vec![],
-        linear_combinations: vec![(FieldElement::from(4u128), Witness(4))],
+        mul_terms: smallvec![],
+        linear_combinations: smallvec![(FieldElement::from(4u128), Witness(4))],
         q_c: FieldElement::one(),
     };
 
     assert_eq!(
         (&a * &b).unwrap(),
         Expression {
-            mul_terms: vec![(FieldElement::from(8u128), Witness(2), Witness(4)),],
-            linear_combinations: vec![
+            mul_terms: smallvec![(FieldElement::from(8u128), Witness(2), Witness(4)),],
+            linear_combinations: smallvec![
                 (FieldElement::from(2u128), Witness(2)),
                 (FieldElement::from(8u128), Witness(4))
             ],
@@ -288,3 +292,25 @@ fn mul_smoketest() {
     // Enforce commutativity
     assert_eq!(&a * &b, &b * &a);
 }
+
+#[test]
+fn field_element_sub_smoketest() {
+    use smallvec::smallvec;
+
+    let a = Expression {
+        mul_terms: smallvec![(FieldElement::from(3u128), Witness(1), Witness(2))],
+        linear_combinations: smallvec![(FieldElement::from(2u128), Witness(2))],
+        q_c: FieldElement::from(2u128),
+    };
+
+    // `c - a` negates every term of `a` and adds `c`, rather than subtracting `c` from `a`.
+    assert_eq!(
+        FieldElement::from(5u128) - a.clone(),
+        Expression {
+            mul_terms: smallvec![(-FieldElement::from(3u128), Witness(1), Witness(2))],
+            linear_combinations: smallvec![(-FieldElement::from(2u128), Witness(2))],
+            q_c: FieldElement::from(3u128)
+        }
+    );
+    assert_eq!(FieldElement::from(5u128) - a.clone(), -&(a - FieldElement::from(5u128)));
+}
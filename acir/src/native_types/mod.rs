@@ -2,7 +2,7 @@ mod expression;
 mod witness;
 mod witness_map;
 
-pub use expression::Expression;
+pub use expression::{Expression, LinearCombinations, MulTerms};
 pub use witness::Witness;
 pub use witness_map::WitnessMap;
 pub use witness_map::WitnessMapError;
@@ -1,7 +1,7 @@
 use std::{
     collections::{btree_map, BTreeMap},
     io::Read,
-    ops::Index,
+    ops::{Index, Range},
 };
 
 use acir_field::FieldElement;
@@ -9,7 +9,7 @@ use flate2::{
     bufread::{DeflateDecoder, DeflateEncoder},
     Compression,
 };
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use thiserror::Error;
 
 use crate::native_types::Witness;
@@ -30,25 +30,195 @@ enum SerializationError {
 #[error(transparent)]
 pub struct WitnessMapError(#[from] SerializationError);
 
+/// Number of entries above which a [`WitnessMap`] considers switching to dense storage.
+const DENSE_THRESHOLD: usize = 1 << 12;
+
 /// A map from the witnesses in a constraint system to the field element values
-#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Default, Serialize, Deserialize)]
-pub struct WitnessMap(BTreeMap<Witness, FieldElement>);
+///
+/// Small or sparsely populated maps are stored in a [`BTreeMap`]. Once a map holds a few thousand
+/// entries covering at least half of the witness indices below the largest one, it switches to
+/// a vector indexed by witness, which is much cheaper to read and write.
+/// Iteration is always in increasing witness order, whichever storage is in use.
+#[derive(Clone, Debug)]
+pub struct WitnessMap(WitnessStorage);
+
+#[derive(Clone, Debug)]
+enum WitnessStorage {
+    Sparse(BTreeMap<Witness, FieldElement>),
+    Dense { values: Vec<Option<FieldElement>>, len: usize },
+}
 
 impl WitnessMap {
     pub fn new() -> Self {
-        Self(BTreeMap::new())
+        Self(WitnessStorage::Sparse(BTreeMap::new()))
     }
     pub fn get(&self, witness: &Witness) -> Option<&FieldElement> {
-        self.0.get(witness)
+        self.get_index(witness.0)
     }
     pub fn get_index(&self, index: u32) -> Option<&FieldElement> {
-        self.0.get(&index.into())
+        match &self.0 {
+            WitnessStorage::Sparse(map) => map.get(&index.into()),
+            WitnessStorage::Dense { values, .. } => {
+                values.get(index as usize).and_then(Option::as_ref)
+            }
+        }
     }
     pub fn contains_key(&self, key: &Witness) -> bool {
-        self.0.contains_key(key)
+        self.get(key).is_some()
     }
     pub fn insert(&mut self, key: Witness, value: FieldElement) -> Option<FieldElement> {
-        self.0.insert(key, value)
+        if let WitnessStorage::Dense { values, len } = &self.0 {
+            // Avoid growing the vector for an outlying witness which would leave it mostly empty.
+            if key.as_usize() >= values.len() && key.as_usize() >= 4 * (len + 1) {
+                self.make_sparse();
+            }
+        }
+        match &mut self.0 {
+            WitnessStorage::Sparse(map) => {
+                let old_value = map.insert(key, value);
+                if old_value.is_none() {
+                    self.try_make_dense();
+                }
+                old_value
+            }
+            WitnessStorage::Dense { values, len } => {
+                let index = key.as_usize();
+                if index >= values.len() {
+                    values.resize(index + 1, None);
+                }
+                let old_value = values[index].replace(value);
+                if old_value.is_none() {
+                    *len += 1;
+                }
+                old_value
+            }
+        }
+    }
+    /// Returns the number of witnesses which have been assigned a value.
+    pub fn len(&self) -> usize {
+        match &self.0 {
+            WitnessStorage::Sparse(map) => map.len(),
+            WitnessStorage::Dense { len, .. } => *len,
+        }
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    /// Returns an iterator over the assigned witnesses in increasing order.
+    pub fn iter(&self) -> Iter<'_> {
+        match &self.0 {
+            WitnessStorage::Sparse(map) => Iter::Sparse(map.iter()),
+            WitnessStorage::Dense { values, .. } => Iter::Dense(values.iter().enumerate()),
+        }
+    }
+    /// Returns an iterator over the assigned witnesses within `range` in increasing order.
+    pub fn iter_range(
+        &self,
+        range: Range<Witness>,
+    ) -> impl Iterator<Item = (Witness, &FieldElement)> + '_ {
+        // An empty range would make `BTreeMap::range` panic if its start were past its end.
+        let range = range.start..range.end.max(range.start);
+        let (sparse, dense) = match &self.0 {
+            WitnessStorage::Sparse(map) => {
+                (Some(map.range(range).map(|(witness, value)| (*witness, value))), None)
+            }
+            WitnessStorage::Dense { values, .. } => {
+                let start = range.start.as_usize().min(values.len());
+                let end = range.end.as_usize().clamp(start, values.len());
+                let entries =
+                    values[start..end].iter().enumerate().filter_map(move |(i, value)| {
+                        value.as_ref().map(|value| (Witness((start + i) as u32), value))
+                    });
+                (None, Some(entries))
+            }
+        };
+        sparse.into_iter().flatten().chain(dense.into_iter().flatten())
+    }
+    /// Inserts every entry of `entries`, overwriting the values of witnesses which were already assigned.
+    pub fn extend_from_iter(&mut self, entries: impl IntoIterator<Item = (Witness, FieldElement)>) {
+        for (witness, value) in entries {
+            self.insert(witness, value);
+        }
+    }
+    /// Removes the witnesses for which `keep` returns `false`.
+    pub fn retain(&mut self, mut keep: impl FnMut(Witness, &FieldElement) -> bool) {
+        match &mut self.0 {
+            WitnessStorage::Sparse(map) => map.retain(|witness, value| keep(*witness, value)),
+            WitnessStorage::Dense { values, len } => {
+                for (index, entry) in values.iter_mut().enumerate() {
+                    if matches!(entry, Some(value) if !keep(Witness(index as u32), value)) {
+                        *entry = None;
+                        *len -= 1;
+                    }
+                }
+            }
+        }
+    }
+    /// Returns the assigned witnesses along with their values, in increasing order.
+    pub fn into_sorted_vec(self) -> Vec<(Witness, FieldElement)> {
+        self.into_iter().collect()
+    }
+
+    /// Switches to dense storage if the map holds enough entries and covers most witness indices.
+    fn try_make_dense(&mut self) {
+        if let WitnessStorage::Sparse(map) = &mut self.0 {
+            let max_index = map.keys().next_back().map_or(0, Witness::as_usize);
+            if map.len() <= DENSE_THRESHOLD || max_index >= 2 * map.len() {
+                return;
+            }
+            let len = map.len();
+            let mut values = vec![None; max_index + 1];
+            for (witness, value) in std::mem::take(map) {
+                values[witness.as_usize()] = Some(value);
+            }
+            self.0 = WitnessStorage::Dense { values, len };
+        }
+    }
+
+    fn make_sparse(&mut self) {
+        if let WitnessStorage::Dense { values, .. } = &mut self.0 {
+            let map = std::mem::take(values)
+                .into_iter()
+                .enumerate()
+                .filter_map(|(index, value)| Some((Witness(index as u32), value?)))
+                .collect();
+            self.0 = WitnessStorage::Sparse(map);
+        }
+    }
+}
+
+impl Default for WitnessMap {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PartialEq for WitnessMap {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().eq(other.iter())
+    }
+}
+
+impl Eq for WitnessMap {}
+
+impl PartialOrd for WitnessMap {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for WitnessMap {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.iter().cmp(other.iter())
+    }
+}
+
+impl std::hash::Hash for WitnessMap {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        state.write_usize(self.len());
+        for entry in self.iter() {
+            entry.hash(state);
+        }
     }
 }
 
@@ -56,17 +226,43 @@ impl Index<&Witness> for WitnessMap {
     type Output = FieldElement;
 
     fn index(&self, index: &Witness) -> &Self::Output {
-        &self.0[index]
+        self.get(index).expect("witness should have been assigned a value")
     }
 }
 
-pub struct IntoIter(btree_map::IntoIter<Witness, FieldElement>);
+pub enum Iter<'a> {
+    Sparse(btree_map::Iter<'a, Witness, FieldElement>),
+    Dense(std::iter::Enumerate<std::slice::Iter<'a, Option<FieldElement>>>),
+}
+
+impl<'a> Iterator for Iter<'a> {
+    type Item = (Witness, &'a FieldElement);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            Iter::Sparse(iter) => iter.next().map(|(witness, value)| (*witness, value)),
+            Iter::Dense(iter) => iter.find_map(|(index, value)| {
+                value.as_ref().map(|value| (Witness(index as u32), value))
+            }),
+        }
+    }
+}
+
+pub enum IntoIter {
+    Sparse(btree_map::IntoIter<Witness, FieldElement>),
+    Dense(std::iter::Enumerate<std::vec::IntoIter<Option<FieldElement>>>),
+}
 
 impl Iterator for IntoIter {
     type Item = (Witness, FieldElement);
 
     fn next(&mut self) -> Option<Self::Item> {
-        self.0.next()
+        match self {
+            IntoIter::Sparse(iter) => iter.next(),
+            IntoIter::Dense(iter) => {
+                iter.find_map(|(index, value)| value.map(|value| (Witness(index as u32), value)))
+            }
+        }
     }
 }
 
@@ -75,13 +271,64 @@ impl IntoIterator for WitnessMap {
     type IntoIter = IntoIter;
 
     fn into_iter(self) -> Self::IntoIter {
-        IntoIter(self.0.into_iter())
+        match self.0 {
+            WitnessStorage::Sparse(map) => IntoIter::Sparse(map.into_iter()),
+            WitnessStorage::Dense { values, .. } => IntoIter::Dense(values.into_iter().enumerate()),
+        }
+    }
+}
+
+impl<'a> IntoIterator for &'a WitnessMap {
+    type Item = (Witness, &'a FieldElement);
+    type IntoIter = Iter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
     }
 }
 
 impl From<BTreeMap<Witness, FieldElement>> for WitnessMap {
     fn from(value: BTreeMap<Witness, FieldElement>) -> Self {
-        Self(value)
+        let mut witness_map = Self(WitnessStorage::Sparse(value));
+        witness_map.try_make_dense();
+        witness_map
+    }
+}
+
+impl FromIterator<(Witness, FieldElement)> for WitnessMap {
+    fn from_iter<I: IntoIterator<Item = (Witness, FieldElement)>>(iter: I) -> Self {
+        iter.into_iter().collect::<BTreeMap<_, _>>().into()
+    }
+}
+
+impl Extend<(Witness, FieldElement)> for WitnessMap {
+    fn extend<I: IntoIterator<Item = (Witness, FieldElement)>>(&mut self, iter: I) {
+        self.extend_from_iter(iter);
+    }
+}
+
+// The serialized form is that of a `BTreeMap<Witness, FieldElement>` regardless of the storage in use.
+impl Serialize for WitnessMap {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        struct Entries<'a>(&'a WitnessMap);
+
+        impl Serialize for Entries<'_> {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.collect_map(self.0.iter())
+            }
+        }
+
+        serializer.serialize_newtype_struct("WitnessMap", &Entries(self))
+    }
+}
+
+impl<'de> Deserialize<'de> for WitnessMap {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(rename = "WitnessMap")]
+        struct Entries(BTreeMap<Witness, FieldElement>);
+
+        Entries::deserialize(deserializer).map(|Entries(map)| map.into())
     }
 }
 
@@ -106,6 +353,104 @@ impl TryFrom<&[u8]> for WitnessMap {
         deflater.read_to_end(&mut buf_d).map_err(|err| WitnessMapError(err.into()))?;
         let witness_map =
             rmp_serde::from_slice(buf_d.as_slice()).map_err(|err| WitnessMapError(err.into()))?;
-        Ok(Self(witness_map))
+        Ok(witness_map)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use acir_field::FieldElement;
+
+    use super::{WitnessMap, WitnessStorage, DENSE_THRESHOLD};
+    use crate::native_types::Witness;
+
+    fn is_dense(witness_map: &WitnessMap) -> bool {
+        matches!(witness_map.0, WitnessStorage::Dense { .. })
+    }
+
+    #[test]
+    fn switches_to_dense_storage_above_threshold() {
+        let mut witness_map = WitnessMap::new();
+        for i in (1..=DENSE_THRESHOLD as u32 + 1).rev() {
+            assert!(!is_dense(&witness_map));
+            witness_map.insert(Witness(i), FieldElement::from(i as u128));
+        }
+        assert!(is_dense(&witness_map));
+        assert_eq!(witness_map.len(), DENSE_THRESHOLD + 1);
+        assert_eq!(witness_map.get(&Witness(7)), Some(&FieldElement::from(7u128)));
+        assert_eq!(witness_map.get(&Witness(0)), None);
+
+        // Iteration is in index order.
+        let witnesses: Vec<_> = witness_map.iter().map(|(witness, _)| witness.0).collect();
+        assert_eq!(witnesses, (1..=DENSE_THRESHOLD as u32 + 1).collect::<Vec<_>>());
+
+        // A far away witness makes dense storage wasteful.
+        witness_map.insert(Witness(u32::MAX / 2), FieldElement::one());
+        assert!(!is_dense(&witness_map));
+        assert_eq!(witness_map.len(), DENSE_THRESHOLD + 2);
+    }
+
+    #[test]
+    fn storage_does_not_affect_equality_or_serialization() {
+        let entries: BTreeMap<_, _> = (0..2 * DENSE_THRESHOLD as u32)
+            .map(|i| (Witness(i), FieldElement::from(i as u128)))
+            .collect();
+        let dense = WitnessMap::from(entries.clone());
+        let mut sparse = WitnessMap::new();
+        for (witness, value) in entries.clone() {
+            sparse.0 = match sparse.0 {
+                WitnessStorage::Sparse(mut map) => {
+                    map.insert(witness, value);
+                    WitnessStorage::Sparse(map)
+                }
+                storage => storage,
+            };
+        }
+        assert!(is_dense(&dense));
+        assert!(!is_dense(&sparse));
+        assert_eq!(dense, sparse);
+
+        let dense_bytes: Vec<u8> = dense.clone().try_into().unwrap();
+        let sparse_bytes: Vec<u8> = sparse.try_into().unwrap();
+        assert_eq!(dense_bytes, sparse_bytes);
+        assert_eq!(WitnessMap::try_from(dense_bytes.as_slice()).unwrap(), dense);
+        assert_eq!(dense.into_iter().collect::<BTreeMap<_, _>>(), entries);
+    }
+
+    #[test]
+    fn bulk_operations_agree_across_storages() {
+        let entries: Vec<_> = (0..2 * DENSE_THRESHOLD as u32)
+            .map(|i| (Witness(3 * i), FieldElement::from(i as u128)))
+            .collect();
+        let sparse: WitnessMap = entries.iter().copied().collect();
+        let mut dense = WitnessMap::new();
+        dense.extend_from_iter(
+            (0..6 * DENSE_THRESHOLD as u32).map(|i| (Witness(i), FieldElement::zero())),
+        );
+        dense.retain(|witness, _| witness.0 % 3 == 0);
+        dense.extend(entries.iter().copied());
+        assert!(!is_dense(&sparse));
+        assert!(is_dense(&dense));
+        assert_eq!(dense, sparse);
+        assert_eq!(dense.len(), 2 * DENSE_THRESHOLD);
+
+        for witness_map in [&sparse, &dense] {
+            let in_range: Vec<_> = witness_map.iter_range(Witness(3)..Witness(10)).collect();
+            let expected: Vec<_> =
+                [3u32, 6, 9].map(|i| (Witness(i), FieldElement::from(i as u128 / 3))).to_vec();
+            assert_eq!(
+                in_range,
+                expected.iter().map(|(witness, value)| (*witness, value)).collect::<Vec<_>>()
+            );
+            assert_eq!(witness_map.iter_range(Witness(9)..Witness(3)).count(), 0);
+            assert_eq!(witness_map.iter_range(Witness(u32::MAX - 1)..Witness(u32::MAX)).count(), 0);
+        }
+
+        let mut retained = sparse.clone();
+        retained.retain(|witness, _| witness.0 < 10);
+        assert_eq!(retained.len(), 4);
+        assert_eq!(dense.into_sorted_vec(), entries);
     }
 }
@@ -1,5 +1,5 @@
-use ark_ff::PrimeField;
 use ark_ff::Zero;
+use ark_ff::{BigInteger, PrimeField};
 use num_bigint::BigUint;
 use serde::{Deserialize, Serialize};
 
@@ -87,7 +87,9 @@ impl<F: PrimeField> std::hash::Hash for FieldElement<F> {
 
 impl<F: PrimeField> PartialEq for FieldElement<F> {
     fn eq(&self, other: &Self) -> bool {
-        self.to_be_bytes() == other.to_be_bytes()
+        // Field elements have a unique internal representation so can be compared directly
+        // rather than through their (allocated) byte encoding.
+        self.0 == other.0
     }
 }
 
@@ -135,11 +137,7 @@ impl<'de, T: ark_ff::PrimeField> Deserialize<'de> for FieldElement<T> {
 
 impl<F: PrimeField> From<u128> for FieldElement<F> {
     fn from(a: u128) -> FieldElement<F> {
-        let result = match F::from_str(&a.to_string()) {
-            Ok(result) => result,
-            Err(_) => panic!("Cannot convert u128 as a string to a field element"),
-        };
-        FieldElement(result)
+        FieldElement(F::from(a))
     }
 }
 
@@ -210,14 +208,10 @@ impl<F: PrimeField> FieldElement<F> {
         Some(FieldElement(fr))
     }
 
-    /// This is the number of bits required to represent this specific field element
+    /// Returns the number of bits required to represent this field element as an integer, that is the position of
+    /// its most significant set bit plus one. Zero requires zero bits.
     pub fn num_bits(&self) -> u32 {
-        let bits = self.bits();
-        // Iterate the number of bits and pop off all leading zeroes
-        let iter = bits.iter().skip_while(|x| !(**x));
-        // Note: count will panic if it goes over usize::MAX.
-        // This may not be suitable for devices whose usize < u16
-        iter.count() as u32
+        self.0.into_bigint().num_bits()
     }
 
     pub fn fits_in_u128(&self) -> bool {
@@ -230,7 +224,29 @@ impl<F: PrimeField> FieldElement<F> {
     }
 
     pub fn try_into_u128(self) -> Option<u128> {
-        self.fits_in_u128().then(|| self.to_u128())
+        // Reads the limbs of the integer directly rather than through its byte encoding.
+        let integer = self.0.into_bigint();
+        let limbs: &[u64] = integer.as_ref();
+        limbs[2..]
+            .iter()
+            .all(Zero::is_zero)
+            .then(|| u128::from(limbs[0]) | u128::from(limbs[1]) << 64)
+    }
+
+    /// Returns the integer whose value in the field is `self`, should it fit in an `i128`,
+    /// negative integers being those whose opposite fits.
+    pub fn try_into_i128(self) -> Option<i128> {
+        let magnitude = |integer: &F::BigInt| {
+            let limbs: &[u64] = integer.as_ref();
+            (limbs[2..].iter().all(Zero::is_zero) && limbs[1] >> 63 == 0)
+                .then(|| i128::from(limbs[0]) | i128::from(limbs[1]) << 64)
+        };
+        let integer = self.0.into_bigint();
+        magnitude(&integer).or_else(|| {
+            let mut opposite = F::MODULUS;
+            opposite.sub_with_borrow(&integer);
+            magnitude(&opposite).map(|magnitude| -magnitude)
+        })
     }
 
     pub fn try_to_u64(&self) -> Option<u64> {
@@ -248,40 +264,103 @@ impl<F: PrimeField> FieldElement<F> {
         self.0.inverse_in_place().map(|f| FieldElement(*f))
     }
 
+    /// Returns a square root of this field element, or `None` if it is not a quadratic residue.
+    ///
+    /// Which of the two roots is returned is unspecified.
+    pub fn sqrt(&self) -> Option<Self> {
+        self.0.sqrt().map(FieldElement)
+    }
+
     // XXX: This method is used while this field element
     // implementation is not generic.
     pub fn into_repr(self) -> F {
         self.0
     }
 
+    /// Returns the big endian hex encoding of this field element, without a `0x` prefix and padded with zeroes
+    /// to two digits for each of the [`max_num_bytes`][FieldElement::max_num_bytes] bytes.
     pub fn to_hex(self) -> String {
-        let mut bytes = Vec::new();
-        self.0.serialize_uncompressed(&mut bytes).unwrap();
-        bytes.reverse();
-        hex::encode(bytes)
+        hex::encode(self.to_be_bytes())
     }
+
+    /// Parses a field element from its big endian hex encoding, with or without a `0x` prefix.
+    ///
+    /// Any number of digits is accepted, an odd number being padded with a leading zero. Returns `None` should the
+    /// string be empty or not hexadecimal, or should its value not be less than the [modulus][FieldElement::modulus].
     pub fn from_hex(hex_str: &str) -> Option<FieldElement<F>> {
         let value = hex_str.strip_prefix("0x").unwrap_or(hex_str);
-        let hex_as_bytes = hex::decode(value).ok()?;
-        Some(FieldElement::from_be_bytes_reduce(&hex_as_bytes))
+        if value.is_empty() {
+            return None;
+        }
+        let hex_as_bytes = if value.len() % 2 == 1 {
+            hex::decode(format!("0{value}"))
+        } else {
+            hex::decode(value)
+        }
+        .ok()?;
+        let integer = F::BigInt::try_from(BigUint::from_bytes_be(&hex_as_bytes)).ok()?;
+        F::from_bigint(integer).map(FieldElement)
     }
 
+    /// Returns the big endian encoding of this field element over [`max_num_bytes`][FieldElement::max_num_bytes] bytes.
     pub fn to_be_bytes(self) -> Vec<u8> {
-        // to_be_bytes! uses little endian which is why we reverse the output
-        // TODO: Add a little endian equivalent, so the caller can use whichever one
-        // TODO they desire
-        let mut bytes = Vec::new();
-        self.0.serialize_uncompressed(&mut bytes).unwrap();
+        let mut bytes = self.to_le_bytes();
         bytes.reverse();
         bytes
     }
 
-    /// Converts bytes into a FieldElement and applies a
-    /// reduction if needed.
+    /// Returns the little endian encoding of this field element over [`max_num_bytes`][FieldElement::max_num_bytes] bytes.
+    pub fn to_le_bytes(self) -> Vec<u8> {
+        let mut bytes = self.0.into_bigint().to_bytes_le();
+        bytes.resize(Self::max_num_bytes() as usize, 0);
+        bytes
+    }
+
+    /// Returns the big endian encoding of this field element over exactly `num_bytes` bytes, padded with zeroes,
+    /// or `None` if its value does not fit within them.
+    pub fn to_be_bytes_fixed(self, num_bytes: usize) -> Option<Vec<u8>> {
+        let mut bytes = self.to_le_bytes_fixed(num_bytes)?;
+        bytes.reverse();
+        Some(bytes)
+    }
+
+    /// Returns the little endian encoding of this field element over exactly `num_bytes` bytes, padded with zeroes,
+    /// or `None` if its value does not fit within them.
+    pub fn to_le_bytes_fixed(self, num_bytes: usize) -> Option<Vec<u8>> {
+        if self.num_bits() as usize > 8 * num_bytes {
+            return None;
+        }
+        let mut bytes = self.to_le_bytes();
+        bytes.resize(num_bytes, 0);
+        Some(bytes)
+    }
+
+    /// Converts big endian bytes into a FieldElement, reducing their value modulo the [modulus][FieldElement::modulus].
     pub fn from_be_bytes_reduce(bytes: &[u8]) -> FieldElement<F> {
         FieldElement(F::from_be_bytes_mod_order(bytes))
     }
 
+    /// Converts little endian bytes into a FieldElement, reducing their value modulo the [modulus][FieldElement::modulus].
+    pub fn from_le_bytes_reduce(bytes: &[u8]) -> FieldElement<F> {
+        FieldElement(F::from_le_bytes_mod_order(bytes))
+    }
+
+    /// Returns the little endian bit decomposition of this field element over exactly `num_bits` bits, padded with
+    /// `false`, or `None` if its value does not fit within them.
+    pub fn to_le_bits(self, num_bits: u32) -> Option<Vec<bool>> {
+        if self.num_bits() > num_bits {
+            return None;
+        }
+        let integer = self.0.into_bigint();
+        Some(
+            (0..num_bits)
+                .map(|bit| bit < F::MODULUS_BIT_SIZE && integer.get_bit(bit as usize))
+                .collect(),
+        )
+    }
+
+    /// Returns the big endian bit decomposition of this field element over the `8 * max_num_bytes` bits of
+    /// [`to_be_bytes`][FieldElement::to_be_bytes], leading zeroes included.
     pub fn bits(&self) -> Vec<bool> {
         let bytes = self.to_be_bytes();
         let mut bits = Vec::with_capacity(bytes.len() * 8);
@@ -300,20 +379,12 @@ impl<F: PrimeField> FieldElement<F> {
         bits
     }
 
-    /// Returns the closest number of bytes to the bits specified
-    /// This method truncates
+    /// Returns the `ceil(num_bits / 8)` least significant bytes of this field element in little endian order,
+    /// truncating any bytes above them but at most [`max_num_bytes`][FieldElement::max_num_bytes] bytes.
     pub fn fetch_nearest_bytes(&self, num_bits: usize) -> Vec<u8> {
-        fn nearest_bytes(num_bits: usize) -> usize {
-            ((num_bits + 7) / 8) * 8
-        }
-
-        let num_bytes = nearest_bytes(num_bits);
-        let num_elements = num_bytes / 8;
-
-        let mut bytes = self.to_be_bytes();
-        bytes.reverse(); // put it in big endian format. XXX(next refactor): we should be explicit about endianness.
-
-        bytes[0..num_elements].to_vec()
+        let mut bytes = self.to_le_bytes();
+        bytes.truncate((num_bits + 7) / 8);
+        bytes
     }
 
     // mask_to methods will not remove any bytes from the field
@@ -341,7 +412,7 @@ impl<F: PrimeField> FieldElement<F> {
 
         let and_byte_arr: Vec<_> = lhs_bytes
             .into_iter()
-            .zip(rhs_bytes.into_iter())
+            .zip(rhs_bytes)
             .map(|(lhs, rhs)| if is_xor { lhs ^ rhs } else { lhs & rhs })
             .collect();
 
@@ -405,44 +476,6 @@ impl<F: PrimeField> SubAssign for FieldElement<F> {
     }
 }
 
-#[cfg(test)]
-mod tests {
-    #[test]
-    fn and() {
-        let max = 10_000u32;
-
-        let num_bits = (std::mem::size_of::<u32>() * 8) as u32 - max.leading_zeros();
-
-        for x in 0..max {
-            let x = crate::generic_ark::FieldElement::<ark_bn254::Fr>::from(x as i128);
-            let res = x.and(&x, num_bits);
-            assert_eq!(res.to_be_bytes(), x.to_be_bytes());
-        }
-    }
-
-    #[test]
-    fn serialize_fixed_test_vectors() {
-        // Serialized field elements from of 0, -1, -2, -3
-        let hex_strings = vec![
-            "0000000000000000000000000000000000000000000000000000000000000000",
-            "30644e72e131a029b85045b68181585d2833e84879b9709143e1f593f0000000",
-            "30644e72e131a029b85045b68181585d2833e84879b9709143e1f593efffffff",
-            "30644e72e131a029b85045b68181585d2833e84879b9709143e1f593effffffe",
-        ];
-
-        for (i, string) in hex_strings.into_iter().enumerate() {
-            let minus_i_field_element =
-                -crate::generic_ark::FieldElement::<ark_bn254::Fr>::from(i as i128);
-            assert_eq!(minus_i_field_element.to_hex(), string)
-        }
-    }
-    #[test]
-    fn max_num_bits_smoke() {
-        let max_num_bits_bn254 = crate::generic_ark::FieldElement::<ark_bn254::Fr>::max_num_bits();
-        assert_eq!(max_num_bits_bn254, 254)
-    }
-}
-
 fn mask_vector_le(bytes: &mut [u8], num_bits: usize) {
     // reverse to big endian format
     bytes.reverse();
@@ -500,3 +533,127 @@ fn superscript(n: u64) -> String {
         panic!("{}", n.to_string() + " can't be converted to superscript.");
     }
 }
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn and() {
+        let max = 10_000u32;
+
+        let num_bits = (std::mem::size_of::<u32>() * 8) as u32 - max.leading_zeros();
+
+        for x in 0..max {
+            let x = crate::generic_ark::FieldElement::<ark_bn254::Fr>::from(x as i128);
+            let res = x.and(&x, num_bits);
+            assert_eq!(res.to_be_bytes(), x.to_be_bytes());
+        }
+    }
+
+    #[test]
+    fn serialize_fixed_test_vectors() {
+        // Serialized field elements from of 0, -1, -2, -3
+        let hex_strings = vec![
+            "0000000000000000000000000000000000000000000000000000000000000000",
+            "30644e72e131a029b85045b68181585d2833e84879b9709143e1f593f0000000",
+            "30644e72e131a029b85045b68181585d2833e84879b9709143e1f593efffffff",
+            "30644e72e131a029b85045b68181585d2833e84879b9709143e1f593effffffe",
+        ];
+
+        for (i, string) in hex_strings.into_iter().enumerate() {
+            let minus_i_field_element =
+                -crate::generic_ark::FieldElement::<ark_bn254::Fr>::from(i as i128);
+            assert_eq!(minus_i_field_element.to_hex(), string)
+        }
+    }
+    #[test]
+    fn max_num_bits_smoke() {
+        let max_num_bits_bn254 = crate::generic_ark::FieldElement::<ark_bn254::Fr>::max_num_bits();
+        assert_eq!(max_num_bits_bn254, 254)
+    }
+
+    #[test]
+    fn sqrt_of_quadratic_residues() {
+        type FieldElement = crate::generic_ark::FieldElement<ark_bn254::Fr>;
+
+        for i in 0..100_i128 {
+            let square = FieldElement::from(i) * FieldElement::from(i);
+            let root = square.sqrt().expect("squares have a square root");
+            assert_eq!(root * root, square);
+        }
+        // 5 is a quadratic non-residue modulo the BN254 scalar field.
+        assert_eq!(FieldElement::from(5_i128).sqrt(), None);
+    }
+
+    #[test]
+    fn u128_round_trip() {
+        type FieldElement = crate::generic_ark::FieldElement<ark_bn254::Fr>;
+
+        for value in [0, 1, u64::MAX as u128, u64::MAX as u128 + 1, u128::MAX] {
+            let element = FieldElement::from(value);
+            assert_eq!(element, FieldElement::from_hex(&format!("{value:032x}")).unwrap());
+            assert_eq!(element.try_into_u128(), Some(value));
+        }
+        assert_eq!((FieldElement::from(u128::MAX) + FieldElement::one()).try_into_u128(), None);
+        assert_eq!((-FieldElement::one()).try_into_u128(), None);
+
+        for value in [0, 1, -1, i64::MIN as i128, i128::MAX, -i128::MAX] {
+            assert_eq!(FieldElement::from(value).try_into_i128(), Some(value));
+        }
+        assert_eq!(FieldElement::from(i128::MAX as u128 + 1).try_into_i128(), None);
+        assert_eq!((-FieldElement::from(i128::MAX as u128 + 1)).try_into_i128(), None);
+    }
+
+    #[test]
+    fn hex_encoding() {
+        type FieldElement = crate::generic_ark::FieldElement<ark_bn254::Fr>;
+
+        let value = FieldElement::from(0x1234_u128);
+        assert_eq!(value.to_hex(), format!("{:064x}", 0x1234));
+        for hex in [
+            "1234",
+            "0x1234",
+            "0x01234",
+            "0x0000000000000000000000000000000000000000000000000000000000000001234",
+        ] {
+            assert_eq!(FieldElement::from_hex(hex), Some(value), "{hex}");
+        }
+        assert_eq!(FieldElement::from_hex("0x5"), Some(FieldElement::from(5_u128)));
+
+        let max = -FieldElement::one();
+        assert_eq!(FieldElement::from_hex(&max.to_hex()), Some(max));
+        let modulus = FieldElement::modulus().to_str_radix(16);
+        assert_eq!(FieldElement::from_hex(&modulus), None);
+        assert_eq!(FieldElement::from_hex(""), None);
+        assert_eq!(FieldElement::from_hex("0x"), None);
+        assert_eq!(FieldElement::from_hex("0xg"), None);
+    }
+
+    #[test]
+    fn byte_and_bit_conversions() {
+        type FieldElement = crate::generic_ark::FieldElement<ark_bn254::Fr>;
+
+        let value = FieldElement::from(0x0102_u128);
+        let mut le_bytes = vec![0; 32];
+        le_bytes[..2].copy_from_slice(&[2, 1]);
+        assert_eq!(value.to_le_bytes(), le_bytes);
+        assert_eq!(value.to_be_bytes(), le_bytes.iter().rev().copied().collect::<Vec<_>>());
+        assert_eq!(FieldElement::from_le_bytes_reduce(&[2, 1]), value);
+        assert_eq!(FieldElement::from_be_bytes_reduce(&[1, 2]), value);
+
+        assert_eq!(value.to_le_bytes_fixed(3), Some(vec![2, 1, 0]));
+        assert_eq!(value.to_be_bytes_fixed(3), Some(vec![0, 1, 2]));
+        assert_eq!(value.to_le_bytes_fixed(1), None);
+        assert_eq!(value.to_be_bytes_fixed(40).map(|bytes| bytes.len()), Some(40));
+        assert_eq!(value.fetch_nearest_bytes(9), vec![2, 1]);
+        assert_eq!(value.fetch_nearest_bytes(512).len(), 32);
+
+        assert_eq!(value.num_bits(), 9);
+        assert_eq!(FieldElement::zero().num_bits(), 0);
+        assert_eq!((-FieldElement::one()).num_bits(), FieldElement::max_num_bits());
+        let bits = value.to_le_bits(10).unwrap();
+        assert_eq!(bits, [false, true, false, false, false, false, false, false, true, false]);
+        assert_eq!(value.to_le_bits(8), None);
+        assert_eq!(value.bits().len(), 256);
+        assert_eq!(value.bits()[256 - 10..], bits.iter().rev().copied().collect::<Vec<_>>()[..]);
+    }
+}
@@ -0,0 +1,123 @@
+//! Reports the number of heap allocations made when cloning expressions and solving circuits.
+//!
+//! Run with `cargo bench -p acvm --bench allocations`.
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use acir::{
+    native_types::{Expression, Witness, WitnessMap},
+    FieldElement,
+};
+use acvm::{
+    pwg::{OpcodeResolutionError, ACVM},
+    PartialWitnessGenerator,
+};
+
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+fn count_allocations<T>(f: impl FnOnce() -> T) -> (T, usize) {
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    let result = f();
+    (result, ALLOCATIONS.load(Ordering::Relaxed) - before)
+}
+
+struct StubbedPwg;
+
+impl PartialWitnessGenerator for StubbedPwg {
+    fn schnorr_verify(
+        &self,
+        _public_key_x: &FieldElement,
+        _public_key_y: &FieldElement,
+        _signature_s: &FieldElement,
+        _signature_e: &FieldElement,
+        _message: &[u8],
+    ) -> Result<bool, OpcodeResolutionError> {
+        panic!("Path not trodden by this test")
+    }
+
+    fn pedersen(
+        &self,
+        _inputs: &[FieldElement],
+        _domain_separator: u32,
+    ) -> Result<(FieldElement, FieldElement), OpcodeResolutionError> {
+        panic!("Path not trodden by this test")
+    }
+
+    fn fixed_base_scalar_mul(
+        &self,
+        _input: &FieldElement,
+    ) -> Result<(FieldElement, FieldElement), OpcodeResolutionError> {
+        panic!("Path not trodden by this test")
+    }
+}
+
+/// Returns an expression with a single multiplication term and `num_linear_terms` linear terms.
+fn expression(num_linear_terms: u32) -> Expression {
+    let mut expr = Expression::default();
+    expr.push_multiplication_term(FieldElement::one(), Witness(1), Witness(2));
+    for i in 0..num_linear_terms {
+        expr.push_addition_term(FieldElement::from(i as u128 + 1), Witness(i + 3));
+    }
+    expr
+}
+
+/// Returns opcodes constraining `w_{i+1} = w_i * w_i + 1`, listed in reverse order
+/// so that the solver has to make many passes over the opcodes which it cannot yet solve.
+fn reversed_chain(length: u32) -> Vec<acir::circuit::Opcode> {
+    (1..=length)
+        .rev()
+        .map(|i| {
+            let mut expr = Expression::default();
+            expr.push_multiplication_term(FieldElement::one(), Witness(i), Witness(i));
+            expr.push_addition_term(-FieldElement::one(), Witness(i + 1));
+            expr.q_c = FieldElement::one();
+            acir::circuit::Opcode::AssertZero(expr)
+        })
+        .collect()
+}
+
+fn main() {
+    println!("expression clone");
+    for num_linear_terms in [1, 3, 4, 8] {
+        let expr = expression(num_linear_terms);
+        let (_, allocations) = count_allocations(|| expr.clone());
+        println!("  {num_linear_terms} linear terms: {allocations} allocations");
+    }
+
+    println!("solving a reversed chain of arithmetic opcodes");
+    for length in [10, 100, 1000] {
+        let opcodes = reversed_chain(length);
+        let initial_witness = WitnessMap::from(std::collections::BTreeMap::from([(
+            Witness(1),
+            FieldElement::from(2u128),
+        )]));
+        let mut acvm = ACVM::new(StubbedPwg, opcodes, initial_witness);
+        let (result, allocations) = count_allocations(|| acvm.solve());
+        result.expect("circuit should be solvable");
+        println!("  {length} opcodes: {allocations} allocations");
+    }
+}
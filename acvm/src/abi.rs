@@ -0,0 +1,396 @@
+//! Encoding of typed program inputs into the initial witness of a circuit, and decoding of its return value.
+//!
+//! Front-ends describe the parameters of a circuit with an [`Abi`]. Each parameter is flattened into field elements,
+//! assigned to consecutive witnesses starting at `Witness(1)` in the order the parameters are declared. Arrays are
+//! flattened element by element and structs field by field, in the order their fields are declared.
+use std::collections::BTreeMap;
+
+use acir::{
+    native_types::{Witness, WitnessMap},
+    FieldElement,
+};
+use thiserror::Error;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Sign {
+    Unsigned,
+    Signed,
+}
+
+/// The type of a parameter or return value of a circuit.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AbiType {
+    Field,
+    /// An integer of `width` bits. Signed integers are represented in two's complement.
+    Integer {
+        sign: Sign,
+        width: u32,
+    },
+    Boolean,
+    Array {
+        length: u32,
+        typ: Box<AbiType>,
+    },
+    Struct {
+        fields: Vec<(String, AbiType)>,
+    },
+}
+
+impl AbiType {
+    /// Returns the number of field elements, and thus of witnesses, a value of this type is flattened into.
+    pub fn field_count(&self) -> u32 {
+        match self {
+            AbiType::Field | AbiType::Integer { .. } | AbiType::Boolean => 1,
+            AbiType::Array { length, typ } => length * typ.field_count(),
+            AbiType::Struct { fields } => fields.iter().map(|(_, typ)| typ.field_count()).sum(),
+        }
+    }
+}
+
+/// A value provided for, or decoded from, an [`AbiType`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum InputValue {
+    Field(FieldElement),
+    Vec(Vec<InputValue>),
+    Struct(BTreeMap<String, InputValue>),
+}
+
+impl InputValue {
+    /// Returns the two's complement representation of `value` as a `width` bits integer.
+    pub fn signed(value: i128, width: u32) -> InputValue {
+        assert!((1..=128).contains(&width), "signed integers have between 1 and 128 bits");
+        let mask = u128::MAX >> (128 - width);
+        InputValue::Field(FieldElement::from(value as u128 & mask))
+    }
+}
+
+impl From<FieldElement> for InputValue {
+    fn from(value: FieldElement) -> Self {
+        InputValue::Field(value)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AbiParameter {
+    pub name: String,
+    pub typ: AbiType,
+}
+
+#[derive(Debug, PartialEq, Eq, Error)]
+pub enum AbiError {
+    #[error("missing value for parameter `{0}`")]
+    MissingParameter(String),
+    #[error("value provided for unknown parameter `{0}`")]
+    UnexpectedParameter(String),
+    #[error("value of `{path}` does not match its type, expected {expected}")]
+    TypeMismatch { path: String, expected: String },
+    #[error("value of `{path}` does not fit in a {width} bits integer")]
+    IntegerOutOfRange { path: String, width: u32 },
+    #[error("value of `{path}` is not a boolean")]
+    InvalidBoolean { path: String },
+    #[error("`{path}` should have {expected} elements, but has {actual}")]
+    ArrayLengthMismatch { path: String, expected: u32, actual: usize },
+    #[error("missing value for witness index {}", .0.witness_index())]
+    MissingWitness(Witness),
+    #[error("return value is made of {expected} field elements, but {actual} return witnesses were given")]
+    ReturnWitnessCountMismatch { expected: u32, actual: usize },
+}
+
+/// Describes the parameters and return value of a circuit.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Abi {
+    pub parameters: Vec<AbiParameter>,
+    pub return_type: Option<AbiType>,
+    /// The witnesses holding the flattened return value, in order.
+    pub return_witnesses: Vec<Witness>,
+}
+
+impl Abi {
+    /// Returns the number of witnesses assigned to the parameters.
+    pub fn field_count(&self) -> u32 {
+        self.parameters.iter().map(|parameter| parameter.typ.field_count()).sum()
+    }
+
+    /// Returns the witnesses assigned to each parameter, by name.
+    pub fn parameter_witnesses(&self) -> BTreeMap<&str, std::ops::Range<u32>> {
+        let mut next_witness = 1;
+        self.parameters
+            .iter()
+            .map(|parameter| {
+                let start = next_witness;
+                next_witness += parameter.typ.field_count();
+                (parameter.name.as_str(), start..next_witness)
+            })
+            .collect()
+    }
+
+    /// Encodes `inputs`, holding a value for each parameter, into the initial witness of the circuit.
+    pub fn encode(&self, inputs: &BTreeMap<String, InputValue>) -> Result<WitnessMap, AbiError> {
+        if let Some(name) = inputs
+            .keys()
+            .find(|name| !self.parameters.iter().any(|parameter| &parameter.name == *name))
+        {
+            return Err(AbiError::UnexpectedParameter(name.clone()));
+        }
+
+        let mut fields = Vec::with_capacity(self.field_count() as usize);
+        for parameter in &self.parameters {
+            let value = inputs
+                .get(&parameter.name)
+                .ok_or_else(|| AbiError::MissingParameter(parameter.name.clone()))?;
+            flatten(&parameter.typ, value, &parameter.name, &mut fields)?;
+        }
+
+        let witness_map: BTreeMap<_, _> = (1..).map(Witness).zip(fields).collect();
+        Ok(witness_map.into())
+    }
+
+    /// Decodes the value of each parameter from a witness map, such as a solved witness.
+    pub fn decode(
+        &self,
+        witness_map: &WitnessMap,
+    ) -> Result<BTreeMap<String, InputValue>, AbiError> {
+        let mut witnesses = (1..).map(Witness);
+        self.parameters
+            .iter()
+            .map(|parameter| {
+                let value = unflatten(&parameter.typ, &mut || {
+                    let witness = witnesses.next().expect("witness indices are unbounded");
+                    witness_value(witness_map, witness)
+                })?;
+                Ok((parameter.name.clone(), value))
+            })
+            .collect()
+    }
+
+    /// Decodes the return value of the circuit from its solved witness, or returns `None` if it has no return value.
+    pub fn decode_return_value(
+        &self,
+        witness_map: &WitnessMap,
+    ) -> Result<Option<InputValue>, AbiError> {
+        let return_type = match &self.return_type {
+            Some(return_type) => return_type,
+            None => return Ok(None),
+        };
+        if return_type.field_count() as usize != self.return_witnesses.len() {
+            return Err(AbiError::ReturnWitnessCountMismatch {
+                expected: return_type.field_count(),
+                actual: self.return_witnesses.len(),
+            });
+        }
+
+        let mut witnesses = self.return_witnesses.iter();
+        unflatten(return_type, &mut || {
+            let witness = witnesses.next().expect("return witnesses have been counted");
+            witness_value(witness_map, *witness)
+        })
+        .map(Some)
+    }
+}
+
+fn witness_value(witness_map: &WitnessMap, witness: Witness) -> Result<FieldElement, AbiError> {
+    witness_map.get(&witness).copied().ok_or(AbiError::MissingWitness(witness))
+}
+
+/// Appends the field elements of `value`, checked against `typ`, to `fields`.
+fn flatten(
+    typ: &AbiType,
+    value: &InputValue,
+    path: &str,
+    fields: &mut Vec<FieldElement>,
+) -> Result<(), AbiError> {
+    let mismatch = |expected: &str| AbiError::TypeMismatch {
+        path: path.to_owned(),
+        expected: expected.to_owned(),
+    };
+    match (typ, value) {
+        (AbiType::Field, InputValue::Field(field)) => fields.push(*field),
+        (AbiType::Integer { width, .. }, InputValue::Field(field)) => {
+            if field.num_bits() > *width {
+                return Err(AbiError::IntegerOutOfRange { path: path.to_owned(), width: *width });
+            }
+            fields.push(*field);
+        }
+        (AbiType::Boolean, InputValue::Field(field)) => {
+            if !field.is_zero() && !field.is_one() {
+                return Err(AbiError::InvalidBoolean { path: path.to_owned() });
+            }
+            fields.push(*field);
+        }
+        (AbiType::Array { length, typ }, InputValue::Vec(elements)) => {
+            if elements.len() != *length as usize {
+                return Err(AbiError::ArrayLengthMismatch {
+                    path: path.to_owned(),
+                    expected: *length,
+                    actual: elements.len(),
+                });
+            }
+            for (i, element) in elements.iter().enumerate() {
+                flatten(typ, element, &format!("{path}[{i}]"), fields)?;
+            }
+        }
+        (AbiType::Struct { fields: struct_fields }, InputValue::Struct(values)) => {
+            if let Some(name) =
+                values.keys().find(|name| !struct_fields.iter().any(|(field, _)| field == *name))
+            {
+                return Err(AbiError::UnexpectedParameter(format!("{path}.{name}")));
+            }
+            for (name, typ) in struct_fields {
+                let path = format!("{path}.{name}");
+                let value =
+                    values.get(name).ok_or_else(|| AbiError::MissingParameter(path.clone()))?;
+                flatten(typ, value, &path, fields)?;
+            }
+        }
+        (AbiType::Field | AbiType::Integer { .. } | AbiType::Boolean, _) => {
+            return Err(mismatch("a field element"))
+        }
+        (AbiType::Array { .. }, _) => return Err(mismatch("an array")),
+        (AbiType::Struct { .. }, _) => return Err(mismatch("a struct")),
+    }
+    Ok(())
+}
+
+/// Builds a value of type `typ` from the field elements returned by `next_field`.
+fn unflatten(
+    typ: &AbiType,
+    next_field: &mut impl FnMut() -> Result<FieldElement, AbiError>,
+) -> Result<InputValue, AbiError> {
+    match typ {
+        AbiType::Field | AbiType::Integer { .. } | AbiType::Boolean => {
+            next_field().map(InputValue::Field)
+        }
+        AbiType::Array { length, typ } => (0..*length)
+            .map(|_| unflatten(typ, next_field))
+            .collect::<Result<_, _>>()
+            .map(InputValue::Vec),
+        AbiType::Struct { fields } => fields
+            .iter()
+            .map(|(name, typ)| Ok((name.clone(), unflatten(typ, next_field)?)))
+            .collect::<Result<_, _>>()
+            .map(InputValue::Struct),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use acir::{
+        native_types::{Witness, WitnessMap},
+        FieldElement,
+    };
+
+    use super::{Abi, AbiError, AbiParameter, AbiType, InputValue, Sign};
+
+    fn field(value: u128) -> InputValue {
+        InputValue::Field(FieldElement::from(value))
+    }
+
+    fn abi() -> Abi {
+        let point = AbiType::Struct {
+            fields: vec![("x".to_owned(), AbiType::Field), ("y".to_owned(), AbiType::Field)],
+        };
+        Abi {
+            parameters: vec![
+                AbiParameter {
+                    name: "points".to_owned(),
+                    typ: AbiType::Array { length: 2, typ: Box::new(point) },
+                },
+                AbiParameter {
+                    name: "offset".to_owned(),
+                    typ: AbiType::Integer { sign: Sign::Signed, width: 8 },
+                },
+                AbiParameter { name: "flag".to_owned(), typ: AbiType::Boolean },
+            ],
+            return_type: Some(AbiType::Array {
+                length: 2,
+                typ: Box::new(AbiType::Integer { sign: Sign::Unsigned, width: 32 }),
+            }),
+            return_witnesses: vec![Witness(8), Witness(7)],
+        }
+    }
+
+    fn point(x: u128, y: u128) -> InputValue {
+        InputValue::Struct(BTreeMap::from([("x".to_owned(), field(x)), ("y".to_owned(), field(y))]))
+    }
+
+    fn inputs() -> BTreeMap<String, InputValue> {
+        BTreeMap::from([
+            ("points".to_owned(), InputValue::Vec(vec![point(1, 2), point(3, 4)])),
+            ("offset".to_owned(), InputValue::signed(-1, 8)),
+            ("flag".to_owned(), field(1)),
+        ])
+    }
+
+    #[test]
+    fn encodes_and_decodes_inputs() {
+        let abi = abi();
+        assert_eq!(abi.field_count(), 6);
+        assert_eq!(abi.parameter_witnesses()["offset"], 5..6);
+
+        let witness_map = abi.encode(&inputs()).unwrap();
+        let expected: BTreeMap<_, _> = [1, 2, 3, 4, 0xff, 1]
+            .into_iter()
+            .enumerate()
+            .map(|(i, value)| (Witness(i as u32 + 1), FieldElement::from(value as u128)))
+            .collect();
+        assert_eq!(witness_map, WitnessMap::from(expected));
+        assert_eq!(abi.decode(&witness_map).unwrap(), inputs());
+    }
+
+    #[test]
+    fn decodes_return_value() {
+        let abi = abi();
+        let mut witness_map = abi.encode(&inputs()).unwrap();
+        witness_map.insert(Witness(7), FieldElement::from(7_u128));
+        witness_map.insert(Witness(8), FieldElement::from(8_u128));
+        assert_eq!(
+            abi.decode_return_value(&witness_map).unwrap(),
+            Some(InputValue::Vec(vec![field(8), field(7)]))
+        );
+
+        witness_map = abi.encode(&inputs()).unwrap();
+        assert_eq!(
+            abi.decode_return_value(&witness_map),
+            Err(AbiError::MissingWitness(Witness(8)))
+        );
+        assert_eq!(Abi::default().decode_return_value(&witness_map), Ok(None));
+    }
+
+    #[test]
+    fn rejects_invalid_inputs() {
+        let abi = abi();
+        let with = |name: &str, value: InputValue| {
+            let mut inputs = inputs();
+            inputs.insert(name.to_owned(), value);
+            abi.encode(&inputs).unwrap_err()
+        };
+
+        assert_eq!(
+            with("offset", field(256)),
+            AbiError::IntegerOutOfRange { path: "offset".to_owned(), width: 8 }
+        );
+        assert_eq!(with("flag", field(2)), AbiError::InvalidBoolean { path: "flag".to_owned() });
+        assert_eq!(
+            with("points", InputValue::Vec(vec![point(1, 2)])),
+            AbiError::ArrayLengthMismatch { path: "points".to_owned(), expected: 2, actual: 1 }
+        );
+        assert_eq!(
+            with("points", InputValue::Vec(vec![point(1, 2), field(3)])),
+            AbiError::TypeMismatch {
+                path: "points[1]".to_owned(),
+                expected: "a struct".to_owned()
+            }
+        );
+        assert_eq!(
+            with("points", InputValue::Vec(vec![point(1, 2), InputValue::Struct(BTreeMap::new())])),
+            AbiError::MissingParameter("points[1].x".to_owned())
+        );
+        assert_eq!(with("other", field(0)), AbiError::UnexpectedParameter("other".to_owned()));
+
+        let mut inputs = inputs();
+        inputs.remove("flag");
+        assert_eq!(abi.encode(&inputs), Err(AbiError::MissingParameter("flag".to_owned())));
+    }
+}
@@ -0,0 +1,10 @@
+//! Native implementations of the black box functions, operating directly on bytes and [`FieldElement`][acir::FieldElement]s.
+//!
+//! These are the primitives with which the ACVM solves black box opcodes, so that oracles and backends
+//! relying on them compute the same results as the solver.
+
+pub use crate::pwg::blackbox::{
+    aes128_cbc_encrypt, blake2b512, blake2s256, blake3, keccak256, keccakf1600,
+    poseidon2_permutation, recover_secp256k1_public_key, sha256, sha512, verify_ed25519_signature,
+    verify_secp256k1_ecdsa_signature,
+};
@@ -0,0 +1,123 @@
+use acir::{
+    brillig_vm::{self, BinaryFieldOp, RegisterIndex},
+    circuit::{
+        brillig::{Brillig, BrilligInputs, BrilligOutputs},
+        Opcode,
+    },
+    native_types::{Expression, Witness},
+};
+use std::collections::HashMap;
+
+/// The arithmetic opcodes equivalent to a Brillig opcode, as returned by [`lift_brillig`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LiftedBrillig {
+    /// Opcodes constraining each output of the Brillig opcode, preceded by those defining the intermediate
+    /// witnesses they need.
+    pub opcodes: Vec<Opcode>,
+    /// The highest witness index once the intermediate witnesses have been allocated.
+    pub current_witness_index: u32,
+}
+
+/// Lifts `brillig` into arithmetic opcodes constraining its outputs to the values it computes, if its bytecode
+/// is a straight-line arithmetic computation over its inputs.
+///
+/// The bytecode must only move constants and registers and add, subtract and multiply field elements, or divide
+/// them by non-zero constants, before stopping. Its inputs and outputs must be single values, and it must not have
+/// a predicate. Values whose degree would exceed two are assigned to intermediate witnesses, allocated after
+/// `current_witness_index`. The lifted opcodes can be solved by the ACVM in place of the Brillig opcode, and
+/// constrain its outputs such that a proof shows they were computed as the hint would have.
+pub fn lift_brillig(brillig: &Brillig, current_witness_index: u32) -> Option<LiftedBrillig> {
+    if brillig.predicate.is_some() || !brillig.foreign_call_results.is_empty() {
+        return None;
+    }
+    let mut lifter =
+        Lifter { registers: HashMap::new(), opcodes: Vec::new(), current_witness_index };
+    for (index, input) in brillig.inputs.iter().enumerate() {
+        match input {
+            BrilligInputs::Single(expr) => lifter.set(RegisterIndex::from(index), expr.clone()),
+            BrilligInputs::Array(_) => return None,
+        }
+    }
+
+    for opcode in &brillig.bytecode {
+        match opcode {
+            brillig_vm::Opcode::Const { destination, value } => {
+                lifter.set(*destination, Expression::from_field(value.to_field()));
+            }
+            brillig_vm::Opcode::Mov { destination, source } => {
+                let value = lifter.get(*source);
+                lifter.set(*destination, value);
+            }
+            brillig_vm::Opcode::BinaryFieldOp { destination, op, lhs, rhs } => {
+                let value = lifter.binary_field_op(*op, lifter.get(*lhs), lifter.get(*rhs))?;
+                lifter.set(*destination, value);
+            }
+            brillig_vm::Opcode::Stop => break,
+            _ => return None,
+        }
+    }
+
+    for (index, output) in brillig.outputs.iter().enumerate() {
+        let witness = match output {
+            BrilligOutputs::Simple(witness) => *witness,
+            BrilligOutputs::Array(_) => return None,
+        };
+        let value = lifter.get(RegisterIndex::from(index));
+        lifter.opcodes.push(Opcode::AssertZero(&value - &Expression::from(witness)));
+    }
+    Some(LiftedBrillig {
+        opcodes: lifter.opcodes,
+        current_witness_index: lifter.current_witness_index,
+    })
+}
+
+/// Tracks the value of each register of the bytecode as an expression over the inputs.
+struct Lifter {
+    /// The value of each register written so far, by index.
+    registers: HashMap<usize, Expression>,
+    opcodes: Vec<Opcode>,
+    current_witness_index: u32,
+}
+
+impl Lifter {
+    /// Returns the value of `register`, registers which were never written holding zero as in the VM.
+    fn get(&self, register: RegisterIndex) -> Expression {
+        self.registers.get(&register.to_usize()).cloned().unwrap_or_default()
+    }
+
+    fn set(&mut self, register: RegisterIndex, value: Expression) {
+        self.registers.insert(register.to_usize(), value);
+    }
+
+    fn binary_field_op(
+        &mut self,
+        op: BinaryFieldOp,
+        lhs: Expression,
+        rhs: Expression,
+    ) -> Option<Expression> {
+        match op {
+            BinaryFieldOp::Add => Some(&lhs + &rhs),
+            BinaryFieldOp::Sub => Some(&lhs - &rhs),
+            BinaryFieldOp::Mul => {
+                // Operands are assigned to witnesses until their product is of degree two.
+                let lhs = if lhs.is_linear() { lhs } else { self.assign(lhs) };
+                let rhs = if rhs.is_linear() { rhs } else { self.assign(rhs) };
+                &lhs * &rhs
+            }
+            // Dividing by zero yields zero in the VM, which no constraint would match for a non-constant divisor.
+            BinaryFieldOp::Div => match rhs.to_const() {
+                Some(divisor) if !divisor.is_zero() => Some(&lhs * divisor.inverse()),
+                _ => None,
+            },
+            BinaryFieldOp::Equals | BinaryFieldOp::Pow => None,
+        }
+    }
+
+    /// Returns a new witness constrained to be equal to `value`.
+    fn assign(&mut self, value: Expression) -> Expression {
+        self.current_witness_index += 1;
+        let witness = Witness(self.current_witness_index);
+        self.opcodes.push(Opcode::AssertZero(&value - &Expression::from(witness)));
+        Expression::from(witness)
+    }
+}
@@ -0,0 +1,98 @@
+use acir::{
+    circuit::{opcodes::BlackBoxFuncCall, Opcode},
+    BlackBoxFunc,
+};
+use std::collections::HashSet;
+
+use crate::Language;
+
+/// Describes the constraints a backend can prove natively, from which the compiler determines
+/// which black box function calls must be replaced by their fallbacks.
+#[derive(Debug, Clone)]
+pub struct BackendCapabilities {
+    /// The language of the constraint system accepted by the backend.
+    pub language: Language,
+    /// The black box functions supported by the backend.
+    pub black_box_functions: HashSet<BlackBoxFunc>,
+    /// The largest number of bits a value can be range constrained to by the backend,
+    /// or `None` if range constraints of any size are supported.
+    ///
+    /// Larger range constraints are split into chunks of this size.
+    pub max_range_bits: Option<u32>,
+    /// Whether the backend supports memory opcodes natively.
+    ///
+    /// Otherwise, memory blocks are lowered into constraints checking the consistency of their trace.
+    pub supports_memory: bool,
+    /// Whether circuits using [deprecated opcodes][super::check_deprecated_opcodes] are rejected rather than compiled.
+    pub strict: bool,
+}
+
+impl BackendCapabilities {
+    /// Returns the capabilities of a backend for `language` which supports every black box function.
+    pub fn new(language: Language) -> Self {
+        BackendCapabilities {
+            language,
+            black_box_functions: BlackBoxFunc::ALL.into_iter().collect(),
+            max_range_bits: None,
+            supports_memory: true,
+            strict: false,
+        }
+    }
+
+    /// Returns the capabilities of a backend for `language` which only supports arithmetic opcodes,
+    /// such that every black box function call is replaced by its fallback and memory blocks are lowered.
+    pub fn arithmetic_only(language: Language) -> Self {
+        BackendCapabilities {
+            language,
+            black_box_functions: HashSet::new(),
+            max_range_bits: None,
+            supports_memory: false,
+            strict: false,
+        }
+    }
+
+    /// Removes `black_box_func` from the supported black box functions.
+    pub fn without(mut self, black_box_func: BlackBoxFunc) -> Self {
+        self.black_box_functions.remove(&black_box_func);
+        self
+    }
+
+    /// Removes the support for memory opcodes.
+    pub fn without_memory(mut self) -> Self {
+        self.supports_memory = false;
+        self
+    }
+
+    /// Rejects circuits using deprecated opcodes, such as directives, rather than compiling them.
+    pub fn with_strict_mode(mut self) -> Self {
+        self.strict = true;
+        self
+    }
+
+    /// Limits range constraints to values of at most `max_range_bits` bits.
+    pub fn with_max_range_bits(mut self, max_range_bits: u32) -> Self {
+        self.max_range_bits = Some(max_range_bits);
+        self
+    }
+
+    /// Returns whether the backend can prove `opcode` without it being replaced by a fallback.
+    ///
+    /// Opcodes other than black box function calls and memory blocks are always supported.
+    pub fn supports_opcode(&self, opcode: &Opcode) -> bool {
+        let fits_in_range = |num_bits: u32| self.max_range_bits.map_or(true, |max| num_bits <= max);
+        match opcode {
+            Opcode::BlackBoxFuncCall(call) => {
+                let fits_in_range = match call {
+                    BlackBoxFuncCall::RANGE { input } => fits_in_range(input.num_bits),
+                    BlackBoxFuncCall::RangeBatch { inputs } => {
+                        inputs.iter().all(|input| fits_in_range(input.num_bits))
+                    }
+                    _ => true,
+                };
+                fits_in_range && self.black_box_functions.contains(&call.get_black_box_func())
+            }
+            Opcode::MemoryInit { .. } | Opcode::MemoryOp { .. } => self.supports_memory,
+            _ => true,
+        }
+    }
+}
@@ -0,0 +1,341 @@
+use acir::{
+    circuit::Circuit,
+    native_types::{Witness, WitnessMap},
+    FieldElement,
+};
+use std::collections::BTreeMap;
+use thiserror::Error;
+
+use crate::{
+    pwg::{PartialWitnessGeneratorStatus, ACVM},
+    PartialWitnessGenerator,
+};
+
+/// Bit sizes of the random values assigned to inputs, the last one spanning the whole field.
+///
+/// Small values are drawn often so that inputs regularly satisfy range constraints.
+const SAMPLE_BIT_SIZES: [u32; 6] = [1, 8, 32, 64, 128, FieldElement::max_num_bits()];
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum EquivalenceError {
+    #[error("only the {} circuit is satisfied by the inputs {inputs:?}", if *.original_is_satisfied { "original" } else { "optimized" })]
+    SatisfiabilityMismatch { inputs: WitnessMap, original_is_satisfied: bool },
+    #[error("the optimized circuit assigns {actual:?} to {witness:?} rather than {expected} for the inputs {inputs:?}")]
+    OutputMismatch {
+        inputs: WitnessMap,
+        witness: Witness,
+        expected: FieldElement,
+        actual: Option<FieldElement>,
+    },
+}
+
+/// Checks that `optimized` behaves as `original` on `num_samples` assignments of random values to `inputs`,
+/// drawn from a generator seeded with `seed`.
+///
+/// Returns the number of samples which satisfied both circuits, so that callers can tell whether the inputs
+/// exercised them at all. See [`check_equivalence_on_inputs`] for the properties checked on each sample.
+pub fn check_equivalence<B: PartialWitnessGenerator + Clone>(
+    backend: &B,
+    original: &Circuit,
+    optimized: &Circuit,
+    inputs: &[Witness],
+    num_samples: usize,
+    seed: u64,
+) -> Result<usize, EquivalenceError> {
+    let mut rng = SplitMix64(seed);
+    let mut num_satisfied = 0;
+    for _ in 0..num_samples {
+        let initial_witness: BTreeMap<Witness, FieldElement> =
+            inputs.iter().map(|input| (*input, rng.next_field_element())).collect();
+        let initial_witness = WitnessMap::from(initial_witness);
+        if check_equivalence_on_inputs(backend, original, optimized, initial_witness)? {
+            num_satisfied += 1;
+        }
+    }
+    Ok(num_satisfied)
+}
+
+/// Checks that `optimized` behaves as `original` when both are solved from `initial_witness`.
+///
+/// Both circuits must either be unsatisfiable or assign the same values to the return values of `original`.
+/// A circuit is unsatisfiable if the solver fails or stops before solving every opcode, such as on
+/// a foreign call. Returns whether both circuits were satisfied.
+pub fn check_equivalence_on_inputs<B: PartialWitnessGenerator + Clone>(
+    backend: &B,
+    original: &Circuit,
+    optimized: &Circuit,
+    initial_witness: WitnessMap,
+) -> Result<bool, EquivalenceError> {
+    let original_solution = solve(backend, original, initial_witness.clone());
+    let optimized_solution = solve(backend, optimized, initial_witness.clone());
+    let (original_solution, optimized_solution) = match (original_solution, optimized_solution) {
+        (Some(original_solution), Some(optimized_solution)) => {
+            (original_solution, optimized_solution)
+        }
+        (None, None) => return Ok(false),
+        (original_solution, _) => {
+            return Err(EquivalenceError::SatisfiabilityMismatch {
+                inputs: initial_witness,
+                original_is_satisfied: original_solution.is_some(),
+            })
+        }
+    };
+
+    for witness in &original.return_values.0 {
+        let expected = original_solution[witness];
+        let actual = optimized_solution.get(witness).copied();
+        if actual != Some(expected) {
+            return Err(EquivalenceError::OutputMismatch {
+                inputs: initial_witness,
+                witness: *witness,
+                expected,
+                actual,
+            });
+        }
+    }
+    Ok(true)
+}
+
+/// Returns the solution of `circuit` from `initial_witness`, or `None` if it cannot be fully solved.
+fn solve<B: PartialWitnessGenerator + Clone>(
+    backend: &B,
+    circuit: &Circuit,
+    initial_witness: WitnessMap,
+) -> Option<WitnessMap> {
+    let mut acvm = ACVM::new(backend.clone(), circuit.opcodes.clone(), initial_witness);
+    match acvm.solve() {
+        Ok(PartialWitnessGeneratorStatus::Solved) => Some(acvm.witness_map().clone()),
+        _ => None,
+    }
+}
+
+/// Small deterministic generator, which is enough to draw inputs reproducibly from a seed.
+pub(crate) struct SplitMix64(pub(crate) u64);
+
+impl SplitMix64 {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Returns a random field element with as many bits as one of the [`SAMPLE_BIT_SIZES`].
+    pub(crate) fn next_field_element(&mut self) -> FieldElement {
+        let num_bits = SAMPLE_BIT_SIZES[(self.next_u64() % SAMPLE_BIT_SIZES.len() as u64) as usize];
+        self.next_field_element_with_bits(num_bits)
+    }
+
+    /// Returns a random field element fitting within `num_bits` bits.
+    pub(crate) fn next_field_element_with_bits(&mut self, num_bits: u32) -> FieldElement {
+        let mut bytes: Vec<u8> = (0..4).flat_map(|_| self.next_u64().to_be_bytes()).collect();
+        // Bits above `num_bits` are cleared, the value being reduced into the field if it spans all of them.
+        let num_bytes = ((num_bits + 7) / 8) as usize;
+        let first_byte = bytes.len().saturating_sub(num_bytes);
+        bytes[..first_byte].fill(0);
+        if num_bits % 8 != 0 {
+            bytes[first_byte] &= (1 << (num_bits % 8)) - 1;
+        }
+        FieldElement::from_be_bytes_reduce(&bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{check_equivalence, check_equivalence_on_inputs, EquivalenceError};
+    use crate::{
+        compiler::{
+            compile,
+            optimizers::{
+                CircuitSimplifier, CommonSubexpressionOptimizer, ConstantFolder, GeneralOptimizer,
+                RangeOptimizer,
+            },
+            transform,
+            transformers::FallbackTransformer,
+            AcirTransformationMap, BackendCapabilities, ExpressionWidth,
+        },
+        pwg::OpcodeResolutionError,
+        Language, PartialWitnessGenerator,
+    };
+    use acir::{
+        circuit::{
+            opcodes::{BlackBoxFuncCall, FunctionInput},
+            Circuit, Opcode, PublicInputs,
+        },
+        native_types::{Expression, Witness, WitnessMap},
+        BlackBoxFunc, FieldElement,
+    };
+    use std::collections::BTreeMap;
+
+    const NUM_SAMPLES: usize = 64;
+
+    #[derive(Clone)]
+    struct StubbedPwg;
+
+    impl PartialWitnessGenerator for StubbedPwg {
+        fn schnorr_verify(
+            &self,
+            _public_key_x: &FieldElement,
+            _public_key_y: &FieldElement,
+            _signature_s: &FieldElement,
+            _signature_e: &FieldElement,
+            _message: &[u8],
+        ) -> Result<bool, OpcodeResolutionError> {
+            panic!("Path not trodden by this test")
+        }
+
+        fn pedersen(
+            &self,
+            _inputs: &[FieldElement],
+            _domain_separator: u32,
+        ) -> Result<(FieldElement, FieldElement), OpcodeResolutionError> {
+            panic!("Path not trodden by this test")
+        }
+
+        fn fixed_base_scalar_mul(
+            &self,
+            _input: &FieldElement,
+        ) -> Result<(FieldElement, FieldElement), OpcodeResolutionError> {
+            panic!("Path not trodden by this test")
+        }
+    }
+
+    const INPUTS: [Witness; 2] = [Witness(1), Witness(2)];
+
+    /// Returns a circuit over the inputs `x` and `y` which every pass of the compiler can simplify.
+    fn test_circuit() -> Circuit {
+        let [x, y] = INPUTS;
+        let (c, a, b, s, and) = (Witness(3), Witness(4), Witness(5), Witness(6), Witness(7));
+        let mut c_definition = Expression::from(c);
+        c_definition.q_c = -FieldElement::from(5_u128);
+        let product = |scale: u128, output: Witness| {
+            let mut expr = Expression::default();
+            expr.push_multiplication_term(FieldElement::from(scale), x, y);
+            expr.push_addition_term(-FieldElement::from(scale), output);
+            Opcode::AssertZero(expr)
+        };
+        let mut sum = Expression::default();
+        for witness in [a, b, c, x, y] {
+            sum.push_addition_term(FieldElement::one(), witness);
+        }
+        sum.push_addition_term(-FieldElement::one(), s);
+        let range = |witness: Witness, num_bits: u32| {
+            Opcode::BlackBoxFuncCall(BlackBoxFuncCall::RANGE {
+                input: FunctionInput { witness, num_bits },
+            })
+        };
+
+        Circuit {
+            current_witness_index: 7,
+            opcodes: vec![
+                Opcode::AssertZero(c_definition),
+                product(1, a),
+                product(2, b),
+                Opcode::AssertZero(sum),
+                range(x, 16),
+                range(x, 8),
+                range(y, 8),
+                Opcode::BlackBoxFuncCall(BlackBoxFuncCall::AND {
+                    lhs: FunctionInput { witness: x, num_bits: 8 },
+                    rhs: FunctionInput { witness: y, num_bits: 8 },
+                    output: and,
+                }),
+            ],
+            return_values: PublicInputs([s, and].into_iter().collect()),
+            ..Circuit::default()
+        }
+    }
+
+    fn assert_equivalent(original: &Circuit, optimized: &Circuit) {
+        let num_satisfied =
+            check_equivalence(&StubbedPwg, original, optimized, &INPUTS, NUM_SAMPLES, 42)
+                .unwrap_or_else(|error| panic!("{error}"));
+        assert!(num_satisfied > 0, "no sample satisfies the circuits");
+    }
+
+    fn initial_map(circuit: &Circuit) -> AcirTransformationMap {
+        AcirTransformationMap::new((0..circuit.opcodes.len()).collect())
+    }
+
+    #[test]
+    fn every_pass_preserves_semantics() {
+        let circuit = test_circuit();
+        let is_observable = |witness: Witness| INPUTS.contains(&witness);
+
+        let (fallback, _, _) = FallbackTransformer::transform(
+            circuit.clone(),
+            &BackendCapabilities::new(Language::R1CS).without(BlackBoxFunc::AND),
+            &CircuitSimplifier::new(3),
+        )
+        .unwrap();
+        assert_equivalent(&circuit, &fallback);
+
+        let opcodes = circuit
+            .opcodes
+            .iter()
+            .cloned()
+            .map(|opcode| match opcode {
+                Opcode::AssertZero(expr) => Opcode::AssertZero(GeneralOptimizer::optimize(expr)),
+                other => other,
+            })
+            .collect();
+        assert_equivalent(&circuit, &Circuit { opcodes, ..circuit.clone() });
+
+        let (folded, _, _) = ConstantFolder::new(circuit.clone())
+            .fold_constants(is_observable, initial_map(&circuit));
+        assert_equivalent(&circuit, &folded);
+
+        let (eliminated, _) = CommonSubexpressionOptimizer::new(circuit.clone())
+            .eliminate_common_subexpressions(is_observable, initial_map(&circuit));
+        assert_equivalent(&circuit, &eliminated);
+
+        let (ranges, _) =
+            RangeOptimizer::new(circuit.clone()).replace_redundant_ranges(initial_map(&circuit));
+        assert_equivalent(&circuit, &ranges);
+
+        let (reduced, _) = transform(circuit.clone(), ExpressionWidth::Bounded(3));
+        assert_equivalent(&circuit, &reduced);
+
+        for language in [Language::R1CS, Language::PLONKCSat { width: 3 }] {
+            let capabilities = BackendCapabilities::new(language);
+            let (compiled, _, _) =
+                compile(circuit.clone(), &capabilities, &CircuitSimplifier::new(3)).unwrap();
+            assert_equivalent(&circuit, &compiled);
+        }
+    }
+
+    #[test]
+    fn reports_diverging_circuits() {
+        let circuit = test_circuit();
+        let inputs = WitnessMap::from(BTreeMap::from([
+            (INPUTS[0], FieldElement::from(3_u128)),
+            (INPUTS[1], FieldElement::from(4_u128)),
+        ]));
+
+        // Dropping the range constraints of `y` lets inputs which do not fit within 8 bits through.
+        let mut unconstrained = circuit.clone();
+        unconstrained.opcodes.remove(6);
+        let mut wide_inputs = inputs.clone();
+        wide_inputs.insert(INPUTS[1], FieldElement::from(1_u128 << 20));
+        assert_eq!(
+            check_equivalence_on_inputs(&StubbedPwg, &circuit, &unconstrained, wide_inputs.clone()),
+            Err(EquivalenceError::SatisfiabilityMismatch {
+                inputs: wide_inputs,
+                original_is_satisfied: false
+            })
+        );
+
+        // Changing the constant changes the sum.
+        let mut shifted = circuit.clone();
+        let mut c_definition = Expression::from(Witness(3));
+        c_definition.q_c = -FieldElement::from(6_u128);
+        shifted.opcodes[0] = Opcode::AssertZero(c_definition);
+        assert!(matches!(
+            check_equivalence_on_inputs(&StubbedPwg, &circuit, &shifted, inputs.clone()),
+            Err(EquivalenceError::OutputMismatch { witness: Witness(6), .. })
+        ));
+
+        assert_eq!(check_equivalence_on_inputs(&StubbedPwg, &circuit, &circuit, inputs), Ok(true));
+    }
+}
@@ -0,0 +1,245 @@
+use acir::{
+    circuit::{Circuit, Opcode},
+    native_types::Expression,
+    FieldElement,
+};
+
+use super::attach_opcode_metadata;
+
+/// A circuit reduced by [`minimize`], along with the index in the original circuit of each of its opcodes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MinimizedCircuit {
+    pub circuit: Circuit,
+    pub opcode_indices: Vec<usize>,
+}
+
+/// Reduces `circuit` to a smaller circuit for which `predicate` still holds, such as a circuit on which
+/// the solver panics or whose semantics an optimization changes, in order to report it.
+///
+/// Opcodes are removed by delta debugging, trying to remove ever smaller chunks of the remaining opcodes until
+/// none can be removed, and the terms of the remaining arithmetic opcodes are removed one at a time. The result is
+/// minimal in that no single opcode or term can be removed from it, while the witnesses, public inputs and
+/// return values of the circuit are kept. The predicate is called once for each candidate circuit, so a predicate
+/// checking for a panic should catch it through [`std::panic::catch_unwind`].
+///
+/// # Panics
+///
+/// Panics if `predicate` does not hold for `circuit`.
+pub fn minimize(
+    circuit: &Circuit,
+    mut predicate: impl FnMut(&Circuit) -> bool,
+) -> MinimizedCircuit {
+    assert!(predicate(circuit), "the predicate does not hold for the circuit to minimize");
+
+    let build = |opcodes: &[(usize, Opcode)]| Circuit {
+        opcodes: opcodes.iter().map(|(_, opcode)| opcode.clone()).collect(),
+        debug_info: None,
+        phases: Vec::new(),
+        ..circuit.clone()
+    };
+    let mut holds = |opcodes: &[(usize, Opcode)]| predicate(&build(opcodes));
+
+    // Simplifying an opcode may make others unnecessary, so both steps are repeated until neither applies.
+    let mut opcodes: Vec<(usize, Opcode)> = circuit.opcodes.iter().cloned().enumerate().collect();
+    loop {
+        opcodes = remove_opcodes(opcodes, &mut holds);
+        let simplified = simplify_opcodes(opcodes.clone(), &mut holds);
+        if simplified == opcodes {
+            break;
+        }
+        opcodes = simplified;
+    }
+
+    let opcode_indices: Vec<usize> = opcodes.iter().map(|(index, _)| *index).collect();
+    let minimized = attach_opcode_metadata(
+        build(&opcodes),
+        circuit.debug_info.clone(),
+        circuit.phases.clone(),
+        &opcode_indices,
+    );
+    MinimizedCircuit { circuit: minimized, opcode_indices }
+}
+
+/// Removes chunks of `opcodes` as long as `holds` still holds for the remaining ones, by delta debugging.
+///
+/// Each chunk is first kept on its own, then removed from the others, the chunks being refined until they
+/// are single opcodes.
+fn remove_opcodes(
+    mut opcodes: Vec<(usize, Opcode)>,
+    holds: &mut impl FnMut(&[(usize, Opcode)]) -> bool,
+) -> Vec<(usize, Opcode)> {
+    let mut num_chunks = 2;
+    while opcodes.len() >= 2 {
+        let chunk_size = (opcodes.len() + num_chunks - 1) / num_chunks;
+        let chunks: Vec<Vec<(usize, Opcode)>> =
+            opcodes.chunks(chunk_size).map(|chunk| chunk.to_vec()).collect();
+
+        if let Some(chunk) = chunks.iter().find(|chunk| holds(chunk)) {
+            opcodes = chunk.clone();
+            num_chunks = 2;
+            continue;
+        }
+        let complement = (0..chunks.len()).find_map(|removed| {
+            let complement: Vec<(usize, Opcode)> = chunks
+                .iter()
+                .enumerate()
+                .filter(|(index, _)| *index != removed)
+                .flat_map(|(_, chunk)| chunk.iter().cloned())
+                .collect();
+            holds(&complement).then_some(complement)
+        });
+        if let Some(complement) = complement {
+            opcodes = complement;
+            num_chunks = (num_chunks - 1).max(2);
+            continue;
+        }
+
+        if chunk_size == 1 {
+            break;
+        }
+        num_chunks = (num_chunks * 2).min(opcodes.len());
+    }
+    if opcodes.len() == 1 && holds(&[]) {
+        opcodes.clear();
+    }
+    opcodes
+}
+
+/// Simplifies the arithmetic opcodes of `opcodes` term by term, as long as `holds` still holds.
+fn simplify_opcodes(
+    mut opcodes: Vec<(usize, Opcode)>,
+    holds: &mut impl FnMut(&[(usize, Opcode)]) -> bool,
+) -> Vec<(usize, Opcode)> {
+    for position in 0..opcodes.len() {
+        let expr = match &opcodes[position].1 {
+            Opcode::AssertZero(expr) => expr.clone(),
+            _ => continue,
+        };
+        let simplified = simplify(expr, |candidate| {
+            opcodes[position].1 = Opcode::AssertZero(candidate.clone());
+            holds(&opcodes)
+        });
+        opcodes[position].1 = Opcode::AssertZero(simplified);
+    }
+    opcodes
+}
+
+/// Removes the terms of `expr` one at a time, last to first, as long as `holds` still holds for the remaining ones.
+fn simplify(mut expr: Expression, mut holds: impl FnMut(&Expression) -> bool) -> Expression {
+    for index in (0..expr.mul_terms.len()).rev() {
+        let mut candidate = expr.clone();
+        candidate.mul_terms.remove(index);
+        if holds(&candidate) {
+            expr = candidate;
+        }
+    }
+    for index in (0..expr.linear_combinations.len()).rev() {
+        let mut candidate = expr.clone();
+        candidate.linear_combinations.remove(index);
+        if holds(&candidate) {
+            expr = candidate;
+        }
+    }
+    if !expr.q_c.is_zero() {
+        let candidate = Expression { q_c: FieldElement::zero(), ..expr.clone() };
+        if holds(&candidate) {
+            expr = candidate;
+        }
+    }
+    expr
+}
+
+#[cfg(test)]
+mod tests {
+    use super::minimize;
+    use crate::{
+        pwg::{OpcodeResolutionError, ACVM},
+        PartialWitnessGenerator,
+    };
+    use acir::{
+        circuit::{Circuit, Opcode},
+        native_types::{Expression, Witness, WitnessMap},
+        FieldElement,
+    };
+    use std::collections::BTreeMap;
+
+    struct StubbedPwg;
+
+    impl PartialWitnessGenerator for StubbedPwg {
+        fn schnorr_verify(
+            &self,
+            _public_key_x: &FieldElement,
+            _public_key_y: &FieldElement,
+            _signature_s: &FieldElement,
+            _signature_e: &FieldElement,
+            _message: &[u8],
+        ) -> Result<bool, OpcodeResolutionError> {
+            panic!("Path not trodden by this test")
+        }
+
+        fn pedersen(
+            &self,
+            _inputs: &[FieldElement],
+            _domain_separator: u32,
+        ) -> Result<(FieldElement, FieldElement), OpcodeResolutionError> {
+            panic!("Path not trodden by this test")
+        }
+
+        fn fixed_base_scalar_mul(
+            &self,
+            _input: &FieldElement,
+        ) -> Result<(FieldElement, FieldElement), OpcodeResolutionError> {
+            panic!("Path not trodden by this test")
+        }
+    }
+
+    /// Solves `circuit` from `x = 2`, returning the solved witness map.
+    fn solve(circuit: &Circuit) -> Result<WitnessMap, OpcodeResolutionError> {
+        let initial_witness = BTreeMap::from([(Witness(1), FieldElement::from(2_u128))]).into();
+        let mut acvm = ACVM::new(StubbedPwg, circuit.opcodes.clone(), initial_witness);
+        acvm.solve()?;
+        Ok(acvm.witness_map().clone())
+    }
+
+    #[test]
+    fn removes_opcodes_and_terms_not_needed_by_the_predicate() {
+        let (x, y, z, w) = (Witness(1), Witness(2), Witness(3), Witness(4));
+        let term = |coefficient: u128, witness: Witness| {
+            let mut expr = Expression::default();
+            expr.push_addition_term(FieldElement::from(coefficient), witness);
+            expr
+        };
+        let circuit = Circuit {
+            current_witness_index: 4,
+            opcodes: vec![
+                // y = x
+                Opcode::AssertZero(&term(1, y) - &term(1, x)),
+                // z = 2 * y
+                Opcode::AssertZero(&term(1, z) - &term(2, y)),
+                // w = x + 1
+                Opcode::AssertZero(&(&term(1, w) - &term(1, x)) - &Expression::one()),
+                // z = 5, which does not hold
+                Opcode::AssertZero(
+                    &term(1, z) - &Expression::from_field(FieldElement::from(5_u128)),
+                ),
+            ],
+            ..Circuit::default()
+        };
+
+        // The input alone is enough for a constraint not to be satisfied.
+        let minimized = minimize(&circuit, |circuit| {
+            matches!(solve(circuit), Err(OpcodeResolutionError::UnsatisfiedConstrain { .. }))
+        });
+        assert_eq!(minimized.opcode_indices, [0]);
+        assert_eq!(minimized.circuit.opcodes, [Opcode::AssertZero(-&term(1, x))]);
+
+        // Every term of the opcodes computing z is needed for it to be 4.
+        let satisfiable = Circuit { opcodes: circuit.opcodes[..3].to_vec(), ..circuit.clone() };
+        let minimized = minimize(&satisfiable, |circuit| {
+            solve(circuit)
+                .map_or(false, |solution| solution.get(&z) == Some(&FieldElement::from(4_u128)))
+        });
+        assert_eq!(minimized.opcode_indices, [0, 1]);
+        assert_eq!(minimized.circuit.opcodes, circuit.opcodes[..2]);
+    }
+}
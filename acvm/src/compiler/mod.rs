@@ -1,81 +1,318 @@
 use acir::{
-    circuit::{Circuit, Opcode},
-    native_types::{Expression, Witness},
+    circuit::{debug_info::DebugInfo, phases::Phase, Circuit, Opcode, OpcodeLocation},
+    native_types::{Expression, Witness, WitnessMap},
     BlackBoxFunc, FieldElement,
 };
 use indexmap::IndexMap;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
 use thiserror::Error;
 
 use crate::Language;
 
+mod brillig_lifting;
+mod capabilities;
 // The various passes that we can use over ACIR
+mod equivalence;
+mod minimizer;
 mod optimizers;
+mod partition;
+mod passes;
+mod sensitivity;
+mod strict;
 mod transformers;
 
-use optimizers::{GeneralOptimizer, RangeOptimizer};
+use optimizers::BooleanOptimizer;
 use transformers::{CSatTransformer, FallbackTransformer, R1CSTransformer};
 
+pub use brillig_lifting::{lift_brillig, LiftedBrillig};
+pub use capabilities::BackendCapabilities;
+#[cfg(feature = "testing")]
+pub(crate) use equivalence::SplitMix64;
+pub use equivalence::{check_equivalence, check_equivalence_on_inputs, EquivalenceError};
+pub use minimizer::{minimize, MinimizedCircuit};
 pub use optimizers::{CircuitSimplifier, SimplifyResult};
+pub use partition::{partition, CircuitPartition};
+pub use passes::{
+    AcirPass, BooleanPass, CommonSubexpressionPass, ConstantBrilligPass, ConstantFolderPass,
+    ExpressionWidthPass, GeneralOptimizerPass, PassManager, PassMetrics, PassReport,
+    RangeOptimizerPass,
+};
+pub(crate) use sensitivity::opcode_sensitivity;
+pub use sensitivity::{sensitivity, Sensitivity};
+pub(crate) use strict::check_deprecated_opcodes_indexed;
+pub use strict::{check_deprecated_opcodes, DeprecatedOpcodeError};
+pub use transformers::{FallbackReport, FallbackSubstitution};
 
 #[derive(PartialEq, Eq, Debug, Error)]
 pub enum CompileError {
     #[error("The blackbox function {0} is not supported by the backend and acvm does not have a fallback implementation")]
     UnsupportedBlackBox(BlackBoxFunc),
+    #[error(transparent)]
+    DeprecatedOpcode(#[from] DeprecatedOpcodeError),
 }
 
 /// Applies [`ProofSystemCompiler`][crate::ProofSystemCompiler] specific optimizations to a [`Circuit`].
+///
+/// Every black box function call which is not supported according to `capabilities` is replaced by its fallback,
+/// failing only if it has none. In [strict mode][BackendCapabilities::with_strict_mode], circuits using deprecated
+/// opcodes are rejected. Alongside the optimized circuit, returns the map relating its opcodes and witnesses
+/// to those of `acir` and the report of the fallbacks which were substituted.
+///
+/// Compilation is deterministic: no pass depends on randomness or on the iteration order of hashed collections,
+/// so that compiling the same circuit always produces the same bytes, as required to reproduce published
+/// verification keys.
 pub fn compile(
-    acir: Circuit,
-    np_language: Language,
-    is_opcode_supported: impl Fn(&Opcode) -> bool,
+    mut acir: Circuit,
+    capabilities: &BackendCapabilities,
     simplifier: &CircuitSimplifier,
-) -> Result<Circuit, CompileError> {
+) -> Result<(Circuit, AcirTransformationMap, FallbackReport), CompileError> {
+    if capabilities.strict {
+        check_deprecated_opcodes(&acir.opcodes)?;
+    }
+
     // Instantiate the optimizer.
     // Currently the optimizer and reducer are one in the same
     // for CSAT
 
+    // Debug information and phases are detached from the circuit while it is transformed
+    // and remapped onto the new opcode indices afterwards.
+    let debug_info = acir.debug_info.take();
+    let phases = std::mem::take(&mut acir.phases);
+
+    // Fallbacks replace black box calls with arithmetic opcodes, the outputs of which must still be
+    // assigned by the solver even though they no longer appear in any other kind of opcode.
+    let black_box_outputs: HashSet<Witness> = acir
+        .opcodes
+        .iter()
+        .filter_map(|opcode| match opcode {
+            Opcode::BlackBoxFuncCall(call) => Some(call.get_outputs_vec()),
+            _ => None,
+        })
+        .flatten()
+        .collect();
+    let abi_len = simplifier.abi_len();
+    let is_observable =
+        move |witness: Witness| witness.0 < abi_len || black_box_outputs.contains(&witness);
+
     // Fallback transformer pass
-    let acir = FallbackTransformer::transform(acir, is_opcode_supported, simplifier)?;
+    let (mut acir, transformation_map, fallback_report) =
+        FallbackTransformer::transform(acir, capabilities, simplifier)?;
 
-    // General optimizer pass
-    let mut opcodes: Vec<Opcode> = Vec::new();
-    for opcode in acir.opcodes {
-        match opcode {
-            Opcode::Arithmetic(arith_expr) => {
-                opcodes.push(Opcode::Arithmetic(GeneralOptimizer::optimize(arith_expr)))
+    // Optimization passes
+    let pass_manager = PassManager::with_default_passes(
+        ExpressionWidth::from(&capabilities.language),
+        is_observable,
+    );
+    let (pass_map, _metrics) = pass_manager.run(&mut acir);
+    let transformation_map = transformation_map.then(pass_map);
+
+    let acir =
+        attach_opcode_metadata(acir, debug_info, phases, transformation_map.opcode_indices());
+    Ok((acir, transformation_map, fallback_report))
+}
+
+/// The maximum number of witnesses which a single arithmetic opcode may refer to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExpressionWidth {
+    /// Expressions may contain any number of terms, as in R1CS.
+    Unbounded,
+    /// Expressions must fit within a single arithmetic gate of the given width.
+    ///
+    /// Widths below 3 are not supported.
+    Bounded(usize),
+}
+
+impl From<&Language> for ExpressionWidth {
+    fn from(language: &Language) -> Self {
+        match language {
+            Language::R1CS => ExpressionWidth::Unbounded,
+            Language::PLONKCSat { width } => ExpressionWidth::Bounded(*width),
+        }
+    }
+}
+
+/// Relates the opcodes and witnesses of a transformed circuit to those of the circuit it was derived from.
+///
+/// Every pass of the compiler updates the map, so that errors raised while solving the transformed circuit
+/// can be reported against the opcodes of the original circuit, and the witnesses it eliminated can be restored.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AcirTransformationMap {
+    /// Index in the original circuit of the opcode from which each opcode was derived.
+    opcode_indices: Vec<usize>,
+    /// Witnesses introduced by the transformation, mapped to the expression they are constrained to.
+    intermediate_witnesses: BTreeMap<Witness, Expression>,
+    /// Witnesses of the original circuit which were replaced by another witness holding the same value.
+    witness_renames: BTreeMap<Witness, Witness>,
+    /// Witnesses of the original circuit which were eliminated as they are constrained to a constant.
+    constant_witnesses: BTreeMap<Witness, FieldElement>,
+    /// Witnesses of the transformed circuit which are constrained to be either zero or one.
+    boolean_witnesses: BTreeSet<Witness>,
+}
+
+impl AcirTransformationMap {
+    /// Returns the map of a circuit where the opcode at each position was derived from the opcode
+    /// of the original circuit at the index given by `opcode_indices`.
+    pub fn new(opcode_indices: Vec<usize>) -> Self {
+        AcirTransformationMap { opcode_indices, ..AcirTransformationMap::default() }
+    }
+
+    /// Returns the map of applying the transformation described by `self` followed by the one described by `next`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `next` refers to an opcode which is not part of the circuit produced by `self`.
+    pub(crate) fn then(self, next: AcirTransformationMap) -> AcirTransformationMap {
+        let opcode_indices =
+            next.opcode_indices.iter().map(|index| self.opcode_indices[*index]).collect();
+        let mut intermediate_witnesses = self.intermediate_witnesses;
+        intermediate_witnesses.extend(next.intermediate_witnesses);
+        let mut witness_renames = self.witness_renames;
+        witness_renames.extend(next.witness_renames);
+        let mut constant_witnesses = self.constant_witnesses;
+        constant_witnesses.extend(next.constant_witnesses);
+        // Boolean witnesses which were eliminated by `next` no longer belong to the transformed circuit.
+        let mut boolean_witnesses: BTreeSet<Witness> = self
+            .boolean_witnesses
+            .into_iter()
+            .filter(|witness| {
+                !witness_renames.contains_key(witness) && !constant_witnesses.contains_key(witness)
+            })
+            .collect();
+        boolean_witnesses.extend(next.boolean_witnesses);
+        AcirTransformationMap {
+            opcode_indices,
+            intermediate_witnesses,
+            witness_renames,
+            constant_witnesses,
+            boolean_witnesses,
+        }
+    }
+
+    /// Returns the index in the original circuit of the opcode from which each opcode of the transformed
+    /// circuit was derived.
+    pub fn opcode_indices(&self) -> &[usize] {
+        &self.opcode_indices
+    }
+
+    /// Returns the index in the original circuit of the opcode from which the opcode at `index`
+    /// in the transformed circuit was derived.
+    pub fn original_opcode_index(&self, index: usize) -> Option<usize> {
+        self.opcode_indices.get(index).copied()
+    }
+
+    /// Returns the location in the original circuit of the opcode at `location` in the transformed circuit.
+    ///
+    /// Brillig opcodes are never transformed, so the location within a Brillig opcode is kept as is.
+    pub fn original_location(&self, location: OpcodeLocation) -> Option<OpcodeLocation> {
+        match location {
+            OpcodeLocation::Acir(index) => {
+                self.original_opcode_index(index).map(OpcodeLocation::Acir)
             }
-            other_gate => opcodes.push(other_gate),
-        };
+            OpcodeLocation::Brillig { acir_index, brillig_index } => self
+                .original_opcode_index(acir_index)
+                .map(|acir_index| OpcodeLocation::Brillig { acir_index, brillig_index }),
+        }
     }
-    let acir = Circuit { opcodes, ..acir };
 
-    // Range optimization pass
-    let range_optimizer = RangeOptimizer::new(acir);
-    let acir = range_optimizer.replace_redundant_ranges();
+    /// Returns the witnesses introduced by the transformation, each mapped to the expression it is equal to.
+    ///
+    /// An expression refers to witnesses of the original circuit or to intermediate witnesses with a lower index,
+    /// so evaluating them in order assigns every intermediate witness.
+    pub fn intermediate_witnesses(&self) -> &BTreeMap<Witness, Expression> {
+        &self.intermediate_witnesses
+    }
+
+    /// Returns the witnesses of the original circuit which no longer appear in the transformed circuit,
+    /// each mapped to the witness holding the same value.
+    ///
+    /// The witness it is mapped to may itself have been renamed or eliminated.
+    pub fn witness_renames(&self) -> &BTreeMap<Witness, Witness> {
+        &self.witness_renames
+    }
+
+    /// Returns the witnesses of the original circuit which no longer appear in the transformed circuit
+    /// as they are constrained to a constant, each mapped to its value.
+    pub fn constant_witnesses(&self) -> &BTreeMap<Witness, FieldElement> {
+        &self.constant_witnesses
+    }
 
-    let transformer = match &np_language {
-        crate::Language::R1CS => {
+    /// Returns the witnesses of the transformed circuit which are known to be constrained to be either zero or one.
+    pub fn boolean_witnesses(&self) -> &BTreeSet<Witness> {
+        &self.boolean_witnesses
+    }
+
+    /// Assigns the witnesses eliminated by the transformation in `witness_map`, the solution of the transformed
+    /// circuit, so that it also holds every witness of the original circuit.
+    pub fn restore_witnesses(&self, witness_map: &mut WitnessMap) {
+        for (witness, value) in &self.constant_witnesses {
+            witness_map.insert(*witness, *value);
+        }
+        for witness in self.witness_renames.keys() {
+            let mut replacement = *witness;
+            while let Some(next) = self.witness_renames.get(&replacement) {
+                replacement = *next;
+            }
+            let value = witness_map
+                .get(&replacement)
+                .or_else(|| self.constant_witnesses.get(&replacement))
+                .copied();
+            if let Some(value) = value {
+                witness_map.insert(*witness, value);
+            }
+        }
+    }
+}
+
+/// Splits the arithmetic opcodes of `acir` which do not fit within `expression_width` into chains of
+/// opcodes which do, by introducing intermediate witnesses to hold partial sums.
+///
+/// Other opcodes are left unchanged. Alongside the transformed circuit, returns the map relating its opcodes
+/// to those of `acir` and its intermediate witnesses to the expressions they hold.
+///
+/// The squares of witnesses constrained to be boolean are first replaced by the witnesses themselves,
+/// as done by the [`BooleanPass`], so that they do not take part in the split.
+///
+/// # Panics
+///
+/// Panics if `expression_width` is bounded below 3.
+pub fn transform(
+    acir: Circuit,
+    expression_width: ExpressionWidth,
+) -> (Circuit, AcirTransformationMap) {
+    let transformation_map = AcirTransformationMap::new((0..acir.opcodes.len()).collect());
+    let (acir, transformation_map) =
+        BooleanOptimizer::new(acir).simplify_boolean_terms(transformation_map);
+    transform_internal(acir, expression_width, transformation_map)
+}
+
+/// Same as [`transform`], where `transformation_map` already relates `acir` to the original circuit
+/// it was derived from.
+fn transform_internal(
+    acir: Circuit,
+    expression_width: ExpressionWidth,
+    transformation_map: AcirTransformationMap,
+) -> (Circuit, AcirTransformationMap) {
+    let transformer = match expression_width {
+        ExpressionWidth::Unbounded => {
             let transformer = R1CSTransformer::new(acir);
-            return Ok(transformer.transform());
+            return (transformer.transform(), transformation_map);
         }
-        crate::Language::PLONKCSat { width } => CSatTransformer::new(*width),
+        ExpressionWidth::Bounded(width) => CSatTransformer::new(width),
     };
 
-    // TODO: the code below is only for CSAT transformer
-    // TODO it may be possible to refactor it in a way that we do not need to return early from the r1cs
-    // TODO or at the very least, we could put all of it inside of CSatOptimizer pass
-
     // Optimize the arithmetic gates by reducing them into the correct width and
     // creating intermediate variables when necessary
     let mut transformed_gates = Vec::new();
+    let mut new_opcode_indices = Vec::new();
+    let mut intermediate_witnesses = transformation_map.intermediate_witnesses;
 
     let mut next_witness_index = acir.current_witness_index + 1;
     // maps a normalized expression to the intermediate variable which represents the expression, along with its 'norm'
     // the 'norm' is simply the value of the first non zero coefficient in the expression, taken from the linear terms, or quadratic terms if there is none.
     let mut intermediate_variables: IndexMap<Expression, (FieldElement, Witness)> = IndexMap::new();
-    for opcode in acir.opcodes {
+    for (opcode, index) in acir.opcodes.into_iter().zip(transformation_map.opcode_indices) {
         match opcode {
-            Opcode::Arithmetic(arith_expr) => {
+            Opcode::AssertZero(arith_expr) => {
                 let len = intermediate_variables.len();
 
                 let arith_expr = transformer.transform(
@@ -90,6 +327,7 @@ pub fn compile(
                 for (g, (norm, w)) in intermediate_variables.iter().skip(len) {
                     // de-normalize
                     let mut intermediate_gate = g * *norm;
+                    intermediate_witnesses.insert(*w, intermediate_gate.clone());
                     // constrain the intermediate gate to the intermediate variable
                     intermediate_gate.linear_combinations.push((-FieldElement::one(), *w));
                     intermediate_gate.sort();
@@ -98,20 +336,180 @@ pub fn compile(
                 new_gates.push(arith_expr);
                 new_gates.sort();
                 for gate in new_gates {
-                    transformed_gates.push(Opcode::Arithmetic(gate));
+                    transformed_gates.push(Opcode::AssertZero(gate));
+                    new_opcode_indices.push(index);
                 }
             }
-            other_gate => transformed_gates.push(other_gate),
+            other_gate => {
+                transformed_gates.push(other_gate);
+                new_opcode_indices.push(index);
+            }
         }
     }
 
     let current_witness_index = next_witness_index - 1;
 
-    Ok(Circuit {
+    let acir = Circuit {
         current_witness_index,
         opcodes: transformed_gates,
         // The optimizer does not add new public inputs
         public_parameters: acir.public_parameters,
         return_values: acir.return_values,
-    })
+        debug_info: None,
+        phases: Vec::new(),
+    };
+    let transformation_map = AcirTransformationMap {
+        opcode_indices: new_opcode_indices,
+        intermediate_witnesses,
+        ..transformation_map
+    };
+    (acir, transformation_map)
+}
+
+/// Reattaches `debug_info` and `phases` to the transformed circuit, where `opcode_indices` maps each
+/// opcode of `acir` to the index of the opcode of the original circuit it was derived from.
+fn attach_opcode_metadata(
+    mut acir: Circuit,
+    debug_info: Option<DebugInfo>,
+    mut phases: Vec<Phase>,
+    opcode_indices: &[usize],
+) -> Circuit {
+    acir.debug_info = debug_info.map(|mut debug_info| {
+        debug_info.update_acir(opcode_indices);
+        debug_info
+    });
+    for phase in &mut phases {
+        phase.update_acir(opcode_indices);
+    }
+    acir.phases = phases;
+    acir
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{compile, transform, BackendCapabilities, CircuitSimplifier, ExpressionWidth};
+    use crate::Language;
+    use acir::{
+        circuit::{
+            opcodes::{BlackBoxFuncCall, FunctionInput},
+            Circuit, Opcode, OpcodeLocation,
+        },
+        native_types::{Expression, Witness, WitnessMap},
+        FieldElement,
+    };
+    use std::collections::BTreeMap;
+
+    fn evaluate(expr: &Expression, values: &BTreeMap<Witness, FieldElement>) -> FieldElement {
+        let mul_terms = expr
+            .mul_terms
+            .iter()
+            .map(|(coefficient, lhs, rhs)| *coefficient * values[lhs] * values[rhs]);
+        let linear_terms = expr
+            .linear_combinations
+            .iter()
+            .map(|(coefficient, witness)| *coefficient * values[witness]);
+        mul_terms.chain(linear_terms).fold(expr.q_c, |sum, term| sum + term)
+    }
+
+    #[test]
+    fn splits_wide_expressions_into_chains() {
+        // 1 * 2 + 3 + 4 + 5 + 6 - 7 = 0
+        let mut expr = Expression::default();
+        expr.push_multiplication_term(FieldElement::one(), Witness(1), Witness(2));
+        for index in 3..=6 {
+            expr.push_addition_term(FieldElement::one(), Witness(index));
+        }
+        expr.push_addition_term(-FieldElement::one(), Witness(7));
+        let range = Opcode::BlackBoxFuncCall(BlackBoxFuncCall::RANGE {
+            input: FunctionInput { witness: Witness(7), num_bits: 32 },
+        });
+        let circuit = Circuit {
+            current_witness_index: 7,
+            opcodes: vec![range.clone(), Opcode::AssertZero(expr.clone())],
+            ..Circuit::default()
+        };
+
+        let (unbounded_circuit, transformation_map) =
+            transform(circuit.clone(), ExpressionWidth::Unbounded);
+        assert_eq!(unbounded_circuit, circuit);
+        assert_eq!(transformation_map.opcode_indices(), [0, 1]);
+        assert!(transformation_map.intermediate_witnesses().is_empty());
+
+        let (bounded_circuit, transformation_map) = transform(circuit, ExpressionWidth::Bounded(3));
+        assert_eq!(bounded_circuit.opcodes[0], range);
+        let num_opcodes = bounded_circuit.opcodes.len();
+        assert!(num_opcodes > 2, "expression should be split");
+        assert_eq!(transformation_map.original_opcode_index(0), Some(0));
+        assert!((1..num_opcodes)
+            .all(|index| transformation_map.original_opcode_index(index) == Some(1)));
+
+        // Assigning the intermediate witnesses the values of their expressions satisfies every opcode.
+        let mut values: BTreeMap<Witness, FieldElement> =
+            (1..=6).map(|index| (Witness(index), FieldElement::from(index as u128))).collect();
+        values.insert(Witness(7), FieldElement::from(2 + 3 + 4 + 5 + 6_u128));
+        for (witness, expr) in transformation_map.intermediate_witnesses() {
+            assert!((8..=bounded_circuit.current_witness_index).contains(&witness.witness_index()));
+            let value = evaluate(expr, &values);
+            values.insert(*witness, value);
+        }
+        for opcode in &bounded_circuit.opcodes[1..] {
+            let expr = match opcode {
+                Opcode::AssertZero(expr) => expr,
+                _ => panic!("expected an arithmetic opcode"),
+            };
+            assert!(expr.fits_in_one_identity(3));
+            assert!(evaluate(expr, &values).is_zero());
+        }
+    }
+
+    #[test]
+    fn tracks_provenance_through_every_pass() {
+        // `x` and `w` belong to the ABI while `c`, `y` and `z` are intermediate witnesses.
+        let (x, w, c, y, z) = (Witness(1), Witness(2), Witness(3), Witness(4), Witness(5));
+        let mut c_definition = Expression::from(c);
+        c_definition.q_c = -FieldElement::from(3_u128);
+        let square = |output: Witness| {
+            let mut expr = Expression::default();
+            expr.push_multiplication_term(FieldElement::one(), x, x);
+            expr.push_addition_term(-FieldElement::one(), output);
+            Opcode::AssertZero(expr)
+        };
+        let mut sum = Expression::from(y);
+        sum.push_addition_term(FieldElement::one(), z);
+        sum.push_addition_term(FieldElement::one(), c);
+        sum.push_addition_term(-FieldElement::one(), w);
+        let circuit = Circuit {
+            current_witness_index: 5,
+            opcodes: vec![
+                Opcode::AssertZero(c_definition),
+                square(y),
+                square(z),
+                Opcode::AssertZero(sum),
+            ],
+            ..Circuit::default()
+        };
+
+        let capabilities = BackendCapabilities::new(Language::R1CS);
+        let (circuit, transformation_map, _) =
+            compile(circuit, &capabilities, &CircuitSimplifier::new(3)).unwrap();
+
+        // The definition of `c` is folded into the sum, while that of `z` is merged with the one of `y`.
+        assert_eq!(circuit.opcodes.len(), 2);
+        assert_eq!(transformation_map.opcode_indices(), [1, 3]);
+        assert_eq!(
+            transformation_map
+                .original_location(OpcodeLocation::Brillig { acir_index: 1, brillig_index: 4 }),
+            Some(OpcodeLocation::Brillig { acir_index: 3, brillig_index: 4 })
+        );
+        assert_eq!(transformation_map.original_location(OpcodeLocation::Acir(2)), None);
+
+        let mut witness_map = WitnessMap::from(BTreeMap::from([
+            (x, FieldElement::from(2_u128)),
+            (y, FieldElement::from(4_u128)),
+            (w, FieldElement::from(11_u128)),
+        ]));
+        transformation_map.restore_witnesses(&mut witness_map);
+        assert_eq!(witness_map[&c], FieldElement::from(3_u128));
+        assert_eq!(witness_map[&z], FieldElement::from(4_u128));
+    }
 }
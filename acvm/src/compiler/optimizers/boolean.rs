@@ -0,0 +1,228 @@
+use acir::{
+    circuit::{opcodes::BlackBoxFuncCall, Circuit, Opcode},
+    native_types::{Expression, Witness},
+    FieldElement,
+};
+use std::collections::BTreeSet;
+
+use crate::compiler::AcirTransformationMap;
+
+/// `BooleanOptimizer` simplifies the arithmetic opcodes using the witnesses which are constrained to be boolean.
+///
+/// # Example
+///
+/// Suppose a witness `b` is constrained to be boolean and used to select between `x` and `y`:
+///
+/// ```text
+/// b*b - b = 0
+/// b*b + b*x - b*y - z = 0
+/// ```
+/// As `b*b = b`, the second opcode is rewritten to `b + b*x - b*y - z = 0`, which has one less
+/// multiplication term and is cheaper to split into opcodes of a bounded width.
+///
+/// A witness is boolean if it is constrained by `b*b - b = 0` (up to a factor) or by a 1-bit range constraint.
+/// Witnesses constrained to be equal to a boolean witness `b`, or to `1 - b`, are boolean as well.
+/// The opcodes establishing that a witness is boolean are left untouched, as simplifying them would
+/// remove the constraint.
+pub(crate) struct BooleanOptimizer {
+    circuit: Circuit,
+}
+
+impl BooleanOptimizer {
+    pub(crate) fn new(circuit: Circuit) -> Self {
+        Self { circuit }
+    }
+
+    /// Returns a `Circuit` where the squares of boolean witnesses are replaced by the witnesses themselves.
+    ///
+    /// `transformation_map` relates the circuit to the original one,
+    /// the returned map does the same for the optimized circuit and records the boolean witnesses.
+    pub(crate) fn simplify_boolean_terms(
+        mut self,
+        mut transformation_map: AcirTransformationMap,
+    ) -> (Circuit, AcirTransformationMap) {
+        let boolean_witnesses = boolean_witnesses(&self.circuit);
+
+        for opcode in &mut self.circuit.opcodes {
+            match opcode {
+                Opcode::AssertZero(expr) if boolean_definition(expr).is_none() => {
+                    simplify_squares(expr, &boolean_witnesses);
+                }
+                _ => (),
+            }
+        }
+
+        transformation_map.boolean_witnesses.extend(boolean_witnesses);
+        (self.circuit, transformation_map)
+    }
+}
+
+/// Returns the witnesses of `circuit` which are constrained to be either zero or one.
+fn boolean_witnesses(circuit: &Circuit) -> BTreeSet<Witness> {
+    let mut boolean_witnesses: BTreeSet<Witness> = circuit
+        .opcodes
+        .iter()
+        .flat_map(|opcode| match opcode {
+            Opcode::AssertZero(expr) => boolean_definition(expr).into_iter().collect(),
+            Opcode::BlackBoxFuncCall(BlackBoxFuncCall::RANGE { input }) if input.num_bits == 1 => {
+                vec![input.witness]
+            }
+            Opcode::BlackBoxFuncCall(BlackBoxFuncCall::RangeBatch { inputs }) => inputs
+                .iter()
+                .filter(|input| input.num_bits == 1)
+                .map(|input| input.witness)
+                .collect(),
+            _ => Vec::new(),
+        })
+        .collect();
+
+    // Booleanity propagates through copies and negations until no new boolean witness is found.
+    loop {
+        let propagated: Vec<Witness> = circuit
+            .opcodes
+            .iter()
+            .filter_map(|opcode| match opcode {
+                Opcode::AssertZero(expr) => propagated_boolean(expr, &boolean_witnesses),
+                _ => None,
+            })
+            .collect();
+        let num_boolean_witnesses = boolean_witnesses.len();
+        boolean_witnesses.extend(propagated);
+        if boolean_witnesses.len() == num_boolean_witnesses {
+            return boolean_witnesses;
+        }
+    }
+}
+
+/// Returns the witness `b` if `expr` is the constraint `c*b*b - c*b = 0`.
+fn boolean_definition(expr: &Expression) -> Option<Witness> {
+    match (expr.mul_terms.as_slice(), expr.linear_combinations.as_slice()) {
+        ([(square_coefficient, lhs, rhs)], [(coefficient, witness)])
+            if lhs == rhs
+                && lhs == witness
+                && !square_coefficient.is_zero()
+                && *coefficient == -*square_coefficient
+                && expr.q_c.is_zero() =>
+        {
+            Some(*witness)
+        }
+        _ => None,
+    }
+}
+
+/// Returns the witness constrained by `expr` to be equal to a boolean witness `b` or to `1 - b`,
+/// if it is not already known to be boolean.
+fn propagated_boolean(expr: &Expression, boolean_witnesses: &BTreeSet<Witness>) -> Option<Witness> {
+    if !expr.mul_terms.is_empty() {
+        return None;
+    }
+    let [(lhs_coefficient, lhs), (rhs_coefficient, rhs)] = match expr.linear_combinations.as_slice()
+    {
+        [lhs, rhs] => [*lhs, *rhs],
+        _ => return None,
+    };
+    let (coefficient, witness, boolean_coefficient) =
+        match (boolean_witnesses.contains(&lhs), boolean_witnesses.contains(&rhs)) {
+            (true, false) => (rhs_coefficient, rhs, lhs_coefficient),
+            (false, true) => (lhs_coefficient, lhs, rhs_coefficient),
+            _ => return None,
+        };
+    if coefficient.is_zero() || boolean_coefficient.is_zero() {
+        return None;
+    }
+
+    // witness = factor * boolean + offset
+    let factor = -boolean_coefficient / coefficient;
+    let offset = -expr.q_c / coefficient;
+    let is_copy = factor.is_one() && offset.is_zero();
+    let is_negation = factor == -FieldElement::one() && offset.is_one();
+    (is_copy || is_negation).then_some(witness)
+}
+
+/// Replaces every term `c*b*b` of `expr` where `b` is a boolean witness with the linear term `c*b`.
+fn simplify_squares(expr: &mut Expression, boolean_witnesses: &BTreeSet<Witness>) {
+    let is_boolean_square = |(_, lhs, rhs): &(FieldElement, Witness, Witness)| {
+        lhs == rhs && boolean_witnesses.contains(lhs)
+    };
+    if !expr.mul_terms.iter().any(is_boolean_square) {
+        return;
+    }
+
+    let (squares, mul_terms): (Vec<_>, Vec<_>) =
+        std::mem::take(&mut expr.mul_terms).into_iter().partition(is_boolean_square);
+    expr.mul_terms = mul_terms.into_iter().collect();
+
+    for (coefficient, witness, _) in squares {
+        match expr.linear_combinations.iter_mut().find(|(_, term)| *term == witness) {
+            Some((linear_coefficient, _)) => *linear_coefficient += coefficient,
+            None => expr.linear_combinations.push((coefficient, witness)),
+        }
+    }
+    expr.linear_combinations.retain(|(coefficient, _)| !coefficient.is_zero());
+    expr.sort();
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::compiler::{optimizers::BooleanOptimizer, AcirTransformationMap};
+    use acir::{
+        circuit::{
+            opcodes::{BlackBoxFuncCall, FunctionInput},
+            Circuit, Opcode,
+        },
+        native_types::{Expression, Witness},
+        FieldElement,
+    };
+    use std::collections::BTreeSet;
+
+    fn boolean_constraint(witness: Witness) -> Opcode {
+        let mut expr = Expression::default();
+        expr.push_multiplication_term(FieldElement::one(), witness, witness);
+        expr.push_addition_term(-FieldElement::one(), witness);
+        Opcode::AssertZero(expr)
+    }
+
+    #[test]
+    fn replaces_squares_of_boolean_witnesses() {
+        let (b, c, d, x, z) = (Witness(1), Witness(2), Witness(3), Witness(4), Witness(5));
+        // d = 1 - c
+        let mut negation = Expression::from(d);
+        negation.push_addition_term(FieldElement::one(), c);
+        negation.q_c = -FieldElement::one();
+        // b*b + c*c + d*d + x*x + b - z = 0
+        let mut expr = Expression::default();
+        for witness in [b, c, d, x] {
+            expr.push_multiplication_term(FieldElement::one(), witness, witness);
+        }
+        expr.push_addition_term(FieldElement::one(), b);
+        expr.push_addition_term(-FieldElement::one(), z);
+
+        let circuit = Circuit {
+            current_witness_index: 5,
+            opcodes: vec![
+                boolean_constraint(b),
+                Opcode::BlackBoxFuncCall(BlackBoxFuncCall::RANGE {
+                    input: FunctionInput { witness: c, num_bits: 1 },
+                }),
+                Opcode::AssertZero(negation),
+                Opcode::AssertZero(expr),
+            ],
+            ..Circuit::default()
+        };
+
+        let (optimized_circuit, transformation_map) = BooleanOptimizer::new(circuit.clone())
+            .simplify_boolean_terms(AcirTransformationMap::new((0..4).collect()));
+
+        assert_eq!(transformation_map.boolean_witnesses(), &BTreeSet::from([b, c, d]));
+        // The definition of `b` is kept as is.
+        assert_eq!(optimized_circuit.opcodes[..3], circuit.opcodes[..3]);
+        let mut expected = Expression::default();
+        expected.push_multiplication_term(FieldElement::one(), x, x);
+        expected.push_addition_term(FieldElement::from(2_u128), b);
+        expected.push_addition_term(FieldElement::one(), c);
+        expected.push_addition_term(FieldElement::one(), d);
+        expected.push_addition_term(-FieldElement::one(), z);
+        expected.sort();
+        assert_eq!(optimized_circuit.opcodes[3], Opcode::AssertZero(expected));
+    }
+}
@@ -0,0 +1,253 @@
+use acir::{
+    circuit::{
+        visitor::{Visitor, VisitorMut},
+        Circuit, Opcode,
+    },
+    native_types::{Expression, Witness},
+};
+use std::collections::{hash_map::Entry, HashMap, HashSet};
+
+use crate::compiler::AcirTransformationMap;
+
+/// `CommonSubexpressionOptimizer` merges intermediate witnesses which are defined by identical expressions.
+///
+/// # Example
+///
+/// Suppose the compiler emitted the following opcodes, where `y` and `z` are intermediate witnesses:
+///
+/// ```text
+/// x * x - y = 0
+/// 2 * x * x - 2 * z = 0
+/// y + z - w = 0
+/// ```
+/// Both `y` and `z` are equal to `x * x`, so this optimization pass removes the second opcode
+/// and replaces `z` with `y` in the opcodes which follow it.
+///
+/// An arithmetic opcode defines a witness if it is the first opcode to mention it and the witness
+/// is the only unknown of the opcode, appearing within a single linear term. Observable witnesses,
+/// such as those of the ABI, public inputs and return values, are never replaced as their values are
+/// provided to or expected from the solver.
+pub(crate) struct CommonSubexpressionOptimizer {
+    circuit: Circuit,
+}
+
+impl CommonSubexpressionOptimizer {
+    pub(crate) fn new(circuit: Circuit) -> Self {
+        Self { circuit }
+    }
+
+    /// Returns a `Circuit` where no two arithmetic opcodes define intermediate witnesses
+    /// through the same expression.
+    ///
+    /// `is_observable` returns whether a witness must be assigned by the solver, such as those of the ABI.
+    /// `transformation_map` relates the circuit to the original one, the returned map does the same for
+    /// the optimized circuit and records the witness replacing each of the merged witnesses.
+    pub(crate) fn eliminate_common_subexpressions(
+        mut self,
+        is_observable: impl Fn(Witness) -> bool,
+        transformation_map: AcirTransformationMap,
+    ) -> (Circuit, AcirTransformationMap) {
+        let is_intermediate = |witness: Witness| {
+            !is_observable(witness)
+                && !self.circuit.public_parameters.0.contains(&witness)
+                && !self.circuit.return_values.0.contains(&witness)
+        };
+
+        let opcodes = std::mem::take(&mut self.circuit.opcodes);
+        let mut optimized_opcodes = Vec::with_capacity(opcodes.len());
+        let mut new_opcode_indices = Vec::with_capacity(opcodes.len());
+
+        let mut seen_witnesses = WitnessCollector::default();
+        let mut replacer = WitnessReplacer::default();
+        // Maps a normalized expression to the witness it first defined.
+        let mut definitions: HashMap<Expression, Witness> = HashMap::new();
+        for (mut opcode, index) in opcodes.into_iter().zip(transformation_map.opcode_indices) {
+            replacer.visit_opcode_mut(&mut opcode);
+
+            if let Opcode::AssertZero(expr) = &opcode {
+                let is_unknown = |witness: Witness| {
+                    !seen_witnesses.0.contains(&witness) && is_intermediate(witness)
+                };
+                if let Some((witness, definition)) = defined_witness(expr, is_unknown) {
+                    match definitions.entry(definition) {
+                        Entry::Occupied(entry) => {
+                            // The opcode is redundant as its witness is equal to the one already defined.
+                            replacer.0.insert(witness, *entry.get());
+                            continue;
+                        }
+                        Entry::Vacant(entry) => {
+                            entry.insert(witness);
+                        }
+                    }
+                }
+            }
+
+            seen_witnesses.visit_opcode(&opcode);
+            optimized_opcodes.push(opcode);
+            new_opcode_indices.push(index);
+        }
+
+        let mut witness_renames = transformation_map.witness_renames;
+        witness_renames.extend(replacer.0);
+        let transformation_map = AcirTransformationMap {
+            opcode_indices: new_opcode_indices,
+            witness_renames,
+            ..transformation_map
+        };
+        (Circuit { opcodes: optimized_opcodes, ..self.circuit }, transformation_map)
+    }
+}
+
+/// Returns the witness defined by `expr` along with the normalized expression it is equal to,
+/// or `None` if `expr` does not contain exactly one unknown witness within a linear term.
+fn defined_witness(
+    expr: &Expression,
+    is_unknown: impl Fn(Witness) -> bool,
+) -> Option<(Witness, Expression)> {
+    if expr.mul_terms.iter().any(|&(_, lhs, rhs)| is_unknown(lhs) || is_unknown(rhs)) {
+        return None;
+    }
+    let mut unknown_terms =
+        expr.linear_combinations.iter().filter(|(_, witness)| is_unknown(*witness));
+    let (coefficient, witness) = *unknown_terms.next()?;
+    if unknown_terms.next().is_some() || coefficient.is_zero() {
+        return None;
+    }
+
+    // `expr` is `definition + coefficient * witness`, hence `witness = -definition / coefficient`.
+    let mut definition = expr.clone();
+    definition.linear_combinations.retain(|(_, term)| *term != witness);
+    let mut definition = &definition * -coefficient.inverse();
+    definition.sort();
+    Some((witness, definition))
+}
+
+/// Collects every witness mentioned by the opcodes it visits.
+#[derive(Default)]
+pub(super) struct WitnessCollector(pub(super) HashSet<Witness>);
+
+impl Visitor for WitnessCollector {
+    fn visit_witness(&mut self, witness: &Witness) {
+        self.0.insert(*witness);
+    }
+}
+
+/// Replaces witnesses with the witness first defined by the same expression.
+#[derive(Default)]
+struct WitnessReplacer(HashMap<Witness, Witness>);
+
+impl VisitorMut for WitnessReplacer {
+    fn visit_witness_mut(&mut self, witness: &mut Witness) {
+        if let Some(replacement) = self.0.get(witness) {
+            *witness = *replacement;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::compiler::{optimizers::CommonSubexpressionOptimizer, AcirTransformationMap};
+    use acir::{
+        circuit::{
+            opcodes::{BlackBoxFuncCall, FunctionInput},
+            Circuit, Opcode, PublicInputs,
+        },
+        native_types::{Expression, Witness},
+        FieldElement,
+    };
+
+    fn test_circuit(opcodes: Vec<Opcode>) -> Circuit {
+        Circuit { current_witness_index: 10, opcodes, ..Circuit::default() }
+    }
+
+    /// Returns an opcode constraining `witness` to `scale * x * x`.
+    fn square(x: Witness, witness: Witness, scale: u128) -> Opcode {
+        let scale = FieldElement::from(scale);
+        let mut expr = Expression::default();
+        expr.push_multiplication_term(scale, x, x);
+        expr.push_addition_term(-scale, witness);
+        Opcode::AssertZero(expr)
+    }
+
+    fn range(witness: Witness) -> Opcode {
+        Opcode::BlackBoxFuncCall(BlackBoxFuncCall::RANGE {
+            input: FunctionInput { witness, num_bits: 8 },
+        })
+    }
+
+    #[test]
+    fn merges_witnesses_defined_by_identical_expressions() {
+        let (x, y, z) = (Witness(1), Witness(2), Witness(3));
+        let circuit = test_circuit(vec![square(x, y, 1), square(x, z, 2), range(z)]);
+
+        let optimizer = CommonSubexpressionOptimizer::new(circuit);
+        let (optimized_circuit, transformation_map) = optimizer.eliminate_common_subexpressions(
+            |witness| witness == x,
+            AcirTransformationMap::new(vec![0, 1, 2]),
+        );
+
+        assert_eq!(optimized_circuit.opcodes, vec![square(x, y, 1), range(y)]);
+        assert_eq!(transformation_map.opcode_indices(), [0, 2]);
+        assert_eq!(transformation_map.witness_renames().iter().collect::<Vec<_>>(), vec![(&z, &y)]);
+    }
+
+    #[test]
+    fn keeps_constraints_on_known_witnesses() {
+        let (x, y, z) = (Witness(1), Witness(2), Witness(3));
+        // `z` is first used by the range constraint, so the second opcode is a constraint rather than its definition.
+        let circuit = test_circuit(vec![square(x, y, 1), range(z), square(x, z, 1)]);
+        let optimizer = CommonSubexpressionOptimizer::new(circuit.clone());
+        let (optimized_circuit, _) = optimizer.eliminate_common_subexpressions(
+            |witness| witness == x,
+            AcirTransformationMap::new(vec![0, 1, 2]),
+        );
+        assert_eq!(optimized_circuit.opcodes, circuit.opcodes);
+
+        // Witnesses of the ABI and return values are never replaced.
+        let circuit = test_circuit(vec![square(x, y, 1), square(x, z, 1)]);
+        let optimizer = CommonSubexpressionOptimizer::new(circuit.clone());
+        let (optimized_circuit, _) = optimizer.eliminate_common_subexpressions(
+            |witness| witness != y,
+            AcirTransformationMap::new(vec![0, 1]),
+        );
+        assert_eq!(optimized_circuit.opcodes, circuit.opcodes);
+
+        let circuit = Circuit {
+            return_values: PublicInputs([z].into_iter().collect()),
+            ..test_circuit(vec![square(x, y, 1), square(x, z, 1)])
+        };
+        let optimizer = CommonSubexpressionOptimizer::new(circuit.clone());
+        let (optimized_circuit, _) = optimizer.eliminate_common_subexpressions(
+            |witness| witness == x,
+            AcirTransformationMap::new(vec![0, 1]),
+        );
+        assert_eq!(optimized_circuit.opcodes, circuit.opcodes);
+    }
+
+    #[test]
+    fn merges_definitions_which_become_identical_after_replacement() {
+        let (x, y, z, u, v) = (Witness(1), Witness(2), Witness(3), Witness(4), Witness(5));
+        let increment = |input: Witness, output: Witness| {
+            let mut expr = Expression::from(input);
+            expr.q_c = FieldElement::one();
+            expr.push_addition_term(-FieldElement::one(), output);
+            Opcode::AssertZero(expr)
+        };
+        let circuit = test_circuit(vec![
+            square(x, y, 1),
+            square(x, z, 1),
+            increment(y, u),
+            increment(z, v),
+            range(v),
+        ]);
+
+        let optimizer = CommonSubexpressionOptimizer::new(circuit);
+        let (optimized_circuit, transformation_map) = optimizer.eliminate_common_subexpressions(
+            |witness| witness == x,
+            AcirTransformationMap::new((0..5).collect()),
+        );
+
+        assert_eq!(optimized_circuit.opcodes, vec![square(x, y, 1), increment(y, u), range(u)]);
+        assert_eq!(transformation_map.opcode_indices(), [0, 2, 4]);
+    }
+}
@@ -0,0 +1,230 @@
+use acir::{
+    circuit::{
+        brillig::{Brillig, BrilligOutputs},
+        Circuit, Opcode,
+    },
+    native_types::{Expression, Witness, WitnessMap},
+    FieldElement,
+};
+
+use crate::{
+    compiler::AcirTransformationMap,
+    pwg::brillig::{BrilligResolution, BrilligSolver},
+};
+
+/// `ConstantBrilligExecutor` executes the Brillig opcodes whose inputs are known at compile time.
+///
+/// # Example
+///
+/// Suppose a Brillig opcode computes a lookup table from a constant seed `s`:
+///
+/// ```text
+/// s - 7 = 0
+/// BRILLIG: inputs: [s] outputs: [t0, t1, t2]
+/// ```
+/// Its outputs only depend on `s`, so the bytecode is run once by the compiler and the opcode replaced with
+/// the arithmetic opcodes `t0 - v0 = 0`, `t1 - v1 = 0` and `t2 - v2 = 0` assigning the values it computed.
+///
+/// The inputs of a Brillig opcode are known when they are constant expressions, or only refer to witnesses
+/// constrained to constants by an arithmetic opcode or assigned by a previously executed Brillig opcode.
+/// Opcodes which fail or make foreign calls which are yet to be resolved are left to the solver, so that errors
+/// are still reported when the circuit is executed.
+pub(crate) struct ConstantBrilligExecutor {
+    circuit: Circuit,
+}
+
+impl ConstantBrilligExecutor {
+    pub(crate) fn new(circuit: Circuit) -> Self {
+        Self { circuit }
+    }
+
+    /// Returns a `Circuit` where every Brillig opcode whose inputs are known is replaced by
+    /// the assignment of its outputs, along with the number of Brillig opcodes which were executed.
+    ///
+    /// `transformation_map` relates the circuit to the original one,
+    /// the returned map does the same for the optimized circuit.
+    pub(crate) fn execute_constant_calls(
+        mut self,
+        transformation_map: AcirTransformationMap,
+    ) -> (Circuit, AcirTransformationMap, usize) {
+        let opcodes = std::mem::take(&mut self.circuit.opcodes);
+        let mut known_values = WitnessMap::new();
+        for opcode in &opcodes {
+            if let Some((witness, value)) = constant_definition(opcode) {
+                known_values.insert(witness, value);
+            }
+        }
+
+        let mut optimized_opcodes = Vec::with_capacity(opcodes.len());
+        let mut new_opcode_indices = Vec::with_capacity(opcodes.len());
+        let mut num_executed_opcodes = 0;
+        for (position, (opcode, index)) in
+            opcodes.into_iter().zip(transformation_map.opcode_indices).enumerate()
+        {
+            let assignments = match &opcode {
+                Opcode::Brillig(brillig) => execute(brillig, &mut known_values, position),
+                _ => None,
+            };
+            match assignments {
+                Some(assignments) => {
+                    num_executed_opcodes += 1;
+                    for (witness, value) in assignments {
+                        let mut assignment = Expression::from(witness);
+                        assignment.q_c = -value;
+                        optimized_opcodes.push(Opcode::AssertZero(assignment));
+                        new_opcode_indices.push(index);
+                    }
+                }
+                None => {
+                    optimized_opcodes.push(opcode);
+                    new_opcode_indices.push(index);
+                }
+            }
+        }
+
+        let transformation_map =
+            AcirTransformationMap { opcode_indices: new_opcode_indices, ..transformation_map };
+        (
+            Circuit { opcodes: optimized_opcodes, ..self.circuit },
+            transformation_map,
+            num_executed_opcodes,
+        )
+    }
+}
+
+/// Returns the witness constrained to a constant by `opcode` along with its value, if any.
+fn constant_definition(opcode: &Opcode) -> Option<(Witness, FieldElement)> {
+    match opcode {
+        Opcode::AssertZero(expr) if expr.is_linear() => match expr.linear_combinations.as_slice() {
+            [(coefficient, witness)] if !coefficient.is_zero() => {
+                Some((*witness, -expr.q_c / *coefficient))
+            }
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Executes `brillig` from the `known_values` of its inputs, returning the values of its outputs
+/// or `None` if it cannot be executed at compile time.
+///
+/// The values of the outputs are added to `known_values`.
+fn execute(
+    brillig: &Brillig,
+    known_values: &mut WitnessMap,
+    acir_index: usize,
+) -> Option<Vec<(Witness, FieldElement)>> {
+    // Values are only inserted into a copy, as outputs may be partially assigned before the execution fails.
+    let mut values = known_values.clone();
+    match BrilligSolver::solve(&mut values, brillig, acir_index, None, None) {
+        Ok(BrilligResolution::Solved(_)) => {}
+        _ => return None,
+    }
+
+    let assignments: Vec<_> = brillig
+        .outputs
+        .iter()
+        .flat_map(|output| match output {
+            BrilligOutputs::Simple(witness) => vec![*witness],
+            BrilligOutputs::Array(witnesses) => witnesses.clone(),
+        })
+        .map(|witness| (witness, values[&witness]))
+        .collect();
+    *known_values = values;
+    Some(assignments)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::compiler::{optimizers::ConstantBrilligExecutor, AcirTransformationMap};
+    use acir::{
+        brillig_vm::{self, BinaryFieldOp, RegisterIndex},
+        circuit::{
+            brillig::{Brillig, BrilligInputs, BrilligOutputs},
+            Circuit, Opcode,
+        },
+        native_types::{Expression, Witness},
+        FieldElement,
+    };
+
+    /// Returns a Brillig opcode setting `output` to the sum of its two inputs.
+    fn sum(lhs: Expression, rhs: Expression, output: Witness) -> Opcode {
+        Opcode::Brillig(Brillig {
+            inputs: vec![BrilligInputs::Single(lhs), BrilligInputs::Single(rhs)],
+            outputs: vec![BrilligOutputs::Simple(output)],
+            foreign_call_results: vec![],
+            bytecode: vec![brillig_vm::Opcode::BinaryFieldOp {
+                op: BinaryFieldOp::Add,
+                lhs: RegisterIndex::from(0),
+                rhs: RegisterIndex::from(1),
+                destination: RegisterIndex::from(0),
+            }],
+            predicate: None,
+        })
+    }
+
+    fn assignment(witness: Witness, value: u128) -> Opcode {
+        let mut expr = Expression::from(witness);
+        expr.q_c = -FieldElement::from(value);
+        Opcode::AssertZero(expr)
+    }
+
+    #[test]
+    fn executes_brillig_opcodes_with_known_inputs() {
+        let (x, s, t, u, v) = (Witness(1), Witness(2), Witness(3), Witness(4), Witness(5));
+        let circuit = Circuit {
+            current_witness_index: 5,
+            opcodes: vec![
+                assignment(s, 7),
+                sum(Expression::from(s), Expression::from_field(FieldElement::from(3_u128)), t),
+                // The output of the previous opcode is known once it has been executed.
+                sum(Expression::from(t), Expression::from(s), u),
+                // `x` is only known when the circuit is solved.
+                sum(Expression::from(x), Expression::from(s), v),
+            ],
+            ..Circuit::default()
+        };
+
+        let (optimized_circuit, transformation_map, num_executed_opcodes) =
+            ConstantBrilligExecutor::new(circuit.clone())
+                .execute_constant_calls(AcirTransformationMap::new((0..4).collect()));
+
+        assert_eq!(num_executed_opcodes, 2);
+        assert_eq!(
+            optimized_circuit.opcodes,
+            vec![
+                assignment(s, 7),
+                assignment(t, 10),
+                assignment(u, 17),
+                circuit.opcodes[3].clone()
+            ]
+        );
+        assert_eq!(transformation_map.opcode_indices(), [0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn leaves_failing_executions_to_the_solver() {
+        let (s, t) = (Witness(1), Witness(2));
+        let circuit = Circuit {
+            current_witness_index: 2,
+            opcodes: vec![
+                assignment(s, 7),
+                Opcode::Brillig(Brillig {
+                    inputs: vec![BrilligInputs::Single(Expression::from(s))],
+                    outputs: vec![BrilligOutputs::Simple(t)],
+                    foreign_call_results: vec![],
+                    bytecode: vec![brillig_vm::Opcode::Trap],
+                    predicate: None,
+                }),
+            ],
+            ..Circuit::default()
+        };
+
+        let (optimized_circuit, _, num_executed_opcodes) =
+            ConstantBrilligExecutor::new(circuit.clone())
+                .execute_constant_calls(AcirTransformationMap::new((0..2).collect()));
+
+        assert_eq!(num_executed_opcodes, 0);
+        assert_eq!(optimized_circuit.opcodes, circuit.opcodes);
+    }
+}
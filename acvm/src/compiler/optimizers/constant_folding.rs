@@ -0,0 +1,279 @@
+use acir::{
+    circuit::{visitor::Visitor, Circuit, Opcode},
+    native_types::{Expression, Witness},
+    FieldElement,
+};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use super::common_subexpression::WitnessCollector;
+use crate::compiler::AcirTransformationMap;
+
+/// `ConstantFolder` propagates the values of witnesses which are constrained to constants.
+///
+/// # Example
+///
+/// Suppose we had the following opcodes, where `c` is an intermediate witness:
+///
+/// ```text
+/// c - 3 = 0
+/// c * x - y = 0
+/// c - 3 = 0
+/// ```
+/// The first opcode defines `c` to be `3`, which is substituted into the other opcodes.
+/// The multiplication then becomes the linear opcode `3 * x - y = 0` while the last opcode
+/// becomes the tautology `0 = 0` and is removed.
+///
+/// Once substituted, the definition of `c` is itself removed unless `c` must still be assigned:
+/// because it is observable, such as the witnesses of the ABI, is a public input or return value,
+/// or is used by an opcode other than an arithmetic one. Opcodes which are folded into a non-zero constant are kept so that the
+/// solver reports them as unsatisfied.
+pub(crate) struct ConstantFolder {
+    circuit: Circuit,
+}
+
+impl ConstantFolder {
+    pub(crate) fn new(circuit: Circuit) -> Self {
+        Self { circuit }
+    }
+
+    /// Returns a `Circuit` where the witnesses constrained to constants are substituted into every
+    /// arithmetic opcode, along with the number of opcodes which were removed.
+    ///
+    /// `is_observable` returns whether a witness must be assigned by the solver, such as those of the ABI.
+    /// `transformation_map` relates the circuit to the original one, the returned map does the same for
+    /// the optimized circuit and records the value of each witness whose definition was removed.
+    pub(crate) fn fold_constants(
+        mut self,
+        is_observable: impl Fn(Witness) -> bool,
+        transformation_map: AcirTransformationMap,
+    ) -> (Circuit, AcirTransformationMap, usize) {
+        let mut opcodes = std::mem::take(&mut self.circuit.opcodes);
+        let num_opcodes = opcodes.len();
+
+        let mut assigned_witnesses = WitnessCollector::default();
+        for opcode in &opcodes {
+            if !matches!(opcode, Opcode::AssertZero(_)) {
+                assigned_witnesses.visit_opcode(opcode);
+            }
+        }
+        let must_be_assigned = |witness: Witness| {
+            is_observable(witness)
+                || assigned_witnesses.0.contains(&witness)
+                || self.circuit.public_parameters.0.contains(&witness)
+                || self.circuit.return_values.0.contains(&witness)
+        };
+
+        let mut constants: HashMap<Witness, FieldElement> = HashMap::new();
+        // Positions of the opcodes defining a constant witness and of those folded into `0 = 0`.
+        let mut definitions: HashMap<usize, Witness> = HashMap::new();
+        let mut tautologies: HashSet<usize> = HashSet::new();
+
+        // Substituting a constant may reveal another one, so opcodes are folded until no new constant is found.
+        let mut found_constant = true;
+        while found_constant {
+            found_constant = false;
+            for (position, opcode) in opcodes.iter_mut().enumerate() {
+                let expr = match opcode {
+                    Opcode::AssertZero(expr)
+                        if !definitions.contains_key(&position)
+                            && !tautologies.contains(&position) =>
+                    {
+                        expr
+                    }
+                    _ => continue,
+                };
+                if expr
+                    .mul_terms
+                    .iter()
+                    .any(|(_, lhs, rhs)| constants.contains_key(lhs) || constants.contains_key(rhs))
+                    || expr.linear_combinations.iter().any(|(_, term)| constants.contains_key(term))
+                {
+                    *expr = fold_expression(expr, &constants);
+                }
+
+                if expr.is_const() {
+                    if expr.q_c.is_zero() {
+                        tautologies.insert(position);
+                    }
+                } else if let (true, [(coefficient, witness)]) =
+                    (expr.is_linear(), expr.linear_combinations.as_slice())
+                {
+                    constants.insert(*witness, -expr.q_c / *coefficient);
+                    definitions.insert(position, *witness);
+                    found_constant = true;
+                }
+            }
+        }
+
+        let mut optimized_opcodes = Vec::with_capacity(num_opcodes);
+        let mut new_opcode_indices = Vec::with_capacity(num_opcodes);
+        let mut constant_witnesses = transformation_map.constant_witnesses;
+        let opcode_indices = transformation_map.opcode_indices;
+        for (position, (opcode, index)) in opcodes.into_iter().zip(opcode_indices).enumerate() {
+            let removed_definition =
+                definitions.get(&position).filter(|&&witness| !must_be_assigned(witness));
+            if let Some(witness) = removed_definition {
+                constant_witnesses.insert(*witness, constants[witness]);
+            } else if !tautologies.contains(&position) {
+                optimized_opcodes.push(opcode);
+                new_opcode_indices.push(index);
+            }
+        }
+
+        let num_removed_opcodes = num_opcodes - optimized_opcodes.len();
+        let transformation_map = AcirTransformationMap {
+            opcode_indices: new_opcode_indices,
+            constant_witnesses,
+            ..transformation_map
+        };
+        (
+            Circuit { opcodes: optimized_opcodes, ..self.circuit },
+            transformation_map,
+            num_removed_opcodes,
+        )
+    }
+}
+
+/// Returns `expr` with the value of each of the `constants` it contains substituted,
+/// merging the terms which then share the same witnesses.
+fn fold_expression(expr: &Expression, constants: &HashMap<Witness, FieldElement>) -> Expression {
+    let mut q_c = expr.q_c;
+    let mut linear_combinations: BTreeMap<Witness, FieldElement> = BTreeMap::new();
+    let mut mul_terms: BTreeMap<(Witness, Witness), FieldElement> = BTreeMap::new();
+
+    for &(coefficient, witness) in &expr.linear_combinations {
+        match constants.get(&witness) {
+            Some(value) => q_c += coefficient * *value,
+            None => {
+                *linear_combinations.entry(witness).or_insert_with(FieldElement::zero) +=
+                    coefficient
+            }
+        }
+    }
+    for &(coefficient, lhs, rhs) in &expr.mul_terms {
+        match (constants.get(&lhs), constants.get(&rhs)) {
+            (Some(lhs), Some(rhs)) => q_c += coefficient * *lhs * *rhs,
+            (Some(value), None) => {
+                *linear_combinations.entry(rhs).or_insert_with(FieldElement::zero) +=
+                    coefficient * *value
+            }
+            (None, Some(value)) => {
+                *linear_combinations.entry(lhs).or_insert_with(FieldElement::zero) +=
+                    coefficient * *value
+            }
+            (None, None) => {
+                *mul_terms
+                    .entry((lhs.min(rhs), lhs.max(rhs)))
+                    .or_insert_with(FieldElement::zero) += coefficient;
+            }
+        }
+    }
+
+    Expression {
+        mul_terms: mul_terms
+            .into_iter()
+            .filter(|(_, coefficient)| !coefficient.is_zero())
+            .map(|((lhs, rhs), coefficient)| (coefficient, lhs, rhs))
+            .collect(),
+        linear_combinations: linear_combinations
+            .into_iter()
+            .filter(|(_, coefficient)| !coefficient.is_zero())
+            .map(|(witness, coefficient)| (coefficient, witness))
+            .collect(),
+        q_c,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::compiler::{optimizers::ConstantFolder, AcirTransformationMap};
+    use acir::{
+        circuit::{
+            opcodes::{BlackBoxFuncCall, FunctionInput},
+            Circuit, Opcode, PublicInputs,
+        },
+        native_types::{Expression, Witness},
+        FieldElement,
+    };
+
+    fn test_circuit(opcodes: Vec<Opcode>) -> Circuit {
+        Circuit { current_witness_index: 10, opcodes, ..Circuit::default() }
+    }
+
+    /// Returns an opcode constraining `witness` to `value`.
+    fn constant(witness: Witness, value: u128) -> Opcode {
+        let mut expr = Expression::from(witness);
+        expr.q_c = -FieldElement::from(value);
+        Opcode::AssertZero(expr)
+    }
+
+    /// Returns an opcode constraining `output` to `lhs * rhs`.
+    fn product(lhs: Witness, rhs: Witness, output: Witness) -> Opcode {
+        let mut expr = Expression::default();
+        expr.push_multiplication_term(FieldElement::one(), lhs, rhs);
+        expr.push_addition_term(-FieldElement::one(), output);
+        Opcode::AssertZero(expr)
+    }
+
+    #[test]
+    fn folds_constant_witnesses() {
+        let (x, c, y, z) = (Witness(1), Witness(2), Witness(3), Witness(4));
+        let circuit = test_circuit(vec![
+            constant(c, 3),
+            product(c, x, y),
+            constant(c, 3),
+            product(c, c, z),
+            Opcode::BlackBoxFuncCall(BlackBoxFuncCall::RANGE {
+                input: FunctionInput { witness: z, num_bits: 8 },
+            }),
+        ]);
+
+        let (optimized_circuit, transformation_map, num_removed_opcodes) = ConstantFolder::new(
+            circuit,
+        )
+        .fold_constants(|witness| witness == x, AcirTransformationMap::new((0..5).collect()));
+
+        // `c` is only used by arithmetic opcodes so its definition is removed,
+        // while `z` is used by the range constraint and must still be assigned.
+        let mut linear_product = Expression::default();
+        linear_product.push_addition_term(FieldElement::from(3_u128), x);
+        linear_product.push_addition_term(-FieldElement::one(), y);
+        let mut z_definition = Expression::from_field(FieldElement::from(9_u128));
+        z_definition.push_addition_term(-FieldElement::one(), z);
+        assert_eq!(
+            optimized_circuit.opcodes[..2],
+            [Opcode::AssertZero(linear_product), Opcode::AssertZero(z_definition)]
+        );
+        assert_eq!(transformation_map.opcode_indices(), [1, 3, 4]);
+        assert_eq!(num_removed_opcodes, 2);
+        assert_eq!(
+            transformation_map.constant_witnesses().iter().collect::<Vec<_>>(),
+            vec![(&c, &FieldElement::from(3_u128))]
+        );
+    }
+
+    #[test]
+    fn keeps_unsatisfiable_and_observable_constraints() {
+        let (x, c) = (Witness(1), Witness(2));
+        let circuit = Circuit {
+            return_values: PublicInputs([c].into_iter().collect()),
+            ..test_circuit(vec![constant(c, 3), constant(c, 4), constant(x, 5)])
+        };
+
+        let (optimized_circuit, transformation_map, num_removed_opcodes) = ConstantFolder::new(
+            circuit,
+        )
+        .fold_constants(|witness| witness == x, AcirTransformationMap::new((0..3).collect()));
+
+        assert_eq!(num_removed_opcodes, 0);
+        assert!(transformation_map.constant_witnesses().is_empty());
+        assert_eq!(
+            optimized_circuit.opcodes,
+            vec![
+                constant(c, 3),
+                Opcode::AssertZero(Expression::from_field(-FieldElement::one())),
+                constant(x, 5),
+            ]
+        );
+    }
+}
@@ -12,8 +12,9 @@ pub(crate) struct GeneralOptimizer;
 impl GeneralOptimizer {
     pub(crate) fn optimize(gate: Expression) -> Expression {
         // XXX: Perhaps this optimization can be done on the fly
-        let gate = remove_zero_coefficients(gate);
-        simplify_mul_terms(gate)
+        // Merging quadratic terms may cancel them out, so zero coefficients are removed afterwards.
+        let gate = simplify_mul_terms(gate);
+        remove_zero_coefficients(gate)
     }
 }
 
@@ -32,7 +33,7 @@ fn simplify_mul_terms(mut gate: Expression) -> Expression {
 
     // Canonicalize the ordering of the multiplication, lets just order by variable name
     for (scale, w_l, w_r) in gate.mul_terms.clone().into_iter() {
-        let mut pair = vec![w_l, w_r];
+        let mut pair = [w_l, w_r];
         // Sort using rust sort algorithm
         pair.sort();
 
@@ -1,7 +1,15 @@
+mod boolean;
+mod common_subexpression;
+mod constant_brillig;
+mod constant_folding;
 mod general;
 mod redundant_range;
 mod simplify;
 
+pub(crate) use boolean::BooleanOptimizer;
+pub(crate) use common_subexpression::CommonSubexpressionOptimizer;
+pub(crate) use constant_brillig::ConstantBrilligExecutor;
+pub(crate) use constant_folding::ConstantFolder;
 pub(crate) use general::GeneralOptimizer;
 pub(crate) use redundant_range::RangeOptimizer;
 // Public as these need to be passed to `acvm::compiler::compile()`
@@ -1,9 +1,14 @@
 use acir::{
-    circuit::{opcodes::BlackBoxFuncCall, Circuit, Opcode},
+    circuit::{
+        opcodes::{BlackBoxFuncCall, FunctionInput},
+        Circuit, Opcode,
+    },
     native_types::Witness,
 };
 use std::collections::{BTreeMap, HashSet};
 
+use crate::compiler::AcirTransformationMap;
+
 /// `RangeOptimizer` will remove redundant range constraints.
 ///
 /// # Example
@@ -23,6 +28,9 @@ use std::collections::{BTreeMap, HashSet};
 ///
 /// This optimization pass will keep the 16-bit range constraint
 /// and remove the 32-bit range constraint opcode.
+///
+/// Range constraints within a `RangeBatch` opcode are treated in the same way,
+/// the batch being removed once all of its constraints are.
 pub(crate) struct RangeOptimizer {
     /// Maps witnesses to their lowest known bit sizes.
     lists: BTreeMap<Witness, u32>,
@@ -46,14 +54,13 @@ impl RangeOptimizer {
     fn collect_ranges(circuit: &Circuit) -> BTreeMap<Witness, u32> {
         let mut witness_to_bit_sizes = BTreeMap::new();
 
-        for opcode in &circuit.opcodes {
-            // Extract the witness index and number of bits,
-            // if it is a range constraint
-            let (witness, num_bits) = match extract_range_opcode(opcode) {
-                Some(func_inputs) => func_inputs,
-                None => continue,
-            };
-
+        // Extract the witness index and number of bits of each range constraint
+        let range_constraints = circuit.opcodes.iter().flat_map(|opcode| {
+            extract_range_opcode(opcode)
+                .into_iter()
+                .chain(extract_range_batch(opcode).into_iter().flatten())
+        });
+        for (witness, num_bits) in range_constraints {
             // Check if the witness has already been recorded and if the witness
             // size is more than the current one, we replace it
             let should_replace = match witness_to_bit_sizes.get(&witness).copied() {
@@ -69,46 +76,90 @@ impl RangeOptimizer {
 
     /// Returns a `Circuit` where each Witness is only range constrained
     /// once to the lowest number `bit size` possible.
-    pub(crate) fn replace_redundant_ranges(self) -> Circuit {
+    ///
+    /// `transformation_map` relates the circuit to the original one,
+    /// the returned map does the same for the optimized circuit.
+    pub(crate) fn replace_redundant_ranges(
+        self,
+        transformation_map: AcirTransformationMap,
+    ) -> (Circuit, AcirTransformationMap) {
         let mut already_seen_witness = HashSet::new();
 
         let mut optimized_opcodes = Vec::with_capacity(self.circuit.opcodes.len());
+        let mut new_opcode_indices = Vec::with_capacity(self.circuit.opcodes.len());
+
+        for (opcode, index) in
+            self.circuit.opcodes.into_iter().zip(transformation_map.opcode_indices)
+        {
+            if let Some(range_constraints) = extract_range_batch(&opcode) {
+                let inputs: Vec<_> = range_constraints
+                    .into_iter()
+                    .filter(|&(witness, num_bits)| {
+                        Self::retain_range(
+                            &self.lists,
+                            &mut already_seen_witness,
+                            witness,
+                            num_bits,
+                        )
+                    })
+                    .map(|(witness, num_bits)| FunctionInput { witness, num_bits })
+                    .collect();
+                if !inputs.is_empty() {
+                    optimized_opcodes
+                        .push(Opcode::BlackBoxFuncCall(BlackBoxFuncCall::RangeBatch { inputs }));
+                    new_opcode_indices.push(index);
+                }
+                continue;
+            }
 
-        for opcode in self.circuit.opcodes {
             let (witness, num_bits) = match extract_range_opcode(&opcode) {
                 Some(range_opcode) => range_opcode,
                 None => {
                     // If its not the range opcode, add it to the opcode
                     // list and continue;
                     optimized_opcodes.push(opcode);
+                    new_opcode_indices.push(index);
                     continue;
                 }
             };
-            // If we've already applied the range constraint for this witness then skip this opcode.
-            let already_added = already_seen_witness.contains(&witness);
-            if already_added {
-                continue;
+            if Self::retain_range(&self.lists, &mut already_seen_witness, witness, num_bits) {
+                optimized_opcodes.push(opcode);
+                new_opcode_indices.push(index);
             }
+        }
 
-            // Check if this is the lowest number of bits in the circuit
-            let stored_num_bits = self.lists.get(&witness).expect("Could not find witness. This should never be the case if `collect_ranges` is called");
-            let is_lowest_bit_size = num_bits <= *stored_num_bits;
+        let transformation_map =
+            AcirTransformationMap { opcode_indices: new_opcode_indices, ..transformation_map };
+        (Circuit { opcodes: optimized_opcodes, ..self.circuit }, transformation_map)
+    }
 
-            // If the opcode is associated with the lowest bit size
-            // and we have not added a duplicate of this opcode yet,
-            // then we should add retain this range opcode.
-            if is_lowest_bit_size {
-                already_seen_witness.insert(witness);
-                optimized_opcodes.push(opcode);
-            }
+    /// Returns whether the range constraint of `witness` to `num_bits` should be retained,
+    /// recording it in `already_seen_witness` if so.
+    fn retain_range(
+        lists: &BTreeMap<Witness, u32>,
+        already_seen_witness: &mut HashSet<Witness>,
+        witness: Witness,
+        num_bits: u32,
+    ) -> bool {
+        // If we've already applied the range constraint for this witness then skip this constraint.
+        let already_added = already_seen_witness.contains(&witness);
+        if already_added {
+            return false;
         }
 
-        Circuit {
-            current_witness_index: self.circuit.current_witness_index,
-            opcodes: optimized_opcodes,
-            public_parameters: self.circuit.public_parameters,
-            return_values: self.circuit.return_values,
+        // Check if this is the lowest number of bits in the circuit
+        let stored_num_bits = lists.get(&witness).expect(
+            "Could not find witness. This should never be the case if `collect_ranges` is called",
+        );
+        let is_lowest_bit_size = num_bits <= *stored_num_bits;
+
+        // If the constraint is associated with the lowest bit size
+        // and we have not added a duplicate of this constraint yet,
+        // then we should retain this range constraint.
+        if is_lowest_bit_size {
+            already_seen_witness.insert(witness);
         }
+        is_lowest_bit_size
     }
 }
 
@@ -129,9 +180,23 @@ fn extract_range_opcode(opcode: &Opcode) -> Option<(Witness, u32)> {
     }
 }
 
+/// Extract the range constraints of a `RangeBatch` opcode.
+/// Returns None, if `Opcode` is not a batch of range constraints.
+fn extract_range_batch(opcode: &Opcode) -> Option<Vec<(Witness, u32)>> {
+    match opcode {
+        Opcode::BlackBoxFuncCall(BlackBoxFuncCall::RangeBatch { inputs }) => {
+            Some(inputs.iter().map(|input| (input.witness, input.num_bits)).collect())
+        }
+        _ => None,
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::compiler::optimizers::redundant_range::{extract_range_opcode, RangeOptimizer};
+    use crate::compiler::{
+        optimizers::redundant_range::{extract_range_opcode, RangeOptimizer},
+        AcirTransformationMap,
+    };
     use acir::{
         circuit::{
             opcodes::{BlackBoxFuncCall, FunctionInput},
@@ -157,6 +222,8 @@ mod tests {
             opcodes,
             public_parameters: PublicInputs::default(),
             return_values: PublicInputs::default(),
+            debug_info: None,
+            phases: Vec::new(),
         }
     }
 
@@ -176,8 +243,10 @@ mod tests {
             "expected a range size of 16 since that was the lowest bit size provided"
         );
 
-        let optimized_circuit = optimizer.replace_redundant_ranges();
+        let (optimized_circuit, transformation_map) =
+            optimizer.replace_redundant_ranges(AcirTransformationMap::new(vec![0, 1]));
         assert_eq!(optimized_circuit.opcodes.len(), 1);
+        assert_eq!(transformation_map.opcode_indices(), [1]);
 
         let (witness, num_bits) =
             extract_range_opcode(&optimized_circuit.opcodes[0]).expect("expected one range opcode");
@@ -198,8 +267,10 @@ mod tests {
         ]);
 
         let optimizer = RangeOptimizer::new(circuit);
-        let optimized_circuit = optimizer.replace_redundant_ranges();
+        let (optimized_circuit, transformation_map) =
+            optimizer.replace_redundant_ranges(AcirTransformationMap::new(vec![0, 1, 2, 3]));
         assert_eq!(optimized_circuit.opcodes.len(), 2);
+        assert_eq!(transformation_map.opcode_indices(), [0, 2]);
 
         let (witness_a, num_bits_a) =
             extract_range_opcode(&optimized_circuit.opcodes[0]).expect("expected two range opcode");
@@ -218,13 +289,45 @@ mod tests {
         // The four Arithmetic opcodes should remain unchanged.
         let mut circuit = test_circuit(vec![(Witness(1), 16), (Witness(1), 16)]);
 
-        circuit.opcodes.push(Opcode::Arithmetic(Expression::default()));
-        circuit.opcodes.push(Opcode::Arithmetic(Expression::default()));
-        circuit.opcodes.push(Opcode::Arithmetic(Expression::default()));
-        circuit.opcodes.push(Opcode::Arithmetic(Expression::default()));
+        circuit.opcodes.push(Opcode::AssertZero(Expression::default()));
+        circuit.opcodes.push(Opcode::AssertZero(Expression::default()));
+        circuit.opcodes.push(Opcode::AssertZero(Expression::default()));
+        circuit.opcodes.push(Opcode::AssertZero(Expression::default()));
 
         let optimizer = RangeOptimizer::new(circuit);
-        let optimized_circuit = optimizer.replace_redundant_ranges();
+        let (optimized_circuit, _) =
+            optimizer.replace_redundant_ranges(AcirTransformationMap::new((0..6).collect()));
         assert_eq!(optimized_circuit.opcodes.len(), 5)
     }
+
+    #[test]
+    fn removes_redundant_constraints_from_batches() {
+        // Constraints within a batch are compared with those of individual range opcodes.
+        let mut circuit = test_circuit(vec![(Witness(1), 16)]);
+        let batch = |ranges: Vec<(u32, u32)>| {
+            let inputs = ranges
+                .into_iter()
+                .map(|(witness, num_bits)| FunctionInput { witness: Witness(witness), num_bits })
+                .collect();
+            Opcode::BlackBoxFuncCall(BlackBoxFuncCall::RangeBatch { inputs })
+        };
+        circuit.opcodes.push(batch(vec![(1, 32), (2, 8), (3, 8)]));
+        circuit.opcodes.push(batch(vec![(2, 8), (3, 4)]));
+
+        let optimizer = RangeOptimizer::new(circuit);
+        let (optimized_circuit, transformation_map) =
+            optimizer.replace_redundant_ranges(AcirTransformationMap::new(vec![0, 1, 2]));
+
+        assert_eq!(transformation_map.opcode_indices(), [0, 1, 2]);
+        assert_eq!(optimized_circuit.opcodes[1], batch(vec![(2, 8)]));
+        assert_eq!(optimized_circuit.opcodes[2], batch(vec![(3, 4)]));
+
+        // A batch whose constraints are all redundant is removed.
+        let mut circuit = test_circuit(vec![(Witness(1), 16)]);
+        circuit.opcodes.push(batch(vec![(1, 16)]));
+        let (optimized_circuit, transformation_map) = RangeOptimizer::new(circuit)
+            .replace_redundant_ranges(AcirTransformationMap::new(vec![0, 1]));
+        assert_eq!(optimized_circuit.opcodes.len(), 1);
+        assert_eq!(transformation_map.opcode_indices(), [0]);
+    }
 }
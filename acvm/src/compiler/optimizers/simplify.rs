@@ -1,4 +1,4 @@
-use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::collections::{BTreeMap, BTreeSet};
 
 use acir::{
     circuit::{
@@ -7,6 +7,7 @@ use acir::{
         Opcode,
     },
     native_types::{Expression, Witness},
+    smallvec::{smallvec, SmallVec},
     FieldElement,
 };
 
@@ -33,8 +34,13 @@ pub struct CircuitSimplifier {
     /// Number of witness in the ABI
     abi_len: u32,
     solved: BTreeMap<Witness, FieldElement>,
-    /// List of solved witness that should be defined with an Arithmetic gate
-    pub defined: HashSet<Witness>,
+    /// Index of the gate from which each solved witness was derived
+    solved_by: BTreeMap<Witness, usize>,
+    /// List of solved witness that should be defined with an Arithmetic gate,
+    /// along with the index of the first gadget which requires the definition
+    ///
+    /// Ordered so that the definitions are added to the circuit in the same order on every run.
+    pub defined: BTreeMap<Witness, usize>,
     /// Index of the Arithmetic gate that defines a witness
     def_info: BTreeMap<Witness, usize>,
     /// Min of the solved witness definition
@@ -50,7 +56,8 @@ impl CircuitSimplifier {
         CircuitSimplifier {
             abi_len,
             solved: BTreeMap::new(),
-            defined: HashSet::new(),
+            solved_by: BTreeMap::new(),
+            defined: BTreeMap::new(),
             def_info: BTreeMap::new(),
             min_use: usize::MAX,
             solved_gates: BTreeSet::new(),
@@ -64,10 +71,19 @@ impl CircuitSimplifier {
         }
     }
 
+    /// Returns the index of the gate from which the value of the solved witness `w` was derived, if any.
+    pub fn definition_index(&self, w: &Witness) -> Option<usize> {
+        self.solved_by.get(w).copied()
+    }
+
     pub fn is_abi(&self, w: Witness) -> bool {
         w.0 < self.abi_len
     }
 
+    pub(crate) fn abi_len(&self) -> u32 {
+        self.abi_len
+    }
+
     pub fn is_solved(&self, w: &Witness) -> bool {
         self.solved.contains_key(w)
     }
@@ -91,6 +107,7 @@ impl CircuitSimplifier {
             SimplifyResult::Solved
         } else {
             self.solved.insert(w, f);
+            self.solved_by.insert(w, gate_idx);
             SimplifyResult::SolvedWitness(w)
         }
     }
@@ -103,11 +120,11 @@ impl CircuitSimplifier {
     pub fn define(&self, w: &Witness) -> Opcode {
         let mut a = Expression::from(*w);
         a.q_c = -self.solved[w];
-        Opcode::Arithmetic(a)
+        Opcode::AssertZero(a)
     }
 
     // Simplify a gate and propagate the solved witness onto the previous gates, as long as it can solve some witness
-    pub fn simplify(&mut self, gates: &mut Vec<Opcode>) -> SimplifyResult {
+    pub fn simplify(&mut self, gates: &mut [Opcode]) -> SimplifyResult {
         let mut first = true;
         let mut solved = true;
         self.min_use = gates.len() - 1;
@@ -127,9 +144,9 @@ impl CircuitSimplifier {
                         solved = true;
                         if self.is_abi(w) {
                             self.def_gates.insert(i);
-                            gates[i] = Opcode::Arithmetic(Expression {
-                                mul_terms: Vec::new(),
-                                linear_combinations: vec![(FieldElement::one(), w)],
+                            gates[i] = Opcode::AssertZero(Expression {
+                                mul_terms: SmallVec::new(),
+                                linear_combinations: smallvec![(FieldElement::one(), w)],
                                 q_c: -self.solved[&w],
                             });
                         } else {
@@ -156,7 +173,7 @@ impl CircuitSimplifier {
             return SimplifyResult::Unresolved;
         }
         match gate {
-            Opcode::Arithmetic(expr) => self.simplify_arithmetic(expr, gate_idx, first),
+            Opcode::AssertZero(expr) => self.simplify_arithmetic(expr, gate_idx, first),
             Opcode::Directive(Directive::Invert { x, result }) => {
                 self.simplify_inverse(*x, *result, gate_idx, first)
             }
@@ -197,7 +214,7 @@ impl CircuitSimplifier {
             _ => {
                 for i in gadget.get_inputs_vec() {
                     if self.is_solved(&i.witness) && !self.is_abi(i.witness) {
-                        self.defined.insert(i.witness);
+                        self.defined.entry(i.witness).or_insert(gate_idx);
                     }
                 }
                 for i in gadget.get_outputs_vec() {
@@ -252,7 +269,7 @@ impl CircuitSimplifier {
             }
         }
         if expr != *expression {
-            SimplifyResult::Replace(Box::new(Opcode::Arithmetic(expr)))
+            SimplifyResult::Replace(Box::new(Opcode::AssertZero(expr)))
         } else {
             SimplifyResult::Unresolved
         }
@@ -265,7 +282,6 @@ impl CircuitSimplifier {
         gate_idx: usize,
         first: bool,
     ) -> SimplifyResult {
-        if result.0 == 44 {}
         self.use_witness(result, gate_idx, first);
         if let Some(f) = self.solved.get(&x) {
             let result_value = if f.is_zero() { FieldElement::zero() } else { f.inverse() };
@@ -432,12 +448,22 @@ impl CircuitSimplifier {
 #[cfg(test)]
 mod tests {
     use acir::{
-        circuit::{Circuit, Opcode},
+        circuit::{
+            directives::Directive,
+            opcodes::{BlackBoxFuncCall, FunctionInput},
+            Circuit, Opcode,
+        },
         native_types::{Expression, Witness},
+        smallvec::smallvec,
         FieldElement,
     };
 
-    use crate::compiler::{optimizers::CircuitSimplifier, transformers::FallbackTransformer};
+    use crate::{
+        compiler::{
+            optimizers::CircuitSimplifier, transformers::FallbackTransformer, BackendCapabilities,
+        },
+        Language,
+    };
 
     #[test]
     fn simplify_test() {
@@ -449,35 +475,83 @@ mod tests {
         let one = FieldElement::one();
         // b = c * d ;
         let gate_b = Expression {
-            mul_terms: vec![(one, b, c)],
-            linear_combinations: vec![(-one, a)],
+            mul_terms: smallvec![(one, b, c)],
+            linear_combinations: smallvec![(-one, a)],
             q_c: FieldElement::zero(),
         };
         // d = 3;
         let gate_d = Expression {
-            mul_terms: vec![],
-            linear_combinations: vec![(one, d)],
+            mul_terms: smallvec![],
+            linear_combinations: smallvec![(one, d)],
             q_c: FieldElement::from(-3_i128),
         };
         // a = 0;
         let gate_a = Expression {
-            mul_terms: vec![],
-            linear_combinations: vec![(one, a)],
+            mul_terms: smallvec![],
+            linear_combinations: smallvec![(one, a)],
             q_c: FieldElement::zero(),
         };
         let mut simplifier = CircuitSimplifier::new(1);
         let mut circuit = vec![
-            Opcode::Arithmetic(gate_a),
-            Opcode::Arithmetic(gate_b),
-            Opcode::Arithmetic(gate_d),
+            Opcode::AssertZero(gate_a),
+            Opcode::AssertZero(gate_b),
+            Opcode::AssertZero(gate_d),
         ];
         simplifier.simplify(&mut circuit);
         assert_eq!(circuit.len(), 3);
         assert_eq!(simplifier.solved_gates.len(), 1);
-        let support_all = |_opcode: &Opcode| true;
-        let mut acir = Circuit::default();
-        acir.opcodes = circuit;
-        let acir = FallbackTransformer::transform(acir, support_all, &simplifier).unwrap();
+        let support_all = BackendCapabilities::new(Language::R1CS);
+        let acir = Circuit { opcodes: circuit, ..Circuit::default() };
+        let (acir, transformation_map, _) =
+            FallbackTransformer::transform(acir, &support_all, &simplifier).unwrap();
         assert_eq!(acir.opcodes.len(), 2);
+        assert_eq!(transformation_map.opcode_indices().len(), 2);
+    }
+
+    #[test]
+    fn definitions_are_attributed_to_the_gate_solving_the_witness() {
+        let a = Witness(0);
+        let b = Witness(1);
+        let c = Witness(2);
+        let r = Witness(3);
+
+        let one = FieldElement::one();
+        // a * b = 0;
+        let gate_a = Expression {
+            mul_terms: smallvec![(one, a, b)],
+            linear_combinations: smallvec![],
+            q_c: FieldElement::zero(),
+        };
+        // r = 0;
+        let gate_r = Expression {
+            mul_terms: smallvec![],
+            linear_combinations: smallvec![(one, r)],
+            q_c: FieldElement::zero(),
+        };
+        // the inverse of `c` is zero, so `c` is solved by the directive
+        let invert = Directive::Invert { x: c, result: r };
+        let hash = BlackBoxFuncCall::SHA256 {
+            inputs: vec![FunctionInput { witness: c, num_bits: 8 }],
+            outputs: (4..36).map(Witness).collect(),
+        };
+        let mut simplifier = CircuitSimplifier::new(1);
+        let mut circuit = vec![
+            Opcode::AssertZero(gate_a),
+            Opcode::AssertZero(gate_r),
+            Opcode::Directive(invert),
+            Opcode::BlackBoxFuncCall(hash),
+        ];
+        for len in 1..=circuit.len() {
+            simplifier.simplify(&mut circuit[..len]);
+        }
+        assert_eq!(simplifier.definition_index(&c), Some(2));
+
+        let support_all = BackendCapabilities::new(Language::R1CS);
+        let acir = Circuit { current_witness_index: 35, opcodes: circuit, ..Circuit::default() };
+        let (acir, transformation_map, _) =
+            FallbackTransformer::transform(acir, &support_all, &simplifier).unwrap();
+        // The definition of `c` is inserted first and must map back to the directive which solved it.
+        assert_eq!(acir.opcodes.len(), 3);
+        assert_eq!(transformation_map.opcode_indices(), &[2, 0, 3]);
     }
 }
@@ -0,0 +1,269 @@
+use acir::{
+    circuit::{
+        opcodes::{BlackBoxFuncCall, BlockId},
+        visitor::Visitor,
+        Circuit, Opcode, PublicInputs,
+    },
+    native_types::{Witness, WitnessMap},
+};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use super::attach_opcode_metadata;
+
+/// A sub-circuit made of the opcodes of a [`Circuit`] which share no witness with the rest of the circuit.
+///
+/// Witnesses keep their index in the original circuit, such that the witness map solved for the original
+/// circuit can be split between its partitions using [`CircuitPartition::witness_map`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CircuitPartition {
+    /// The opcodes of the partition, along with the public inputs and return values which they use.
+    pub circuit: Circuit,
+    /// The index in the original circuit of each opcode of the partition.
+    pub opcode_indices: Vec<usize>,
+    /// Every witness used by the partition.
+    pub witnesses: BTreeSet<Witness>,
+    /// The witnesses of the partition which are public inputs or return values of the original circuit,
+    /// i.e. the values the partition exposes to the verifier.
+    pub interface_witnesses: BTreeSet<Witness>,
+}
+
+impl CircuitPartition {
+    /// Returns the values `witness_map` assigns to the witnesses of this partition.
+    pub fn witness_map(&self, witness_map: &WitnessMap) -> WitnessMap {
+        let values: BTreeMap<_, _> = self
+            .witnesses
+            .iter()
+            .filter_map(|witness| witness_map.get(witness).map(|value| (*witness, *value)))
+            .collect();
+        WitnessMap::from(values)
+    }
+}
+
+/// Splits `circuit` into its weakly-connected components, such that each of them can be proven independently.
+///
+/// Two opcodes belong to the same partition when they share a witness, access the same memory block
+/// or refer to the same big integer. Partitions are returned in the order of their first opcode,
+/// and opcodes keep their relative order within each partition.
+///
+/// Public inputs which are not used by any opcode are assigned to the first partition.
+pub fn partition(circuit: &Circuit) -> Vec<CircuitPartition> {
+    let mut components = UnionFind::new(circuit.opcodes.len());
+    let mut witness_owners: HashMap<Witness, usize> = HashMap::new();
+    let mut block_owners: HashMap<BlockId, usize> = HashMap::new();
+    let mut big_int_owners: HashMap<u32, usize> = HashMap::new();
+    let mut opcode_witnesses = Vec::with_capacity(circuit.opcodes.len());
+    for (index, opcode) in circuit.opcodes.iter().enumerate() {
+        let mut collector = WitnessCollector::default();
+        collector.visit_opcode(opcode);
+        for witness in &collector.0 {
+            let owner = *witness_owners.entry(*witness).or_insert(index);
+            components.union(owner, index);
+        }
+        if let Some(block_id) = opcode.memory_block_id() {
+            let owner = *block_owners.entry(block_id).or_insert(index);
+            components.union(owner, index);
+        }
+        for big_int_id in big_int_ids(opcode) {
+            let owner = *big_int_owners.entry(big_int_id).or_insert(index);
+            components.union(owner, index);
+        }
+        opcode_witnesses.push(collector.0);
+    }
+
+    // Components are keyed by their representative, ordered by the first opcode they contain.
+    let mut partition_of_component: HashMap<usize, usize> = HashMap::new();
+    let mut partitions: Vec<(Vec<usize>, BTreeSet<Witness>)> = Vec::new();
+    for (index, witnesses) in opcode_witnesses.into_iter().enumerate() {
+        let component = components.find(index);
+        let partition = *partition_of_component.entry(component).or_insert_with(|| {
+            partitions.push((Vec::new(), BTreeSet::new()));
+            partitions.len() - 1
+        });
+        partitions[partition].0.push(index);
+        partitions[partition].1.extend(witnesses);
+    }
+
+    let public_inputs = circuit.public_inputs();
+    let unused_public_inputs: Vec<Witness> = public_inputs
+        .0
+        .iter()
+        .filter(|witness| !witness_owners.contains_key(witness))
+        .copied()
+        .collect();
+    if !unused_public_inputs.is_empty() {
+        if partitions.is_empty() {
+            partitions.push((Vec::new(), BTreeSet::new()));
+        }
+        partitions[0].1.extend(unused_public_inputs);
+    }
+
+    partitions
+        .into_iter()
+        .map(|(opcode_indices, witnesses)| {
+            let restrict = |inputs: &PublicInputs| {
+                PublicInputs(inputs.0.intersection(&witnesses).copied().collect())
+            };
+            let sub_circuit = Circuit {
+                current_witness_index: circuit.current_witness_index,
+                opcodes: opcode_indices
+                    .iter()
+                    .map(|index| circuit.opcodes[*index].clone())
+                    .collect(),
+                public_parameters: restrict(&circuit.public_parameters),
+                return_values: restrict(&circuit.return_values),
+                debug_info: None,
+                phases: Vec::new(),
+            };
+            let sub_circuit = attach_opcode_metadata(
+                sub_circuit,
+                circuit.debug_info.clone(),
+                circuit.phases.clone(),
+                &opcode_indices,
+            );
+            let interface_witnesses = public_inputs.0.intersection(&witnesses).copied().collect();
+            CircuitPartition {
+                circuit: sub_circuit,
+                opcode_indices,
+                witnesses,
+                interface_witnesses,
+            }
+        })
+        .collect()
+}
+
+/// Returns the identifiers of the big integers which `opcode` reads or defines.
+fn big_int_ids(opcode: &Opcode) -> Vec<u32> {
+    match opcode {
+        Opcode::BlackBoxFuncCall(
+            BlackBoxFuncCall::BigIntAdd { lhs, rhs, output }
+            | BlackBoxFuncCall::BigIntMul { lhs, rhs, output }
+            | BlackBoxFuncCall::BigIntDiv { lhs, rhs, output },
+        ) => vec![*lhs, *rhs, *output],
+        Opcode::BlackBoxFuncCall(BlackBoxFuncCall::BigIntFromLeBytes { output, .. }) => {
+            vec![*output]
+        }
+        Opcode::BlackBoxFuncCall(BlackBoxFuncCall::BigIntToLeBytes { input, .. }) => vec![*input],
+        _ => Vec::new(),
+    }
+}
+
+/// Collects every witness mentioned by the opcodes it visits.
+#[derive(Default)]
+pub(super) struct WitnessCollector(pub(super) BTreeSet<Witness>);
+
+impl Visitor for WitnessCollector {
+    fn visit_witness(&mut self, witness: &Witness) {
+        self.0.insert(*witness);
+    }
+}
+
+/// Disjoint sets of opcode indices, merged as the opcodes are found to be connected.
+struct UnionFind {
+    parents: Vec<usize>,
+}
+
+impl UnionFind {
+    fn new(len: usize) -> Self {
+        UnionFind { parents: (0..len).collect() }
+    }
+
+    fn find(&mut self, mut index: usize) -> usize {
+        while self.parents[index] != index {
+            // Path halving keeps the trees shallow without recursion.
+            self.parents[index] = self.parents[self.parents[index]];
+            index = self.parents[index];
+        }
+        index
+    }
+
+    fn union(&mut self, lhs: usize, rhs: usize) {
+        let (lhs, rhs) = (self.find(lhs), self.find(rhs));
+        // The smallest index is kept as the representative.
+        if lhs < rhs {
+            self.parents[rhs] = lhs;
+        } else {
+            self.parents[lhs] = rhs;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::partition;
+    use acir::{
+        circuit::{
+            opcodes::{BlackBoxFuncCall, BlockId, FunctionInput, MemOp},
+            Circuit, Opcode, PublicInputs,
+        },
+        native_types::{Expression, Witness, WitnessMap},
+        FieldElement,
+    };
+    use std::collections::{BTreeMap, BTreeSet};
+
+    fn sum(lhs: Witness, rhs: Witness, output: Witness) -> Opcode {
+        Opcode::AssertZero(&(&Expression::from(lhs) + &Expression::from(rhs)) - output)
+    }
+
+    fn range(witness: Witness, num_bits: u32) -> Opcode {
+        Opcode::BlackBoxFuncCall(BlackBoxFuncCall::RANGE {
+            input: FunctionInput { witness, num_bits },
+        })
+    }
+
+    #[test]
+    fn splits_independent_components() {
+        let (a, b, c, x, y, z) =
+            (Witness(1), Witness(2), Witness(3), Witness(4), Witness(5), Witness(6));
+        let circuit = Circuit {
+            current_witness_index: 7,
+            opcodes: vec![sum(a, b, c), sum(x, y, z), range(c, 8), range(y, 8)],
+            public_parameters: PublicInputs(BTreeSet::from([a, x, Witness(7)])),
+            return_values: PublicInputs(BTreeSet::from([z])),
+            debug_info: None,
+            phases: Vec::new(),
+        };
+
+        let partitions = partition(&circuit);
+
+        assert_eq!(partitions.len(), 2);
+        assert_eq!(partitions[0].opcode_indices, [0, 2]);
+        assert_eq!(partitions[1].opcode_indices, [1, 3]);
+        assert_eq!(partitions[1].circuit.opcodes, vec![sum(x, y, z), range(y, 8)]);
+        // The unused public input is kept by the first partition.
+        assert_eq!(partitions[0].interface_witnesses, BTreeSet::from([a, Witness(7)]));
+        assert_eq!(partitions[1].interface_witnesses, BTreeSet::from([x, z]));
+        assert_eq!(partitions[1].circuit.public_parameters.0, BTreeSet::from([x]));
+        assert_eq!(partitions[1].circuit.return_values.0, BTreeSet::from([z]));
+        assert_eq!(partitions[1].circuit.current_witness_index, 7);
+
+        let values: BTreeMap<_, _> =
+            (1..=7).map(|index| (Witness(index), FieldElement::from(index as u128))).collect();
+        let witness_map = WitnessMap::from(values);
+        let values = partitions[1].witness_map(&witness_map);
+        assert_eq!(values.get(&y), Some(&FieldElement::from(5_u128)));
+        assert_eq!(values.get(&a), None);
+    }
+
+    #[test]
+    fn connects_opcodes_accessing_the_same_memory_block() {
+        let (a, b) = (Witness(1), Witness(2));
+        let write = |value: Witness| Opcode::MemoryOp {
+            block_id: BlockId(0),
+            op: MemOp {
+                operation: Expression::one(),
+                index: Expression::zero(),
+                value: Expression::from(value),
+            },
+        };
+        let circuit = Circuit {
+            current_witness_index: 2,
+            opcodes: vec![write(a), write(b)],
+            ..Circuit::default()
+        };
+
+        let partitions = partition(&circuit);
+
+        assert_eq!(partitions.len(), 1);
+        assert_eq!(partitions[0].witnesses, BTreeSet::from([a, b]));
+    }
+}
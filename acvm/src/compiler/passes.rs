@@ -0,0 +1,365 @@
+use acir::{
+    circuit::{Circuit, Opcode},
+    native_types::Witness,
+};
+use std::rc::Rc;
+
+use super::{
+    attach_opcode_metadata,
+    optimizers::{
+        BooleanOptimizer, CommonSubexpressionOptimizer, ConstantBrilligExecutor, ConstantFolder,
+        GeneralOptimizer, RangeOptimizer,
+    },
+    transform_internal, AcirTransformationMap, ExpressionWidth,
+};
+
+/// A transformation of a [`Circuit`] which can be registered with a [`PassManager`].
+pub trait AcirPass {
+    /// Name of the pass, under which its metrics are reported.
+    fn name(&self) -> &str {
+        std::any::type_name::<Self>()
+    }
+
+    /// Transforms `circuit` in place, returning how its opcodes and witnesses relate to those it had before.
+    fn run(&self, circuit: &mut Circuit) -> PassReport;
+}
+
+/// Describes the changes made by a single run of an [`AcirPass`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PassReport {
+    /// Relates the opcodes and witnesses of the circuit after the pass to those of the circuit before it,
+    /// or `None` if every opcode was kept at its position and no witness was eliminated.
+    pub transformation_map: Option<AcirTransformationMap>,
+}
+
+/// Size of the circuit before and after a pass run by a [`PassManager`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PassMetrics {
+    pub name: String,
+    pub num_opcodes_before: usize,
+    pub num_opcodes_after: usize,
+    pub current_witness_index_before: u32,
+    pub current_witness_index_after: u32,
+}
+
+/// Runs a sequence of [`AcirPass`]es over a circuit, in the order in which they are registered.
+#[derive(Default)]
+pub struct PassManager {
+    passes: Vec<Box<dyn AcirPass>>,
+}
+
+impl PassManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the passes run by [`compile`][super::compile] after replacing unsupported black box functions:
+    /// the general optimizer, the execution of Brillig opcodes with constant inputs, constant folding,
+    /// common subexpression elimination, the removal of redundant range constraints, the simplification
+    /// of boolean witnesses and the reduction of expressions to `expression_width`.
+    ///
+    /// `is_observable` returns whether a witness must be assigned by the solver, such as those of the ABI.
+    pub fn with_default_passes(
+        expression_width: ExpressionWidth,
+        is_observable: impl Fn(Witness) -> bool + 'static,
+    ) -> Self {
+        let is_observable: Rc<dyn Fn(Witness) -> bool> = Rc::new(is_observable);
+        let mut pass_manager = Self::new();
+        pass_manager
+            .add_pass(GeneralOptimizerPass)
+            .add_pass(ConstantBrilligPass)
+            .add_pass(ConstantFolderPass { is_observable: is_observable.clone() })
+            .add_pass(CommonSubexpressionPass { is_observable })
+            .add_pass(RangeOptimizerPass)
+            .add_pass(BooleanPass)
+            .add_pass(ExpressionWidthPass(expression_width));
+        pass_manager
+    }
+
+    /// Registers `pass` to run after every pass registered so far.
+    pub fn add_pass(&mut self, pass: impl AcirPass + 'static) -> &mut Self {
+        self.passes.push(Box::new(pass));
+        self
+    }
+
+    /// Registers `pass` to run at position `index` in the sequence of passes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is greater than the number of registered passes.
+    pub fn insert_pass(&mut self, index: usize, pass: impl AcirPass + 'static) -> &mut Self {
+        self.passes.insert(index, Box::new(pass));
+        self
+    }
+
+    /// Removes the pass at position `index` in the sequence of passes, if there is one.
+    pub fn remove_pass(&mut self, index: usize) -> Option<Box<dyn AcirPass>> {
+        (index < self.passes.len()).then(|| self.passes.remove(index))
+    }
+
+    /// Returns the names of the registered passes, in the order in which they run.
+    pub fn pass_names(&self) -> Vec<&str> {
+        self.passes.iter().map(|pass| pass.name()).collect()
+    }
+
+    /// Runs every registered pass over `circuit`, returning the map relating the transformed circuit to
+    /// the original one along with the metrics of each pass.
+    ///
+    /// Debug information and phases are remapped onto the opcodes of the transformed circuit.
+    pub fn run(&self, circuit: &mut Circuit) -> (AcirTransformationMap, Vec<PassMetrics>) {
+        let debug_info = circuit.debug_info.take();
+        let phases = std::mem::take(&mut circuit.phases);
+        let mut transformation_map =
+            AcirTransformationMap::new((0..circuit.opcodes.len()).collect());
+        let mut metrics = Vec::with_capacity(self.passes.len());
+
+        for pass in &self.passes {
+            let num_opcodes_before = circuit.opcodes.len();
+            let current_witness_index_before = circuit.current_witness_index;
+            let report = pass.run(circuit);
+            if let Some(pass_map) = report.transformation_map {
+                transformation_map = transformation_map.then(pass_map);
+            }
+            metrics.push(PassMetrics {
+                name: pass.name().to_owned(),
+                num_opcodes_before,
+                num_opcodes_after: circuit.opcodes.len(),
+                current_witness_index_before,
+                current_witness_index_after: circuit.current_witness_index,
+            });
+        }
+
+        *circuit = attach_opcode_metadata(
+            std::mem::take(circuit),
+            debug_info,
+            phases,
+            transformation_map.opcode_indices(),
+        );
+        (transformation_map, metrics)
+    }
+}
+
+/// Returns the map of a pass over `circuit` which has yet to move any of its opcodes.
+fn identity_map(circuit: &Circuit) -> AcirTransformationMap {
+    AcirTransformationMap::new((0..circuit.opcodes.len()).collect())
+}
+
+/// Simplifies each arithmetic opcode on its own, removing terms with zero coefficients and merging
+/// the terms which share the same witnesses.
+pub struct GeneralOptimizerPass;
+
+impl AcirPass for GeneralOptimizerPass {
+    fn name(&self) -> &str {
+        "general_optimizer"
+    }
+
+    fn run(&self, circuit: &mut Circuit) -> PassReport {
+        for opcode in &mut circuit.opcodes {
+            if let Opcode::AssertZero(expr) = opcode {
+                *expr = GeneralOptimizer::optimize(std::mem::take(expr));
+            }
+        }
+        PassReport::default()
+    }
+}
+
+/// Executes the Brillig opcodes whose inputs are known at compile time, replacing them with
+/// the assignment of their outputs.
+pub struct ConstantBrilligPass;
+
+impl AcirPass for ConstantBrilligPass {
+    fn name(&self) -> &str {
+        "constant_brillig_execution"
+    }
+
+    fn run(&self, circuit: &mut Circuit) -> PassReport {
+        let transformation_map = identity_map(circuit);
+        let executor = ConstantBrilligExecutor::new(std::mem::take(circuit));
+        let (acir, transformation_map, _num_executed_opcodes) =
+            executor.execute_constant_calls(transformation_map);
+        *circuit = acir;
+        PassReport { transformation_map: Some(transformation_map) }
+    }
+}
+
+/// Substitutes the witnesses constrained to constants into the arithmetic opcodes which use them.
+pub struct ConstantFolderPass {
+    is_observable: Rc<dyn Fn(Witness) -> bool>,
+}
+
+impl ConstantFolderPass {
+    /// `is_observable` returns whether a witness must be assigned by the solver, such as those of the ABI.
+    pub fn new(is_observable: impl Fn(Witness) -> bool + 'static) -> Self {
+        Self { is_observable: Rc::new(is_observable) }
+    }
+}
+
+impl AcirPass for ConstantFolderPass {
+    fn name(&self) -> &str {
+        "constant_folding"
+    }
+
+    fn run(&self, circuit: &mut Circuit) -> PassReport {
+        let transformation_map = identity_map(circuit);
+        let constant_folder = ConstantFolder::new(std::mem::take(circuit));
+        let (acir, transformation_map, _num_removed_opcodes) =
+            constant_folder.fold_constants(&*self.is_observable, transformation_map);
+        *circuit = acir;
+        PassReport { transformation_map: Some(transformation_map) }
+    }
+}
+
+/// Merges the intermediate witnesses which are defined by identical expressions.
+pub struct CommonSubexpressionPass {
+    is_observable: Rc<dyn Fn(Witness) -> bool>,
+}
+
+impl CommonSubexpressionPass {
+    /// `is_observable` returns whether a witness must be assigned by the solver, such as those of the ABI.
+    pub fn new(is_observable: impl Fn(Witness) -> bool + 'static) -> Self {
+        Self { is_observable: Rc::new(is_observable) }
+    }
+}
+
+impl AcirPass for CommonSubexpressionPass {
+    fn name(&self) -> &str {
+        "common_subexpression_elimination"
+    }
+
+    fn run(&self, circuit: &mut Circuit) -> PassReport {
+        let transformation_map = identity_map(circuit);
+        let cse_optimizer = CommonSubexpressionOptimizer::new(std::mem::take(circuit));
+        let (acir, transformation_map) =
+            cse_optimizer.eliminate_common_subexpressions(&*self.is_observable, transformation_map);
+        *circuit = acir;
+        PassReport { transformation_map: Some(transformation_map) }
+    }
+}
+
+/// Removes the range constraints implied by stricter constraints on the same witnesses.
+pub struct RangeOptimizerPass;
+
+impl AcirPass for RangeOptimizerPass {
+    fn name(&self) -> &str {
+        "redundant_range_elimination"
+    }
+
+    fn run(&self, circuit: &mut Circuit) -> PassReport {
+        let transformation_map = identity_map(circuit);
+        let range_optimizer = RangeOptimizer::new(std::mem::take(circuit));
+        let (acir, transformation_map) =
+            range_optimizer.replace_redundant_ranges(transformation_map);
+        *circuit = acir;
+        PassReport { transformation_map: Some(transformation_map) }
+    }
+}
+
+/// Replaces the squares of the witnesses constrained to be boolean by the witnesses themselves, recording
+/// those witnesses in the transformation map.
+///
+/// Running it before the [`ExpressionWidthPass`] spares the multiplication terms which it removes from being split.
+pub struct BooleanPass;
+
+impl AcirPass for BooleanPass {
+    fn name(&self) -> &str {
+        "boolean_simplification"
+    }
+
+    fn run(&self, circuit: &mut Circuit) -> PassReport {
+        let transformation_map = identity_map(circuit);
+        let boolean_optimizer = BooleanOptimizer::new(std::mem::take(circuit));
+        let (acir, transformation_map) =
+            boolean_optimizer.simplify_boolean_terms(transformation_map);
+        *circuit = acir;
+        PassReport { transformation_map: Some(transformation_map) }
+    }
+}
+
+/// Splits the arithmetic opcodes which do not fit within an [`ExpressionWidth`], as [`transform`][super::transform] does.
+pub struct ExpressionWidthPass(pub ExpressionWidth);
+
+impl AcirPass for ExpressionWidthPass {
+    fn name(&self) -> &str {
+        "expression_width"
+    }
+
+    fn run(&self, circuit: &mut Circuit) -> PassReport {
+        let transformation_map = identity_map(circuit);
+        let (acir, transformation_map) =
+            transform_internal(std::mem::take(circuit), self.0, transformation_map);
+        *circuit = acir;
+        PassReport { transformation_map: Some(transformation_map) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AcirPass, PassManager, PassReport};
+    use crate::compiler::{AcirTransformationMap, ExpressionWidth};
+    use acir::{
+        circuit::{
+            opcodes::{BlackBoxFuncCall, FunctionInput},
+            Circuit, Opcode,
+        },
+        native_types::{Expression, Witness},
+        FieldElement,
+    };
+
+    fn range(witness: Witness, num_bits: u32) -> Opcode {
+        Opcode::BlackBoxFuncCall(BlackBoxFuncCall::RANGE {
+            input: FunctionInput { witness, num_bits },
+        })
+    }
+
+    /// Moves the last opcode of the circuit to the front.
+    struct RotateRight;
+
+    impl AcirPass for RotateRight {
+        fn run(&self, circuit: &mut Circuit) -> PassReport {
+            let num_opcodes = circuit.opcodes.len();
+            circuit.opcodes.rotate_right(1);
+            let opcode_indices =
+                std::iter::once(num_opcodes - 1).chain(0..num_opcodes - 1).collect();
+            PassReport { transformation_map: Some(AcirTransformationMap::new(opcode_indices)) }
+        }
+    }
+
+    #[test]
+    fn runs_passes_in_order_and_composes_their_maps() {
+        let (x, y) = (Witness(1), Witness(2));
+        let mut sum = Expression::from(x);
+        sum.push_addition_term(FieldElement::zero(), y);
+        let mut circuit = Circuit {
+            current_witness_index: 2,
+            opcodes: vec![range(x, 8), Opcode::AssertZero(sum), range(x, 16)],
+            ..Circuit::default()
+        };
+
+        let mut pass_manager =
+            PassManager::with_default_passes(ExpressionWidth::Bounded(3), |_| true);
+        pass_manager.insert_pass(0, RotateRight);
+        assert_eq!(
+            pass_manager.pass_names(),
+            vec![
+                std::any::type_name::<RotateRight>(),
+                "general_optimizer",
+                "constant_brillig_execution",
+                "constant_folding",
+                "common_subexpression_elimination",
+                "redundant_range_elimination",
+                "boolean_simplification",
+                "expression_width",
+            ]
+        );
+        assert!(pass_manager.remove_pass(8).is_none());
+
+        let (transformation_map, metrics) = pass_manager.run(&mut circuit);
+
+        // The redundant range constraint, which was moved to the front, is removed. The remaining opcodes
+        // are the second and third ones of the rotated circuit, hence the first and second of the original one.
+        assert_eq!(circuit.opcodes, vec![range(x, 8), Opcode::AssertZero(Expression::from(x))]);
+        assert_eq!(transformation_map.opcode_indices(), [0, 1]);
+        let range_metrics = &metrics[5];
+        assert_eq!((range_metrics.num_opcodes_before, range_metrics.num_opcodes_after), (3, 2));
+    }
+}
@@ -0,0 +1,313 @@
+use acir::{
+    circuit::{
+        brillig::BrilligOutputs,
+        opcodes::{BlackBoxFuncCall, BlockId, MemOp},
+        visitor::Visitor,
+        Circuit, Opcode,
+    },
+    native_types::Witness,
+};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use super::partition::WitnessCollector;
+
+/// Which inputs of a [`Circuit`] may influence the value of each of its witnesses and opcodes,
+/// as computed by [`sensitivity`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Sensitivity {
+    /// The inputs influencing each witness which can be solved from the inputs.
+    dependencies: BTreeMap<Witness, BTreeSet<Witness>>,
+    /// The inputs influencing the witnesses of each opcode, or `None` if the opcode cannot be solved from the inputs.
+    opcode_dependencies: Vec<Option<BTreeSet<Witness>>>,
+    /// The witnesses of the circuit which cannot be solved from the inputs.
+    unresolved: BTreeSet<Witness>,
+}
+
+impl Sensitivity {
+    /// Returns the inputs which may influence the value of `witness`, or `None` if it cannot be solved
+    /// from the inputs. An empty set means that the witness is a constant of the circuit.
+    pub fn dependencies(&self, witness: Witness) -> Option<&BTreeSet<Witness>> {
+        self.dependencies.get(&witness)
+    }
+
+    /// Returns the witnesses whose value may be influenced by any of `inputs`, i.e. their forward cone.
+    pub fn influenced_by(&self, inputs: &BTreeSet<Witness>) -> BTreeSet<Witness> {
+        self.dependencies
+            .iter()
+            .filter(|(_, dependencies)| !dependencies.is_disjoint(inputs))
+            .map(|(witness, _)| *witness)
+            .collect()
+    }
+
+    /// Returns the index of each opcode whose witnesses may be influenced by any of `inputs`, in order.
+    ///
+    /// When only `inputs` change, these are the only opcodes which need to be solved again, along with
+    /// any opcode which could not be solved from the inputs.
+    pub fn opcodes_influenced_by(&self, inputs: &BTreeSet<Witness>) -> Vec<usize> {
+        self.opcode_dependencies
+            .iter()
+            .enumerate()
+            .filter(|(_, dependencies)| {
+                dependencies
+                    .as_ref()
+                    .map_or(false, |dependencies| !dependencies.is_disjoint(inputs))
+            })
+            .map(|(index, _)| index)
+            .collect()
+    }
+
+    /// Returns the witnesses of the circuit which cannot be solved from the inputs.
+    pub fn unresolved(&self) -> &BTreeSet<Witness> {
+        &self.unresolved
+    }
+
+    /// Returns the index of each opcode which cannot be solved from the inputs, in order.
+    pub fn unresolved_opcodes(&self) -> Vec<usize> {
+        self.opcode_dependencies
+            .iter()
+            .enumerate()
+            .filter(|(_, dependencies)| dependencies.is_none())
+            .map(|(index, _)| index)
+            .collect()
+    }
+}
+
+/// Computes which of `inputs` may influence each witness of `circuit`, by following how the ACVM solves it.
+///
+/// Opcodes are attempted in passes, as by the ACVM: an arithmetic opcode with a single unknown witness solves it
+/// from the others, and the outputs of black box function calls, directives and Brillig opcodes are solved from
+/// all of their inputs. Memory blocks are tracked as a whole, a read depending on every value written to its block
+/// beforehand. The result is an over-approximation: a witness may not actually vary with all of its dependencies.
+///
+/// Return values which depend on no input, or which are [unresolved][Sensitivity::unresolved], usually point
+/// to outputs which are not constrained as expected.
+pub fn sensitivity(circuit: &Circuit, inputs: &BTreeSet<Witness>) -> Sensitivity {
+    let mut witnesses = WitnessCollector::default();
+    witnesses.visit_circuit(circuit);
+    opcode_sensitivity(&circuit.opcodes, inputs, witnesses.0)
+}
+
+/// Computes the [`sensitivity`] of `opcodes`, `witnesses` being all of the witnesses of their circuit.
+pub(crate) fn opcode_sensitivity(
+    opcodes: &[Opcode],
+    inputs: &BTreeSet<Witness>,
+    witnesses: BTreeSet<Witness>,
+) -> Sensitivity {
+    let mut dependencies: BTreeMap<Witness, BTreeSet<Witness>> =
+        inputs.iter().map(|input| (*input, BTreeSet::from([*input]))).collect();
+    let mut block_dependencies: HashMap<BlockId, BTreeSet<Witness>> = HashMap::new();
+    let mut big_int_dependencies: HashMap<u32, BTreeSet<Witness>> = HashMap::new();
+    let mut opcode_dependencies = vec![None; opcodes.len()];
+
+    let mut unsolved: Vec<usize> = (0..opcodes.len()).collect();
+    loop {
+        let mut progress = false;
+        // As in the ACVM, operations on a memory block are only attempted once all previous ones are solved.
+        let mut stalled_blocks = BTreeSet::new();
+        unsolved.retain(|&index| {
+            let opcode = &opcodes[index];
+            let block_id = opcode.memory_block_id();
+            if matches!(block_id, Some(block_id) if stalled_blocks.contains(&block_id)) {
+                return true;
+            }
+            let solved = solve(
+                opcode,
+                &mut dependencies,
+                &mut block_dependencies,
+                &mut big_int_dependencies,
+            );
+            match solved {
+                Some(opcode_inputs) => {
+                    opcode_dependencies[index] = Some(opcode_inputs);
+                    progress = true;
+                    false
+                }
+                None => {
+                    stalled_blocks.extend(block_id);
+                    true
+                }
+            }
+        });
+        if !progress {
+            break;
+        }
+    }
+
+    let unresolved =
+        witnesses.into_iter().filter(|witness| !dependencies.contains_key(witness)).collect();
+    Sensitivity { dependencies, opcode_dependencies, unresolved }
+}
+
+/// Solves the witnesses of `opcode` if it can be, returning the inputs influencing them.
+fn solve(
+    opcode: &Opcode,
+    dependencies: &mut BTreeMap<Witness, BTreeSet<Witness>>,
+    block_dependencies: &mut HashMap<BlockId, BTreeSet<Witness>>,
+    big_int_dependencies: &mut HashMap<u32, BTreeSet<Witness>>,
+) -> Option<BTreeSet<Witness>> {
+    let mut witnesses = WitnessCollector::default();
+    witnesses.visit_opcode(opcode);
+    let (known, unknown): (Vec<Witness>, Vec<Witness>) =
+        witnesses.0.iter().partition(|witness| dependencies.contains_key(witness));
+    let mut opcode_inputs = union(known.iter().map(|witness| &dependencies[witness]));
+
+    match opcode {
+        Opcode::AssertZero(_) => match unknown[..] {
+            [] => (),
+            [witness] => {
+                dependencies.insert(witness, opcode_inputs.clone());
+            }
+            _ => return None,
+        },
+        Opcode::MemoryInit { block_id, .. } => {
+            if !unknown.is_empty() {
+                return None;
+            }
+            block_dependencies.entry(*block_id).or_default().extend(opcode_inputs.iter().copied());
+        }
+        Opcode::MemoryOp { block_id, op } => {
+            let block = block_dependencies.entry(*block_id).or_default();
+            match unknown[..] {
+                // A write, or a read of a known value, which may also be stored into the block.
+                [] => block.extend(opcode_inputs.iter().copied()),
+                // A read into the single unknown witness of the value.
+                [witness] if value_witnesses(op).contains(&witness) => {
+                    opcode_inputs.extend(block.iter().copied());
+                    dependencies.insert(witness, opcode_inputs.clone());
+                }
+                _ => return None,
+            }
+        }
+        Opcode::BlackBoxFuncCall(call) => {
+            let outputs = call.get_outputs_vec();
+            let (reads, defines) = big_int_ids(call);
+            let big_ints_known = reads.iter().all(|id| big_int_dependencies.contains_key(id));
+            if !big_ints_known || unknown.iter().any(|witness| !outputs.contains(witness)) {
+                return None;
+            }
+            for id in &reads {
+                opcode_inputs.extend(big_int_dependencies[id].iter().copied());
+            }
+            for id in defines {
+                big_int_dependencies.insert(id, opcode_inputs.clone());
+            }
+            assign(dependencies, &outputs, &opcode_inputs);
+        }
+        Opcode::Directive(directive) => {
+            let outputs = directive.outputs();
+            if unknown.iter().any(|witness| !outputs.contains(witness)) {
+                return None;
+            }
+            assign(dependencies, &outputs, &opcode_inputs);
+        }
+        Opcode::Brillig(brillig) => {
+            let outputs: Vec<Witness> = brillig
+                .outputs
+                .iter()
+                .flat_map(|output| match output {
+                    BrilligOutputs::Simple(witness) => vec![*witness],
+                    BrilligOutputs::Array(witnesses) => witnesses.clone(),
+                })
+                .collect();
+            if unknown.iter().any(|witness| !outputs.contains(witness)) {
+                return None;
+            }
+            assign(dependencies, &outputs, &opcode_inputs);
+        }
+    }
+    Some(opcode_inputs)
+}
+
+/// Assigns `inputs` as the dependencies of each of `outputs` which is not yet solved.
+fn assign(
+    dependencies: &mut BTreeMap<Witness, BTreeSet<Witness>>,
+    outputs: &[Witness],
+    inputs: &BTreeSet<Witness>,
+) {
+    for output in outputs {
+        dependencies.entry(*output).or_insert_with(|| inputs.clone());
+    }
+}
+
+fn union<'a>(sets: impl Iterator<Item = &'a BTreeSet<Witness>>) -> BTreeSet<Witness> {
+    sets.flatten().copied().collect()
+}
+
+fn value_witnesses(op: &MemOp) -> BTreeSet<Witness> {
+    let mut witnesses = WitnessCollector::default();
+    witnesses.visit_expression(&op.value);
+    witnesses.0
+}
+
+/// Returns the identifiers of the big integers which `call` reads and those which it defines.
+fn big_int_ids(call: &BlackBoxFuncCall) -> (Vec<u32>, Vec<u32>) {
+    match call {
+        BlackBoxFuncCall::BigIntAdd { lhs, rhs, output }
+        | BlackBoxFuncCall::BigIntMul { lhs, rhs, output }
+        | BlackBoxFuncCall::BigIntDiv { lhs, rhs, output } => (vec![*lhs, *rhs], vec![*output]),
+        BlackBoxFuncCall::BigIntFromLeBytes { output, .. } => (Vec::new(), vec![*output]),
+        BlackBoxFuncCall::BigIntToLeBytes { input, .. } => (vec![*input], Vec::new()),
+        _ => (Vec::new(), Vec::new()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::sensitivity;
+    use acir::{
+        circuit::{
+            brillig::{Brillig, BrilligInputs, BrilligOutputs},
+            opcodes::{BlockId, MemOp},
+            Circuit, Opcode, PublicInputs,
+        },
+        native_types::{Expression, Witness},
+    };
+    use std::collections::BTreeSet;
+
+    fn sum(lhs: Witness, rhs: Witness, output: Witness) -> Opcode {
+        Opcode::AssertZero(&(&Expression::from(lhs) + &Expression::from(rhs)) - output)
+    }
+
+    #[test]
+    fn follows_inputs_through_opcodes() {
+        let (a, b, c, d, e, f, g) =
+            (Witness(1), Witness(2), Witness(3), Witness(4), Witness(5), Witness(6), Witness(7));
+        let hint = Opcode::Brillig(Brillig {
+            inputs: vec![BrilligInputs::Single(c.into())],
+            outputs: vec![BrilligOutputs::Simple(d)],
+            foreign_call_results: Vec::new(),
+            bytecode: Vec::new(),
+            predicate: None,
+        });
+        let block_id = BlockId(0);
+        let memory_op = |operation: Expression, value: Witness| Opcode::MemoryOp {
+            block_id,
+            op: MemOp { operation, index: Expression::zero(), value: value.into() },
+        };
+        let circuit = Circuit {
+            current_witness_index: 8,
+            opcodes: vec![
+                // Out of order: `d` is only known once the Brillig opcode has been solved.
+                sum(d, b, e),
+                sum(a, b, c),
+                hint,
+                Opcode::MemoryInit { block_id, init: vec![b] },
+                memory_op(Expression::zero(), f),
+                // `g` is never solved.
+                sum(g, g, Witness(8)),
+            ],
+            return_values: PublicInputs(BTreeSet::from([e, f])),
+            ..Circuit::default()
+        };
+
+        let analysis = sensitivity(&circuit, &BTreeSet::from([a, b]));
+
+        assert_eq!(analysis.dependencies(c), Some(&BTreeSet::from([a, b])));
+        assert_eq!(analysis.dependencies(d), Some(&BTreeSet::from([a, b])));
+        assert_eq!(analysis.dependencies(f), Some(&BTreeSet::from([b])));
+        assert_eq!(analysis.influenced_by(&BTreeSet::from([a])), BTreeSet::from([a, c, d, e]));
+        assert_eq!(analysis.opcodes_influenced_by(&BTreeSet::from([a])), [0, 1, 2]);
+        assert_eq!(analysis.unresolved(), &BTreeSet::from([g, Witness(8)]));
+        assert_eq!(analysis.unresolved_opcodes(), [5]);
+    }
+}
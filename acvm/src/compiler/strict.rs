@@ -0,0 +1,114 @@
+use acir::circuit::{directives::Directive, opcodes::BlackBoxFuncCall, Opcode, OpcodeLocation};
+use thiserror::Error;
+
+/// A legacy construct rejected in strict mode, along with what replaces it.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("{construct} at opcode {location} is deprecated, {replacement}")]
+pub struct DeprecatedOpcodeError {
+    pub construct: &'static str,
+    pub location: OpcodeLocation,
+    pub replacement: &'static str,
+}
+
+/// Checks that none of `opcodes` uses a legacy construct, so that projects migrating to hints written as
+/// [Brillig][Opcode::Brillig] bytecode can enforce it and catch stale artifacts.
+///
+/// [Directives][Opcode::Directive] are replaced by Brillig opcodes, and deprecated black box functions by
+/// their successors. Memory `Block` opcodes cannot appear as they are migrated when a circuit is deserialized.
+pub fn check_deprecated_opcodes(opcodes: &[Opcode]) -> Result<(), DeprecatedOpcodeError> {
+    check_deprecated_opcodes_indexed(opcodes.iter().enumerate())
+}
+
+/// Checks the opcodes of a circuit like [`check_deprecated_opcodes`], given along with their index in the circuit.
+pub(crate) fn check_deprecated_opcodes_indexed<'a>(
+    opcodes: impl IntoIterator<Item = (usize, &'a Opcode)>,
+) -> Result<(), DeprecatedOpcodeError> {
+    for (index, opcode) in opcodes {
+        if let Some((construct, replacement)) = deprecation(opcode) {
+            let location = OpcodeLocation::Acir(index);
+            return Err(DeprecatedOpcodeError { construct, location, replacement });
+        }
+    }
+    Ok(())
+}
+
+/// Returns the name of the legacy construct used by `opcode` along with what replaces it, if any.
+fn deprecation(opcode: &Opcode) -> Option<(&'static str, &'static str)> {
+    let deprecation = match opcode {
+        Opcode::Directive(directive) => match directive {
+            Directive::Invert { .. } => {
+                ("`Invert` directive", "use a Brillig hint dividing one by the value instead")
+            }
+            Directive::Quotient(_) => (
+                "`Quotient` directive",
+                "use a Brillig hint with a `FieldIntegerDiv` opcode instead",
+            ),
+            Directive::ToLeRadix { .. } => {
+                ("`ToLeRadix` directive", "use a Brillig hint with a `ToRadix` opcode instead")
+            }
+            Directive::PermutationSort { .. } => (
+                "`PermutationSort` directive",
+                "use a Brillig hint computing the control bits of the sorting network instead",
+            ),
+            Directive::Log(_) => {
+                ("`Log` directive", "use a Brillig hint making a foreign call to a logger instead")
+            }
+            Directive::BigQuotient { .. } => (
+                "`BigQuotient` directive",
+                "use a Brillig hint dividing the limbs of the big integers instead",
+            ),
+            Directive::BigInvert { .. } => (
+                "`BigInvert` directive",
+                "use a Brillig hint inverting the limbs of the big integer instead",
+            ),
+        },
+        #[allow(deprecated)]
+        Opcode::BlackBoxFuncCall(BlackBoxFuncCall::HashToField128Security { .. }) => {
+            ("`HashToField128Security` black box function", "use `HashToField` instead")
+        }
+        #[allow(deprecated)]
+        Opcode::BlackBoxFuncCall(BlackBoxFuncCall::FixedBaseScalarMul { .. }) => (
+            "`FixedBaseScalarMul` black box function",
+            "use `MultiScalarMul` with the generator of the embedded curve instead",
+        ),
+        _ => return None,
+    };
+    Some(deprecation)
+}
+
+#[cfg(test)]
+mod tests {
+    use acir::{
+        circuit::{directives::Directive, Opcode, OpcodeLocation},
+        native_types::{Expression, Witness},
+    };
+
+    use super::{check_deprecated_opcodes, DeprecatedOpcodeError};
+
+    #[test]
+    fn rejects_directives_with_their_replacement() {
+        let opcodes = vec![
+            Opcode::AssertZero(Expression::from(Witness(1))),
+            Opcode::Directive(Directive::ToLeRadix {
+                a: Expression::from(Witness(1)),
+                b: vec![Witness(2)],
+                radix: 2,
+            }),
+        ];
+        let err = check_deprecated_opcodes(&opcodes).expect_err("directives are deprecated");
+        assert_eq!(
+            err,
+            DeprecatedOpcodeError {
+                construct: "`ToLeRadix` directive",
+                location: OpcodeLocation::Acir(1),
+                replacement: "use a Brillig hint with a `ToRadix` opcode instead",
+            }
+        );
+        assert_eq!(
+            err.to_string(),
+            "`ToLeRadix` directive at opcode 1 is deprecated, use a Brillig hint with a `ToRadix` opcode instead"
+        );
+
+        assert_eq!(check_deprecated_opcodes(&opcodes[..1]), Ok(()));
+    }
+}
@@ -347,6 +347,8 @@ impl CSatTransformer {
 
 #[test]
 fn simple_reduction_smoke_test() {
+    use acir::smallvec::smallvec;
+
     let a = Witness(0);
     let b = Witness(1);
     let c = Witness(2);
@@ -354,8 +356,8 @@ fn simple_reduction_smoke_test() {
 
     // a = b + c + d;
     let gate_a = Expression {
-        mul_terms: vec![],
-        linear_combinations: vec![
+        mul_terms: smallvec![],
+        linear_combinations: smallvec![
             (FieldElement::one(), a),
             (-FieldElement::one(), b),
             (-FieldElement::one(), c),
@@ -380,8 +382,8 @@ fn simple_reduction_smoke_test() {
     // a - b + e = 0
     let e = Witness(4);
     let expected_optimized_gate_a = Expression {
-        mul_terms: vec![],
-        linear_combinations: vec![
+        mul_terms: smallvec![],
+        linear_combinations: smallvec![
             (FieldElement::one(), a),
             (-FieldElement::one(), b),
             (FieldElement::one(), e),
@@ -394,8 +396,8 @@ fn simple_reduction_smoke_test() {
 
     // e = - c - d
     let expected_intermediate_gate = Expression {
-        mul_terms: vec![],
-        linear_combinations: vec![(-FieldElement::one(), d), (-FieldElement::one(), c)],
+        mul_terms: smallvec![],
+        linear_combinations: smallvec![(-FieldElement::one(), d), (-FieldElement::one(), c)],
         q_c: FieldElement::zero(),
     };
     let (_, normalized_gate) = CSatTransformer::normalize(expected_intermediate_gate);
@@ -1,10 +1,51 @@
+use std::collections::{HashMap, HashSet};
+
 use crate::compiler::optimizers::CircuitSimplifier;
 
-use super::super::CompileError;
+use super::super::{AcirTransformationMap, BackendCapabilities, CompileError};
 use acir::{
-    circuit::{opcodes::BlackBoxFuncCall, Circuit, Opcode},
+    circuit::{
+        opcodes::{BlackBoxFuncCall, BlockId, FunctionInput, MemOp, MemoryBlock},
+        Circuit, Opcode,
+    },
     native_types::Expression,
+    BlackBoxFunc, FieldElement,
 };
+use stdlib::helpers::CircuitBuilder;
+
+/// The largest chunks which unsupported range constraints are split into.
+///
+/// Decomposing a value into chunks relies on a radix which must fit within a `u32`.
+const MAX_RANGE_CHUNK_BITS: u32 = 31;
+
+/// A black box function call which was replaced by its fallback.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FallbackSubstitution {
+    /// Index of the call in the circuit given to the compiler.
+    pub opcode_index: usize,
+    pub black_box_func: BlackBoxFunc,
+    /// Number of opcodes the call was replaced with.
+    pub num_opcodes: usize,
+    /// Number of witnesses introduced by the fallback.
+    pub num_witnesses: u32,
+}
+
+/// Lists the black box function calls which were replaced by their fallbacks, in the order of the circuit.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FallbackReport {
+    pub substitutions: Vec<FallbackSubstitution>,
+}
+
+impl FallbackReport {
+    /// Returns the number of opcodes added by the fallbacks of every call of `black_box_func`.
+    pub fn num_opcodes_of(&self, black_box_func: BlackBoxFunc) -> usize {
+        self.substitutions
+            .iter()
+            .filter(|substitution| substitution.black_box_func == black_box_func)
+            .map(|substitution| substitution.num_opcodes)
+            .sum()
+    }
+}
 
 /// The initial transformer to act on a [`Circuit`]. This replaces any unsupported opcodes with
 /// fallback implementations consisting of well supported opcodes.
@@ -12,46 +53,82 @@ pub(crate) struct FallbackTransformer;
 
 impl FallbackTransformer {
     //ACIR pass which replace unsupported opcodes using arithmetic fallback
+    //
+    // Alongside the new circuit, returns the map relating each new opcode to the opcode of `acir` from which it was derived
+    // and the report of the substitutions which took place.
     pub(crate) fn transform(
         acir: Circuit,
-        is_supported: impl Fn(&Opcode) -> bool,
+        capabilities: &BackendCapabilities,
         simplifier: &CircuitSimplifier,
-    ) -> Result<Circuit, CompileError> {
+    ) -> Result<(Circuit, AcirTransformationMap, FallbackReport), CompileError> {
+        let is_supported = |opcode: &Opcode| capabilities.supports_opcode(opcode);
         let mut acir_supported_opcodes = Vec::with_capacity(acir.opcodes.len());
+        let mut opcode_indices = Vec::with_capacity(acir.opcodes.len());
+        let mut report = FallbackReport::default();
 
         let mut witness_idx = acir.current_witness_index + 1;
         // add opcodes for defining the witness that will be solved through simplification but must be kept
-        for w in &simplifier.defined {
+        for (w, &gadget_idx) in &simplifier.defined {
             acir_supported_opcodes.push(simplifier.define(w));
+            opcode_indices.push(simplifier.definition_index(w).unwrap_or(gadget_idx));
         }
+        let mut unsupported_blocks = Self::unsupported_memory_blocks(&acir.opcodes, &is_supported);
         for (idx, opcode) in acir.opcodes.into_iter().enumerate() {
             if !simplifier.solved_gates.contains(&idx) {
                 match &opcode {
-                    Opcode::Arithmetic(_)
-                    | Opcode::Directive(_)
-                    | Opcode::Brillig(_)
-                    | Opcode::Block(_)
-                    | Opcode::ROM(_)
-                    | Opcode::RAM(_) => {
-                        // directive, arithmetic expression or blocks are handled by acvm
+                    Opcode::AssertZero(_) | Opcode::Directive(_) | Opcode::Brillig(_) => {
+                        // directive, arithmetic expression or brillig are handled by acvm
                         acir_supported_opcodes.push(opcode);
+                        opcode_indices.push(idx);
                         continue;
                     }
+                    Opcode::MemoryInit { block_id, .. } | Opcode::MemoryOp { block_id, .. } => {
+                        let (last_index, block) = match unsupported_blocks.get(block_id) {
+                            Some(unsupported_block) => unsupported_block,
+                            None => {
+                                acir_supported_opcodes.push(opcode);
+                                opcode_indices.push(idx);
+                                continue;
+                            }
+                        };
+                        // The backend cannot prove memory blocks, so the consistency of their trace
+                        // is checked using arithmetic and range constraints instead, once all of
+                        // their operations are known.
+                        if *last_index != idx {
+                            continue;
+                        }
+                        let (updated_witness_index, opcodes_fallback) =
+                            Self::memory_fallback(block, witness_idx, &is_supported)?;
+                        unsupported_blocks.remove(block_id);
+                        witness_idx = updated_witness_index;
+
+                        opcode_indices.extend(std::iter::repeat(idx).take(opcodes_fallback.len()));
+                        acir_supported_opcodes.extend(opcodes_fallback);
+                    }
                     Opcode::BlackBoxFuncCall(bb_func_call) => {
                         // We know it is an black box function. Now check if it is
                         // supported by the backend. If it is supported, then we can simply
                         // collect the opcode
                         if is_supported(&opcode) {
                             acir_supported_opcodes.push(opcode);
+                            opcode_indices.push(idx);
                             continue;
                         } else {
                             // If we get here then we know that this black box function is not supported
                             // so we need to replace it with a version of the opcode which only uses arithmetic
                             // expressions
                             let (updated_witness_index, opcodes_fallback) =
-                                Self::opcode_fallback(bb_func_call, witness_idx)?;
+                                Self::opcode_fallback(bb_func_call, witness_idx, &is_supported)?;
+                            report.substitutions.push(FallbackSubstitution {
+                                opcode_index: idx,
+                                black_box_func: bb_func_call.get_black_box_func(),
+                                num_opcodes: opcodes_fallback.len(),
+                                num_witnesses: updated_witness_index - witness_idx,
+                            });
                             witness_idx = updated_witness_index;
 
+                            opcode_indices
+                                .extend(std::iter::repeat(idx).take(opcodes_fallback.len()));
                             acir_supported_opcodes.extend(opcodes_fallback);
                         }
                     }
@@ -59,19 +136,20 @@ impl FallbackTransformer {
             }
         }
 
-        Ok(Circuit {
-            current_witness_index: witness_idx,
-            opcodes: acir_supported_opcodes,
-            public_parameters: acir.public_parameters,
-            return_values: acir.return_values,
-        })
+        Ok((
+            Circuit { current_witness_index: witness_idx, opcodes: acir_supported_opcodes, ..acir },
+            AcirTransformationMap::new(opcode_indices),
+            report,
+        ))
     }
 
     fn opcode_fallback(
         gc: &BlackBoxFuncCall,
         current_witness_idx: u32,
+        is_supported: &impl Fn(&Opcode) -> bool,
     ) -> Result<(u32, Vec<Opcode>), CompileError> {
-        let (updated_witness_index, opcodes_fallback) = match gc {
+        let mut builder = CircuitBuilder::new(current_witness_idx);
+        match gc {
             BlackBoxFuncCall::AND { lhs, rhs, output } => {
                 assert_eq!(
                     lhs.num_bits, rhs.num_bits,
@@ -82,7 +160,7 @@ impl FallbackTransformer {
                     Expression::from(rhs.witness),
                     *output,
                     lhs.num_bits,
-                    current_witness_idx,
+                    &mut builder,
                 )
             }
             BlackBoxFuncCall::XOR { lhs, rhs, output } => {
@@ -95,22 +173,254 @@ impl FallbackTransformer {
                     Expression::from(rhs.witness),
                     *output,
                     lhs.num_bits,
-                    current_witness_idx,
+                    &mut builder,
                 )
             }
+            BlackBoxFuncCall::NOT { input, output } => stdlib::fallback::not(
+                Expression::from(input.witness),
+                *output,
+                input.num_bits,
+                &mut builder,
+            ),
+            BlackBoxFuncCall::SHL { input, shift, output } => stdlib::fallback::shl(
+                Expression::from(input.witness),
+                *output,
+                *shift,
+                input.num_bits,
+                &mut builder,
+            ),
+            BlackBoxFuncCall::SHR { input, shift, output } => stdlib::fallback::shr(
+                Expression::from(input.witness),
+                *output,
+                *shift,
+                input.num_bits,
+                &mut builder,
+            ),
             BlackBoxFuncCall::RANGE { input } => {
                 // Note there are no outputs because range produces no outputs
-                stdlib::fallback::range(
-                    Expression::from(input.witness),
-                    input.num_bits,
-                    current_witness_idx,
+                //
+                // Backends which support smaller range constraints, e.g. through a lookup table,
+                // have the input split into chunks of the largest size they support rather than into bits.
+                let chunk_bits =
+                    (2..input.num_bits.min(MAX_RANGE_CHUNK_BITS + 1)).rev().find(|&num_bits| {
+                        is_supported(&Opcode::BlackBoxFuncCall(BlackBoxFuncCall::RANGE {
+                            input: FunctionInput { witness: input.witness, num_bits },
+                        }))
+                    });
+                match chunk_bits {
+                    Some(chunk_bits) => stdlib::fallback::range_in_chunks(
+                        Expression::from(input.witness),
+                        input.num_bits,
+                        chunk_bits,
+                        &mut builder,
+                    ),
+                    None => stdlib::fallback::range(
+                        Expression::from(input.witness),
+                        input.num_bits,
+                        &mut builder,
+                    ),
+                }
+            }
+            BlackBoxFuncCall::RangeBatch { inputs } => {
+                // The batch is split into individual range constraints, which are lowered in turn if unsupported.
+                builder.extend(inputs.iter().map(|input| {
+                    Opcode::BlackBoxFuncCall(BlackBoxFuncCall::RANGE { input: *input })
+                }));
+            }
+            BlackBoxFuncCall::Blake2b { inputs, outputs } => stdlib::blackbox_fallbacks::blake2b(
+                hash_inputs(inputs),
+                outputs.clone(),
+                &mut builder,
+            ),
+            BlackBoxFuncCall::Blake2s { inputs, outputs } => stdlib::blackbox_fallbacks::blake2s(
+                hash_inputs(inputs),
+                outputs.clone(),
+                &mut builder,
+            ),
+            BlackBoxFuncCall::Blake3 { inputs, outputs } => stdlib::blackbox_fallbacks::blake3(
+                hash_inputs(inputs),
+                outputs.clone(),
+                &mut builder,
+            ),
+            BlackBoxFuncCall::EcdsaSecp256k1 {
+                public_key_x,
+                public_key_y,
+                signature,
+                hashed_message,
+                output,
+            } => {
+                let to_expressions = |inputs: &[FunctionInput]| {
+                    inputs.iter().map(|input| Expression::from(input.witness)).collect()
+                };
+                stdlib::blackbox_fallbacks::ecdsa_secp256k1(
+                    to_expressions(public_key_x),
+                    to_expressions(public_key_y),
+                    to_expressions(signature),
+                    to_expressions(hashed_message),
+                    *output,
+                    &mut builder,
+                )
+            }
+            BlackBoxFuncCall::Keccak256 { inputs, outputs } => {
+                stdlib::blackbox_fallbacks::keccak256(
+                    hash_inputs(inputs),
+                    outputs.clone(),
+                    &mut builder,
+                )
+            }
+            BlackBoxFuncCall::Keccak256VariableLength { inputs, var_message_size, outputs } => {
+                stdlib::blackbox_fallbacks::keccak256_variable_length(
+                    hash_inputs(inputs),
+                    Expression::from(var_message_size.witness),
+                    outputs.clone(),
+                    &mut builder,
+                )
+            }
+            BlackBoxFuncCall::Aes128Encrypt { inputs, iv, key, outputs } => {
+                let to_expressions = |inputs: &[FunctionInput]| {
+                    inputs.iter().map(|input| Expression::from(input.witness)).collect()
+                };
+                stdlib::blackbox_fallbacks::aes128_encrypt(
+                    to_expressions(inputs),
+                    to_expressions(iv),
+                    to_expressions(key),
+                    outputs.clone(),
+                    &mut builder,
+                )
+            }
+            BlackBoxFuncCall::Keccakf1600 { inputs, outputs } => {
+                stdlib::blackbox_fallbacks::keccakf1600(
+                    inputs.iter().map(|input| Expression::from(input.witness)).collect(),
+                    outputs.clone(),
+                    &mut builder,
+                )
+            }
+            // States whose width has no known parameters are left to be reported as unsupported.
+            BlackBoxFuncCall::Poseidon2Permutation { inputs, outputs, len }
+                if inputs.len() == *len as usize
+                    && outputs.len() == *len as usize
+                    && stdlib::blackbox_fallbacks::poseidon2_supports_width(*len as usize) =>
+            {
+                stdlib::blackbox_fallbacks::poseidon2_permutation(
+                    inputs.iter().map(|input| Expression::from(input.witness)).collect(),
+                    outputs.clone(),
+                    &mut builder,
+                )
+            }
+            BlackBoxFuncCall::SHA256 { inputs, outputs } => stdlib::blackbox_fallbacks::sha256(
+                hash_inputs(inputs),
+                outputs.clone(),
+                &mut builder,
+            ),
+            BlackBoxFuncCall::Sha256Compression { inputs, hash_values, outputs } => {
+                let to_expressions = |inputs: &[FunctionInput]| {
+                    inputs.iter().map(|input| Expression::from(input.witness)).collect()
+                };
+                stdlib::blackbox_fallbacks::sha256_compression(
+                    to_expressions(inputs),
+                    to_expressions(hash_values),
+                    outputs.clone(),
+                    &mut builder,
+                )
+            }
+            BlackBoxFuncCall::Sha512 { inputs, outputs } => stdlib::blackbox_fallbacks::sha512(
+                hash_inputs(inputs),
+                outputs.clone(),
+                &mut builder,
+            ),
+            BlackBoxFuncCall::Sha512VariableLength { inputs, var_message_size, outputs } => {
+                stdlib::blackbox_fallbacks::sha512_variable_length(
+                    hash_inputs(inputs),
+                    Expression::from(var_message_size.witness),
+                    outputs.clone(),
+                    &mut builder,
                 )
             }
             _ => {
                 return Err(CompileError::UnsupportedBlackBox(gc.get_black_box_func()));
             }
-        };
+        }
+        Self::lower_nested_fallbacks(builder, is_supported)
+    }
 
-        Ok((updated_witness_index, opcodes_fallback))
+    /// Returns the opcodes lowering `block` into constraints checking the consistency of its trace.
+    /// Gathers the memory blocks of which some opcodes are not supported, keyed by their id, along with
+    /// the index of the last opcode accessing each of them.
+    ///
+    /// The trace of each block starts with the writes of its initial values, followed by its operations in order.
+    fn unsupported_memory_blocks(
+        opcodes: &[Opcode],
+        is_supported: &impl Fn(&Opcode) -> bool,
+    ) -> HashMap<BlockId, (usize, MemoryBlock)> {
+        let unsupported_ids: HashSet<BlockId> = opcodes
+            .iter()
+            .filter(|opcode| !is_supported(opcode))
+            .filter_map(Opcode::memory_block_id)
+            .collect();
+
+        let mut blocks = HashMap::new();
+        for (idx, opcode) in opcodes.iter().enumerate() {
+            let block_id = match opcode.memory_block_id() {
+                Some(block_id) if unsupported_ids.contains(&block_id) => block_id,
+                _ => continue,
+            };
+            let (last_index, block) = blocks
+                .entry(block_id)
+                .or_insert_with(|| (idx, MemoryBlock { id: block_id, len: 0, trace: Vec::new() }));
+            *last_index = idx;
+            match opcode {
+                Opcode::MemoryInit { init, .. } => {
+                    block.len = init.len() as u32;
+                    block.trace.extend(init.iter().enumerate().map(|(index, witness)| MemOp {
+                        operation: Expression::one(),
+                        index: Expression::from_field(FieldElement::from(index as u128)),
+                        value: Expression::from(*witness),
+                    }));
+                }
+                Opcode::MemoryOp { op, .. } => block.trace.push(op.clone()),
+                _ => unreachable!("only memory opcodes access memory blocks"),
+            }
+        }
+        blocks
     }
+
+    fn memory_fallback(
+        block: &MemoryBlock,
+        current_witness_idx: u32,
+        is_supported: &impl Fn(&Opcode) -> bool,
+    ) -> Result<(u32, Vec<Opcode>), CompileError> {
+        let mut builder = CircuitBuilder::new(current_witness_idx);
+        stdlib::blackbox_fallbacks::memory::memory_block(block, &mut builder);
+        Self::lower_nested_fallbacks(builder, is_supported)
+    }
+
+    fn lower_nested_fallbacks(
+        builder: CircuitBuilder,
+        is_supported: &impl Fn(&Opcode) -> bool,
+    ) -> Result<(u32, Vec<Opcode>), CompileError> {
+        let (updated_witness_index, opcodes_fallback) = builder.finalize();
+
+        // Fallbacks may themselves be built out of black box functions (e.g. `AND` and `XOR`),
+        // which must also be replaced if the backend does not support them.
+        let mut witness_idx = updated_witness_index;
+        let mut lowered_opcodes = Vec::with_capacity(opcodes_fallback.len());
+        for opcode in opcodes_fallback {
+            match &opcode {
+                Opcode::BlackBoxFuncCall(bb_func_call) if !is_supported(&opcode) => {
+                    let (updated_witness_index, nested_fallback) =
+                        Self::opcode_fallback(bb_func_call, witness_idx, is_supported)?;
+                    witness_idx = updated_witness_index;
+                    lowered_opcodes.extend(nested_fallback);
+                }
+                _ => lowered_opcodes.push(opcode),
+            }
+        }
+
+        Ok((witness_idx, lowered_opcodes))
+    }
+}
+
+/// Pairs each input of a hash function with its bit size, which determines the bytes it contributes to the message.
+fn hash_inputs(inputs: &[FunctionInput]) -> Vec<(Expression, u32)> {
+    inputs.iter().map(|input| (Expression::from(input.witness), input.num_bits)).collect()
 }
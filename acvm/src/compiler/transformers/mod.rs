@@ -4,4 +4,5 @@ mod r1cs;
 
 pub(crate) use csat::CSatTransformer;
 pub(crate) use fallback::FallbackTransformer;
+pub use fallback::{FallbackReport, FallbackSubstitution};
 pub(crate) use r1cs::R1CSTransformer;
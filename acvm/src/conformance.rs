@@ -0,0 +1,364 @@
+//! Conformance test vectors, checking that an ACIR interpreter solves circuits as this crate does.
+//!
+//! A test vector holds a serialized circuit, the initial witness it is solved from, the responses to
+//! the foreign calls it makes and the expected outcome: either the solved witness along with the values
+//! of the circuit's return witnesses, or the kind of error solving fails with.
+//! Test vectors are grouped in JSON files, each holding an array of vectors:
+//!
+//! ```json
+//! [
+//!   {
+//!     "name": "arithmetic",
+//!     "description": "Solves an unknown witness of a linear expression.",
+//!     "circuit": "<hex encoding of the circuit serialized by `Circuit::write`>",
+//!     "inputs": { "1": "<field element>" },
+//!     "foreign_calls": [{ "function": "oracle", "inputs": [["<field element>"]], "outputs": ["<field element>"] }],
+//!     "expected": {
+//!       "solved": { "witness": { "1": "<field element>", "2": "<field element>" }, "return_values": ["<field element>"] }
+//!     }
+//!   }
+//! ]
+//! ```
+//!
+//! Field elements are encoded as unprefixed hex strings of 64 characters.
+//! A failure is expected as `{ "failed": { "error": "unsatisfied_constraint", "opcode_location": "1" } }`,
+//! where the location is omitted if the error does not report one.
+//!
+//! The initial corpus lives in `acvm/tests/conformance` and covers every opcode and black box function.
+use std::collections::BTreeMap;
+
+use acir::{
+    brillig_vm::{ForeignCallOutput, ForeignCallResult, Value},
+    circuit::Circuit,
+    native_types::{Witness, WitnessMap},
+    FieldElement,
+};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{
+    pwg::{OpcodeResolutionError, PartialWitnessGeneratorStatus, ACVM},
+    PartialWitnessGenerator,
+};
+
+#[derive(Debug, Error)]
+pub enum ConformanceError {
+    #[error("invalid test vector: {0}")]
+    InvalidTestVector(String),
+    #[error("unexpected foreign call `{function}`")]
+    UnexpectedForeignCall { function: String },
+    #[error("test vector `{name}` expected {expected:?} but got {actual:?}")]
+    Mismatch { name: String, expected: Box<Outcome>, actual: Box<Outcome> },
+}
+
+/// A circuit along with the outcome of solving it from an initial witness.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TestVector {
+    pub name: String,
+    pub description: String,
+    /// The circuit, as serialized by [`Circuit::write`].
+    #[serde(with = "hex_bytes")]
+    pub circuit: Vec<u8>,
+    pub inputs: BTreeMap<u32, FieldElement>,
+    /// The foreign calls made while solving the circuit, in the order in which they are made.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub foreign_calls: Vec<ForeignCallRecord>,
+    pub expected: Outcome,
+}
+
+/// A foreign call along with the response given to it.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ForeignCallRecord {
+    pub function: String,
+    pub inputs: Vec<Vec<FieldElement>>,
+    pub outputs: Vec<ForeignCallValues>,
+}
+
+/// The output of a foreign call, either a single value or an array of values.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ForeignCallValues {
+    Single(FieldElement),
+    Array(Vec<FieldElement>),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Outcome {
+    Solved {
+        witness: BTreeMap<u32, FieldElement>,
+        /// The values of the circuit's return witnesses, in increasing order of their indices.
+        return_values: Vec<FieldElement>,
+    },
+    Failed {
+        error: ErrorKind,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        opcode_location: Option<String>,
+    },
+}
+
+/// The kinds of errors with which solving a circuit can fail.
+///
+/// Interpreters are only expected to agree on the kind of an error, not on its message.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorKind {
+    /// The circuit is missing assignments to solve some of its opcodes.
+    Unsolvable,
+    UnsupportedBlackBoxFunction,
+    UnsatisfiedConstraint,
+    BlackBoxFunctionFailed,
+    /// A black box function call is malformed, e.g. has the wrong number of outputs.
+    InvalidBlackBoxInputs,
+    BrilligFunctionFailed,
+}
+
+impl From<&OpcodeResolutionError> for ErrorKind {
+    fn from(err: &OpcodeResolutionError) -> Self {
+        match err {
+            OpcodeResolutionError::OpcodeNotSolvable(_) => ErrorKind::Unsolvable,
+            OpcodeResolutionError::UnsupportedBlackBoxFunc(_) => {
+                ErrorKind::UnsupportedBlackBoxFunction
+            }
+            OpcodeResolutionError::UnsatisfiedConstrain { .. }
+            | OpcodeResolutionError::RangeCheckFailed { .. }
+            | OpcodeResolutionError::UnsatisfiedHintOutputs { .. } => {
+                ErrorKind::UnsatisfiedConstraint
+            }
+            OpcodeResolutionError::BlackBoxFunctionFailed(..) => ErrorKind::BlackBoxFunctionFailed,
+            OpcodeResolutionError::InvalidBlackBoxInputs { .. } => ErrorKind::InvalidBlackBoxInputs,
+            OpcodeResolutionError::BrilligFunctionFailed { .. } => ErrorKind::BrilligFunctionFailed,
+        }
+    }
+}
+
+impl TestVector {
+    /// Creates a test vector expecting the outcome of solving `circuit` with this crate.
+    pub fn new(
+        name: impl Into<String>,
+        description: impl Into<String>,
+        circuit: &Circuit,
+        inputs: WitnessMap,
+        foreign_calls: Vec<ForeignCallRecord>,
+        backend: impl PartialWitnessGenerator,
+    ) -> Result<Self, ConformanceError> {
+        let mut bytes = Vec::new();
+        circuit.write(&mut bytes).expect("circuits are serializable");
+        let mut test_vector = TestVector {
+            name: name.into(),
+            description: description.into(),
+            circuit: bytes,
+            inputs: inputs.into_iter().map(|(witness, value)| (witness.0, value)).collect(),
+            foreign_calls,
+            // Replaced by the actual outcome below.
+            expected: Outcome::Solved { witness: BTreeMap::new(), return_values: Vec::new() },
+        };
+        test_vector.expected = test_vector.execute(backend)?;
+        Ok(test_vector)
+    }
+
+    /// Solves the circuit of this test vector, resolving its foreign calls with the recorded responses.
+    ///
+    /// # Panics
+    ///
+    /// If the circuit cannot be deserialized.
+    pub fn execute(
+        &self,
+        backend: impl PartialWitnessGenerator,
+    ) -> Result<Outcome, ConformanceError> {
+        let circuit = Circuit::read(&*self.circuit).map_err(|err| {
+            ConformanceError::InvalidTestVector(format!("invalid circuit: {err}"))
+        })?;
+        let initial_witness = WitnessMap::from(
+            self.inputs
+                .iter()
+                .map(|(witness, value)| (Witness(*witness), *value))
+                .collect::<BTreeMap<_, _>>(),
+        );
+
+        let mut acvm = ACVM::new(backend, circuit.opcodes, initial_witness);
+        let mut foreign_calls = self.foreign_calls.iter();
+        loop {
+            match acvm.solve() {
+                Ok(PartialWitnessGeneratorStatus::Solved) => break,
+                Ok(PartialWitnessGeneratorStatus::RequiresForeignCall) => {
+                    let foreign_call = acvm
+                        .get_pending_foreign_call()
+                        .expect("the ACVM should be waiting on a foreign call");
+                    let inputs: Vec<Vec<FieldElement>> = foreign_call
+                        .inputs
+                        .iter()
+                        .map(|input| input.values().iter().map(Value::to_field).collect())
+                        .collect();
+                    let record = foreign_calls
+                        .next()
+                        .filter(|record| {
+                            record.function == foreign_call.function && record.inputs == inputs
+                        })
+                        .ok_or_else(|| ConformanceError::UnexpectedForeignCall {
+                            function: foreign_call.function.clone(),
+                        })?;
+                    acvm.resolve_pending_foreign_call(record.result());
+                }
+                Ok(PartialWitnessGeneratorStatus::RequiresBlackBoxCall) => {
+                    // Deferred calls are computed through the backend, so that vectors have the same
+                    // outcome whether or not it defers them.
+                    let call = acvm
+                        .get_pending_black_box_call()
+                        .expect("the ACVM should be waiting on a black box call");
+                    match call.compute(acvm.backend()) {
+                        Ok(outputs) => acvm.resolve_pending_black_box_call(outputs),
+                        Err(err) => {
+                            return Ok(Outcome::Failed {
+                                error: ErrorKind::from(&err),
+                                opcode_location: None,
+                            })
+                        }
+                    }
+                }
+                Err(err) => {
+                    return Ok(Outcome::Failed {
+                        error: ErrorKind::from(&err),
+                        opcode_location: err.opcode_location().map(|location| location.to_string()),
+                    })
+                }
+            }
+        }
+
+        let witness_map = acvm.witness_map();
+        let return_values = circuit
+            .return_values
+            .indices()
+            .into_iter()
+            .map(|witness| witness_map.get_index(witness).copied())
+            .collect::<Option<_>>();
+        Ok(match return_values {
+            Some(return_values) => Outcome::Solved {
+                witness: witness_map.iter().map(|(witness, value)| (witness.0, *value)).collect(),
+                return_values,
+            },
+            None => Outcome::Failed { error: ErrorKind::Unsolvable, opcode_location: None },
+        })
+    }
+
+    /// Checks that solving the circuit of this test vector results in the expected outcome.
+    pub fn check(&self, backend: impl PartialWitnessGenerator) -> Result<(), ConformanceError> {
+        let actual = self.execute(backend)?;
+        if actual == self.expected {
+            Ok(())
+        } else {
+            Err(ConformanceError::Mismatch {
+                name: self.name.clone(),
+                expected: Box::new(self.expected.clone()),
+                actual: Box::new(actual),
+            })
+        }
+    }
+}
+
+impl ForeignCallRecord {
+    fn result(&self) -> ForeignCallResult {
+        let values = self
+            .outputs
+            .iter()
+            .map(|output| match output {
+                ForeignCallValues::Single(value) => ForeignCallOutput::Single(Value::from(*value)),
+                ForeignCallValues::Array(values) => {
+                    ForeignCallOutput::Array(values.iter().copied().map(Value::from).collect())
+                }
+            })
+            .collect();
+        ForeignCallResult { values }
+    }
+}
+
+/// Parses the test vectors of a JSON file.
+pub fn read_test_vectors(json: &str) -> Result<Vec<TestVector>, ConformanceError> {
+    serde_json::from_str(json).map_err(|err| ConformanceError::InvalidTestVector(err.to_string()))
+}
+
+/// Returns the JSON encoding of `test_vectors`, as read by [`read_test_vectors`].
+pub fn write_test_vectors(test_vectors: &[TestVector]) -> String {
+    serde_json::to_string_pretty(test_vectors).expect("test vectors are serializable")
+}
+
+mod hex_bytes {
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub(super) fn serialize<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        let hex: String = bytes.iter().map(|byte| format!("{byte:02x}")).collect();
+        serializer.serialize_str(&hex)
+    }
+
+    pub(super) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<u8>, D::Error> {
+        let hex = String::deserialize(deserializer)?;
+        if hex.len() % 2 != 0 {
+            return Err(D::Error::custom("hex string has an odd length"));
+        }
+        (0..hex.len())
+            .step_by(2)
+            .map(|i| {
+                hex.get(i..i + 2)
+                    .and_then(|byte| u8::from_str_radix(byte, 16).ok())
+                    .ok_or_else(|| D::Error::custom(format!("invalid hex string `{hex}`")))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{read_test_vectors, write_test_vectors, ConformanceError, Outcome, TestVector};
+    use crate::testing::MockBackend;
+    use acir::{
+        circuit::{Circuit, Opcode, PublicInputs},
+        native_types::{Expression, Witness, WitnessMap},
+        FieldElement,
+    };
+    use std::collections::{BTreeMap, BTreeSet};
+
+    fn sum_test_vector(constant: u128) -> TestVector {
+        // w2 = w1 + constant
+        let mut expr = Expression::from(Witness(1));
+        expr.push_addition_term(-FieldElement::one(), Witness(2));
+        expr.q_c = FieldElement::from(constant);
+        let circuit = Circuit {
+            current_witness_index: 2,
+            opcodes: vec![Opcode::AssertZero(expr)],
+            return_values: PublicInputs(BTreeSet::from([Witness(2)])),
+            ..Circuit::default()
+        };
+        let inputs = WitnessMap::from(BTreeMap::from([(Witness(1), FieldElement::from(2_u128))]));
+        TestVector::new("sum", "", &circuit, inputs, Vec::new(), MockBackend::default()).unwrap()
+    }
+
+    #[test]
+    fn records_and_checks_outcomes() {
+        let test_vector = sum_test_vector(3);
+        let expected = Outcome::Solved {
+            witness: BTreeMap::from([
+                (1, FieldElement::from(2_u128)),
+                (2, FieldElement::from(5_u128)),
+            ]),
+            return_values: vec![FieldElement::from(5_u128)],
+        };
+        assert_eq!(test_vector.expected, expected);
+        assert!(test_vector.check(MockBackend::default()).is_ok());
+
+        let test_vectors = vec![test_vector];
+        let json = write_test_vectors(&test_vectors);
+        assert_eq!(read_test_vectors(&json).unwrap(), test_vectors);
+    }
+
+    #[test]
+    fn reports_mismatching_outcomes() {
+        let mut test_vector = sum_test_vector(3);
+        test_vector.circuit = sum_test_vector(4).circuit;
+        assert!(matches!(
+            test_vector.check(MockBackend::default()),
+            Err(ConformanceError::Mismatch { .. })
+        ));
+    }
+}
@@ -0,0 +1,263 @@
+//! Encodings of foreign calls and their results, allowing oracles to run in other processes or languages.
+//!
+//! Both the JSON and the binary encodings follow the oracle resolver protocol of the Noir tooling:
+//!
+//! ```json
+//! { "function": "get_path", "inputs": ["01", ["02", "03"]] }
+//! { "values": [["04", "05"]] }
+//! ```
+//!
+//! Field elements are encoded as strings of 64 lowercase hex digits without prefix, shortened above. They are decoded
+//! from any number of hex digits, optionally prefixed by `0x`, whose value is less than the field order.
+//! Inputs read from a register are encoded as a string, and inputs read from memory as an array of strings, as are
+//! the outputs.
+//!
+//! The binary encoding is the [MessagePack](https://msgpack.org) encoding of the same structure, with structs encoded
+//! as maps keyed by field name.
+use acir::{
+    brillig_vm::{ForeignCallInput, ForeignCallOutput, ForeignCallResult, Value},
+    FieldElement,
+};
+use serde::{Deserialize, Serialize};
+
+use super::ForeignCallError;
+use crate::pwg::ForeignCallWaitInfo;
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(untagged)]
+enum EncodedValues {
+    Single(String),
+    Array(Vec<String>),
+}
+
+impl EncodedValues {
+    fn encode(input: &ForeignCallInput) -> Self {
+        match input {
+            ForeignCallInput::Single(value) => EncodedValues::Single(value.to_field().to_hex()),
+            input => EncodedValues::Array(
+                input.values().iter().map(|value| value.to_field().to_hex()).collect(),
+            ),
+        }
+    }
+
+    fn decode(self) -> Result<Vec<Value>, ForeignCallError> {
+        let decode_value = |hex: String| {
+            FieldElement::from_hex(&hex).map(Value::from).ok_or_else(|| {
+                ForeignCallError::InvalidEncoding(format!("invalid field element `{hex}`"))
+            })
+        };
+        match self {
+            EncodedValues::Single(value) => Ok(vec![decode_value(value)?]),
+            EncodedValues::Array(values) => values.into_iter().map(decode_value).collect(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct EncodedForeignCall {
+    function: String,
+    inputs: Vec<EncodedValues>,
+}
+
+impl EncodedForeignCall {
+    fn encode(foreign_call: &ForeignCallWaitInfo) -> Self {
+        EncodedForeignCall {
+            function: foreign_call.function.clone(),
+            inputs: foreign_call.inputs.iter().map(EncodedValues::encode).collect(),
+        }
+    }
+
+    fn decode(self) -> Result<ForeignCallWaitInfo, ForeignCallError> {
+        let inputs = self
+            .inputs
+            .into_iter()
+            .map(|input| match input {
+                EncodedValues::Single(_) => {
+                    input.decode().map(|mut values| ForeignCallInput::Single(values.remove(0)))
+                }
+                EncodedValues::Array(_) => {
+                    input.decode().map(|values| ForeignCallInput::Array { pointer: 0, values })
+                }
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(ForeignCallWaitInfo { function: self.function, inputs })
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct EncodedForeignCallResult {
+    values: Vec<EncodedValues>,
+}
+
+impl EncodedForeignCallResult {
+    fn encode(foreign_call_result: &ForeignCallResult) -> Self {
+        let values = foreign_call_result
+            .values
+            .iter()
+            .map(|output| match output {
+                ForeignCallOutput::Single(value) => {
+                    EncodedValues::Single(value.to_field().to_hex())
+                }
+                ForeignCallOutput::Array(values) => EncodedValues::Array(
+                    values.iter().map(|value| value.to_field().to_hex()).collect(),
+                ),
+            })
+            .collect();
+        EncodedForeignCallResult { values }
+    }
+
+    fn decode(self) -> Result<ForeignCallResult, ForeignCallError> {
+        let values = self
+            .values
+            .into_iter()
+            .map(|output| match output {
+                EncodedValues::Single(_) => {
+                    output.decode().map(|mut values| ForeignCallOutput::Single(values.remove(0)))
+                }
+                EncodedValues::Array(_) => output.decode().map(ForeignCallOutput::Array),
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(ForeignCallResult { values })
+    }
+}
+
+fn invalid_encoding(err: impl std::fmt::Display) -> ForeignCallError {
+    ForeignCallError::InvalidEncoding(err.to_string())
+}
+
+/// Returns the JSON encoding of `foreign_call`.
+pub fn encode_foreign_call(foreign_call: &ForeignCallWaitInfo) -> serde_json::Value {
+    serde_json::to_value(EncodedForeignCall::encode(foreign_call))
+        .expect("foreign calls are serializable")
+}
+
+/// Decodes a foreign call from its JSON encoding, as received by an oracle server.
+///
+/// The encoding does not carry the memory layout of the inputs, so arrays are decoded as
+/// [arrays][ForeignCallInput::Array] read from address 0.
+pub fn decode_foreign_call(
+    foreign_call: serde_json::Value,
+) -> Result<ForeignCallWaitInfo, ForeignCallError> {
+    serde_json::from_value::<EncodedForeignCall>(foreign_call).map_err(invalid_encoding)?.decode()
+}
+
+/// Returns the JSON encoding of `foreign_call_result`, as sent by an oracle server.
+pub fn encode_foreign_call_result(foreign_call_result: &ForeignCallResult) -> serde_json::Value {
+    serde_json::to_value(EncodedForeignCallResult::encode(foreign_call_result))
+        .expect("foreign call results are serializable")
+}
+
+/// Decodes a foreign call result from its JSON encoding.
+pub fn decode_foreign_call_result(
+    foreign_call_result: serde_json::Value,
+) -> Result<ForeignCallResult, ForeignCallError> {
+    serde_json::from_value::<EncodedForeignCallResult>(foreign_call_result)
+        .map_err(invalid_encoding)?
+        .decode()
+}
+
+/// Returns the binary encoding of `foreign_call`.
+pub fn foreign_call_to_bytes(foreign_call: &ForeignCallWaitInfo) -> Vec<u8> {
+    rmp_serde::to_vec_named(&EncodedForeignCall::encode(foreign_call))
+        .expect("foreign calls are serializable")
+}
+
+/// Decodes a foreign call from its binary encoding.
+///
+/// As for the JSON encoding, arrays are decoded as [arrays][ForeignCallInput::Array] read from address 0.
+pub fn foreign_call_from_bytes(bytes: &[u8]) -> Result<ForeignCallWaitInfo, ForeignCallError> {
+    rmp_serde::from_slice::<EncodedForeignCall>(bytes).map_err(invalid_encoding)?.decode()
+}
+
+/// Returns the binary encoding of `foreign_call_result`.
+pub fn foreign_call_result_to_bytes(foreign_call_result: &ForeignCallResult) -> Vec<u8> {
+    rmp_serde::to_vec_named(&EncodedForeignCallResult::encode(foreign_call_result))
+        .expect("foreign call results are serializable")
+}
+
+/// Decodes a foreign call result from its binary encoding.
+pub fn foreign_call_result_from_bytes(bytes: &[u8]) -> Result<ForeignCallResult, ForeignCallError> {
+    rmp_serde::from_slice::<EncodedForeignCallResult>(bytes).map_err(invalid_encoding)?.decode()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        decode_foreign_call, decode_foreign_call_result, encode_foreign_call,
+        encode_foreign_call_result, foreign_call_from_bytes, foreign_call_result_from_bytes,
+        foreign_call_result_to_bytes, foreign_call_to_bytes,
+    };
+    use crate::pwg::ForeignCallWaitInfo;
+    use acir::{
+        brillig_vm::{ForeignCallInput, ForeignCallOutput, ForeignCallResult, Value},
+        FieldElement,
+    };
+    use serde_json::json;
+
+    fn foreign_call() -> ForeignCallWaitInfo {
+        ForeignCallWaitInfo {
+            function: "get_path".to_owned(),
+            inputs: vec![
+                ForeignCallInput::Single(Value::from(1_u128)),
+                ForeignCallInput::Array {
+                    pointer: 0,
+                    values: vec![Value::from(2_u128), Value::from(3_u128)],
+                },
+            ],
+        }
+    }
+
+    fn foreign_call_result() -> ForeignCallResult {
+        ForeignCallResult {
+            values: vec![
+                ForeignCallOutput::Single(Value::from(4_u128)),
+                ForeignCallOutput::Array(vec![Value::from(5_u128)]),
+            ],
+        }
+    }
+
+    #[test]
+    fn encodes_foreign_calls_and_results() {
+        let hex = |value: u128| format!("{value:064x}");
+        let encoded_call = encode_foreign_call(&foreign_call());
+        assert_eq!(
+            encoded_call,
+            json!({ "function": "get_path", "inputs": [hex(1), [hex(2), hex(3)]] })
+        );
+        assert_eq!(decode_foreign_call(encoded_call).unwrap(), foreign_call());
+
+        let encoded_result = encode_foreign_call_result(&foreign_call_result());
+        assert_eq!(encoded_result, json!({ "values": [hex(4), [hex(5)]] }));
+        assert_eq!(decode_foreign_call_result(encoded_result).unwrap(), foreign_call_result());
+    }
+
+    #[test]
+    fn binary_encoding_round_trips() {
+        let bytes = foreign_call_to_bytes(&foreign_call());
+        assert_eq!(foreign_call_from_bytes(&bytes).unwrap(), foreign_call());
+
+        let bytes = foreign_call_result_to_bytes(&foreign_call_result());
+        assert_eq!(foreign_call_result_from_bytes(&bytes).unwrap(), foreign_call_result());
+
+        assert!(foreign_call_from_bytes(&[0xc1]).is_err());
+    }
+
+    #[test]
+    fn decodes_prefixed_and_short_field_elements() {
+        let result = decode_foreign_call_result(json!({ "values": ["0x05", ["0a"]] })).unwrap();
+        assert_eq!(
+            result.values,
+            vec![
+                ForeignCallOutput::Single(Value::from(5_u128)),
+                ForeignCallOutput::Array(vec![Value::from(10_u128)]),
+            ]
+        );
+
+        let result = decode_foreign_call_result(json!({ "values": ["0x5"] })).unwrap();
+        assert_eq!(result.values, vec![ForeignCallOutput::Single(Value::from(5_u128))]);
+
+        assert!(decode_foreign_call_result(json!({ "values": ["0xzz"] })).is_err());
+        let modulus = FieldElement::modulus().to_str_radix(16);
+        assert!(decode_foreign_call_result(json!({ "values": [modulus] })).is_err());
+    }
+}
@@ -0,0 +1,175 @@
+//! Foreign calls resolved by a JSON-RPC server, following the oracle resolver protocol of the Noir tooling.
+//!
+//! Each foreign call is sent as a `resolve_foreign_call` request whose single parameter is the call,
+//! and the server answers with its result:
+//!
+//! ```json
+//! --> { "jsonrpc": "2.0", "id": 1, "method": "resolve_foreign_call", "params": [{ "function": "get_path", "inputs": ["01", ["02", "03"]] }] }
+//! <-- { "jsonrpc": "2.0", "id": 1, "result": { "values": [["04", "05"]] } }
+//! ```
+//!
+//! Foreign calls and their results are encoded as described in the [encoding module][super::encoding], with field
+//! elements shortened above.
+use acir::brillig_vm::ForeignCallResult;
+use serde::Deserialize;
+use serde_json::json;
+
+use super::{
+    encoding::{decode_foreign_call_result, encode_foreign_call},
+    ForeignCallError, ForeignCallExecutor,
+};
+use crate::pwg::ForeignCallWaitInfo;
+
+const RESOLVE_FOREIGN_CALL: &str = "resolve_foreign_call";
+
+fn invalid_encoding(err: serde_json::Error) -> ForeignCallError {
+    ForeignCallError::InvalidEncoding(err.to_string())
+}
+
+#[derive(Debug, Deserialize)]
+struct JsonRpcResponse {
+    result: Option<serde_json::Value>,
+    error: Option<JsonRpcError>,
+}
+
+#[derive(Debug, Deserialize)]
+struct JsonRpcError {
+    code: i64,
+    message: String,
+}
+
+/// Resolves foreign calls by sending them to a JSON-RPC server over HTTP.
+#[derive(Debug)]
+pub struct JsonRpcForeignCallExecutor {
+    url: String,
+    /// The identifier of the next request.
+    id: u64,
+}
+
+impl JsonRpcForeignCallExecutor {
+    pub fn new(url: impl Into<String>) -> Self {
+        JsonRpcForeignCallExecutor { url: url.into(), id: 0 }
+    }
+}
+
+impl ForeignCallExecutor for JsonRpcForeignCallExecutor {
+    fn execute(
+        &mut self,
+        foreign_call: &ForeignCallWaitInfo,
+    ) -> Result<ForeignCallResult, ForeignCallError> {
+        let failure = |reason: String| ForeignCallError::Failed {
+            function: foreign_call.function.clone(),
+            reason,
+        };
+
+        self.id += 1;
+        let request = json!({
+            "jsonrpc": "2.0",
+            "id": self.id,
+            "method": RESOLVE_FOREIGN_CALL,
+            "params": [encode_foreign_call(foreign_call)],
+        });
+        let response = ureq::post(&self.url)
+            .header("Content-Type", "application/json")
+            .send(request.to_string())
+            .map_err(|err| failure(err.to_string()))?
+            .body_mut()
+            .read_to_string()
+            .map_err(|err| failure(err.to_string()))?;
+
+        let response: JsonRpcResponse =
+            serde_json::from_str(&response).map_err(invalid_encoding)?;
+        match (response.result, response.error) {
+            (_, Some(JsonRpcError { code, message })) => {
+                Err(failure(format!("JSON-RPC error {code}: {message}")))
+            }
+            (Some(result), None) => decode_foreign_call_result(result),
+            (None, None) => Err(ForeignCallError::InvalidEncoding(
+                "JSON-RPC response has neither a result nor an error".to_owned(),
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::JsonRpcForeignCallExecutor;
+    use crate::{
+        foreign_calls::{encode_foreign_call, encode_foreign_call_result, ForeignCallExecutor},
+        pwg::ForeignCallWaitInfo,
+    };
+    use acir::brillig_vm::{ForeignCallInput, ForeignCallOutput, ForeignCallResult, Value};
+    use serde_json::json;
+    use std::{
+        io::{BufRead, BufReader, Read, Write},
+        net::TcpListener,
+    };
+
+    fn foreign_call() -> ForeignCallWaitInfo {
+        ForeignCallWaitInfo {
+            function: "get_path".to_owned(),
+            inputs: vec![
+                ForeignCallInput::Single(Value::from(1_u128)),
+                ForeignCallInput::Array {
+                    pointer: 0,
+                    values: vec![Value::from(2_u128), Value::from(3_u128)],
+                },
+            ],
+        }
+    }
+
+    fn foreign_call_result() -> ForeignCallResult {
+        ForeignCallResult {
+            values: vec![
+                ForeignCallOutput::Single(Value::from(4_u128)),
+                ForeignCallOutput::Array(vec![Value::from(5_u128)]),
+            ],
+        }
+    }
+
+    #[test]
+    fn resolves_foreign_calls_through_json_rpc() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let server = std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream);
+            let mut content_length = 0;
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                let line = line.trim_end().to_ascii_lowercase();
+                if line.is_empty() {
+                    break;
+                }
+                if let Some(length) = line.strip_prefix("content-length:") {
+                    content_length = length.trim().parse().unwrap();
+                }
+            }
+            let mut body = vec![0; content_length];
+            reader.read_exact(&mut body).unwrap();
+            let request: serde_json::Value = serde_json::from_slice(&body).unwrap();
+
+            let response = json!({
+                "jsonrpc": "2.0",
+                "id": request["id"],
+                "result": encode_foreign_call_result(&foreign_call_result()),
+            })
+            .to_string();
+            write!(
+                reader.get_mut(),
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{response}",
+                response.len()
+            )
+            .unwrap();
+            request
+        });
+
+        let result = JsonRpcForeignCallExecutor::new(url).execute(&foreign_call()).unwrap();
+        let request = server.join().unwrap();
+
+        assert_eq!(result, foreign_call_result());
+        assert_eq!(request["method"], "resolve_foreign_call");
+        assert_eq!(request["params"][0], encode_foreign_call(&foreign_call()));
+    }
+}
@@ -0,0 +1,45 @@
+//! Resolution of the [foreign calls][acir::brillig_vm::Opcode::ForeignCall] made by Brillig opcodes.
+//!
+//! When the [ACVM][crate::pwg::ACVM] requires a foreign call, the caller resolves it with a [`ForeignCallExecutor`]
+//! and passes its result back to the ACVM. The foreign calls made by a circuit can be checked against those the
+//! host resolves ahead of solving it with a [`ForeignCallRegistry`].
+use acir::brillig_vm::ForeignCallResult;
+use thiserror::Error;
+
+use crate::pwg::ForeignCallWaitInfo;
+
+#[cfg(feature = "foreign-call-encoding")]
+pub mod encoding;
+#[cfg(feature = "jsonrpc")]
+mod jsonrpc;
+mod registry;
+
+#[cfg(feature = "foreign-call-encoding")]
+pub use encoding::{
+    decode_foreign_call, decode_foreign_call_result, encode_foreign_call,
+    encode_foreign_call_result, foreign_call_from_bytes, foreign_call_result_from_bytes,
+    foreign_call_result_to_bytes, foreign_call_to_bytes,
+};
+#[cfg(feature = "jsonrpc")]
+pub use jsonrpc::JsonRpcForeignCallExecutor;
+pub use registry::{
+    ForeignCallParamKind, ForeignCallParamShape, ForeignCallRegistry, ForeignCallSignature,
+    ForeignCallSignatureError,
+};
+
+#[derive(Debug, Error)]
+pub enum ForeignCallError {
+    #[error("foreign call `{function}` failed: {reason}")]
+    Failed { function: String, reason: String },
+    #[error("invalid foreign call encoding: {0}")]
+    InvalidEncoding(String),
+}
+
+/// Resolves the foreign calls required by the ACVM.
+pub trait ForeignCallExecutor {
+    /// Returns the outputs of `foreign_call`.
+    fn execute(
+        &mut self,
+        foreign_call: &ForeignCallWaitInfo,
+    ) -> Result<ForeignCallResult, ForeignCallError>;
+}
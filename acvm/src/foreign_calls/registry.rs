@@ -0,0 +1,185 @@
+//! Signatures of the foreign calls which a host provides, against which Brillig bytecode is validated.
+//!
+//! The shapes of the inputs and outputs of a [`ForeignCall`][brillig_vm::Opcode::ForeignCall] are fixed by its
+//! bytecode, so a call which the host would not resolve, or would resolve with values of another shape, is
+//! reported before the circuit is solved rather than when the call is made.
+use std::collections::BTreeMap;
+
+use acir::{
+    brillig_vm::{self, RegisterOrMemory},
+    circuit::{Opcode, OpcodeLocation},
+};
+use thiserror::Error;
+
+/// The shape of an input or output of a foreign call.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ForeignCallParamShape {
+    /// A single value, held in a register.
+    Single,
+    /// An array of the given number of values, held in memory.
+    Array(usize),
+    /// A vector of values whose length is only known at runtime, held in memory.
+    Vector,
+}
+
+impl ForeignCallParamShape {
+    fn of(param: &RegisterOrMemory) -> Self {
+        match param {
+            RegisterOrMemory::RegisterIndex(_) => ForeignCallParamShape::Single,
+            RegisterOrMemory::HeapArray(_, size) => ForeignCallParamShape::Array(*size),
+            RegisterOrMemory::HeapVector(..) => ForeignCallParamShape::Vector,
+        }
+    }
+}
+
+impl std::fmt::Display for ForeignCallParamShape {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ForeignCallParamShape::Single => write!(f, "a single value"),
+            ForeignCallParamShape::Array(size) => write!(f, "an array of {size} values"),
+            ForeignCallParamShape::Vector => write!(f, "a vector"),
+        }
+    }
+}
+
+/// Whether a parameter of a foreign call is one of its inputs or one of its outputs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ForeignCallParamKind {
+    Input,
+    Output,
+}
+
+impl std::fmt::Display for ForeignCallParamKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ForeignCallParamKind::Input => write!(f, "input"),
+            ForeignCallParamKind::Output => write!(f, "output"),
+        }
+    }
+}
+
+/// The shapes of the inputs and outputs of a foreign call, in order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ForeignCallSignature {
+    pub inputs: Vec<ForeignCallParamShape>,
+    pub outputs: Vec<ForeignCallParamShape>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum ForeignCallSignatureError {
+    #[error("foreign call `{function}` at opcode {location} is not registered")]
+    UnknownFunction { function: String, location: OpcodeLocation },
+    #[error("foreign call `{function}` at opcode {location} has {actual} {kind}s rather than {expected}")]
+    ArityMismatch {
+        function: String,
+        location: OpcodeLocation,
+        kind: ForeignCallParamKind,
+        expected: usize,
+        actual: usize,
+    },
+    #[error("{kind} {index} of foreign call `{function}` at opcode {location} is {actual} rather than {expected}")]
+    ShapeMismatch {
+        function: String,
+        location: OpcodeLocation,
+        kind: ForeignCallParamKind,
+        index: usize,
+        expected: ForeignCallParamShape,
+        actual: ForeignCallParamShape,
+    },
+}
+
+/// The foreign calls which a host resolves, by name, along with their signatures.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ForeignCallRegistry {
+    signatures: BTreeMap<String, ForeignCallSignature>,
+}
+
+impl ForeignCallRegistry {
+    pub fn new() -> Self {
+        ForeignCallRegistry::default()
+    }
+
+    /// Declares the foreign call `function`, taking `inputs` and returning `outputs`.
+    pub fn register(
+        mut self,
+        function: impl Into<String>,
+        inputs: Vec<ForeignCallParamShape>,
+        outputs: Vec<ForeignCallParamShape>,
+    ) -> Self {
+        self.signatures.insert(function.into(), ForeignCallSignature { inputs, outputs });
+        self
+    }
+
+    /// Returns the signature of the foreign call `function`, if it is registered.
+    pub fn signature(&self, function: &str) -> Option<&ForeignCallSignature> {
+        self.signatures.get(function)
+    }
+
+    /// Checks that every foreign call made by the Brillig opcodes of `opcodes` is registered,
+    /// with the inputs and outputs of its signature.
+    pub fn validate(&self, opcodes: &[Opcode]) -> Result<(), ForeignCallSignatureError> {
+        self.validate_indexed(opcodes.iter().enumerate())
+    }
+
+    /// Checks the foreign calls of opcodes paired with their index in the circuit.
+    pub(crate) fn validate_indexed<'a>(
+        &self,
+        opcodes: impl IntoIterator<Item = (usize, &'a Opcode)>,
+    ) -> Result<(), ForeignCallSignatureError> {
+        for (acir_index, opcode) in opcodes {
+            if let Opcode::Brillig(brillig) = opcode {
+                self.validate_bytecode(acir_index, &brillig.bytecode)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks the foreign calls of the bytecode of the Brillig opcode at `acir_index`.
+    fn validate_bytecode(
+        &self,
+        acir_index: usize,
+        bytecode: &[brillig_vm::Opcode],
+    ) -> Result<(), ForeignCallSignatureError> {
+        for (brillig_index, opcode) in bytecode.iter().enumerate() {
+            let (function, inputs, destinations) = match opcode {
+                brillig_vm::Opcode::ForeignCall { function, inputs, destinations } => {
+                    (function, inputs, destinations)
+                }
+                _ => continue,
+            };
+            let location = OpcodeLocation::Brillig { acir_index, brillig_index };
+            let signature = self.signature(function).ok_or_else(|| {
+                ForeignCallSignatureError::UnknownFunction { function: function.clone(), location }
+            })?;
+            let params = [
+                (ForeignCallParamKind::Input, &signature.inputs, inputs),
+                (ForeignCallParamKind::Output, &signature.outputs, destinations),
+            ];
+            for (kind, expected, actual) in params {
+                if expected.len() != actual.len() {
+                    return Err(ForeignCallSignatureError::ArityMismatch {
+                        function: function.clone(),
+                        location,
+                        kind,
+                        expected: expected.len(),
+                        actual: actual.len(),
+                    });
+                }
+                for (index, (expected, actual)) in expected.iter().zip(actual).enumerate() {
+                    let actual = ForeignCallParamShape::of(actual);
+                    if *expected != actual {
+                        return Err(ForeignCallSignatureError::ShapeMismatch {
+                            function: function.clone(),
+                            location,
+                            kind,
+                            index,
+                            expected: *expected,
+                            actual,
+                        });
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
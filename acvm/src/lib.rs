@@ -1,19 +1,31 @@
 #![warn(unused_crate_dependencies)]
 #![warn(unreachable_pub)]
 
+pub mod abi;
+pub mod blackbox_solver;
 pub mod compiler;
+#[cfg(feature = "conformance")]
+pub mod conformance;
+pub mod foreign_calls;
 pub mod pwg;
+#[cfg(feature = "testing")]
+pub mod testing;
 
 use acir::{
-    circuit::{opcodes::FunctionInput, Circuit, Opcode},
-    native_types::{Witness, WitnessMap},
+    circuit::{Circuit, Opcode},
+    native_types::WitnessMap,
+    BlackBoxFunc,
 };
 use core::fmt::Debug;
-use pwg::{OpcodeResolution, OpcodeResolutionError};
+use pwg::OpcodeResolutionError;
 
 // We re-export async-trait so consumers can attach it to their impl
 pub use async_trait::async_trait;
 
+// The crate depends on itself to enable the `testing` feature in its integration tests.
+#[cfg(test)]
+use acvm as _;
+
 // re-export acir
 pub use acir;
 pub use acir::FieldElement;
@@ -67,34 +79,61 @@ pub trait CommonReferenceString {
     ) -> Result<Vec<u8>, Self::Error>;
 }
 
-/// This component will generate the backend specific output for each [`Opcode::BlackBoxFuncCall`].
+/// Computes the black box functions which the ACVM cannot solve natively for each [`Opcode::BlackBoxFuncCall`].
+///
+/// The ACVM gathers and validates the values of the inputs of each opcode before calling the backend,
+/// and assigns the returned values to the outputs of the opcode, so that backends only compute on field elements.
 ///
-/// Returns an [`OpcodeResolutionError`] if the backend does not support the given [`Opcode::BlackBoxFuncCall`].
+/// Returns an [`OpcodeResolutionError`] if the backend does not support the given black box function.
 pub trait PartialWitnessGenerator {
-    #[allow(clippy::too_many_arguments)]
+    /// Returns whether `(signature_s, signature_e)` is a valid Schnorr signature of `message` for the public key
+    /// `(public_key_x, public_key_y)`.
     fn schnorr_verify(
         &self,
-        initial_witness: &mut WitnessMap,
-        public_key_x: FunctionInput,
-        public_key_y: FunctionInput,
-        signature_s: FunctionInput,
-        signature_e: FunctionInput,
-        message: &[FunctionInput],
-        output: Witness,
-    ) -> Result<OpcodeResolution, OpcodeResolutionError>;
+        public_key_x: &FieldElement,
+        public_key_y: &FieldElement,
+        signature_s: &FieldElement,
+        signature_e: &FieldElement,
+        message: &[u8],
+    ) -> Result<bool, OpcodeResolutionError>;
+    /// Returns the coordinates of the Pedersen commitment to `inputs`.
+    ///
+    /// This is not called if the `native-pedersen` feature is enabled, in which case the ACVM computes the commitment itself.
     fn pedersen(
         &self,
-        initial_witness: &mut WitnessMap,
-        inputs: &[FunctionInput],
+        inputs: &[FieldElement],
         domain_separator: u32,
-        outputs: (Witness, Witness),
-    ) -> Result<OpcodeResolution, OpcodeResolutionError>;
+    ) -> Result<(FieldElement, FieldElement), OpcodeResolutionError>;
+    /// Returns the coordinates of the product of the generator of the embedded curve by `input`.
     fn fixed_base_scalar_mul(
         &self,
-        initial_witness: &mut WitnessMap,
-        input: FunctionInput,
-        outputs: (Witness, Witness),
-    ) -> Result<OpcodeResolution, OpcodeResolutionError>;
+        input: &FieldElement,
+    ) -> Result<(FieldElement, FieldElement), OpcodeResolutionError>;
+    /// Returns the output aggregation object of a `RecursiveAggregation` opcode, which must have `output_len` elements.
+    ///
+    /// The shapes of the inputs are checked by the ACVM before this is called.
+    /// Backends which cannot aggregate proofs while solving a circuit may rely on the default implementation,
+    /// which returns [`OpcodeResolutionError::UnsupportedBlackBoxFunc`].
+    fn recursive_aggregation(
+        &self,
+        _verification_key: &[FieldElement],
+        _proof: &[FieldElement],
+        _public_inputs: &[FieldElement],
+        _key_hash: &FieldElement,
+        _input_aggregation_object: Option<&[FieldElement]>,
+        _output_len: usize,
+    ) -> Result<Vec<FieldElement>, OpcodeResolutionError> {
+        Err(OpcodeResolutionError::UnsupportedBlackBoxFunc(BlackBoxFunc::RecursiveAggregation))
+    }
+    /// Returns whether the backend computes `func` asynchronously, such as on a hardware accelerator.
+    ///
+    /// Rather than calling the backend for such functions, the ACVM returns
+    /// [`PartialWitnessGeneratorStatus::RequiresBlackBoxCall`][pwg::PartialWitnessGeneratorStatus::RequiresBlackBoxCall]
+    /// so that the caller can compute them without blocking the solver. Only the functions computed by the backend
+    /// through the methods above can be deferred.
+    fn defers_black_box_func(&self, _func: BlackBoxFunc) -> bool {
+        false
+    }
 }
 
 pub trait SmartContract {
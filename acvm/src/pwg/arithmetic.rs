@@ -1,9 +1,14 @@
+use std::{collections::HashMap, sync::Arc};
+
 use acir::{
+    circuit::{opcodes::BlackBoxFuncCall, Opcode},
     native_types::{Expression, Witness, WitnessMap},
     FieldElement,
 };
 
-use super::{insert_value, OpcodeNotSolvable, OpcodeResolution, OpcodeResolutionError};
+use super::{
+    insert_value, ErrorLocation, OpcodeNotSolvable, OpcodeResolution, OpcodeResolutionError,
+};
 
 /// An Arithmetic solver will take a Circuit's arithmetic gates with witness assignments
 /// and create the other witness variables
@@ -22,12 +27,163 @@ pub(crate) enum MulTerm {
     Solved(FieldElement),
 }
 
+/// The [integer forms][IntegerExpression] of the arithmetic opcodes of a circuit whose witnesses are all
+/// [range constrained][BlackBoxFuncCall::RANGE], with which
+/// [`ACVM::with_integer_expressions`][crate::pwg::ACVM::with_integer_expressions] checks these opcodes using
+/// integer rather than field arithmetic once all of their witnesses are known.
+///
+/// Building them costs about as much as evaluating each of these opcodes once with field arithmetic, so they
+/// are meant to be built once per circuit and shared across its solves. Clones share the same integer forms.
+#[derive(Clone, Debug, Default)]
+pub struct IntegerExpressions {
+    /// The integer form of each arithmetic opcode which has one, keyed by the index of the opcode.
+    expressions: Arc<HashMap<usize, IntegerExpression>>,
+}
+
+impl IntegerExpressions {
+    /// Builds the integer forms of the arithmetic opcodes among `opcodes`, the opcodes of the circuit to solve.
+    pub fn new(opcodes: &[Opcode]) -> Self {
+        let max_values = range_constrained_witnesses(opcodes);
+        if max_values.is_empty() {
+            return Self::default();
+        }
+        let expressions = opcodes
+            .iter()
+            .enumerate()
+            .filter_map(|(index, opcode)| match opcode {
+                Opcode::AssertZero(expr) => {
+                    Some((index, IntegerExpression::new(expr, &max_values)?))
+                }
+                _ => None,
+            })
+            .collect();
+        IntegerExpressions { expressions: Arc::new(expressions) }
+    }
+
+    /// Returns the number of arithmetic opcodes which have an integer form.
+    pub fn len(&self) -> usize {
+        self.expressions.len()
+    }
+
+    /// Returns `true` if no arithmetic opcode has an integer form.
+    pub fn is_empty(&self) -> bool {
+        self.expressions.is_empty()
+    }
+
+    /// Returns the integer form of the arithmetic opcode at `index` within the circuit, if it has one.
+    pub(super) fn get(&self, index: usize) -> Option<&IntegerExpression> {
+        if self.expressions.is_empty() {
+            return None;
+        }
+        self.expressions.get(&index)
+    }
+}
+
+/// Returns the largest value of each witness which is [range constrained][BlackBoxFuncCall::RANGE] by `opcodes`
+/// to fit in a `u128`.
+fn range_constrained_witnesses(opcodes: &[Opcode]) -> HashMap<Witness, u128> {
+    let mut max_values = HashMap::new();
+    let range_inputs = opcodes.iter().flat_map(|opcode| match opcode {
+        Opcode::BlackBoxFuncCall(BlackBoxFuncCall::RANGE { input }) => std::slice::from_ref(input),
+        Opcode::BlackBoxFuncCall(BlackBoxFuncCall::RangeBatch { inputs }) => inputs,
+        _ => &[],
+    });
+    for input in range_inputs.filter(|input| input.num_bits <= u128::BITS) {
+        let max_value = u128::MAX >> (u128::BITS - input.num_bits);
+        max_values
+            .entry(input.witness)
+            .and_modify(|max: &mut u128| *max = (*max).min(max_value))
+            .or_insert(max_value);
+    }
+    max_values
+}
+
+/// A range constrained witness along with its largest value.
+type RangedWitness = (Witness, u128);
+
+/// An expression over range constrained witnesses whose value fits in an `i128` for any values of its witnesses
+/// within their ranges, so that it can be evaluated with integer rather than field arithmetic.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct IntegerExpression {
+    mul_terms: Vec<(i128, RangedWitness, RangedWitness)>,
+    linear_combinations: Vec<(i128, RangedWitness)>,
+    q_c: i128,
+}
+
+impl IntegerExpression {
+    /// Returns the integer form of `expr`, given the largest value of the range constrained witnesses,
+    /// or `None` should any of its witnesses not be range constrained or its value not be bounded by an `i128`.
+    fn new(expr: &Expression, max_values: &HashMap<Witness, u128>) -> Option<Self> {
+        let witness = |witness: &Witness| Some((*witness, *max_values.get(witness)?));
+        let mul_terms: Vec<_> = expr
+            .mul_terms
+            .iter()
+            .filter(|(q_m, ..)| !q_m.is_zero())
+            .map(|(q_m, w_l, w_r)| Some((q_m.try_into_i128()?, witness(w_l)?, witness(w_r)?)))
+            .collect::<Option<_>>()?;
+        let linear_combinations: Vec<_> = expr
+            .linear_combinations
+            .iter()
+            .filter(|(q_l, _)| !q_l.is_zero())
+            .map(|(q_l, w_l)| Some((q_l.try_into_i128()?, witness(w_l)?)))
+            .collect::<Option<_>>()?;
+        let q_c = expr.q_c.try_into_i128()?;
+
+        // Terms are evaluated without overflow checks, so the sum of their magnitudes must fit in an i128.
+        let mut bound = q_c.unsigned_abs();
+        for (q_m, (_, max_l), (_, max_r)) in &mul_terms {
+            let term = q_m.unsigned_abs().checked_mul(*max_l)?.checked_mul(*max_r)?;
+            bound = bound.checked_add(term)?;
+        }
+        for (q_l, (_, max_l)) in &linear_combinations {
+            bound = bound.checked_add(q_l.unsigned_abs().checked_mul(*max_l)?)?;
+        }
+        (bound <= i128::MAX as u128).then_some(IntegerExpression {
+            mul_terms,
+            linear_combinations,
+            q_c,
+        })
+    }
+
+    /// Evaluates the expression, returning `None` should any of its witnesses be unassigned or assigned
+    /// a value out of its range, as the range constraints may not have been checked yet.
+    ///
+    /// As the value of the expression is bounded by an `i128`, it is zero in the field if and only if it is zero.
+    fn evaluate(&self, witness_assignments: &WitnessMap) -> Option<i128> {
+        let value = |(witness, max_value): &RangedWitness| {
+            let value = witness_assignments.get(witness)?.try_into_u128()?;
+            (value <= *max_value).then_some(value as i128)
+        };
+        let mut result = self.q_c;
+        for (q_m, w_l, w_r) in &self.mul_terms {
+            result += q_m * value(w_l)? * value(w_r)?;
+        }
+        for (q_l, w_l) in &self.linear_combinations {
+            result += q_l * value(w_l)?;
+        }
+        Some(result)
+    }
+}
+
 impl ArithmeticSolver {
     /// Derives the rest of the witness based on the initial low level variables
+    ///
+    /// The gate is checked with integer arithmetic through `integer_gate`, its [integer form][IntegerExpression]
+    /// if it has one, once all of its witnesses are known.
     pub(super) fn solve(
         initial_witness: &mut WitnessMap,
         gate: &Expression,
+        integer_gate: Option<&IntegerExpression>,
     ) -> Result<OpcodeResolution, OpcodeResolutionError> {
+        if let Some(value) = integer_gate.and_then(|gate| gate.evaluate(initial_witness)) {
+            return if value == 0 {
+                Ok(OpcodeResolution::Solved)
+            } else {
+                Err(OpcodeResolutionError::UnsatisfiedConstrain {
+                    opcode_location: ErrorLocation::Unresolved,
+                })
+            };
+        }
         let gate = &ArithmeticSolver::evaluate(gate, initial_witness);
         // Evaluate multiplication term
         let mul_result = ArithmeticSolver::solve_mul_term(gate, initial_witness);
@@ -37,7 +193,7 @@ impl ArithmeticSolver {
         match (mul_result, gate_status) {
             (MulTerm::TooManyUnknowns, _) | (_, GateStatus::GateUnsolvable) => {
                 Ok(OpcodeResolution::Stalled(OpcodeNotSolvable::ExpressionHasTooManyUnknowns(
-                    gate.clone(),
+                    Box::new(gate.clone()),
                 )))
             }
             (MulTerm::OneUnknown(q, w1), GateStatus::GateSolvable(a, (b, w2))) => {
@@ -46,7 +202,9 @@ impl ArithmeticSolver {
                     let total_sum = a + gate.q_c;
                     if (q + b).is_zero() {
                         if !total_sum.is_zero() {
-                            Err(OpcodeResolutionError::UnsatisfiedConstrain)
+                            Err(OpcodeResolutionError::UnsatisfiedConstrain {
+                                opcode_location: ErrorLocation::Unresolved,
+                            })
                         } else {
                             Ok(OpcodeResolution::Solved)
                         }
@@ -59,7 +217,7 @@ impl ArithmeticSolver {
                 } else {
                     // TODO: can we be more specific with this error?
                     Ok(OpcodeResolution::Stalled(OpcodeNotSolvable::ExpressionHasTooManyUnknowns(
-                        gate.clone(),
+                        Box::new(gate.clone()),
                     )))
                 }
             }
@@ -71,7 +229,9 @@ impl ArithmeticSolver {
                 let total_sum = sum + gate.q_c;
                 if partial_prod.is_zero() {
                     if !total_sum.is_zero() {
-                        Err(OpcodeResolutionError::UnsatisfiedConstrain)
+                        Err(OpcodeResolutionError::UnsatisfiedConstrain {
+                            opcode_location: ErrorLocation::Unresolved,
+                        })
                     } else {
                         Ok(OpcodeResolution::Solved)
                     }
@@ -86,7 +246,9 @@ impl ArithmeticSolver {
                 // All the variables in the MulTerm are solved and the Fan-in is also solved
                 // There is nothing to solve
                 if !(a + b + gate.q_c).is_zero() {
-                    Err(OpcodeResolutionError::UnsatisfiedConstrain)
+                    Err(OpcodeResolutionError::UnsatisfiedConstrain {
+                        opcode_location: ErrorLocation::Unresolved,
+                    })
                 } else {
                     Ok(OpcodeResolution::Solved)
                 }
@@ -101,7 +263,9 @@ impl ArithmeticSolver {
                 let total_sum = total_prod + partial_sum + gate.q_c;
                 if coeff.is_zero() {
                     if !total_sum.is_zero() {
-                        Err(OpcodeResolutionError::UnsatisfiedConstrain)
+                        Err(OpcodeResolutionError::UnsatisfiedConstrain {
+                            opcode_location: ErrorLocation::Unresolved,
+                        })
                     } else {
                         Ok(OpcodeResolution::Solved)
                     }
@@ -245,6 +409,8 @@ impl ArithmeticSolver {
 
 #[test]
 fn arithmetic_smoke_test() {
+    use acir::smallvec::smallvec;
+
     let a = Witness(0);
     let b = Witness(1);
     let c = Witness(2);
@@ -252,8 +418,8 @@ fn arithmetic_smoke_test() {
 
     // a = b + c + d;
     let gate_a = Expression {
-        mul_terms: vec![],
-        linear_combinations: vec![
+        mul_terms: smallvec![],
+        linear_combinations: smallvec![
             (FieldElement::one(), a),
             (-FieldElement::one(), b),
             (-FieldElement::one(), c),
@@ -264,8 +430,8 @@ fn arithmetic_smoke_test() {
 
     let e = Witness(4);
     let gate_b = Expression {
-        mul_terms: vec![],
-        linear_combinations: vec![
+        mul_terms: smallvec![],
+        linear_combinations: smallvec![
             (FieldElement::one(), e),
             (-FieldElement::one(), a),
             (-FieldElement::one(), b),
@@ -278,8 +444,55 @@ fn arithmetic_smoke_test() {
     values.insert(c, FieldElement::from(1_i128));
     values.insert(d, FieldElement::from(1_i128));
 
-    assert_eq!(ArithmeticSolver::solve(&mut values, &gate_a), Ok(OpcodeResolution::Solved));
-    assert_eq!(ArithmeticSolver::solve(&mut values, &gate_b), Ok(OpcodeResolution::Solved));
+    assert_eq!(ArithmeticSolver::solve(&mut values, &gate_a, None), Ok(OpcodeResolution::Solved));
+    assert_eq!(ArithmeticSolver::solve(&mut values, &gate_b, None), Ok(OpcodeResolution::Solved));
 
     assert_eq!(values.get(&a).unwrap(), &FieldElement::from(4_i128));
 }
+
+#[test]
+fn integer_expressions_match_field_evaluation() {
+    use acir::{circuit::opcodes::FunctionInput, smallvec::smallvec};
+
+    let (a, b, c) = (Witness(0), Witness(1), Witness(2));
+    let range = |witness, num_bits| {
+        Opcode::BlackBoxFuncCall(BlackBoxFuncCall::RANGE {
+            input: FunctionInput { witness, num_bits },
+        })
+    };
+    let max_values = range_constrained_witnesses(&[range(a, 8), range(b, 64), range(c, 8)]);
+
+    // 2ab - 3c + 7 - 2^64 a
+    let expr = Expression {
+        mul_terms: smallvec![(FieldElement::from(2_u128), a, b)],
+        linear_combinations: smallvec![
+            (-FieldElement::from(3_u128), c),
+            (-FieldElement::from(1_u128 << 64), a)
+        ],
+        q_c: FieldElement::from(7_u128),
+    };
+    let integer_expr = IntegerExpression::new(&expr, &max_values).unwrap();
+
+    let mut values = WitnessMap::new();
+    assert_eq!(integer_expr.evaluate(&values), None, "witnesses are unknown");
+    values.insert(a, FieldElement::from(200_u128));
+    values.insert(b, FieldElement::from(u64::MAX as u128));
+    values.insert(c, FieldElement::from(3_u128));
+    let value = integer_expr.evaluate(&values).unwrap();
+    assert_eq!(
+        Expression::from(FieldElement::from(value)),
+        ArithmeticSolver::evaluate(&expr, &values)
+    );
+
+    // Values out of their range are left to field arithmetic, as the range constraints may not be checked yet.
+    values.insert(c, FieldElement::from(256_u128));
+    assert_eq!(integer_expr.evaluate(&values), None);
+
+    // Expressions which could overflow an i128 or have a witness without a range have no integer form.
+    let mut overflowing = expr.clone();
+    overflowing.linear_combinations.push((FieldElement::from(u64::MAX as u128), b));
+    assert_eq!(IntegerExpression::new(&overflowing, &max_values), None);
+    let mut unconstrained = expr;
+    unconstrained.linear_combinations.push((FieldElement::one(), Witness(3)));
+    assert_eq!(IntegerExpression::new(&unconstrained, &max_values), None);
+}
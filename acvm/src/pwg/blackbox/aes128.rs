@@ -0,0 +1,209 @@
+use acir::{
+    circuit::opcodes::FunctionInput,
+    native_types::{Witness, WitnessMap},
+    BlackBoxFunc, FieldElement,
+};
+
+use super::ecdsa::to_u8_vec;
+use crate::{
+    pwg::{insert_value, OpcodeResolution},
+    OpcodeResolutionError,
+};
+
+const BLOCK_SIZE: usize = 16;
+const NUM_ROUNDS: usize = 10;
+
+const SBOX: [u8; 256] = [
+    0x63, 0x7c, 0x77, 0x7b, 0xf2, 0x6b, 0x6f, 0xc5, 0x30, 0x01, 0x67, 0x2b, 0xfe, 0xd7, 0xab, 0x76,
+    0xca, 0x82, 0xc9, 0x7d, 0xfa, 0x59, 0x47, 0xf0, 0xad, 0xd4, 0xa2, 0xaf, 0x9c, 0xa4, 0x72, 0xc0,
+    0xb7, 0xfd, 0x93, 0x26, 0x36, 0x3f, 0xf7, 0xcc, 0x34, 0xa5, 0xe5, 0xf1, 0x71, 0xd8, 0x31, 0x15,
+    0x04, 0xc7, 0x23, 0xc3, 0x18, 0x96, 0x05, 0x9a, 0x07, 0x12, 0x80, 0xe2, 0xeb, 0x27, 0xb2, 0x75,
+    0x09, 0x83, 0x2c, 0x1a, 0x1b, 0x6e, 0x5a, 0xa0, 0x52, 0x3b, 0xd6, 0xb3, 0x29, 0xe3, 0x2f, 0x84,
+    0x53, 0xd1, 0x00, 0xed, 0x20, 0xfc, 0xb1, 0x5b, 0x6a, 0xcb, 0xbe, 0x39, 0x4a, 0x4c, 0x58, 0xcf,
+    0xd0, 0xef, 0xaa, 0xfb, 0x43, 0x4d, 0x33, 0x85, 0x45, 0xf9, 0x02, 0x7f, 0x50, 0x3c, 0x9f, 0xa8,
+    0x51, 0xa3, 0x40, 0x8f, 0x92, 0x9d, 0x38, 0xf5, 0xbc, 0xb6, 0xda, 0x21, 0x10, 0xff, 0xf3, 0xd2,
+    0xcd, 0x0c, 0x13, 0xec, 0x5f, 0x97, 0x44, 0x17, 0xc4, 0xa7, 0x7e, 0x3d, 0x64, 0x5d, 0x19, 0x73,
+    0x60, 0x81, 0x4f, 0xdc, 0x22, 0x2a, 0x90, 0x88, 0x46, 0xee, 0xb8, 0x14, 0xde, 0x5e, 0x0b, 0xdb,
+    0xe0, 0x32, 0x3a, 0x0a, 0x49, 0x06, 0x24, 0x5c, 0xc2, 0xd3, 0xac, 0x62, 0x91, 0x95, 0xe4, 0x79,
+    0xe7, 0xc8, 0x37, 0x6d, 0x8d, 0xd5, 0x4e, 0xa9, 0x6c, 0x56, 0xf4, 0xea, 0x65, 0x7a, 0xae, 0x08,
+    0xba, 0x78, 0x25, 0x2e, 0x1c, 0xa6, 0xb4, 0xc6, 0xe8, 0xdd, 0x74, 0x1f, 0x4b, 0xbd, 0x8b, 0x8a,
+    0x70, 0x3e, 0xb5, 0x66, 0x48, 0x03, 0xf6, 0x0e, 0x61, 0x35, 0x57, 0xb9, 0x86, 0xc1, 0x1d, 0x9e,
+    0xe1, 0xf8, 0x98, 0x11, 0x69, 0xd9, 0x8e, 0x94, 0x9b, 0x1e, 0x87, 0xe9, 0xce, 0x55, 0x28, 0xdf,
+    0x8c, 0xa1, 0x89, 0x0d, 0xbf, 0xe6, 0x42, 0x68, 0x41, 0x99, 0x2d, 0x0f, 0xb0, 0x54, 0xbb, 0x16,
+];
+
+const ROUND_CONSTANTS: [u8; NUM_ROUNDS] =
+    [0x01, 0x02, 0x04, 0x08, 0x10, 0x20, 0x40, 0x80, 0x1b, 0x36];
+
+/// Attempts to solve an `Aes128Encrypt` opcode.
+/// If successful, `initial_witness` will be mutated to contain the new witness assignment.
+pub(super) fn solve_aes128_encryption_opcode(
+    initial_witness: &mut WitnessMap,
+    inputs: &[FunctionInput],
+    iv: &[FunctionInput],
+    key: &[FunctionInput],
+    outputs: &[Witness],
+) -> Result<OpcodeResolution, OpcodeResolutionError> {
+    let plaintext = to_u8_vec(initial_witness, inputs, BlackBoxFunc::Aes128Encrypt)?;
+
+    // These errors should never be emitted in practice as they would imply malformed ACIR generation.
+    let iv: [u8; BLOCK_SIZE] =
+        to_u8_vec(initial_witness, iv, BlackBoxFunc::Aes128Encrypt)?.try_into().map_err(|_| {
+            OpcodeResolutionError::BlackBoxFunctionFailed(
+                BlackBoxFunc::Aes128Encrypt,
+                format!("expected iv size 16 but received {}", iv.len()),
+            )
+        })?;
+    let key: [u8; BLOCK_SIZE] =
+        to_u8_vec(initial_witness, key, BlackBoxFunc::Aes128Encrypt)?.try_into().map_err(|_| {
+            OpcodeResolutionError::BlackBoxFunctionFailed(
+                BlackBoxFunc::Aes128Encrypt,
+                format!("expected key size 16 but received {}", key.len()),
+            )
+        })?;
+
+    let ciphertext = aes128_cbc_encrypt(&plaintext, &iv, &key);
+    if ciphertext.len() != outputs.len() {
+        return Err(OpcodeResolutionError::BlackBoxFunctionFailed(
+            BlackBoxFunc::Aes128Encrypt,
+            format!("expected {} outputs but received {}", ciphertext.len(), outputs.len()),
+        ));
+    }
+
+    for (output, byte) in outputs.iter().zip(ciphertext) {
+        insert_value(output, FieldElement::from(byte as u128), initial_witness)?;
+    }
+    Ok(OpcodeResolution::Solved)
+}
+
+/// Encrypts `plaintext` with AES-128 in CBC mode, after padding it to a whole number of blocks following PKCS#7.
+pub fn aes128_cbc_encrypt(
+    plaintext: &[u8],
+    iv: &[u8; BLOCK_SIZE],
+    key: &[u8; BLOCK_SIZE],
+) -> Vec<u8> {
+    let round_keys = expand_key(key);
+
+    let padding = BLOCK_SIZE - plaintext.len() % BLOCK_SIZE;
+    let mut padded = plaintext.to_vec();
+    padded.resize(plaintext.len() + padding, padding as u8);
+
+    let mut ciphertext = Vec::with_capacity(padded.len());
+    let mut previous_block = *iv;
+    for block in padded.chunks(BLOCK_SIZE) {
+        let mut state = previous_block;
+        for (byte, plaintext_byte) in state.iter_mut().zip(block) {
+            *byte ^= plaintext_byte;
+        }
+        encrypt_block(&mut state, &round_keys);
+        ciphertext.extend(state);
+        previous_block = state;
+    }
+    ciphertext
+}
+
+/// Derives the key of each round, starting with the initial key addition.
+fn expand_key(key: &[u8; BLOCK_SIZE]) -> [[u8; BLOCK_SIZE]; NUM_ROUNDS + 1] {
+    let mut round_keys = [[0; BLOCK_SIZE]; NUM_ROUNDS + 1];
+    round_keys[0] = *key;
+    for round in 1..=NUM_ROUNDS {
+        let previous = round_keys[round - 1];
+        let mut word = [previous[13], previous[14], previous[15], previous[12]];
+        for byte in word.iter_mut() {
+            *byte = SBOX[*byte as usize];
+        }
+        word[0] ^= ROUND_CONSTANTS[round - 1];
+
+        for i in 0..BLOCK_SIZE {
+            let byte = previous[i] ^ word[i % 4];
+            round_keys[round][i] = byte;
+            word[i % 4] = byte;
+        }
+    }
+    round_keys
+}
+
+/// Encrypts a single block in place, where `state` is stored column by column.
+fn encrypt_block(state: &mut [u8; BLOCK_SIZE], round_keys: &[[u8; BLOCK_SIZE]; NUM_ROUNDS + 1]) {
+    add_round_key(state, &round_keys[0]);
+    for (round, round_key) in round_keys.iter().enumerate().skip(1) {
+        for byte in state.iter_mut() {
+            *byte = SBOX[*byte as usize];
+        }
+        shift_rows(state);
+        if round != NUM_ROUNDS {
+            mix_columns(state);
+        }
+        add_round_key(state, round_key);
+    }
+}
+
+fn add_round_key(state: &mut [u8; BLOCK_SIZE], round_key: &[u8; BLOCK_SIZE]) {
+    for (byte, key_byte) in state.iter_mut().zip(round_key) {
+        *byte ^= key_byte;
+    }
+}
+
+fn shift_rows(state: &mut [u8; BLOCK_SIZE]) {
+    let unshifted = *state;
+    for column in 0..4 {
+        for row in 0..4 {
+            state[4 * column + row] = unshifted[4 * ((column + row) % 4) + row];
+        }
+    }
+}
+
+fn mix_columns(state: &mut [u8; BLOCK_SIZE]) {
+    for column in state.chunks_mut(4) {
+        let [a0, a1, a2, a3] = [column[0], column[1], column[2], column[3]];
+        column[0] = xtime(a0) ^ xtime(a1) ^ a1 ^ a2 ^ a3;
+        column[1] = a0 ^ xtime(a1) ^ xtime(a2) ^ a2 ^ a3;
+        column[2] = a0 ^ a1 ^ xtime(a2) ^ xtime(a3) ^ a3;
+        column[3] = xtime(a0) ^ a0 ^ a1 ^ a2 ^ xtime(a3);
+    }
+}
+
+/// Multiplies `byte` by `x` in the AES field `GF(2^8) = GF(2)[x] / (x^8 + x^4 + x^3 + x + 1)`.
+fn xtime(byte: u8) -> u8 {
+    (byte << 1) ^ if byte & 0x80 != 0 { 0x1b } else { 0 }
+}
+
+#[cfg(test)]
+mod test {
+    use super::aes128_cbc_encrypt;
+
+    fn from_hex(hex: &str) -> Vec<u8> {
+        (0..hex.len()).step_by(2).map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap()).collect()
+    }
+
+    #[test]
+    fn encrypts_fips_197_vector() {
+        // Appendix C.1 of FIPS 197. CBC with a zero IV reduces to the block cipher for the first block.
+        let key = from_hex("000102030405060708090a0b0c0d0e0f").try_into().unwrap();
+        let plaintext = from_hex("00112233445566778899aabbccddeeff");
+        let ciphertext = aes128_cbc_encrypt(&plaintext, &[0; 16], &key);
+        assert_eq!(ciphertext[..16], from_hex("69c4e0d86a7b0430d8cdb78070b4c55a"));
+    }
+
+    #[test]
+    fn encrypts_sp800_38a_cbc_vector() {
+        // Section F.2.1 of NIST SP 800-38A.
+        let key = from_hex("2b7e151628aed2a6abf7158809cf4f3c").try_into().unwrap();
+        let iv = from_hex("000102030405060708090a0b0c0d0e0f").try_into().unwrap();
+        let plaintext =
+            from_hex("6bc1bee22e409f96e93d7e117393172aae2d8a571e03ac9c9eb76fac45af8e51");
+        let ciphertext = aes128_cbc_encrypt(&plaintext, &iv, &key);
+        assert_eq!(
+            ciphertext[..32],
+            from_hex("7649abac8119b246cee98e9b12e9197d5086cb9b507219ee95db113a917678b2")
+        );
+    }
+
+    #[test]
+    fn pads_to_whole_blocks() {
+        let key = [0; 16];
+        assert_eq!(aes128_cbc_encrypt(&[], &[0; 16], &key).len(), 16);
+        assert_eq!(aes128_cbc_encrypt(&[0; 15], &[0; 16], &key).len(), 16);
+        assert_eq!(aes128_cbc_encrypt(&[0; 16], &[0; 16], &key).len(), 32);
+    }
+}
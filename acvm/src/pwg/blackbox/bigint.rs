@@ -0,0 +1,201 @@
+use std::collections::HashMap;
+
+use acir::{
+    circuit::opcodes::FunctionInput,
+    native_types::{Witness, WitnessMap},
+    BlackBoxFunc, FieldElement,
+};
+use num_bigint::BigUint;
+use num_traits::Zero;
+
+use super::ecdsa::to_u8_vec;
+use crate::{
+    pwg::{insert_value, OpcodeNotSolvable, OpcodeResolution},
+    OpcodeResolutionError,
+};
+
+/// Stores the values of the big integers created while solving a circuit.
+///
+/// Big integers are referenced by opcodes through identifiers rather than witnesses,
+/// so their values must persist between the solving of different opcodes.
+#[derive(Debug, Default)]
+pub(crate) struct BigIntSolver {
+    bigints: HashMap<u32, BigIntValue>,
+}
+
+#[derive(Debug, Clone)]
+struct BigIntValue {
+    value: BigUint,
+    modulus: BigUint,
+}
+
+impl BigIntSolver {
+    pub(crate) fn bigint_from_bytes(
+        &mut self,
+        initial_witness: &WitnessMap,
+        inputs: &[FunctionInput],
+        modulus: &[u8],
+        output: u32,
+    ) -> Result<OpcodeResolution, OpcodeResolutionError> {
+        let modulus = BigUint::from_bytes_le(modulus);
+        if modulus.is_zero() {
+            return Err(OpcodeResolutionError::BlackBoxFunctionFailed(
+                BlackBoxFunc::BigIntFromLeBytes,
+                "modulus must be non-zero".to_string(),
+            ));
+        }
+
+        let value = BigUint::from_bytes_le(&to_u8_vec(
+            initial_witness,
+            inputs,
+            BlackBoxFunc::BigIntFromLeBytes,
+        )?) % &modulus;
+        self.bigints.insert(output, BigIntValue { value, modulus });
+        Ok(OpcodeResolution::Solved)
+    }
+
+    pub(crate) fn bigint_to_bytes(
+        &self,
+        initial_witness: &mut WitnessMap,
+        input: u32,
+        outputs: &[Witness],
+    ) -> Result<OpcodeResolution, OpcodeResolutionError> {
+        let BigIntValue { value, .. } = match self.bigints.get(&input) {
+            Some(bigint) => bigint,
+            None => return Ok(missing_bigint(input)),
+        };
+
+        let mut bytes = value.to_bytes_le();
+        if bytes.len() > outputs.len() {
+            return Err(OpcodeResolutionError::BlackBoxFunctionFailed(
+                BlackBoxFunc::BigIntToLeBytes,
+                format!("big integer does not fit into {} bytes", outputs.len()),
+            ));
+        }
+        bytes.resize(outputs.len(), 0);
+
+        for (output, byte) in outputs.iter().zip(bytes) {
+            insert_value(output, FieldElement::from(byte as u128), initial_witness)?;
+        }
+        Ok(OpcodeResolution::Solved)
+    }
+
+    pub(crate) fn bigint_op(
+        &mut self,
+        lhs: u32,
+        rhs: u32,
+        output: u32,
+        func: BlackBoxFunc,
+    ) -> Result<OpcodeResolution, OpcodeResolutionError> {
+        let (lhs, rhs) = match (self.bigints.get(&lhs), self.bigints.get(&rhs)) {
+            (Some(lhs), Some(rhs)) => (lhs, rhs),
+            (None, _) => return Ok(missing_bigint(lhs)),
+            (_, None) => return Ok(missing_bigint(rhs)),
+        };
+        if lhs.modulus != rhs.modulus {
+            return Err(OpcodeResolutionError::BlackBoxFunctionFailed(
+                func,
+                "operands must share the same modulus".to_string(),
+            ));
+        }
+        let modulus = &lhs.modulus;
+
+        let value = match func {
+            BlackBoxFunc::BigIntAdd => (&lhs.value + &rhs.value) % modulus,
+            BlackBoxFunc::BigIntMul => (&lhs.value * &rhs.value) % modulus,
+            BlackBoxFunc::BigIntDiv => {
+                let rhs_inverse = rhs.value.modinv(modulus).ok_or_else(|| {
+                    OpcodeResolutionError::BlackBoxFunctionFailed(
+                        func,
+                        "divisor is not invertible modulo the modulus".to_string(),
+                    )
+                })?;
+                (&lhs.value * rhs_inverse) % modulus
+            }
+            _ => unreachable!("ICE - {func} is not a big integer operation"),
+        };
+
+        self.bigints.insert(output, BigIntValue { value, modulus: modulus.clone() });
+        Ok(OpcodeResolution::Solved)
+    }
+}
+
+fn missing_bigint(id: u32) -> OpcodeResolution {
+    OpcodeResolution::Stalled(OpcodeNotSolvable::MissingBigInt(id))
+}
+
+#[cfg(test)]
+mod test {
+    use acir::{
+        circuit::opcodes::FunctionInput,
+        native_types::{Witness, WitnessMap},
+        BlackBoxFunc, FieldElement,
+    };
+
+    use super::BigIntSolver;
+    use crate::pwg::{OpcodeNotSolvable, OpcodeResolution};
+
+    const MODULUS: [u8; 2] = [0xf1, 0xff]; // 65521, the largest 16 bit prime.
+
+    /// Loads `value` into the solver under the identifier `id`, reduced modulo [`MODULUS`].
+    fn load(solver: &mut BigIntSolver, value: u16, id: u32) {
+        let inputs = [
+            FunctionInput { witness: Witness(1), num_bits: 8 },
+            FunctionInput { witness: Witness(2), num_bits: 8 },
+        ];
+        let [low, high] = value.to_le_bytes();
+        let witness_map = WitnessMap::from(std::collections::BTreeMap::from([
+            (Witness(1), FieldElement::from(low as u128)),
+            (Witness(2), FieldElement::from(high as u128)),
+        ]));
+        solver.bigint_from_bytes(&witness_map, &inputs, &MODULUS, id).unwrap();
+    }
+
+    fn read(solver: &BigIntSolver, id: u32) -> u128 {
+        let outputs = [Witness(1), Witness(2), Witness(3)];
+        let mut witness_map = WitnessMap::new();
+        solver.bigint_to_bytes(&mut witness_map, id, &outputs).unwrap();
+        outputs.iter().rev().fold(0, |acc, output| (acc << 8) + witness_map[output].to_u128())
+    }
+
+    #[test]
+    fn performs_modular_arithmetic() {
+        let mut solver = BigIntSolver::default();
+        load(&mut solver, 65000, 0);
+        load(&mut solver, 1000, 1);
+
+        solver.bigint_op(0, 1, 2, BlackBoxFunc::BigIntAdd).unwrap();
+        assert_eq!(read(&solver, 2), (65000 + 1000) % 65521);
+
+        solver.bigint_op(0, 1, 3, BlackBoxFunc::BigIntMul).unwrap();
+        assert_eq!(read(&solver, 3), (65000 * 1000) % 65521);
+
+        solver.bigint_op(3, 1, 4, BlackBoxFunc::BigIntDiv).unwrap();
+        assert_eq!(read(&solver, 4), 65000);
+    }
+
+    #[test]
+    fn reduces_inputs_modulo_modulus() {
+        let mut solver = BigIntSolver::default();
+        load(&mut solver, 65535, 0);
+        assert_eq!(read(&solver, 0), 65535 - 65521);
+    }
+
+    #[test]
+    fn stalls_on_missing_operand() {
+        let mut solver = BigIntSolver::default();
+        load(&mut solver, 1, 0);
+        assert_eq!(
+            solver.bigint_op(0, 1, 2, BlackBoxFunc::BigIntAdd),
+            Ok(OpcodeResolution::Stalled(OpcodeNotSolvable::MissingBigInt(1)))
+        );
+    }
+
+    #[test]
+    fn errors_on_division_by_zero() {
+        let mut solver = BigIntSolver::default();
+        load(&mut solver, 1, 0);
+        load(&mut solver, 0, 1);
+        assert!(solver.bigint_op(0, 1, 2, BlackBoxFunc::BigIntDiv).is_err());
+    }
+}
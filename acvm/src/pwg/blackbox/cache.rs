@@ -0,0 +1,133 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, MutexGuard},
+};
+
+use acir::{
+    circuit::{
+        opcodes::{BlackBoxFuncCall, FunctionInput},
+        visitor::VisitorMut,
+    },
+    native_types::{Witness, WitnessMap},
+    BlackBoxFunc, FieldElement,
+};
+
+/// A memoization cache of the values of the outputs of black box function calls, keyed by the function along
+/// with the values of its inputs.
+///
+/// Circuits often apply a black box function to the same values several times, such as hashing padding blocks or
+/// domain separators, and provers solve the same sub-computations again for each proof. A cache passed to
+/// [`ACVM::with_black_box_cache`][crate::pwg::ACVM::with_black_box_cache] lets these calls be solved once.
+/// Clones of a cache share its entries, so that a cache can be shared across solves, including concurrent ones.
+///
+/// The outputs of a call are cached once its opcode is solved, whether by the ACVM or through
+/// [`ACVM::resolve_pending_black_box_call`][crate::pwg::ACVM::resolve_pending_black_box_call], so a cache should
+/// only be shared between solves with the same backend. Big integer operations, whose outputs depend on the big
+/// integers created by earlier opcodes, and calls without outputs are never cached.
+#[derive(Clone, Debug, Default)]
+pub struct BlackBoxCache {
+    entries: Arc<Mutex<HashMap<BlackBoxCacheKey, Vec<FieldElement>>>>,
+}
+
+/// A black box function call with all of its witnesses erased, along with the values of its inputs in order.
+#[derive(Debug, PartialEq, Eq, Hash)]
+pub(crate) struct BlackBoxCacheKey {
+    call: BlackBoxFuncCall,
+    inputs: Vec<FieldElement>,
+}
+
+impl BlackBoxCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the number of calls whose outputs are cached.
+    pub fn len(&self) -> usize {
+        self.entries().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Removes the outputs of all calls from the cache, including from its clones.
+    pub fn clear(&self) {
+        self.entries().clear();
+    }
+
+    /// Returns the key under which the outputs of `bb_func` are cached, or `None` if it is not cached
+    /// or not all of its inputs have been assigned.
+    pub(crate) fn key(
+        bb_func: &BlackBoxFuncCall,
+        witness_map: &WitnessMap,
+    ) -> Option<BlackBoxCacheKey> {
+        if bb_func.get_outputs_vec().is_empty() || is_bigint_func(bb_func.get_black_box_func()) {
+            return None;
+        }
+        let mut call = bb_func.clone();
+        let mut eraser = WitnessEraser { witness_map, inputs: Some(Vec::new()) };
+        eraser.visit_black_box_func_call_mut(&mut call);
+        Some(BlackBoxCacheKey { call, inputs: eraser.inputs? })
+    }
+
+    /// Returns the values of the outputs cached under `key`.
+    pub(crate) fn get(&self, key: &BlackBoxCacheKey) -> Option<Vec<FieldElement>> {
+        self.entries().get(key).cloned()
+    }
+
+    /// Caches the values assigned to the outputs of `bb_func` under `key`.
+    pub(crate) fn insert(
+        &self,
+        key: BlackBoxCacheKey,
+        bb_func: &BlackBoxFuncCall,
+        witness_map: &WitnessMap,
+    ) {
+        let outputs = bb_func
+            .get_outputs_vec()
+            .iter()
+            .map(|output| witness_map.get(output).copied())
+            .collect::<Option<Vec<_>>>();
+        if let Some(outputs) = outputs {
+            self.entries().insert(key, outputs);
+        }
+    }
+
+    fn entries(&self) -> MutexGuard<'_, HashMap<BlackBoxCacheKey, Vec<FieldElement>>> {
+        // The entries are only ever replaced whole, so they remain consistent should a holder of the lock panic.
+        self.entries.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+fn is_bigint_func(func: BlackBoxFunc) -> bool {
+    matches!(
+        func,
+        BlackBoxFunc::BigIntAdd
+            | BlackBoxFunc::BigIntMul
+            | BlackBoxFunc::BigIntDiv
+            | BlackBoxFunc::BigIntFromLeBytes
+            | BlackBoxFunc::BigIntToLeBytes
+    )
+}
+
+/// Replaces every witness of a call with `Witness(0)`, collecting the values of its inputs
+/// or `None` once one is unassigned.
+struct WitnessEraser<'a> {
+    witness_map: &'a WitnessMap,
+    inputs: Option<Vec<FieldElement>>,
+}
+
+impl VisitorMut for WitnessEraser<'_> {
+    fn visit_witness_mut(&mut self, witness: &mut Witness) {
+        *witness = Witness(0);
+    }
+
+    fn visit_function_input_mut(&mut self, input: &mut FunctionInput) {
+        let value = self.witness_map.get(&input.witness).copied();
+        if let (Some(inputs), Some(value)) = (&mut self.inputs, value) {
+            inputs.push(value);
+        } else {
+            self.inputs = None;
+        }
+        self.visit_witness_mut(&mut input.witness);
+    }
+}
@@ -0,0 +1,192 @@
+//! Black box functions computed by the backend.
+//!
+//! The values of the inputs are gathered from the witness map and validated before calling the
+//! [`PartialWitnessGenerator`], and the values it returns are assigned to the outputs of the opcode.
+//! Backends may also [defer][PartialWitnessGenerator::defers_black_box_func] these calls to the caller
+//! of the ACVM, which then provides the values of the outputs itself.
+use acir::{
+    circuit::opcodes::FunctionInput,
+    native_types::{Witness, WitnessMap},
+    BlackBoxFunc, FieldElement,
+};
+
+use super::ecdsa::to_u8_vec;
+use crate::{
+    pwg::{insert_value, witness_to_value, OpcodeResolution},
+    OpcodeResolutionError, PartialWitnessGenerator,
+};
+
+/// A call to a black box function computed by the backend, along with the values of its inputs.
+///
+/// Calls which the backend [defers][PartialWitnessGenerator::defers_black_box_func] are returned by
+/// [`ACVM::get_pending_black_box_call`][crate::pwg::ACVM::get_pending_black_box_call].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BlackBoxCall {
+    SchnorrVerify {
+        public_key_x: FieldElement,
+        public_key_y: FieldElement,
+        signature_s: FieldElement,
+        signature_e: FieldElement,
+        message: Vec<u8>,
+    },
+    Pedersen {
+        inputs: Vec<FieldElement>,
+        domain_separator: u32,
+    },
+    FixedBaseScalarMul {
+        input: FieldElement,
+    },
+    RecursiveAggregation {
+        verification_key: Vec<FieldElement>,
+        proof: Vec<FieldElement>,
+        public_inputs: Vec<FieldElement>,
+        key_hash: FieldElement,
+        input_aggregation_object: Option<Vec<FieldElement>>,
+        /// The number of elements of the output aggregation object.
+        output_len: usize,
+    },
+}
+
+impl BlackBoxCall {
+    pub fn function(&self) -> BlackBoxFunc {
+        match self {
+            BlackBoxCall::SchnorrVerify { .. } => BlackBoxFunc::SchnorrVerify,
+            BlackBoxCall::Pedersen { .. } => BlackBoxFunc::Pedersen,
+            BlackBoxCall::FixedBaseScalarMul { .. } => BlackBoxFunc::FixedBaseScalarMul,
+            BlackBoxCall::RecursiveAggregation { .. } => BlackBoxFunc::RecursiveAggregation,
+        }
+    }
+
+    /// Computes the call through `backend`, returning the values of the outputs of its opcode in order.
+    pub fn compute(
+        &self,
+        backend: &impl PartialWitnessGenerator,
+    ) -> Result<Vec<FieldElement>, OpcodeResolutionError> {
+        match self {
+            BlackBoxCall::SchnorrVerify {
+                public_key_x,
+                public_key_y,
+                signature_s,
+                signature_e,
+                message,
+            } => {
+                let is_valid = backend.schnorr_verify(
+                    public_key_x,
+                    public_key_y,
+                    signature_s,
+                    signature_e,
+                    message,
+                )?;
+                Ok(vec![FieldElement::from(is_valid as u128)])
+            }
+            BlackBoxCall::Pedersen { inputs, domain_separator } => {
+                let (x, y) = backend.pedersen(inputs, *domain_separator)?;
+                Ok(vec![x, y])
+            }
+            BlackBoxCall::FixedBaseScalarMul { input } => {
+                let (x, y) = backend.fixed_base_scalar_mul(input)?;
+                Ok(vec![x, y])
+            }
+            BlackBoxCall::RecursiveAggregation {
+                verification_key,
+                proof,
+                public_inputs,
+                key_hash,
+                input_aggregation_object,
+                output_len,
+            } => backend.recursive_aggregation(
+                verification_key,
+                proof,
+                public_inputs,
+                key_hash,
+                input_aggregation_object.as_deref(),
+                *output_len,
+            ),
+        }
+    }
+}
+
+/// Computes `call` through the backend and assigns the returned values to `outputs`,
+/// unless the backend defers it.
+pub(super) fn solve_black_box_call(
+    backend: &impl PartialWitnessGenerator,
+    initial_witness: &mut WitnessMap,
+    call: BlackBoxCall,
+    outputs: &[Witness],
+) -> Result<OpcodeResolution, OpcodeResolutionError> {
+    if backend.defers_black_box_func(call.function()) {
+        return Ok(OpcodeResolution::InProgressBlackBox(call));
+    }
+    let values = call.compute(backend)?;
+    assign_outputs(initial_witness, call.function(), outputs, values)
+}
+
+/// Assigns the values computed for a call to `func` to `outputs`, checking that there is one for each output.
+pub(crate) fn assign_outputs(
+    initial_witness: &mut WitnessMap,
+    func: BlackBoxFunc,
+    outputs: &[Witness],
+    values: Vec<FieldElement>,
+) -> Result<OpcodeResolution, OpcodeResolutionError> {
+    if values.len() != outputs.len() {
+        return Err(OpcodeResolutionError::BlackBoxFunctionFailed(
+            func,
+            format!("{} values were computed for {} outputs", values.len(), outputs.len()),
+        ));
+    }
+    for (output, value) in outputs.iter().zip(values) {
+        insert_value(output, value, initial_witness)?;
+    }
+    Ok(OpcodeResolution::Solved)
+}
+
+pub(super) fn solve_schnorr_verify_opcode(
+    backend: &impl PartialWitnessGenerator,
+    initial_witness: &mut WitnessMap,
+    public_key: (FunctionInput, FunctionInput),
+    signature: (FunctionInput, FunctionInput),
+    message: &[FunctionInput],
+    output: Witness,
+) -> Result<OpcodeResolution, OpcodeResolutionError> {
+    let call = BlackBoxCall::SchnorrVerify {
+        public_key_x: *witness_to_value(initial_witness, public_key.0.witness)?,
+        public_key_y: *witness_to_value(initial_witness, public_key.1.witness)?,
+        signature_s: *witness_to_value(initial_witness, signature.0.witness)?,
+        signature_e: *witness_to_value(initial_witness, signature.1.witness)?,
+        message: to_u8_vec(initial_witness, message, BlackBoxFunc::SchnorrVerify)?,
+    };
+    solve_black_box_call(backend, initial_witness, call, &[output])
+}
+
+#[cfg(not(feature = "native-pedersen"))]
+pub(super) fn solve_pedersen_opcode(
+    backend: &impl PartialWitnessGenerator,
+    initial_witness: &mut WitnessMap,
+    inputs: &[FunctionInput],
+    domain_separator: u32,
+    outputs: (Witness, Witness),
+) -> Result<OpcodeResolution, OpcodeResolutionError> {
+    let call =
+        BlackBoxCall::Pedersen { inputs: input_values(initial_witness, inputs)?, domain_separator };
+    solve_black_box_call(backend, initial_witness, call, &[outputs.0, outputs.1])
+}
+
+pub(super) fn solve_fixed_base_scalar_mul_opcode(
+    backend: &impl PartialWitnessGenerator,
+    initial_witness: &mut WitnessMap,
+    input: FunctionInput,
+    outputs: (Witness, Witness),
+) -> Result<OpcodeResolution, OpcodeResolutionError> {
+    let call = BlackBoxCall::FixedBaseScalarMul {
+        input: *witness_to_value(initial_witness, input.witness)?,
+    };
+    solve_black_box_call(backend, initial_witness, call, &[outputs.0, outputs.1])
+}
+
+/// Returns the values assigned to `inputs`.
+pub(super) fn input_values(
+    initial_witness: &WitnessMap,
+    inputs: &[FunctionInput],
+) -> Result<Vec<FieldElement>, OpcodeResolutionError> {
+    inputs.iter().map(|input| witness_to_value(initial_witness, input.witness).copied()).collect()
+}
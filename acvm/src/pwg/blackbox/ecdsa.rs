@@ -1,13 +1,14 @@
 use acir::{
     circuit::opcodes::FunctionInput,
     native_types::{Witness, WitnessMap},
-    FieldElement,
+    BlackBoxFunc, FieldElement,
 };
-use blake2::digest::generic_array::GenericArray;
+use k256::elliptic_curve::ops::Reduce;
 use k256::elliptic_curve::sec1::FromEncodedPoint;
-use k256::elliptic_curve::PrimeField;
+use k256::elliptic_curve::subtle::Choice;
+use k256::elliptic_curve::DecompressPoint;
 
-use k256::{ecdsa::Signature, Scalar};
+use k256::{ecdsa::Signature, Scalar, U256};
 use k256::{
     elliptic_curve::{
         sec1::{Coordinates, ToEncodedPoint},
@@ -15,22 +16,22 @@ use k256::{
     },
     AffinePoint, EncodedPoint, ProjectivePoint, PublicKey,
 };
+use stdlib::blackbox_fallbacks::radix::Endianness;
 
 use crate::{
-    pwg::witness_to_value,
-    pwg::{insert_value, OpcodeResolution},
+    pwg::{insert_value, witness_to_bytes, witness_to_value, OpcodeResolution},
     OpcodeResolutionError,
 };
 
-fn to_u8_vec(
+/// Reads the bytes assigned to `inputs`, each of which must fit within a single byte.
+pub(super) fn to_u8_vec(
     initial_witness: &WitnessMap,
     inputs: &[FunctionInput],
+    func: BlackBoxFunc,
 ) -> Result<Vec<u8>, OpcodeResolutionError> {
     let mut result = Vec::with_capacity(inputs.len());
     for input in inputs {
-        let witness_value_bytes = witness_to_value(initial_witness, input.witness)?.to_be_bytes();
-        let byte = witness_value_bytes.last().unwrap();
-        result.push(*byte);
+        result.extend(witness_to_bytes(initial_witness, input.witness, 8, Endianness::Big, func)?);
     }
     Ok(result)
 }
@@ -43,11 +44,14 @@ pub(super) fn secp256k1_prehashed(
     hashed_message_inputs: &[FunctionInput],
     output: Witness,
 ) -> Result<OpcodeResolution, OpcodeResolutionError> {
-    let hashed_message = to_u8_vec(initial_witness, hashed_message_inputs)?;
+    let hashed_message =
+        to_u8_vec(initial_witness, hashed_message_inputs, BlackBoxFunc::EcdsaSecp256k1)?;
 
     // These errors should never be emitted in practice as they would imply malformed ACIR generation.
     let pub_key_x: [u8; 32] =
-        to_u8_vec(initial_witness, public_key_x_inputs)?.try_into().map_err(|_| {
+        to_u8_vec(initial_witness, public_key_x_inputs, BlackBoxFunc::EcdsaSecp256k1)?
+            .try_into()
+            .map_err(|_| {
             OpcodeResolutionError::BlackBoxFunctionFailed(
                 acir::BlackBoxFunc::EcdsaSecp256k1,
                 format!("expected pubkey_x size 32 but received {}", public_key_x_inputs.len()),
@@ -55,7 +59,9 @@ pub(super) fn secp256k1_prehashed(
         })?;
 
     let pub_key_y: [u8; 32] =
-        to_u8_vec(initial_witness, public_key_y_inputs)?.try_into().map_err(|_| {
+        to_u8_vec(initial_witness, public_key_y_inputs, BlackBoxFunc::EcdsaSecp256k1)?
+            .try_into()
+            .map_err(|_| {
             OpcodeResolutionError::BlackBoxFunctionFailed(
                 acir::BlackBoxFunc::EcdsaSecp256k1,
                 format!("expected pubkey_y size 32 but received {}", public_key_y_inputs.len()),
@@ -63,39 +69,55 @@ pub(super) fn secp256k1_prehashed(
         })?;
 
     let signature: [u8; 64] =
-        to_u8_vec(initial_witness, signature_inputs)?.try_into().map_err(|_| {
-            OpcodeResolutionError::BlackBoxFunctionFailed(
-                acir::BlackBoxFunc::EcdsaSecp256k1,
-                format!("expected signature size 64 but received {}", signature_inputs.len()),
-            )
-        })?;
+        to_u8_vec(initial_witness, signature_inputs, BlackBoxFunc::EcdsaSecp256k1)?
+            .try_into()
+            .map_err(|_| {
+                OpcodeResolutionError::BlackBoxFunctionFailed(
+                    acir::BlackBoxFunc::EcdsaSecp256k1,
+                    format!("expected signature size 64 but received {}", signature_inputs.len()),
+                )
+            })?;
 
     let is_valid =
-        verify_secp256k1_ecdsa_signature(&hashed_message, &pub_key_x, &pub_key_y, &signature);
+        verify_secp256k1_ecdsa_signature(&hashed_message, &pub_key_x, &pub_key_y, &signature)?;
 
     insert_value(&output, FieldElement::from(is_valid), initial_witness)?;
     Ok(OpcodeResolution::Solved)
 }
 
 /// Verify an ECDSA signature over the secp256k1 elliptic curve, given the hashed message
-fn verify_secp256k1_ecdsa_signature(
+///
+/// Returns `Ok(false)` for any signature which does not verify, including malformed signatures.
+/// An error is only returned if the public key is not a valid point on the curve.
+pub fn verify_secp256k1_ecdsa_signature(
     hashed_msg: &[u8],
     public_key_x_bytes: &[u8; 32],
     public_key_y_bytes: &[u8; 32],
     signature: &[u8; 64],
-) -> bool {
+) -> Result<bool, OpcodeResolutionError> {
     // Convert the inputs into k256 data structures
 
-    let signature = Signature::try_from(signature.as_slice()).unwrap();
-
+    // The point is left uncompressed so that both coordinates are checked to lie on the curve.
     let point = EncodedPoint::from_affine_coordinates(
         public_key_x_bytes.into(),
         public_key_y_bytes.into(),
-        true,
+        false,
     );
-    let pubkey = PublicKey::from_encoded_point(&point).unwrap();
+    let pubkey =
+        Option::<PublicKey>::from(PublicKey::from_encoded_point(&point)).ok_or_else(|| {
+            OpcodeResolutionError::BlackBoxFunctionFailed(
+                acir::BlackBoxFunc::EcdsaSecp256k1,
+                "public key is not a valid point on the secp256k1 curve".to_string(),
+            )
+        })?;
 
-    let z = Scalar::from_repr(*GenericArray::from_slice(hashed_msg)).unwrap();
+    // Signatures with a component which is zero or not less than the curve order are invalid.
+    let signature = match Signature::try_from(signature.as_slice()) {
+        Ok(signature) => signature,
+        Err(_) => return Ok(false),
+    };
+
+    let z = hashed_message_to_scalar(hashed_msg);
 
     // Finished converting bytes into data structures
 
@@ -104,7 +126,7 @@ fn verify_secp256k1_ecdsa_signature(
 
     // Ensure signature is "low S" normalized ala BIP 0062
     if s.is_high().into() {
-        return false;
+        return Ok(false);
     }
 
     let s_inv = s.invert().unwrap();
@@ -117,50 +139,208 @@ fn verify_secp256k1_ecdsa_signature(
         .to_affine();
 
     match R.to_encoded_point(false).coordinates() {
-        Coordinates::Uncompressed { x, y: _ } => Scalar::from_repr(*x).unwrap().eq(&r),
-        _ => unreachable!("Point is uncompressed"),
+        Coordinates::Uncompressed { x, y: _ } => {
+            Ok(<Scalar as Reduce<U256>>::from_be_bytes_reduced(*x).eq(&r))
+        }
+        // The signature cannot be valid if it leads to the point at infinity.
+        _ => Ok(false),
+    }
+}
+
+/// Attempts to solve an `EcdsaSecp256k1Recover` opcode.
+/// If successful, `initial_witness` will be mutated to contain the new witness assignments.
+pub(super) fn secp256k1_recover(
+    initial_witness: &mut WitnessMap,
+    signature_inputs: &[FunctionInput],
+    recovery_id: FunctionInput,
+    hashed_message_inputs: &[FunctionInput],
+    public_key_x_outputs: &[Witness],
+    public_key_y_outputs: &[Witness],
+) -> Result<OpcodeResolution, OpcodeResolutionError> {
+    let hashed_message =
+        to_u8_vec(initial_witness, hashed_message_inputs, BlackBoxFunc::EcdsaSecp256k1Recover)?;
+
+    // These errors should never be emitted in practice as they would imply malformed ACIR generation.
+    let signature: [u8; 64] =
+        to_u8_vec(initial_witness, signature_inputs, BlackBoxFunc::EcdsaSecp256k1Recover)?
+            .try_into()
+            .map_err(|_| {
+                OpcodeResolutionError::BlackBoxFunctionFailed(
+                    acir::BlackBoxFunc::EcdsaSecp256k1Recover,
+                    format!("expected signature size 64 but received {}", signature_inputs.len()),
+                )
+            })?;
+    if public_key_x_outputs.len() != 32 || public_key_y_outputs.len() != 32 {
+        return Err(OpcodeResolutionError::BlackBoxFunctionFailed(
+            acir::BlackBoxFunc::EcdsaSecp256k1Recover,
+            format!(
+                "expected public key coordinates of size 32 but received {} and {}",
+                public_key_x_outputs.len(),
+                public_key_y_outputs.len()
+            ),
+        ));
     }
+
+    let recovery_id = witness_to_value(initial_witness, recovery_id.witness)?.try_to_u64();
+    // Keys which cannot be recovered are represented by zeroed coordinates.
+    let (public_key_x, public_key_y) = recovery_id
+        .and_then(|recovery_id| {
+            recover_secp256k1_public_key(&hashed_message, &signature, recovery_id)
+        })
+        .unwrap_or(([0; 32], [0; 32]));
+
+    let outputs = public_key_x_outputs.iter().chain(public_key_y_outputs);
+    for (output, byte) in outputs.zip(public_key_x.into_iter().chain(public_key_y)) {
+        insert_value(output, FieldElement::from(byte as u128), initial_witness)?;
+    }
+    Ok(OpcodeResolution::Solved)
+}
+
+/// Recovers the coordinates of the public key which produced an ECDSA signature over the secp256k1 curve,
+/// given the hashed message and the parity of the y coordinate of the point whose x coordinate is `r`.
+///
+/// Returns `None` if no public key can be recovered, including for malformed signatures.
+pub fn recover_secp256k1_public_key(
+    hashed_msg: &[u8],
+    signature: &[u8; 64],
+    recovery_id: u64,
+) -> Option<([u8; 32], [u8; 32])> {
+    // Signatures with a component which is zero or not less than the curve order are invalid.
+    let signature = Signature::try_from(signature.as_slice()).ok()?;
+    let is_y_odd = match recovery_id {
+        0 => Choice::from(0),
+        1 => Choice::from(1),
+        _ => return None,
+    };
+    let z = hashed_message_to_scalar(hashed_msg);
+
+    let r = signature.r();
+    let s = signature.s();
+
+    #[allow(non_snake_case)]
+    let R: AffinePoint = Option::from(AffinePoint::decompress(&r.to_bytes(), is_y_odd))?;
+
+    let r_inv = r.invert().unwrap();
+    let u1 = -(r_inv * z);
+    let u2 = r_inv * *s;
+    let public_key =
+        ((ProjectivePoint::GENERATOR * u1) + (ProjectivePoint::from(R) * u2)).to_affine();
+
+    match public_key.to_encoded_point(false).coordinates() {
+        Coordinates::Uncompressed { x, y } => Some(((*x).into(), (*y).into())),
+        // The point at infinity is not a valid public key.
+        _ => None,
+    }
+}
+
+/// Interprets the message hash as an integer made of its leftmost 256 bits, reduced modulo the curve order.
+fn hashed_message_to_scalar(hashed_msg: &[u8]) -> Scalar {
+    let mut hashed_msg_bytes = [0u8; 32];
+    let hashed_msg = &hashed_msg[..hashed_msg.len().min(32)];
+    hashed_msg_bytes[32 - hashed_msg.len()..].copy_from_slice(hashed_msg);
+    <Scalar as Reduce<U256>>::from_be_bytes_reduced(hashed_msg_bytes.into())
 }
 
 #[cfg(test)]
 mod test {
-    use super::verify_secp256k1_ecdsa_signature;
+    use super::{recover_secp256k1_public_key, verify_secp256k1_ecdsa_signature};
+
+    // 0x3a73f4123a5cd2121f21cd7e8d358835476949d035d9c2da6806b4633ac8c1e2,
+    const HASHED_MESSAGE: [u8; 32] = [
+        0x3a, 0x73, 0xf4, 0x12, 0x3a, 0x5c, 0xd2, 0x12, 0x1f, 0x21, 0xcd, 0x7e, 0x8d, 0x35, 0x88,
+        0x35, 0x47, 0x69, 0x49, 0xd0, 0x35, 0xd9, 0xc2, 0xda, 0x68, 0x06, 0xb4, 0x63, 0x3a, 0xc8,
+        0xc1, 0xe2,
+    ];
+
+    // 0xa0434d9e47f3c86235477c7b1ae6ae5d3442d49b1943c2b752a68e2a47e247c7
+    const PUB_KEY_X: [u8; 32] = [
+        0xa0, 0x43, 0x4d, 0x9e, 0x47, 0xf3, 0xc8, 0x62, 0x35, 0x47, 0x7c, 0x7b, 0x1a, 0xe6, 0xae,
+        0x5d, 0x34, 0x42, 0xd4, 0x9b, 0x19, 0x43, 0xc2, 0xb7, 0x52, 0xa6, 0x8e, 0x2a, 0x47, 0xe2,
+        0x47, 0xc7,
+    ];
+
+    // 0x893aba425419bc27a3b6c7e693a24c696f794c2ed877a1593cbee53b037368d7
+    const PUB_KEY_Y: [u8; 32] = [
+        0x89, 0x3a, 0xba, 0x42, 0x54, 0x19, 0xbc, 0x27, 0xa3, 0xb6, 0xc7, 0xe6, 0x93, 0xa2, 0x4c,
+        0x69, 0x6f, 0x79, 0x4c, 0x2e, 0xd8, 0x77, 0xa1, 0x59, 0x3c, 0xbe, 0xe5, 0x3b, 0x03, 0x73,
+        0x68, 0xd7,
+    ];
+
+    // 0xe5081c80ab427dc370346f4a0e31aa2bad8d9798c38061db9ae55a4e8df454fd28119894344e71b78770cc931d61f480ecbb0b89d6eb69690161e49a715fcd55
+    const SIGNATURE: [u8; 64] = [
+        0xe5, 0x08, 0x1c, 0x80, 0xab, 0x42, 0x7d, 0xc3, 0x70, 0x34, 0x6f, 0x4a, 0x0e, 0x31, 0xaa,
+        0x2b, 0xad, 0x8d, 0x97, 0x98, 0xc3, 0x80, 0x61, 0xdb, 0x9a, 0xe5, 0x5a, 0x4e, 0x8d, 0xf4,
+        0x54, 0xfd, 0x28, 0x11, 0x98, 0x94, 0x34, 0x4e, 0x71, 0xb7, 0x87, 0x70, 0xcc, 0x93, 0x1d,
+        0x61, 0xf4, 0x80, 0xec, 0xbb, 0x0b, 0x89, 0xd6, 0xeb, 0x69, 0x69, 0x01, 0x61, 0xe4, 0x9a,
+        0x71, 0x5f, 0xcd, 0x55,
+    ];
 
     #[test]
     fn verifies_valid_signature_with_low_s_value() {
-        // 0x3a73f4123a5cd2121f21cd7e8d358835476949d035d9c2da6806b4633ac8c1e2,
-        let hashed_message: [u8; 32] = [
-            0x3a, 0x73, 0xf4, 0x12, 0x3a, 0x5c, 0xd2, 0x12, 0x1f, 0x21, 0xcd, 0x7e, 0x8d, 0x35,
-            0x88, 0x35, 0x47, 0x69, 0x49, 0xd0, 0x35, 0xd9, 0xc2, 0xda, 0x68, 0x06, 0xb4, 0x63,
-            0x3a, 0xc8, 0xc1, 0xe2,
-        ];
-
-        // 0xa0434d9e47f3c86235477c7b1ae6ae5d3442d49b1943c2b752a68e2a47e247c7
-        let pub_key_x: [u8; 32] = [
-            0xa0, 0x43, 0x4d, 0x9e, 0x47, 0xf3, 0xc8, 0x62, 0x35, 0x47, 0x7c, 0x7b, 0x1a, 0xe6,
-            0xae, 0x5d, 0x34, 0x42, 0xd4, 0x9b, 0x19, 0x43, 0xc2, 0xb7, 0x52, 0xa6, 0x8e, 0x2a,
-            0x47, 0xe2, 0x47, 0xc7,
-        ];
-
-        // 0x893aba425419bc27a3b6c7e693a24c696f794c2ed877a1593cbee53b037368d7
-        let pub_key_y: [u8; 32] = [
-            0x89, 0x3a, 0xba, 0x42, 0x54, 0x19, 0xbc, 0x27, 0xa3, 0xb6, 0xc7, 0xe6, 0x93, 0xa2,
-            0x4c, 0x69, 0x6f, 0x79, 0x4c, 0x2e, 0xd8, 0x77, 0xa1, 0x59, 0x3c, 0xbe, 0xe5, 0x3b,
-            0x03, 0x73, 0x68, 0xd7,
-        ];
-
-        // 0xe5081c80ab427dc370346f4a0e31aa2bad8d9798c38061db9ae55a4e8df454fd28119894344e71b78770cc931d61f480ecbb0b89d6eb69690161e49a715fcd55
-        let signature: [u8; 64] = [
-            0xe5, 0x08, 0x1c, 0x80, 0xab, 0x42, 0x7d, 0xc3, 0x70, 0x34, 0x6f, 0x4a, 0x0e, 0x31,
-            0xaa, 0x2b, 0xad, 0x8d, 0x97, 0x98, 0xc3, 0x80, 0x61, 0xdb, 0x9a, 0xe5, 0x5a, 0x4e,
-            0x8d, 0xf4, 0x54, 0xfd, 0x28, 0x11, 0x98, 0x94, 0x34, 0x4e, 0x71, 0xb7, 0x87, 0x70,
-            0xcc, 0x93, 0x1d, 0x61, 0xf4, 0x80, 0xec, 0xbb, 0x0b, 0x89, 0xd6, 0xeb, 0x69, 0x69,
-            0x01, 0x61, 0xe4, 0x9a, 0x71, 0x5f, 0xcd, 0x55,
-        ];
+        let valid =
+            verify_secp256k1_ecdsa_signature(&HASHED_MESSAGE, &PUB_KEY_X, &PUB_KEY_Y, &SIGNATURE);
 
+        assert_eq!(valid, Ok(true))
+    }
+
+    #[test]
+    fn rejects_non_verifying_signature() {
+        let mut hashed_message = HASHED_MESSAGE;
+        hashed_message[0] ^= 1;
+        let valid =
+            verify_secp256k1_ecdsa_signature(&hashed_message, &PUB_KEY_X, &PUB_KEY_Y, &SIGNATURE);
+
+        assert_eq!(valid, Ok(false))
+    }
+
+    #[test]
+    fn rejects_malformed_signature() {
+        // Both components of the signature must be non-zero.
+        let mut signature = SIGNATURE;
+        signature[32..].fill(0);
         let valid =
-            verify_secp256k1_ecdsa_signature(&hashed_message, &pub_key_x, &pub_key_y, &signature);
+            verify_secp256k1_ecdsa_signature(&HASHED_MESSAGE, &PUB_KEY_X, &PUB_KEY_Y, &signature);
+
+        assert_eq!(valid, Ok(false))
+    }
+
+    #[test]
+    fn errors_on_public_key_not_on_curve() {
+        let mut pub_key_y = PUB_KEY_Y;
+        pub_key_y[31] ^= 1;
+        let result =
+            verify_secp256k1_ecdsa_signature(&HASHED_MESSAGE, &PUB_KEY_X, &pub_key_y, &SIGNATURE);
+
+        assert!(result.is_err())
+    }
+
+    #[test]
+    fn recovers_public_key() {
+        let recovered = recover_secp256k1_public_key(&HASHED_MESSAGE, &SIGNATURE, 0);
+
+        assert_eq!(recovered, Some((PUB_KEY_X, PUB_KEY_Y)))
+    }
+
+    #[test]
+    fn recovers_other_key_with_wrong_recovery_id() {
+        let recovered = recover_secp256k1_public_key(&HASHED_MESSAGE, &SIGNATURE, 1).unwrap();
+
+        assert_ne!(recovered, (PUB_KEY_X, PUB_KEY_Y));
+        let valid = verify_secp256k1_ecdsa_signature(
+            &HASHED_MESSAGE,
+            &recovered.0,
+            &recovered.1,
+            &SIGNATURE,
+        );
+        assert_eq!(valid, Ok(true))
+    }
+
+    #[test]
+    fn does_not_recover_from_invalid_inputs() {
+        let mut signature = SIGNATURE;
+        signature[32..].fill(0);
 
-        assert!(valid)
+        assert_eq!(recover_secp256k1_public_key(&HASHED_MESSAGE, &signature, 0), None);
+        assert_eq!(recover_secp256k1_public_key(&HASHED_MESSAGE, &SIGNATURE, 2), None);
     }
 }
@@ -0,0 +1,298 @@
+use acir::{
+    acir_field::{FieldOptions, CHOSEN_FIELD},
+    circuit::opcodes::FunctionInput,
+    native_types::{Witness, WitnessMap},
+    BlackBoxFunc, FieldElement,
+};
+use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+use light_poseidon::{Poseidon, PoseidonBytesHasher};
+use num_bigint::BigUint;
+
+use super::ecdsa::to_u8_vec;
+use crate::{
+    pwg::witness_to_value,
+    pwg::{insert_value, OpcodeResolution},
+    OpcodeResolutionError,
+};
+
+pub(super) fn ed25519_verify(
+    initial_witness: &mut WitnessMap,
+    public_key_inputs: &[FunctionInput],
+    signature_inputs: &[FunctionInput],
+    message_inputs: &[FunctionInput],
+    output: Witness,
+) -> Result<OpcodeResolution, OpcodeResolutionError> {
+    let message = to_u8_vec(initial_witness, message_inputs, BlackBoxFunc::EddsaEd25519)?;
+
+    // These errors should never be emitted in practice as they would imply malformed ACIR generation.
+    let public_key: [u8; 32] =
+        to_u8_vec(initial_witness, public_key_inputs, BlackBoxFunc::EddsaEd25519)?
+            .try_into()
+            .map_err(|_| {
+                OpcodeResolutionError::BlackBoxFunctionFailed(
+                    BlackBoxFunc::EddsaEd25519,
+                    format!("expected public key size 32 but received {}", public_key_inputs.len()),
+                )
+            })?;
+
+    let signature: [u8; 64] =
+        to_u8_vec(initial_witness, signature_inputs, BlackBoxFunc::EddsaEd25519)?
+            .try_into()
+            .map_err(|_| {
+                OpcodeResolutionError::BlackBoxFunctionFailed(
+                    BlackBoxFunc::EddsaEd25519,
+                    format!("expected signature size 64 but received {}", signature_inputs.len()),
+                )
+            })?;
+
+    let is_valid = verify_ed25519_signature(&message, &public_key, &signature)?;
+
+    insert_value(&output, FieldElement::from(is_valid), initial_witness)?;
+    Ok(OpcodeResolution::Solved)
+}
+
+/// Verify an Ed25519 signature over `message`.
+///
+/// Returns `Ok(false)` for any signature which does not verify. An error is only returned if
+/// the public key is not the encoding of a point on the curve.
+pub fn verify_ed25519_signature(
+    message: &[u8],
+    public_key: &[u8; 32],
+    signature: &[u8; 64],
+) -> Result<bool, OpcodeResolutionError> {
+    let verifying_key = VerifyingKey::from_bytes(public_key).map_err(|_| {
+        OpcodeResolutionError::BlackBoxFunctionFailed(
+            BlackBoxFunc::EddsaEd25519,
+            "public key is not a valid point on the ed25519 curve".to_string(),
+        )
+    })?;
+    let signature = Signature::from_bytes(signature);
+
+    Ok(verifying_key.verify(message, &signature).is_ok())
+}
+
+#[allow(clippy::too_many_arguments)]
+pub(super) fn baby_jubjub_verify(
+    initial_witness: &mut WitnessMap,
+    public_key_x: FunctionInput,
+    public_key_y: FunctionInput,
+    signature_s: FunctionInput,
+    signature_r8_x: FunctionInput,
+    signature_r8_y: FunctionInput,
+    message: FunctionInput,
+    output: Witness,
+) -> Result<OpcodeResolution, OpcodeResolutionError> {
+    let value = |input: FunctionInput| witness_to_value(initial_witness, input.witness).copied();
+
+    let public_key = Point { x: value(public_key_x)?, y: value(public_key_y)? };
+    let r8 = Point { x: value(signature_r8_x)?, y: value(signature_r8_y)? };
+    let s = value(signature_s)?;
+    let message = value(message)?;
+
+    let is_valid = verify_baby_jubjub_signature(message, public_key, s, r8)?;
+
+    insert_value(&output, FieldElement::from(is_valid), initial_witness)?;
+    Ok(OpcodeResolution::Solved)
+}
+
+/// Verify an EdDSA signature over the Baby Jubjub curve, using the Poseidon hash function as
+/// specified by circomlib.
+///
+/// Returns `Ok(false)` for any signature which does not verify. An error is only returned if
+/// the public key is not a point on the curve.
+fn verify_baby_jubjub_signature(
+    message: FieldElement,
+    public_key: Point,
+    s: FieldElement,
+    r8: Point,
+) -> Result<bool, OpcodeResolutionError> {
+    // Baby Jubjub is defined over the BN254 scalar field.
+    if !matches!(CHOSEN_FIELD, FieldOptions::BN254) {
+        return Err(OpcodeResolutionError::BlackBoxFunctionFailed(
+            BlackBoxFunc::EddsaBabyJubjub,
+            format!("baby jubjub is not supported over the {CHOSEN_FIELD:?} field"),
+        ));
+    }
+    if !public_key.is_on_curve() {
+        return Err(OpcodeResolutionError::BlackBoxFunctionFailed(
+            BlackBoxFunc::EddsaBabyJubjub,
+            "public key is not a valid point on the baby jubjub curve".to_string(),
+        ));
+    }
+
+    let s = to_biguint(s);
+    if !r8.is_on_curve() || s >= subgroup_order() {
+        return Ok(false);
+    }
+
+    let hashed_message = poseidon_hash(&[r8.x, r8.y, public_key.x, public_key.y, message])?;
+
+    // Check that `S * B8 == R8 + 8 * H(R8, A, M) * A`.
+    let lhs = Point::base8().mul(&s);
+    let rhs = r8.add(&public_key.mul(&(to_biguint(hashed_message) * 8_u8)));
+    Ok(lhs == rhs)
+}
+
+fn poseidon_hash(inputs: &[FieldElement]) -> Result<FieldElement, OpcodeResolutionError> {
+    let hash_error = |err: light_poseidon::PoseidonError| {
+        OpcodeResolutionError::BlackBoxFunctionFailed(
+            BlackBoxFunc::EddsaBabyJubjub,
+            format!("failed to compute the poseidon hash: {err}"),
+        )
+    };
+
+    let inputs: Vec<Vec<u8>> = inputs.iter().map(|input| input.to_be_bytes()).collect();
+    let inputs: Vec<&[u8]> = inputs.iter().map(Vec::as_slice).collect();
+    let hash = Poseidon::<ark_bn254::Fr>::new_circom(inputs.len())
+        .and_then(|mut poseidon| poseidon.hash_bytes_be(&inputs))
+        .map_err(hash_error)?;
+    Ok(FieldElement::from_be_bytes_reduce(&hash))
+}
+
+fn to_biguint(value: FieldElement) -> BigUint {
+    BigUint::from_bytes_be(&value.to_be_bytes())
+}
+
+/// Order of the prime subgroup generated by [`Point::base8`].
+fn subgroup_order() -> BigUint {
+    BigUint::parse_bytes(
+        b"2736030358979909402780800718157159386076813972158567259200215660948447373041",
+        10,
+    )
+    .expect("order should be a valid integer")
+}
+
+/// An affine point on the Baby Jubjub twisted Edwards curve `a * x^2 + y^2 = 1 + d * x^2 * y^2`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Point {
+    x: FieldElement,
+    y: FieldElement,
+}
+
+impl Point {
+    const A: u128 = 168700;
+    const D: u128 = 168696;
+
+    fn identity() -> Self {
+        Point { x: FieldElement::zero(), y: FieldElement::one() }
+    }
+
+    /// Generator of the prime subgroup, as used by circomlib.
+    fn base8() -> Self {
+        Point {
+            x: FieldElement::from_hex(
+                "0x0bb77a6ad63e739b4eacb2e09d6277c12ab8d8010534e0b62893f3f6bb957051",
+            )
+            .expect("coordinate should be a valid field element"),
+            y: FieldElement::from_hex(
+                "0x25797203f7a0b24925572e1cd16bf9edfce0051fb9e133774b3c257a872d7d8b",
+            )
+            .expect("coordinate should be a valid field element"),
+        }
+    }
+
+    fn is_on_curve(&self) -> bool {
+        let (a, d) = (FieldElement::from(Self::A), FieldElement::from(Self::D));
+        let (x2, y2) = (self.x * self.x, self.y * self.y);
+        a * x2 + y2 == FieldElement::one() + d * x2 * y2
+    }
+
+    fn add(&self, other: &Point) -> Point {
+        let (a, d) = (FieldElement::from(Self::A), FieldElement::from(Self::D));
+        let t = d * self.x * other.x * self.y * other.y;
+        let x = (self.x * other.y + self.y * other.x) / (FieldElement::one() + t);
+        let y = (self.y * other.y - a * self.x * other.x) / (FieldElement::one() - t);
+        Point { x, y }
+    }
+
+    fn mul(&self, scalar: &BigUint) -> Point {
+        let mut result = Point::identity();
+        for i in (0..scalar.bits()).rev() {
+            result = result.add(&result);
+            if scalar.bit(i) {
+                result = result.add(self);
+            }
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use acir::FieldElement;
+    use num_bigint::BigUint;
+
+    use super::{
+        poseidon_hash, subgroup_order, to_biguint, verify_baby_jubjub_signature,
+        verify_ed25519_signature, Point,
+    };
+
+    // Test vector 2 from RFC 8032.
+    const ED25519_PUBLIC_KEY: [u8; 32] = [
+        0x3d, 0x40, 0x17, 0xc3, 0xe8, 0x43, 0x89, 0x5a, 0x92, 0xb7, 0x0a, 0xa7, 0x4d, 0x1b, 0x7e,
+        0xbc, 0x9c, 0x98, 0x2c, 0xcf, 0x2e, 0xc4, 0x96, 0x8c, 0xc0, 0xcd, 0x55, 0xf1, 0x2a, 0xf4,
+        0x66, 0x0c,
+    ];
+    const ED25519_MESSAGE: [u8; 1] = [0x72];
+    const ED25519_SIGNATURE: [u8; 64] = [
+        0x92, 0xa0, 0x09, 0xa9, 0xf0, 0xd4, 0xca, 0xb8, 0x72, 0x0e, 0x82, 0x0b, 0x5f, 0x64, 0x25,
+        0x40, 0xa2, 0xb2, 0x7b, 0x54, 0x16, 0x50, 0x3f, 0x8f, 0xb3, 0x76, 0x22, 0x23, 0xeb, 0xdb,
+        0x69, 0xda, 0x08, 0x5a, 0xc1, 0xe4, 0x3e, 0x15, 0x99, 0x6e, 0x45, 0x8f, 0x36, 0x13, 0xd0,
+        0xf1, 0x1d, 0x8c, 0x38, 0x7b, 0x2e, 0xae, 0xb4, 0x30, 0x2a, 0xee, 0xb0, 0x0d, 0x29, 0x16,
+        0x12, 0xbb, 0x0c, 0x00,
+    ];
+
+    #[test]
+    fn verifies_valid_ed25519_signature() {
+        let valid =
+            verify_ed25519_signature(&ED25519_MESSAGE, &ED25519_PUBLIC_KEY, &ED25519_SIGNATURE);
+        assert_eq!(valid, Ok(true));
+    }
+
+    #[test]
+    fn rejects_invalid_ed25519_signature() {
+        let mut signature = ED25519_SIGNATURE;
+        signature[0] ^= 1;
+        let valid = verify_ed25519_signature(&ED25519_MESSAGE, &ED25519_PUBLIC_KEY, &signature);
+        assert_eq!(valid, Ok(false));
+    }
+
+    /// Signs `message` with the private key `k` following circomlib's EdDSA-Poseidon scheme.
+    fn sign_baby_jubjub(k: u128, r: u128, message: FieldElement) -> (Point, FieldElement, Point) {
+        let public_key = Point::base8().mul(&BigUint::from(k));
+        let r8 = Point::base8().mul(&BigUint::from(r));
+        let hashed_message =
+            poseidon_hash(&[r8.x, r8.y, public_key.x, public_key.y, message]).unwrap();
+        let s = (BigUint::from(r) + to_biguint(hashed_message) * 8_u8 * k) % subgroup_order();
+        (public_key, FieldElement::from_be_bytes_reduce(&s.to_bytes_be()), r8)
+    }
+
+    #[test]
+    fn verifies_valid_baby_jubjub_signature() {
+        let message = FieldElement::from(1234_u128);
+        let (public_key, s, r8) = sign_baby_jubjub(0xdead_beef, 0x1234_5678, message);
+        assert_eq!(verify_baby_jubjub_signature(message, public_key, s, r8), Ok(true));
+    }
+
+    #[test]
+    fn rejects_invalid_baby_jubjub_signature() {
+        let message = FieldElement::from(1234_u128);
+        let (public_key, s, r8) = sign_baby_jubjub(0xdead_beef, 0x1234_5678, message);
+
+        let other_message = FieldElement::from(1235_u128);
+        assert_eq!(verify_baby_jubjub_signature(other_message, public_key, s, r8), Ok(false));
+
+        // The signature is not malleable by adding the subgroup order to `s`.
+        let malleated_s =
+            FieldElement::from_be_bytes_reduce(&(to_biguint(s) + subgroup_order()).to_bytes_be());
+        assert_eq!(verify_baby_jubjub_signature(message, public_key, malleated_s, r8), Ok(false));
+    }
+
+    #[test]
+    fn errors_on_baby_jubjub_public_key_not_on_curve() {
+        let message = FieldElement::from(1234_u128);
+        let (mut public_key, s, r8) = sign_baby_jubjub(0xdead_beef, 0x1234_5678, message);
+        public_key.y += FieldElement::one();
+        assert!(verify_baby_jubjub_signature(message, public_key, s, r8).is_err());
+    }
+}
@@ -0,0 +1,266 @@
+use acir::{
+    acir_field::{FieldOptions, CHOSEN_FIELD},
+    circuit::opcodes::FunctionInput,
+    native_types::{Witness, WitnessMap},
+    BlackBoxFunc, FieldElement,
+};
+use num_bigint::BigUint;
+
+use crate::{
+    pwg::witness_to_value,
+    pwg::{insert_value, OpcodeResolution},
+    OpcodeResolutionError,
+};
+
+pub(super) fn embedded_curve_add(
+    initial_witness: &mut WitnessMap,
+    input1_x: FunctionInput,
+    input1_y: FunctionInput,
+    input2_x: FunctionInput,
+    input2_y: FunctionInput,
+    outputs: (Witness, Witness),
+) -> Result<OpcodeResolution, OpcodeResolutionError> {
+    let func = BlackBoxFunc::EmbeddedCurveAdd;
+    let lhs = GrumpkinPoint::from_coordinates(
+        func,
+        *witness_to_value(initial_witness, input1_x.witness)?,
+        *witness_to_value(initial_witness, input1_y.witness)?,
+    )?;
+    let rhs = GrumpkinPoint::from_coordinates(
+        func,
+        *witness_to_value(initial_witness, input2_x.witness)?,
+        *witness_to_value(initial_witness, input2_y.witness)?,
+    )?;
+
+    let result = lhs.add(&rhs);
+
+    insert_value(&outputs.0, result.x, initial_witness)?;
+    insert_value(&outputs.1, result.y, initial_witness)?;
+    Ok(OpcodeResolution::Solved)
+}
+
+pub(super) fn embedded_curve_double(
+    initial_witness: &mut WitnessMap,
+    input_x: FunctionInput,
+    input_y: FunctionInput,
+    outputs: (Witness, Witness),
+) -> Result<OpcodeResolution, OpcodeResolutionError> {
+    let point = GrumpkinPoint::from_coordinates(
+        BlackBoxFunc::EmbeddedCurveDouble,
+        *witness_to_value(initial_witness, input_x.witness)?,
+        *witness_to_value(initial_witness, input_y.witness)?,
+    )?;
+
+    let result = point.double();
+
+    insert_value(&outputs.0, result.x, initial_witness)?;
+    insert_value(&outputs.1, result.y, initial_witness)?;
+    Ok(OpcodeResolution::Solved)
+}
+
+pub(super) fn multi_scalar_mul(
+    initial_witness: &mut WitnessMap,
+    points: &[FunctionInput],
+    scalars: &[FunctionInput],
+    outputs: (Witness, Witness),
+) -> Result<OpcodeResolution, OpcodeResolutionError> {
+    let func = BlackBoxFunc::MultiScalarMul;
+    // This error should never be emitted in practice as it would imply malformed ACIR generation.
+    if points.len() != 2 * scalars.len() {
+        return Err(OpcodeResolutionError::BlackBoxFunctionFailed(
+            func,
+            format!(
+                "expected {} point coordinates but received {}",
+                2 * scalars.len(),
+                points.len()
+            ),
+        ));
+    }
+
+    let mut result = GrumpkinPoint::infinity();
+    for (coordinates, scalar) in points.chunks(2).zip(scalars) {
+        let point = GrumpkinPoint::from_coordinates(
+            func,
+            *witness_to_value(initial_witness, coordinates[0].witness)?,
+            *witness_to_value(initial_witness, coordinates[1].witness)?,
+        )?;
+        let scalar = witness_to_value(initial_witness, scalar.witness)?;
+        result = result.add(&point.mul(&BigUint::from_bytes_be(&scalar.to_be_bytes())));
+    }
+
+    insert_value(&outputs.0, result.x, initial_witness)?;
+    insert_value(&outputs.1, result.y, initial_witness)?;
+    Ok(OpcodeResolution::Solved)
+}
+
+/// An affine point on the Grumpkin curve `y^2 = x^3 - 17`, defined over the BN254 scalar field.
+///
+/// The point at infinity is represented by the coordinates `(0, 0)`, which do not lie on the curve.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct GrumpkinPoint {
+    pub(crate) x: FieldElement,
+    pub(crate) y: FieldElement,
+}
+
+impl GrumpkinPoint {
+    /// Creates a point from its coordinates, checking that it lies on the curve.
+    pub(crate) fn from_coordinates(
+        func: BlackBoxFunc,
+        x: FieldElement,
+        y: FieldElement,
+    ) -> Result<Self, OpcodeResolutionError> {
+        // Grumpkin is only defined over the BN254 scalar field.
+        if !matches!(CHOSEN_FIELD, FieldOptions::BN254) {
+            return Err(OpcodeResolutionError::BlackBoxFunctionFailed(
+                func,
+                format!("grumpkin is not supported over the {CHOSEN_FIELD:?} field"),
+            ));
+        }
+
+        let point = GrumpkinPoint { x, y };
+        if !point.is_infinity() && !point.is_on_curve() {
+            return Err(OpcodeResolutionError::BlackBoxFunctionFailed(
+                func,
+                format!("point ({}, {}) is not on the grumpkin curve", x.to_hex(), y.to_hex()),
+            ));
+        }
+        Ok(point)
+    }
+
+    /// Returns the generator of the curve, the point of x coordinate one used by Barretenberg.
+    #[cfg(any(test, feature = "testing"))]
+    pub(crate) fn generator() -> Self {
+        GrumpkinPoint {
+            x: FieldElement::one(),
+            y: FieldElement::from_hex(
+                "0x0000000000000002cf135e7506a45d632d270d45f1181294833fc48d823f272c",
+            )
+            .expect("the generator is a valid hex string"),
+        }
+    }
+
+    pub(crate) fn infinity() -> Self {
+        GrumpkinPoint { x: FieldElement::zero(), y: FieldElement::zero() }
+    }
+
+    pub(crate) fn is_infinity(&self) -> bool {
+        self.x.is_zero() && self.y.is_zero()
+    }
+
+    fn is_on_curve(&self) -> bool {
+        self.y * self.y == self.x * self.x * self.x - FieldElement::from(17_u128)
+    }
+
+    pub(crate) fn add(&self, other: &GrumpkinPoint) -> GrumpkinPoint {
+        if self.is_infinity() {
+            return *other;
+        }
+        if other.is_infinity() {
+            return *self;
+        }
+        if self.x == other.x {
+            // Points sharing an x coordinate are either equal or each other's negation.
+            return if self.y == other.y { self.double() } else { GrumpkinPoint::infinity() };
+        }
+
+        let lambda = (other.y - self.y) / (other.x - self.x);
+        self.with_tangent(other, lambda)
+    }
+
+    pub(crate) fn double(&self) -> GrumpkinPoint {
+        if self.is_infinity() || self.y.is_zero() {
+            return GrumpkinPoint::infinity();
+        }
+
+        let three = FieldElement::from(3_u128);
+        let two = FieldElement::from(2_u128);
+        let lambda = three * self.x * self.x / (two * self.y);
+        self.with_tangent(self, lambda)
+    }
+
+    pub(crate) fn mul(&self, scalar: &BigUint) -> GrumpkinPoint {
+        let mut result = GrumpkinPoint::infinity();
+        for i in (0..scalar.bits()).rev() {
+            result = result.double();
+            if scalar.bit(i) {
+                result = result.add(self);
+            }
+        }
+        result
+    }
+
+    /// Returns the negation of the third point of intersection between the curve and the line
+    /// of slope `lambda` passing through `self` and `other`.
+    fn with_tangent(&self, other: &GrumpkinPoint, lambda: FieldElement) -> GrumpkinPoint {
+        let x = lambda * lambda - self.x - other.x;
+        let y = lambda * (self.x - x) - self.y;
+        GrumpkinPoint { x, y }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use acir::{BlackBoxFunc, FieldElement};
+    use num_bigint::BigUint;
+
+    use super::GrumpkinPoint;
+
+    fn generator() -> GrumpkinPoint {
+        let generator = GrumpkinPoint::generator();
+        GrumpkinPoint::from_coordinates(BlackBoxFunc::EmbeddedCurveAdd, generator.x, generator.y)
+            .unwrap()
+    }
+
+    #[test]
+    fn doubles_generator() {
+        let expected = GrumpkinPoint {
+            x: FieldElement::from_hex(
+                "0x06ce1b0827aafa85ddeb49cdaa36306d19a74caa311e13d46d8bc688cdbffffe",
+            )
+            .unwrap(),
+            y: FieldElement::from_hex(
+                "0x1c122f81a3a14964909ede0ba2a6855fc93faf6fa1a788bf467be7e7a43f80ac",
+            )
+            .unwrap(),
+        };
+        let g = generator();
+        assert_eq!(g.double(), expected);
+        assert_eq!(g.add(&g), expected);
+    }
+
+    #[test]
+    fn addition_is_associative() {
+        let g = generator();
+        let two_g = g.double();
+        assert_eq!(two_g.add(&g).add(&g), two_g.double());
+        assert_eq!(g.add(&two_g), two_g.add(&g));
+    }
+
+    #[test]
+    fn multiplies_by_scalar() {
+        let g = generator();
+        assert_eq!(g.mul(&BigUint::from(0_u8)), GrumpkinPoint::infinity());
+        assert_eq!(g.mul(&BigUint::from(1_u8)), g);
+        assert_eq!(g.mul(&BigUint::from(5_u8)), g.double().double().add(&g));
+    }
+
+    #[test]
+    fn handles_point_at_infinity() {
+        let g = generator();
+        let minus_g = GrumpkinPoint { x: g.x, y: -g.y };
+        assert_eq!(g.add(&minus_g), GrumpkinPoint::infinity());
+        assert_eq!(g.add(&GrumpkinPoint::infinity()), g);
+        assert_eq!(GrumpkinPoint::infinity().add(&g), g);
+        assert_eq!(GrumpkinPoint::infinity().double(), GrumpkinPoint::infinity());
+    }
+
+    #[test]
+    fn rejects_point_not_on_curve() {
+        let result = GrumpkinPoint::from_coordinates(
+            BlackBoxFunc::EmbeddedCurveDouble,
+            FieldElement::one(),
+            FieldElement::one(),
+        );
+        assert!(result.is_err());
+    }
+}
@@ -3,28 +3,50 @@ use acir::{
     native_types::{Witness, WitnessMap},
     BlackBoxFunc, FieldElement,
 };
-use blake2::{Blake2s256, Digest};
-use sha2::Sha256;
+use blake2::{Blake2b512, Blake2s256, Digest};
+use sha2::{digest::generic_array::GenericArray, Sha256, Sha512};
 use sha3::Keccak256;
 
-use crate::pwg::{insert_value, witness_to_value};
+use crate::pwg::{insert_value, witness_to_bytes, witness_to_value};
 use crate::{pwg::OpcodeResolution, OpcodeResolutionError};
+use stdlib::blackbox_fallbacks::radix::Endianness;
 
 /// Returns the sha256 hash of the provided `data`.
-pub(crate) fn sha256(data: &[u8]) -> [u8; 32] {
+pub fn sha256(data: &[u8]) -> [u8; 32] {
     generic_hash_256::<Sha256>(data)
 }
 
+/// Returns the sha512 hash of the provided `data`.
+pub fn sha512(data: &[u8]) -> [u8; 64] {
+    Sha512::digest(data).as_slice().try_into().expect("digest should be 512 bits")
+}
+
 /// Returns the blake2s hash of the provided `data`.
-pub(crate) fn blake2s256(data: &[u8]) -> [u8; 32] {
+pub fn blake2s256(data: &[u8]) -> [u8; 32] {
     generic_hash_256::<Blake2s256>(data)
 }
 
+/// Returns the blake2b hash of the provided `data`.
+pub fn blake2b512(data: &[u8]) -> [u8; 64] {
+    Blake2b512::digest(data).as_slice().try_into().expect("digest should be 512 bits")
+}
+
+/// Returns the blake3 hash of the provided `data`.
+pub fn blake3(data: &[u8]) -> [u8; 32] {
+    blake3::hash(data).into()
+}
+
 /// Returns the keccak256 hash of the provided `data`.
-pub(crate) fn keccak256(data: &[u8]) -> [u8; 32] {
+pub fn keccak256(data: &[u8]) -> [u8; 32] {
     generic_hash_256::<Keccak256>(data)
 }
 
+/// Returns the result of applying the keccak-f\[1600\] permutation to `state`, a list of 25 lanes indexed by `x + 5 * y`.
+pub fn keccakf1600(mut state: [u64; 25]) -> [u64; 25] {
+    keccak::f1600(&mut state);
+    state
+}
+
 /// Hashes `data` into a 32 byte digest.
 fn generic_hash_256<D: Digest>(data: &[u8]) -> [u8; 32] {
     D::digest(data).as_slice().try_into().expect("digest should be 256 bits")
@@ -37,7 +59,8 @@ pub(super) fn hash_to_field_128_security(
     inputs: &[FunctionInput],
     output: &Witness,
 ) -> Result<OpcodeResolution, OpcodeResolutionError> {
-    let message_input = get_hash_input(initial_witness, inputs, None)?;
+    let message_input =
+        get_hash_input(initial_witness, inputs, None, BlackBoxFunc::HashToField128Security)?;
     let digest = blake2s256(&message_input);
 
     let reduced_res = FieldElement::from_be_bytes_reduce(&digest);
@@ -56,7 +79,7 @@ pub(super) fn solve_generic_256_hash_opcode(
     hash_function: fn(data: &[u8]) -> [u8; 32],
     black_box_func: BlackBoxFunc,
 ) -> Result<OpcodeResolution, OpcodeResolutionError> {
-    let message_input = get_hash_input(initial_witness, inputs, var_message_size)?;
+    let message_input = get_hash_input(initial_witness, inputs, var_message_size, black_box_func)?;
     let digest: [u8; 32] = hash_function(&message_input);
 
     let outputs: [Witness; 32] = outputs.try_into().map_err(|_| {
@@ -70,21 +93,143 @@ pub(super) fn solve_generic_256_hash_opcode(
     Ok(OpcodeResolution::Solved)
 }
 
+/// Attempts to solve a 512 bit hash function opcode.
+/// If successful, `initial_witness` will be mutated to contain the new witness assignment.
+pub(super) fn solve_generic_512_hash_opcode(
+    initial_witness: &mut WitnessMap,
+    inputs: &[FunctionInput],
+    var_message_size: Option<&FunctionInput>,
+    outputs: &[Witness],
+    hash_function: fn(data: &[u8]) -> [u8; 64],
+    black_box_func: BlackBoxFunc,
+) -> Result<OpcodeResolution, OpcodeResolutionError> {
+    let message_input = get_hash_input(initial_witness, inputs, var_message_size, black_box_func)?;
+    let digest = hash_function(&message_input);
+
+    let outputs: [Witness; 64] = outputs.try_into().map_err(|_| {
+        OpcodeResolutionError::BlackBoxFunctionFailed(
+            black_box_func,
+            format!("Expected 64 outputs but encountered {}", outputs.len()),
+        )
+    })?;
+    write_digest_to_outputs(initial_witness, outputs, digest)?;
+
+    Ok(OpcodeResolution::Solved)
+}
+
+/// Attempts to solve a `Keccakf1600` opcode.
+/// If successful, `initial_witness` will be mutated to contain the new witness assignment.
+pub(super) fn solve_keccakf1600_opcode(
+    initial_witness: &mut WitnessMap,
+    inputs: &[FunctionInput],
+    outputs: &[Witness],
+) -> Result<OpcodeResolution, OpcodeResolutionError> {
+    // These errors should never be emitted in practice as they would imply malformed ACIR generation.
+    if inputs.len() != 25 || outputs.len() != 25 {
+        return Err(OpcodeResolutionError::BlackBoxFunctionFailed(
+            BlackBoxFunc::Keccakf1600,
+            format!(
+                "Expected 25 inputs and outputs but encountered {} inputs and {} outputs",
+                inputs.len(),
+                outputs.len()
+            ),
+        ));
+    }
+
+    let mut state = [0_u64; 25];
+    for (lane, input) in state.iter_mut().zip(inputs) {
+        let bytes = witness_to_bytes(
+            initial_witness,
+            input.witness,
+            64,
+            Endianness::Big,
+            BlackBoxFunc::Keccakf1600,
+        )?;
+        *lane = u64::from_be_bytes(bytes.try_into().expect("64 bits fit within 8 bytes"));
+    }
+
+    for (output, lane) in outputs.iter().zip(keccakf1600(state)) {
+        insert_value(output, FieldElement::from(lane as u128), initial_witness)?;
+    }
+
+    Ok(OpcodeResolution::Solved)
+}
+
+/// Attempts to solve a `Sha256Compression` opcode.
+/// If successful, `initial_witness` will be mutated to contain the new witness assignment.
+pub(super) fn solve_sha256_compression_opcode(
+    initial_witness: &mut WitnessMap,
+    inputs: &[FunctionInput],
+    hash_values: &[FunctionInput],
+    outputs: &[Witness],
+) -> Result<OpcodeResolution, OpcodeResolutionError> {
+    // These errors should never be emitted in practice as they would imply malformed ACIR generation.
+    if inputs.len() != 16 || hash_values.len() != 8 || outputs.len() != 8 {
+        return Err(OpcodeResolutionError::BlackBoxFunctionFailed(
+            BlackBoxFunc::Sha256Compression,
+            format!(
+                "Expected 16 inputs, 8 hash values and 8 outputs but encountered {} inputs, {} hash values and {} outputs",
+                inputs.len(),
+                hash_values.len(),
+                outputs.len()
+            ),
+        ));
+    }
+
+    let mut block = [0_u8; 64];
+    for (word_bytes, input) in block.chunks_mut(4).zip(inputs) {
+        word_bytes.copy_from_slice(&read_u32(initial_witness, input)?.to_be_bytes());
+    }
+    let mut state = [0_u32; 8];
+    for (word, input) in state.iter_mut().zip(hash_values) {
+        *word = read_u32(initial_witness, input)?;
+    }
+
+    sha2::compress256(&mut state, &[GenericArray::from(block)]);
+
+    for (output, word) in outputs.iter().zip(state) {
+        insert_value(output, FieldElement::from(word as u128), initial_witness)?;
+    }
+
+    Ok(OpcodeResolution::Solved)
+}
+
+/// Reads the assignment of `input` as a 32 bit word.
+fn read_u32(
+    initial_witness: &WitnessMap,
+    input: &FunctionInput,
+) -> Result<u32, OpcodeResolutionError> {
+    let bytes = witness_to_bytes(
+        initial_witness,
+        input.witness,
+        32,
+        Endianness::Big,
+        BlackBoxFunc::Sha256Compression,
+    )?;
+    Ok(u32::from_be_bytes(bytes.try_into().expect("32 bits fit within 4 bytes")))
+}
+
 /// Reads the hash function input from a [`WitnessMap`].
-fn get_hash_input(
+///
+/// Each input contributes the `ceil(num_bits / 8)` bytes of its value in little-endian order,
+/// matching the byte decomposition performed by the fallback implementations in the stdlib.
+/// An error is returned if an input does not fit within its bit size.
+pub(super) fn get_hash_input(
     initial_witness: &WitnessMap,
     inputs: &[FunctionInput],
     message_size: Option<&FunctionInput>,
+    black_box_func: BlackBoxFunc,
 ) -> Result<Vec<u8>, OpcodeResolutionError> {
     // Read witness assignments.
     let mut message_input = Vec::new();
     for input in inputs.iter() {
-        let witness = input.witness;
-        let num_bits = input.num_bits as usize;
-
-        let witness_assignment = witness_to_value(initial_witness, witness)?;
-        let bytes = witness_assignment.fetch_nearest_bytes(num_bits);
-        message_input.extend(bytes);
+        message_input.extend(witness_to_bytes(
+            initial_witness,
+            input.witness,
+            input.num_bits,
+            Endianness::Little,
+            black_box_func,
+        )?);
     }
 
     // Truncate the message if there is a `message_size` parameter given
@@ -97,7 +242,7 @@ fn get_hash_input(
             // in the message, then we error.
             if num_bytes_to_take > message_input.len() {
                 return Err(OpcodeResolutionError::BlackBoxFunctionFailed(
-                        acir::BlackBoxFunc::Keccak256,
+                        black_box_func,
                         format!("the number of bytes to take from the message is more than the number of bytes in the message. {} > {}", num_bytes_to_take, message_input.len()),
                     ));
             }
@@ -109,12 +254,12 @@ fn get_hash_input(
 }
 
 /// Writes a `digest` to the [`WitnessMap`] at witness indices `outputs`.
-fn write_digest_to_outputs(
+fn write_digest_to_outputs<const N: usize>(
     initial_witness: &mut WitnessMap,
-    outputs: [Witness; 32],
-    digest: [u8; 32],
+    outputs: [Witness; N],
+    digest: [u8; N],
 ) -> Result<(), OpcodeResolutionError> {
-    for (output_witness, value) in outputs.iter().zip(digest.into_iter()) {
+    for (output_witness, value) in outputs.iter().zip(digest) {
         insert_value(
             output_witness,
             FieldElement::from_be_bytes_reduce(&[value]),
@@ -0,0 +1,337 @@
+//! Native implementations of the hashing to fields and curves specified in RFC 9380.
+//!
+//! All suites use `expand_message_xmd` with SHA256 and target 128 bits of security.
+use acir::{
+    acir_field::{FieldOptions, CHOSEN_FIELD},
+    circuit::opcodes::FunctionInput,
+    native_types::{Witness, WitnessMap},
+    BlackBoxFunc, FieldElement,
+};
+use k256::{
+    elliptic_curve::{
+        hash2curve::{ExpandMsg, ExpandMsgXmd, Expander, GroupDigest},
+        sec1::{Coordinates, ToEncodedPoint},
+    },
+    Secp256k1,
+};
+use sha2::Sha256;
+
+use super::{embedded_curve_ops::GrumpkinPoint, hash::get_hash_input};
+use crate::{
+    pwg::{insert_value, OpcodeResolution},
+    OpcodeResolutionError,
+};
+
+/// Security level, in bits, of the elements obtained by hashing to a field.
+const SECURITY_BITS: u32 = 128;
+
+/// The constant `Z` of the Shallue-van de Woestijne map to the Grumpkin curve,
+/// selected with the procedure of appendix H.1 of RFC 9380.
+const GRUMPKIN_SVDW_Z: i128 = 1;
+
+/// Attempts to solve a `HashToField` opcode.
+/// If successful, `initial_witness` will be mutated to contain the new witness assignment.
+pub(super) fn solve_hash_to_field_opcode(
+    initial_witness: &mut WitnessMap,
+    inputs: &[FunctionInput],
+    domain_separator: &[u8],
+    output: &Witness,
+) -> Result<OpcodeResolution, OpcodeResolutionError> {
+    let func = BlackBoxFunc::HashToField;
+    let message = get_hash_input(initial_witness, inputs, None, func)?;
+    let elements = hash_to_field(func, &message, domain_separator, 1)?;
+
+    insert_value(output, elements[0], initial_witness)?;
+    Ok(OpcodeResolution::Solved)
+}
+
+/// Attempts to solve a `HashToCurveSecp256k1` opcode.
+/// If successful, `initial_witness` will be mutated to contain the new witness assignments.
+pub(super) fn solve_hash_to_curve_secp256k1_opcode(
+    initial_witness: &mut WitnessMap,
+    inputs: &[FunctionInput],
+    domain_separator: &[u8],
+    outputs: &[Witness],
+) -> Result<OpcodeResolution, OpcodeResolutionError> {
+    let func = BlackBoxFunc::HashToCurveSecp256k1;
+    // This error should never be emitted in practice as it would imply malformed ACIR generation.
+    if outputs.len() != 64 {
+        return Err(OpcodeResolutionError::BlackBoxFunctionFailed(
+            func,
+            format!("Expected 64 outputs but encountered {}", outputs.len()),
+        ));
+    }
+
+    let message = get_hash_input(initial_witness, inputs, None, func)?;
+    let point = Secp256k1::hash_from_bytes::<ExpandMsgXmd<Sha256>>(&[&message], domain_separator)
+        .map_err(|_| invalid_domain_separator(func, domain_separator))?
+        .to_affine()
+        .to_encoded_point(false);
+    let (x, y) = match point.coordinates() {
+        Coordinates::Uncompressed { x, y } => (x, y),
+        _ => {
+            return Err(OpcodeResolutionError::BlackBoxFunctionFailed(
+                func,
+                "message hashed to the point at infinity".to_string(),
+            ))
+        }
+    };
+
+    for (output, byte) in outputs.iter().zip(x.iter().chain(y.iter())) {
+        insert_value(output, FieldElement::from(*byte as u128), initial_witness)?;
+    }
+    Ok(OpcodeResolution::Solved)
+}
+
+/// Attempts to solve a `HashToEmbeddedCurve` opcode.
+/// If successful, `initial_witness` will be mutated to contain the new witness assignments.
+pub(super) fn solve_hash_to_embedded_curve_opcode(
+    initial_witness: &mut WitnessMap,
+    inputs: &[FunctionInput],
+    domain_separator: &[u8],
+    outputs: (Witness, Witness),
+) -> Result<OpcodeResolution, OpcodeResolutionError> {
+    let func = BlackBoxFunc::HashToEmbeddedCurve;
+    // Grumpkin is only defined over the BN254 scalar field.
+    if !matches!(CHOSEN_FIELD, FieldOptions::BN254) {
+        return Err(OpcodeResolutionError::BlackBoxFunctionFailed(
+            func,
+            format!("grumpkin is not supported over the {CHOSEN_FIELD:?} field"),
+        ));
+    }
+
+    let message = get_hash_input(initial_witness, inputs, None, func)?;
+    let point = hash_to_grumpkin(func, &message, domain_separator)?;
+
+    insert_value(&outputs.0, point.x, initial_witness)?;
+    insert_value(&outputs.1, point.y, initial_witness)?;
+    Ok(OpcodeResolution::Solved)
+}
+
+/// Hashes `message` to a point on the Grumpkin curve, following the `hash_to_curve` procedure of RFC 9380
+/// with the Shallue-van de Woestijne map.
+fn hash_to_grumpkin(
+    func: BlackBoxFunc,
+    message: &[u8],
+    domain_separator: &[u8],
+) -> Result<GrumpkinPoint, OpcodeResolutionError> {
+    let u = hash_to_field(func, message, domain_separator, 2)?;
+    // Grumpkin has a cofactor of 1 so there is no cofactor to clear from the sum.
+    Ok(map_to_grumpkin(u[0]).add(&map_to_grumpkin(u[1])))
+}
+
+/// Hashes `message` to `count` field elements, following the `hash_to_field` procedure of RFC 9380.
+fn hash_to_field(
+    func: BlackBoxFunc,
+    message: &[u8],
+    domain_separator: &[u8],
+    count: usize,
+) -> Result<Vec<FieldElement>, OpcodeResolutionError> {
+    // Each element is reduced from enough bytes for the bias of the reduction to be negligible.
+    let element_len = ((FieldElement::max_num_bits() + SECURITY_BITS + 7) / 8) as usize;
+    let bytes = expand_message_xmd(func, message, domain_separator, count * element_len)?;
+    Ok(bytes.chunks(element_len).map(FieldElement::from_be_bytes_reduce).collect())
+}
+
+/// Expands `message` into `len_in_bytes` uniformly random bytes, following the `expand_message_xmd`
+/// procedure of RFC 9380 with SHA256.
+fn expand_message_xmd(
+    func: BlackBoxFunc,
+    message: &[u8],
+    domain_separator: &[u8],
+    len_in_bytes: usize,
+) -> Result<Vec<u8>, OpcodeResolutionError> {
+    let mut expander =
+        ExpandMsgXmd::<Sha256>::expand_message(&[message], domain_separator, len_in_bytes)
+            .map_err(|_| invalid_domain_separator(func, domain_separator))?;
+    let mut bytes = vec![0; len_in_bytes];
+    expander.fill_bytes(&mut bytes);
+    Ok(bytes)
+}
+
+fn invalid_domain_separator(func: BlackBoxFunc, domain_separator: &[u8]) -> OpcodeResolutionError {
+    OpcodeResolutionError::BlackBoxFunctionFailed(
+        func,
+        format!(
+            "cannot hash with the domain separator {:?}",
+            String::from_utf8_lossy(domain_separator)
+        ),
+    )
+}
+
+/// Maps `u` to a point on the Grumpkin curve `y^2 = x^3 - 17` using the Shallue-van de Woestijne method,
+/// as specified in section 6.6.1 of RFC 9380.
+fn map_to_grumpkin(u: FieldElement) -> GrumpkinPoint {
+    let one = FieldElement::one();
+    let three = FieldElement::from(3_u128);
+    let g = |x: FieldElement| x * x * x - FieldElement::from(17_u128);
+
+    let z = FieldElement::from(GRUMPKIN_SVDW_Z);
+    let c1 = g(z);
+    let c2 = -z / FieldElement::from(2_u128);
+    let c3 = (-c1 * three * z * z).sqrt().expect("Z is chosen such that c3 exists");
+    let c3 = if sgn0(c3) { -c3 } else { c3 };
+    let c4 = -FieldElement::from(4_u128) * c1 / (three * z * z);
+
+    let tv1 = u * u * c1;
+    let tv2 = one + tv1;
+    let tv1 = one - tv1;
+    let tv3 = (tv1 * tv2).inverse();
+    let tv4 = u * tv1 * tv3 * c3;
+
+    let x1 = c2 - tv4;
+    let x2 = c2 + tv4;
+    let x3 = tv2 * tv2 * tv3;
+    let x3 = x3 * x3 * c4 + z;
+
+    let x = [x1, x2].into_iter().find(|&x| is_square(g(x))).unwrap_or(x3);
+    let y = g(x).sqrt().expect("one of the candidates is the x coordinate of a point");
+    let y = if sgn0(u) == sgn0(y) { y } else { -y };
+    GrumpkinPoint { x, y }
+}
+
+fn is_square(value: FieldElement) -> bool {
+    value.sqrt().is_some()
+}
+
+/// Returns the sign of `value`, which RFC 9380 defines as its parity for prime fields.
+fn sgn0(value: FieldElement) -> bool {
+    value.to_be_bytes().last().expect("field elements are not empty") & 1 == 1
+}
+
+#[cfg(test)]
+mod test {
+    use acir::{
+        circuit::opcodes::FunctionInput,
+        native_types::{Witness, WitnessMap},
+        BlackBoxFunc, FieldElement,
+    };
+
+    use super::{
+        expand_message_xmd, hash_to_grumpkin, is_square, map_to_grumpkin,
+        solve_hash_to_curve_secp256k1_opcode, GrumpkinPoint, GRUMPKIN_SVDW_Z,
+    };
+
+    fn from_hex(hex: &str) -> Vec<u8> {
+        (0..hex.len()).step_by(2).map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap()).collect()
+    }
+
+    #[test]
+    fn expands_messages_as_in_rfc_9380() {
+        // Appendix K.1 of RFC 9380.
+        let domain_separator = b"QUUX-V01-CS02-with-expander-SHA256-128";
+        let test_vectors = [
+            (&b""[..], "68a985b87eb6b46952128911f2a4412bbc302a9d759667f87f7a21d803f07235"),
+            (b"abc", "d8ccab23b5985ccea865c6c97b6e5b8350e794e603b4b97902f53a8a0d605615"),
+        ];
+        for (message, expected) in test_vectors {
+            let bytes =
+                expand_message_xmd(BlackBoxFunc::HashToField, message, domain_separator, 32);
+            assert_eq!(bytes.unwrap(), from_hex(expected));
+        }
+    }
+
+    #[test]
+    fn hashes_to_secp256k1_as_in_rfc_9380() {
+        // Appendix J.8.1 of RFC 9380.
+        let domain_separator = b"QUUX-V01-CS02-with-secp256k1_XMD:SHA-256_SSWU_RO_";
+        let test_vectors = [
+            (
+                &b""[..],
+                "c1cae290e291aee617ebaef1be6d73861479c48b841eaba9b7b5852ddfeb1346",
+                "64fa678e07ae116126f08b022a94af6de15985c996c3a91b64c406a960e51067",
+            ),
+            (
+                b"abc",
+                "3377e01eab42db296b512293120c6cee72b6ecf9f9205760bd9ff11fb3cb2c4b",
+                "7f95890f33efebd1044d382a01b1bee0900fb6116f94688d487c6c7b9c8371f6",
+            ),
+            (
+                b"abcdef0123456789",
+                "bac54083f293f1fe08e4a70137260aa90783a5cb84d3f35848b324d0674b0e3a",
+                "4436476085d4c3c4508b60fcf4389c40176adce756b398bdee27bca19758d828",
+            ),
+        ];
+
+        for (message, expected_x, expected_y) in test_vectors {
+            let mut witness_map = WitnessMap::new();
+            let mut inputs = Vec::with_capacity(message.len());
+            for (i, &byte) in message.iter().enumerate() {
+                let witness = Witness(i as u32 + 1);
+                witness_map.insert(witness, FieldElement::from(byte as u128));
+                inputs.push(FunctionInput { witness, num_bits: 8 });
+            }
+            let outputs: Vec<Witness> =
+                (0..64).map(|i| Witness(message.len() as u32 + i + 1)).collect();
+
+            solve_hash_to_curve_secp256k1_opcode(
+                &mut witness_map,
+                &inputs,
+                domain_separator,
+                &outputs,
+            )
+            .unwrap();
+
+            let point: Vec<u8> =
+                outputs.iter().map(|output| witness_map[output].to_u128() as u8).collect();
+            assert_eq!(point, [from_hex(expected_x), from_hex(expected_y)].concat());
+        }
+    }
+
+    #[test]
+    fn rejects_empty_domain_separator() {
+        assert!(expand_message_xmd(BlackBoxFunc::HashToField, b"abc", b"", 32).is_err());
+    }
+
+    #[test]
+    fn selects_svdw_constant_as_in_rfc_9380() {
+        // Appendix H.1 of RFC 9380, specialised to `A = 0`.
+        let g = |x: FieldElement| x * x * x - FieldElement::from(17_u128);
+        let h = |z: FieldElement| {
+            -(FieldElement::from(3_u128) * z * z) / (FieldElement::from(4_u128) * g(z))
+        };
+
+        let mut candidates =
+            (1..).flat_map(|i: i128| [FieldElement::from(i), -FieldElement::from(i)]);
+        let z = candidates
+            .find(|&z| {
+                !g(z).is_zero()
+                    && !h(z).is_zero()
+                    && is_square(h(z))
+                    && (is_square(g(z)) || is_square(g(-z / FieldElement::from(2_u128))))
+            })
+            .unwrap();
+        assert_eq!(z, FieldElement::from(GRUMPKIN_SVDW_Z));
+    }
+
+    #[test]
+    fn maps_field_elements_onto_the_curve() {
+        // Zero is an exceptional case of the map, for which `tv1 * tv2` has no inverse.
+        for u in [0_i128, 1, 2, 3, -7, 1 << 100] {
+            let point = map_to_grumpkin(FieldElement::from(u));
+            assert!(
+                GrumpkinPoint::from_coordinates(
+                    BlackBoxFunc::HashToEmbeddedCurve,
+                    point.x,
+                    point.y
+                )
+                .is_ok(),
+                "map of {u} is not on the curve"
+            );
+        }
+    }
+
+    #[test]
+    fn hashes_to_distinct_points_on_grumpkin() {
+        let func = BlackBoxFunc::HashToEmbeddedCurve;
+        let hash = |message: &[u8], domain_separator: &[u8]| {
+            let point = hash_to_grumpkin(func, message, domain_separator).unwrap();
+            GrumpkinPoint::from_coordinates(func, point.x, point.y).unwrap()
+        };
+
+        let point = hash(b"abc", b"QUUX-V01-CS02-with-Grumpkin_XMD:SHA-256_SVDW_RO_");
+        assert_eq!(point, hash(b"abc", b"QUUX-V01-CS02-with-Grumpkin_XMD:SHA-256_SVDW_RO_"));
+        assert_ne!(point, hash(b"abd", b"QUUX-V01-CS02-with-Grumpkin_XMD:SHA-256_SVDW_RO_"));
+        assert_ne!(point, hash(b"abc", b"another domain"));
+    }
+}
@@ -5,6 +5,7 @@ use acir::{
     native_types::{Witness, WitnessMap},
     FieldElement,
 };
+use num_bigint::BigUint;
 
 /// Solves a [`BlackBoxFunc::And`][acir::circuit::black_box_functions::BlackBoxFunc::AND] opcode and inserts
 /// the result into the supplied witness map
@@ -40,6 +41,60 @@ pub(super) fn xor(
     })
 }
 
+/// Solves a [`BlackBoxFunc::NOT`][acir::circuit::black_box_functions::BlackBoxFunc::NOT] opcode and inserts
+/// the result into the supplied witness map
+pub(super) fn not(
+    initial_witness: &mut WitnessMap,
+    input: &FunctionInput,
+    output: &Witness,
+) -> Result<OpcodeResolution, OpcodeResolutionError> {
+    solve_unary_gate(initial_witness, input, *output, |value, mask| value ^ mask)
+}
+
+/// Solves a [`BlackBoxFunc::SHL`][acir::circuit::black_box_functions::BlackBoxFunc::SHL] opcode and inserts
+/// the result into the supplied witness map
+pub(super) fn shl(
+    initial_witness: &mut WitnessMap,
+    input: &FunctionInput,
+    shift: u32,
+    output: &Witness,
+) -> Result<OpcodeResolution, OpcodeResolutionError> {
+    solve_unary_gate(initial_witness, input, *output, |value, mask| (value << shift) & mask)
+}
+
+/// Solves a [`BlackBoxFunc::SHR`][acir::circuit::black_box_functions::BlackBoxFunc::SHR] opcode and inserts
+/// the result into the supplied witness map
+pub(super) fn shr(
+    initial_witness: &mut WitnessMap,
+    input: &FunctionInput,
+    shift: u32,
+    output: &Witness,
+) -> Result<OpcodeResolution, OpcodeResolutionError> {
+    solve_unary_gate(initial_witness, input, *output, |value, _| value >> shift)
+}
+
+/// Derives the output of a bitwise operation on a single input, which is first truncated to its `num_bits` low bits.
+///
+/// The operation is given the truncated input along with a mask of `num_bits` ones.
+fn solve_unary_gate(
+    initial_witness: &mut WitnessMap,
+    input: &FunctionInput,
+    result: Witness,
+    op: impl Fn(BigUint, &BigUint) -> BigUint,
+) -> Result<OpcodeResolution, OpcodeResolutionError> {
+    let value =
+        BigUint::from_bytes_be(&witness_to_value(initial_witness, input.witness)?.to_be_bytes());
+    let mask = (BigUint::from(1_u32) << input.num_bits) - 1_u32;
+    let assignment = op(value & &mask, &mask);
+
+    insert_value(
+        &result,
+        FieldElement::from_be_bytes_reduce(&assignment.to_bytes_be()),
+        initial_witness,
+    )?;
+    Ok(OpcodeResolution::Solved)
+}
+
 /// Derives the rest of the witness based on the initial low level variables
 fn solve_logic_gate(
     initial_witness: &mut WitnessMap,
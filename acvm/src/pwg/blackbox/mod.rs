@@ -6,17 +6,57 @@ use acir::{
 use super::{OpcodeNotSolvable, OpcodeResolution, OpcodeResolutionError};
 use crate::PartialWitnessGenerator;
 
+mod aes128;
+mod bigint;
+mod cache;
+mod delegated;
 mod ecdsa;
+mod eddsa;
+mod embedded_curve_ops;
 mod hash;
+mod hash_to_curve;
 mod logic;
+#[cfg(feature = "native-pedersen")]
+mod pedersen;
+mod poseidon2;
 mod range;
+mod recursion;
+mod validation;
 
-use ecdsa::secp256k1_prehashed;
-// Hash functions should eventually be exposed for external consumers.
-use hash::{blake2s256, keccak256, sha256};
-use hash::{hash_to_field_128_security, solve_generic_256_hash_opcode};
-use logic::{and, xor};
-use range::solve_range_opcode;
+use aes128::solve_aes128_encryption_opcode;
+pub(crate) use bigint::BigIntSolver;
+pub use cache::BlackBoxCache;
+pub(crate) use delegated::assign_outputs;
+#[cfg(not(feature = "native-pedersen"))]
+use delegated::solve_pedersen_opcode;
+pub use delegated::BlackBoxCall;
+use delegated::{solve_fixed_base_scalar_mul_opcode, solve_schnorr_verify_opcode};
+// The native implementations of black box functions are exposed through the `blackbox_solver` module.
+pub use aes128::aes128_cbc_encrypt;
+pub use ecdsa::{recover_secp256k1_public_key, verify_secp256k1_ecdsa_signature};
+use ecdsa::{secp256k1_prehashed, secp256k1_recover};
+pub use eddsa::verify_ed25519_signature;
+use eddsa::{baby_jubjub_verify, ed25519_verify};
+#[cfg(feature = "testing")]
+pub(crate) use embedded_curve_ops::GrumpkinPoint;
+use embedded_curve_ops::{embedded_curve_add, embedded_curve_double, multi_scalar_mul};
+pub use hash::{blake2b512, blake2s256, blake3, keccak256, keccakf1600, sha256, sha512};
+use hash::{
+    hash_to_field_128_security, solve_generic_256_hash_opcode, solve_generic_512_hash_opcode,
+    solve_keccakf1600_opcode, solve_sha256_compression_opcode,
+};
+use hash_to_curve::{
+    solve_hash_to_curve_secp256k1_opcode, solve_hash_to_embedded_curve_opcode,
+    solve_hash_to_field_opcode,
+};
+use logic::{and, not, shl, shr, xor};
+#[cfg(feature = "native-pedersen")]
+pub(crate) use pedersen::{pedersen, pedersen_commitment};
+pub use poseidon2::poseidon2_permutation;
+use poseidon2::solve_poseidon2_permutation_opcode;
+use range::{solve_range_batch_opcode, solve_range_opcode};
+use recursion::solve_recursive_aggregation_opcode;
+use validation::validate;
 
 /// Check if all of the inputs to the function have assignments
 ///
@@ -42,8 +82,11 @@ fn contains_all_inputs(witness_assignments: &WitnessMap, inputs: &[FunctionInput
 pub(crate) fn solve(
     backend: &impl PartialWitnessGenerator,
     initial_witness: &mut WitnessMap,
+    bigint_solver: &mut BigIntSolver,
     bb_func: &BlackBoxFuncCall,
 ) -> Result<OpcodeResolution, OpcodeResolutionError> {
+    validate(bb_func)?;
+
     let inputs = bb_func.get_inputs_vec();
     if !contains_all_inputs(initial_witness, &inputs) {
         let unassigned_witness = first_missing_assignment(initial_witness, &inputs)
@@ -56,7 +99,17 @@ pub(crate) fn solve(
     match bb_func {
         BlackBoxFuncCall::AND { lhs, rhs, output } => and(initial_witness, lhs, rhs, output),
         BlackBoxFuncCall::XOR { lhs, rhs, output } => xor(initial_witness, lhs, rhs, output),
+        BlackBoxFuncCall::NOT { input, output } => not(initial_witness, input, output),
+        BlackBoxFuncCall::SHL { input, shift, output } => {
+            shl(initial_witness, input, *shift, output)
+        }
+        BlackBoxFuncCall::SHR { input, shift, output } => {
+            shr(initial_witness, input, *shift, output)
+        }
         BlackBoxFuncCall::RANGE { input } => solve_range_opcode(initial_witness, input),
+        BlackBoxFuncCall::RangeBatch { inputs } => {
+            solve_range_batch_opcode(initial_witness, inputs)
+        }
         BlackBoxFuncCall::SHA256 { inputs, outputs } => solve_generic_256_hash_opcode(
             initial_witness,
             inputs,
@@ -65,6 +118,32 @@ pub(crate) fn solve(
             sha256,
             bb_func.get_black_box_func(),
         ),
+        BlackBoxFuncCall::Sha512 { inputs, outputs } => solve_generic_512_hash_opcode(
+            initial_witness,
+            inputs,
+            None,
+            outputs,
+            sha512,
+            bb_func.get_black_box_func(),
+        ),
+        BlackBoxFuncCall::Sha512VariableLength { inputs, var_message_size, outputs } => {
+            solve_generic_512_hash_opcode(
+                initial_witness,
+                inputs,
+                Some(var_message_size),
+                outputs,
+                sha512,
+                bb_func.get_black_box_func(),
+            )
+        }
+        BlackBoxFuncCall::Blake2b { inputs, outputs } => solve_generic_512_hash_opcode(
+            initial_witness,
+            inputs,
+            None,
+            outputs,
+            blake2b512,
+            bb_func.get_black_box_func(),
+        ),
         BlackBoxFuncCall::Blake2s { inputs, outputs } => solve_generic_256_hash_opcode(
             initial_witness,
             inputs,
@@ -73,6 +152,14 @@ pub(crate) fn solve(
             blake2s256,
             bb_func.get_black_box_func(),
         ),
+        BlackBoxFuncCall::Blake3 { inputs, outputs } => solve_generic_256_hash_opcode(
+            initial_witness,
+            inputs,
+            None,
+            outputs,
+            blake3,
+            bb_func.get_black_box_func(),
+        ),
         BlackBoxFuncCall::Keccak256 { inputs, outputs } => solve_generic_256_hash_opcode(
             initial_witness,
             inputs,
@@ -91,9 +178,58 @@ pub(crate) fn solve(
                 bb_func.get_black_box_func(),
             )
         }
+        BlackBoxFuncCall::Sha256VariableLength { inputs, var_message_size, outputs } => {
+            solve_generic_256_hash_opcode(
+                initial_witness,
+                inputs,
+                Some(var_message_size),
+                outputs,
+                sha256,
+                bb_func.get_black_box_func(),
+            )
+        }
+        BlackBoxFuncCall::Blake2sVariableLength { inputs, var_message_size, outputs } => {
+            solve_generic_256_hash_opcode(
+                initial_witness,
+                inputs,
+                Some(var_message_size),
+                outputs,
+                blake2s256,
+                bb_func.get_black_box_func(),
+            )
+        }
+        BlackBoxFuncCall::Blake3VariableLength { inputs, var_message_size, outputs } => {
+            solve_generic_256_hash_opcode(
+                initial_witness,
+                inputs,
+                Some(var_message_size),
+                outputs,
+                blake3,
+                bb_func.get_black_box_func(),
+            )
+        }
+        BlackBoxFuncCall::Keccakf1600 { inputs, outputs } => {
+            solve_keccakf1600_opcode(initial_witness, inputs, outputs)
+        }
+        BlackBoxFuncCall::Aes128Encrypt { inputs, iv, key, outputs } => {
+            solve_aes128_encryption_opcode(initial_witness, inputs, iv, key, outputs)
+        }
+        BlackBoxFuncCall::Sha256Compression { inputs, hash_values, outputs } => {
+            solve_sha256_compression_opcode(initial_witness, inputs, hash_values, outputs)
+        }
+        #[allow(deprecated)]
         BlackBoxFuncCall::HashToField128Security { inputs, output } => {
             hash_to_field_128_security(initial_witness, inputs, output)
         }
+        BlackBoxFuncCall::HashToField { inputs, domain_separator, output } => {
+            solve_hash_to_field_opcode(initial_witness, inputs, domain_separator, output)
+        }
+        BlackBoxFuncCall::HashToCurveSecp256k1 { inputs, domain_separator, outputs } => {
+            solve_hash_to_curve_secp256k1_opcode(initial_witness, inputs, domain_separator, outputs)
+        }
+        BlackBoxFuncCall::HashToEmbeddedCurve { inputs, domain_separator, outputs } => {
+            solve_hash_to_embedded_curve_opcode(initial_witness, inputs, domain_separator, *outputs)
+        }
         BlackBoxFuncCall::SchnorrVerify {
             public_key_x,
             public_key_y,
@@ -101,17 +237,21 @@ pub(crate) fn solve(
             signature_e,
             message,
             output,
-        } => backend.schnorr_verify(
+        } => solve_schnorr_verify_opcode(
+            backend,
             initial_witness,
-            *public_key_x,
-            *public_key_y,
-            *signature_s,
-            *signature_e,
+            (*public_key_x, *public_key_y),
+            (*signature_s, *signature_e),
             message,
             *output,
         ),
+        #[cfg(feature = "native-pedersen")]
         BlackBoxFuncCall::Pedersen { inputs, domain_separator, outputs } => {
-            backend.pedersen(initial_witness, inputs, *domain_separator, *outputs)
+            pedersen(initial_witness, inputs, *domain_separator, *outputs)
+        }
+        #[cfg(not(feature = "native-pedersen"))]
+        BlackBoxFuncCall::Pedersen { inputs, domain_separator, outputs } => {
+            solve_pedersen_opcode(backend, initial_witness, inputs, *domain_separator, *outputs)
         }
         BlackBoxFuncCall::EcdsaSecp256k1 {
             public_key_x,
@@ -127,9 +267,91 @@ pub(crate) fn solve(
             message,
             *output,
         ),
+        BlackBoxFuncCall::EcdsaSecp256k1Recover {
+            signature,
+            recovery_id,
+            hashed_message,
+            public_key_x,
+            public_key_y,
+        } => secp256k1_recover(
+            initial_witness,
+            signature,
+            *recovery_id,
+            hashed_message,
+            public_key_x,
+            public_key_y,
+        ),
+        BlackBoxFuncCall::EddsaEd25519 { public_key, signature, message, output } => {
+            ed25519_verify(initial_witness, public_key, signature, message, *output)
+        }
+        BlackBoxFuncCall::EddsaBabyJubjub {
+            public_key_x,
+            public_key_y,
+            signature_s,
+            signature_r8_x,
+            signature_r8_y,
+            message,
+            output,
+        } => baby_jubjub_verify(
+            initial_witness,
+            *public_key_x,
+            *public_key_y,
+            *signature_s,
+            *signature_r8_x,
+            *signature_r8_y,
+            *message,
+            *output,
+        ),
+        BlackBoxFuncCall::EmbeddedCurveAdd { input1_x, input1_y, input2_x, input2_y, outputs } => {
+            embedded_curve_add(
+                initial_witness,
+                *input1_x,
+                *input1_y,
+                *input2_x,
+                *input2_y,
+                *outputs,
+            )
+        }
+        BlackBoxFuncCall::EmbeddedCurveDouble { input_x, input_y, outputs } => {
+            embedded_curve_double(initial_witness, *input_x, *input_y, *outputs)
+        }
+        BlackBoxFuncCall::MultiScalarMul { points, scalars, outputs } => {
+            multi_scalar_mul(initial_witness, points, scalars, *outputs)
+        }
+        BlackBoxFuncCall::BigIntAdd { lhs, rhs, output }
+        | BlackBoxFuncCall::BigIntMul { lhs, rhs, output }
+        | BlackBoxFuncCall::BigIntDiv { lhs, rhs, output } => {
+            bigint_solver.bigint_op(*lhs, *rhs, *output, bb_func.get_black_box_func())
+        }
+        BlackBoxFuncCall::BigIntFromLeBytes { inputs, modulus, output } => {
+            bigint_solver.bigint_from_bytes(initial_witness, inputs, modulus, *output)
+        }
+        BlackBoxFuncCall::BigIntToLeBytes { input, outputs } => {
+            bigint_solver.bigint_to_bytes(initial_witness, *input, outputs)
+        }
+        #[allow(deprecated)]
         BlackBoxFuncCall::FixedBaseScalarMul { input, outputs } => {
-            backend.fixed_base_scalar_mul(initial_witness, *input, *outputs)
+            solve_fixed_base_scalar_mul_opcode(backend, initial_witness, *input, *outputs)
+        }
+        BlackBoxFuncCall::RecursiveAggregation {
+            verification_key,
+            proof,
+            public_inputs,
+            key_hash,
+            input_aggregation_object,
+            output_aggregation_object,
+        } => solve_recursive_aggregation_opcode(
+            backend,
+            initial_witness,
+            verification_key,
+            proof,
+            public_inputs,
+            *key_hash,
+            input_aggregation_object.as_deref(),
+            output_aggregation_object,
+        ),
+        BlackBoxFuncCall::Poseidon2Permutation { inputs, outputs, len } => {
+            solve_poseidon2_permutation_opcode(initial_witness, inputs, outputs, *len)
         }
-        BlackBoxFuncCall::RecursiveAggregation { .. } => Ok(OpcodeResolution::Solved),
     }
 }
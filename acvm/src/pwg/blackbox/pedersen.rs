@@ -0,0 +1,194 @@
+//! Native implementation of the Pedersen commitment over the Grumpkin curve.
+//!
+//! Generators are derived by hashing to the curve in the same manner as Barretenberg,
+//! so that commitments match those computed by its backends.
+use acir::{
+    circuit::opcodes::FunctionInput,
+    native_types::{Witness, WitnessMap},
+    FieldElement,
+};
+use ark_bn254::{Fq, Fr};
+use ark_ec::{
+    models::CurveConfig,
+    short_weierstrass::{Affine, Projective, SWCurveConfig},
+    AffineRepr, CurveGroup,
+};
+use ark_ff::{BigInteger, MontFp, PrimeField, Zero};
+
+use super::hash::blake3;
+use crate::{
+    pwg::{insert_value, witness_to_value, OpcodeResolution},
+    OpcodeResolutionError,
+};
+
+/// Domain separator from which the generators of the commitment are derived.
+const DEFAULT_DOMAIN_SEPARATOR: &[u8] = b"DEFAULT_DOMAIN_SEPARATOR";
+
+/// Parameters of the Grumpkin curve `y^2 = x^3 - 17`, defined over the BN254 scalar field.
+struct GrumpkinConfig;
+
+impl CurveConfig for GrumpkinConfig {
+    type BaseField = Fr;
+    type ScalarField = Fq;
+
+    const COFACTOR: &'static [u64] = &[1];
+    const COFACTOR_INV: Fq = MontFp!("1");
+}
+
+impl SWCurveConfig for GrumpkinConfig {
+    const COEFF_A: Fr = MontFp!("0");
+    const COEFF_B: Fr = MontFp!("-17");
+    const GENERATOR: Affine<Self> = Affine::new_unchecked(
+        MontFp!("1"),
+        MontFp!("17631683881184975370165255887551781615748388533673675138860"),
+    );
+
+    fn mul_by_a(_: Fr) -> Fr {
+        Fr::zero()
+    }
+}
+
+type GrumpkinAffine = Affine<GrumpkinConfig>;
+
+/// Attempts to solve a `Pedersen` opcode, using `domain_separator` as the index of the first generator.
+/// If successful, `initial_witness` will be mutated to contain the new witness assignment.
+pub(crate) fn pedersen(
+    initial_witness: &mut WitnessMap,
+    inputs: &[FunctionInput],
+    domain_separator: u32,
+    outputs: (Witness, Witness),
+) -> Result<OpcodeResolution, OpcodeResolutionError> {
+    let inputs = inputs
+        .iter()
+        .map(|input| witness_to_value(initial_witness, input.witness).copied())
+        .collect::<Result<Vec<_>, OpcodeResolutionError>>()?;
+    let (x, y) = pedersen_commitment(&inputs, domain_separator);
+
+    insert_value(&outputs.0, x, initial_witness)?;
+    insert_value(&outputs.1, y, initial_witness)?;
+    Ok(OpcodeResolution::Solved)
+}
+
+/// Returns the coordinates of the commitment to `inputs`, using `domain_separator` as the index of the first generator.
+pub(crate) fn pedersen_commitment(
+    inputs: &[FieldElement],
+    domain_separator: u32,
+) -> (FieldElement, FieldElement) {
+    let scalars: Vec<Fr> = inputs.iter().map(|input| input.into_repr()).collect();
+    let commitment = commit(&scalars, domain_separator);
+    // The point at infinity is represented by the coordinates `(0, 0)`.
+    let (x, y) = commitment.xy().map(|(x, y)| (*x, *y)).unwrap_or_default();
+    (to_field_element(x), to_field_element(y))
+}
+
+fn to_field_element(value: Fr) -> FieldElement {
+    FieldElement::from_be_bytes_reduce(&value.into_bigint().to_bytes_be())
+}
+
+/// Commits to `inputs` using the generators of the default domain, starting from the generator at `starting_index`.
+fn commit(inputs: &[Fr], starting_index: u32) -> GrumpkinAffine {
+    let generators =
+        derive_generators(DEFAULT_DOMAIN_SEPARATOR, inputs.len() as u32, starting_index);
+    generators
+        .iter()
+        .zip(inputs)
+        .fold(Projective::zero(), |commitment, (generator, input)| {
+            commitment + generator.mul_bigint(input.into_bigint())
+        })
+        .into_affine()
+}
+
+/// Derives `num_generators` generators for the domain `domain_separator`, starting from the generator at `starting_index`.
+///
+/// Each generator is obtained by hashing to the curve the hash of the domain separator
+/// followed by the big-endian index of the generator, padded to 64 bytes.
+fn derive_generators(
+    domain_separator: &[u8],
+    num_generators: u32,
+    starting_index: u32,
+) -> Vec<GrumpkinAffine> {
+    let mut preimage = [0_u8; 64];
+    preimage[..32].copy_from_slice(&blake3(domain_separator));
+
+    (starting_index..starting_index + num_generators)
+        .map(|index| {
+            preimage[32..36].copy_from_slice(&index.to_be_bytes());
+            hash_to_curve(&preimage, 0)
+        })
+        .collect()
+}
+
+/// Hashes `seed` to a point on the curve, retrying with an incremented `attempt_count`
+/// until the hash is the x coordinate of a point.
+///
+/// The parity of the y coordinate is given by the most significant bit of the hash.
+fn hash_to_curve(seed: &[u8], attempt_count: u8) -> GrumpkinAffine {
+    let mut target_seed = seed.to_vec();
+    target_seed.extend([attempt_count, 0]);
+    let hash_hi = blake3(&target_seed);
+    target_seed[seed.len() + 1] = 1;
+    let hash_lo = blake3(&target_seed);
+
+    let x = Fr::from_be_bytes_mod_order(&[hash_hi, hash_lo].concat());
+    match GrumpkinAffine::get_point_from_x_unchecked(x, false) {
+        Some(point) => {
+            let is_odd = point.y.into_bigint().is_odd();
+            if is_odd == (hash_hi[0] > 127) {
+                point
+            } else {
+                -point
+            }
+        }
+        None => hash_to_curve(seed, attempt_count + 1),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use ark_bn254::Fr;
+    use ark_ff::{BigInteger, One, PrimeField};
+
+    use super::{commit, derive_generators, GrumpkinAffine};
+
+    fn to_hex(value: Fr) -> String {
+        value.into_bigint().to_bytes_be().iter().map(|byte| format!("{byte:02x}")).collect()
+    }
+
+    fn coordinates(point: GrumpkinAffine) -> (String, String) {
+        (to_hex(point.x), to_hex(point.y))
+    }
+
+    #[test]
+    fn derives_generators_on_the_curve() {
+        let generators = derive_generators(b"test domain", 16, 0);
+        for (i, generator) in generators.iter().enumerate() {
+            assert!(generator.is_on_curve());
+            assert!(!generators[..i].contains(generator), "generators should be distinct");
+        }
+        assert_eq!(derive_generators(b"test domain", 4, 12), generators[12..]);
+    }
+
+    #[test]
+    fn derives_length_generator() {
+        let length_generator = derive_generators(b"pedersen_hash_length", 1, 0)[0];
+        assert_eq!(
+            coordinates(length_generator),
+            (
+                "2df8b940e5890e4e1377e05373fae69a1d754f6935e6a780b666947431f2cdcd".to_string(),
+                "2ecd88d15967bc53b885912e0d16866154acb6aac2d3f85e27ca7eefb2c19083".to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn commitment_matches_barretenberg() {
+        let commitment = commit(&[Fr::one(), Fr::one()], 0);
+        assert_eq!(
+            coordinates(commitment),
+            (
+                "2f7a8f9a6c96926682205fb73ee43215bf13523c19d7afe36f12760266cdfe15".to_string(),
+                "01916b316adbbf0e10e39b18c1d24b33ec84b46daddf72f43878bcc92b6057e6".to_string()
+            )
+        );
+    }
+}
@@ -1,4 +1,7 @@
-use crate::{pwg::witness_to_value, pwg::OpcodeResolution, OpcodeResolutionError};
+use crate::{
+    pwg::{witness_to_value, ErrorLocation, OpcodeResolution},
+    OpcodeResolutionError,
+};
 use acir::{circuit::opcodes::FunctionInput, native_types::WitnessMap};
 
 pub(super) fn solve_range_opcode(
@@ -7,7 +10,22 @@ pub(super) fn solve_range_opcode(
 ) -> Result<OpcodeResolution, OpcodeResolutionError> {
     let w_value = witness_to_value(initial_witness, input.witness)?;
     if w_value.num_bits() > input.num_bits {
-        return Err(OpcodeResolutionError::UnsatisfiedConstrain);
+        return Err(OpcodeResolutionError::RangeCheckFailed {
+            witness: input.witness,
+            value: *w_value,
+            num_bits: input.num_bits,
+            opcode_location: ErrorLocation::Unresolved,
+        });
+    }
+    Ok(OpcodeResolution::Solved)
+}
+
+pub(super) fn solve_range_batch_opcode(
+    initial_witness: &mut WitnessMap,
+    inputs: &[FunctionInput],
+) -> Result<OpcodeResolution, OpcodeResolutionError> {
+    for input in inputs {
+        solve_range_opcode(initial_witness, input)?;
     }
     Ok(OpcodeResolution::Solved)
 }
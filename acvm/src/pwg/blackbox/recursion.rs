@@ -0,0 +1,70 @@
+use acir::{
+    circuit::opcodes::FunctionInput,
+    native_types::{Witness, WitnessMap},
+    BlackBoxFunc,
+};
+
+use super::{
+    delegated::{input_values, solve_black_box_call, BlackBoxCall},
+    first_missing_assignment,
+};
+use crate::{
+    pwg::{witness_to_value, OpcodeNotSolvable, OpcodeResolution},
+    OpcodeResolutionError, PartialWitnessGenerator,
+};
+
+/// Attempts to solve a `RecursiveAggregation` opcode by delegating the aggregation of the proof to the backend.
+/// If successful, `initial_witness` will be mutated to contain the output aggregation object.
+#[allow(clippy::too_many_arguments)]
+pub(super) fn solve_recursive_aggregation_opcode(
+    backend: &impl PartialWitnessGenerator,
+    initial_witness: &mut WitnessMap,
+    verification_key: &[FunctionInput],
+    proof: &[FunctionInput],
+    public_inputs: &[FunctionInput],
+    key_hash: FunctionInput,
+    input_aggregation_object: Option<&[FunctionInput]>,
+    output_aggregation_object: &[Witness],
+) -> Result<OpcodeResolution, OpcodeResolutionError> {
+    // These errors should never be emitted in practice as they would imply malformed ACIR generation.
+    if verification_key.is_empty() || proof.is_empty() {
+        return Err(failure("expected a non-empty verification key and proof".to_string()));
+    }
+    if output_aggregation_object.is_empty() {
+        return Err(failure("expected a non-empty output aggregation object".to_string()));
+    }
+    if let Some(input_aggregation_object) = input_aggregation_object {
+        if input_aggregation_object.len() != output_aggregation_object.len() {
+            return Err(failure(format!(
+                "input aggregation object has {} elements but output aggregation object has {}",
+                input_aggregation_object.len(),
+                output_aggregation_object.len()
+            )));
+        }
+        // The input aggregation object is not part of the opcode's inputs as it is the output
+        // of a previous aggregation, so it must be checked for here.
+        if let Some(unassigned_witness) =
+            first_missing_assignment(initial_witness, input_aggregation_object)
+        {
+            return Ok(OpcodeResolution::Stalled(OpcodeNotSolvable::MissingAssignment(
+                unassigned_witness.0,
+            )));
+        }
+    }
+
+    let call = BlackBoxCall::RecursiveAggregation {
+        verification_key: input_values(initial_witness, verification_key)?,
+        proof: input_values(initial_witness, proof)?,
+        public_inputs: input_values(initial_witness, public_inputs)?,
+        key_hash: *witness_to_value(initial_witness, key_hash.witness)?,
+        input_aggregation_object: input_aggregation_object
+            .map(|object| input_values(initial_witness, object))
+            .transpose()?,
+        output_len: output_aggregation_object.len(),
+    };
+    solve_black_box_call(backend, initial_witness, call, output_aggregation_object)
+}
+
+fn failure(reason: String) -> OpcodeResolutionError {
+    OpcodeResolutionError::BlackBoxFunctionFailed(BlackBoxFunc::RecursiveAggregation, reason)
+}
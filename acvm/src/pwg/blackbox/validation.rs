@@ -0,0 +1,165 @@
+use acir::{circuit::opcodes::BlackBoxFuncCall, FieldElement};
+
+use crate::OpcodeResolutionError;
+
+/// Checks that the number of inputs and outputs of `bb_func` and the bit sizes of its inputs are consistent,
+/// which does not depend on the values being solved.
+///
+/// Calls failing these checks can only come from malformed ACIR, so they are rejected up front rather than
+/// being left to panic in, or be silently truncated by, the solver of each function.
+pub(super) fn validate(bb_func: &BlackBoxFuncCall) -> Result<(), OpcodeResolutionError> {
+    let invalid = |reason: String| OpcodeResolutionError::InvalidBlackBoxInputs {
+        func: bb_func.get_black_box_func(),
+        reason,
+    };
+    let expect_len = |name: &str, len: usize, expected: usize| {
+        if len == expected {
+            Ok(())
+        } else {
+            Err(invalid(format!("expected {expected} {name} but found {len}")))
+        }
+    };
+
+    let max_num_bits = FieldElement::max_num_bits();
+    if let Some(input) = bb_func.get_inputs_vec().iter().find(|input| input.num_bits > max_num_bits)
+    {
+        return Err(invalid(format!(
+            "input witness {} has {} bits, which exceeds the {max_num_bits} bits of a field element",
+            input.witness.0, input.num_bits
+        )));
+    }
+
+    match bb_func {
+        BlackBoxFuncCall::AND { lhs, rhs, .. } | BlackBoxFuncCall::XOR { lhs, rhs, .. }
+            if lhs.num_bits != rhs.num_bits =>
+        {
+            return Err(invalid(format!(
+                "inputs have {} and {} bits but must have the same number of bits",
+                lhs.num_bits, rhs.num_bits
+            )));
+        }
+        BlackBoxFuncCall::SHA256 { outputs, .. }
+        | BlackBoxFuncCall::Sha256VariableLength { outputs, .. }
+        | BlackBoxFuncCall::Blake2s { outputs, .. }
+        | BlackBoxFuncCall::Blake2sVariableLength { outputs, .. }
+        | BlackBoxFuncCall::Blake3 { outputs, .. }
+        | BlackBoxFuncCall::Blake3VariableLength { outputs, .. }
+        | BlackBoxFuncCall::Keccak256 { outputs, .. }
+        | BlackBoxFuncCall::Keccak256VariableLength { outputs, .. } => {
+            expect_len("outputs", outputs.len(), 32)?;
+        }
+        BlackBoxFuncCall::Sha512 { outputs, .. }
+        | BlackBoxFuncCall::Sha512VariableLength { outputs, .. }
+        | BlackBoxFuncCall::Blake2b { outputs, .. }
+        | BlackBoxFuncCall::HashToCurveSecp256k1 { outputs, .. } => {
+            expect_len("outputs", outputs.len(), 64)?;
+        }
+        BlackBoxFuncCall::Keccakf1600 { inputs, outputs } => {
+            expect_len("inputs", inputs.len(), 25)?;
+            expect_len("outputs", outputs.len(), 25)?;
+        }
+        BlackBoxFuncCall::Sha256Compression { inputs, hash_values, outputs } => {
+            expect_len("inputs", inputs.len(), 16)?;
+            expect_len("hash values", hash_values.len(), 8)?;
+            expect_len("outputs", outputs.len(), 8)?;
+        }
+        BlackBoxFuncCall::Aes128Encrypt { inputs, iv, key, outputs } => {
+            expect_len("iv bytes", iv.len(), 16)?;
+            expect_len("key bytes", key.len(), 16)?;
+            // The plaintext is always padded with at least one byte.
+            expect_len("outputs", outputs.len(), (inputs.len() / 16 + 1) * 16)?;
+        }
+        BlackBoxFuncCall::EcdsaSecp256k1 { public_key_x, public_key_y, signature, .. } => {
+            expect_len("public key x bytes", public_key_x.len(), 32)?;
+            expect_len("public key y bytes", public_key_y.len(), 32)?;
+            expect_len("signature bytes", signature.len(), 64)?;
+        }
+        BlackBoxFuncCall::EcdsaSecp256k1Recover {
+            signature, public_key_x, public_key_y, ..
+        } => {
+            expect_len("signature bytes", signature.len(), 64)?;
+            expect_len("public key x outputs", public_key_x.len(), 32)?;
+            expect_len("public key y outputs", public_key_y.len(), 32)?;
+        }
+        BlackBoxFuncCall::EddsaEd25519 { public_key, signature, .. } => {
+            expect_len("public key bytes", public_key.len(), 32)?;
+            expect_len("signature bytes", signature.len(), 64)?;
+        }
+        BlackBoxFuncCall::Poseidon2Permutation { inputs, outputs, len } => {
+            expect_len("inputs", inputs.len(), *len as usize)?;
+            expect_len("outputs", outputs.len(), *len as usize)?;
+        }
+        BlackBoxFuncCall::MultiScalarMul { points, scalars, .. } => {
+            expect_len("point coordinates", points.len(), 2 * scalars.len())?;
+        }
+        _ => (),
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use acir::{
+        circuit::opcodes::{BlackBoxFuncCall, FunctionInput},
+        native_types::Witness,
+        BlackBoxFunc,
+    };
+
+    use super::validate;
+    use crate::OpcodeResolutionError;
+
+    fn inputs(count: u32, num_bits: u32) -> Vec<FunctionInput> {
+        (0..count).map(|i| FunctionInput { witness: Witness(i), num_bits }).collect()
+    }
+
+    fn outputs(count: u32) -> Vec<Witness> {
+        (100..100 + count).map(Witness).collect()
+    }
+
+    #[test]
+    fn rejects_wrong_output_counts() {
+        let call = BlackBoxFuncCall::SHA256 { inputs: inputs(3, 8), outputs: outputs(31) };
+        assert_eq!(
+            validate(&call),
+            Err(OpcodeResolutionError::InvalidBlackBoxInputs {
+                func: BlackBoxFunc::SHA256,
+                reason: "expected 32 outputs but found 31".to_owned(),
+            })
+        );
+        let call = BlackBoxFuncCall::SHA256 { inputs: inputs(3, 8), outputs: outputs(32) };
+        assert_eq!(validate(&call), Ok(()));
+    }
+
+    #[test]
+    fn rejects_signatures_of_the_wrong_size() {
+        let call = BlackBoxFuncCall::EcdsaSecp256k1 {
+            public_key_x: inputs(32, 8),
+            public_key_y: inputs(32, 8),
+            signature: inputs(63, 8),
+            hashed_message: inputs(32, 8),
+            output: Witness(200),
+        };
+        assert!(matches!(
+            validate(&call),
+            Err(OpcodeResolutionError::InvalidBlackBoxInputs {
+                func: BlackBoxFunc::EcdsaSecp256k1,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn rejects_bit_sizes_over_the_field_width() {
+        let call = BlackBoxFuncCall::RANGE { input: inputs(1, 255)[0] };
+        assert!(validate(&call).is_err());
+        let call = BlackBoxFuncCall::RANGE { input: inputs(1, 254)[0] };
+        assert_eq!(validate(&call), Ok(()));
+
+        let call = BlackBoxFuncCall::AND {
+            lhs: inputs(1, 8)[0],
+            rhs: inputs(1, 16)[0],
+            output: Witness(200),
+        };
+        assert!(validate(&call).is_err());
+    }
+}
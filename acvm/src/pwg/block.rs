@@ -12,16 +12,108 @@ use super::{
 };
 use super::{OpcodeNotSolvable, OpcodeResolution, OpcodeResolutionError};
 
-/// Maintains the state for solving Block opcode
-/// block_value is the value of the Block at the solved_operations step
-/// solved_operations is the number of solved elements in the block
+/// Whether a [`MemoryAccess`] initializes, reads or writes an element of a memory block.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum MemoryAccessKind {
+    Init,
+    Read,
+    Write,
+}
+
+/// An access to an element of a memory block, with the value read or written.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MemoryAccess {
+    /// The position of the access among those made to the block.
+    ///
+    /// The elements initialized by a [`MemoryInit`][acir::circuit::Opcode::MemoryInit] share its timestamp,
+    /// and each [`MemoryOp`][acir::circuit::Opcode::MemoryOp] then has the next one.
+    pub timestamp: u32,
+    pub index: u32,
+    pub value: FieldElement,
+    pub kind: MemoryAccessKind,
+}
+
+/// The accesses made to a memory block while solving a circuit, as recorded by
+/// [`ACVM::with_memory_traces`][super::ACVM::with_memory_traces].
+///
+/// Backends checking memory through lookups or offline memory checking build their tables from the accesses
+/// in the order they were made and from the same accesses sorted by index.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MemoryTrace {
+    accesses: Vec<MemoryAccess>,
+    /// The timestamp of the next access.
+    timestamp: u32,
+}
+
+impl MemoryTrace {
+    /// Returns the accesses in the order they were made, that is by timestamp.
+    pub fn accesses(&self) -> &[MemoryAccess] {
+        &self.accesses
+    }
+
+    /// Returns the accesses sorted by index, the accesses to each index remaining sorted by timestamp.
+    pub fn sorted_accesses(&self) -> Vec<MemoryAccess> {
+        let mut accesses = self.accesses.clone();
+        // The sort is stable, so accesses to the same index stay in the order they were made.
+        accesses.sort_by_key(|access| access.index);
+        accesses
+    }
+
+    fn record(
+        &mut self,
+        accesses: impl IntoIterator<Item = (u32, FieldElement)>,
+        kind: MemoryAccessKind,
+    ) {
+        let timestamp = self.timestamp;
+        self.accesses.extend(accesses.into_iter().map(|(index, value)| MemoryAccess {
+            timestamp,
+            index,
+            value,
+            kind,
+        }));
+        self.timestamp += 1;
+    }
+}
+
+/// Maintains the contents of a memory block while its [initialization][acir::circuit::Opcode::MemoryInit]
+/// and [operations][acir::circuit::Opcode::MemoryOp] are solved, in order.
 #[derive(Default)]
 pub(super) struct BlockSolver {
     block_value: HashMap<u32, FieldElement>,
-    solved_operations: usize,
+    /// The accesses made to the block, if they are traced.
+    trace: Option<MemoryTrace>,
 }
 
 impl BlockSolver {
+    /// Creates a solver for a block of `len` elements.
+    pub(super) fn with_capacity(len: usize) -> Self {
+        BlockSolver { block_value: HashMap::with_capacity(len), trace: None }
+    }
+
+    /// Creates a solver which traces the accesses made to the block if `traced`.
+    pub(super) fn new(traced: bool) -> Self {
+        let mut solver = BlockSolver::default();
+        if traced {
+            solver.trace_accesses();
+        }
+        solver
+    }
+
+    /// Starts tracing the accesses made to the block, unless they already are.
+    pub(super) fn trace_accesses(&mut self) {
+        self.trace.get_or_insert_with(MemoryTrace::default);
+    }
+
+    /// Returns the accesses made to the block, if they are traced.
+    pub(super) fn trace(&self) -> Option<&MemoryTrace> {
+        self.trace.as_ref()
+    }
+
+    /// Returns the number of elements the block has been assigned.
+    pub(super) fn len(&self) -> usize {
+        self.block_value.len()
+    }
+
     fn insert_value(&mut self, index: u32, value: FieldElement) {
         self.block_value.insert(index, value);
     }
@@ -30,13 +122,43 @@ impl BlockSolver {
         self.block_value.get(&index).copied()
     }
 
-    // Helper function which tries to solve a Block opcode
-    // As long as operations are resolved, we update/read from the block_value
-    // We stop when an operation cannot be resolved
+    /// Initializes the block with the values assigned to `init`, once they are all known.
+    pub(crate) fn init(
+        &mut self,
+        initial_witness: &WitnessMap,
+        init: &[Witness],
+    ) -> Result<OpcodeResolution, OpcodeResolutionError> {
+        let values = init
+            .iter()
+            .map(|witness| {
+                initial_witness
+                    .get(witness)
+                    .copied()
+                    .ok_or(OpcodeNotSolvable::MissingAssignment(witness.0))
+            })
+            .collect::<Result<Vec<_>, _>>();
+        match values {
+            Ok(values) => {
+                for (index, value) in values.iter().enumerate() {
+                    self.insert_value(index as u32, *value);
+                }
+                if let Some(trace) = &mut self.trace {
+                    let accesses =
+                        values.into_iter().enumerate().map(|(index, value)| (index as u32, value));
+                    trace.record(accesses, MemoryAccessKind::Init);
+                }
+                Ok(OpcodeResolution::Solved)
+            }
+            Err(not_solvable) => Ok(OpcodeResolution::Stalled(not_solvable)),
+        }
+    }
+
+    // Helper function which tries to solve a memory operation
+    // by reading from or writing to the block_value
     fn solve_helper(
         &mut self,
         initial_witness: &mut WitnessMap,
-        trace: &[MemOp],
+        block_op: &MemOp,
     ) -> Result<(), OpcodeResolutionError> {
         let missing_assignment = |witness: Option<Witness>| {
             OpcodeResolutionError::OpcodeNotSolvable(OpcodeNotSolvable::MissingAssignment(
@@ -44,58 +166,55 @@ impl BlockSolver {
             ))
         };
 
-        for block_op in trace.iter().skip(self.solved_operations) {
-            let op_expr = ArithmeticSolver::evaluate(&block_op.operation, initial_witness);
-            let operation = op_expr.to_const().ok_or_else(|| {
-                missing_assignment(ArithmeticSolver::any_witness_from_expression(&op_expr))
-            })?;
-            let index_expr = ArithmeticSolver::evaluate(&block_op.index, initial_witness);
-            let index = index_expr.to_const().ok_or_else(|| {
-                missing_assignment(ArithmeticSolver::any_witness_from_expression(&index_expr))
-            })?;
-            let index = index.try_to_u64().unwrap() as u32;
-            let value = ArithmeticSolver::evaluate(&block_op.value, initial_witness);
-            let value_witness = ArithmeticSolver::any_witness_from_expression(&value);
-            if value.is_const() {
-                self.insert_value(index, value.q_c);
-            } else if operation.is_zero() && value.is_linear() {
-                match ArithmeticSolver::solve_fan_in_term(&value, initial_witness) {
-                    GateStatus::GateUnsolvable => return Err(missing_assignment(value_witness)),
-                    GateStatus::GateSolvable(sum, (coef, w)) => {
-                        let map_value =
-                            self.get_value(index).ok_or_else(|| missing_assignment(Some(w)))?;
-                        insert_value(&w, (map_value - sum - value.q_c) / coef, initial_witness)?;
-                    }
-                    GateStatus::GateSatisfied(sum) => {
-                        self.insert_value(index, sum + value.q_c);
-                    }
+        let op_expr = ArithmeticSolver::evaluate(&block_op.operation, initial_witness);
+        let operation = op_expr.to_const().ok_or_else(|| {
+            missing_assignment(ArithmeticSolver::any_witness_from_expression(&op_expr))
+        })?;
+        let index_expr = ArithmeticSolver::evaluate(&block_op.index, initial_witness);
+        let index = index_expr.to_const().ok_or_else(|| {
+            missing_assignment(ArithmeticSolver::any_witness_from_expression(&index_expr))
+        })?;
+        let index = index.try_to_u64().unwrap() as u32;
+        let value = ArithmeticSolver::evaluate(&block_op.value, initial_witness);
+        let value_witness = ArithmeticSolver::any_witness_from_expression(&value);
+        if value.is_const() {
+            self.insert_value(index, value.q_c);
+        } else if operation.is_zero() && value.is_linear() {
+            match ArithmeticSolver::solve_fan_in_term(&value, initial_witness) {
+                GateStatus::GateUnsolvable => return Err(missing_assignment(value_witness)),
+                GateStatus::GateSolvable(sum, (coef, w)) => {
+                    let map_value =
+                        self.get_value(index).ok_or_else(|| missing_assignment(Some(w)))?;
+                    insert_value(&w, (map_value - sum - value.q_c) / coef, initial_witness)?;
+                }
+                GateStatus::GateSatisfied(sum) => {
+                    self.insert_value(index, sum + value.q_c);
                 }
-            } else {
-                return Err(missing_assignment(value_witness));
             }
-            self.solved_operations += 1;
+        } else {
+            return Err(missing_assignment(value_witness));
+        }
+        if let Some(trace) = &mut self.trace {
+            let kind =
+                if operation.is_zero() { MemoryAccessKind::Read } else { MemoryAccessKind::Write };
+            let value = self.block_value[&index];
+            trace.record([(index, value)], kind);
         }
         Ok(())
     }
 
-    // Try to solve block operations from the trace
-    // The function calls solve_helper() for solving the opcode
+    // Try to solve a memory operation
+    // The function calls solve_helper() for solving the operation
     // and converts its result into GateResolution
-    pub(crate) fn solve(
+    pub(crate) fn solve_memory_op(
         &mut self,
         initial_witness: &mut WitnessMap,
-        trace: &[MemOp],
+        block_op: &MemOp,
     ) -> Result<OpcodeResolution, OpcodeResolutionError> {
-        let initial_solved_operations = self.solved_operations;
-
-        match self.solve_helper(initial_witness, trace) {
+        match self.solve_helper(initial_witness, block_op) {
             Ok(()) => Ok(OpcodeResolution::Solved),
             Err(OpcodeResolutionError::OpcodeNotSolvable(err)) => {
-                if self.solved_operations > initial_solved_operations {
-                    Ok(OpcodeResolution::InProgress)
-                } else {
-                    Ok(OpcodeResolution::Stalled(err))
-                }
+                Ok(OpcodeResolution::Stalled(err))
             }
             Err(err) => Err(err),
         }
@@ -110,34 +229,17 @@ mod tests {
         FieldElement,
     };
 
-    use super::BlockSolver;
+    use super::{BlockSolver, MemoryAccess, MemoryAccessKind};
     use crate::pwg::insert_value;
 
     #[test]
     fn test_solver() {
-        let mut index = FieldElement::zero();
-        let mut trace = vec![MemOp {
-            operation: Expression::one(),
-            index: Expression::from_field(index),
-            value: Expression::from(Witness(1)),
-        }];
-        index += FieldElement::one();
-        trace.push(MemOp {
-            operation: Expression::one(),
-            index: Expression::from_field(index),
-            value: Expression::from(Witness(2)),
-        });
-        index += FieldElement::one();
-        trace.push(MemOp {
-            operation: Expression::one(),
-            index: Expression::from_field(index),
-            value: Expression::from(Witness(3)),
-        });
-        trace.push(MemOp {
+        let init = vec![Witness(1), Witness(2), Witness(3)];
+        let read = MemOp {
             operation: Expression::zero(),
             index: Expression::one(),
             value: Expression::from(Witness(4)),
-        });
+        };
         let mut initial_witness = WitnessMap::new();
         let mut value = FieldElement::zero();
         insert_value(&Witness(1), value, &mut initial_witness).unwrap();
@@ -146,7 +248,57 @@ mod tests {
         value = value + value;
         insert_value(&Witness(3), value, &mut initial_witness).unwrap();
         let mut block_solver = BlockSolver::default();
-        block_solver.solve(&mut initial_witness, &trace).unwrap();
+        block_solver.init(&initial_witness, &init).unwrap();
+        block_solver.solve_memory_op(&mut initial_witness, &read).unwrap();
         assert_eq!(initial_witness[&Witness(4)], FieldElement::one());
     }
+
+    #[test]
+    fn traces_accesses() {
+        let write = |index: u32, value: u32| MemOp {
+            operation: Expression::one(),
+            index: Expression::from_field(FieldElement::from(index as u128)),
+            value: Expression::from(Witness(value)),
+        };
+        let read =
+            |index: u32, value: u32| MemOp { operation: Expression::zero(), ..write(index, value) };
+        let mut initial_witness = WitnessMap::new();
+        for witness in 1..=3 {
+            initial_witness.insert(Witness(witness), FieldElement::from(witness as u128));
+        }
+        let mut block_solver = BlockSolver::new(true);
+        block_solver.init(&initial_witness, &[Witness(1), Witness(2)]).unwrap();
+        block_solver.solve_memory_op(&mut initial_witness, &write(0, 3)).unwrap();
+        block_solver.solve_memory_op(&mut initial_witness, &read(1, 4)).unwrap();
+        block_solver.solve_memory_op(&mut initial_witness, &read(0, 5)).unwrap();
+        assert_eq!(initial_witness[&Witness(5)], FieldElement::from(3_u128));
+
+        let access = |timestamp, index, value: u128, kind| MemoryAccess {
+            timestamp,
+            index,
+            value: FieldElement::from(value),
+            kind,
+        };
+        let trace = block_solver.trace().unwrap();
+        assert_eq!(
+            trace.accesses(),
+            [
+                access(0, 0, 1, MemoryAccessKind::Init),
+                access(0, 1, 2, MemoryAccessKind::Init),
+                access(1, 0, 3, MemoryAccessKind::Write),
+                access(2, 1, 2, MemoryAccessKind::Read),
+                access(3, 0, 3, MemoryAccessKind::Read),
+            ]
+        );
+        assert_eq!(
+            trace.sorted_accesses(),
+            [
+                access(0, 0, 1, MemoryAccessKind::Init),
+                access(1, 0, 3, MemoryAccessKind::Write),
+                access(3, 0, 3, MemoryAccessKind::Read),
+                access(0, 1, 2, MemoryAccessKind::Init),
+                access(2, 1, 2, MemoryAccessKind::Read),
+            ]
+        );
+    }
 }
@@ -1,21 +1,130 @@
+use std::ops::Range;
+
 use acir::{
-    brillig_vm::{RegisterIndex, Registers, VMStatus, Value, VM},
-    circuit::brillig::{Brillig, BrilligInputs, BrilligOutputs},
-    native_types::WitnessMap,
+    brillig_vm::{
+        ForeignCallInput, ForeignCallResult, RegisterIndex, Registers, VMStatus, Value, VM,
+    },
+    circuit::{
+        brillig::{Brillig, BrilligInputs, BrilligOutputs},
+        OpcodeLocation,
+    },
+    native_types::{Witness, WitnessMap},
     FieldElement,
 };
 
-use crate::{pwg::OpcodeNotSolvable, OpcodeResolution, OpcodeResolutionError};
+use crate::{
+    pwg::{ErrorLocation, OpcodeNotSolvable, OpcodeResolution},
+    OpcodeResolutionError,
+};
+
+use super::{get_value, insert_value, secret_audit::SecretAuditor};
+
+#[cfg(feature = "rayon")]
+use acir::circuit::Opcode;
+#[cfg(feature = "rayon")]
+use std::collections::{HashMap, HashSet};
+
+/// The outcome of solving a [`Brillig`] opcode.
+#[derive(Debug, PartialEq)]
+pub(crate) enum BrilligResolution {
+    /// The outputs have been assigned as described by the solution.
+    Solved(BrilligSolution),
+    /// The predicate or inputs can not be evaluated yet.
+    Stalled(OpcodeNotSolvable),
+    /// The bytecode is waiting on the result of a foreign call, and can be resumed with the paused VM.
+    ForeignCallWait(ForeignCallWaitInfo, Box<VM>),
+}
+
+impl From<BrilligResolution> for OpcodeResolution {
+    fn from(resolution: BrilligResolution) -> Self {
+        match resolution {
+            BrilligResolution::Solved(_) => OpcodeResolution::Solved,
+            BrilligResolution::Stalled(not_solvable) => OpcodeResolution::Stalled(not_solvable),
+            BrilligResolution::ForeignCallWait(wait_info, _) => {
+                OpcodeResolution::InProgressBrillig(wait_info)
+            }
+        }
+    }
+}
+
+/// Where the values assigned to a [Brillig output][BrilligOutputs] were read from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BrilligOutputSource {
+    /// The value of a register.
+    Register(RegisterIndex),
+    /// The values of a range of memory, starting at the address held by the `pointer` register.
+    Memory { pointer: RegisterIndex, range: Range<usize> },
+    /// No value was read, as the predicate of the opcode was zero and the output was assigned zero.
+    Skipped,
+}
+
+/// The witnesses assigned by a solved [`Brillig`] opcode, along with where their values were read from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BrilligSolution {
+    /// The index of the Brillig opcode in the circuit.
+    pub acir_index: usize,
+    /// Each output of the opcode, in order, along with its source.
+    pub outputs: Vec<(BrilligOutputs, BrilligOutputSource)>,
+}
+
+impl BrilligSolution {
+    /// Returns every witness assigned by the opcode.
+    pub fn assigned_witnesses(&self) -> impl Iterator<Item = Witness> + '_ {
+        self.outputs.iter().flat_map(|(output, _)| match output {
+            BrilligOutputs::Simple(witness) => std::slice::from_ref(witness).iter().copied(),
+            BrilligOutputs::Array(witnesses) => witnesses.iter().copied(),
+        })
+    }
+}
 
-use super::{get_value, insert_value};
+/// The state of a [`Brillig`] opcode before its bytecode is executed.
+pub(super) enum BrilligSetup {
+    /// The predicate and inputs have been evaluated into a VM ready to execute the bytecode.
+    Ready(Box<VM>),
+    /// The predicate or inputs can not be evaluated yet.
+    Stalled(OpcodeNotSolvable),
+    /// The predicate is zero, so the bytecode is not executed and the outputs are assigned zero.
+    Skipped,
+}
 
-pub(super) struct BrilligSolver;
+pub(crate) struct BrilligSolver;
 
 impl BrilligSolver {
-    pub(super) fn solve(
+    pub(crate) fn solve(
         initial_witness: &mut WitnessMap,
         brillig: &Brillig,
-    ) -> Result<OpcodeResolution, OpcodeResolutionError> {
+        acir_index: usize,
+        paused_vm: Option<VM>,
+        auditor: Option<&mut SecretAuditor>,
+    ) -> Result<BrilligResolution, OpcodeResolutionError> {
+        let mut vm = match paused_vm {
+            // A paused VM has already evaluated the predicate and inputs, and resumes from its foreign call.
+            Some(vm) => vm,
+            None => match Self::setup(initial_witness, brillig, auditor.as_deref())? {
+                BrilligSetup::Ready(vm) => *vm,
+                BrilligSetup::Stalled(not_solvable) => {
+                    return Ok(BrilligResolution::Stalled(not_solvable))
+                }
+                BrilligSetup::Skipped => {
+                    return Self::zero_out_brillig_outputs(initial_witness, brillig, acir_index)
+                }
+            },
+        };
+
+        // Run the Brillig VM on these inputs, bytecode, etc!
+        let vm_status = vm.process_opcodes();
+        Self::finish(initial_witness, brillig, acir_index, vm, vm_status, auditor)
+    }
+
+    /// Evaluates the predicate and inputs of `brillig`, returning a VM ready to execute its bytecode
+    /// unless the opcode cannot be executed yet or is to be skipped.
+    ///
+    /// The VM audits the secrets of `auditor` among the inputs, if any.
+    pub(super) fn setup(
+        initial_witness: &WitnessMap,
+        brillig: &Brillig,
+        auditor: Option<&SecretAuditor>,
+    ) -> Result<BrilligSetup, OpcodeResolutionError> {
         // If the predicate is `None`, then we simply return the value 1
         // If the predicate is `Some` but we cannot find a value, then we return stalled
         let pred_value = match &brillig.predicate {
@@ -25,14 +134,14 @@ impl BrilligSolver {
         let pred_value = match pred_value {
             Ok(pred_value) => pred_value,
             Err(OpcodeResolutionError::OpcodeNotSolvable(unsolved)) => {
-                return Ok(OpcodeResolution::Stalled(unsolved))
+                return Ok(BrilligSetup::Stalled(unsolved))
             }
             Err(err) => return Err(err),
         };
 
         // A zero predicate indicates the oracle should be skipped, and its outputs zeroed.
         if pred_value.is_zero() {
-            return Self::zero_out_brillig_outputs(initial_witness, brillig);
+            return Ok(BrilligSetup::Skipped);
         }
 
         // Set input values
@@ -47,8 +156,8 @@ impl BrilligSolver {
                 BrilligInputs::Single(expr) => match get_value(expr, initial_witness) {
                     Ok(value) => input_register_values.push(value.into()),
                     Err(_) => {
-                        return Ok(OpcodeResolution::Stalled(
-                            OpcodeNotSolvable::ExpressionHasTooManyUnknowns(expr.clone()),
+                        return Ok(BrilligSetup::Stalled(
+                            OpcodeNotSolvable::ExpressionHasTooManyUnknowns(Box::new(expr.clone())),
                         ))
                     }
                 },
@@ -59,8 +168,10 @@ impl BrilligSolver {
                         match get_value(expr, initial_witness) {
                             Ok(value) => input_memory.push(value.into()),
                             Err(_) => {
-                                return Ok(OpcodeResolution::Stalled(
-                                    OpcodeNotSolvable::ExpressionHasTooManyUnknowns(expr.clone()),
+                                return Ok(BrilligSetup::Stalled(
+                                    OpcodeNotSolvable::ExpressionHasTooManyUnknowns(Box::new(
+                                        expr.clone(),
+                                    )),
                                 ))
                             }
                         }
@@ -81,9 +192,26 @@ impl BrilligSolver {
             brillig.bytecode.clone(),
             brillig.foreign_call_results.clone(),
         );
+        if let Some(auditor) = auditor {
+            vm.audit_secrets(auditor.audit(brillig));
+        }
+        Ok(BrilligSetup::Ready(Box::new(vm)))
+    }
 
-        // Run the Brillig VM on these inputs, bytecode, etc!
-        let vm_status = vm.process_opcodes();
+    /// Assigns the outputs of `brillig` once its bytecode has been executed by `vm`, halting with `vm_status`.
+    ///
+    /// The leaks of secrets found by `vm` so far are recorded by `auditor`, if any.
+    pub(super) fn finish(
+        initial_witness: &mut WitnessMap,
+        brillig: &Brillig,
+        acir_index: usize,
+        mut vm: VM,
+        vm_status: VMStatus,
+        auditor: Option<&mut SecretAuditor>,
+    ) -> Result<BrilligResolution, OpcodeResolutionError> {
+        if let Some(auditor) = auditor {
+            auditor.record(acir_index, &mut vm);
+        }
 
         // Check the status of the Brillig VM.
         // It may be finished, in-progress, failed, or may be waiting for results of a foreign call.
@@ -91,40 +219,118 @@ impl BrilligSolver {
         // (when it gets foreign call results for example).
         let result = match vm_status {
             VMStatus::Finished => {
+                let mut outputs = Vec::with_capacity(brillig.outputs.len());
                 for (i, output) in brillig.outputs.iter().enumerate() {
-                    let register_value = vm.get_registers().get(RegisterIndex::from(i));
-                    match output {
+                    let register = RegisterIndex::from(i);
+                    let register_value = vm.get_registers().get(register);
+                    let source = match output {
                         BrilligOutputs::Simple(witness) => {
                             insert_value(witness, register_value.to_field(), initial_witness)?;
+                            BrilligOutputSource::Register(register)
                         }
                         BrilligOutputs::Array(witness_arr) => {
                             // Treat the register value as a pointer to memory
+                            let start = register_value.try_to_usize().map_err(|err| {
+                                OpcodeResolutionError::BrilligFunctionFailed {
+                                    message: format!("output pointer {err}"),
+                                    opcode_location: ErrorLocation::Resolved(OpcodeLocation::Acir(
+                                        acir_index,
+                                    )),
+                                    call_stack: Vec::new(),
+                                    foreign_calls: Vec::new(),
+                                }
+                            })?;
                             for (i, witness) in witness_arr.iter().enumerate() {
-                                let value = &vm.get_memory()[register_value.to_usize() + i];
+                                let value = &vm.get_memory()[start + i];
                                 insert_value(witness, value.to_field(), initial_witness)?;
                             }
+                            BrilligOutputSource::Memory {
+                                pointer: register,
+                                range: start..start + witness_arr.len(),
+                            }
                         }
-                    }
+                    };
+                    outputs.push((output.clone(), source));
                 }
-                OpcodeResolution::Solved
+                BrilligResolution::Solved(BrilligSolution { acir_index, outputs })
             }
             VMStatus::InProgress => unreachable!("Brillig VM has not completed execution"),
+            VMStatus::Paused { .. } => unreachable!("Brillig VM has no watchpoints"),
             VMStatus::Failure { message } => {
-                return Err(OpcodeResolutionError::BrilligFunctionFailed(message))
-            }
-            VMStatus::ForeignCallWait { function, inputs } => {
-                OpcodeResolution::InProgressBrillig(ForeignCallWaitInfo { function, inputs })
+                return Err(OpcodeResolutionError::BrilligFunctionFailed {
+                    message,
+                    call_stack: vm.call_stack(),
+                    foreign_calls: Vec::new(),
+                    opcode_location: ErrorLocation::Resolved(OpcodeLocation::Brillig {
+                        acir_index,
+                        brillig_index: vm.program_counter(),
+                    }),
+                })
             }
+            VMStatus::ForeignCallWait { function, inputs } => BrilligResolution::ForeignCallWait(
+                ForeignCallWaitInfo { function, inputs },
+                Box::new(vm),
+            ),
         };
 
         Ok(result)
     }
 
+    /// Executes the bytecode of the Brillig opcodes among `opcodes` whose predicate and inputs are known,
+    /// concurrently, returning the halted VM of each along with its status, keyed by the index of its opcode.
+    ///
+    /// Only opcodes assigning disjoint outputs are executed, and their outputs are left for [`BrilligSolver::finish`]
+    /// to assign in order, so that solving does not depend on the order in which the VMs halt. Opcodes resuming
+    /// from a foreign call are left to be executed as usual. Nothing is executed unless at least two opcodes can be.
+    #[cfg(feature = "rayon")]
+    pub(super) fn execute_concurrently(
+        initial_witness: &WitnessMap,
+        opcodes: &[Opcode],
+        opcode_indices: &[usize],
+        paused_vms: &HashMap<usize, VM>,
+        auditor: Option<&SecretAuditor>,
+    ) -> HashMap<usize, (VM, VMStatus)> {
+        use rayon::prelude::*;
+
+        let mut outputs: HashSet<Witness> = HashSet::new();
+        let mut ready = Vec::new();
+        for (opcode, &index) in opcodes.iter().zip(opcode_indices) {
+            let brillig = match opcode {
+                Opcode::Brillig(brillig) if !paused_vms.contains_key(&index) => brillig,
+                _ => continue,
+            };
+            let mut brillig_outputs = brillig.outputs.iter().flat_map(|output| match output {
+                BrilligOutputs::Simple(witness) => std::slice::from_ref(witness),
+                BrilligOutputs::Array(witnesses) => witnesses.as_slice(),
+            });
+            if brillig_outputs.clone().any(|witness| outputs.contains(witness)) {
+                continue;
+            }
+            // Errors are left to be reported when the opcode is solved.
+            if let Ok(BrilligSetup::Ready(vm)) = Self::setup(initial_witness, brillig, auditor) {
+                outputs.extend(brillig_outputs.by_ref());
+                ready.push((index, *vm));
+            }
+        }
+        if ready.len() < 2 {
+            return HashMap::new();
+        }
+
+        ready
+            .into_par_iter()
+            .map(|(index, mut vm)| {
+                let vm_status = vm.process_opcodes();
+                (index, (vm, vm_status))
+            })
+            .collect()
+    }
+
     /// Assigns the zero value to all outputs of the given [`Brillig`] bytecode.
     fn zero_out_brillig_outputs(
         initial_witness: &mut WitnessMap,
         brillig: &Brillig,
-    ) -> Result<OpcodeResolution, OpcodeResolutionError> {
+        acir_index: usize,
+    ) -> Result<BrilligResolution, OpcodeResolutionError> {
         for output in &brillig.outputs {
             match output {
                 BrilligOutputs::Simple(witness) => {
@@ -137,7 +343,12 @@ impl BrilligSolver {
                 }
             }
         }
-        Ok(OpcodeResolution::Solved)
+        let outputs = brillig
+            .outputs
+            .iter()
+            .map(|output| (output.clone(), BrilligOutputSource::Skipped))
+            .collect();
+        Ok(BrilligResolution::Solved(BrilligSolution { acir_index, outputs }))
     }
 }
 
@@ -145,10 +356,20 @@ impl BrilligSolver {
 /// where the result of the foreign call has not yet been provided.
 ///
 /// The caller must resolve this opcode externally based upon the information in the request.
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Eq, Clone)]
 pub struct ForeignCallWaitInfo {
     /// An identifier interpreted by the caller process
     pub function: String,
-    /// Resolved inputs to a foreign call computed in the previous steps of a Brillig VM process
-    pub inputs: Vec<Vec<Value>>,
+    /// Resolved inputs to a foreign call computed in the previous steps of a Brillig VM process,
+    /// along with the registers or memory they were read from
+    pub inputs: Vec<ForeignCallInput>,
+}
+
+/// A foreign call made by a Brillig opcode, along with the result it was resolved with.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ResolvedForeignCall {
+    /// The function called and the inputs it was called with.
+    pub call: ForeignCallWaitInfo,
+    /// The result provided by the caller.
+    pub result: ForeignCallResult,
 }
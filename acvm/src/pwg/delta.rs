@@ -0,0 +1,71 @@
+use std::collections::BTreeSet;
+
+use acir::{
+    circuit::{visitor::Visitor, Opcode},
+    native_types::Witness,
+};
+
+use super::WitnessCollector;
+use crate::compiler::{opcode_sensitivity, Sensitivity};
+
+/// The opcodes of a circuit along with which of its inputs influence each of them, so that the circuit
+/// can be solved again for changed inputs by [`ACVM::resolve_delta`][super::ACVM::resolve_delta].
+pub(super) struct DeltaResolver {
+    /// The opcodes of the circuit, by index.
+    opcodes: Vec<Opcode>,
+    inputs: BTreeSet<Witness>,
+    sensitivity: Sensitivity,
+}
+
+/// The witnesses to unassign and the opcodes to solve again once some inputs have changed.
+pub(super) struct Delta {
+    pub(super) witnesses: BTreeSet<Witness>,
+    /// The indices of the opcodes to solve again, in order.
+    pub(super) opcode_indices: Vec<usize>,
+}
+
+impl DeltaResolver {
+    pub(super) fn new(opcodes: Vec<Opcode>, inputs: BTreeSet<Witness>) -> Self {
+        let mut witnesses = WitnessCollector::default();
+        for opcode in &opcodes {
+            witnesses.visit_opcode(opcode);
+        }
+        let sensitivity = opcode_sensitivity(&opcodes, &inputs, witnesses.0);
+        DeltaResolver { opcodes, inputs, sensitivity }
+    }
+
+    pub(super) fn is_input(&self, witness: &Witness) -> bool {
+        self.inputs.contains(witness)
+    }
+
+    pub(super) fn opcode(&self, index: usize) -> &Opcode {
+        &self.opcodes[index]
+    }
+
+    /// Returns what must be solved again when the values of `changed_inputs` change.
+    ///
+    /// The opcodes which cannot be solved from the inputs and their witnesses are always included, as which inputs
+    /// influence them is unknown. Memory operations take effect in order, so every opcode accessing a memory block
+    /// is included as soon as one of them is.
+    pub(super) fn delta(&self, changed_inputs: &BTreeSet<Witness>) -> Delta {
+        let mut opcode_indices: BTreeSet<usize> =
+            self.sensitivity.opcodes_influenced_by(changed_inputs).into_iter().collect();
+        opcode_indices.extend(self.sensitivity.unresolved_opcodes());
+        let blocks: BTreeSet<_> = opcode_indices
+            .iter()
+            .filter_map(|&index| self.opcodes[index].memory_block_id())
+            .collect();
+        opcode_indices.extend((0..self.opcodes.len()).filter(|&index| {
+            matches!(self.opcodes[index].memory_block_id(), Some(block_id) if blocks.contains(&block_id))
+        }));
+
+        let witnesses = self
+            .sensitivity
+            .influenced_by(changed_inputs)
+            .union(self.sensitivity.unresolved())
+            .filter(|witness| !self.is_input(witness))
+            .copied()
+            .collect();
+        Delta { witnesses, opcode_indices: opcode_indices.into_iter().collect() }
+    }
+}
@@ -2,13 +2,16 @@ use std::cmp::Ordering;
 
 use acir::{
     circuit::directives::{Directive, LogInfo, QuotientDirective},
-    native_types::WitnessMap,
+    native_types::{Expression, Witness, WitnessMap},
     FieldElement,
 };
-use num_bigint::BigUint;
-use num_traits::Zero;
+use num_bigint::{BigInt, BigUint, Sign};
+use num_traits::{One, Zero};
 
-use crate::{pwg::OpcodeResolution, OpcodeResolutionError};
+use crate::{
+    pwg::{ErrorLocation, OpcodeResolution},
+    OpcodeResolutionError,
+};
 
 use super::{get_value, insert_value, witness_to_value};
 
@@ -79,10 +82,12 @@ fn solve_directives_internal(
             let big_integer = BigUint::from_bytes_be(&value_a.to_be_bytes());
 
             // Decompose the integer into its radix digits in little endian form.
-            let decomposed_integer = big_integer.to_radix_le(*radix);
+            let decomposed_integer = to_radix_le(&big_integer, *radix);
 
             if b.len() < decomposed_integer.len() {
-                return Err(OpcodeResolutionError::UnsatisfiedConstrain);
+                return Err(OpcodeResolutionError::UnsatisfiedConstrain {
+                    opcode_location: ErrorLocation::Unresolved,
+                });
             }
 
             for (i, witness) in b.iter().enumerate() {
@@ -91,7 +96,7 @@ fn solve_directives_internal(
                 // If it is not available, which can happen when the decomposed integer
                 // list is shorter than the witness list, we return 0.
                 let value = match decomposed_integer.get(i) {
-                    Some(digit) => FieldElement::from_be_bytes_reduce(&[*digit]),
+                    Some(digit) => FieldElement::from(*digit as u128),
                     None => FieldElement::zero(),
                 };
 
@@ -169,7 +174,120 @@ fn solve_directives_internal(
 
             Ok(())
         }
+        Directive::BigQuotient { a, b, q, r, limb_bits } => {
+            let int_a = big_integer_value(a, *limb_bits, initial_witness)?;
+            let int_b = big_integer_value(b, *limb_bits, initial_witness)?;
+            let (int_a, int_b) = match (int_a.to_biguint(), int_b.to_biguint()) {
+                (Some(int_a), Some(int_b)) if !int_b.is_zero() => (int_a, int_b),
+                _ => {
+                    return Err(OpcodeResolutionError::UnsatisfiedConstrain {
+                        opcode_location: ErrorLocation::Unresolved,
+                    })
+                }
+            };
+
+            insert_limbs(&(&int_a / &int_b), q, *limb_bits, initial_witness)?;
+            insert_limbs(&(&int_a % &int_b), r, *limb_bits, initial_witness)
+        }
+        Directive::BigInvert { a, modulus, result, limb_bits } => {
+            let int_a = big_integer_value(a, *limb_bits, initial_witness)?;
+            let modulus = big_integer_value(modulus, *limb_bits, initial_witness)?;
+            if modulus.sign() != Sign::Plus {
+                return Err(OpcodeResolutionError::UnsatisfiedConstrain {
+                    opcode_location: ErrorLocation::Unresolved,
+                });
+            }
+
+            let inverse = modular_inverse(int_a, &modulus).unwrap_or_else(BigInt::zero);
+            let inverse =
+                inverse.to_biguint().expect("inverse is reduced modulo a positive modulus");
+            insert_limbs(&inverse, result, *limb_bits, initial_witness)
+        }
+    }
+}
+
+/// Returns the digits of `value` in base `radix`, starting with the least significant one.
+///
+/// Unlike [`BigUint::to_radix_le`], radices greater than 256 are supported.
+fn to_radix_le(value: &BigUint, radix: u32) -> Vec<u32> {
+    if radix <= 256 {
+        return value.to_radix_le(radix).into_iter().map(u32::from).collect();
+    }
+    let mut digits = Vec::new();
+    let mut value = value.clone();
+    while !value.is_zero() {
+        let digit = &value % radix;
+        digits.push(digit.to_u32_digits().first().copied().unwrap_or_default());
+        value /= radix;
+    }
+    digits
+}
+
+/// Returns the big integer `\sum limbs[i] * 2^(i * limb_bits)`, where each limb is read as a signed integer
+/// lying between `-p/2` and `p/2` for the field modulus `p`.
+fn big_integer_value(
+    limbs: &[Expression],
+    limb_bits: u32,
+    initial_witness: &WitnessMap,
+) -> Result<BigInt, OpcodeResolutionError> {
+    let field_modulus = BigInt::from(FieldElement::modulus());
+    let mut value = BigInt::zero();
+    for (i, limb) in limbs.iter().enumerate() {
+        let limb_value = get_value(limb, initial_witness)?;
+        let mut limb_value = BigInt::from_bytes_be(Sign::Plus, &limb_value.to_be_bytes());
+        if &limb_value * 2 > field_modulus {
+            limb_value -= &field_modulus;
+        }
+        value += limb_value << (i * limb_bits as usize);
+    }
+    Ok(value)
+}
+
+/// Writes the little endian limbs of `limb_bits` bits of `value` to the witnesses `limbs`.
+///
+/// Returns an error if `value` does not fit within these limbs.
+fn insert_limbs(
+    value: &BigUint,
+    limbs: &[Witness],
+    limb_bits: u32,
+    initial_witness: &mut WitnessMap,
+) -> Result<(), OpcodeResolutionError> {
+    if value.bits() > limbs.len() as u64 * limb_bits as u64 {
+        return Err(OpcodeResolutionError::UnsatisfiedConstrain {
+            opcode_location: ErrorLocation::Unresolved,
+        });
     }
+
+    let limb_mask = (BigUint::one() << limb_bits) - 1_u32;
+    for (i, witness) in limbs.iter().enumerate() {
+        let limb = (value >> (i * limb_bits as usize)) & &limb_mask;
+        insert_value(
+            witness,
+            FieldElement::from_be_bytes_reduce(&limb.to_bytes_be()),
+            initial_witness,
+        )?;
+    }
+    Ok(())
+}
+
+/// Returns the inverse of `value` modulo `modulus`, or `None` if they are not coprime.
+fn modular_inverse(value: BigInt, modulus: &BigInt) -> Option<BigInt> {
+    // Extended euclidean algorithm, only tracking the coefficients of `value`.
+    let (mut old_r, mut r) = (reduce(&value, modulus), modulus.clone());
+    let (mut old_t, mut t) = (BigInt::one(), BigInt::zero());
+    while !r.is_zero() {
+        let quotient = &old_r / &r;
+        let next_r = &old_r - &quotient * &r;
+        old_r = std::mem::replace(&mut r, next_r);
+        let next_t = &old_t - &quotient * &t;
+        old_t = std::mem::replace(&mut t, next_t);
+    }
+    old_r.is_one().then(|| reduce(&old_t, modulus))
+}
+
+/// Returns the representative of `value` modulo `modulus` lying in `[0, modulus)`.
+fn reduce(value: &BigInt, modulus: &BigInt) -> BigInt {
+    ((value % modulus) + modulus) % modulus
 }
 
 /// This trims any leading zeroes.
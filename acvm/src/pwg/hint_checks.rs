@@ -0,0 +1,92 @@
+use std::collections::HashMap;
+
+use acir::{
+    circuit::{Opcode, OpcodeLocation},
+    native_types::{Expression, Witness, WitnessMap},
+};
+
+use super::{arithmetic::ArithmeticSolver, OpcodeResolutionError};
+
+/// Checks the values assigned by hints, i.e. [directives][Opcode::Directive] and [Brillig][Opcode::Brillig] opcodes,
+/// against the arithmetic constraints of the circuit as soon as they are solved.
+///
+/// As hints do not constrain their outputs themselves, this catches hints assigning wrong values at the hint
+/// rather than at whichever constraint happens to fail later on.
+#[derive(Debug, Default)]
+pub(super) struct HintChecker {
+    /// The arithmetic opcodes of the circuit, along with their index.
+    constraints: Vec<(usize, Expression)>,
+    /// The position in `constraints` of each constraint referencing a witness.
+    constraints_by_witness: HashMap<Witness, Vec<usize>>,
+}
+
+impl HintChecker {
+    pub(super) fn new(opcodes: &[Opcode], opcode_indices: &[usize]) -> Self {
+        let mut checker = HintChecker::default();
+        for (opcode, &index) in opcodes.iter().zip(opcode_indices) {
+            if let Opcode::AssertZero(expr) = opcode {
+                let position = checker.constraints.len();
+                let witnesses = expr
+                    .mul_terms
+                    .iter()
+                    .flat_map(|&(_, lhs, rhs)| [lhs, rhs])
+                    .chain(expr.linear_combinations.iter().map(|&(_, witness)| witness));
+                for witness in witnesses {
+                    let positions = checker.constraints_by_witness.entry(witness).or_default();
+                    if positions.last() != Some(&position) {
+                        positions.push(position);
+                    }
+                }
+                checker.constraints.push((index, expr.clone()));
+            }
+        }
+        checker
+    }
+
+    /// Checks that every constraint referencing one of `outputs`, the witnesses assigned by the hint at
+    /// `hint_index`, is satisfied.
+    ///
+    /// Constraints which still reference unassigned witnesses are skipped, as they are checked when solved.
+    pub(super) fn check(
+        &self,
+        hint_index: usize,
+        outputs: impl IntoIterator<Item = Witness>,
+        witness_map: &WitnessMap,
+    ) -> Result<(), OpcodeResolutionError> {
+        match self.unsatisfied_constraints(outputs, witness_map).first() {
+            Some(&constraint_location) => Err(OpcodeResolutionError::UnsatisfiedHintOutputs {
+                hint_location: OpcodeLocation::Acir(hint_index),
+                constraint_location,
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// Returns the location of each constraint referencing one of `witnesses` which is not satisfied, in order.
+    ///
+    /// Constraints which still reference unassigned witnesses are skipped.
+    pub(super) fn unsatisfied_constraints(
+        &self,
+        witnesses: impl IntoIterator<Item = Witness>,
+        witness_map: &WitnessMap,
+    ) -> Vec<OpcodeLocation> {
+        let mut positions: Vec<usize> = witnesses
+            .into_iter()
+            .filter_map(|witness| self.constraints_by_witness.get(&witness))
+            .flatten()
+            .copied()
+            .collect();
+        positions.sort_unstable();
+        positions.dedup();
+
+        positions
+            .into_iter()
+            .filter_map(|position| {
+                let (constraint_index, expr) = &self.constraints[position];
+                let value = ArithmeticSolver::evaluate(expr, witness_map).to_const();
+                matches!(value, Some(value) if !value.is_zero())
+                    .then_some(OpcodeLocation::Acir(*constraint_index))
+            })
+            .collect()
+    }
+}
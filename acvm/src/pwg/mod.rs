@@ -1,18 +1,39 @@
 // Re-usable methods that backends can use to implement their PWG
 
-use std::collections::HashMap;
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
+    time::Instant,
+};
 
-use crate::{Language, PartialWitnessGenerator};
+use crate::{
+    compiler::{check_deprecated_opcodes_indexed, DeprecatedOpcodeError},
+    foreign_calls::{ForeignCallRegistry, ForeignCallSignatureError},
+    Language, PartialWitnessGenerator,
+};
 use acir::{
-    brillig_vm::ForeignCallResult,
-    circuit::{brillig::Brillig, opcodes::BlockId, Opcode},
+    brillig_vm::{ForeignCallResult, VM},
+    circuit::{
+        brillig::Brillig,
+        opcodes::{BlackBoxFuncCall, BlockId},
+        phases::Phase,
+        visitor::Visitor,
+        Opcode, OpcodeLocation,
+    },
     native_types::{Expression, Witness, WitnessMap},
     BlackBoxFunc, FieldElement,
 };
+use num_bigint::BigUint;
+use stdlib::blackbox_fallbacks::radix::{round_to_nearest_byte, Endianness};
 
 use self::{
-    arithmetic::ArithmeticSolver, block::BlockSolver, brillig::BrilligSolver,
+    arithmetic::ArithmeticSolver,
+    blackbox::BigIntSolver,
+    block::BlockSolver,
+    brillig::{BrilligResolution, BrilligSolver},
+    delta::DeltaResolver,
     directives::solve_directives,
+    hint_checks::HintChecker,
+    secret_audit::SecretAuditor,
 };
 
 use thiserror::Error;
@@ -20,18 +41,29 @@ use thiserror::Error;
 // arithmetic
 pub(crate) mod arithmetic;
 // Brillig bytecode
-mod brillig;
+pub(crate) mod brillig;
 // Directives
 mod directives;
 // black box functions
-mod blackbox;
+pub(crate) mod blackbox;
 mod block;
+mod delta;
+mod hint_checks;
+mod plan;
+mod secret_audit;
+mod trace;
 
-pub use brillig::ForeignCallWaitInfo;
+pub use arithmetic::IntegerExpressions;
+pub use blackbox::{BlackBoxCache, BlackBoxCall};
+pub use block::{MemoryAccess, MemoryAccessKind, MemoryTrace};
+pub use brillig::{BrilligOutputSource, BrilligSolution, ForeignCallWaitInfo, ResolvedForeignCall};
+pub use plan::ResolutionPlan;
+pub use secret_audit::SecretAuditFinding;
+pub use trace::{SolveTrace, TraceEvent, TraceEventKind, TraceOutcome};
 
 #[derive(Debug, PartialEq)]
 pub enum PartialWitnessGeneratorStatus {
-    /// All opcodes have been solved.
+    /// All opcodes have been solved, apart from those of the [phases][ACVM::with_phases] which are not active.
     Solved,
 
     /// The ACVM has encountered a request for a Brillig [foreign call][acir::brillig_vm::Opcode::ForeignCall]
@@ -40,6 +72,14 @@ pub enum PartialWitnessGeneratorStatus {
     ///
     /// Once this is done, the ACVM can be restarted to solve the remaining opcodes.
     RequiresForeignCall,
+
+    /// The backend [defers][PartialWitnessGenerator::defers_black_box_func] a black box function which the ACVM
+    /// needs to compute. The values of the outputs of the call returned by [`ACVM::get_pending_black_box_call`]
+    /// must be passed back to the ACVM using [`ACVM::resolve_pending_black_box_call`].
+    ///
+    /// The other opcodes are solved as far as possible before this is returned, so that the call can be
+    /// computed asynchronously. Once it is resolved, the ACVM can be restarted to solve the remaining opcodes.
+    RequiresBlackBoxCall,
 }
 
 #[derive(Debug, PartialEq)]
@@ -52,6 +92,8 @@ pub enum OpcodeResolution {
     InProgress,
     /// The brillig oracle opcode is not solved but could be resolved given some values
     InProgressBrillig(brillig::ForeignCallWaitInfo),
+    /// The black box function is not solved as the backend defers the call to the caller of the ACVM
+    InProgressBlackBox(BlackBoxCall),
 }
 
 // This enum represents the different cases in which an
@@ -67,7 +109,33 @@ pub enum OpcodeNotSolvable {
     #[error("missing assignment for witness index {0}")]
     MissingAssignment(u32),
     #[error("expression has too many unknowns {0}")]
-    ExpressionHasTooManyUnknowns(Expression),
+    ExpressionHasTooManyUnknowns(Box<Expression>),
+    #[error("missing value for big integer {0}")]
+    MissingBigInt(u32),
+}
+
+/// Raised when [activating][ACVM::activate_phase] a phase which is not one of the inactive phases of the ACVM.
+#[derive(Clone, PartialEq, Eq, Debug, Error)]
+#[error("no inactive phase is named `{0}`")]
+pub struct UnknownPhaseError(pub String);
+
+/// Where an [`OpcodeResolutionError`] occurred within the circuit being solved.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ErrorLocation {
+    /// The error has been raised by a solver which does not know which opcode it is solving.
+    ///
+    /// The [ACVM][ACVM::solve] resolves this into the location of the failing opcode.
+    Unresolved,
+    Resolved(OpcodeLocation),
+}
+
+impl std::fmt::Display for ErrorLocation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ErrorLocation::Unresolved => write!(f, "unresolved"),
+            ErrorLocation::Resolved(location) => write!(f, "{location}"),
+        }
+    }
 }
 
 #[derive(PartialEq, Eq, Debug, Error)]
@@ -77,26 +145,160 @@ pub enum OpcodeResolutionError {
     #[error("backend does not currently support the {0} opcode. ACVM does not currently have a fallback for this opcode.")]
     UnsupportedBlackBoxFunc(BlackBoxFunc),
     #[error("could not satisfy all constraints")]
-    UnsatisfiedConstrain,
+    UnsatisfiedConstrain { opcode_location: ErrorLocation },
+    #[error(
+        "value {} ({}) of witness {} does not fit within {num_bits} bits",
+        decimal_string(value),
+        hex_string(value),
+        witness.witness_index()
+    )]
+    RangeCheckFailed {
+        witness: Witness,
+        value: FieldElement,
+        num_bits: u32,
+        opcode_location: ErrorLocation,
+    },
     #[error("failed to solve blackbox function: {0}, reason: {1}")]
     BlackBoxFunctionFailed(BlackBoxFunc, String),
-    #[error("failed to solve brillig function, reason: {0}")]
-    BrilligFunctionFailed(String),
+    #[error("invalid inputs to blackbox function: {func}, reason: {reason}")]
+    InvalidBlackBoxInputs { func: BlackBoxFunc, reason: String },
+    #[error("failed to solve brillig function, reason: {message}")]
+    BrilligFunctionFailed {
+        message: String,
+        /// The location of the failing Brillig opcode, along with the program counter of its VM.
+        opcode_location: ErrorLocation,
+        /// The program counter of each call which the VM had not yet returned from, outermost first.
+        call_stack: Vec<usize>,
+        /// The foreign calls made by the Brillig opcode and resolved through the ACVM before it failed, in order.
+        foreign_calls: Vec<ResolvedForeignCall>,
+    },
+    #[error("values assigned by the opcode at {hint_location} do not satisfy the constraint at {constraint_location}")]
+    UnsatisfiedHintOutputs { hint_location: OpcodeLocation, constraint_location: OpcodeLocation },
+}
+
+impl OpcodeResolutionError {
+    /// Returns the location of the opcode which raised this error, if it is known.
+    pub fn opcode_location(&self) -> Option<OpcodeLocation> {
+        match self {
+            OpcodeResolutionError::UnsatisfiedConstrain {
+                opcode_location: ErrorLocation::Resolved(location),
+            }
+            | OpcodeResolutionError::RangeCheckFailed {
+                opcode_location: ErrorLocation::Resolved(location),
+                ..
+            }
+            | OpcodeResolutionError::BrilligFunctionFailed {
+                opcode_location: ErrorLocation::Resolved(location),
+                ..
+            } => Some(*location),
+            OpcodeResolutionError::UnsatisfiedHintOutputs { hint_location, .. } => {
+                Some(*hint_location)
+            }
+            _ => None,
+        }
+    }
+
+    /// Attaches `location` to this error if its location has not yet been resolved.
+    fn resolve_location(mut self, location: OpcodeLocation) -> Self {
+        match &mut self {
+            OpcodeResolutionError::UnsatisfiedConstrain { opcode_location }
+            | OpcodeResolutionError::RangeCheckFailed { opcode_location, .. }
+            | OpcodeResolutionError::BrilligFunctionFailed { opcode_location, .. }
+                if *opcode_location == ErrorLocation::Unresolved =>
+            {
+                *opcode_location = ErrorLocation::Resolved(location);
+            }
+            _ => (),
+        }
+        self
+    }
+}
+
+/// An assignment made through [`ACVM::overwrite_witness`], along with the opcodes found to conflict with it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WitnessOverwrite {
+    /// The overwritten witness.
+    pub witness: Witness,
+    /// The value assigned to the witness before it was overwritten, if any.
+    pub previous_value: Option<FieldElement>,
+    /// The value the witness was overwritten with.
+    pub value: FieldElement,
+    /// The location of each opcode which was found not to be satisfied by the overwritten value, in the order found.
+    pub conflicts: Vec<OpcodeLocation>,
 }
 
 pub struct ACVM<B: PartialWitnessGenerator> {
     backend: B,
-    /// Stores the solver for each [block][`Opcode::Block`] opcode. This persists their internal state to prevent recomputation.
+    /// Stores the contents of each memory block, as initialized by [`Opcode::MemoryInit`] and updated by [`Opcode::MemoryOp`].
     block_solvers: HashMap<BlockId, BlockSolver>,
+    /// Stores the values of the big integers created by [big integer opcodes][acir::circuit::opcodes::BlackBoxFuncCall::BigIntFromLeBytes].
+    bigint_solver: BigIntSolver,
     /// A list of opcodes which are to be executed by the ACVM.
     ///
     /// Note that this doesn't include any opcodes which are waiting on a pending foreign call.
     opcodes: Vec<Opcode>,
+    /// The index in the original circuit of each opcode in `opcodes`, used to locate errors.
+    opcode_indices: Vec<usize>,
 
     witness_map: WitnessMap,
 
-    /// A list of foreign calls which must be resolved before the ACVM can resume execution.
-    pending_foreign_calls: Vec<UnresolvedBrilligCall>,
+    /// A list of foreign calls which must be resolved before the ACVM can resume execution,
+    /// along with the index of the Brillig opcode which made each call.
+    pending_foreign_calls: Vec<(usize, UnresolvedBrilligCall)>,
+
+    /// A list of black box calls deferred by the backend which must be resolved before the ACVM can resume execution,
+    /// along with the index of the opcode which made each call.
+    pending_black_box_calls: Vec<(usize, BlackBoxFuncCall, BlackBoxCall)>,
+
+    /// The values of the outputs of the resolved black box calls whose opcodes have not yet been solved,
+    /// keyed by the index of their opcode.
+    black_box_call_results: HashMap<usize, Vec<FieldElement>>,
+
+    /// Caches the outputs of black box calls keyed by their inputs, if enabled.
+    black_box_cache: Option<BlackBoxCache>,
+
+    /// The VMs of the Brillig opcodes whose foreign calls have been resolved, keyed by the index of their opcode.
+    /// These resume from the foreign call rather than re-executing the bytecode from the start.
+    paused_brillig_vms: HashMap<usize, VM>,
+
+    /// How the outputs of each solved Brillig opcode were assigned, in the order they were solved.
+    brillig_solutions: Vec<BrilligSolution>,
+
+    /// The foreign calls resolved for each Brillig opcode which has not yet been solved, keyed by the index of
+    /// its opcode. These are reported should the opcode fail.
+    foreign_call_transcripts: HashMap<usize, Vec<ResolvedForeignCall>>,
+
+    /// Checks the outputs of hints against the constraints referencing them, if enabled.
+    hint_checker: Option<HintChecker>,
+
+    /// Witnesses which are assigned zero if the solver stalls on them without them having been assigned.
+    implicit_zeros: BTreeSet<Witness>,
+    /// The witnesses of `implicit_zeros` which have been assigned zero.
+    applied_implicit_zeros: BTreeSet<Witness>,
+
+    /// The index of each solved opcode, in the order they were solved.
+    solved_opcode_indices: Vec<usize>,
+
+    /// The journal of the witnesses overwritten through [`ACVM::overwrite_witness`], in order.
+    witness_overwrites: Vec<WitnessOverwrite>,
+
+    /// The timeline of the solver, if tracing is enabled.
+    trace: Option<SolveTrace>,
+
+    /// Audits Brillig opcodes for leaks of secret witnesses, if enabled.
+    secret_auditor: Option<SecretAuditor>,
+
+    /// Whether the accesses made to each memory block are traced.
+    memory_traced: bool,
+
+    /// Tracks which opcodes each input influences, if [delta resolution][ACVM::with_delta_resolution] is enabled.
+    delta_resolver: Option<DeltaResolver>,
+
+    /// The name of each phase which has not been activated, along with its opcodes and their index.
+    inactive_phases: Vec<(String, Vec<(usize, Opcode)>)>,
+
+    /// The integer forms of the arithmetic opcodes, if [enabled][ACVM::with_integer_expressions].
+    integer_expressions: IntegerExpressions,
 }
 
 impl<B: PartialWitnessGenerator> ACVM<B> {
@@ -104,12 +306,294 @@ impl<B: PartialWitnessGenerator> ACVM<B> {
         ACVM {
             backend,
             block_solvers: HashMap::default(),
+            bigint_solver: BigIntSolver::default(),
+            opcode_indices: (0..opcodes.len()).collect(),
             opcodes,
             witness_map: initial_witness,
             pending_foreign_calls: Vec::new(),
+            pending_black_box_calls: Vec::new(),
+            black_box_call_results: HashMap::default(),
+            black_box_cache: None,
+            paused_brillig_vms: HashMap::default(),
+            brillig_solutions: Vec::new(),
+            foreign_call_transcripts: HashMap::default(),
+            hint_checker: None,
+            implicit_zeros: BTreeSet::new(),
+            applied_implicit_zeros: BTreeSet::new(),
+            solved_opcode_indices: Vec::new(),
+            witness_overwrites: Vec::new(),
+            trace: None,
+            secret_auditor: None,
+            memory_traced: false,
+            delta_resolver: None,
+            inactive_phases: Vec::new(),
+            integer_expressions: IntegerExpressions::default(),
         }
     }
 
+    /// Enables checking the values assigned by each [directive][Opcode::Directive] and [Brillig][Opcode::Brillig]
+    /// opcode against all arithmetic constraints referencing them as soon as the opcode is solved.
+    ///
+    /// A hint assigning wrong values then fails with [`OpcodeResolutionError::UnsatisfiedHintOutputs`], locating both
+    /// the hint and the constraint, rather than with an unsatisfied constraint later on. This has a cost and is meant
+    /// for debugging circuits.
+    pub fn with_hint_checks(mut self) -> Self {
+        self.hint_checker = Some(HintChecker::new(&self.opcodes, &self.opcode_indices));
+        self
+    }
+
+    /// Treats `witnesses` as implicitly zero, such as the optional inputs of a circuit which the caller left out.
+    ///
+    /// Once the remaining opcodes cannot be solved, any of these witnesses which they reference and which have
+    /// not been assigned are assigned zero, and solving resumes. The witnesses assigned this way are
+    /// returned by [`ACVM::applied_implicit_zeros`].
+    pub fn with_implicit_zeros(mut self, witnesses: impl IntoIterator<Item = Witness>) -> Self {
+        self.implicit_zeros.extend(witnesses);
+        self
+    }
+
+    /// Schedules the opcodes which remain to be solved following `plan`, recorded by [`ACVM::resolution_plan`]
+    /// once the same circuit was solved, and prepares the memory blocks it records.
+    ///
+    /// Opcodes are still solved to the same values whatever the plan, so a plan recorded with other inputs
+    /// only costs additional passes over the opcodes should these solve in a different order.
+    pub fn with_resolution_plan(mut self, plan: &ResolutionPlan) -> Self {
+        let opcodes = std::mem::take(&mut self.opcodes);
+        let opcode_indices = std::mem::take(&mut self.opcode_indices);
+        (self.opcodes, self.opcode_indices) = plan.schedule(opcodes, opcode_indices);
+        for (&block_id, &len) in plan.block_sizes() {
+            let solver = self
+                .block_solvers
+                .entry(block_id)
+                .or_insert_with(|| BlockSolver::with_capacity(len));
+            if self.memory_traced {
+                solver.trace_accesses();
+            }
+        }
+        self
+    }
+
+    /// Returns the order in which opcodes have been solved so far, along with the size of each memory block.
+    ///
+    /// Once [`ACVM::solve`] has returned [`PartialWitnessGeneratorStatus::Solved`], the plan can be reused through
+    /// [`ACVM::with_resolution_plan`] to solve the same circuit with other inputs without searching for this order again.
+    pub fn resolution_plan(&self) -> ResolutionPlan {
+        let block_sizes: BTreeMap<BlockId, usize> =
+            self.block_solvers.iter().map(|(&block_id, solver)| (block_id, solver.len())).collect();
+        ResolutionPlan::new(self.solved_opcode_indices.clone(), block_sizes)
+    }
+
+    /// Checks that every foreign call made by the Brillig opcodes to solve is declared by `registry`, with
+    /// the inputs and outputs of its signature, so that a call the host cannot resolve is reported up front
+    /// rather than once it is made.
+    pub fn with_foreign_call_registry(
+        self,
+        registry: &ForeignCallRegistry,
+    ) -> Result<Self, ForeignCallSignatureError> {
+        registry.validate_indexed(self.opcode_indices.iter().copied().zip(&self.opcodes))?;
+        Ok(self)
+    }
+
+    /// Solves black box function calls through `cache`, assigning the cached outputs of a call applying the same
+    /// function to the same input values rather than solving it, and caching the outputs of the calls it solves.
+    ///
+    /// Clones of a cache share its entries, so passing a clone of the same cache to several ACVMs shares the
+    /// outputs of the calls between their solves.
+    pub fn with_black_box_cache(mut self, cache: BlackBoxCache) -> Self {
+        self.black_box_cache = Some(cache);
+        self
+    }
+
+    /// Checks the arithmetic opcodes over range constrained witnesses with integer rather than field arithmetic
+    /// once all of their witnesses are known, using `expressions` built from the opcodes of this circuit.
+    ///
+    /// The result of solving is the same either way, as is the error should the opcode not be satisfied.
+    pub fn with_integer_expressions(mut self, expressions: IntegerExpressions) -> Self {
+        self.integer_expressions = expressions;
+        self
+    }
+
+    /// Groups the opcodes to solve into `phases`, as found in [`Circuit::phases`][acir::circuit::Circuit::phases],
+    /// holding back the opcodes of each phase until it is [activated][ACVM::activate_phase].
+    ///
+    /// [`ACVM::solve`] then returns [`PartialWitnessGeneratorStatus::Solved`] once the opcodes of the active phases
+    /// and those belonging to no phase are solved. This allows committing to part of the witness before activating
+    /// a phase which obtains a challenge, such as through a foreign call, from which the rest is derived.
+    /// An opcode belonging to several phases is held back until the first of them is activated.
+    pub fn with_phases(mut self, phases: &[Phase]) -> Self {
+        let mut phase_opcodes = vec![Vec::new(); phases.len()];
+        let opcodes = std::mem::take(&mut self.opcodes);
+        let opcode_indices = std::mem::take(&mut self.opcode_indices);
+        for (opcode, index) in opcodes.into_iter().zip(opcode_indices) {
+            match phases.iter().position(|phase| phase.opcodes.contains(&index)) {
+                Some(phase) => phase_opcodes[phase].push((index, opcode)),
+                None => {
+                    self.opcodes.push(opcode);
+                    self.opcode_indices.push(index);
+                }
+            }
+        }
+        self.inactive_phases =
+            phases.iter().map(|phase| phase.name.clone()).zip(phase_opcodes).collect();
+        self
+    }
+
+    /// Activates the phase named `name`, so that its opcodes are solved by the next calls to [`ACVM::solve`].
+    pub fn activate_phase(&mut self, name: &str) -> Result<(), UnknownPhaseError> {
+        let position = self
+            .inactive_phases
+            .iter()
+            .position(|(phase, _)| phase == name)
+            .ok_or_else(|| UnknownPhaseError(name.to_owned()))?;
+        let (_, opcodes) = self.inactive_phases.remove(position);
+        for (index, opcode) in opcodes {
+            self.opcodes.push(opcode);
+            self.opcode_indices.push(index);
+        }
+        Ok(())
+    }
+
+    /// Returns the names of the [phases][ACVM::with_phases] which have not yet been activated, in order.
+    pub fn inactive_phases(&self) -> Vec<&str> {
+        self.inactive_phases.iter().map(|(name, _)| name.as_str()).collect()
+    }
+
+    /// Rejects the opcodes to solve should any of them use a legacy construct, such as a directive, along with
+    /// what replaces it, so that artifacts compiled for the Brillig based flow can be enforced.
+    pub fn with_strict_mode(self) -> Result<Self, DeprecatedOpcodeError> {
+        check_deprecated_opcodes_indexed(self.opcode_indices.iter().copied().zip(&self.opcodes))?;
+        Ok(self)
+    }
+
+    /// Enables tracing the time spent attempting each opcode and waiting on each foreign call, returned by
+    /// [`ACVM::solve_trace`].
+    ///
+    /// This has a cost and is meant for profiling witness generation.
+    pub fn with_solve_trace(mut self) -> Self {
+        self.trace = Some(SolveTrace::new());
+        self
+    }
+
+    /// Returns the timeline of the solver so far, if [tracing][ACVM::with_solve_trace] is enabled.
+    pub fn solve_trace(&self) -> Option<&SolveTrace> {
+        self.trace.as_ref()
+    }
+
+    /// Audits the execution of Brillig opcodes for behavior observable by the host which depends on `secrets`,
+    /// reporting each opcode which may leak them through [`ACVM::secret_audit_findings`].
+    ///
+    /// An opcode leaks a secret when it jumps on a value derived from it, accesses memory at an address derived
+    /// from it, or passes values derived from it to a foreign call, including as the length of an input. Secrets are
+    /// tracked within each Brillig opcode, so the witnesses which other opcodes derive from secrets should be
+    /// designated as secrets too. This has a cost and is meant for reviewing hints.
+    pub fn with_secret_audit(mut self, secrets: impl IntoIterator<Item = Witness>) -> Self {
+        self.secret_auditor = Some(SecretAuditor::new(secrets.into_iter().collect()));
+        self
+    }
+
+    /// Returns the possible leaks of secrets found so far by the [secret audit][ACVM::with_secret_audit],
+    /// each reported once, in the order they were found.
+    pub fn secret_audit_findings(&self) -> &[SecretAuditFinding] {
+        self.secret_auditor.as_ref().map_or(&[], SecretAuditor::findings)
+    }
+
+    /// Enables tracing the accesses made to each memory block, with the values read and written,
+    /// returned by [`ACVM::memory_traces`].
+    pub fn with_memory_traces(mut self) -> Self {
+        self.memory_traced = true;
+        for solver in self.block_solvers.values_mut() {
+            solver.trace_accesses();
+        }
+        self
+    }
+
+    /// Returns the accesses made so far to each memory block, if [memory tracing][ACVM::with_memory_traces] is enabled.
+    ///
+    /// Once [`ACVM::solve`] has returned [`PartialWitnessGeneratorStatus::Solved`], these are the final accesses
+    /// from which the tables of lookup based memory checks can be built.
+    pub fn memory_traces(&self) -> BTreeMap<BlockId, &MemoryTrace> {
+        self.block_solvers
+            .iter()
+            .filter_map(|(&block_id, solver)| solver.trace().map(|trace| (block_id, trace)))
+            .collect()
+    }
+
+    /// Enables solving the circuit again for changed inputs through [`ACVM::resolve_delta`], the inputs
+    /// being the witnesses assigned when this is called.
+    ///
+    /// This must be called before solving, and keeps a copy of the opcodes along with which inputs influence them.
+    pub fn with_delta_resolution(mut self) -> Self {
+        let mut opcodes: Vec<(usize, Opcode)> =
+            self.opcode_indices.iter().copied().zip(self.opcodes.iter().cloned()).collect();
+        opcodes.sort_by_key(|(index, _)| *index);
+        let opcodes = opcodes.into_iter().map(|(_, opcode)| opcode).collect();
+        let inputs = self.witness_map.iter().map(|(witness, _)| witness).collect();
+        self.delta_resolver = Some(DeltaResolver::new(opcodes, inputs));
+        self
+    }
+
+    /// Assigns new values to some of the inputs of a solved circuit and solves it again, invalidating only
+    /// the witnesses which these inputs may influence and solving again only the opcodes referencing them.
+    ///
+    /// The witnesses which cannot be solved from the inputs, such as those assigned by foreign calls, are always
+    /// solved again. Execution halts for the same reasons as [`ACVM::solve`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if [delta resolution][ACVM::with_delta_resolution] is not enabled, if a foreign call or black box
+    /// call is pending, or if one of `changed_inputs` is not an input of the circuit.
+    pub fn resolve_delta(
+        &mut self,
+        changed_inputs: WitnessMap,
+    ) -> Result<PartialWitnessGeneratorStatus, OpcodeResolutionError> {
+        let resolver = self.delta_resolver.as_ref().expect("delta resolution is not enabled");
+        if !self.pending_foreign_calls.is_empty() || !self.pending_black_box_calls.is_empty() {
+            panic!("ACVM cannot resolve a delta with pending calls");
+        }
+        let changed: BTreeSet<Witness> =
+            changed_inputs.iter().map(|(witness, _)| witness).collect();
+        if let Some(witness) = changed.iter().find(|witness| !resolver.is_input(witness)) {
+            panic!("witness {} is not an input of the circuit", witness.0);
+        }
+        let delta = resolver.delta(&changed);
+
+        // Invalidate the affected witnesses and assign the new inputs.
+        self.witness_map.retain(|witness, _| !delta.witnesses.contains(&witness));
+        self.applied_implicit_zeros.retain(|witness| !delta.witnesses.contains(witness));
+        for (witness, value) in changed_inputs {
+            self.witness_map.insert(witness, value);
+        }
+
+        // Memory blocks are rebuilt from their initialization, as all of their opcodes are solved again.
+        let resolved_indices: HashSet<usize> = delta.opcode_indices.iter().copied().collect();
+        for &index in &delta.opcode_indices {
+            if let Some(block_id) = resolver.opcode(index).memory_block_id() {
+                self.block_solvers.remove(&block_id);
+            }
+        }
+        self.solved_opcode_indices.retain(|index| !resolved_indices.contains(index));
+        self.brillig_solutions.retain(|solution| !resolved_indices.contains(&solution.acir_index));
+
+        // Schedule the affected opcodes ahead of any which remain unsolved.
+        let opcodes: Vec<Opcode> =
+            delta.opcode_indices.iter().map(|&index| resolver.opcode(index).clone()).collect();
+        self.opcodes.splice(0..0, opcodes);
+        self.opcode_indices.splice(0..0, delta.opcode_indices);
+
+        self.solve()
+    }
+
+    /// Returns the [implicitly zero][ACVM::with_implicit_zeros] witnesses which have been assigned zero
+    /// as they were left unassigned.
+    pub fn applied_implicit_zeros(&self) -> &BTreeSet<Witness> {
+        &self.applied_implicit_zeros
+    }
+
+    /// Returns the backend computing the black box functions which the ACVM cannot solve natively.
+    pub fn backend(&self) -> &B {
+        &self.backend
+    }
+
     /// Returns a reference to the current state of the ACVM's [`WitnessMap`].
     ///
     /// Once execution has completed, the witness map can be extracted using [`ACVM::finalize`]
@@ -117,16 +601,61 @@ impl<B: PartialWitnessGenerator> ACVM<B> {
         &self.witness_map
     }
 
+    /// Returns how the outputs of each solved Brillig opcode were assigned, in the order they were solved.
+    ///
+    /// This allows checking that the opcodes following a Brillig opcode constrain all of its outputs.
+    pub fn brillig_solutions(&self) -> &[BrilligSolution] {
+        &self.brillig_solutions
+    }
+
+    /// Assigns `value` to `witness` whether or not it has already been assigned, returning its previous value.
+    ///
+    /// This is meant for exploring how a circuit behaves with other values and for patching trusted values,
+    /// and is recorded in the journal returned by [`ACVM::witness_overwrites`]. Rather than failing, opcodes
+    /// which turn out not to be satisfied by an overwritten value are recorded as conflicting with it in the
+    /// journal and are not solved any further. With [hint checks][ACVM::with_hint_checks] enabled, the
+    /// constraints referencing the witness which the new value does not satisfy are recorded immediately.
+    pub fn overwrite_witness(
+        &mut self,
+        witness: Witness,
+        value: FieldElement,
+    ) -> Option<FieldElement> {
+        let previous_value = self.witness_map.insert(witness, value);
+        let conflicts = match &self.hint_checker {
+            Some(checker) => checker.unsatisfied_constraints([witness], &self.witness_map),
+            None => Vec::new(),
+        };
+        self.witness_overwrites.push(WitnessOverwrite {
+            witness,
+            previous_value,
+            value,
+            conflicts,
+        });
+        previous_value
+    }
+
+    /// Returns the journal of the witnesses overwritten through [`ACVM::overwrite_witness`], in order.
+    pub fn witness_overwrites(&self) -> &[WitnessOverwrite] {
+        &self.witness_overwrites
+    }
+
     /// Returns a slice containing the opcodes which remain to be solved.
     ///
-    /// Note: this doesn't include any opcodes which are waiting on a pending foreign call.
+    /// Note: this doesn't include any opcodes which are waiting on a pending foreign call or black box call.
     pub fn unresolved_opcodes(&self) -> &[Opcode] {
         &self.opcodes
     }
 
     /// Finalize the ACVM execution, returning the resulting [`WitnessMap`].
+    ///
+    /// Panics unless every opcode has been solved, including those of the [phases][ACVM::with_phases] which have
+    /// not been activated.
     pub fn finalize(self) -> WitnessMap {
-        if self.opcodes.is_empty() || self.get_pending_foreign_call().is_some() {
+        if !self.opcodes.is_empty()
+            || !self.inactive_phases.is_empty()
+            || self.get_pending_foreign_call().is_some()
+            || self.get_pending_black_box_call().is_some()
+        {
             panic!("ACVM is not ready to be finalized");
         }
         self.witness_map
@@ -134,17 +663,49 @@ impl<B: PartialWitnessGenerator> ACVM<B> {
 
     /// Return a reference to the arguments for the next pending foreign call, if one exists.
     pub fn get_pending_foreign_call(&self) -> Option<&ForeignCallWaitInfo> {
-        self.pending_foreign_calls.first().map(|foreign_call| &foreign_call.foreign_call_wait_info)
+        self.pending_foreign_calls
+            .first()
+            .map(|(_, foreign_call)| &foreign_call.foreign_call_wait_info)
     }
 
     /// Resolves a pending foreign call using a result calculated outside of the ACVM.
     pub fn resolve_pending_foreign_call(&mut self, foreign_call_result: ForeignCallResult) {
-        // Remove the first foreign call and inject the result to create a new opcode.
-        let foreign_call = self.pending_foreign_calls.remove(0);
-        let resolved_brillig = foreign_call.resolve(foreign_call_result);
+        // Remove the first foreign call and inject the result into both its opcode and its paused VM.
+        let (index, UnresolvedBrilligCall { mut brillig, mut vm, foreign_call_wait_info }) =
+            self.pending_foreign_calls.remove(0);
+        brillig.foreign_call_results.push(foreign_call_result.clone());
+        vm.resolve_foreign_call(foreign_call_result.clone());
+        if let Some(trace) = &mut self.trace {
+            trace
+                .record_foreign_call(&foreign_call_wait_info.function, OpcodeLocation::Acir(index));
+        }
+        self.foreign_call_transcripts.entry(index).or_default().push(ResolvedForeignCall {
+            call: foreign_call_wait_info,
+            result: foreign_call_result,
+        });
+        self.paused_brillig_vms.insert(index, vm);
+
+        // Mark this opcode to be executed next.
+        self.opcodes.insert(0, Opcode::Brillig(brillig));
+        self.opcode_indices.insert(0, index);
+    }
+
+    /// Returns the next black box call deferred by the backend, if one exists.
+    pub fn get_pending_black_box_call(&self) -> Option<&BlackBoxCall> {
+        self.pending_black_box_calls.first().map(|(_, _, call)| call)
+    }
+
+    /// Resolves the pending black box call with the values of the outputs of its opcode, in order,
+    /// such as those returned by [`BlackBoxCall::compute`].
+    ///
+    /// The values are assigned once the ACVM is restarted, failing should there not be one for each output.
+    pub fn resolve_pending_black_box_call(&mut self, outputs: Vec<FieldElement>) {
+        let (index, bb_func, _) = self.pending_black_box_calls.remove(0);
+        self.black_box_call_results.insert(index, outputs);
 
         // Mark this opcode to be executed next.
-        self.opcodes.insert(0, Opcode::Brillig(resolved_brillig));
+        self.opcodes.insert(0, Opcode::BlackBoxFuncCall(bb_func));
+        self.opcode_indices.insert(0, index);
     }
 
     /// Executes the ACVM's circuit until execution halts.
@@ -153,53 +714,197 @@ impl<B: PartialWitnessGenerator> ACVM<B> {
     /// 1. All opcodes have been executed successfully.
     /// 2. The circuit has been found to be unsatisfiable.
     /// 2. A Brillig [foreign call][`UnresolvedBrilligCall`] has been encountered and must be resolved.
+    /// 3. A [black box call][`BlackBoxCall`] has been deferred by the backend and must be resolved.
     pub fn solve(&mut self) -> Result<PartialWitnessGeneratorStatus, OpcodeResolutionError> {
         // TODO: Prevent execution with outstanding foreign calls?
+        if let Some(trace) = &mut self.trace {
+            trace.resume();
+        }
         let mut unresolved_opcodes: Vec<Opcode> = Vec::new();
+        let mut unresolved_opcode_indices: Vec<usize> = Vec::new();
         while !self.opcodes.is_empty() {
             unresolved_opcodes.clear();
+            unresolved_opcode_indices.clear();
             let mut stalled = true;
             let mut opcode_not_solvable = None;
-            for opcode in &self.opcodes {
+            // Rather than cloning opcodes which remain unsolved, we record which ones to keep
+            // and move them out of `self.opcodes` once the pass over them has completed.
+            let mut keep_opcode = Vec::with_capacity(self.opcodes.len());
+            let mut foreign_call_waits = Vec::new();
+            let mut black_box_waits = Vec::new();
+            // Operations on a memory block take effect in order, so none of them are attempted
+            // once one has stalled until the next pass.
+            let mut stalled_blocks = HashSet::new();
+            // Brillig opcodes which can be executed at the start of the pass are executed concurrently,
+            // their outputs then being assigned in order as they are reached.
+            #[cfg(feature = "rayon")]
+            let mut executed_brillig_vms = BrilligSolver::execute_concurrently(
+                &self.witness_map,
+                &self.opcodes,
+                &self.opcode_indices,
+                &self.paused_brillig_vms,
+                self.secret_auditor.as_ref(),
+            );
+            #[cfg(not(feature = "rayon"))]
+            let mut executed_brillig_vms: HashMap<usize, (VM, _)> = HashMap::new();
+            for (position, (opcode, &index)) in
+                self.opcodes.iter().zip(&self.opcode_indices).enumerate()
+            {
+                let block_id = opcode.memory_block_id();
+                if matches!(block_id, Some(block_id) if stalled_blocks.contains(&block_id)) {
+                    keep_opcode.push(true);
+                    continue;
+                }
+                let started = self.trace.as_ref().map(|_| Instant::now());
                 let resolution = match opcode {
-                    Opcode::Arithmetic(expr) => {
-                        ArithmeticSolver::solve(&mut self.witness_map, expr)
-                    }
+                    Opcode::AssertZero(expr) => ArithmeticSolver::solve(
+                        &mut self.witness_map,
+                        expr,
+                        self.integer_expressions.get(index),
+                    ),
                     Opcode::BlackBoxFuncCall(bb_func) => {
-                        blackbox::solve(&self.backend, &mut self.witness_map, bb_func)
+                        let cache_key = self
+                            .black_box_cache
+                            .as_ref()
+                            .and_then(|_| BlackBoxCache::key(bb_func, &self.witness_map));
+                        let outputs = self
+                            .black_box_call_results
+                            .remove(&index)
+                            .or_else(|| self.black_box_cache.as_ref()?.get(cache_key.as_ref()?));
+                        let resolution = match outputs {
+                            Some(outputs) => blackbox::assign_outputs(
+                                &mut self.witness_map,
+                                bb_func.get_black_box_func(),
+                                &bb_func.get_outputs_vec(),
+                                outputs,
+                            ),
+                            None => blackbox::solve(
+                                &self.backend,
+                                &mut self.witness_map,
+                                &mut self.bigint_solver,
+                                bb_func,
+                            ),
+                        };
+                        if let (Some(cache), Some(key), Ok(OpcodeResolution::Solved)) =
+                            (&self.black_box_cache, cache_key, &resolution)
+                        {
+                            cache.insert(key, bb_func, &self.witness_map);
+                        }
+                        resolution
                     }
                     Opcode::Directive(directive) => {
-                        solve_directives(&mut self.witness_map, directive)
+                        solve_directives(&mut self.witness_map, directive).and_then(|resolution| {
+                            if let (Some(checker), OpcodeResolution::Solved) =
+                                (&self.hint_checker, &resolution)
+                            {
+                                checker.check(index, directive.outputs(), &self.witness_map)?;
+                            }
+                            Ok(resolution)
+                        })
                     }
-                    Opcode::Block(block) | Opcode::ROM(block) | Opcode::RAM(block) => {
-                        let solver = self.block_solvers.entry(block.id).or_default();
-                        solver.solve(&mut self.witness_map, &block.trace)
+                    Opcode::MemoryInit { block_id, init } => {
+                        let solver = self
+                            .block_solvers
+                            .entry(*block_id)
+                            .or_insert_with(|| BlockSolver::new(self.memory_traced));
+                        solver.init(&self.witness_map, init)
+                    }
+                    Opcode::MemoryOp { block_id, op } => {
+                        let solver = self
+                            .block_solvers
+                            .entry(*block_id)
+                            .or_insert_with(|| BlockSolver::new(self.memory_traced));
+                        solver.solve_memory_op(&mut self.witness_map, op)
                     }
                     Opcode::Brillig(brillig) => {
-                        BrilligSolver::solve(&mut self.witness_map, brillig)
+                        let mut resolution = match executed_brillig_vms.remove(&index) {
+                            Some((vm, vm_status)) => BrilligSolver::finish(
+                                &mut self.witness_map,
+                                brillig,
+                                index,
+                                vm,
+                                vm_status,
+                                self.secret_auditor.as_mut(),
+                            ),
+                            None => {
+                                let paused_vm = self.paused_brillig_vms.remove(&index);
+                                BrilligSolver::solve(
+                                    &mut self.witness_map,
+                                    brillig,
+                                    index,
+                                    paused_vm,
+                                    self.secret_auditor.as_mut(),
+                                )
+                            }
+                        };
+                        match &mut resolution {
+                            Ok(BrilligResolution::Solved(_)) => {
+                                self.foreign_call_transcripts.remove(&index);
+                            }
+                            Err(OpcodeResolutionError::BrilligFunctionFailed {
+                                foreign_calls,
+                                ..
+                            }) => {
+                                *foreign_calls = self
+                                    .foreign_call_transcripts
+                                    .remove(&index)
+                                    .unwrap_or_default();
+                            }
+                            _ => (),
+                        }
+                        resolution.and_then(|resolution| match resolution {
+                            BrilligResolution::Solved(solution) => {
+                                if let Some(checker) = &self.hint_checker {
+                                    checker.check(
+                                        index,
+                                        solution.assigned_witnesses(),
+                                        &self.witness_map,
+                                    )?;
+                                }
+                                self.brillig_solutions.push(solution);
+                                Ok(OpcodeResolution::Solved)
+                            }
+                            BrilligResolution::ForeignCallWait(wait_info, vm) => {
+                                self.paused_brillig_vms.insert(index, *vm);
+                                Ok(OpcodeResolution::InProgressBrillig(wait_info))
+                            }
+                            resolution => Ok(resolution.into()),
+                        })
                     }
                 };
+                if let (Some(trace), Some(started)) = (&mut self.trace, started) {
+                    let outcome = TraceOutcome::of(&resolution);
+                    trace.record_opcode(
+                        opcode.name(),
+                        OpcodeLocation::Acir(index),
+                        outcome,
+                        started,
+                    );
+                }
                 match resolution {
                     Ok(OpcodeResolution::Solved) => {
                         stalled = false;
+                        keep_opcode.push(false);
+                        self.solved_opcode_indices.push(index);
                     }
                     Ok(OpcodeResolution::InProgress) => {
                         stalled = false;
-                        unresolved_opcodes.push(opcode.clone());
+                        keep_opcode.push(true);
                     }
                     Ok(OpcodeResolution::InProgressBrillig(oracle_wait_info)) => {
                         stalled = false;
                         // InProgressBrillig Oracles must be externally re-solved
-                        let brillig = match opcode {
-                            Opcode::Brillig(brillig) => brillig.clone(),
-                            _ => unreachable!("Brillig resolution for non brillig opcode"),
-                        };
-                        self.pending_foreign_calls.push(UnresolvedBrilligCall {
-                            brillig,
-                            foreign_call_wait_info: oracle_wait_info,
-                        })
+                        keep_opcode.push(false);
+                        foreign_call_waits.push((position, oracle_wait_info));
+                    }
+                    Ok(OpcodeResolution::InProgressBlackBox(call)) => {
+                        stalled = false;
+                        // Deferred black box calls must be externally resolved
+                        keep_opcode.push(false);
+                        black_box_waits.push((position, call));
                     }
                     Ok(OpcodeResolution::Stalled(not_solvable)) => {
+                        stalled_blocks.extend(block_id);
                         if opcode_not_solvable.is_none() {
                             // we keep track of the first unsolvable opcode
                             opcode_not_solvable = Some(not_solvable);
@@ -207,33 +912,146 @@ impl<B: PartialWitnessGenerator> ACVM<B> {
                         // We push those opcodes not solvable to the back as
                         // it could be because the opcodes are out of order, i.e. this assignment
                         // relies on a later opcodes' results
-                        unresolved_opcodes.push(opcode.clone());
+                        keep_opcode.push(true);
                     }
                     Err(OpcodeResolutionError::OpcodeNotSolvable(_)) => {
                         unreachable!("ICE - Result should have been converted to GateResolution")
                     }
-                    Err(err) => return Err(err),
+                    Err(
+                        OpcodeResolutionError::UnsatisfiedConstrain { .. }
+                        | OpcodeResolutionError::RangeCheckFailed { .. },
+                    ) if record_conflict(
+                        &mut self.witness_overwrites,
+                        opcode,
+                        OpcodeLocation::Acir(index),
+                    ) =>
+                    {
+                        // The opcode conflicts with an overwritten witness, which was recorded instead of failing.
+                        stalled = false;
+                        keep_opcode.push(false);
+                    }
+                    Err(err) => return Err(err.resolve_location(OpcodeLocation::Acir(index))),
                 }
             }
 
             // Before potentially ending execution, we must save the list of opcodes which remain to be solved.
+            let mut foreign_call_waits = foreign_call_waits.into_iter().peekable();
+            let mut black_box_waits = black_box_waits.into_iter().peekable();
+            let opcodes = self.opcodes.drain(..).zip(self.opcode_indices.drain(..));
+            for (position, (opcode, index)) in opcodes.enumerate() {
+                if keep_opcode[position] {
+                    unresolved_opcodes.push(opcode);
+                    unresolved_opcode_indices.push(index);
+                } else if let Some((_, foreign_call_wait_info)) =
+                    foreign_call_waits.next_if(|(wait_position, _)| *wait_position == position)
+                {
+                    let brillig = match opcode {
+                        Opcode::Brillig(brillig) => brillig,
+                        _ => unreachable!("Brillig resolution for non brillig opcode"),
+                    };
+                    let vm = self.paused_brillig_vms.remove(&index).expect(
+                        "infallible: the VM of a Brillig opcode is paused on a foreign call",
+                    );
+                    self.pending_foreign_calls.push((
+                        index,
+                        UnresolvedBrilligCall { brillig, foreign_call_wait_info, vm },
+                    ));
+                } else if let Some((_, call)) =
+                    black_box_waits.next_if(|(wait_position, _)| *wait_position == position)
+                {
+                    let bb_func = match opcode {
+                        Opcode::BlackBoxFuncCall(bb_func) => bb_func,
+                        _ => unreachable!("black box call for non black box opcode"),
+                    };
+                    self.pending_black_box_calls.push((index, bb_func, call));
+                }
+            }
             std::mem::swap(&mut self.opcodes, &mut unresolved_opcodes);
+            std::mem::swap(&mut self.opcode_indices, &mut unresolved_opcode_indices);
 
             // We have oracles that must be externally resolved
             if self.get_pending_foreign_call().is_some() {
+                if let Some(trace) = &mut self.trace {
+                    trace.wait_on_foreign_calls();
+                }
                 return Ok(PartialWitnessGeneratorStatus::RequiresForeignCall);
             }
 
+            // We have black box calls that must be externally resolved
+            if self.get_pending_black_box_call().is_some() {
+                return Ok(PartialWitnessGeneratorStatus::RequiresBlackBoxCall);
+            }
+
             // We are stalled because of an opcode being bad
             if stalled && !self.opcodes.is_empty() {
+                if self.apply_implicit_zeros() {
+                    continue;
+                }
                 return Err(OpcodeResolutionError::OpcodeNotSolvable(
                     opcode_not_solvable
                         .expect("infallible: cannot be stalled and None at the same time"),
                 ));
             }
         }
+        // The remaining opcodes may all be waiting on deferred black box calls.
+        if self.get_pending_black_box_call().is_some() {
+            return Ok(PartialWitnessGeneratorStatus::RequiresBlackBoxCall);
+        }
         Ok(PartialWitnessGeneratorStatus::Solved)
     }
+
+    /// Assigns zero to the implicitly zero witnesses which are referenced by the remaining opcodes
+    /// but have not been assigned, returning whether any were.
+    fn apply_implicit_zeros(&mut self) -> bool {
+        if self.implicit_zeros.is_empty() {
+            return false;
+        }
+        let mut referenced = WitnessCollector::default();
+        for opcode in &self.opcodes {
+            referenced.visit_opcode(opcode);
+        }
+        let mut applied = false;
+        for witness in referenced.0.intersection(&self.implicit_zeros) {
+            if !self.witness_map.contains_key(witness) {
+                self.witness_map.insert(*witness, FieldElement::zero());
+                self.applied_implicit_zeros.insert(*witness);
+                applied = true;
+            }
+        }
+        applied
+    }
+}
+
+/// Records `location`, that of an unsatisfied `opcode`, as a conflict of the latest overwrite of each witness
+/// referenced by the opcode, returning whether the opcode references any overwritten witness.
+fn record_conflict(
+    overwrites: &mut [WitnessOverwrite],
+    opcode: &Opcode,
+    location: OpcodeLocation,
+) -> bool {
+    if overwrites.is_empty() {
+        return false;
+    }
+    let mut referenced = WitnessCollector::default();
+    referenced.visit_opcode(opcode);
+    let mut conflicting = false;
+    for overwrite in overwrites.iter_mut().rev() {
+        if referenced.0.remove(&overwrite.witness) {
+            overwrite.conflicts.push(location);
+            conflicting = true;
+        }
+    }
+    conflicting
+}
+
+/// Collects every witness mentioned by the opcodes it visits.
+#[derive(Default)]
+struct WitnessCollector(BTreeSet<Witness>);
+
+impl Visitor for WitnessCollector {
+    fn visit_witness(&mut self, witness: &Witness) {
+        self.0.insert(*witness);
+    }
 }
 
 // Returns the concrete value for a particular witness
@@ -249,6 +1067,45 @@ pub fn witness_to_value(
     }
 }
 
+/// Returns the `ceil(num_bits / 8)` bytes of the value assigned to `witness`, in the given order.
+///
+/// This is the byte decomposition which the fallback implementations of the stdlib constrain, so every
+/// black box solver reading bytes from the witness map goes through it. An error is returned on behalf of `func`
+/// if the value does not fit within `num_bits` bits, rather than silently truncating it.
+pub fn witness_to_bytes(
+    initial_witness: &WitnessMap,
+    witness: Witness,
+    num_bits: u32,
+    endianness: Endianness,
+    func: BlackBoxFunc,
+) -> Result<Vec<u8>, OpcodeResolutionError> {
+    let value = witness_to_value(initial_witness, witness)?;
+    if value.num_bits() > num_bits {
+        return Err(OpcodeResolutionError::BlackBoxFunctionFailed(
+            func,
+            format!("input {} does not fit within {num_bits} bits", value.to_hex()),
+        ));
+    }
+
+    let num_bytes = round_to_nearest_byte(num_bits) as usize;
+    let bytes = match endianness {
+        Endianness::Big => value.to_be_bytes_fixed(num_bytes),
+        Endianness::Little => value.to_le_bytes_fixed(num_bytes),
+    }
+    .expect("value fits within its number of bits");
+    Ok(bytes)
+}
+
+/// Renders `value` as an unsigned decimal integer, rather than as the negative integer its display may use.
+fn decimal_string(value: &FieldElement) -> String {
+    BigUint::from_bytes_be(&value.to_be_bytes()).to_string()
+}
+
+/// Renders `value` as a `0x` prefixed hex integer without leading zeroes.
+fn hex_string(value: &FieldElement) -> String {
+    format!("{:#x}", BigUint::from_bytes_be(&value.to_be_bytes()))
+}
+
 // TODO: There is an issue open to decide on whether we need to get values from Expressions
 // TODO versus just getting values from Witness
 pub fn get_value(
@@ -283,7 +1140,9 @@ pub fn insert_value(
     };
 
     if old_value != value_to_insert {
-        return Err(OpcodeResolutionError::UnsatisfiedConstrain);
+        return Err(OpcodeResolutionError::UnsatisfiedConstrain {
+            opcode_location: ErrorLocation::Unresolved,
+        });
     }
 
     Ok(())
@@ -300,6 +1159,9 @@ pub struct UnresolvedBrilligCall {
     pub brillig: Brillig,
     /// Inputs for a pending foreign call required to restart bytecode processing.
     pub foreign_call_wait_info: brillig::ForeignCallWaitInfo,
+    /// The Brillig VM paused on the foreign call, which resumes from it once its result is provided
+    /// instead of re-executing the bytecode from the start.
+    pub vm: VM,
 }
 
 impl UnresolvedBrilligCall {
@@ -320,10 +1182,10 @@ impl UnresolvedBrilligCall {
 // Where we could deduce what opcodes were supported
 // by knowing the np complete language
 pub fn default_is_opcode_supported(language: Language) -> fn(&Opcode) -> bool {
-    // R1CS does not support any of the opcode except Arithmetic by default.
+    // R1CS does not support any of the opcode except AssertZero by default.
     // The compiler will replace those that it can -- ie range, xor, and
     fn r1cs_is_supported(opcode: &Opcode) -> bool {
-        matches!(opcode, Opcode::Arithmetic(_))
+        matches!(opcode, Opcode::AssertZero(_))
     }
 
     // PLONK supports most of the opcodes by default
@@ -331,7 +1193,8 @@ pub fn default_is_opcode_supported(language: Language) -> fn(&Opcode) -> bool {
     // attempt to transform into supported gates. If these are also not available
     // then a compiler error will be emitted.
     fn plonk_is_supported(opcode: &Opcode) -> bool {
-        !matches!(opcode, Opcode::Block(_))
+        // Memory operations must be known to be reads or writes.
+        !matches!(opcode, Opcode::MemoryOp { op, .. } if !op.operation.is_const())
     }
 
     match language {
@@ -0,0 +1,128 @@
+use std::collections::{BTreeMap, HashMap};
+
+use acir::circuit::{opcodes::BlockId, Opcode};
+
+/// The order in which the opcodes of a circuit were solved, along with the size of each of its memory blocks.
+///
+/// Solving a circuit whose opcodes are out of order takes several passes over the opcodes, each deferring
+/// the opcodes which depend on ones yet to be solved. A plan recorded by [`ACVM::resolution_plan`][super::ACVM::resolution_plan]
+/// once a circuit has been solved lets [`ACVM::with_resolution_plan`][super::ACVM::with_resolution_plan] schedule
+/// the opcodes of the same circuit so that they are solved in a single pass, whatever the inputs.
+///
+/// A plan only affects the order in which opcodes are attempted and never the values they are solved to.
+/// Opcodes missing from the plan are attempted after those within it, and the operations on each memory block
+/// are always attempted in their order within the circuit.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ResolutionPlan {
+    /// The index within the circuit of each opcode, in the order they were solved.
+    order: Vec<usize>,
+    /// The number of elements of each memory block.
+    block_sizes: BTreeMap<BlockId, usize>,
+}
+
+impl ResolutionPlan {
+    pub(super) fn new(order: Vec<usize>, block_sizes: BTreeMap<BlockId, usize>) -> Self {
+        ResolutionPlan { order, block_sizes }
+    }
+
+    /// Returns the index within the circuit of each opcode, in the order they were solved.
+    pub fn order(&self) -> &[usize] {
+        &self.order
+    }
+
+    /// Returns the number of elements of each memory block of the circuit.
+    pub fn block_sizes(&self) -> &BTreeMap<BlockId, usize> {
+        &self.block_sizes
+    }
+
+    /// Reorders `opcodes`, along with their index within the circuit in `opcode_indices`, following the plan.
+    pub(super) fn schedule(
+        &self,
+        opcodes: Vec<Opcode>,
+        opcode_indices: Vec<usize>,
+    ) -> (Vec<Opcode>, Vec<usize>) {
+        let ranks: HashMap<usize, usize> =
+            self.order.iter().enumerate().map(|(rank, &index)| (index, rank)).collect();
+        let mut positions: Vec<usize> = (0..opcodes.len()).collect();
+        positions.sort_by_key(|&position| {
+            ranks.get(&opcode_indices[position]).copied().unwrap_or(usize::MAX)
+        });
+
+        // Memory operations take effect in order, so those of each block are put back into their original
+        // order within the slots the plan gives them.
+        let mut block_slots: HashMap<BlockId, Vec<usize>> = HashMap::new();
+        for (slot, &position) in positions.iter().enumerate() {
+            if let Some(block_id) = opcodes[position].memory_block_id() {
+                block_slots.entry(block_id).or_default().push(slot);
+            }
+        }
+        for slots in block_slots.values() {
+            let mut block_positions: Vec<usize> =
+                slots.iter().map(|&slot| positions[slot]).collect();
+            block_positions.sort_unstable();
+            for (&slot, position) in slots.iter().zip(block_positions) {
+                positions[slot] = position;
+            }
+        }
+
+        let mut opcodes: Vec<Option<Opcode>> = opcodes.into_iter().map(Some).collect();
+        positions
+            .into_iter()
+            .map(|position| {
+                let opcode = opcodes[position].take().expect("each position is scheduled once");
+                (opcode, opcode_indices[position])
+            })
+            .unzip()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use acir::{
+        circuit::{
+            opcodes::{BlockId, MemOp},
+            Opcode,
+        },
+        native_types::{Expression, Witness},
+    };
+
+    use super::ResolutionPlan;
+
+    #[test]
+    fn keeps_memory_operations_in_order() {
+        let read = |index: u32| Opcode::MemoryOp {
+            block_id: BlockId(0),
+            op: MemOp {
+                operation: Expression::zero(),
+                index: Expression::zero(),
+                value: Witness(index).into(),
+            },
+        };
+        let opcodes = vec![
+            Opcode::MemoryInit { block_id: BlockId(0), init: vec![Witness(10)] },
+            read(1),
+            Opcode::AssertZero(Expression::from(Witness(2))),
+            read(3),
+        ];
+        let plan = ResolutionPlan::new(vec![2, 3, 1, 0], BTreeMap::new());
+
+        let (scheduled, indices) = plan.schedule(opcodes.clone(), vec![0, 1, 2, 3]);
+        assert_eq!(indices, vec![2, 0, 1, 3]);
+        assert_eq!(
+            scheduled,
+            vec![opcodes[2].clone(), opcodes[0].clone(), opcodes[1].clone(), opcodes[3].clone()]
+        );
+    }
+
+    #[test]
+    fn attempts_opcodes_missing_from_the_plan_last() {
+        let opcodes: Vec<Opcode> =
+            (0..4).map(|witness| Opcode::AssertZero(Witness(witness).into())).collect();
+        let plan = ResolutionPlan::new(vec![3, 1], BTreeMap::new());
+
+        let (_, indices) = plan.schedule(opcodes, vec![0, 1, 2, 3]);
+        assert_eq!(indices, vec![3, 1, 0, 2]);
+    }
+}
@@ -0,0 +1,93 @@
+use std::collections::BTreeSet;
+
+use acir::{
+    brillig_vm::{LeakKind, RegisterIndex, SecretAudit, VM},
+    circuit::{
+        brillig::{Brillig, BrilligInputs},
+        OpcodeLocation,
+    },
+    native_types::{Expression, Witness},
+};
+
+/// A possible leak of a secret by a Brillig opcode, found by [`ACVM::with_secret_audit`][super::ACVM::with_secret_audit].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SecretAuditFinding {
+    /// The location of the leaking opcode within the bytecode of its Brillig opcode.
+    pub location: OpcodeLocation,
+    pub kind: LeakKind,
+}
+
+impl std::fmt::Display for SecretAuditFinding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.location, self.kind)
+    }
+}
+
+/// Audits the execution of Brillig opcodes for leaks of the designated secret witnesses.
+pub(crate) struct SecretAuditor {
+    secrets: BTreeSet<Witness>,
+    findings: Vec<SecretAuditFinding>,
+}
+
+impl SecretAuditor {
+    pub(super) fn new(secrets: BTreeSet<Witness>) -> Self {
+        SecretAuditor { secrets, findings: Vec::new() }
+    }
+
+    pub(super) fn findings(&self) -> &[SecretAuditFinding] {
+        &self.findings
+    }
+
+    /// Marks the registers and memory which the inputs of `brillig` referencing secrets are loaded into.
+    ///
+    /// This follows the layout of [`BrilligSolver::setup`][super::brillig::BrilligSolver::setup]: each input is
+    /// loaded into the register of the same index, with arrays being laid out in memory one after the other.
+    pub(super) fn audit(&self, brillig: &Brillig) -> SecretAudit {
+        let mut registers = Vec::new();
+        let mut memory = Vec::new();
+        let mut memory_len = 0;
+        for (register, input) in brillig.inputs.iter().enumerate() {
+            match input {
+                BrilligInputs::Single(expr) => {
+                    if self.is_secret(expr) {
+                        registers.push(RegisterIndex::from(register));
+                    }
+                }
+                BrilligInputs::Array(exprs) => {
+                    for expr in exprs {
+                        if self.is_secret(expr) {
+                            memory.push(memory_len);
+                        }
+                        memory_len += 1;
+                    }
+                }
+            }
+        }
+        SecretAudit::new(registers, memory)
+    }
+
+    /// Records the leaks found by `vm` while executing the Brillig opcode at `acir_index`.
+    pub(super) fn record(&mut self, acir_index: usize, vm: &mut VM) {
+        for leak in vm.take_secret_leaks() {
+            let finding = SecretAuditFinding {
+                location: OpcodeLocation::Brillig {
+                    acir_index,
+                    brillig_index: leak.program_counter,
+                },
+                kind: leak.kind,
+            };
+            if !self.findings.contains(&finding) {
+                self.findings.push(finding);
+            }
+        }
+    }
+
+    fn is_secret(&self, expr: &Expression) -> bool {
+        let mut witnesses = expr
+            .mul_terms
+            .iter()
+            .flat_map(|(_, lhs, rhs)| [lhs, rhs])
+            .chain(expr.linear_combinations.iter().map(|(_, witness)| witness));
+        witnesses.any(|witness| self.secrets.contains(witness))
+    }
+}
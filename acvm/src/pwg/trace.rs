@@ -0,0 +1,190 @@
+use std::time::{Duration, Instant};
+
+use acir::circuit::OpcodeLocation;
+
+use super::{OpcodeResolution, OpcodeResolutionError};
+
+/// A timeline of the attempts of the ACVM at solving each opcode, and of the time it spent waiting on
+/// foreign calls to be resolved.
+///
+/// Tracing is enabled through [`ACVM::with_solve_trace`][super::ACVM::with_solve_trace]. Once enabled,
+/// every attempt at solving an opcode is timed, which has a cost and is meant for profiling. With the
+/// `chrome-trace` feature, the timeline can be exported with [`SolveTrace::to_chrome_trace`] and opened
+/// in `chrome://tracing` or in the Perfetto UI.
+#[derive(Clone, Debug)]
+pub struct SolveTrace {
+    /// The instant the trace started, from which the start of each event is measured.
+    origin: Instant,
+    events: Vec<TraceEvent>,
+    /// When the ACVM returned to wait on the pending foreign calls, if it is waiting on any.
+    waiting_since: Option<Instant>,
+}
+
+/// An event of a [`SolveTrace`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TraceEvent {
+    pub kind: TraceEventKind,
+    /// When the event started, since the trace started.
+    pub start: Duration,
+    pub duration: Duration,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TraceEventKind {
+    /// An attempt at solving an opcode, of which there may be several over multiple passes.
+    Opcode {
+        /// The [name][acir::circuit::Opcode::name] of the opcode.
+        name: String,
+        location: OpcodeLocation,
+        outcome: TraceOutcome,
+    },
+    /// The time between the ACVM requiring a foreign call and the foreign call being resolved.
+    ForeignCallWait {
+        function: String,
+        /// The location of the Brillig opcode which made the call.
+        location: OpcodeLocation,
+    },
+}
+
+/// The outcome of an attempt at solving an opcode.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TraceOutcome {
+    Solved,
+    /// Some of the witnesses of the opcode were assigned, but not all.
+    InProgress,
+    /// The opcode is waiting on a foreign call.
+    ForeignCall,
+    /// The opcode is waiting on a black box call deferred by the backend.
+    BlackBoxCall,
+    /// The opcode cannot be solved yet and is deferred to the next pass.
+    Stalled,
+    Failed,
+}
+
+impl TraceOutcome {
+    pub(super) fn of(resolution: &Result<OpcodeResolution, OpcodeResolutionError>) -> Self {
+        match resolution {
+            Ok(OpcodeResolution::Solved) => TraceOutcome::Solved,
+            Ok(OpcodeResolution::InProgress) => TraceOutcome::InProgress,
+            Ok(OpcodeResolution::InProgressBrillig(_)) => TraceOutcome::ForeignCall,
+            Ok(OpcodeResolution::InProgressBlackBox(_)) => TraceOutcome::BlackBoxCall,
+            Ok(OpcodeResolution::Stalled(_)) => TraceOutcome::Stalled,
+            Err(_) => TraceOutcome::Failed,
+        }
+    }
+
+    #[cfg(feature = "chrome-trace")]
+    fn as_str(&self) -> &'static str {
+        match self {
+            TraceOutcome::Solved => "solved",
+            TraceOutcome::InProgress => "in progress",
+            TraceOutcome::ForeignCall => "foreign call",
+            TraceOutcome::BlackBoxCall => "black box call",
+            TraceOutcome::Stalled => "stalled",
+            TraceOutcome::Failed => "failed",
+        }
+    }
+}
+
+impl SolveTrace {
+    pub(super) fn new() -> Self {
+        SolveTrace { origin: Instant::now(), events: Vec::new(), waiting_since: None }
+    }
+
+    /// Returns the events of the trace, in the order they started.
+    pub fn events(&self) -> &[TraceEvent] {
+        &self.events
+    }
+
+    pub(super) fn record_opcode(
+        &mut self,
+        name: &str,
+        location: OpcodeLocation,
+        outcome: TraceOutcome,
+        started: Instant,
+    ) {
+        let kind = TraceEventKind::Opcode { name: name.to_owned(), location, outcome };
+        self.record(kind, started);
+    }
+
+    /// Marks the start of a wait on the pending foreign calls.
+    pub(super) fn wait_on_foreign_calls(&mut self) {
+        self.waiting_since = Some(Instant::now());
+    }
+
+    /// Records the wait on a foreign call which has just been resolved, the next pending call, if any,
+    /// being waited on from then on.
+    pub(super) fn record_foreign_call(&mut self, function: &str, location: OpcodeLocation) {
+        if let Some(started) = self.waiting_since {
+            let kind = TraceEventKind::ForeignCallWait { function: function.to_owned(), location };
+            self.record(kind, started);
+            self.waiting_since = Some(Instant::now());
+        }
+    }
+
+    /// Marks the end of a wait on the pending foreign calls, as the ACVM resumes solving.
+    pub(super) fn resume(&mut self) {
+        self.waiting_since = None;
+    }
+
+    fn record(&mut self, kind: TraceEventKind, started: Instant) {
+        self.events.push(TraceEvent {
+            kind,
+            start: started.saturating_duration_since(self.origin),
+            duration: started.elapsed(),
+        });
+    }
+
+    /// Exports the trace in the [Trace Event Format] read by `chrome://tracing` and the Perfetto UI.
+    ///
+    /// Opcodes are laid out on a `solver` thread and the waits on foreign calls on a `foreign calls` thread,
+    /// so that the gaps in the solving timeline line up with the foreign calls responsible for them.
+    ///
+    /// [Trace Event Format]: https://docs.google.com/document/d/1CvAClvFfyA5R-PhYUmn5OOQtYMH4h6I0nSsKchNAySU
+    #[cfg(feature = "chrome-trace")]
+    pub fn to_chrome_trace(&self) -> serde_json::Value {
+        use serde_json::json;
+
+        const SOLVER_THREAD: u32 = 1;
+        const FOREIGN_CALL_THREAD: u32 = 2;
+
+        let thread_name = |tid: u32, name: &str| {
+            json!({
+                "name": "thread_name",
+                "ph": "M",
+                "pid": 1,
+                "tid": tid,
+                "args": { "name": name },
+            })
+        };
+        let mut trace_events = vec![
+            thread_name(SOLVER_THREAD, "solver"),
+            thread_name(FOREIGN_CALL_THREAD, "foreign calls"),
+        ];
+        trace_events.extend(self.events.iter().map(|event| {
+            let (name, tid, args) = match &event.kind {
+                TraceEventKind::Opcode { name, location, outcome } => (
+                    name.clone(),
+                    SOLVER_THREAD,
+                    json!({ "location": location.to_string(), "outcome": outcome.as_str() }),
+                ),
+                TraceEventKind::ForeignCallWait { function, location } => (
+                    function.clone(),
+                    FOREIGN_CALL_THREAD,
+                    json!({ "location": location.to_string() }),
+                ),
+            };
+            json!({
+                "name": name,
+                "ph": "X",
+                "pid": 1,
+                "tid": tid,
+                // Timestamps are in microseconds.
+                "ts": event.start.as_secs_f64() * 1e6,
+                "dur": event.duration.as_secs_f64() * 1e6,
+                "args": args,
+            })
+        }));
+        json!({ "traceEvents": trace_events, "displayTimeUnit": "ns" })
+    }
+}
@@ -0,0 +1,591 @@
+//! A [`Backend`] for tests which does not link a proving system, available with the `testing` feature.
+//!
+//! [`MockBackend`] solves every black box function in pure Rust and "proves" a circuit by checking that
+//! its witness satisfies every constraint, such that circuits can be solved and proven end to end.
+//! Its signatures, proofs and keys are not compatible with those of any real backend.
+//!
+//! [`RandomWitnessGenerator`] assigns pseudo-random values to the inputs of a circuit, in order to check that it
+//! can be solved at all or to drive fuzzing.
+use acir::{
+    circuit::{brillig::BrilligOutputs, Circuit, Opcode},
+    native_types::{Expression, Witness, WitnessMap},
+    BlackBoxFunc, FieldElement,
+};
+use num_bigint::BigUint;
+use std::collections::{BTreeMap, BTreeSet};
+use thiserror::Error;
+
+use crate::{
+    blackbox_solver::{blake2s256, sha256},
+    compiler::{sensitivity, BackendCapabilities, SplitMix64},
+    pwg::{
+        blackbox::{self, GrumpkinPoint},
+        OpcodeResolutionError, PartialWitnessGeneratorStatus, ACVM,
+    },
+    Backend, CommonReferenceString, Language, PartialWitnessGenerator, ProofSystemCompiler,
+    SmartContract,
+};
+
+/// The order of the Grumpkin curve, which is the modulus of the BN254 base field.
+const GRUMPKIN_ORDER: &str =
+    "21888242871839275222246405745257275088696311157297823662689037894645226208583";
+
+#[derive(Debug, Error)]
+pub enum MockBackendError {
+    #[error("the proving key was not generated for this circuit")]
+    KeyMismatch,
+    #[error("the witness does not satisfy the circuit: {0}")]
+    UnsatisfiedCircuit(#[from] OpcodeResolutionError),
+    #[error("the witness does not assign the public input {0}")]
+    MissingPublicInput(u32),
+}
+
+/// A backend supporting the opcodes of its [`BackendCapabilities`], which are those of a
+/// PLONK backend of width 3 supporting every black box function by default.
+///
+/// # Schnorr signatures
+///
+/// Signatures are checked against the public key `P = sk * G` on the Grumpkin curve, where `G` is its generator.
+/// The signature `(s, e)` of a message `m` with the nonce `k` is `s = k - e * sk`, where `e` is the
+/// BLAKE2s hash of `(k * G).x || P.x || P.y || m` reduced into the field, and is computed by [`MockBackend::schnorr_sign`].
+///
+/// # Proofs
+///
+/// Both keys of a circuit are the SHA-256 hash of its serialization.
+/// Proving a circuit checks its witness, ignoring the Brillig opcodes and directives which do not constrain it,
+/// and the proof is the hash of the verification key followed by the values of the public inputs.
+#[derive(Debug, Clone)]
+pub struct MockBackend {
+    capabilities: BackendCapabilities,
+}
+
+impl Default for MockBackend {
+    fn default() -> Self {
+        MockBackend::new(BackendCapabilities::new(Language::PLONKCSat { width: 3 }))
+    }
+}
+
+impl MockBackend {
+    pub fn new(capabilities: BackendCapabilities) -> Self {
+        MockBackend { capabilities }
+    }
+
+    pub fn capabilities(&self) -> &BackendCapabilities {
+        &self.capabilities
+    }
+
+    /// Returns the coordinates of the public key corresponding to `secret_key`.
+    pub fn schnorr_public_key(secret_key: FieldElement) -> (FieldElement, FieldElement) {
+        let public_key = GrumpkinPoint::generator().mul(&to_biguint(secret_key));
+        (public_key.x, public_key.y)
+    }
+
+    /// Returns the signature `(s, e)` of `message` with `secret_key`, using the secret `nonce`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `s` does not fit in the field, which happens for a negligible proportion of nonces.
+    pub fn schnorr_sign(
+        secret_key: FieldElement,
+        nonce: FieldElement,
+        message: &[u8],
+    ) -> (FieldElement, FieldElement) {
+        let order = grumpkin_order();
+        let generator = GrumpkinPoint::generator();
+        let public_key = generator.mul(&to_biguint(secret_key));
+        let e = schnorr_challenge(&generator.mul(&to_biguint(nonce)), &public_key, message);
+
+        let e_sk = (to_biguint(e) * to_biguint(secret_key)) % &order;
+        let s = (to_biguint(nonce) + &order - e_sk) % &order;
+        assert!(s < FieldElement::modulus(), "the signature does not fit in the field");
+        (FieldElement::from_be_bytes_reduce(&s.to_bytes_be()), e)
+    }
+
+    /// Returns the SHA-256 hash of `circuit`, which is both its proving and verification key.
+    fn circuit_key(circuit: &Circuit) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        circuit.write(&mut bytes).expect("writing to a vector cannot fail");
+        sha256(&bytes).to_vec()
+    }
+
+    fn proof(
+        verification_key: &[u8],
+        public_values: &[FieldElement],
+        is_recursive: bool,
+    ) -> Vec<u8> {
+        let mut preimage = verification_key.to_vec();
+        for value in public_values {
+            preimage.extend(value.to_be_bytes());
+        }
+        preimage.push(is_recursive as u8);
+        sha256(&preimage).to_vec()
+    }
+}
+
+impl Backend for MockBackend {}
+
+impl PartialWitnessGenerator for MockBackend {
+    fn schnorr_verify(
+        &self,
+        public_key_x: &FieldElement,
+        public_key_y: &FieldElement,
+        signature_s: &FieldElement,
+        signature_e: &FieldElement,
+        message: &[u8],
+    ) -> Result<bool, OpcodeResolutionError> {
+        let public_key = GrumpkinPoint::from_coordinates(
+            BlackBoxFunc::SchnorrVerify,
+            *public_key_x,
+            *public_key_y,
+        )?;
+
+        // s * G + e * P = (k - e * sk) * G + e * sk * G = k * G
+        let nonce_point = GrumpkinPoint::generator()
+            .mul(&to_biguint(*signature_s))
+            .add(&public_key.mul(&to_biguint(*signature_e)));
+        Ok(!nonce_point.is_infinity()
+            && schnorr_challenge(&nonce_point, &public_key, message) == *signature_e)
+    }
+
+    fn pedersen(
+        &self,
+        inputs: &[FieldElement],
+        domain_separator: u32,
+    ) -> Result<(FieldElement, FieldElement), OpcodeResolutionError> {
+        // The `testing` feature enables `native-pedersen`, so this is only reached by direct calls.
+        Ok(blackbox::pedersen_commitment(inputs, domain_separator))
+    }
+
+    fn fixed_base_scalar_mul(
+        &self,
+        input: &FieldElement,
+    ) -> Result<(FieldElement, FieldElement), OpcodeResolutionError> {
+        let result = GrumpkinPoint::generator().mul(&to_biguint(*input));
+        Ok((result.x, result.y))
+    }
+
+    /// Proofs are not verified, the output aggregation object being a copy of the input aggregation object,
+    /// or zero if there is none.
+    fn recursive_aggregation(
+        &self,
+        _verification_key: &[FieldElement],
+        _proof: &[FieldElement],
+        _public_inputs: &[FieldElement],
+        _key_hash: &FieldElement,
+        input_aggregation_object: Option<&[FieldElement]>,
+        output_len: usize,
+    ) -> Result<Vec<FieldElement>, OpcodeResolutionError> {
+        Ok(match input_aggregation_object {
+            Some(inputs) => inputs.to_vec(),
+            None => vec![FieldElement::zero(); output_len],
+        })
+    }
+}
+
+impl ProofSystemCompiler for MockBackend {
+    type Error = MockBackendError;
+
+    fn np_language(&self) -> Language {
+        self.capabilities.language.clone()
+    }
+
+    fn supports_opcode(&self, opcode: &Opcode) -> bool {
+        self.capabilities.supports_opcode(opcode)
+    }
+
+    fn get_exact_circuit_size(&self, circuit: &Circuit) -> Result<u32, Self::Error> {
+        Ok(circuit.opcodes.len() as u32)
+    }
+
+    fn preprocess(
+        &self,
+        _common_reference_string: &[u8],
+        circuit: &Circuit,
+    ) -> Result<(Vec<u8>, Vec<u8>), Self::Error> {
+        let key = Self::circuit_key(circuit);
+        Ok((key.clone(), key))
+    }
+
+    fn prove_with_pk(
+        &self,
+        _common_reference_string: &[u8],
+        circuit: &Circuit,
+        witness_values: WitnessMap,
+        proving_key: &[u8],
+        is_recursive: bool,
+    ) -> Result<Vec<u8>, Self::Error> {
+        let verification_key = Self::circuit_key(circuit);
+        if proving_key != verification_key {
+            return Err(MockBackendError::KeyMismatch);
+        }
+
+        // Brillig opcodes and directives only compute hints, the values of which are part of the witness.
+        let constraints = circuit
+            .opcodes
+            .iter()
+            .filter(|opcode| !matches!(opcode, Opcode::Brillig(_) | Opcode::Directive(_)))
+            .cloned()
+            .collect();
+        let mut acvm = ACVM::new(self.clone(), constraints, witness_values);
+        let status = acvm.solve()?;
+        assert_eq!(status, PartialWitnessGeneratorStatus::Solved, "no opcode makes foreign calls");
+
+        let public_values = circuit
+            .public_inputs()
+            .0
+            .iter()
+            .map(|witness| {
+                acvm.witness_map()
+                    .get(witness)
+                    .copied()
+                    .ok_or(MockBackendError::MissingPublicInput(witness.witness_index()))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self::proof(&verification_key, &public_values, is_recursive))
+    }
+
+    fn verify_with_vk(
+        &self,
+        _common_reference_string: &[u8],
+        proof: &[u8],
+        public_inputs: WitnessMap,
+        circuit: &Circuit,
+        verification_key: &[u8],
+        is_recursive: bool,
+    ) -> Result<bool, Self::Error> {
+        if verification_key != Self::circuit_key(circuit) {
+            return Ok(false);
+        }
+        let public_values: Option<Vec<FieldElement>> = circuit
+            .public_inputs()
+            .0
+            .iter()
+            .map(|witness| public_inputs.get(witness).copied())
+            .collect();
+        Ok(public_values.map_or(false, |public_values| {
+            proof == Self::proof(verification_key, &public_values, is_recursive)
+        }))
+    }
+
+    fn proof_as_fields(
+        &self,
+        proof: &[u8],
+        public_inputs: WitnessMap,
+    ) -> Result<Vec<FieldElement>, Self::Error> {
+        let mut fields: Vec<FieldElement> =
+            public_inputs.into_iter().map(|(_, value)| value).collect();
+        fields.extend(bytes_as_fields(proof));
+        Ok(fields)
+    }
+
+    fn vk_as_fields(
+        &self,
+        _common_reference_string: &[u8],
+        verification_key: &[u8],
+    ) -> Result<(Vec<FieldElement>, FieldElement), Self::Error> {
+        let key_hash = FieldElement::from_be_bytes_reduce(&sha256(verification_key));
+        Ok((bytes_as_fields(verification_key), key_hash))
+    }
+}
+
+impl SmartContract for MockBackend {
+    type Error = MockBackendError;
+
+    fn eth_contract_from_vk(
+        &self,
+        _common_reference_string: &[u8],
+        verification_key: &[u8],
+    ) -> Result<String, Self::Error> {
+        let verification_key: String =
+            verification_key.iter().map(|byte| format!("{byte:02x}")).collect();
+        Ok(format!(
+            "// SPDX-License-Identifier: MIT
+// Mock verifier accepting every proof, which must not be deployed.
+pragma solidity ^0.8.0;
+
+contract MockVerifier {{
+    bytes public constant VERIFICATION_KEY = hex\"{verification_key}\";
+
+    function verify(bytes calldata, bytes32[] calldata) external pure returns (bool) {{
+        return true;
+    }}
+}}
+"
+        ))
+    }
+}
+
+#[async_trait::async_trait(?Send)]
+impl CommonReferenceString for MockBackend {
+    type Error = MockBackendError;
+
+    /// The mock reference string is made of one zero byte per opcode of the circuit.
+    async fn generate_common_reference_string(
+        &self,
+        circuit: &Circuit,
+    ) -> Result<Vec<u8>, Self::Error> {
+        Ok(vec![0; circuit.opcodes.len()])
+    }
+
+    async fn update_common_reference_string(
+        &self,
+        mut common_reference_string: Vec<u8>,
+        circuit: &Circuit,
+    ) -> Result<Vec<u8>, Self::Error> {
+        if common_reference_string.len() < circuit.opcodes.len() {
+            common_reference_string.resize(circuit.opcodes.len(), 0);
+        }
+        Ok(common_reference_string)
+    }
+}
+
+/// Assigns deterministic pseudo-random values to the inputs of circuits which are left unassigned, and solves them.
+///
+/// The inputs are found by following how the ACVM solves the circuit: as long as some witness cannot be solved,
+/// the lowest such witness which no opcode computes is assigned a value, such that the witnesses constrained by
+/// arithmetic opcodes are solved rather than drawn. Values are drawn in this order from a generator seeded by the
+/// seed, so the same seed always yields the same values for the same circuit. Small values are drawn often so that
+/// inputs regularly satisfy range constraints, and inputs can be [given a bit size][Self::with_bit_size] to always do.
+#[derive(Debug, Clone)]
+pub struct RandomWitnessGenerator {
+    seed: u64,
+    bit_sizes: BTreeMap<Witness, u32>,
+}
+
+impl RandomWitnessGenerator {
+    pub fn new(seed: u64) -> Self {
+        RandomWitnessGenerator { seed, bit_sizes: BTreeMap::new() }
+    }
+
+    /// Draws the values of `witness` among those fitting within `num_bits` bits.
+    pub fn with_bit_size(mut self, witness: Witness, num_bits: u32) -> Self {
+        self.bit_sizes.insert(witness, num_bits);
+        self
+    }
+
+    /// Assigns a value to each input of `circuit` which `witness_map` does not assign, returning these inputs.
+    ///
+    /// This solves the sensitivity of the circuit once for each input, and so is meant for circuits of the size of tests.
+    pub fn fill_inputs(
+        &self,
+        circuit: &Circuit,
+        witness_map: &mut WitnessMap,
+    ) -> BTreeSet<Witness> {
+        let computed = computed_witnesses(&circuit.opcodes);
+        let mut rng = SplitMix64(self.seed);
+        let mut assigned: BTreeSet<Witness> =
+            witness_map.iter().map(|(witness, _)| witness).collect();
+        let mut inputs = BTreeSet::new();
+        loop {
+            let sensitivity = sensitivity(circuit, &assigned);
+            let input =
+                match sensitivity.unresolved().iter().find(|witness| !computed.contains(witness)) {
+                    Some(input) => *input,
+                    None => return inputs,
+                };
+            let value = match self.bit_sizes.get(&input) {
+                Some(num_bits) => rng.next_field_element_with_bits(*num_bits),
+                None => rng.next_field_element(),
+            };
+            witness_map.insert(input, value);
+            assigned.insert(input);
+            inputs.insert(input);
+        }
+    }
+
+    /// Solves `circuit` once its inputs which `initial_witness` does not assign have been [filled][Self::fill_inputs].
+    ///
+    /// Returns the ACVM once it halts, so that the caller can resolve any foreign call it waits on.
+    pub fn solve<B: PartialWitnessGenerator>(
+        &self,
+        backend: B,
+        circuit: &Circuit,
+        mut initial_witness: WitnessMap,
+    ) -> Result<ACVM<B>, OpcodeResolutionError> {
+        self.fill_inputs(circuit, &mut initial_witness);
+        let mut acvm = ACVM::new(backend, circuit.opcodes.clone(), initial_witness);
+        acvm.solve()?;
+        Ok(acvm)
+    }
+}
+
+/// Returns the witnesses which are computed by `opcodes` rather than only constrained by them, i.e. the outputs
+/// of black box functions, directives and Brillig opcodes, along with the values read from memory.
+fn computed_witnesses(opcodes: &[Opcode]) -> BTreeSet<Witness> {
+    let mut computed = BTreeSet::new();
+    for opcode in opcodes {
+        match opcode {
+            Opcode::BlackBoxFuncCall(call) => computed.extend(call.get_outputs_vec()),
+            Opcode::Directive(directive) => computed.extend(directive.outputs()),
+            Opcode::Brillig(brillig) => {
+                for output in &brillig.outputs {
+                    match output {
+                        BrilligOutputs::Simple(witness) => {
+                            computed.insert(*witness);
+                        }
+                        BrilligOutputs::Array(witnesses) => computed.extend(witnesses),
+                    }
+                }
+            }
+            Opcode::MemoryOp { op, .. } if op.operation == Expression::zero() => {
+                computed.extend(op.value.to_witness());
+            }
+            _ => (),
+        }
+    }
+    computed
+}
+
+fn grumpkin_order() -> BigUint {
+    BigUint::parse_bytes(GRUMPKIN_ORDER.as_bytes(), 10).expect("the order is a decimal integer")
+}
+
+fn to_biguint(value: FieldElement) -> BigUint {
+    BigUint::from_bytes_be(&value.to_be_bytes())
+}
+
+/// Returns the BLAKE2s hash of `nonce_point.x || public_key.x || public_key.y || message`, reduced into the field.
+fn schnorr_challenge(
+    nonce_point: &GrumpkinPoint,
+    public_key: &GrumpkinPoint,
+    message: &[u8],
+) -> FieldElement {
+    let mut preimage = nonce_point.x.to_be_bytes();
+    preimage.extend(public_key.x.to_be_bytes());
+    preimage.extend(public_key.y.to_be_bytes());
+    preimage.extend(message);
+    FieldElement::from_be_bytes_reduce(&blake2s256(&preimage))
+}
+
+/// Packs `bytes` into field elements of 16 bytes each.
+fn bytes_as_fields(bytes: &[u8]) -> Vec<FieldElement> {
+    bytes.chunks(16).map(FieldElement::from_be_bytes_reduce).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{MockBackend, RandomWitnessGenerator};
+    use crate::{
+        pwg::{PartialWitnessGeneratorStatus, ACVM},
+        ProofSystemCompiler,
+    };
+    use acir::{
+        circuit::{
+            opcodes::{BlackBoxFuncCall, FunctionInput},
+            Circuit, Opcode, PublicInputs,
+        },
+        native_types::{Expression, Witness, WitnessMap},
+        FieldElement,
+    };
+    use std::collections::{BTreeMap, BTreeSet};
+
+    fn witness_map(values: &[(u32, FieldElement)]) -> WitnessMap {
+        let values: BTreeMap<_, _> =
+            values.iter().map(|(index, value)| (Witness(*index), *value)).collect();
+        values.into()
+    }
+
+    fn verify_signature(message: &[u8], signed_message: &[u8]) -> FieldElement {
+        let secret_key = FieldElement::from(42_u128);
+        let (public_key_x, public_key_y) = MockBackend::schnorr_public_key(secret_key);
+        let (s, e) =
+            MockBackend::schnorr_sign(secret_key, FieldElement::from(7_u128), signed_message);
+
+        let input =
+            |witness: u32, num_bits: u32| FunctionInput { witness: Witness(witness), num_bits };
+        let opcode = Opcode::BlackBoxFuncCall(BlackBoxFuncCall::SchnorrVerify {
+            public_key_x: input(1, 254),
+            public_key_y: input(2, 254),
+            signature_s: input(3, 254),
+            signature_e: input(4, 254),
+            message: (0..message.len() as u32).map(|index| input(5 + index, 8)).collect(),
+            output: Witness(100),
+        });
+        let mut values = vec![(1, public_key_x), (2, public_key_y), (3, s), (4, e)];
+        values.extend(
+            message
+                .iter()
+                .enumerate()
+                .map(|(index, byte)| (5 + index as u32, FieldElement::from(*byte as u128))),
+        );
+
+        let mut acvm = ACVM::new(MockBackend::default(), vec![opcode], witness_map(&values));
+        assert_eq!(acvm.solve(), Ok(PartialWitnessGeneratorStatus::Solved));
+        acvm.witness_map()[&Witness(100)]
+    }
+
+    #[test]
+    fn verifies_schnorr_signatures() {
+        assert_eq!(verify_signature(b"hello", b"hello"), FieldElement::one());
+        assert_eq!(verify_signature(b"hello", b"hellp"), FieldElement::zero());
+    }
+
+    #[test]
+    fn proves_satisfied_circuits() {
+        // x * y - z = 0, where z is public
+        let mut product = Expression::default();
+        product.push_multiplication_term(FieldElement::one(), Witness(1), Witness(2));
+        product.push_addition_term(-FieldElement::one(), Witness(3));
+        let circuit = Circuit {
+            current_witness_index: 3,
+            opcodes: vec![Opcode::AssertZero(product)],
+            public_parameters: PublicInputs(BTreeSet::from([Witness(3)])),
+            ..Circuit::default()
+        };
+        let backend = MockBackend::default();
+        let (proving_key, verification_key) = backend.preprocess(&[], &circuit).unwrap();
+        let value = |value: u128| FieldElement::from(value);
+
+        let witness = witness_map(&[(1, value(2)), (2, value(3)), (3, value(6))]);
+        let proof = backend.prove_with_pk(&[], &circuit, witness, &proving_key, false).unwrap();
+        let verify = |public_value: u128| {
+            let public_inputs = witness_map(&[(3, value(public_value))]);
+            backend
+                .verify_with_vk(&[], &proof, public_inputs, &circuit, &verification_key, false)
+                .unwrap()
+        };
+        assert!(verify(6));
+        assert!(!verify(7));
+
+        let witness = witness_map(&[(1, value(2)), (2, value(3)), (3, value(7))]);
+        assert!(backend.prove_with_pk(&[], &circuit, witness, &proving_key, false).is_err());
+    }
+
+    #[test]
+    fn random_witness_generator_fills_inputs_deterministically() {
+        // x * y - z = 0 and x fits within 8 bits, where z is computed from the inputs x and y.
+        let mut product = Expression::default();
+        product.push_multiplication_term(FieldElement::one(), Witness(1), Witness(2));
+        product.push_addition_term(-FieldElement::one(), Witness(3));
+        let circuit = Circuit {
+            current_witness_index: 3,
+            opcodes: vec![
+                Opcode::AssertZero(product),
+                Opcode::BlackBoxFuncCall(BlackBoxFuncCall::RANGE {
+                    input: FunctionInput { witness: Witness(1), num_bits: 8 },
+                }),
+            ],
+            ..Circuit::default()
+        };
+
+        let generator = RandomWitnessGenerator::new(7).with_bit_size(Witness(1), 8);
+        let mut inputs_map = WitnessMap::new();
+        let inputs = generator.fill_inputs(&circuit, &mut inputs_map);
+        assert_eq!(inputs, BTreeSet::from([Witness(1), Witness(2)]));
+
+        let acvm = generator.solve(MockBackend::default(), &circuit, WitnessMap::new()).unwrap();
+        assert!(acvm.unresolved_opcodes().is_empty());
+        let solution = acvm.witness_map();
+        assert_eq!(solution[&Witness(1)], inputs_map[&Witness(1)]);
+        assert_eq!(solution[&Witness(3)], solution[&Witness(1)] * solution[&Witness(2)]);
+
+        // Assigned inputs are kept, and other seeds draw other values.
+        let mut partial_witness_map = witness_map(&[(2, FieldElement::from(5_u128))]);
+        let inputs = generator.fill_inputs(&circuit, &mut partial_witness_map);
+        assert_eq!(inputs, BTreeSet::from([Witness(1)]));
+        assert_eq!(partial_witness_map[&Witness(2)], FieldElement::from(5_u128));
+        let mut other_witness_map = WitnessMap::new();
+        RandomWitnessGenerator::new(8).fill_inputs(&circuit, &mut other_witness_map);
+        assert_ne!(other_witness_map, inputs_map);
+    }
+}
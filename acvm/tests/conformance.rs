@@ -0,0 +1,1053 @@
+//! The corpus of conformance test vectors, which alternative ACIR interpreters can check themselves against.
+//!
+//! The corpus is built by this file and stored as JSON in `tests/conformance`. After changing the corpus
+//! or the behaviour of the solver, regenerate the stored vectors with:
+//!
+//! ```text
+//! UPDATE_CONFORMANCE_VECTORS=1 cargo test -p acvm --test conformance
+//! ```
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::PathBuf,
+};
+
+use acir::{
+    brillig_vm::{self, BinaryFieldOp, RegisterIndex, RegisterOrMemory},
+    circuit::{
+        brillig::{Brillig, BrilligInputs, BrilligOutputs},
+        directives::{Directive, LogInfo, QuotientDirective},
+        opcodes::{BlackBoxFuncCall, BlockId, FunctionInput, MemOp},
+        Circuit, Opcode, PublicInputs,
+    },
+    native_types::{Expression, Witness, WitnessMap},
+    BlackBoxFunc, FieldElement,
+};
+use acvm::{
+    conformance::{
+        read_test_vectors, write_test_vectors, ForeignCallRecord, ForeignCallValues, TestVector,
+    },
+    testing::MockBackend,
+};
+
+/// Allocates the witnesses of a circuit and assigns the initial witness.
+struct CircuitBuilder {
+    next_witness: u32,
+    inputs: BTreeMap<Witness, FieldElement>,
+    return_values: BTreeSet<Witness>,
+    foreign_calls: Vec<ForeignCallRecord>,
+}
+
+impl CircuitBuilder {
+    fn new() -> Self {
+        CircuitBuilder {
+            next_witness: 1,
+            inputs: BTreeMap::new(),
+            return_values: BTreeSet::new(),
+            foreign_calls: Vec::new(),
+        }
+    }
+
+    /// Returns a new witness without any initial value.
+    fn witness(&mut self) -> Witness {
+        self.next_witness += 1;
+        Witness(self.next_witness - 1)
+    }
+
+    fn witnesses(&mut self, len: usize) -> Vec<Witness> {
+        (0..len).map(|_| self.witness()).collect()
+    }
+
+    /// Returns a new witness whose initial value is `value`.
+    fn input(&mut self, value: impl Into<FieldElement>) -> Witness {
+        let witness = self.witness();
+        self.inputs.insert(witness, value.into());
+        witness
+    }
+
+    fn function_input(&mut self, value: impl Into<FieldElement>, num_bits: u32) -> FunctionInput {
+        FunctionInput { witness: self.input(value), num_bits }
+    }
+
+    fn bytes(&mut self, bytes: &[u8]) -> Vec<FunctionInput> {
+        bytes.iter().map(|byte| self.function_input(*byte as u128, 8)).collect()
+    }
+
+    fn build(self, name: &str, description: &str, opcodes: Vec<Opcode>) -> TestVector {
+        let circuit = Circuit {
+            current_witness_index: self.next_witness - 1,
+            opcodes,
+            return_values: PublicInputs(self.return_values),
+            ..Circuit::default()
+        };
+        TestVector::new(
+            name,
+            description,
+            &circuit,
+            WitnessMap::from(self.inputs),
+            self.foreign_calls,
+            MockBackend::default(),
+        )
+        .unwrap()
+    }
+}
+
+fn field(value: u128) -> FieldElement {
+    FieldElement::from(value)
+}
+
+fn bytes_from_hex(hex: &str) -> Vec<u8> {
+    (0..hex.len()).step_by(2).map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap()).collect()
+}
+
+fn linear(terms: &[(i128, Witness)], constant: i128) -> Expression {
+    let mut expr = Expression::from_field(FieldElement::from(constant));
+    for (coefficient, witness) in terms {
+        expr.push_addition_term(FieldElement::from(*coefficient), *witness);
+    }
+    expr
+}
+
+fn arithmetic() -> Vec<TestVector> {
+    let mut builder = CircuitBuilder::new();
+    let (a, b, sum) = (builder.input(3_u128), builder.input(4_u128), builder.witness());
+    builder.return_values.insert(sum);
+    let linear_vector = builder.build(
+        "linear",
+        "Solves the unknown witness of a linear expression and returns it.",
+        vec![Opcode::AssertZero(linear(&[(1, a), (2, b), (-1, sum)], 5))],
+    );
+
+    let mut builder = CircuitBuilder::new();
+    let (a, b, product) = (builder.input(3_u128), builder.input(4_u128), builder.witness());
+    let mut expr = linear(&[(-1, product)], 1);
+    expr.push_multiplication_term(field(2), a, b);
+    let quadratic = builder.build(
+        "quadratic",
+        "Solves the unknown witness of an expression with a multiplication term.",
+        vec![Opcode::AssertZero(expr)],
+    );
+
+    let mut builder = CircuitBuilder::new();
+    let (a, b) = (builder.input(1_u128), builder.input(2_u128));
+    let unsatisfied = builder.build(
+        "unsatisfied",
+        "Fails on an expression which does not hold for the initial witness.",
+        vec![
+            Opcode::AssertZero(linear(&[(1, a)], -1)),
+            Opcode::AssertZero(linear(&[(1, a), (-1, b)], 0)),
+        ],
+    );
+
+    let mut builder = CircuitBuilder::new();
+    let (a, b, c) = (builder.input(1_u128), builder.witness(), builder.witness());
+    let unsolvable = builder.build(
+        "unsolvable",
+        "Fails on an expression with two unknown witnesses.",
+        vec![Opcode::AssertZero(linear(&[(1, a), (1, b), (-1, c)], 0))],
+    );
+
+    vec![linear_vector, quadratic, unsatisfied, unsolvable]
+}
+
+fn directives() -> Vec<TestVector> {
+    let mut builder = CircuitBuilder::new();
+    let (x, result) = (builder.input(7_u128), builder.witness());
+    let invert = builder.build(
+        "invert",
+        "Inverts a field element.",
+        vec![Opcode::Directive(Directive::Invert { x, result })],
+    );
+
+    let mut builder = CircuitBuilder::new();
+    let (a, b) = (builder.input(17_u128), builder.input(5_u128));
+    let (q, r) = (builder.witness(), builder.witness());
+    let quotient = builder.build(
+        "quotient",
+        "Computes the quotient and remainder of an integer division.",
+        vec![Opcode::Directive(Directive::Quotient(QuotientDirective {
+            a: a.into(),
+            b: b.into(),
+            q,
+            r,
+            predicate: None,
+        }))],
+    );
+
+    let mut builder = CircuitBuilder::new();
+    let (a, b) = (builder.input(17_u128), builder.input(5_u128));
+    let (q, r) = (builder.witness(), builder.witness());
+    let quotient_predicate = builder.build(
+        "quotient_with_false_predicate",
+        "Assigns zero to the quotient and remainder of a division whose predicate is zero.",
+        vec![Opcode::Directive(Directive::Quotient(QuotientDirective {
+            a: a.into(),
+            b: b.into(),
+            q,
+            r,
+            predicate: Some(Expression::zero()),
+        }))],
+    );
+
+    let mut builder = CircuitBuilder::new();
+    let a = builder.input(0xabcd_u128);
+    let b = builder.witnesses(5);
+    let to_le_radix = builder.build(
+        "to_le_radix",
+        "Decomposes an integer into its little-endian digits in base 16.",
+        vec![Opcode::Directive(Directive::ToLeRadix { a: a.into(), b, radix: 16 })],
+    );
+
+    let mut builder = CircuitBuilder::new();
+    let inputs = [3_u128, 1, 2].map(|value| vec![Expression::from(builder.input(value))]);
+    let bits = builder.witnesses(3);
+    let permutation_sort = builder.build(
+        "permutation_sort",
+        "Computes the control bits of the sorting network sorting three values.",
+        vec![Opcode::Directive(Directive::PermutationSort {
+            inputs: inputs.to_vec(),
+            tuple: 1,
+            bits,
+            sort_by: vec![0],
+        })],
+    );
+
+    let mut builder = CircuitBuilder::new();
+    let value = builder.input(42_u128);
+    let log = builder.build(
+        "log",
+        "Logs witnesses and strings without assigning any witness.",
+        vec![
+            Opcode::Directive(Directive::Log(LogInfo::WitnessOutput(vec![value]))),
+            Opcode::Directive(Directive::Log(LogInfo::FinalizedOutput("conformance".to_owned()))),
+        ],
+    );
+
+    // Limbs of 8 bits: a = 0x1234 and b = 0x0105.
+    let mut builder = CircuitBuilder::new();
+    let a = [0x34_u128, 0x12].map(|limb| Expression::from(builder.input(limb)));
+    let b = [0x05_u128, 0x01].map(|limb| Expression::from(builder.input(limb)));
+    let (q, r) = (builder.witnesses(2), builder.witnesses(2));
+    let big_quotient = builder.build(
+        "big_quotient",
+        "Computes the quotient and remainder of the division of big integers given by their limbs.",
+        vec![Opcode::Directive(Directive::BigQuotient {
+            a: a.to_vec(),
+            b: b.to_vec(),
+            q,
+            r,
+            limb_bits: 8,
+        })],
+    );
+
+    let mut builder = CircuitBuilder::new();
+    let a = [0x03_u128, 0x00].map(|limb| Expression::from(builder.input(limb)));
+    let modulus = [0x01_u128, 0x01].map(|limb| Expression::from(builder.input(limb)));
+    let result = builder.witnesses(2);
+    let big_invert = builder.build(
+        "big_invert",
+        "Inverts a big integer modulo 257, both given by their limbs.",
+        vec![Opcode::Directive(Directive::BigInvert {
+            a: a.to_vec(),
+            modulus: modulus.to_vec(),
+            result,
+            limb_bits: 8,
+        })],
+    );
+
+    vec![
+        invert,
+        quotient,
+        quotient_predicate,
+        to_le_radix,
+        permutation_sort,
+        log,
+        big_quotient,
+        big_invert,
+    ]
+}
+
+fn memory() -> Vec<TestVector> {
+    let op = |operation: u128, index: Expression, value: Witness| MemOp {
+        operation: Expression::from_field(field(operation)),
+        index,
+        value: value.into(),
+    };
+    let constant = |value: u128| Expression::from_field(field(value));
+
+    let mut builder = CircuitBuilder::new();
+    let (a, b, index) = (builder.input(5_u128), builder.input(6_u128), builder.input(1_u128));
+    let operation = builder.input(0_u128);
+    let read = builder.witness();
+    let block = builder.build(
+        "block",
+        "Reads a value written to a block at an index given by a witness, through an operation given by a witness.",
+        vec![
+            Opcode::MemoryInit { block_id: BlockId(0), init: vec![a, b] },
+            Opcode::MemoryOp {
+                block_id: BlockId(0),
+                op: MemOp { operation: operation.into(), index: index.into(), value: read.into() },
+            },
+        ],
+    );
+
+    let mut builder = CircuitBuilder::new();
+    let (a, b, index) = (builder.input(5_u128), builder.input(6_u128), builder.input(0_u128));
+    let (read_a, read_b) = (builder.witness(), builder.witness());
+    let rom = builder.build(
+        "rom",
+        "Reads values from an initialized read-only memory.",
+        vec![
+            Opcode::MemoryInit { block_id: BlockId(0), init: vec![a, b] },
+            Opcode::MemoryOp { block_id: BlockId(0), op: op(0, index.into(), read_a) },
+            Opcode::MemoryOp { block_id: BlockId(0), op: op(0, constant(1), read_b) },
+        ],
+    );
+
+    let mut builder = CircuitBuilder::new();
+    let (a, b, index, c) = (
+        builder.input(5_u128),
+        builder.input(6_u128),
+        builder.input(1_u128),
+        builder.input(7_u128),
+    );
+    let (read_a, read_b) = (builder.witness(), builder.witness());
+    let ram = builder.build(
+        "ram",
+        "Overwrites a value of an initialized random-access memory before reading it back.",
+        vec![
+            Opcode::MemoryInit { block_id: BlockId(0), init: vec![a, b] },
+            Opcode::MemoryOp { block_id: BlockId(0), op: op(0, index.into(), read_a) },
+            Opcode::MemoryOp { block_id: BlockId(0), op: op(1, index.into(), c) },
+            Opcode::MemoryOp { block_id: BlockId(0), op: op(0, constant(1), read_b) },
+        ],
+    );
+
+    vec![block, rom, ram]
+}
+
+fn brillig() -> Vec<TestVector> {
+    let registers = [0, 1, 2, 3].map(RegisterIndex::from);
+
+    let mut builder = CircuitBuilder::new();
+    let (a, b) = (builder.input(6_u128), builder.input(4_u128));
+    let (sum, quotient) = (builder.witness(), builder.witness());
+    let field_ops = Brillig {
+        inputs: vec![BrilligInputs::Single(a.into()), BrilligInputs::Single(b.into())],
+        outputs: vec![BrilligOutputs::Simple(sum), BrilligOutputs::Simple(quotient)],
+        foreign_call_results: Vec::new(),
+        bytecode: vec![
+            brillig_vm::Opcode::BinaryFieldOp {
+                destination: registers[2],
+                op: BinaryFieldOp::Add,
+                lhs: registers[0],
+                rhs: registers[1],
+            },
+            brillig_vm::Opcode::BinaryFieldOp {
+                destination: registers[3],
+                op: BinaryFieldOp::Div,
+                lhs: registers[0],
+                rhs: registers[1],
+            },
+            brillig_vm::Opcode::Mov { destination: registers[0], source: registers[2] },
+            brillig_vm::Opcode::Mov { destination: registers[1], source: registers[3] },
+            brillig_vm::Opcode::Stop,
+        ],
+        predicate: None,
+    };
+    let field_ops = builder.build(
+        "brillig_field_ops",
+        "Adds and divides field elements in Brillig.",
+        vec![Opcode::Brillig(field_ops)],
+    );
+
+    let square = |builder: &mut CircuitBuilder, predicate: Option<Expression>| {
+        let input = builder.input(3_u128);
+        let output = builder.witness();
+        let square = Brillig {
+            inputs: vec![BrilligInputs::Single(input.into())],
+            outputs: vec![BrilligOutputs::Simple(output)],
+            foreign_call_results: Vec::new(),
+            bytecode: vec![
+                brillig_vm::Opcode::ForeignCall {
+                    function: "square".to_owned(),
+                    destinations: vec![RegisterOrMemory::RegisterIndex(registers[0])],
+                    inputs: vec![RegisterOrMemory::RegisterIndex(registers[0])],
+                },
+                brillig_vm::Opcode::Stop,
+            ],
+            predicate,
+        };
+        // The square is checked against the output of the foreign call.
+        let mut check = linear(&[(-1, output)], 0);
+        check.push_multiplication_term(FieldElement::one(), input, input);
+        vec![Opcode::Brillig(square), Opcode::AssertZero(check)]
+    };
+
+    let mut builder = CircuitBuilder::new();
+    let opcodes = square(&mut builder, None);
+    builder.foreign_calls.push(ForeignCallRecord {
+        function: "square".to_owned(),
+        inputs: vec![vec![field(3)]],
+        outputs: vec![ForeignCallValues::Single(field(9))],
+    });
+    let foreign_call = builder.build(
+        "brillig_foreign_call",
+        "Resolves a foreign call made by Brillig with the recorded response.",
+        opcodes,
+    );
+
+    let mut builder = CircuitBuilder::new();
+    let x = builder.input(0_u128);
+    let opcodes = square(&mut builder, Some(x.into()));
+    let opcodes = vec![opcodes[0].clone()];
+    let predicate = builder.build(
+        "brillig_false_predicate",
+        "Skips Brillig code whose predicate is zero, assigning zero to its outputs.",
+        opcodes,
+    );
+
+    let mut builder = CircuitBuilder::new();
+    let a = builder.input(1_u128);
+    let trap = Brillig {
+        inputs: vec![BrilligInputs::Single(a.into())],
+        outputs: Vec::new(),
+        foreign_call_results: Vec::new(),
+        bytecode: vec![brillig_vm::Opcode::Trap],
+        predicate: None,
+    };
+    let trap = builder.build(
+        "brillig_trap",
+        "Fails on Brillig code which traps.",
+        vec![Opcode::AssertZero(linear(&[(1, a)], -1)), Opcode::Brillig(trap)],
+    );
+
+    vec![field_ops, foreign_call, predicate, trap]
+}
+
+fn logic() -> Vec<TestVector> {
+    let mut builder = CircuitBuilder::new();
+    let (lhs, rhs) =
+        (builder.function_input(0b1100_u128, 4), builder.function_input(0b1010_u128, 4));
+    let (and_output, xor_output, not_output, shl_output, shr_output) = (
+        builder.witness(),
+        builder.witness(),
+        builder.witness(),
+        builder.witness(),
+        builder.witness(),
+    );
+    let bitwise = builder.build(
+        "bitwise",
+        "Computes bitwise operations on 4 bit integers.",
+        vec![
+            Opcode::BlackBoxFuncCall(BlackBoxFuncCall::AND { lhs, rhs, output: and_output }),
+            Opcode::BlackBoxFuncCall(BlackBoxFuncCall::XOR { lhs, rhs, output: xor_output }),
+            Opcode::BlackBoxFuncCall(BlackBoxFuncCall::NOT { input: lhs, output: not_output }),
+            Opcode::BlackBoxFuncCall(BlackBoxFuncCall::SHL {
+                input: lhs,
+                shift: 1,
+                output: shl_output,
+            }),
+            Opcode::BlackBoxFuncCall(BlackBoxFuncCall::SHR {
+                input: lhs,
+                shift: 2,
+                output: shr_output,
+            }),
+        ],
+    );
+
+    let mut builder = CircuitBuilder::new();
+    let (a, b, c) = (
+        builder.function_input(255_u128, 8),
+        builder.function_input(1_u128, 1),
+        builder.function_input(1_u128 << 100, 101),
+    );
+    let range = builder.build(
+        "range",
+        "Checks values fitting within their number of bits.",
+        vec![
+            Opcode::BlackBoxFuncCall(BlackBoxFuncCall::RANGE { input: a }),
+            Opcode::BlackBoxFuncCall(BlackBoxFuncCall::RangeBatch { inputs: vec![b, c] }),
+        ],
+    );
+
+    let mut builder = CircuitBuilder::new();
+    let input = builder.function_input(256_u128, 8);
+    let range_failure = builder.build(
+        "range_failure",
+        "Fails on a value which does not fit within its number of bits.",
+        vec![Opcode::BlackBoxFuncCall(BlackBoxFuncCall::RANGE { input })],
+    );
+
+    vec![bitwise, range, range_failure]
+}
+
+fn hashes() -> Vec<TestVector> {
+    const MESSAGE: &[u8] = b"abc";
+
+    type FixedLengthCall = fn(Vec<FunctionInput>, Vec<Witness>) -> BlackBoxFuncCall;
+    let fixed_length: [(&str, FixedLengthCall, usize); 6] = [
+        ("sha256", |inputs, outputs| BlackBoxFuncCall::SHA256 { inputs, outputs }, 32),
+        ("sha512", |inputs, outputs| BlackBoxFuncCall::Sha512 { inputs, outputs }, 64),
+        ("blake2s", |inputs, outputs| BlackBoxFuncCall::Blake2s { inputs, outputs }, 32),
+        ("blake2b", |inputs, outputs| BlackBoxFuncCall::Blake2b { inputs, outputs }, 64),
+        ("blake3", |inputs, outputs| BlackBoxFuncCall::Blake3 { inputs, outputs }, 32),
+        ("keccak256", |inputs, outputs| BlackBoxFuncCall::Keccak256 { inputs, outputs }, 32),
+    ];
+    let mut vectors: Vec<TestVector> = fixed_length
+        .into_iter()
+        .map(|(name, call, digest_len)| {
+            let mut builder = CircuitBuilder::new();
+            let inputs = builder.bytes(MESSAGE);
+            let outputs = builder.witnesses(digest_len);
+            builder.build(
+                name,
+                "Hashes the bytes of `abc`.",
+                vec![Opcode::BlackBoxFuncCall(call(inputs, outputs))],
+            )
+        })
+        .collect();
+
+    type VariableLengthCall =
+        fn(Vec<FunctionInput>, FunctionInput, Vec<Witness>) -> BlackBoxFuncCall;
+    let variable_length: [(&str, VariableLengthCall, usize); 5] = [
+        (
+            "sha256_variable_length",
+            |inputs, var_message_size, outputs| BlackBoxFuncCall::Sha256VariableLength {
+                inputs,
+                var_message_size,
+                outputs,
+            },
+            32,
+        ),
+        (
+            "sha512_variable_length",
+            |inputs, var_message_size, outputs| BlackBoxFuncCall::Sha512VariableLength {
+                inputs,
+                var_message_size,
+                outputs,
+            },
+            64,
+        ),
+        (
+            "blake2s_variable_length",
+            |inputs, var_message_size, outputs| BlackBoxFuncCall::Blake2sVariableLength {
+                inputs,
+                var_message_size,
+                outputs,
+            },
+            32,
+        ),
+        (
+            "blake3_variable_length",
+            |inputs, var_message_size, outputs| BlackBoxFuncCall::Blake3VariableLength {
+                inputs,
+                var_message_size,
+                outputs,
+            },
+            32,
+        ),
+        (
+            "keccak256_variable_length",
+            |inputs, var_message_size, outputs| BlackBoxFuncCall::Keccak256VariableLength {
+                inputs,
+                var_message_size,
+                outputs,
+            },
+            32,
+        ),
+    ];
+    vectors.extend(variable_length.into_iter().map(|(name, call, digest_len)| {
+        let mut builder = CircuitBuilder::new();
+        let inputs = builder.bytes(b"abcdef");
+        let var_message_size = builder.function_input(3_u128, 32);
+        let outputs = builder.witnesses(digest_len);
+        builder.build(
+            name,
+            "Hashes the first 3 bytes of `abcdef`.",
+            vec![Opcode::BlackBoxFuncCall(call(inputs, var_message_size, outputs))],
+        )
+    }));
+
+    let mut builder = CircuitBuilder::new();
+    let inputs = (0..25_u128).map(|lane| builder.function_input(lane, 64)).collect();
+    let outputs = builder.witnesses(25);
+    vectors.push(builder.build(
+        "keccakf1600",
+        "Applies the keccak-f[1600] permutation to the lanes 0 to 24.",
+        vec![Opcode::BlackBoxFuncCall(BlackBoxFuncCall::Keccakf1600 { inputs, outputs })],
+    ));
+
+    // The padded block of the message `abc`, compressed from the initial hash values of SHA256.
+    let mut builder = CircuitBuilder::new();
+    let mut block = [0_u128; 16];
+    block[0] = 0x6162_6380;
+    block[15] = 24;
+    let inputs = block.iter().map(|word| builder.function_input(*word, 32)).collect();
+    let hash_values = [
+        0x6a09e667_u128,
+        0xbb67ae85,
+        0x3c6ef372,
+        0xa54ff53a,
+        0x510e527f,
+        0x9b05688c,
+        0x1f83d9ab,
+        0x5be0cd19,
+    ]
+    .iter()
+    .map(|word| builder.function_input(*word, 32))
+    .collect();
+    let outputs = builder.witnesses(8);
+    vectors.push(builder.build(
+        "sha256_compression",
+        "Compresses the padded block of `abc` from the initial hash values of SHA256.",
+        vec![Opcode::BlackBoxFuncCall(BlackBoxFuncCall::Sha256Compression {
+            inputs,
+            hash_values,
+            outputs,
+        })],
+    ));
+
+    let mut builder = CircuitBuilder::new();
+    let inputs = (0..4_u128).map(|value| builder.function_input(value, 254)).collect();
+    let outputs = builder.witnesses(4);
+    vectors.push(builder.build(
+        "poseidon2_permutation",
+        "Applies the Poseidon2 permutation of width 4 to the state [0, 1, 2, 3].",
+        vec![Opcode::BlackBoxFuncCall(BlackBoxFuncCall::Poseidon2Permutation {
+            inputs,
+            outputs,
+            len: 4,
+        })],
+    ));
+
+    let mut builder = CircuitBuilder::new();
+    let inputs = vec![builder.function_input(1_u128, 254), builder.function_input(2_u128, 254)];
+    let outputs = (builder.witness(), builder.witness());
+    vectors.push(builder.build(
+        "pedersen",
+        "Computes the Pedersen commitment to [1, 2].",
+        vec![Opcode::BlackBoxFuncCall(BlackBoxFuncCall::Pedersen {
+            inputs,
+            domain_separator: 0,
+            outputs,
+        })],
+    ));
+
+    let mut builder = CircuitBuilder::new();
+    let inputs = builder.bytes(MESSAGE);
+    let output = builder.witness();
+    #[allow(deprecated)]
+    let hash_to_field_128 = BlackBoxFuncCall::HashToField128Security { inputs, output };
+    vectors.push(builder.build(
+        "hash_to_field_128_security",
+        "Hashes the bytes of `abc` to a field element.",
+        vec![Opcode::BlackBoxFuncCall(hash_to_field_128)],
+    ));
+
+    let domain_separator = b"ACVM-CONFORMANCE".to_vec();
+    let mut builder = CircuitBuilder::new();
+    let inputs = builder.bytes(MESSAGE);
+    let output = builder.witness();
+    vectors.push(builder.build(
+        "hash_to_field",
+        "Hashes the bytes of `abc` to a field element following RFC 9380.",
+        vec![Opcode::BlackBoxFuncCall(BlackBoxFuncCall::HashToField {
+            inputs,
+            domain_separator: domain_separator.clone(),
+            output,
+        })],
+    ));
+
+    let mut builder = CircuitBuilder::new();
+    let inputs = builder.bytes(MESSAGE);
+    let outputs = builder.witnesses(64);
+    vectors.push(builder.build(
+        "hash_to_curve_secp256k1",
+        "Hashes the bytes of `abc` to a point of secp256k1 following RFC 9380.",
+        vec![Opcode::BlackBoxFuncCall(BlackBoxFuncCall::HashToCurveSecp256k1 {
+            inputs,
+            domain_separator: domain_separator.clone(),
+            outputs,
+        })],
+    ));
+
+    let mut builder = CircuitBuilder::new();
+    let inputs = builder.bytes(MESSAGE);
+    let outputs = (builder.witness(), builder.witness());
+    vectors.push(builder.build(
+        "hash_to_embedded_curve",
+        "Hashes the bytes of `abc` to a point of the embedded curve.",
+        vec![Opcode::BlackBoxFuncCall(BlackBoxFuncCall::HashToEmbeddedCurve {
+            inputs,
+            domain_separator,
+            outputs,
+        })],
+    ));
+
+    let mut builder = CircuitBuilder::new();
+    let inputs = builder.bytes(MESSAGE);
+    let iv = builder.bytes(&[0; 16]);
+    let key = builder.bytes(&(0..16).collect::<Vec<u8>>());
+    let outputs = builder.witnesses(16);
+    vectors.push(builder.build(
+        "aes128_encrypt",
+        "Encrypts the bytes of `abc` with AES-128 in CBC mode.",
+        vec![Opcode::BlackBoxFuncCall(BlackBoxFuncCall::Aes128Encrypt {
+            inputs,
+            iv,
+            key,
+            outputs,
+        })],
+    ));
+
+    vectors
+}
+
+fn signatures() -> Vec<TestVector> {
+    const MESSAGE: &[u8] = b"abc";
+
+    let secret_key = field(0xdead_beef);
+    let (public_key_x, public_key_y) = MockBackend::schnorr_public_key(secret_key);
+    let (signature_s, signature_e) = MockBackend::schnorr_sign(secret_key, field(0x1234), MESSAGE);
+    let mut builder = CircuitBuilder::new();
+    let call = BlackBoxFuncCall::SchnorrVerify {
+        public_key_x: builder.function_input(public_key_x, 254),
+        public_key_y: builder.function_input(public_key_y, 254),
+        signature_s: builder.function_input(signature_s, 254),
+        signature_e: builder.function_input(signature_e, 254),
+        message: builder.bytes(MESSAGE),
+        output: builder.witness(),
+    };
+    let schnorr = builder.build(
+        "schnorr_verify",
+        "Verifies a valid Schnorr signature. The signature scheme is defined by the backend, here the mock backend of `acvm::testing`.",
+        vec![Opcode::BlackBoxFuncCall(call)],
+    );
+
+    let hashed_message =
+        bytes_from_hex("3a73f4123a5cd2121f21cd7e8d358835476949d035d9c2da6806b4633ac8c1e2");
+    let ecdsa_public_key_x =
+        bytes_from_hex("a0434d9e47f3c86235477c7b1ae6ae5d3442d49b1943c2b752a68e2a47e247c7");
+    let ecdsa_public_key_y =
+        bytes_from_hex("893aba425419bc27a3b6c7e693a24c696f794c2ed877a1593cbee53b037368d7");
+    let ecdsa_signature = bytes_from_hex("e5081c80ab427dc370346f4a0e31aa2bad8d9798c38061db9ae55a4e8df454fd28119894344e71b78770cc931d61f480ecbb0b89d6eb69690161e49a715fcd55");
+
+    let mut builder = CircuitBuilder::new();
+    let call = BlackBoxFuncCall::EcdsaSecp256k1 {
+        public_key_x: builder.bytes(&ecdsa_public_key_x),
+        public_key_y: builder.bytes(&ecdsa_public_key_y),
+        signature: builder.bytes(&ecdsa_signature),
+        hashed_message: builder.bytes(&hashed_message),
+        output: builder.witness(),
+    };
+    let ecdsa = builder.build(
+        "ecdsa_secp256k1",
+        "Verifies a valid ECDSA signature over secp256k1.",
+        vec![Opcode::BlackBoxFuncCall(call)],
+    );
+
+    let mut invalid_hashed_message = hashed_message.clone();
+    invalid_hashed_message[0] ^= 1;
+    let mut builder = CircuitBuilder::new();
+    let call = BlackBoxFuncCall::EcdsaSecp256k1 {
+        public_key_x: builder.bytes(&ecdsa_public_key_x),
+        public_key_y: builder.bytes(&ecdsa_public_key_y),
+        signature: builder.bytes(&ecdsa_signature),
+        hashed_message: builder.bytes(&invalid_hashed_message),
+        output: builder.witness(),
+    };
+    let ecdsa_invalid = builder.build(
+        "ecdsa_secp256k1_invalid",
+        "Rejects an ECDSA signature over secp256k1 of another message.",
+        vec![Opcode::BlackBoxFuncCall(call)],
+    );
+
+    let mut builder = CircuitBuilder::new();
+    let call = BlackBoxFuncCall::EcdsaSecp256k1Recover {
+        signature: builder.bytes(&ecdsa_signature),
+        recovery_id: builder.function_input(0_u128, 1),
+        hashed_message: builder.bytes(&hashed_message),
+        public_key_x: builder.witnesses(32),
+        public_key_y: builder.witnesses(32),
+    };
+    let ecdsa_recover = builder.build(
+        "ecdsa_secp256k1_recover",
+        "Recovers the public key of an ECDSA signature over secp256k1.",
+        vec![Opcode::BlackBoxFuncCall(call)],
+    );
+
+    // Test vector 2 of RFC 8032.
+    let mut builder = CircuitBuilder::new();
+    let call = BlackBoxFuncCall::EddsaEd25519 {
+        public_key: builder
+            .bytes(&bytes_from_hex("3d4017c3e843895a92b70aa74d1b7ebc9c982ccf2ec4968cc0cd55f12af4660c")),
+        signature: builder.bytes(&bytes_from_hex("92a009a9f0d4cab8720e820b5f642540a2b27b5416503f8fb3762223ebdb69da085ac1e43e15996e458f3613d0f11d8c387b2eaeb4302aeeb00d291612bb0c00")),
+        message: builder.bytes(&[0x72]),
+        output: builder.witness(),
+    };
+    let ed25519 = builder.build(
+        "eddsa_ed25519",
+        "Verifies the signature of test vector 2 of RFC 8032.",
+        vec![Opcode::BlackBoxFuncCall(call)],
+    );
+
+    // With the identity as public key, `R8 = B8` and `s = 1` is a valid signature of any message.
+    let base8_x =
+        FieldElement::from_hex("0bb77a6ad63e739b4eacb2e09d6277c12ab8d8010534e0b62893f3f6bb957051")
+            .unwrap();
+    let base8_y =
+        FieldElement::from_hex("25797203f7a0b24925572e1cd16bf9edfce0051fb9e133774b3c257a872d7d8b")
+            .unwrap();
+    let mut builder = CircuitBuilder::new();
+    let call = BlackBoxFuncCall::EddsaBabyJubjub {
+        public_key_x: builder.function_input(0_u128, 254),
+        public_key_y: builder.function_input(1_u128, 254),
+        signature_s: builder.function_input(1_u128, 254),
+        signature_r8_x: builder.function_input(base8_x, 254),
+        signature_r8_y: builder.function_input(base8_y, 254),
+        message: builder.function_input(1234_u128, 254),
+        output: builder.witness(),
+    };
+    let baby_jubjub = builder.build(
+        "eddsa_baby_jubjub",
+        "Verifies an EdDSA-Poseidon signature for the identity public key.",
+        vec![Opcode::BlackBoxFuncCall(call)],
+    );
+
+    vec![schnorr, ecdsa, ecdsa_invalid, ecdsa_recover, ed25519, baby_jubjub]
+}
+
+#[allow(deprecated)]
+fn curves() -> Vec<TestVector> {
+    let mut builder = CircuitBuilder::new();
+    let (one, two, three) = (
+        builder.function_input(1_u128, 254),
+        builder.function_input(2_u128, 254),
+        builder.function_input(3_u128, 254),
+    );
+    let (g, g2) = ((builder.witness(), builder.witness()), (builder.witness(), builder.witness()));
+    let (doubled, sum, msm) = (
+        (builder.witness(), builder.witness()),
+        (builder.witness(), builder.witness()),
+        (builder.witness(), builder.witness()),
+    );
+    let coordinate = |witness: Witness| FunctionInput { witness, num_bits: 254 };
+    let opcodes = vec![
+        BlackBoxFuncCall::FixedBaseScalarMul { input: one, outputs: g },
+        BlackBoxFuncCall::FixedBaseScalarMul { input: two, outputs: g2 },
+        BlackBoxFuncCall::EmbeddedCurveDouble {
+            input_x: coordinate(g.0),
+            input_y: coordinate(g.1),
+            outputs: doubled,
+        },
+        BlackBoxFuncCall::EmbeddedCurveAdd {
+            input1_x: coordinate(g.0),
+            input1_y: coordinate(g.1),
+            input2_x: coordinate(g2.0),
+            input2_y: coordinate(g2.1),
+            outputs: sum,
+        },
+        BlackBoxFuncCall::MultiScalarMul {
+            points: vec![coordinate(g.0), coordinate(g.1), coordinate(g2.0), coordinate(g2.1)],
+            scalars: vec![three, one],
+            outputs: msm,
+        },
+    ];
+    let embedded_curve = builder.build(
+        "embedded_curve_ops",
+        "Computes multiples of the generator of the embedded curve: G, 2G, G + G, G + 2G and 3G + 2G.",
+        opcodes.into_iter().map(Opcode::BlackBoxFuncCall).collect(),
+    );
+
+    vec![embedded_curve]
+}
+
+fn bigint() -> Vec<TestVector> {
+    // The modulus 2^61 - 1 in little-endian order.
+    let modulus = (2_u64.pow(61) - 1).to_le_bytes().to_vec();
+    let mut builder = CircuitBuilder::new();
+    let a = builder.bytes(&u64::MAX.to_le_bytes());
+    let b = builder.bytes(&[3]);
+    let outputs = builder.witnesses(8);
+    let opcodes = vec![
+        BlackBoxFuncCall::BigIntFromLeBytes { inputs: a, modulus: modulus.clone(), output: 0 },
+        BlackBoxFuncCall::BigIntFromLeBytes { inputs: b, modulus, output: 1 },
+        BlackBoxFuncCall::BigIntAdd { lhs: 0, rhs: 1, output: 2 },
+        BlackBoxFuncCall::BigIntMul { lhs: 2, rhs: 0, output: 3 },
+        BlackBoxFuncCall::BigIntDiv { lhs: 3, rhs: 1, output: 4 },
+        BlackBoxFuncCall::BigIntToLeBytes { input: 4, outputs },
+    ];
+    let bigint = builder.build(
+        "bigint",
+        "Computes `(a + b) * a / b` modulo `2^61 - 1` for `a = 2^64 - 1` and `b = 3`.",
+        opcodes.into_iter().map(Opcode::BlackBoxFuncCall).collect(),
+    );
+
+    vec![bigint]
+}
+
+fn recursion() -> Vec<TestVector> {
+    let mut builder = CircuitBuilder::new();
+    let call = BlackBoxFuncCall::RecursiveAggregation {
+        verification_key: vec![builder.function_input(1_u128, 254)],
+        proof: vec![builder.function_input(2_u128, 254)],
+        public_inputs: vec![builder.function_input(3_u128, 254)],
+        key_hash: builder.function_input(4_u128, 254),
+        input_aggregation_object: Some(vec![
+            builder.function_input(5_u128, 254),
+            builder.function_input(6_u128, 254),
+        ]),
+        output_aggregation_object: builder.witnesses(2),
+    };
+    let recursive_aggregation = builder.build(
+        "recursive_aggregation",
+        "Aggregates a proof. The aggregation is defined by the backend, here the mock backend of `acvm::testing`, which forwards the input aggregation object.",
+        vec![Opcode::BlackBoxFuncCall(call)],
+    );
+
+    vec![recursive_aggregation]
+}
+
+/// The files of the corpus, along with the test vectors they hold.
+fn corpus() -> Vec<(&'static str, Vec<TestVector>)> {
+    vec![
+        ("arithmetic", arithmetic()),
+        ("directives", directives()),
+        ("memory", memory()),
+        ("brillig", brillig()),
+        ("logic", logic()),
+        ("hashes", hashes()),
+        ("signatures", signatures()),
+        ("curves", curves()),
+        ("bigint", bigint()),
+        ("recursion", recursion()),
+    ]
+}
+
+fn corpus_path(file: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/conformance").join(format!("{file}.json"))
+}
+
+#[test]
+fn corpus_is_up_to_date() {
+    let update = std::env::var_os("UPDATE_CONFORMANCE_VECTORS").is_some();
+    for (file, test_vectors) in corpus() {
+        let path = corpus_path(file);
+        let json = write_test_vectors(&test_vectors) + "\n";
+        if update {
+            std::fs::write(&path, json).unwrap();
+        } else {
+            let stored = std::fs::read_to_string(&path).unwrap();
+            assert_eq!(
+                read_test_vectors(&stored).unwrap(),
+                test_vectors,
+                "{file}.json is out of date, regenerate it with UPDATE_CONFORMANCE_VECTORS=1"
+            );
+        }
+    }
+}
+
+#[test]
+fn stored_corpus_passes() {
+    for (file, _) in corpus() {
+        let stored = std::fs::read_to_string(corpus_path(file)).unwrap();
+        for test_vector in read_test_vectors(&stored).unwrap() {
+            test_vector.check(MockBackend::default()).unwrap();
+        }
+    }
+}
+
+#[test]
+fn corpus_covers_every_opcode_and_black_box_function() {
+    let mut opcodes = BTreeSet::new();
+    let mut black_box_functions = BTreeSet::new();
+    for (_, test_vectors) in corpus() {
+        for test_vector in test_vectors {
+            for opcode in Circuit::read(&*test_vector.circuit).unwrap().opcodes {
+                match &opcode {
+                    Opcode::BlackBoxFuncCall(call) => {
+                        black_box_functions.insert(call.get_black_box_func().name());
+                    }
+                    Opcode::Directive(directive) => {
+                        opcodes.insert(format!("directive {}", directive.name()));
+                    }
+                    _ => (),
+                }
+                opcodes.insert(opcode_kind(&opcode).to_owned());
+            }
+        }
+    }
+
+    let expected_opcodes = [
+        "assert_zero",
+        "black_box_func_call",
+        "memory_init",
+        "memory_op",
+        "brillig",
+        "directive",
+        "directive invert",
+        "directive quotient",
+        "directive to_le_radix",
+        "directive permutation_sort",
+        "directive log",
+        "directive big_quotient",
+        "directive big_invert",
+    ];
+    assert_eq!(opcodes, expected_opcodes.iter().map(|name| name.to_string()).collect());
+
+    let missing: Vec<_> = ALL_BLACK_BOX_FUNCTIONS
+        .iter()
+        .map(BlackBoxFunc::name)
+        .filter(|name| !black_box_functions.contains(name))
+        .collect();
+    assert!(missing.is_empty(), "black box functions missing from the corpus: {missing:?}");
+}
+
+fn opcode_kind(opcode: &Opcode) -> &'static str {
+    match opcode {
+        Opcode::AssertZero(_) => "assert_zero",
+        Opcode::BlackBoxFuncCall(_) => "black_box_func_call",
+        Opcode::Directive(_) => "directive",
+        Opcode::MemoryInit { .. } => "memory_init",
+        Opcode::MemoryOp { .. } => "memory_op",
+        Opcode::Brillig(_) => "brillig",
+    }
+}
+
+const ALL_BLACK_BOX_FUNCTIONS: [BlackBoxFunc; 35] = [
+    BlackBoxFunc::AND,
+    BlackBoxFunc::XOR,
+    BlackBoxFunc::RANGE,
+    BlackBoxFunc::SHA256,
+    BlackBoxFunc::Sha512,
+    BlackBoxFunc::Blake2s,
+    BlackBoxFunc::Blake3,
+    BlackBoxFunc::SchnorrVerify,
+    BlackBoxFunc::Pedersen,
+    BlackBoxFunc::HashToField128Security,
+    BlackBoxFunc::EcdsaSecp256k1,
+    BlackBoxFunc::FixedBaseScalarMul,
+    BlackBoxFunc::Keccak256,
+    BlackBoxFunc::RecursiveAggregation,
+    BlackBoxFunc::Poseidon2Permutation,
+    BlackBoxFunc::EddsaEd25519,
+    BlackBoxFunc::EddsaBabyJubjub,
+    BlackBoxFunc::EmbeddedCurveAdd,
+    BlackBoxFunc::EmbeddedCurveDouble,
+    BlackBoxFunc::MultiScalarMul,
+    BlackBoxFunc::BigIntAdd,
+    BlackBoxFunc::BigIntMul,
+    BlackBoxFunc::BigIntDiv,
+    BlackBoxFunc::BigIntFromLeBytes,
+    BlackBoxFunc::BigIntToLeBytes,
+    BlackBoxFunc::Keccakf1600,
+    BlackBoxFunc::Aes128Encrypt,
+    BlackBoxFunc::Sha256Compression,
+    BlackBoxFunc::HashToField,
+    BlackBoxFunc::HashToCurveSecp256k1,
+    BlackBoxFunc::HashToEmbeddedCurve,
+    BlackBoxFunc::EcdsaSecp256k1Recover,
+    BlackBoxFunc::RangeBatch,
+    BlackBoxFunc::NOT,
+    BlackBoxFunc::SHL,
+];
@@ -0,0 +1,327 @@
+//! Differential tests running each black box function with a fallback both through its native solver
+//! and through the circuit its fallback is compiled into, on inputs drawn from a seeded generator.
+//!
+//! Both circuits must either agree on every output or both be unsatisfiable.
+use std::collections::BTreeMap;
+
+use acir::{
+    circuit::{
+        opcodes::{BlackBoxFuncCall, FunctionInput},
+        Circuit, Opcode,
+    },
+    native_types::{Witness, WitnessMap},
+    FieldElement,
+};
+use acvm::{
+    compiler::{compile, BackendCapabilities, CircuitSimplifier},
+    pwg::{PartialWitnessGeneratorStatus, ACVM},
+    testing::MockBackend,
+    Language,
+};
+use k256::{
+    ecdsa::{signature::Signer, Signature, SigningKey},
+    elliptic_curve::sec1::ToEncodedPoint,
+};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use sha2::{Digest, Sha256};
+
+/// Number of random calls each black box function is checked against.
+const NUM_SAMPLES: usize = 4;
+
+/// Allocates the witnesses of a black box call, keeping track of the values assigned to its inputs.
+#[derive(Clone, Default)]
+struct CallWitnesses {
+    num_witness: u32,
+    initial_witness: BTreeMap<Witness, FieldElement>,
+}
+
+impl CallWitnesses {
+    fn input(&mut self, value: FieldElement, num_bits: u32) -> FunctionInput {
+        self.num_witness += 1;
+        let witness = Witness(self.num_witness);
+        self.initial_witness.insert(witness, value);
+        FunctionInput { witness, num_bits }
+    }
+
+    fn inputs(&mut self, values: &[u128], num_bits: u32) -> Vec<FunctionInput> {
+        values.iter().map(|&value| self.input(FieldElement::from(value), num_bits)).collect()
+    }
+
+    fn outputs(&mut self, count: u32) -> Vec<Witness> {
+        (0..count)
+            .map(|_| {
+                self.num_witness += 1;
+                Witness(self.num_witness)
+            })
+            .collect()
+    }
+}
+
+/// Returns the values of the outputs of `opcodes` once solved from `initial_witness`,
+/// or `None` if they cannot be satisfied.
+fn solve_outputs(
+    opcodes: Vec<Opcode>,
+    initial_witness: WitnessMap,
+    outputs: &[Witness],
+) -> Option<Vec<FieldElement>> {
+    let mut acvm = ACVM::new(MockBackend::default(), opcodes, initial_witness);
+    let solver_status = acvm.solve().ok()?;
+    assert_eq!(solver_status, PartialWitnessGeneratorStatus::Solved, "should be fully solved");
+    Some(outputs.iter().map(|output| acvm.witness_map()[output]).collect())
+}
+
+/// Asserts that `call` produces the same outputs when solved natively as when replaced by its fallback,
+/// returning these outputs or `None` if neither circuit is satisfied.
+fn assert_fallback_matches_native(
+    call: BlackBoxFuncCall,
+    witnesses: CallWitnesses,
+) -> Option<Vec<FieldElement>> {
+    let black_box_func = call.get_black_box_func();
+    let outputs = call.get_outputs_vec();
+    let num_inputs = witnesses.initial_witness.len() as u32;
+    let initial_witness = WitnessMap::from(witnesses.initial_witness);
+    let circuit = Circuit {
+        current_witness_index: witnesses.num_witness,
+        opcodes: vec![Opcode::BlackBoxFuncCall(call)],
+        ..Circuit::default()
+    };
+
+    // Fallbacks are not reduced to width 3, which only slows down solving without changing their outputs.
+    let capabilities = BackendCapabilities::new(Language::R1CS).without(black_box_func);
+    let fallback_circuit =
+        compile(circuit.clone(), &capabilities, &CircuitSimplifier::new(num_inputs))
+            .unwrap_or_else(|error| panic!("{black_box_func} should have a fallback: {error}"))
+            .0;
+
+    let native = solve_outputs(circuit.opcodes, initial_witness.clone(), &outputs);
+    let fallback = solve_outputs(fallback_circuit.opcodes, initial_witness, &outputs);
+    assert_eq!(fallback, native, "fallback of {black_box_func} diverges from its native solver");
+    native
+}
+
+fn random_bytes(rng: &mut StdRng, len: usize) -> Vec<u128> {
+    (0..len).map(|_| rng.gen::<u8>() as u128).collect()
+}
+
+/// Returns random hash inputs of mixed bit sizes, each contributing a different number of bytes to the message.
+fn random_hash_inputs(rng: &mut StdRng, witnesses: &mut CallWitnesses) -> Vec<FunctionInput> {
+    let num_inputs = rng.gen_range(0..=40);
+    (0..num_inputs)
+        .map(|_| {
+            let num_bits = [1, 8, 8, 8, 12, 32, 64][rng.gen_range(0..7)];
+            let value = rng.gen::<u64>() as u128 & ((1 << num_bits) - 1);
+            witnesses.input(FieldElement::from(value), num_bits)
+        })
+        .collect()
+}
+
+#[test]
+fn bitwise_fallbacks_match_native_solver() {
+    let mut rng = StdRng::seed_from_u64(1);
+    for _ in 0..NUM_SAMPLES {
+        let num_bits = [1, 8, 32, 64][rng.gen_range(0..4)];
+        let mut random_input = |witnesses: &mut CallWitnesses| {
+            let value = rng.gen::<u64>() as u128 & ((1 << num_bits) - 1);
+            witnesses.input(FieldElement::from(value), num_bits)
+        };
+
+        let mut witnesses = CallWitnesses::default();
+        let (lhs, rhs) = (random_input(&mut witnesses), random_input(&mut witnesses));
+        let output = witnesses.outputs(1)[0];
+        let binary_calls = [
+            BlackBoxFuncCall::AND { lhs, rhs, output },
+            BlackBoxFuncCall::XOR { lhs, rhs, output },
+        ];
+        let input = lhs;
+        let shift = rng.gen_range(0..=num_bits + 1);
+        let unary_calls = [
+            BlackBoxFuncCall::NOT { input, output },
+            BlackBoxFuncCall::SHL { input, shift, output },
+            BlackBoxFuncCall::SHR { input, shift, output },
+        ];
+        for call in binary_calls.into_iter().chain(unary_calls) {
+            assert!(assert_fallback_matches_native(call, witnesses.clone()).is_some());
+        }
+    }
+}
+
+#[test]
+fn range_fallbacks_match_native_solver() {
+    let mut rng = StdRng::seed_from_u64(2);
+    for _ in 0..NUM_SAMPLES {
+        // Values are drawn from twice the range so that roughly half of them exceed it.
+        let num_bits = rng.gen_range(1..=64);
+        let values: Vec<u128> =
+            (0..3).map(|_| rng.gen::<u128>() & ((1 << (num_bits + 1)) - 1)).collect();
+        let fits = values.iter().all(|value| value >> num_bits == 0);
+
+        let mut witnesses = CallWitnesses::default();
+        let inputs = witnesses.inputs(&values, num_bits);
+        let call = BlackBoxFuncCall::RangeBatch { inputs: inputs.clone() };
+        let satisfied = assert_fallback_matches_native(call, witnesses).is_some();
+        assert_eq!(satisfied, fits, "range of {num_bits} bits on {values:?}");
+
+        for (input, value) in inputs.into_iter().zip(values) {
+            let witnesses = CallWitnesses {
+                num_witness: input.witness.witness_index(),
+                initial_witness: BTreeMap::from([(input.witness, FieldElement::from(value))]),
+            };
+            let satisfied =
+                assert_fallback_matches_native(BlackBoxFuncCall::RANGE { input }, witnesses)
+                    .is_some();
+            assert_eq!(satisfied, value >> num_bits == 0, "range of {num_bits} bits on {value}");
+        }
+    }
+}
+
+#[test]
+fn hash_fallbacks_match_native_solver() {
+    type HashCall = fn(Vec<FunctionInput>, Vec<Witness>) -> BlackBoxFuncCall;
+    let hash_calls: [(HashCall, u32); 6] = [
+        (|inputs, outputs| BlackBoxFuncCall::SHA256 { inputs, outputs }, 32),
+        (|inputs, outputs| BlackBoxFuncCall::Sha512 { inputs, outputs }, 64),
+        (|inputs, outputs| BlackBoxFuncCall::Blake2b { inputs, outputs }, 64),
+        (|inputs, outputs| BlackBoxFuncCall::Blake2s { inputs, outputs }, 32),
+        (|inputs, outputs| BlackBoxFuncCall::Blake3 { inputs, outputs }, 32),
+        (|inputs, outputs| BlackBoxFuncCall::Keccak256 { inputs, outputs }, 32),
+    ];
+
+    let mut rng = StdRng::seed_from_u64(3);
+    for _ in 0..NUM_SAMPLES {
+        for (hash_call, digest_len) in hash_calls {
+            let mut witnesses = CallWitnesses::default();
+            let inputs = random_hash_inputs(&mut rng, &mut witnesses);
+            let outputs = witnesses.outputs(digest_len);
+            assert!(assert_fallback_matches_native(hash_call(inputs, outputs), witnesses).is_some());
+        }
+    }
+}
+
+#[test]
+fn variable_length_hash_fallbacks_match_native_solver() {
+    type VariableLengthHashCall =
+        fn(Vec<FunctionInput>, FunctionInput, Vec<Witness>) -> BlackBoxFuncCall;
+    let hash_calls: [(VariableLengthHashCall, u32); 2] = [
+        (
+            |inputs, var_message_size, outputs| BlackBoxFuncCall::Keccak256VariableLength {
+                inputs,
+                var_message_size,
+                outputs,
+            },
+            32,
+        ),
+        (
+            |inputs, var_message_size, outputs| BlackBoxFuncCall::Sha512VariableLength {
+                inputs,
+                var_message_size,
+                outputs,
+            },
+            64,
+        ),
+    ];
+
+    let mut rng = StdRng::seed_from_u64(4);
+    for _ in 0..NUM_SAMPLES {
+        for (hash_call, digest_len) in hash_calls {
+            let mut witnesses = CallWitnesses::default();
+            let message_len = rng.gen_range(0..=150);
+            let inputs = witnesses.inputs(&random_bytes(&mut rng, message_len), 8);
+            let length = rng.gen_range(0..=message_len) as u128;
+            let var_message_size = witnesses.input(FieldElement::from(length), 32);
+            let outputs = witnesses.outputs(digest_len);
+            let call = hash_call(inputs, var_message_size, outputs);
+            assert!(assert_fallback_matches_native(call, witnesses).is_some());
+        }
+    }
+}
+
+#[test]
+fn permutation_fallbacks_match_native_solver() {
+    let mut rng = StdRng::seed_from_u64(5);
+    for _ in 0..NUM_SAMPLES {
+        let mut witnesses = CallWitnesses::default();
+        let lanes: Vec<u128> = (0..25).map(|_| rng.gen::<u64>() as u128).collect();
+        let inputs = witnesses.inputs(&lanes, 64);
+        let outputs = witnesses.outputs(25);
+        let call = BlackBoxFuncCall::Keccakf1600 { inputs, outputs };
+        assert!(assert_fallback_matches_native(call, witnesses).is_some());
+
+        let mut witnesses = CallWitnesses::default();
+        let words: Vec<u128> = (0..24).map(|_| rng.gen::<u32>() as u128).collect();
+        let inputs = witnesses.inputs(&words[..16], 32);
+        let hash_values = witnesses.inputs(&words[16..], 32);
+        let outputs = witnesses.outputs(8);
+        let call = BlackBoxFuncCall::Sha256Compression { inputs, hash_values, outputs };
+        assert!(assert_fallback_matches_native(call, witnesses).is_some());
+
+        for len in [3, 4] {
+            let mut witnesses = CallWitnesses::default();
+            let inputs = (0..len)
+                .map(|_| {
+                    let bytes: [u8; 32] = rng.gen();
+                    witnesses.input(FieldElement::from_be_bytes_reduce(&bytes), 254)
+                })
+                .collect();
+            let outputs = witnesses.outputs(len);
+            let call = BlackBoxFuncCall::Poseidon2Permutation { inputs, outputs, len };
+            assert!(assert_fallback_matches_native(call, witnesses).is_some());
+        }
+    }
+}
+
+#[test]
+fn aes128_encrypt_fallback_matches_native_solver() {
+    let mut rng = StdRng::seed_from_u64(6);
+    // Each block encrypted through the fallback is slow to solve, so fewer and shorter plaintexts are checked.
+    for _ in 0..NUM_SAMPLES / 2 {
+        let mut witnesses = CallWitnesses::default();
+        let plaintext_len = rng.gen_range(0..=20);
+        let inputs = witnesses.inputs(&random_bytes(&mut rng, plaintext_len), 8);
+        let iv = witnesses.inputs(&random_bytes(&mut rng, 16), 8);
+        let key = witnesses.inputs(&random_bytes(&mut rng, 16), 8);
+        let outputs = witnesses.outputs((plaintext_len as u32 / 16 + 1) * 16);
+        let call = BlackBoxFuncCall::Aes128Encrypt { inputs, iv, key, outputs };
+        assert!(assert_fallback_matches_native(call, witnesses).is_some());
+    }
+}
+
+#[test]
+fn ecdsa_secp256k1_fallback_matches_native_solver() {
+    let mut rng = StdRng::seed_from_u64(7);
+    // Verifying a signature through its fallback is slow, so fewer of them are checked.
+    for _ in 0..NUM_SAMPLES / 2 {
+        let signing_key = SigningKey::random(&mut rng);
+        let message =
+            random_bytes(&mut rng, 32).into_iter().map(|byte| byte as u8).collect::<Vec<_>>();
+        let signature: Signature = signing_key.sign(&message);
+        // High `s` values are rejected, so only half of the signatures are normalized to test both.
+        let signature =
+            if rng.gen() { signature.normalize_s().unwrap_or(signature) } else { signature };
+        let is_low_s = signature.normalize_s().is_none();
+
+        let public_key = signing_key.verifying_key().to_encoded_point(false);
+        let mut hashed_message = Sha256::digest(&message).to_vec();
+        let tamper = rng.gen::<bool>();
+        if tamper {
+            hashed_message[rng.gen_range(0..32)] ^= 1 << rng.gen_range(0..8);
+        }
+
+        let to_values = |bytes: &[u8]| bytes.iter().map(|&byte| byte as u128).collect::<Vec<_>>();
+        let mut witnesses = CallWitnesses::default();
+        let public_key_x = witnesses.inputs(&to_values(public_key.x().unwrap()), 8);
+        let public_key_y = witnesses.inputs(&to_values(public_key.y().unwrap()), 8);
+        let signature = witnesses.inputs(&to_values(signature.as_ref()), 8);
+        let hashed_message = witnesses.inputs(&to_values(&hashed_message), 8);
+        let output = witnesses.outputs(1)[0];
+        let call = BlackBoxFuncCall::EcdsaSecp256k1 {
+            public_key_x,
+            public_key_y,
+            signature,
+            hashed_message,
+            output,
+        };
+
+        let outputs = assert_fallback_matches_native(call, witnesses).expect("public key is valid");
+        assert_eq!(outputs[0].is_one(), is_low_s && !tamper);
+    }
+}
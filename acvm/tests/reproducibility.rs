@@ -0,0 +1,172 @@
+//! Regression tests checking that compiling a circuit always produces the same bytes, on every platform.
+//!
+//! Verification keys are published for compiled circuits, which must be reproducible from their source.
+//! Each circuit of the corpus is compiled and serialized, and the digest of the serialized circuit is
+//! compared against the digest recorded when the test was written.
+use acir::{
+    circuit::{
+        opcodes::{BlackBoxFuncCall, BlockId, FunctionInput, MemOp},
+        Circuit, Opcode, PublicInputs,
+    },
+    native_types::{Expression, Witness},
+    BlackBoxFunc, FieldElement,
+};
+use acvm::{
+    compiler::{compile, BackendCapabilities, CircuitSimplifier},
+    Language,
+};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeSet;
+
+const PLONK: Language = Language::PLONKCSat { width: 3 };
+
+fn input(witness: u32, num_bits: u32) -> FunctionInput {
+    FunctionInput { witness: Witness(witness), num_bits }
+}
+
+fn linear(terms: &[(i128, u32)], constant: i128) -> Expression {
+    let mut expr = Expression::from_field(FieldElement::from(constant));
+    for (coefficient, witness) in terms {
+        expr.push_addition_term(FieldElement::from(*coefficient), Witness(*witness));
+    }
+    expr
+}
+
+/// Wide expressions split into chains, with identical subexpressions and a boolean selector.
+fn arithmetic_circuit() -> (Circuit, CircuitSimplifier, BackendCapabilities) {
+    let mut wide = linear(&[(1, 1), (2, 2), (3, 3), (4, 4), (5, 5), (-1, 8)], 7);
+    wide.push_multiplication_term(FieldElement::one(), Witness(6), Witness(6));
+    wide.push_multiplication_term(FieldElement::from(3_u128), Witness(6), Witness(1));
+    let mut selector = Expression::default();
+    selector.push_multiplication_term(FieldElement::one(), Witness(6), Witness(6));
+    selector.push_addition_term(-FieldElement::one(), Witness(6));
+    let circuit = Circuit {
+        current_witness_index: 10,
+        opcodes: vec![
+            Opcode::AssertZero(selector),
+            Opcode::AssertZero(wide),
+            Opcode::AssertZero(linear(&[(1, 1), (1, 2), (-1, 9)], 0)),
+            Opcode::AssertZero(linear(&[(1, 1), (1, 2), (-1, 10)], 0)),
+            Opcode::AssertZero(linear(&[(1, 9), (-1, 10), (1, 3)], 0)),
+        ],
+        public_parameters: PublicInputs(BTreeSet::from([Witness(1), Witness(2)])),
+        return_values: PublicInputs(BTreeSet::from([Witness(8)])),
+        debug_info: None,
+        phases: Vec::new(),
+    };
+    (circuit, CircuitSimplifier::new(7), BackendCapabilities::new(PLONK))
+}
+
+/// Black box calls replaced by their fallbacks, with ranges split into chunks.
+fn fallback_circuit() -> (Circuit, CircuitSimplifier, BackendCapabilities) {
+    let circuit = Circuit {
+        current_witness_index: 5,
+        opcodes: vec![
+            Opcode::BlackBoxFuncCall(BlackBoxFuncCall::AND {
+                lhs: input(1, 8),
+                rhs: input(2, 8),
+                output: Witness(3),
+            }),
+            Opcode::BlackBoxFuncCall(BlackBoxFuncCall::XOR {
+                lhs: input(3, 8),
+                rhs: input(2, 8),
+                output: Witness(4),
+            }),
+            Opcode::BlackBoxFuncCall(BlackBoxFuncCall::RANGE { input: input(5, 40) }),
+        ],
+        ..Circuit::default()
+    };
+    let capabilities = BackendCapabilities::new(PLONK)
+        .without(BlackBoxFunc::AND)
+        .without(BlackBoxFunc::XOR)
+        .with_max_range_bits(16);
+    (circuit, CircuitSimplifier::new(3), capabilities)
+}
+
+/// Witnesses solved at compile time which must be defined again for the hash consuming them.
+fn simplified_circuit() -> (Circuit, CircuitSimplifier, BackendCapabilities) {
+    let mut opcodes: Vec<Opcode> = (3..9)
+        .map(|witness| Opcode::AssertZero(linear(&[(1, witness)], -(witness as i128))))
+        .collect();
+    opcodes.push(Opcode::BlackBoxFuncCall(BlackBoxFuncCall::SHA256 {
+        inputs: (1..9).map(|witness| input(witness, 8)).collect(),
+        outputs: (9..41).map(Witness).collect(),
+    }));
+    let mut simplifier = CircuitSimplifier::new(3);
+    for len in 1..=opcodes.len() {
+        simplifier.simplify(&mut opcodes[..len]);
+    }
+    let circuit = Circuit { current_witness_index: 40, opcodes, ..Circuit::default() };
+    (circuit, simplifier, BackendCapabilities::new(PLONK))
+}
+
+/// A memory block lowered into constraints on its sorted trace.
+fn memory_circuit() -> (Circuit, CircuitSimplifier, BackendCapabilities) {
+    let op = |operation: u128, index: Expression, value: u32| Opcode::MemoryOp {
+        block_id: BlockId(0),
+        op: MemOp {
+            operation: Expression::from_field(FieldElement::from(operation)),
+            index,
+            value: Expression::from(Witness(value)),
+        },
+    };
+    let constant = |value: u128| Expression::from_field(FieldElement::from(value));
+    let circuit = Circuit {
+        current_witness_index: 6,
+        opcodes: vec![
+            Opcode::MemoryInit { block_id: BlockId(0), init: vec![Witness(1), Witness(2)] },
+            op(0, Expression::from(Witness(3)), 4),
+            op(1, Expression::from(Witness(3)), 5),
+            op(0, constant(0), 6),
+        ],
+        ..Circuit::default()
+    };
+    (circuit, CircuitSimplifier::new(6), BackendCapabilities::new(PLONK).without_memory())
+}
+
+fn compiled_bytes(
+    (circuit, simplifier, capabilities): (Circuit, CircuitSimplifier, BackendCapabilities),
+) -> Vec<u8> {
+    let (compiled, _, _) = compile(circuit, &capabilities, &simplifier).unwrap();
+    let mut bytes = Vec::new();
+    compiled.write(&mut bytes).unwrap();
+    bytes
+}
+
+fn hex_digest(bytes: &[u8]) -> String {
+    Sha256::digest(bytes).iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+#[test]
+fn compiled_circuits_are_byte_identical() {
+    type CircuitFn = fn() -> (Circuit, CircuitSimplifier, BackendCapabilities);
+    let corpus: [(&str, CircuitFn, &str); 4] = [
+        (
+            "arithmetic",
+            arithmetic_circuit,
+            "f19774cc07e8f3c4998d374bd32c97417ce77a72abf5172a037bf75da16b8459",
+        ),
+        (
+            "fallback",
+            fallback_circuit,
+            "96dc1b967e34990f78eaf01fc7489647c7d9fa80f8ae760c69e2038349a424d1",
+        ),
+        (
+            "simplified",
+            simplified_circuit,
+            "f57afbe3ba04f35df0f1fcf6b890b58eb943acebfe174f074573f72bb10a0ede",
+        ),
+        (
+            "memory",
+            memory_circuit,
+            "9996418b3c8b357863ffd2e9dd2a606464f3ed548d53c240e7940cfee215003a",
+        ),
+    ];
+
+    for (name, circuit, expected_digest) in corpus {
+        let bytes = compiled_bytes(circuit());
+        // Compiling again within the same process rules out any dependency on the state of hashers.
+        assert_eq!(compiled_bytes(circuit()), bytes, "{name} circuit compiled differently twice");
+        assert_eq!(hex_digest(&bytes), expected_digest, "{name} circuit compiled differently");
+    }
+}
@@ -1,21 +1,32 @@
 use std::collections::BTreeMap;
 
 use acir::{
-    brillig_vm::{self, BinaryFieldOp, RegisterIndex, RegisterOrMemory, Value},
+    brillig_vm::{
+        self, BinaryFieldOp, ForeignCallResult, LeakKind, RegisterIndex, RegisterOrMemory, Value,
+    },
     circuit::{
         brillig::{Brillig, BrilligInputs, BrilligOutputs},
-        directives::Directive,
-        opcodes::FunctionInput,
-        Opcode,
+        directives::{Directive, QuotientDirective},
+        opcodes::{BlackBoxFuncCall, BlockId, FunctionInput, MemOp},
+        phases::Phase,
+        Opcode, OpcodeLocation,
     },
     native_types::{Expression, Witness, WitnessMap},
-    FieldElement,
+    smallvec::smallvec,
+    BlackBoxFunc, FieldElement,
 };
 
 use acvm::{
+    blackbox_solver,
+    compiler::lift_brillig,
+    foreign_calls::{
+        ForeignCallParamKind, ForeignCallParamShape, ForeignCallRegistry, ForeignCallSignatureError,
+    },
     pwg::{
-        ForeignCallWaitInfo, OpcodeResolution, OpcodeResolutionError,
-        PartialWitnessGeneratorStatus, ACVM,
+        BlackBoxCache, BrilligOutputSource, BrilligSolution, ErrorLocation, ForeignCallWaitInfo,
+        IntegerExpressions, MemoryAccessKind, OpcodeResolutionError, PartialWitnessGeneratorStatus,
+        ResolvedForeignCall, SecretAuditFinding, TraceEventKind, TraceOutcome, UnknownPhaseError,
+        WitnessOverwrite, ACVM,
     },
     PartialWitnessGenerator,
 };
@@ -25,35 +36,44 @@ struct StubbedPwg;
 impl PartialWitnessGenerator for StubbedPwg {
     fn schnorr_verify(
         &self,
-        _initial_witness: &mut WitnessMap,
-        _public_key_x: FunctionInput,
-        _public_key_y: FunctionInput,
-        _signature_s: FunctionInput,
-        _signature_e: FunctionInput,
-        _message: &[FunctionInput],
-        _output: Witness,
-    ) -> Result<OpcodeResolution, OpcodeResolutionError> {
+        _public_key_x: &FieldElement,
+        _public_key_y: &FieldElement,
+        _signature_s: &FieldElement,
+        _signature_e: &FieldElement,
+        _message: &[u8],
+    ) -> Result<bool, OpcodeResolutionError> {
         panic!("Path not trodden by this test")
     }
 
     fn pedersen(
         &self,
-        _initial_witness: &mut WitnessMap,
-        _inputs: &[FunctionInput],
+        _inputs: &[FieldElement],
         _domain_separator: u32,
-        _outputs: (Witness, Witness),
-    ) -> Result<OpcodeResolution, OpcodeResolutionError> {
+    ) -> Result<(FieldElement, FieldElement), OpcodeResolutionError> {
         panic!("Path not trodden by this test")
     }
 
     fn fixed_base_scalar_mul(
         &self,
-        _initial_witness: &mut WitnessMap,
-        _input: FunctionInput,
-        _outputs: (Witness, Witness),
-    ) -> Result<OpcodeResolution, OpcodeResolutionError> {
+        _input: &FieldElement,
+    ) -> Result<(FieldElement, FieldElement), OpcodeResolutionError> {
         panic!("Path not trodden by this test")
     }
+
+    fn recursive_aggregation(
+        &self,
+        _verification_key: &[FieldElement],
+        _proof: &[FieldElement],
+        _public_inputs: &[FieldElement],
+        key_hash: &FieldElement,
+        input_aggregation_object: Option<&[FieldElement]>,
+        output_len: usize,
+    ) -> Result<Vec<FieldElement>, OpcodeResolutionError> {
+        // Accumulates the key hash into each element of the aggregation object.
+        let input = input_aggregation_object
+            .map_or_else(|| vec![FieldElement::zero(); output_len], <[_]>::to_vec);
+        Ok(input.into_iter().map(|element| element + *key_hash).collect())
+    }
 }
 
 #[test]
@@ -86,8 +106,8 @@ fn inversion_brillig_oracle_equivalence() {
         inputs: vec![
             BrilligInputs::Single(Expression {
                 // Input Register 0
-                mul_terms: vec![],
-                linear_combinations: vec![(fe_1, w_x), (fe_1, w_y)],
+                mul_terms: smallvec![],
+                linear_combinations: smallvec![(fe_1, w_x), (fe_1, w_y)],
                 q_c: fe_0,
             }),
             BrilligInputs::Single(Expression::default()), // Input Register 1
@@ -114,20 +134,20 @@ fn inversion_brillig_oracle_equivalence() {
 
     let opcodes = vec![
         Opcode::Brillig(brillig_data),
-        Opcode::Arithmetic(Expression {
-            mul_terms: vec![],
-            linear_combinations: vec![(fe_1, w_x), (fe_1, w_y), (-fe_1, w_z)],
+        Opcode::AssertZero(Expression {
+            mul_terms: smallvec![],
+            linear_combinations: smallvec![(fe_1, w_x), (fe_1, w_y), (-fe_1, w_z)],
             q_c: fe_0,
         }),
         Opcode::Directive(Directive::Invert { x: w_z, result: w_z_inverse }),
-        Opcode::Arithmetic(Expression {
-            mul_terms: vec![(fe_1, w_z, w_z_inverse)],
-            linear_combinations: vec![],
+        Opcode::AssertZero(Expression {
+            mul_terms: smallvec![(fe_1, w_z, w_z_inverse)],
+            linear_combinations: smallvec![],
             q_c: -fe_1,
         }),
-        Opcode::Arithmetic(Expression {
-            mul_terms: vec![],
-            linear_combinations: vec![(-fe_1, w_oracle), (fe_1, w_z_inverse)],
+        Opcode::AssertZero(Expression {
+            mul_terms: smallvec![],
+            linear_combinations: smallvec![(-fe_1, w_oracle), (fe_1, w_z_inverse)],
             q_c: fe_0,
         }),
     ];
@@ -154,13 +174,117 @@ fn inversion_brillig_oracle_equivalence() {
     assert_eq!(foreign_call_wait_info.inputs.len(), 1, "Should be waiting for a single input");
 
     // As caller of VM, need to resolve foreign calls
-    let foreign_call_result = Value::from(foreign_call_wait_info.inputs[0][0].to_field().inverse());
+    let foreign_call_result =
+        Value::from(foreign_call_wait_info.inputs[0].values()[0].to_field().inverse());
     // Alter Brillig oracle opcode with foreign call resolution
     acvm.resolve_pending_foreign_call(foreign_call_result.into());
 
     // After filling data request, continue solving
     let solver_status = acvm.solve().expect("should not stall on brillig call");
     assert_eq!(solver_status, PartialWitnessGeneratorStatus::Solved, "should be fully solved");
+
+    // The outputs were read from the registers in order, once the foreign call was resolved.
+    assert_eq!(acvm.brillig_solutions().len(), 1);
+    let solution = &acvm.brillig_solutions()[0];
+    assert_eq!(solution.acir_index, 0);
+    let sources: Vec<_> = solution.outputs.iter().map(|(_, source)| source.clone()).collect();
+    assert_eq!(
+        sources,
+        [0, 1, 2].map(|index| BrilligOutputSource::Register(RegisterIndex::from(index)))
+    );
+}
+
+#[test]
+fn brillig_solution_describes_output_sources() {
+    let (w_x, w_y, w_z) = (Witness(1), Witness(2), Witness(3));
+    let array_output = BrilligOutputs::Array(vec![Witness(4), Witness(5)]);
+    let simple_output = BrilligOutputs::Simple(Witness(6));
+    // The bytecode returns its inputs as they are: the pointer to the array in the first register
+    // and the single value in the second one.
+    let brillig_opcode = Opcode::Brillig(Brillig {
+        inputs: vec![
+            BrilligInputs::Array(vec![w_x.into(), w_y.into()]),
+            BrilligInputs::Single(w_z.into()),
+        ],
+        outputs: vec![array_output.clone(), simple_output.clone()],
+        bytecode: vec![brillig_vm::Opcode::Stop],
+        predicate: None,
+        foreign_call_results: vec![],
+    });
+
+    let witness_assignments = BTreeMap::from([
+        (w_x, FieldElement::from(1u128)),
+        (w_y, FieldElement::from(2u128)),
+        (w_z, FieldElement::from(3u128)),
+    ])
+    .into();
+    let mut acvm = ACVM::new(StubbedPwg, vec![brillig_opcode], witness_assignments);
+    let solver_status = acvm.solve().expect("should solve the brillig opcode");
+    assert_eq!(solver_status, PartialWitnessGeneratorStatus::Solved, "should be fully solved");
+
+    assert_eq!(
+        acvm.brillig_solutions(),
+        [BrilligSolution {
+            acir_index: 0,
+            outputs: vec![
+                (
+                    array_output,
+                    BrilligOutputSource::Memory { pointer: RegisterIndex::from(0), range: 0..2 }
+                ),
+                (simple_output, BrilligOutputSource::Register(RegisterIndex::from(1))),
+            ],
+        }]
+    );
+    assert_eq!(
+        acvm.brillig_solutions()[0].assigned_witnesses().collect::<Vec<_>>(),
+        [Witness(4), Witness(5), Witness(6)]
+    );
+    assert_eq!(acvm.witness_map()[&Witness(5)], FieldElement::from(2u128));
+}
+
+#[test]
+fn independent_brillig_opcodes_are_solved_in_order() {
+    // Each opcode doubles its input, a multiple of `x`, into its own output.
+    let w_x = Witness(1);
+    let doubling = |input: Expression, output: Witness| {
+        Opcode::Brillig(Brillig {
+            inputs: vec![BrilligInputs::Single(input)],
+            outputs: vec![BrilligOutputs::Simple(output)],
+            bytecode: vec![
+                brillig_vm::Opcode::BinaryFieldOp {
+                    op: BinaryFieldOp::Add,
+                    lhs: RegisterIndex::from(0),
+                    rhs: RegisterIndex::from(0),
+                    destination: RegisterIndex::from(0),
+                },
+                brillig_vm::Opcode::Stop,
+            ],
+            predicate: None,
+            foreign_call_results: vec![],
+        })
+    };
+    let multiple = |factor: u128| {
+        let mut expr = Expression::default();
+        expr.push_addition_term(FieldElement::from(factor), w_x);
+        expr
+    };
+    let mut opcodes: Vec<Opcode> =
+        (0..4).map(|i| doubling(multiple(i as u128 + 1), Witness(10 + i))).collect();
+    // An opcode assigning the same output as the first one, and one depending on the output of the first one.
+    opcodes.push(doubling(multiple(1), Witness(10)));
+    opcodes.push(doubling(Witness(10).into(), Witness(20)));
+
+    let witness_assignments = BTreeMap::from([(w_x, FieldElement::from(3u128))]).into();
+    let mut acvm = ACVM::new(StubbedPwg, opcodes, witness_assignments);
+    assert_eq!(acvm.solve(), Ok(PartialWitnessGeneratorStatus::Solved));
+
+    for i in 0..4 {
+        assert_eq!(acvm.witness_map()[&Witness(10 + i)], FieldElement::from(6 * (i as u128 + 1)));
+    }
+    assert_eq!(acvm.witness_map()[&Witness(20)], FieldElement::from(12u128));
+    let solved_indices: Vec<usize> =
+        acvm.brillig_solutions().iter().map(|solution| solution.acir_index).collect();
+    assert_eq!(solved_indices, [0, 1, 2, 3, 4, 5]);
 }
 
 #[test]
@@ -199,15 +323,15 @@ fn double_inversion_brillig_oracle() {
         inputs: vec![
             BrilligInputs::Single(Expression {
                 // Input Register 0
-                mul_terms: vec![],
-                linear_combinations: vec![(fe_1, w_x), (fe_1, w_y)],
+                mul_terms: smallvec![],
+                linear_combinations: smallvec![(fe_1, w_x), (fe_1, w_y)],
                 q_c: fe_0,
             }),
             BrilligInputs::Single(Expression::default()), // Input Register 1
             BrilligInputs::Single(Expression {
                 // Input Register 2
-                mul_terms: vec![],
-                linear_combinations: vec![(fe_1, w_i), (fe_1, w_j)],
+                mul_terms: smallvec![],
+                linear_combinations: smallvec![(fe_1, w_i), (fe_1, w_j)],
                 q_c: fe_0,
             }),
         ],
@@ -239,20 +363,20 @@ fn double_inversion_brillig_oracle() {
 
     let opcodes = vec![
         Opcode::Brillig(brillig_data),
-        Opcode::Arithmetic(Expression {
-            mul_terms: vec![],
-            linear_combinations: vec![(fe_1, w_x), (fe_1, w_y), (-fe_1, w_z)],
+        Opcode::AssertZero(Expression {
+            mul_terms: smallvec![],
+            linear_combinations: smallvec![(fe_1, w_x), (fe_1, w_y), (-fe_1, w_z)],
             q_c: fe_0,
         }),
         Opcode::Directive(Directive::Invert { x: w_z, result: w_z_inverse }),
-        Opcode::Arithmetic(Expression {
-            mul_terms: vec![(fe_1, w_z, w_z_inverse)],
-            linear_combinations: vec![],
+        Opcode::AssertZero(Expression {
+            mul_terms: smallvec![(fe_1, w_z, w_z_inverse)],
+            linear_combinations: smallvec![],
             q_c: -fe_1,
         }),
-        Opcode::Arithmetic(Expression {
-            mul_terms: vec![],
-            linear_combinations: vec![(-fe_1, w_oracle), (fe_1, w_z_inverse)],
+        Opcode::AssertZero(Expression {
+            mul_terms: smallvec![],
+            linear_combinations: smallvec![(-fe_1, w_oracle), (fe_1, w_z_inverse)],
             q_c: fe_0,
         }),
     ];
@@ -280,7 +404,8 @@ fn double_inversion_brillig_oracle() {
         acvm.get_pending_foreign_call().expect("should have a brillig foreign call request");
     assert_eq!(foreign_call_wait_info.inputs.len(), 1, "Should be waiting for a single input");
 
-    let x_plus_y_inverse = Value::from(foreign_call_wait_info.inputs[0][0].to_field().inverse());
+    let x_plus_y_inverse =
+        Value::from(foreign_call_wait_info.inputs[0].values()[0].to_field().inverse());
 
     // Resolve Brillig foreign call
     acvm.resolve_pending_foreign_call(x_plus_y_inverse.into());
@@ -298,7 +423,8 @@ fn double_inversion_brillig_oracle() {
         acvm.get_pending_foreign_call().expect("should have a brillig foreign call request");
     assert_eq!(foreign_call_wait_info.inputs.len(), 1, "Should be waiting for a single input");
 
-    let i_plus_j_inverse = Value::from(foreign_call_wait_info.inputs[0][0].to_field().inverse());
+    let i_plus_j_inverse =
+        Value::from(foreign_call_wait_info.inputs[0].values()[0].to_field().inverse());
     assert_ne!(x_plus_y_inverse, i_plus_j_inverse);
 
     // Alter Brillig oracle opcode
@@ -363,23 +489,23 @@ fn oracle_dependent_execution() {
 
     // This equality check can be executed immediately before resolving any foreign calls.
     let equality_check = Expression {
-        mul_terms: vec![],
-        linear_combinations: vec![(-fe_1, w_x), (fe_1, w_y)],
+        mul_terms: smallvec![],
+        linear_combinations: smallvec![(-fe_1, w_x), (fe_1, w_y)],
         q_c: fe_0,
     };
 
     // This equality check relies on the outputs of the Brillig call.
     // It then cannot be solved until the foreign calls are resolved.
     let inverse_equality_check = Expression {
-        mul_terms: vec![],
-        linear_combinations: vec![(-fe_1, w_x_inv), (fe_1, w_y_inv)],
+        mul_terms: smallvec![],
+        linear_combinations: smallvec![(-fe_1, w_x_inv), (fe_1, w_y_inv)],
         q_c: fe_0,
     };
 
     let opcodes = vec![
-        Opcode::Arithmetic(equality_check),
+        Opcode::AssertZero(equality_check),
         Opcode::Brillig(brillig_data),
-        Opcode::Arithmetic(inverse_equality_check.clone()),
+        Opcode::AssertZero(inverse_equality_check.clone()),
     ];
 
     let witness_assignments =
@@ -397,7 +523,7 @@ fn oracle_dependent_execution() {
     assert_eq!(acvm.unresolved_opcodes().len(), 1, "brillig should have been removed");
     assert_eq!(
         acvm.unresolved_opcodes()[0],
-        Opcode::Arithmetic(inverse_equality_check.clone()),
+        Opcode::AssertZero(inverse_equality_check.clone()),
         "Equality check of inverses should still be waiting to be resolved"
     );
 
@@ -406,7 +532,7 @@ fn oracle_dependent_execution() {
     assert_eq!(foreign_call_wait_info.inputs.len(), 1, "Should be waiting for a single input");
 
     // Resolve Brillig foreign call
-    let x_inverse = Value::from(foreign_call_wait_info.inputs[0][0].to_field().inverse());
+    let x_inverse = Value::from(foreign_call_wait_info.inputs[0].values()[0].to_field().inverse());
     acvm.resolve_pending_foreign_call(x_inverse.into());
 
     // After filling data request, continue solving
@@ -419,7 +545,7 @@ fn oracle_dependent_execution() {
     assert_eq!(acvm.unresolved_opcodes().len(), 1, "brillig should have been removed");
     assert_eq!(
         acvm.unresolved_opcodes()[0],
-        Opcode::Arithmetic(inverse_equality_check),
+        Opcode::AssertZero(inverse_equality_check),
         "Equality check of inverses should still be waiting to be resolved"
     );
 
@@ -428,7 +554,7 @@ fn oracle_dependent_execution() {
     assert_eq!(foreign_call_wait_info.inputs.len(), 1, "Should be waiting for a single input");
 
     // Resolve Brillig foreign call
-    let y_inverse = Value::from(foreign_call_wait_info.inputs[0][0].to_field().inverse());
+    let y_inverse = Value::from(foreign_call_wait_info.inputs[0].values()[0].to_field().inverse());
     acvm.resolve_pending_foreign_call(y_inverse.into());
 
     // We've resolved all the brillig foreign calls so we should be able to complete execution now.
@@ -469,8 +595,8 @@ fn brillig_oracle_predicate() {
     let brillig_opcode = Opcode::Brillig(Brillig {
         inputs: vec![
             BrilligInputs::Single(Expression {
-                mul_terms: vec![],
-                linear_combinations: vec![(fe_1, w_x), (fe_1, w_y)],
+                mul_terms: smallvec![],
+                linear_combinations: smallvec![(fe_1, w_x), (fe_1, w_y)],
                 q_c: fe_0,
             }),
             BrilligInputs::Single(Expression::default()),
@@ -497,15 +623,15 @@ fn brillig_oracle_predicate() {
 
     let opcodes = vec![
         brillig_opcode,
-        Opcode::Arithmetic(Expression {
-            mul_terms: vec![],
-            linear_combinations: vec![(fe_1, w_x), (fe_1, w_y), (-fe_1, w_z)],
+        Opcode::AssertZero(Expression {
+            mul_terms: smallvec![],
+            linear_combinations: smallvec![(fe_1, w_x), (fe_1, w_y), (-fe_1, w_z)],
             q_c: fe_0,
         }),
         Opcode::Directive(Directive::Invert { x: w_z, result: w_z_inverse }),
-        Opcode::Arithmetic(Expression {
-            mul_terms: vec![(fe_1, w_z, w_z_inverse)],
-            linear_combinations: vec![],
+        Opcode::AssertZero(Expression {
+            mul_terms: smallvec![(fe_1, w_z, w_z_inverse)],
+            linear_combinations: smallvec![],
             q_c: -fe_1,
         }),
     ];
@@ -520,3 +646,1328 @@ fn brillig_oracle_predicate() {
     let solver_status = acvm.solve().expect("should not stall on brillig call");
     assert_eq!(solver_status, PartialWitnessGeneratorStatus::Solved, "should be fully solved");
 }
+
+#[test]
+fn brillig_false_predicate_skips_execution() {
+    // The inputs of the Brillig opcode can not be evaluated and its bytecode traps,
+    // but neither matters as its predicate is false.
+    let (w_condition, w_unknown, w_output, w_array_output) =
+        (Witness(1), Witness(2), Witness(3), Witness(4));
+    let brillig_opcode = Opcode::Brillig(Brillig {
+        inputs: vec![BrilligInputs::Single(w_unknown.into())],
+        outputs: vec![
+            BrilligOutputs::Simple(w_output),
+            BrilligOutputs::Array(vec![w_array_output, Witness(5)]),
+        ],
+        bytecode: vec![brillig_vm::Opcode::Trap],
+        predicate: Some(w_condition.into()),
+        foreign_call_results: vec![],
+    });
+
+    let witness_assignments = BTreeMap::from([(w_condition, FieldElement::zero())]).into();
+    let mut acvm = ACVM::new(StubbedPwg, vec![brillig_opcode], witness_assignments);
+    let solver_status = acvm.solve().expect("should skip the brillig opcode");
+    assert_eq!(solver_status, PartialWitnessGeneratorStatus::Solved, "should be fully solved");
+
+    let witness_map = acvm.witness_map();
+    for witness in [w_output, w_array_output, Witness(5)] {
+        assert_eq!(witness_map[&witness], FieldElement::zero());
+    }
+    assert_eq!(witness_map.get(&w_unknown), None);
+
+    assert_eq!(acvm.brillig_solutions().len(), 1);
+    let solution = &acvm.brillig_solutions()[0];
+    assert!(solution.outputs.iter().all(|(_, source)| *source == BrilligOutputSource::Skipped));
+}
+
+#[test]
+fn range_check_failure_reports_violating_value() {
+    let w_x = Witness(1);
+    let opcodes = vec![
+        Opcode::BlackBoxFuncCall(BlackBoxFuncCall::RANGE {
+            input: FunctionInput { witness: w_x, num_bits: 64 },
+        }),
+        Opcode::BlackBoxFuncCall(BlackBoxFuncCall::RANGE {
+            input: FunctionInput { witness: w_x, num_bits: 8 },
+        }),
+    ];
+    let witness_assignments = BTreeMap::from([(w_x, FieldElement::from(300u128))]).into();
+
+    let mut acvm = ACVM::new(StubbedPwg, opcodes, witness_assignments);
+    let err = acvm.solve().expect_err("300 does not fit within 8 bits");
+    assert_eq!(
+        err,
+        OpcodeResolutionError::RangeCheckFailed {
+            witness: w_x,
+            value: FieldElement::from(300u128),
+            num_bits: 8,
+            opcode_location: ErrorLocation::Resolved(OpcodeLocation::Acir(1)),
+        }
+    );
+    assert_eq!(err.to_string(), "value 300 (0x12c) of witness 1 does not fit within 8 bits");
+
+    // Values are rendered as unsigned integers rather than as the negative integers of their display.
+    let witness_assignments = BTreeMap::from([(w_x, -FieldElement::one())]).into();
+    let opcodes = vec![Opcode::BlackBoxFuncCall(BlackBoxFuncCall::RANGE {
+        input: FunctionInput { witness: w_x, num_bits: 8 },
+    })];
+    let err = ACVM::new(StubbedPwg, opcodes, witness_assignments).solve().unwrap_err();
+    let modulus_minus_one = FieldElement::modulus() - 1u32;
+    assert_eq!(
+        err.to_string(),
+        format!(
+            "value {modulus_minus_one} ({modulus_minus_one:#x}) of witness 1 does not fit within 8 bits"
+        )
+    );
+}
+
+#[test]
+fn phases_are_solved_once_activated() {
+    // Commits to `w_square = w_x * w_x`, then derives `w_result = w_challenge * w_x`
+    // from a challenge obtained through a foreign call once the commitment is known.
+    let fe_1 = FieldElement::one();
+    let w_x = Witness(1);
+    let w_square = Witness(2);
+    let w_challenge = Witness(3);
+    let w_result = Witness(4);
+
+    let challenge = Brillig {
+        inputs: vec![BrilligInputs::Single(w_square.into())],
+        outputs: vec![BrilligOutputs::Simple(w_challenge)],
+        foreign_call_results: vec![],
+        bytecode: vec![brillig_vm::Opcode::ForeignCall {
+            function: "challenge".into(),
+            destinations: vec![RegisterOrMemory::RegisterIndex(RegisterIndex::from(0))],
+            inputs: vec![RegisterOrMemory::RegisterIndex(RegisterIndex::from(0))],
+        }],
+        predicate: None,
+    };
+    let opcodes = vec![
+        Opcode::AssertZero(Expression {
+            mul_terms: smallvec![(fe_1, w_x, w_x)],
+            linear_combinations: smallvec![(-fe_1, w_square)],
+            q_c: FieldElement::zero(),
+        }),
+        Opcode::Brillig(challenge),
+        Opcode::AssertZero(Expression {
+            mul_terms: smallvec![(fe_1, w_challenge, w_x)],
+            linear_combinations: smallvec![(-fe_1, w_result)],
+            q_c: FieldElement::zero(),
+        }),
+    ];
+    let phases = [Phase::new("commit", [0]), Phase::new("challenge", [1, 2])];
+    let witness_assignments = BTreeMap::from([(w_x, FieldElement::from(3u128))]).into();
+
+    let mut acvm = ACVM::new(StubbedPwg, opcodes, witness_assignments).with_phases(&phases);
+    assert_eq!(acvm.solve(), Ok(PartialWitnessGeneratorStatus::Solved), "no phase is active");
+    assert_eq!(acvm.witness_map().get(&w_square), None);
+
+    acvm.activate_phase("commit").unwrap();
+    assert_eq!(acvm.solve(), Ok(PartialWitnessGeneratorStatus::Solved));
+    assert_eq!(acvm.witness_map().get(&w_square), Some(&FieldElement::from(9u128)));
+    assert_eq!(acvm.witness_map().get(&w_challenge), None);
+    assert_eq!(acvm.inactive_phases(), ["challenge"]);
+
+    assert_eq!(acvm.activate_phase("commit"), Err(UnknownPhaseError("commit".to_owned())));
+    acvm.activate_phase("challenge").unwrap();
+    assert_eq!(acvm.solve(), Ok(PartialWitnessGeneratorStatus::RequiresForeignCall));
+    let commitment = acvm.get_pending_foreign_call().unwrap().inputs[0].values()[0].to_field();
+    assert_eq!(commitment, FieldElement::from(9u128));
+    acvm.resolve_pending_foreign_call(Value::from(FieldElement::from(5u128)).into());
+
+    assert_eq!(acvm.solve(), Ok(PartialWitnessGeneratorStatus::Solved));
+    assert_eq!(acvm.witness_map().get(&w_result), Some(&FieldElement::from(15u128)));
+    assert!(acvm.inactive_phases().is_empty());
+}
+
+#[test]
+fn integer_expressions_solve_as_field_arithmetic() {
+    // x = lo + 2^8 hi, with each of them range constrained.
+    let [w_x, w_lo, w_hi] = [1, 2, 3].map(Witness);
+    let range = |witness, num_bits| {
+        Opcode::BlackBoxFuncCall(BlackBoxFuncCall::RANGE {
+            input: FunctionInput { witness, num_bits },
+        })
+    };
+    let opcodes = vec![
+        range(w_lo, 8),
+        range(w_hi, 8),
+        range(w_x, 16),
+        Opcode::AssertZero(Expression {
+            mul_terms: smallvec![],
+            linear_combinations: smallvec![
+                (FieldElement::one(), w_x),
+                (-FieldElement::one(), w_lo),
+                (-FieldElement::from(256_u128), w_hi)
+            ],
+            q_c: FieldElement::zero(),
+        }),
+    ];
+    let integer_expressions = IntegerExpressions::new(&opcodes);
+    assert_eq!(integer_expressions.len(), 1);
+
+    let solve = |x: Option<u128>| {
+        let mut witness_assignments = BTreeMap::from([
+            (w_lo, FieldElement::from(0x34_u128)),
+            (w_hi, FieldElement::from(0x12_u128)),
+        ]);
+        if let Some(x) = x {
+            witness_assignments.insert(w_x, FieldElement::from(x));
+        }
+        let mut acvm = ACVM::new(StubbedPwg, opcodes.clone(), witness_assignments.into())
+            .with_integer_expressions(integer_expressions.clone());
+        acvm.solve().map(|status| (status, acvm.witness_map().clone()))
+    };
+
+    // Opcodes whose witnesses are all known are checked with integer arithmetic, the others solved as before.
+    let (status, witness) = solve(Some(0x1234)).unwrap();
+    assert_eq!(status, PartialWitnessGeneratorStatus::Solved);
+    assert_eq!(solve(None).unwrap(), (status, witness));
+    assert_eq!(
+        solve(Some(0x1235)),
+        Err(OpcodeResolutionError::UnsatisfiedConstrain {
+            opcode_location: ErrorLocation::Resolved(OpcodeLocation::Acir(3)),
+        })
+    );
+}
+
+#[test]
+fn finalize_returns_the_solved_witness() {
+    let [w_x, w_y] = [1, 2].map(Witness);
+    // y = 2x
+    let opcodes = vec![Opcode::AssertZero(Expression {
+        mul_terms: smallvec![],
+        linear_combinations: smallvec![
+            (FieldElement::from(2_u128), w_x),
+            (-FieldElement::one(), w_y)
+        ],
+        q_c: FieldElement::zero(),
+    })];
+    let witness_assignments = BTreeMap::from([(w_x, FieldElement::from(3_u128))]).into();
+
+    let mut acvm = ACVM::new(StubbedPwg, opcodes, witness_assignments);
+    assert_eq!(acvm.solve(), Ok(PartialWitnessGeneratorStatus::Solved));
+    let witness = acvm.finalize();
+    assert_eq!(witness.get(&w_y), Some(&FieldElement::from(6_u128)));
+}
+
+#[test]
+#[should_panic(expected = "ACVM is not ready to be finalized")]
+fn finalize_rejects_unsolved_opcodes() {
+    let opcodes = vec![Opcode::AssertZero(Witness(1).into())];
+    ACVM::new(StubbedPwg, opcodes, WitnessMap::new()).finalize();
+}
+
+#[test]
+#[should_panic(expected = "ACVM is not ready to be finalized")]
+fn finalize_rejects_inactive_phases() {
+    // y = x, in a phase which is never activated.
+    let opcodes = vec![Opcode::AssertZero(&Expression::from(Witness(1)) - Witness(2))];
+    let witness_assignments = BTreeMap::from([(Witness(1), FieldElement::one())]).into();
+
+    let mut acvm = ACVM::new(StubbedPwg, opcodes, witness_assignments)
+        .with_phases(&[Phase::new("challenge", [0])]);
+    assert_eq!(acvm.solve(), Ok(PartialWitnessGeneratorStatus::Solved));
+    acvm.finalize();
+}
+
+#[test]
+fn unsatisfied_constraint_reports_opcode_location() {
+    let fe_1 = FieldElement::one();
+    let w_x = Witness(1);
+    let w_y = Witness(2);
+
+    let opcodes = vec![
+        // y = x
+        Opcode::AssertZero(Expression {
+            mul_terms: smallvec![],
+            linear_combinations: smallvec![(fe_1, w_x), (-fe_1, w_y)],
+            q_c: FieldElement::zero(),
+        }),
+        // y = 1
+        Opcode::AssertZero(Expression {
+            mul_terms: smallvec![],
+            linear_combinations: smallvec![(fe_1, w_y)],
+            q_c: -fe_1,
+        }),
+    ];
+
+    let witness_assignments = BTreeMap::from([(w_x, FieldElement::from(2u128))]).into();
+
+    let mut acvm = ACVM::new(StubbedPwg, opcodes, witness_assignments);
+    let err = acvm.solve().expect_err("should fail on the second opcode");
+    assert_eq!(
+        err,
+        OpcodeResolutionError::UnsatisfiedConstrain {
+            opcode_location: ErrorLocation::Resolved(OpcodeLocation::Acir(1))
+        }
+    );
+}
+
+#[test]
+fn memory_operations_take_effect_in_order() {
+    let block_id = BlockId(0);
+    let (w_init, w_written, w_read) = (Witness(1), Witness(2), Witness(3));
+
+    let opcodes = vec![
+        Opcode::MemoryInit { block_id, init: vec![w_init] },
+        // The value written is only solved by the last opcode, so the read must wait for the write.
+        Opcode::MemoryOp {
+            block_id,
+            op: MemOp {
+                operation: Expression::one(),
+                index: Expression::zero(),
+                value: w_written.into(),
+            },
+        },
+        Opcode::MemoryOp {
+            block_id,
+            op: MemOp {
+                operation: Expression::zero(),
+                index: Expression::zero(),
+                value: w_read.into(),
+            },
+        },
+        // written = 5
+        Opcode::AssertZero(Expression {
+            mul_terms: smallvec![],
+            linear_combinations: smallvec![(FieldElement::one(), w_written)],
+            q_c: -FieldElement::from(5_u128),
+        }),
+    ];
+
+    let witness_assignments = BTreeMap::from([(w_init, FieldElement::from(1_u128))]).into();
+
+    let mut acvm = ACVM::new(StubbedPwg, opcodes, witness_assignments).with_memory_traces();
+    assert_eq!(acvm.solve(), Ok(PartialWitnessGeneratorStatus::Solved));
+    assert_eq!(acvm.witness_map()[&w_read], FieldElement::from(5_u128));
+
+    // The trace follows the order of the operations rather than that of the passes solving them.
+    let kinds: Vec<_> = acvm.memory_traces()[&block_id]
+        .accesses()
+        .iter()
+        .map(|access| (access.timestamp, access.kind))
+        .collect();
+    assert_eq!(
+        kinds,
+        [(0, MemoryAccessKind::Init), (1, MemoryAccessKind::Write), (2, MemoryAccessKind::Read)]
+    );
+}
+
+#[test]
+fn solves_memory_blocks_migrated_from_version_0_circuits() {
+    let read_hex = |hex: &str| {
+        let bytes: Vec<u8> = (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+            .collect();
+        acir::circuit::Circuit::read(&*bytes).unwrap()
+    };
+    let solve = |hex: &str, inputs: &[(u32, u128)]| {
+        let initial_witness = inputs
+            .iter()
+            .map(|(witness, value)| (Witness(*witness), FieldElement::from(*value)))
+            .collect::<BTreeMap<_, _>>();
+        let mut acvm = ACVM::new(StubbedPwg, read_hex(hex).opcodes, initial_witness.into());
+        assert_eq!(acvm.solve(), Ok(PartialWitnessGeneratorStatus::Solved));
+        acvm.finalize()
+    };
+
+    // A `ROM` block initialized by 5 and `w1 + 1`, then read at `w2` into `w3`.
+    let rom = "cd91490d00200c04390cc1833f0608098e70d0521ca00a37e0a1fbe80a98cc6477e479466fe2828810dd9a74cb084842408a9d9e0fe1a5a7788008e81e444eb02212f522440f";
+    assert_eq!(solve(rom, &[(1, 4), (2, 0)])[&Witness(3)], FieldElement::from(5_u128));
+    assert_eq!(solve(rom, &[(1, 4), (2, 1)])[&Witness(3)], FieldElement::from(5_u128));
+    assert_eq!(solve(rom, &[(1, 9), (2, 1)])[&Witness(3)], FieldElement::from(10_u128));
+
+    // A `Block` without an initialization phase, writing `w1` to index 1 and 7 to index 0,
+    // then reading them into `w2` and `w3`.
+    let block = "cdd1410d00200c03c00086c68b2fc10a4f48d08083c1b08028dc20627dac022e4d7bc29ab7f4519b387f44985f265d2208595baf3835417636210492c44c15d0c7dec8c70442109b047d11e60f";
+    let witness_map = solve(block, &[(1, 9)]);
+    assert_eq!(witness_map[&Witness(2)], FieldElement::from(9_u128));
+    assert_eq!(witness_map[&Witness(3)], FieldElement::from(7_u128));
+}
+
+#[test]
+fn resolve_delta_only_solves_the_opcodes_affected_by_the_changed_inputs() {
+    let block_id = BlockId(0);
+    let (w_x, w_y) = (Witness(1), Witness(2));
+    let (w_x_plus_one, w_double_y, w_read) = (Witness(3), Witness(4), Witness(5));
+
+    let opcodes = vec![
+        // x_plus_one = x + 1
+        Opcode::AssertZero(Expression {
+            mul_terms: smallvec![],
+            linear_combinations: smallvec![
+                (FieldElement::one(), w_x),
+                (-FieldElement::one(), w_x_plus_one)
+            ],
+            q_c: FieldElement::one(),
+        }),
+        // double_y = 2 * y
+        Opcode::AssertZero(Expression {
+            mul_terms: smallvec![],
+            linear_combinations: smallvec![
+                (FieldElement::from(2_u128), w_y),
+                (-FieldElement::one(), w_double_y)
+            ],
+            q_c: FieldElement::zero(),
+        }),
+        Opcode::MemoryInit { block_id, init: vec![w_double_y] },
+        Opcode::MemoryOp {
+            block_id,
+            op: MemOp {
+                operation: Expression::zero(),
+                index: Expression::zero(),
+                value: w_read.into(),
+            },
+        },
+    ];
+    let inputs = |x: u128, y: u128| -> WitnessMap {
+        BTreeMap::from([(w_x, FieldElement::from(x)), (w_y, FieldElement::from(y))]).into()
+    };
+
+    let mut acvm = ACVM::new(StubbedPwg, opcodes.clone(), inputs(1, 2))
+        .with_delta_resolution()
+        .with_solve_trace();
+    assert_eq!(acvm.solve(), Ok(PartialWitnessGeneratorStatus::Solved));
+    let events_before = acvm.solve_trace().unwrap().events().len();
+
+    let changed_inputs = BTreeMap::from([(w_y, FieldElement::from(7_u128))]).into();
+    assert_eq!(acvm.resolve_delta(changed_inputs), Ok(PartialWitnessGeneratorStatus::Solved));
+
+    // The opcode solving `x_plus_one` is not attempted again.
+    let attempted: Vec<_> = acvm.solve_trace().unwrap().events()[events_before..]
+        .iter()
+        .filter_map(|event| match &event.kind {
+            TraceEventKind::Opcode { location: OpcodeLocation::Acir(index), .. } => Some(*index),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(attempted, [1, 2, 3]);
+
+    let mut fresh_acvm = ACVM::new(StubbedPwg, opcodes, inputs(1, 7));
+    assert_eq!(fresh_acvm.solve(), Ok(PartialWitnessGeneratorStatus::Solved));
+    assert_eq!(acvm.witness_map(), fresh_acvm.witness_map());
+    assert_eq!(acvm.witness_map()[&w_read], FieldElement::from(14_u128));
+}
+
+#[test]
+fn lifted_brillig_opcodes_assign_the_outputs_of_the_hint() {
+    let (w_x, w_y, w_output) = (Witness(1), Witness(2), Witness(3));
+    let field_op =
+        |op, lhs: usize, rhs: usize, destination: usize| brillig_vm::Opcode::BinaryFieldOp {
+            op,
+            lhs: RegisterIndex::from(lhs),
+            rhs: RegisterIndex::from(rhs),
+            destination: RegisterIndex::from(destination),
+        };
+    // output = (x * (y + 1) * x + 5) / 2 - (y + 1), the second input being y + 1
+    let bytecode = vec![
+        field_op(BinaryFieldOp::Mul, 0, 1, 2),
+        field_op(BinaryFieldOp::Mul, 2, 0, 2),
+        brillig_vm::Opcode::Const {
+            destination: RegisterIndex::from(3),
+            value: Value::from(5u128),
+        },
+        field_op(BinaryFieldOp::Add, 2, 3, 2),
+        brillig_vm::Opcode::Const {
+            destination: RegisterIndex::from(3),
+            value: Value::from(2u128),
+        },
+        field_op(BinaryFieldOp::Div, 2, 3, 2),
+        brillig_vm::Opcode::Mov {
+            destination: RegisterIndex::from(0),
+            source: RegisterIndex::from(2),
+        },
+        field_op(BinaryFieldOp::Sub, 0, 1, 0),
+        brillig_vm::Opcode::Stop,
+    ];
+    let brillig = Brillig {
+        inputs: vec![
+            BrilligInputs::Single(w_x.into()),
+            BrilligInputs::Single(&Expression::from(w_y) + &Expression::one()),
+        ],
+        outputs: vec![BrilligOutputs::Simple(w_output)],
+        foreign_call_results: vec![],
+        bytecode,
+        predicate: None,
+    };
+    let witness_assignments: WitnessMap =
+        BTreeMap::from([(w_x, FieldElement::from(3u128)), (w_y, FieldElement::from(4u128))]).into();
+
+    let lifted = lift_brillig(&brillig, 3).expect("bytecode is straight-line arithmetic");
+    // The product of three values is split by an intermediate witness.
+    assert_eq!(lifted.current_witness_index, 4);
+
+    let mut acvm =
+        ACVM::new(StubbedPwg, vec![Opcode::Brillig(brillig.clone())], witness_assignments.clone());
+    assert_eq!(acvm.solve(), Ok(PartialWitnessGeneratorStatus::Solved));
+    let expected = acvm.witness_map()[&w_output];
+    let mut acvm = ACVM::new(StubbedPwg, lifted.opcodes, witness_assignments);
+    assert_eq!(acvm.solve(), Ok(PartialWitnessGeneratorStatus::Solved));
+    assert_eq!(acvm.witness_map()[&w_output], expected);
+    assert_eq!(expected, FieldElement::from(20u128));
+
+    // Comparisons and divisions by values only known at runtime are not lifted.
+    for op in [BinaryFieldOp::Equals, BinaryFieldOp::Div] {
+        let brillig = Brillig { bytecode: vec![field_op(op, 0, 1, 0)], ..brillig.clone() };
+        assert_eq!(lift_brillig(&brillig, 3), None);
+    }
+    let brillig = Brillig { bytecode: vec![brillig_vm::Opcode::Trap], ..brillig };
+    assert_eq!(lift_brillig(&brillig, 3), None);
+}
+
+#[test]
+fn range_batch_checks_each_input_against_its_bit_size() {
+    let range_batch = |inputs: Vec<(Witness, u32)>| {
+        Opcode::BlackBoxFuncCall(BlackBoxFuncCall::RangeBatch {
+            inputs: inputs
+                .into_iter()
+                .map(|(witness, num_bits)| FunctionInput { witness, num_bits })
+                .collect(),
+        })
+    };
+    let witness_assignments: WitnessMap = BTreeMap::from([
+        (Witness(1), FieldElement::from(255u128)),
+        (Witness(2), FieldElement::from(256u128)),
+    ])
+    .into();
+
+    let opcodes = vec![range_batch(vec![(Witness(1), 8), (Witness(2), 9)])];
+    let mut acvm = ACVM::new(StubbedPwg, opcodes, witness_assignments.clone());
+    let solver_status = acvm.solve().expect("both inputs fit within their bit sizes");
+    assert_eq!(solver_status, PartialWitnessGeneratorStatus::Solved, "should be fully solved");
+
+    let opcodes = vec![
+        range_batch(vec![(Witness(1), 8)]),
+        range_batch(vec![(Witness(1), 8), (Witness(2), 8)]),
+    ];
+    let mut acvm = ACVM::new(StubbedPwg, opcodes, witness_assignments);
+    let err = acvm.solve().expect_err("second input does not fit within 8 bits");
+    assert_eq!(
+        err,
+        OpcodeResolutionError::RangeCheckFailed {
+            witness: Witness(2),
+            value: FieldElement::from(256u128),
+            num_bits: 8,
+            opcode_location: ErrorLocation::Resolved(OpcodeLocation::Acir(1))
+        }
+    );
+}
+
+#[test]
+fn big_integer_directives_divide_and_invert_limbs() {
+    let limbs = |values: &[i128]| -> Vec<Expression> {
+        values
+            .iter()
+            .map(|&value| {
+                let magnitude = FieldElement::from(value.unsigned_abs());
+                Expression::from(if value < 0 { -magnitude } else { magnitude })
+            })
+            .collect()
+    };
+    let solve = |directive: Directive| {
+        let mut acvm = ACVM::new(StubbedPwg, vec![Opcode::Directive(directive)], WitnessMap::new());
+        acvm.solve().map(|_| acvm.witness_map().clone())
+    };
+    let (q, r) = (vec![Witness(1), Witness(2)], vec![Witness(3)]);
+
+    // 0x1234 = 46 * 100 + 60
+    let witness_map = solve(Directive::BigQuotient {
+        a: limbs(&[0x34, 0x12]),
+        b: limbs(&[100]),
+        q: q.clone(),
+        r: r.clone(),
+        limb_bits: 8,
+    })
+    .expect("quotient and remainder fit within their limbs");
+    assert_eq!(witness_map[&Witness(1)], FieldElement::from(46_u128));
+    assert_eq!(witness_map[&Witness(2)], FieldElement::zero());
+    assert_eq!(witness_map[&Witness(3)], FieldElement::from(60_u128));
+
+    // Limbs are signed: -1 + 2 * 2^8 = 511 = 5 * 100 + 11
+    let witness_map = solve(Directive::BigQuotient {
+        a: limbs(&[-1, 2]),
+        b: limbs(&[100]),
+        q: q.clone(),
+        r: r.clone(),
+        limb_bits: 8,
+    })
+    .expect("quotient and remainder fit within their limbs");
+    assert_eq!(witness_map[&Witness(1)], FieldElement::from(5_u128));
+    assert_eq!(witness_map[&Witness(3)], FieldElement::from(11_u128));
+
+    let err = solve(Directive::BigQuotient {
+        a: limbs(&[0x34, 0x12]),
+        b: limbs(&[0]),
+        q: q.clone(),
+        r: r.clone(),
+        limb_bits: 8,
+    });
+    assert!(err.is_err(), "division by zero should fail");
+    let err = solve(Directive::BigQuotient {
+        a: limbs(&[0x34, 0x12]),
+        b: limbs(&[1]),
+        q: vec![Witness(1)],
+        r,
+        limb_bits: 8,
+    });
+    assert!(err.is_err(), "quotient does not fit within a single limb");
+
+    let invert = |a: i128| {
+        let witness_map = solve(Directive::BigInvert {
+            a: limbs(&[a]),
+            modulus: limbs(&[7]),
+            result: vec![Witness(1)],
+            limb_bits: 8,
+        })
+        .expect("modulus is positive");
+        witness_map[&Witness(1)]
+    };
+    assert_eq!(invert(3), FieldElement::from(5_u128));
+    assert_eq!(invert(-3), FieldElement::from(2_u128));
+    assert_eq!(invert(14), FieldElement::zero(), "multiples of the modulus have no inverse");
+}
+
+#[test]
+fn hint_checks_locate_hints_assigning_wrong_values() {
+    // The Brillig opcode is meant to invert `x` but returns `x` itself.
+    let (w_x, w_x_inverse) = (Witness(1), Witness(2));
+    let opcodes = vec![
+        Opcode::Brillig(Brillig {
+            inputs: vec![BrilligInputs::Single(w_x.into())],
+            outputs: vec![BrilligOutputs::Simple(w_x_inverse)],
+            bytecode: vec![brillig_vm::Opcode::Stop],
+            predicate: None,
+            foreign_call_results: vec![],
+        }),
+        Opcode::AssertZero(Expression::default()),
+        Opcode::AssertZero(Expression {
+            mul_terms: smallvec![(FieldElement::one(), w_x, w_x_inverse)],
+            linear_combinations: smallvec![],
+            q_c: -FieldElement::one(),
+        }),
+    ];
+    let witness_assignments: WitnessMap = BTreeMap::from([(w_x, FieldElement::from(2u128))]).into();
+
+    // Without hint checks, the error is located at the failing constraint.
+    let mut acvm = ACVM::new(StubbedPwg, opcodes.clone(), witness_assignments.clone());
+    let err = acvm.solve().expect_err("constraint should not be satisfied");
+    assert_eq!(err.opcode_location(), Some(OpcodeLocation::Acir(2)));
+
+    let mut acvm = ACVM::new(StubbedPwg, opcodes, witness_assignments).with_hint_checks();
+    let err = acvm.solve().expect_err("hint outputs should not satisfy the constraint");
+    assert_eq!(
+        err,
+        OpcodeResolutionError::UnsatisfiedHintOutputs {
+            hint_location: OpcodeLocation::Acir(0),
+            constraint_location: OpcodeLocation::Acir(2),
+        }
+    );
+    assert_eq!(err.opcode_location(), Some(OpcodeLocation::Acir(0)));
+}
+
+#[test]
+fn hint_checks_accept_correct_hints() {
+    // The quotient directive assigns q and r such that a = q * b + r.
+    let (w_a, w_q, w_r) = (Witness(1), Witness(2), Witness(3));
+    let opcodes = vec![
+        Opcode::Directive(Directive::Quotient(QuotientDirective {
+            a: w_a.into(),
+            b: Expression::from_field(FieldElement::from(3u128)),
+            q: w_q,
+            r: w_r,
+            predicate: None,
+        })),
+        Opcode::AssertZero(Expression {
+            mul_terms: smallvec![],
+            linear_combinations: smallvec![
+                (FieldElement::from(3u128), w_q),
+                (FieldElement::one(), w_r),
+                (-FieldElement::one(), w_a),
+            ],
+            q_c: FieldElement::zero(),
+        }),
+    ];
+    let witness_assignments = BTreeMap::from([(w_a, FieldElement::from(10u128))]).into();
+
+    let mut acvm = ACVM::new(StubbedPwg, opcodes, witness_assignments).with_hint_checks();
+    let solver_status = acvm.solve().expect("hint outputs should satisfy the constraint");
+    assert_eq!(solver_status, PartialWitnessGeneratorStatus::Solved);
+    assert_eq!(acvm.witness_map()[&w_q], FieldElement::from(3u128));
+    assert_eq!(acvm.witness_map()[&w_r], FieldElement::one());
+}
+
+#[test]
+fn implicit_zeros_are_assigned_to_unassigned_witnesses() {
+    // `out = a + b + c`, where `b` and `c` are optional inputs of which only `c` is provided.
+    let (w_a, w_b, w_c, w_out, w_unused) =
+        (Witness(1), Witness(2), Witness(3), Witness(4), Witness(5));
+    let opcodes = vec![Opcode::AssertZero(Expression {
+        mul_terms: smallvec![],
+        linear_combinations: smallvec![
+            (FieldElement::one(), w_a),
+            (FieldElement::one(), w_b),
+            (FieldElement::one(), w_c),
+            (-FieldElement::one(), w_out),
+        ],
+        q_c: FieldElement::zero(),
+    })];
+    let witness_assignments: WitnessMap =
+        BTreeMap::from([(w_a, FieldElement::from(2u128)), (w_c, FieldElement::from(3u128))]).into();
+
+    let mut acvm = ACVM::new(StubbedPwg, opcodes.clone(), witness_assignments.clone());
+    assert!(matches!(acvm.solve(), Err(OpcodeResolutionError::OpcodeNotSolvable(_))));
+
+    let mut acvm = ACVM::new(StubbedPwg, opcodes, witness_assignments)
+        .with_implicit_zeros([w_b, w_c, w_unused]);
+    let solver_status = acvm.solve().expect("optional inputs should default to zero");
+    assert_eq!(solver_status, PartialWitnessGeneratorStatus::Solved);
+    assert_eq!(acvm.witness_map()[&w_b], FieldElement::zero());
+    assert_eq!(acvm.witness_map()[&w_out], FieldElement::from(5u128));
+    // Only the optional input which was left out and is referenced by the circuit is defaulted.
+    assert_eq!(acvm.applied_implicit_zeros().iter().collect::<Vec<_>>(), vec![&w_b]);
+    assert!(!acvm.witness_map().contains_key(&w_unused));
+}
+
+#[test]
+fn resolution_plan_is_reused_with_other_inputs() {
+    // `c = 2 * b` is listed before `b = a + 1`, so it is deferred to a second pass.
+    let (w_a, w_b, w_c) = (Witness(1), Witness(2), Witness(3));
+    let opcodes = vec![
+        Opcode::AssertZero(Expression {
+            mul_terms: smallvec![],
+            linear_combinations: smallvec![
+                (FieldElement::from(2u128), w_b),
+                (-FieldElement::one(), w_c)
+            ],
+            q_c: FieldElement::zero(),
+        }),
+        Opcode::AssertZero(Expression {
+            mul_terms: smallvec![],
+            linear_combinations: smallvec![(FieldElement::one(), w_a), (-FieldElement::one(), w_b)],
+            q_c: FieldElement::one(),
+        }),
+    ];
+
+    let inputs = |a: u128| WitnessMap::from(BTreeMap::from([(w_a, FieldElement::from(a))]));
+    let mut acvm = ACVM::new(StubbedPwg, opcodes.clone(), inputs(1));
+    assert_eq!(acvm.solve(), Ok(PartialWitnessGeneratorStatus::Solved));
+    let plan = acvm.resolution_plan();
+    assert_eq!(plan.order(), [1, 0]);
+
+    let mut acvm = ACVM::new(StubbedPwg, opcodes, inputs(4)).with_resolution_plan(&plan);
+    assert_eq!(acvm.solve(), Ok(PartialWitnessGeneratorStatus::Solved));
+    assert_eq!(acvm.witness_map()[&w_c], FieldElement::from(10u128));
+    assert_eq!(acvm.resolution_plan(), plan);
+}
+
+#[test]
+fn overwritten_witnesses_record_conflicting_opcodes() {
+    // `a + b = c` and `2 * c = d`.
+    let (w_a, w_b, w_c, w_d) = (Witness(1), Witness(2), Witness(3), Witness(4));
+    let opcodes = vec![
+        Opcode::AssertZero(Expression {
+            mul_terms: smallvec![],
+            linear_combinations: smallvec![
+                (FieldElement::one(), w_a),
+                (FieldElement::one(), w_b),
+                (-FieldElement::one(), w_c)
+            ],
+            q_c: FieldElement::zero(),
+        }),
+        Opcode::AssertZero(Expression {
+            mul_terms: smallvec![],
+            linear_combinations: smallvec![
+                (FieldElement::from(2u128), w_c),
+                (-FieldElement::one(), w_d)
+            ],
+            q_c: FieldElement::zero(),
+        }),
+    ];
+    let witness_assignments: WitnessMap = BTreeMap::from([
+        (w_a, FieldElement::from(1u128)),
+        (w_b, FieldElement::from(2u128)),
+        (w_c, FieldElement::from(3u128)),
+    ])
+    .into();
+
+    // Overwriting `c` before solving makes the first opcode conflict with it rather than fail.
+    let mut acvm = ACVM::new(StubbedPwg, opcodes.clone(), witness_assignments.clone());
+    assert_eq!(
+        acvm.overwrite_witness(w_c, FieldElement::from(4u128)),
+        Some(FieldElement::from(3u128))
+    );
+    assert_eq!(acvm.solve(), Ok(PartialWitnessGeneratorStatus::Solved));
+    assert_eq!(acvm.witness_map()[&w_d], FieldElement::from(8u128));
+    assert_eq!(
+        acvm.witness_overwrites(),
+        [WitnessOverwrite {
+            witness: w_c,
+            previous_value: Some(FieldElement::from(3u128)),
+            value: FieldElement::from(4u128),
+            conflicts: vec![OpcodeLocation::Acir(0)],
+        }]
+    );
+
+    // With hint checks, overwriting a solved witness reports the constraints it breaks.
+    let mut acvm = ACVM::new(StubbedPwg, opcodes, witness_assignments).with_hint_checks();
+    assert_eq!(acvm.solve(), Ok(PartialWitnessGeneratorStatus::Solved));
+    acvm.overwrite_witness(w_d, FieldElement::from(5u128));
+    acvm.overwrite_witness(w_b, FieldElement::from(1u128));
+    let conflicts: Vec<_> =
+        acvm.witness_overwrites().iter().map(|overwrite| overwrite.conflicts.clone()).collect();
+    assert_eq!(conflicts, [vec![OpcodeLocation::Acir(1)], vec![OpcodeLocation::Acir(0)]]);
+}
+
+#[test]
+fn brillig_failure_reports_opcode_location() {
+    let brillig_opcode = Opcode::Brillig(Brillig {
+        inputs: vec![],
+        outputs: vec![],
+        bytecode: vec![
+            brillig_vm::Opcode::Const {
+                destination: RegisterIndex::from(0),
+                value: Value::from(1u128),
+            },
+            brillig_vm::Opcode::Trap,
+        ],
+        predicate: None,
+        foreign_call_results: vec![],
+    });
+
+    let opcodes = vec![Opcode::AssertZero(Expression::default()), brillig_opcode];
+
+    let mut acvm = ACVM::new(StubbedPwg, opcodes, WitnessMap::new());
+    let err = acvm.solve().expect_err("brillig function should trap");
+    assert_eq!(
+        err.opcode_location(),
+        Some(OpcodeLocation::Brillig { acir_index: 1, brillig_index: 1 })
+    );
+}
+
+#[test]
+fn brillig_failure_reports_call_stack_and_foreign_calls() {
+    // The bytecode calls a function which makes a foreign call and then traps.
+    let register = RegisterIndex::from(0);
+    let brillig_opcode = Opcode::Brillig(Brillig {
+        inputs: vec![BrilligInputs::Single(Witness(1).into())],
+        outputs: vec![],
+        bytecode: vec![
+            brillig_vm::Opcode::Call { location: 2 },
+            brillig_vm::Opcode::Stop,
+            brillig_vm::Opcode::ForeignCall {
+                function: "oracle".into(),
+                destinations: vec![RegisterOrMemory::RegisterIndex(register)],
+                inputs: vec![RegisterOrMemory::RegisterIndex(register)],
+            },
+            brillig_vm::Opcode::Trap,
+        ],
+        predicate: None,
+        foreign_call_results: vec![],
+    });
+    let witness_assignments = BTreeMap::from([(Witness(1), FieldElement::from(5u128))]).into();
+
+    let mut acvm = ACVM::new(StubbedPwg, vec![brillig_opcode], witness_assignments);
+    assert_eq!(acvm.solve(), Ok(PartialWitnessGeneratorStatus::RequiresForeignCall));
+    let call = acvm.get_pending_foreign_call().unwrap().clone();
+    let result = ForeignCallResult::from(Value::from(7u128));
+    acvm.resolve_pending_foreign_call(result.clone());
+
+    let err = acvm.solve().expect_err("brillig function should trap");
+    match err {
+        OpcodeResolutionError::BrilligFunctionFailed {
+            opcode_location,
+            call_stack,
+            foreign_calls,
+            ..
+        } => {
+            assert_eq!(
+                opcode_location,
+                ErrorLocation::Resolved(OpcodeLocation::Brillig {
+                    acir_index: 0,
+                    brillig_index: 3
+                })
+            );
+            assert_eq!(call_stack, [0]);
+            assert_eq!(call.function, "oracle");
+            assert_eq!(foreign_calls, [ResolvedForeignCall { call, result }]);
+        }
+        err => panic!("unexpected error: {err}"),
+    }
+}
+
+#[test]
+fn solve_trace_records_opcodes_and_foreign_call_waits() {
+    // An oracle returns its input, which is then constrained by an arithmetic opcode deferred to the next pass.
+    let register = RegisterIndex::from(0);
+    let brillig_opcode = Opcode::Brillig(Brillig {
+        inputs: vec![BrilligInputs::Single(Witness(1).into())],
+        outputs: vec![BrilligOutputs::Simple(Witness(2))],
+        bytecode: vec![brillig_vm::Opcode::ForeignCall {
+            function: "oracle".into(),
+            destinations: vec![RegisterOrMemory::RegisterIndex(register)],
+            inputs: vec![RegisterOrMemory::RegisterIndex(register)],
+        }],
+        predicate: None,
+        foreign_call_results: vec![],
+    });
+    let mut check = Expression::from_field(-FieldElement::from(25u128));
+    check.push_multiplication_term(FieldElement::one(), Witness(2), Witness(2));
+    let opcodes = vec![Opcode::AssertZero(check), brillig_opcode];
+    let witness_assignments = BTreeMap::from([(Witness(1), FieldElement::from(5u128))]).into();
+
+    let mut acvm = ACVM::new(StubbedPwg, opcodes, witness_assignments).with_solve_trace();
+    assert_eq!(acvm.solve(), Ok(PartialWitnessGeneratorStatus::RequiresForeignCall));
+    acvm.resolve_pending_foreign_call(ForeignCallResult::from(Value::from(5u128)));
+    assert_eq!(acvm.solve(), Ok(PartialWitnessGeneratorStatus::Solved));
+
+    let trace = acvm.solve_trace().unwrap();
+    let kinds: Vec<_> = trace.events().iter().map(|event| event.kind.clone()).collect();
+    let opcode = |name: &str, index, outcome| TraceEventKind::Opcode {
+        name: name.into(),
+        location: OpcodeLocation::Acir(index),
+        outcome,
+    };
+    assert_eq!(
+        kinds,
+        vec![
+            opcode("assert_zero", 0, TraceOutcome::Stalled),
+            opcode("brillig", 1, TraceOutcome::ForeignCall),
+            TraceEventKind::ForeignCallWait {
+                function: "oracle".into(),
+                location: OpcodeLocation::Acir(1)
+            },
+            opcode("brillig", 1, TraceOutcome::Solved),
+            opcode("assert_zero", 0, TraceOutcome::Solved),
+        ]
+    );
+    assert!(trace.events().windows(2).all(|events| events[0].start <= events[1].start));
+
+    let chrome_trace = trace.to_chrome_trace();
+    let trace_events = chrome_trace["traceEvents"].as_array().unwrap();
+    let wait = trace_events.iter().find(|event| event["name"] == "oracle").unwrap();
+    assert_eq!(wait["ph"], "X");
+    assert_eq!(wait["args"]["location"], "1");
+}
+
+#[test]
+fn foreign_call_registry_rejects_calls_not_matching_their_signature() {
+    // The oracle is passed an array of two values and returns a single value.
+    let brillig_opcode = Opcode::Brillig(Brillig {
+        inputs: vec![],
+        outputs: vec![],
+        bytecode: vec![
+            brillig_vm::Opcode::Const {
+                destination: RegisterIndex::from(0),
+                value: Value::from(0u128),
+            },
+            brillig_vm::Opcode::ForeignCall {
+                function: "oracle".into(),
+                destinations: vec![RegisterOrMemory::RegisterIndex(RegisterIndex::from(1))],
+                inputs: vec![RegisterOrMemory::HeapArray(RegisterIndex::from(0), 2)],
+            },
+        ],
+        predicate: None,
+        foreign_call_results: vec![],
+    });
+    let opcodes = vec![Opcode::AssertZero(Expression::default()), brillig_opcode];
+    let location = OpcodeLocation::Brillig { acir_index: 1, brillig_index: 1 };
+    let acvm = || ACVM::new(StubbedPwg, opcodes.clone(), WitnessMap::new());
+
+    let registry = ForeignCallRegistry::new().register(
+        "oracle",
+        vec![ForeignCallParamShape::Array(2)],
+        vec![ForeignCallParamShape::Single],
+    );
+    assert!(acvm().with_foreign_call_registry(&registry).is_ok());
+
+    let err = acvm().with_foreign_call_registry(&ForeignCallRegistry::new()).err();
+    assert_eq!(
+        err,
+        Some(ForeignCallSignatureError::UnknownFunction { function: "oracle".into(), location })
+    );
+
+    let registry = ForeignCallRegistry::new().register(
+        "oracle",
+        vec![ForeignCallParamShape::Vector],
+        vec![ForeignCallParamShape::Single],
+    );
+    let err = acvm().with_foreign_call_registry(&registry).err().unwrap();
+    assert_eq!(
+        err,
+        ForeignCallSignatureError::ShapeMismatch {
+            function: "oracle".into(),
+            location,
+            kind: ForeignCallParamKind::Input,
+            index: 0,
+            expected: ForeignCallParamShape::Vector,
+            actual: ForeignCallParamShape::Array(2),
+        }
+    );
+    assert_eq!(
+        err.to_string(),
+        "input 0 of foreign call `oracle` at opcode 1.1 is an array of 2 values rather than a vector"
+    );
+
+    let registry = ForeignCallRegistry::new().register(
+        "oracle",
+        vec![ForeignCallParamShape::Array(2)],
+        vec![ForeignCallParamShape::Single, ForeignCallParamShape::Single],
+    );
+    assert_eq!(
+        registry.validate(&opcodes),
+        Err(ForeignCallSignatureError::ArityMismatch {
+            function: "oracle".into(),
+            location,
+            kind: ForeignCallParamKind::Output,
+            expected: 2,
+            actual: 1,
+        })
+    );
+}
+
+#[test]
+fn secret_audit_reports_brillig_opcodes_leaking_secrets() {
+    // The bytecode branches on its first input, a secret, then passes its second, public, input to an oracle.
+    let r = RegisterIndex::from;
+    let brillig_opcode = Opcode::Brillig(Brillig {
+        inputs: vec![
+            BrilligInputs::Single(Witness(1).into()),
+            BrilligInputs::Single(Witness(2).into()),
+        ],
+        outputs: vec![],
+        bytecode: vec![
+            brillig_vm::Opcode::JumpIfNot { condition: r(0), location: 1 },
+            brillig_vm::Opcode::JumpIfNot { condition: r(1), location: 2 },
+            brillig_vm::Opcode::ForeignCall {
+                function: "log".into(),
+                destinations: vec![],
+                inputs: vec![RegisterOrMemory::RegisterIndex(r(1))],
+            },
+            brillig_vm::Opcode::Stop,
+        ],
+        predicate: None,
+        foreign_call_results: vec![],
+    });
+    let witness_assignments = BTreeMap::from([
+        (Witness(1), FieldElement::from(5u128)),
+        (Witness(2), FieldElement::from(6u128)),
+    ])
+    .into();
+
+    let mut acvm = ACVM::new(StubbedPwg, vec![brillig_opcode], witness_assignments)
+        .with_secret_audit([Witness(1)]);
+    assert_eq!(acvm.solve(), Ok(PartialWitnessGeneratorStatus::RequiresForeignCall));
+    acvm.resolve_pending_foreign_call(ForeignCallResult { values: vec![] });
+    assert_eq!(acvm.solve(), Ok(PartialWitnessGeneratorStatus::Solved));
+
+    assert_eq!(
+        acvm.secret_audit_findings(),
+        [SecretAuditFinding {
+            location: OpcodeLocation::Brillig { acir_index: 0, brillig_index: 0 },
+            kind: LeakKind::Branch,
+        }]
+    );
+}
+
+#[test]
+fn multi_scalar_mul_matches_embedded_curve_ops() {
+    let input = |witness| FunctionInput { witness: Witness(witness), num_bits: 254 };
+    let (g_x, g_y) = (Witness(1), Witness(2));
+    let (lhs_scalar, rhs_scalar) = (Witness(3), Witness(10));
+
+    let opcodes = vec![
+        // 4G = double(double(G))
+        Opcode::BlackBoxFuncCall(BlackBoxFuncCall::EmbeddedCurveDouble {
+            input_x: input(1),
+            input_y: input(2),
+            outputs: (Witness(4), Witness(5)),
+        }),
+        Opcode::BlackBoxFuncCall(BlackBoxFuncCall::EmbeddedCurveDouble {
+            input_x: input(4),
+            input_y: input(5),
+            outputs: (Witness(6), Witness(7)),
+        }),
+        // 5G = 4G + G
+        Opcode::BlackBoxFuncCall(BlackBoxFuncCall::EmbeddedCurveAdd {
+            input1_x: input(6),
+            input1_y: input(7),
+            input2_x: input(1),
+            input2_y: input(2),
+            outputs: (Witness(8), Witness(9)),
+        }),
+        // 2 * G + 3 * G
+        Opcode::BlackBoxFuncCall(BlackBoxFuncCall::MultiScalarMul {
+            points: vec![input(1), input(2), input(1), input(2)],
+            scalars: vec![input(3), input(10)],
+            outputs: (Witness(11), Witness(12)),
+        }),
+    ];
+
+    // The generator of the Grumpkin curve.
+    let witness_assignments = BTreeMap::from([
+        (g_x, FieldElement::one()),
+        (
+            g_y,
+            FieldElement::from_hex(
+                "0x0000000000000002cf135e7506a45d632d270d45f1181294833fc48d823f272c",
+            )
+            .unwrap(),
+        ),
+        (lhs_scalar, FieldElement::from(2u128)),
+        (rhs_scalar, FieldElement::from(3u128)),
+    ])
+    .into();
+
+    let mut acvm = ACVM::new(StubbedPwg, opcodes, witness_assignments);
+    let solver_status = acvm.solve().expect("should solve embedded curve operations");
+    assert_eq!(solver_status, PartialWitnessGeneratorStatus::Solved, "should be fully solved");
+
+    let witness_map = acvm.witness_map();
+    assert_eq!(witness_map[&Witness(8)], witness_map[&Witness(11)]);
+    assert_eq!(witness_map[&Witness(9)], witness_map[&Witness(12)]);
+}
+
+fn recursive_aggregation_opcode(
+    input_aggregation_object: Option<Vec<Witness>>,
+    output_aggregation_object: Vec<Witness>,
+) -> Opcode {
+    let input = |witness| FunctionInput { witness, num_bits: FieldElement::max_num_bits() };
+    Opcode::BlackBoxFuncCall(BlackBoxFuncCall::RecursiveAggregation {
+        verification_key: vec![input(Witness(1))],
+        proof: vec![input(Witness(2))],
+        public_inputs: vec![],
+        key_hash: input(Witness(3)),
+        input_aggregation_object: input_aggregation_object
+            .map(|object| object.into_iter().map(input).collect()),
+        output_aggregation_object,
+    })
+}
+
+#[test]
+fn recursive_aggregation_assigns_output_aggregation_object() {
+    let first_object = vec![Witness(4), Witness(5)];
+    let second_object = vec![Witness(6), Witness(7)];
+    let opcodes = vec![
+        recursive_aggregation_opcode(None, first_object.clone()),
+        recursive_aggregation_opcode(Some(first_object), second_object.clone()),
+    ];
+    let witness_assignments = BTreeMap::from([
+        (Witness(1), FieldElement::from(1_i128)),
+        (Witness(2), FieldElement::from(2_i128)),
+        (Witness(3), FieldElement::from(3_i128)),
+    ])
+    .into();
+
+    let mut acvm = ACVM::new(StubbedPwg, opcodes, witness_assignments);
+    let solver_status = acvm.solve().expect("should aggregate both proofs");
+    assert_eq!(solver_status, PartialWitnessGeneratorStatus::Solved, "should be fully solved");
+
+    let witness_map = acvm.witness_map();
+    for output in second_object {
+        assert_eq!(witness_map[&output], FieldElement::from(6_i128));
+    }
+}
+
+#[test]
+fn recursive_aggregation_rejects_mismatched_aggregation_objects() {
+    let opcodes =
+        vec![recursive_aggregation_opcode(Some(vec![Witness(4)]), vec![Witness(5), Witness(6)])];
+    let witness_assignments = BTreeMap::from([
+        (Witness(1), FieldElement::from(1_i128)),
+        (Witness(2), FieldElement::from(2_i128)),
+        (Witness(3), FieldElement::from(3_i128)),
+        (Witness(4), FieldElement::from(4_i128)),
+    ])
+    .into();
+
+    let mut acvm = ACVM::new(StubbedPwg, opcodes, witness_assignments);
+    let err = acvm.solve().expect_err("aggregation objects should be the same size");
+    assert!(matches!(
+        err,
+        OpcodeResolutionError::BlackBoxFunctionFailed(BlackBoxFunc::RecursiveAggregation, _)
+    ));
+}
+
+/// Defers recursive aggregation, which is then computed through [`StubbedPwg`].
+struct DeferringPwg;
+
+impl PartialWitnessGenerator for DeferringPwg {
+    fn schnorr_verify(
+        &self,
+        _public_key_x: &FieldElement,
+        _public_key_y: &FieldElement,
+        _signature_s: &FieldElement,
+        _signature_e: &FieldElement,
+        _message: &[u8],
+    ) -> Result<bool, OpcodeResolutionError> {
+        panic!("Path not trodden by this test")
+    }
+
+    fn pedersen(
+        &self,
+        _inputs: &[FieldElement],
+        _domain_separator: u32,
+    ) -> Result<(FieldElement, FieldElement), OpcodeResolutionError> {
+        panic!("Path not trodden by this test")
+    }
+
+    fn fixed_base_scalar_mul(
+        &self,
+        _input: &FieldElement,
+    ) -> Result<(FieldElement, FieldElement), OpcodeResolutionError> {
+        panic!("Path not trodden by this test")
+    }
+
+    fn defers_black_box_func(&self, func: BlackBoxFunc) -> bool {
+        func == BlackBoxFunc::RecursiveAggregation
+    }
+}
+
+#[test]
+fn deferred_black_box_calls_are_resolved_by_the_caller() {
+    let first_object = vec![Witness(4), Witness(5)];
+    let second_object = vec![Witness(6), Witness(7)];
+    let opcodes = vec![
+        recursive_aggregation_opcode(None, first_object.clone()),
+        recursive_aggregation_opcode(Some(first_object), second_object.clone()),
+        // Solved while the first call is pending.
+        Opcode::AssertZero(Expression {
+            mul_terms: smallvec![],
+            linear_combinations: smallvec![
+                (FieldElement::one(), Witness(1)),
+                (-FieldElement::one(), Witness(8)),
+            ],
+            q_c: FieldElement::zero(),
+        }),
+    ];
+    let witness_assignments = BTreeMap::from([
+        (Witness(1), FieldElement::from(1_i128)),
+        (Witness(2), FieldElement::from(2_i128)),
+        (Witness(3), FieldElement::from(3_i128)),
+    ])
+    .into();
+
+    let mut acvm = ACVM::new(DeferringPwg, opcodes, witness_assignments);
+    for _ in 0..2 {
+        assert_eq!(acvm.solve(), Ok(PartialWitnessGeneratorStatus::RequiresBlackBoxCall));
+        assert_eq!(acvm.witness_map()[&Witness(8)], FieldElement::one());
+        let call = acvm.get_pending_black_box_call().expect("should have a pending call");
+        assert_eq!(call.function(), BlackBoxFunc::RecursiveAggregation);
+        let outputs = call.compute(&StubbedPwg).expect("should aggregate the proof");
+        acvm.resolve_pending_black_box_call(outputs);
+    }
+    assert_eq!(acvm.solve(), Ok(PartialWitnessGeneratorStatus::Solved));
+
+    let witness_map = acvm.witness_map();
+    for output in second_object {
+        assert_eq!(witness_map[&output], FieldElement::from(6_i128));
+    }
+}
+
+#[test]
+fn deferred_black_box_calls_reject_the_wrong_number_of_outputs() {
+    let opcodes = vec![recursive_aggregation_opcode(None, vec![Witness(4), Witness(5)])];
+    let witness_assignments = BTreeMap::from([
+        (Witness(1), FieldElement::from(1_i128)),
+        (Witness(2), FieldElement::from(2_i128)),
+        (Witness(3), FieldElement::from(3_i128)),
+    ])
+    .into();
+
+    let mut acvm = ACVM::new(DeferringPwg, opcodes, witness_assignments);
+    assert_eq!(acvm.solve(), Ok(PartialWitnessGeneratorStatus::RequiresBlackBoxCall));
+    acvm.resolve_pending_black_box_call(vec![FieldElement::one()]);
+    let err = acvm.solve().expect_err("one value was provided for two outputs");
+    assert!(matches!(
+        err,
+        OpcodeResolutionError::BlackBoxFunctionFailed(BlackBoxFunc::RecursiveAggregation, _)
+    ));
+}
+
+#[test]
+fn black_box_cache_is_shared_across_solves() {
+    let witness_assignments = |verification_key: i128| -> WitnessMap {
+        BTreeMap::from([
+            (Witness(1), FieldElement::from(verification_key)),
+            (Witness(2), FieldElement::from(2_i128)),
+            (Witness(3), FieldElement::from(3_i128)),
+        ])
+        .into()
+    };
+    let cache = BlackBoxCache::new();
+
+    let opcodes = vec![recursive_aggregation_opcode(None, vec![Witness(4), Witness(5)])];
+    let mut acvm = ACVM::new(DeferringPwg, opcodes, witness_assignments(1))
+        .with_black_box_cache(cache.clone());
+    assert_eq!(acvm.solve(), Ok(PartialWitnessGeneratorStatus::RequiresBlackBoxCall));
+    let call = acvm.get_pending_black_box_call().expect("should have a pending call");
+    let outputs = call.compute(&StubbedPwg).expect("should aggregate the proof");
+    acvm.resolve_pending_black_box_call(outputs.clone());
+    assert_eq!(acvm.solve(), Ok(PartialWitnessGeneratorStatus::Solved));
+    assert_eq!(cache.len(), 1);
+
+    // The same call assigning other witnesses is solved from the cache rather than deferred.
+    let opcodes = vec![recursive_aggregation_opcode(None, vec![Witness(6), Witness(7)])];
+    let mut acvm = ACVM::new(DeferringPwg, opcodes.clone(), witness_assignments(1))
+        .with_black_box_cache(cache.clone());
+    assert_eq!(acvm.solve(), Ok(PartialWitnessGeneratorStatus::Solved));
+    assert_eq!(acvm.witness_map()[&Witness(6)], outputs[0]);
+    assert_eq!(acvm.witness_map()[&Witness(7)], outputs[1]);
+
+    // Calls on other input values are not.
+    let mut acvm =
+        ACVM::new(DeferringPwg, opcodes, witness_assignments(4)).with_black_box_cache(cache);
+    assert_eq!(acvm.solve(), Ok(PartialWitnessGeneratorStatus::RequiresBlackBoxCall));
+}
+
+#[test]
+fn blackbox_solver_hashes_match_solved_opcodes() {
+    let message = b"hello world";
+    let inputs: Vec<FunctionInput> = (1..=message.len() as u32)
+        .map(|index| FunctionInput { witness: Witness(index), num_bits: 8 })
+        .collect();
+    let outputs: Vec<Witness> =
+        (0..32).map(|index| Witness(message.len() as u32 + 1 + index)).collect();
+    let witness_assignments: WitnessMap = inputs
+        .iter()
+        .zip(message)
+        .map(|(input, &byte)| (input.witness, FieldElement::from(byte as u128)))
+        .collect::<BTreeMap<_, _>>()
+        .into();
+
+    type HashFunction = fn(&[u8]) -> [u8; 32];
+    let hash_calls: [(BlackBoxFuncCall, HashFunction); 4] = [
+        (
+            BlackBoxFuncCall::SHA256 { inputs: inputs.clone(), outputs: outputs.clone() },
+            blackbox_solver::sha256,
+        ),
+        (
+            BlackBoxFuncCall::Blake2s { inputs: inputs.clone(), outputs: outputs.clone() },
+            blackbox_solver::blake2s256,
+        ),
+        (
+            BlackBoxFuncCall::Blake3 { inputs: inputs.clone(), outputs: outputs.clone() },
+            blackbox_solver::blake3,
+        ),
+        (
+            BlackBoxFuncCall::Keccak256 { inputs: inputs.clone(), outputs: outputs.clone() },
+            blackbox_solver::keccak256,
+        ),
+    ];
+    for (call, hash) in hash_calls {
+        let mut acvm = ACVM::new(
+            StubbedPwg,
+            vec![Opcode::BlackBoxFuncCall(call)],
+            witness_assignments.clone(),
+        );
+        let solver_status = acvm.solve().expect("should hash the message");
+        assert_eq!(solver_status, PartialWitnessGeneratorStatus::Solved, "should be fully solved");
+
+        let witness_map = acvm.witness_map();
+        let digest: Vec<u8> =
+            outputs.iter().map(|output| witness_map[output].to_u128() as u8).collect();
+        assert_eq!(digest, hash(message));
+    }
+}
@@ -0,0 +1,1531 @@
+use acir::{
+    circuit::{
+        directives::Directive,
+        opcodes::{BlackBoxFuncCall, BlockId, FunctionInput, MemOp},
+        Circuit, Opcode,
+    },
+    native_types::{Expression, Witness, WitnessMap},
+    BlackBoxFunc, FieldElement,
+};
+use acvm::{
+    blackbox_solver,
+    compiler::{compile, BackendCapabilities, CircuitSimplifier, CompileError},
+    pwg::{OpcodeResolutionError, PartialWitnessGeneratorStatus, ACVM},
+    testing::MockBackend,
+    Language,
+};
+use blake2::{Blake2b512, Blake2s256};
+use num_bigint::BigUint;
+use sha2::{Sha256, Sha512};
+use sha3::{Digest, Keccak256};
+use stdlib::blackbox_fallbacks::{
+    big_field::{BigField, BigFieldElement, LIMB_BITS},
+    comparison::{less_than, max, min, sort},
+    fixed_point::{self, FixedPointFormat, Rounding},
+    int::{Int32, Int64, Int8},
+    radix::{byte_decomposition, radix_decomposition, Endianness},
+    uint::{UInt128, UInt16, UInt32, UInt64, UInt8, UIntN},
+    Blake2sHasher, Keccak256Hasher, Sha256Hasher,
+};
+use stdlib::helpers::CircuitBuilder;
+
+type HashCall = fn(Vec<FunctionInput>, Vec<Witness>) -> BlackBoxFuncCall;
+type VariableLengthHashCall =
+    fn(Vec<FunctionInput>, FunctionInput, Vec<Witness>) -> BlackBoxFuncCall;
+
+/// Solves a circuit containing a single hash black box call for `message`, returning the resulting digest.
+///
+/// If `use_fallback` is set, the black box call is first replaced by its fallback implementation.
+fn solve_hash(message: &[u8], digest_len: u32, hash_call: HashCall, use_fallback: bool) -> Vec<u8> {
+    let values: Vec<(u128, u32)> = message.iter().map(|&byte| (byte as u128, 8)).collect();
+    solve_hash_call(&values, digest_len, hash_call, use_fallback)
+        .expect("circuit should be satisfied")
+}
+
+/// Solves a circuit containing a single hash black box call for the first `length` bytes of `message`,
+/// returning the resulting digest.
+fn solve_variable_length_hash(
+    message: &[u8],
+    length: usize,
+    digest_len: u32,
+    hash_call: VariableLengthHashCall,
+    use_fallback: bool,
+) -> Vec<u8> {
+    let mut values: Vec<(u128, u32)> = message.iter().map(|&byte| (byte as u128, 8)).collect();
+    values.push((length as u128, 32));
+    let hash_call = |mut inputs: Vec<FunctionInput>, outputs| {
+        let var_message_size = inputs.pop().unwrap();
+        hash_call(inputs, var_message_size, outputs)
+    };
+    solve_hash_call(&values, digest_len, hash_call, use_fallback)
+        .expect("circuit should be satisfied")
+}
+
+/// Solves a circuit containing the hash black box call built by `hash_call` over witnesses assigned to `values`,
+/// each of which is given along with its bit size, returning the resulting digest.
+fn solve_hash_call(
+    values: &[(u128, u32)],
+    digest_len: u32,
+    hash_call: impl FnOnce(Vec<FunctionInput>, Vec<Witness>) -> BlackBoxFuncCall,
+    use_fallback: bool,
+) -> Result<Vec<u8>, OpcodeResolutionError> {
+    let inputs: Vec<Witness> = (1..=values.len() as u32).map(Witness).collect();
+    let outputs: Vec<Witness> =
+        (0..digest_len).map(|i| Witness(values.len() as u32 + 1 + i)).collect();
+
+    let call = hash_call(
+        inputs
+            .iter()
+            .zip(values)
+            .map(|(&witness, &(_, num_bits))| FunctionInput { witness, num_bits })
+            .collect(),
+        outputs.clone(),
+    );
+    let hash_function = call.get_black_box_func();
+    let mut circuit = Circuit {
+        current_witness_index: values.len() as u32 + digest_len,
+        opcodes: vec![Opcode::BlackBoxFuncCall(call)],
+        ..Circuit::default()
+    };
+
+    if use_fallback {
+        let capabilities =
+            BackendCapabilities::new(Language::PLONKCSat { width: 3 }).without(hash_function);
+        circuit = compile(circuit, &capabilities, &CircuitSimplifier::new(values.len() as u32))
+            .expect("hash function should be replaced by its fallback")
+            .0;
+        assert!(
+            circuit.opcodes.iter().all(|opcode| capabilities.supports_opcode(opcode)),
+            "compiled circuit should not contain any unsupported opcodes"
+        );
+    }
+
+    let initial_witness: WitnessMap = inputs
+        .into_iter()
+        .zip(values)
+        .map(|(witness, &(value, _))| (witness, FieldElement::from(value)))
+        .collect::<std::collections::BTreeMap<_, _>>()
+        .into();
+
+    let mut acvm = ACVM::new(MockBackend::default(), circuit.opcodes, initial_witness);
+    let solver_status = acvm.solve()?;
+    assert_eq!(solver_status, PartialWitnessGeneratorStatus::Solved, "should be fully solved");
+
+    let witness_map = acvm.witness_map();
+    Ok(outputs.iter().map(|output| witness_map[output].to_u128() as u8).collect())
+}
+
+fn keccak256_call(inputs: Vec<FunctionInput>, outputs: Vec<Witness>) -> BlackBoxFuncCall {
+    BlackBoxFuncCall::Keccak256 { inputs, outputs }
+}
+
+fn blake3_call(inputs: Vec<FunctionInput>, outputs: Vec<Witness>) -> BlackBoxFuncCall {
+    BlackBoxFuncCall::Blake3 { inputs, outputs }
+}
+
+fn sha512_call(inputs: Vec<FunctionInput>, outputs: Vec<Witness>) -> BlackBoxFuncCall {
+    BlackBoxFuncCall::Sha512 { inputs, outputs }
+}
+
+fn sha256_call(inputs: Vec<FunctionInput>, outputs: Vec<Witness>) -> BlackBoxFuncCall {
+    BlackBoxFuncCall::SHA256 { inputs, outputs }
+}
+
+fn blake2b_call(inputs: Vec<FunctionInput>, outputs: Vec<Witness>) -> BlackBoxFuncCall {
+    BlackBoxFuncCall::Blake2b { inputs, outputs }
+}
+
+fn blake2s_call(inputs: Vec<FunctionInput>, outputs: Vec<Witness>) -> BlackBoxFuncCall {
+    BlackBoxFuncCall::Blake2s { inputs, outputs }
+}
+
+fn keccak256_variable_length_call(
+    inputs: Vec<FunctionInput>,
+    var_message_size: FunctionInput,
+    outputs: Vec<Witness>,
+) -> BlackBoxFuncCall {
+    BlackBoxFuncCall::Keccak256VariableLength { inputs, var_message_size, outputs }
+}
+
+fn sha256_variable_length_call(
+    inputs: Vec<FunctionInput>,
+    var_message_size: FunctionInput,
+    outputs: Vec<Witness>,
+) -> BlackBoxFuncCall {
+    BlackBoxFuncCall::Sha256VariableLength { inputs, var_message_size, outputs }
+}
+
+fn sha512_variable_length_call(
+    inputs: Vec<FunctionInput>,
+    var_message_size: FunctionInput,
+    outputs: Vec<Witness>,
+) -> BlackBoxFuncCall {
+    BlackBoxFuncCall::Sha512VariableLength { inputs, var_message_size, outputs }
+}
+
+fn blake2s_variable_length_call(
+    inputs: Vec<FunctionInput>,
+    var_message_size: FunctionInput,
+    outputs: Vec<Witness>,
+) -> BlackBoxFuncCall {
+    BlackBoxFuncCall::Blake2sVariableLength { inputs, var_message_size, outputs }
+}
+
+fn blake3_variable_length_call(
+    inputs: Vec<FunctionInput>,
+    var_message_size: FunctionInput,
+    outputs: Vec<Witness>,
+) -> BlackBoxFuncCall {
+    BlackBoxFuncCall::Blake3VariableLength { inputs, var_message_size, outputs }
+}
+
+#[test]
+fn hash_inputs_contribute_their_little_endian_bytes() {
+    let values = [(0x0102, 16), (0x03, 4), (0x040506, 24), (0x07, 8)];
+    let message = [0x02, 0x01, 0x03, 0x06, 0x05, 0x04, 0x07];
+
+    let hashes: [(HashCall, u32, Vec<u8>); 5] = [
+        (sha256_call, 32, Sha256::digest(message).to_vec()),
+        (sha512_call, 64, Sha512::digest(message).to_vec()),
+        (blake2s_call, 32, Blake2s256::digest(message).to_vec()),
+        (blake3_call, 32, blake3::hash(&message).as_bytes().to_vec()),
+        (keccak256_call, 32, Keccak256::digest(message).to_vec()),
+    ];
+    for (hash_call, digest_len, expected) in hashes {
+        assert_eq!(solve_hash_call(&values, digest_len, hash_call, false), Ok(expected.clone()));
+        assert_eq!(solve_hash_call(&values, digest_len, hash_call, true), Ok(expected));
+    }
+}
+
+#[test]
+fn hash_inputs_exceeding_their_bit_size_are_rejected() {
+    let values = [(0x01, 8), (0x100, 8)];
+    for use_fallback in [false, true] {
+        assert!(solve_hash_call(&values, 32, keccak256_call, use_fallback).is_err());
+        assert!(solve_hash_call(&values, 64, sha512_call, use_fallback).is_err());
+        assert!(solve_hash_call(&values, 32, blake3_call, use_fallback).is_err());
+    }
+}
+
+#[test]
+fn variable_length_hashes_match_native_implementation() {
+    let message: Vec<u8> = (0..20).map(|i| i * 7 + 3).collect();
+    for length in [0, 7, 20] {
+        let prefix = &message[..length];
+        let native_hashes: [(VariableLengthHashCall, u32, Vec<u8>); 5] = [
+            (sha256_variable_length_call, 32, Sha256::digest(prefix).to_vec()),
+            (sha512_variable_length_call, 64, Sha512::digest(prefix).to_vec()),
+            (blake2s_variable_length_call, 32, Blake2s256::digest(prefix).to_vec()),
+            (blake3_variable_length_call, 32, blake3::hash(prefix).as_bytes().to_vec()),
+            (keccak256_variable_length_call, 32, Keccak256::digest(prefix).to_vec()),
+        ];
+        for (hash_call, digest_len, expected) in native_hashes {
+            assert_eq!(
+                solve_variable_length_hash(&message, length, digest_len, hash_call, false),
+                expected,
+                "digest mismatch for a message of {length} bytes"
+            );
+        }
+    }
+}
+
+#[test]
+fn keccak256_variable_length_fallback_matches_native_implementation() {
+    // The padding moves into a second block once the message fills the rate (136 bytes).
+    let message: Vec<u8> = (0..140).map(|i| (i * 7 + 3) as u8).collect();
+    for length in [0, 135, 136, 140] {
+        assert_eq!(
+            solve_variable_length_hash(&message, length, 32, keccak256_variable_length_call, true),
+            Keccak256::digest(&message[..length]).to_vec(),
+            "digest mismatch for a message of {length} bytes"
+        );
+    }
+}
+
+#[test]
+fn sha512_variable_length_fallback_matches_native_implementation() {
+    // A message of 112 bytes no longer leaves room for the length in its final block.
+    let message: Vec<u8> = (0..120).map(|i| (i * 7 + 3) as u8).collect();
+    for length in [0, 111, 112, 120] {
+        assert_eq!(
+            solve_variable_length_hash(&message, length, 64, sha512_variable_length_call, true),
+            Sha512::digest(&message[..length]).to_vec(),
+            "digest mismatch for a message of {length} bytes"
+        );
+    }
+}
+
+#[test]
+fn keccak256_fallback_empty_message() {
+    let expected = hex_digest("c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470");
+    assert_eq!(solve_hash(&[], 32, keccak256_call, true), expected);
+}
+
+#[test]
+fn keccak256_fallback_matches_native_implementation() {
+    // Lengths on either side of the rate (136 bytes) exercise the padding and multi-block absorption.
+    for len in [1, 135, 136, 200] {
+        let message: Vec<u8> = (0..len).map(|i| (i * 7 + 3) as u8).collect();
+        assert_eq!(
+            solve_hash(&message, 32, keccak256_call, true),
+            Keccak256::digest(&message).to_vec(),
+            "digest mismatch for a message of {len} bytes"
+        );
+    }
+}
+
+#[test]
+fn sha256_fallback_matches_native_implementation() {
+    // A message of 56 bytes no longer leaves room for the length in its final block.
+    for len in [0, 55, 56, 64, 130] {
+        let message: Vec<u8> = (0..len).map(|i| (i * 7 + 3) as u8).collect();
+        assert_eq!(
+            solve_hash(&message, 32, sha256_call, true),
+            Sha256::digest(&message).to_vec(),
+            "digest mismatch for a message of {len} bytes"
+        );
+    }
+}
+
+#[test]
+fn blake2s_fallback_matches_native_implementation() {
+    // A message of 64 bytes exactly fills a block, which is then the final block.
+    for len in [0, 63, 64, 65, 130] {
+        let message: Vec<u8> = (0..len).map(|i| (i * 7 + 3) as u8).collect();
+        assert_eq!(
+            solve_hash(&message, 32, blake2s_call, true),
+            Blake2s256::digest(&message).to_vec(),
+            "digest mismatch for a message of {len} bytes"
+        );
+    }
+}
+
+/// Solves the opcodes built by `hasher` to hash `prefix` followed by each of `suffixes`, returning the digests.
+///
+/// `hasher` is given the witnesses holding the bytes of the prefix and of each suffix,
+/// the outputs of each digest and the builder to add its opcodes to.
+fn solve_streaming_hash(
+    prefix: &[u8],
+    suffixes: &[&[u8]],
+    digest_len: u32,
+    hasher: impl FnOnce(&[Witness], &[Vec<Witness>], &[Vec<Witness>], &mut CircuitBuilder),
+) -> Vec<Vec<u8>> {
+    let mut next_witness = 1;
+    let mut allocate = |len: usize| {
+        let witnesses: Vec<Witness> =
+            (next_witness..next_witness + len as u32).map(Witness).collect();
+        next_witness += len as u32;
+        witnesses
+    };
+    let prefix_bytes = allocate(prefix.len());
+    let suffix_bytes: Vec<Vec<Witness>> =
+        suffixes.iter().map(|suffix| allocate(suffix.len())).collect();
+    let outputs: Vec<Vec<Witness>> =
+        suffixes.iter().map(|_| allocate(digest_len as usize)).collect();
+    let mut builder = CircuitBuilder::new(next_witness);
+    hasher(&prefix_bytes, &suffix_bytes, &outputs, &mut builder);
+    let (_, opcodes) = builder.finalize();
+
+    let mut initial_witness = std::collections::BTreeMap::new();
+    for (witnesses, values) in std::iter::once((&prefix_bytes, prefix))
+        .chain(suffix_bytes.iter().zip(suffixes.iter().copied()))
+    {
+        for (witness, value) in witnesses.iter().zip(values) {
+            initial_witness.insert(*witness, FieldElement::from(*value as u128));
+        }
+    }
+    let mut acvm = ACVM::new(MockBackend::default(), opcodes, initial_witness.into());
+    assert_eq!(acvm.solve(), Ok(PartialWitnessGeneratorStatus::Solved));
+    outputs
+        .iter()
+        .map(|digest| {
+            digest.iter().map(|output| acvm.witness_map()[output].to_u128() as u8).collect()
+        })
+        .collect()
+}
+
+#[test]
+fn streaming_hashers_reuse_the_state_of_a_common_prefix() {
+    let prefix: Vec<u8> = (0..100).map(|i| (i * 7 + 3) as u8).collect();
+    let suffixes: [&[u8]; 3] = [&[], b"abc", &[0x5a; 150]];
+    let to_expressions =
+        |witnesses: &[Witness]| witnesses.iter().copied().map(Expression::from).collect::<Vec<_>>();
+
+    // Defines a closure hashing the prefix once, then each suffix in chunks of 7 bytes from a clone of the hasher.
+    macro_rules! streaming_hash {
+        ($hasher:ty) => {
+            |prefix: &[Witness],
+             suffixes: &[Vec<Witness>],
+             outputs: &[Vec<Witness>],
+             builder: &mut CircuitBuilder| {
+                let mut hasher = <$hasher>::new(builder);
+                hasher.update(&to_expressions(prefix), builder);
+                for (suffix, outputs) in suffixes.iter().zip(outputs) {
+                    let mut hasher = hasher.clone();
+                    for chunk in suffix.chunks(7) {
+                        hasher.update(&to_expressions(chunk), builder);
+                    }
+                    hasher.finalize(outputs, builder);
+                }
+            }
+        };
+    }
+
+    let expected = |digest: fn(&[u8]) -> Vec<u8>| {
+        suffixes
+            .iter()
+            .map(|suffix| digest(&[prefix.as_slice(), suffix].concat()))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        solve_streaming_hash(&prefix, &suffixes, 32, streaming_hash!(Sha256Hasher)),
+        expected(|message| Sha256::digest(message).to_vec())
+    );
+    assert_eq!(
+        solve_streaming_hash(&prefix, &suffixes, 32, streaming_hash!(Blake2sHasher)),
+        expected(|message| Blake2s256::digest(message).to_vec())
+    );
+    assert_eq!(
+        solve_streaming_hash(&prefix, &suffixes, 32, streaming_hash!(Keccak256Hasher)),
+        expected(|message| Keccak256::digest(message).to_vec())
+    );
+}
+
+#[test]
+fn sha512_empty_message() {
+    let expected = hex_digest(
+        "cf83e1357eefb8bdf1542850d66d8007d620e4050b5715dc83f4a921d36ce9ce\
+         47d0d13c5d85f2b0ff8318d2877eec2f63b931bd47417a81a538327af927da3e",
+    );
+    assert_eq!(solve_hash(&[], 64, sha512_call, false), expected);
+    assert_eq!(solve_hash(&[], 64, sha512_call, true), expected);
+}
+
+#[test]
+fn sha512_fallback_matches_native_implementation() {
+    // A message of 112 bytes no longer leaves room for the length in its final block.
+    for len in [3, 111, 112, 200] {
+        let message: Vec<u8> = (0..len).map(|i| (i * 7 + 3) as u8).collect();
+        assert_eq!(
+            solve_hash(&message, 64, sha512_call, true),
+            Sha512::digest(&message).to_vec(),
+            "digest mismatch for a message of {len} bytes"
+        );
+    }
+}
+
+#[test]
+fn blake2b_empty_message() {
+    let expected = hex_digest(
+        "786a02f742015903c6c6fd852552d272912f4740e15847618a86e217f71f5419\
+         d25e1031afee585313896444934eb04b903a685b1448b755d56f701afe9be2ce",
+    );
+    assert_eq!(solve_hash(&[], 64, blake2b_call, false), expected);
+    assert_eq!(solve_hash(&[], 64, blake2b_call, true), expected);
+}
+
+#[test]
+fn blake2b_fallback_matches_native_implementation() {
+    // A message of 128 bytes exactly fills a block, which is then the final block.
+    for len in [3, 127, 128, 129, 300] {
+        let message: Vec<u8> = (0..len).map(|i| (i * 7 + 3) as u8).collect();
+        assert_eq!(
+            solve_hash(&message, 64, blake2b_call, true),
+            Blake2b512::digest(&message).to_vec(),
+            "digest mismatch for a message of {len} bytes"
+        );
+    }
+}
+
+#[test]
+fn blake3_empty_message() {
+    let expected = hex_digest("af1349b9f5f9a1a6a0404dea36dcc9499bcb25c9adc112b7cc9a93cae41f3262");
+    assert_eq!(solve_hash(&[], 32, blake3_call, false), expected);
+    assert_eq!(solve_hash(&[], 32, blake3_call, true), expected);
+}
+
+#[test]
+fn blake3_fallback_matches_native_implementation() {
+    // Messages spanning several chunks (1024 bytes) exercise the parent nodes of the hash tree.
+    for len in [1, 64, 65, 1024, 1025, 2049] {
+        let message: Vec<u8> = (0..len).map(|i| (i * 7 + 3) as u8).collect();
+        assert_eq!(
+            solve_hash(&message, 32, blake3_call, true),
+            blake3::hash(&message).as_bytes().to_vec(),
+            "digest mismatch for a message of {len} bytes"
+        );
+    }
+}
+
+#[test]
+fn keccakf1600_matches_native_implementation() {
+    let mut state: [u64; 25] = std::array::from_fn(|i| (i as u64 + 1) * 0x0123_4567_89ab_cdef);
+    let inputs: Vec<Witness> = (1..=25).map(Witness).collect();
+    let outputs: Vec<Witness> = (26..=50).map(Witness).collect();
+
+    let call = BlackBoxFuncCall::Keccakf1600 {
+        inputs: inputs.iter().map(|&witness| FunctionInput { witness, num_bits: 64 }).collect(),
+        outputs: outputs.clone(),
+    };
+    let circuit = Circuit {
+        current_witness_index: 50,
+        opcodes: vec![Opcode::BlackBoxFuncCall(call)],
+        ..Circuit::default()
+    };
+    let fallback_circuit = compile(
+        circuit.clone(),
+        &BackendCapabilities::new(Language::PLONKCSat { width: 3 })
+            .without(BlackBoxFunc::Keccakf1600),
+        &CircuitSimplifier::new(25),
+    )
+    .expect("keccakf1600 should be replaced by its fallback")
+    .0;
+
+    let initial_witness: WitnessMap = inputs
+        .into_iter()
+        .zip(state)
+        .map(|(witness, lane)| (witness, FieldElement::from(lane as u128)))
+        .collect::<std::collections::BTreeMap<_, _>>()
+        .into();
+    keccak::f1600(&mut state);
+    let expected: Vec<u128> = state.iter().map(|&lane| lane as u128).collect();
+
+    for circuit in [circuit, fallback_circuit] {
+        let mut acvm = ACVM::new(MockBackend::default(), circuit.opcodes, initial_witness.clone());
+        let solver_status = acvm.solve().expect("circuit should be satisfied");
+        assert_eq!(solver_status, PartialWitnessGeneratorStatus::Solved, "should be fully solved");
+
+        let witness_map = acvm.witness_map();
+        let permuted: Vec<u128> =
+            outputs.iter().map(|output| witness_map[output].to_u128()).collect();
+        assert_eq!(permuted, expected);
+    }
+}
+
+#[test]
+fn poseidon2_permutation_fallback_matches_native_implementation() {
+    for width in [3_u32, 4] {
+        let inputs: Vec<Witness> = (1..=width).map(Witness).collect();
+        let outputs: Vec<Witness> = (width + 1..=2 * width).map(Witness).collect();
+        let state: Vec<FieldElement> = (1..width)
+            .map(|i| FieldElement::from(0x0123_4567_89ab_cdef_u128 * i as u128))
+            .chain(std::iter::once(-FieldElement::one()))
+            .collect();
+
+        let call = BlackBoxFuncCall::Poseidon2Permutation {
+            inputs: inputs
+                .iter()
+                .map(|&witness| FunctionInput { witness, num_bits: 254 })
+                .collect(),
+            outputs: outputs.clone(),
+            len: width,
+        };
+        let circuit = Circuit {
+            current_witness_index: 2 * width,
+            opcodes: vec![Opcode::BlackBoxFuncCall(call)],
+            ..Circuit::default()
+        };
+        let fallback_circuit = compile(
+            circuit.clone(),
+            &BackendCapabilities::new(Language::PLONKCSat { width: 3 })
+                .without(BlackBoxFunc::Poseidon2Permutation),
+            &CircuitSimplifier::new(width),
+        )
+        .expect("poseidon2_permutation should be replaced by its fallback")
+        .0;
+
+        let initial_witness: WitnessMap = inputs
+            .into_iter()
+            .zip(state.iter().copied())
+            .collect::<std::collections::BTreeMap<_, _>>()
+            .into();
+        let expected = blackbox_solver::poseidon2_permutation(&state)
+            .expect("width is supported by the native solver");
+
+        for circuit in [circuit, fallback_circuit] {
+            let mut acvm =
+                ACVM::new(MockBackend::default(), circuit.opcodes, initial_witness.clone());
+            let solver_status = acvm.solve().expect("circuit should be satisfied");
+            assert_eq!(
+                solver_status,
+                PartialWitnessGeneratorStatus::Solved,
+                "should be fully solved"
+            );
+
+            let witness_map = acvm.witness_map();
+            let permuted: Vec<FieldElement> =
+                outputs.iter().map(|output| witness_map[output]).collect();
+            assert_eq!(permuted, expected, "mismatch for a state of width {width}");
+        }
+    }
+}
+
+#[test]
+fn poseidon2_permutation_fallback_rejects_unsupported_widths() {
+    let inputs: Vec<FunctionInput> =
+        (1..=5).map(|index| FunctionInput { witness: Witness(index), num_bits: 254 }).collect();
+    let call = BlackBoxFuncCall::Poseidon2Permutation {
+        inputs,
+        outputs: (6..=10).map(Witness).collect(),
+        len: 5,
+    };
+    let circuit = Circuit {
+        current_witness_index: 10,
+        opcodes: vec![Opcode::BlackBoxFuncCall(call)],
+        ..Circuit::default()
+    };
+    let result = compile(
+        circuit,
+        &BackendCapabilities::arithmetic_only(Language::PLONKCSat { width: 3 }),
+        &CircuitSimplifier::new(5),
+    );
+    assert_eq!(result, Err(CompileError::UnsupportedBlackBox(BlackBoxFunc::Poseidon2Permutation)));
+}
+
+#[test]
+fn strict_mode_rejects_deprecated_opcodes() {
+    let circuit = Circuit {
+        current_witness_index: 2,
+        opcodes: vec![Opcode::Directive(Directive::Invert { x: Witness(1), result: Witness(2) })],
+        ..Circuit::default()
+    };
+    let capabilities = BackendCapabilities::new(Language::PLONKCSat { width: 3 });
+    let simplifier = CircuitSimplifier::new(2);
+    assert!(compile(circuit.clone(), &capabilities, &simplifier).is_ok());
+
+    let result = compile(circuit.clone(), &capabilities.with_strict_mode(), &simplifier);
+    let err = result.expect_err("directives are deprecated");
+    assert!(matches!(err, CompileError::DeprecatedOpcode(_)));
+
+    let result =
+        ACVM::new(MockBackend::default(), circuit.opcodes, WitnessMap::new()).with_strict_mode();
+    let err = result.err().expect("directives are deprecated");
+    assert_eq!(
+        err.to_string(),
+        "`Invert` directive at opcode 0 is deprecated, use a Brillig hint dividing one by the value instead"
+    );
+}
+
+#[test]
+fn sha256_compression_of_single_block_message_matches_digest() {
+    // The initial hash values of SHA256 along with the padded message "abc".
+    let hash_values: [u32; 8] = [
+        0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab,
+        0x5be0cd19,
+    ];
+    let mut block = [0_u32; 16];
+    block[0] = 0x61626380;
+    block[15] = 24;
+
+    let inputs: Vec<Witness> = (1..=24).map(Witness).collect();
+    let outputs: Vec<Witness> = (25..=32).map(Witness).collect();
+    let to_function_inputs = |witnesses: &[Witness]| -> Vec<FunctionInput> {
+        witnesses.iter().map(|&witness| FunctionInput { witness, num_bits: 32 }).collect()
+    };
+    let call = BlackBoxFuncCall::Sha256Compression {
+        inputs: to_function_inputs(&inputs[..16]),
+        hash_values: to_function_inputs(&inputs[16..]),
+        outputs: outputs.clone(),
+    };
+    let circuit = Circuit {
+        current_witness_index: 32,
+        opcodes: vec![Opcode::BlackBoxFuncCall(call)],
+        ..Circuit::default()
+    };
+    let fallback_circuit = compile(
+        circuit.clone(),
+        &BackendCapabilities::new(Language::PLONKCSat { width: 3 })
+            .without(BlackBoxFunc::Sha256Compression),
+        &CircuitSimplifier::new(24),
+    )
+    .expect("sha256_compression should be replaced by its fallback")
+    .0;
+
+    let initial_witness: WitnessMap = inputs
+        .into_iter()
+        .zip(block.into_iter().chain(hash_values))
+        .map(|(witness, word)| (witness, FieldElement::from(word as u128)))
+        .collect::<std::collections::BTreeMap<_, _>>()
+        .into();
+    let expected: Vec<u128> = Sha256::digest(b"abc")
+        .chunks(4)
+        .map(|word| u32::from_be_bytes(word.try_into().unwrap()) as u128)
+        .collect();
+
+    for circuit in [circuit, fallback_circuit] {
+        let mut acvm = ACVM::new(MockBackend::default(), circuit.opcodes, initial_witness.clone());
+        let solver_status = acvm.solve().expect("circuit should be satisfied");
+        assert_eq!(solver_status, PartialWitnessGeneratorStatus::Solved, "should be fully solved");
+
+        let witness_map = acvm.witness_map();
+        let compressed: Vec<u128> =
+            outputs.iter().map(|output| witness_map[output].to_u128()).collect();
+        assert_eq!(compressed, expected);
+    }
+}
+
+/// Encrypts `plaintext` with a circuit containing a single `Aes128Encrypt` call, returning the ciphertext.
+///
+/// If `use_fallback` is set, the black box call is first replaced by its fallback implementation.
+fn solve_aes128(plaintext: &[u8], iv: [u8; 16], key: [u8; 16], use_fallback: bool) -> Vec<u8> {
+    let bytes: Vec<u8> = plaintext.iter().chain(&iv).chain(&key).copied().collect();
+    let inputs: Vec<FunctionInput> = (1..=bytes.len() as u32)
+        .map(|index| FunctionInput { witness: Witness(index), num_bits: 8 })
+        .collect();
+    let ciphertext_len = (plaintext.len() / 16 + 1) as u32 * 16;
+    let outputs: Vec<Witness> =
+        (0..ciphertext_len).map(|i| Witness(bytes.len() as u32 + 1 + i)).collect();
+
+    let (plaintext_inputs, rest) = inputs.split_at(plaintext.len());
+    let (iv_inputs, key_inputs) = rest.split_at(16);
+    let call = BlackBoxFuncCall::Aes128Encrypt {
+        inputs: plaintext_inputs.to_vec(),
+        iv: iv_inputs.to_vec(),
+        key: key_inputs.to_vec(),
+        outputs: outputs.clone(),
+    };
+    let mut circuit = Circuit {
+        current_witness_index: bytes.len() as u32 + ciphertext_len,
+        opcodes: vec![Opcode::BlackBoxFuncCall(call)],
+        ..Circuit::default()
+    };
+
+    if use_fallback {
+        circuit = compile(
+            circuit,
+            &BackendCapabilities::new(Language::PLONKCSat { width: 3 })
+                .without(BlackBoxFunc::Aes128Encrypt),
+            &CircuitSimplifier::new(bytes.len() as u32),
+        )
+        .expect("aes128 should be replaced by its fallback")
+        .0;
+    }
+
+    let initial_witness: WitnessMap = inputs
+        .iter()
+        .zip(&bytes)
+        .map(|(input, &byte)| (input.witness, FieldElement::from(byte as u128)))
+        .collect::<std::collections::BTreeMap<_, _>>()
+        .into();
+
+    let mut acvm = ACVM::new(MockBackend::default(), circuit.opcodes, initial_witness);
+    let solver_status = acvm.solve().expect("circuit should be satisfied");
+    assert_eq!(solver_status, PartialWitnessGeneratorStatus::Solved, "should be fully solved");
+
+    let witness_map = acvm.witness_map();
+    outputs.iter().map(|output| witness_map[output].to_u128() as u8).collect()
+}
+
+#[test]
+fn aes128_encrypt_fallback_matches_sp800_38a_vector() {
+    // Section F.2.1 of NIST SP 800-38A, the second block being the encryption of the padding.
+    let key = hex_digest("2b7e151628aed2a6abf7158809cf4f3c").try_into().unwrap();
+    let iv = hex_digest("000102030405060708090a0b0c0d0e0f").try_into().unwrap();
+    let plaintext = hex_digest("6bc1bee22e409f96e93d7e117393172a");
+
+    let ciphertext = solve_aes128(&plaintext, iv, key, true);
+    assert_eq!(ciphertext[..16], hex_digest("7649abac8119b246cee98e9b12e9197d"));
+    assert_eq!(ciphertext, solve_aes128(&plaintext, iv, key, false));
+}
+
+#[test]
+fn aes128_encrypt_fallback_pads_partial_blocks() {
+    let key: [u8; 16] = std::array::from_fn(|i| (i * 13 + 5) as u8);
+    let iv: [u8; 16] = std::array::from_fn(|i| (i * 31 + 7) as u8);
+    let plaintext: Vec<u8> = (0..5).map(|i| i * 7 + 3).collect();
+
+    assert_eq!(solve_aes128(&plaintext, iv, key, true), solve_aes128(&plaintext, iv, key, false));
+}
+
+/// Solves a circuit containing the single-input bitwise black box call built by `bitwise_call` over a witness
+/// assigned to `value`, returning the resulting output.
+fn solve_bitwise(
+    value: u128,
+    num_bits: u32,
+    bitwise_call: impl FnOnce(FunctionInput, Witness) -> BlackBoxFuncCall,
+    use_fallback: bool,
+) -> u128 {
+    let call = bitwise_call(FunctionInput { witness: Witness(1), num_bits }, Witness(2));
+    let bitwise_function = call.get_black_box_func();
+    let mut circuit = Circuit {
+        current_witness_index: 2,
+        opcodes: vec![Opcode::BlackBoxFuncCall(call)],
+        ..Circuit::default()
+    };
+
+    if use_fallback {
+        circuit = compile(
+            circuit,
+            &BackendCapabilities::new(Language::PLONKCSat { width: 3 }).without(bitwise_function),
+            &CircuitSimplifier::new(1),
+        )
+        .expect("bitwise function should be replaced by its fallback")
+        .0;
+    }
+
+    let initial_witness = WitnessMap::from(std::collections::BTreeMap::from([(
+        Witness(1),
+        FieldElement::from(value),
+    )]));
+    let mut acvm = ACVM::new(MockBackend::default(), circuit.opcodes, initial_witness);
+    let solver_status = acvm.solve().expect("circuit should be satisfied");
+    assert_eq!(solver_status, PartialWitnessGeneratorStatus::Solved, "should be fully solved");
+
+    acvm.witness_map()[&Witness(2)].to_u128()
+}
+
+#[test]
+fn bitwise_fallbacks_match_native_implementation() {
+    for use_fallback in [false, true] {
+        for value in [0_u8, 1, 0x5a, 0x80, 0xff] {
+            let not = solve_bitwise(
+                value as u128,
+                8,
+                |input, output| BlackBoxFuncCall::NOT { input, output },
+                use_fallback,
+            );
+            assert_eq!(not, !value as u128);
+
+            for shift in [0, 1, 3, 7, 8, 9] {
+                let shl = solve_bitwise(
+                    value as u128,
+                    8,
+                    |input, output| BlackBoxFuncCall::SHL { input, shift, output },
+                    use_fallback,
+                );
+                assert_eq!(shl, value.checked_shl(shift).unwrap_or(0) as u128);
+
+                let shr = solve_bitwise(
+                    value as u128,
+                    8,
+                    |input, output| BlackBoxFuncCall::SHR { input, shift, output },
+                    use_fallback,
+                );
+                assert_eq!(shr, value.checked_shr(shift).unwrap_or(0) as u128);
+            }
+        }
+    }
+}
+
+#[test]
+fn bitwise_fallbacks_reject_oversized_inputs() {
+    let circuit = compile(
+        Circuit {
+            current_witness_index: 2,
+            opcodes: vec![Opcode::BlackBoxFuncCall(BlackBoxFuncCall::NOT {
+                input: FunctionInput { witness: Witness(1), num_bits: 8 },
+                output: Witness(2),
+            })],
+            ..Circuit::default()
+        },
+        &BackendCapabilities::arithmetic_only(Language::PLONKCSat { width: 3 }),
+        &CircuitSimplifier::new(1),
+    )
+    .expect("NOT should be replaced by its fallback")
+    .0;
+
+    let initial_witness = WitnessMap::from(std::collections::BTreeMap::from([(
+        Witness(1),
+        FieldElement::from(0x100_u128),
+    )]));
+    let mut acvm = ACVM::new(MockBackend::default(), circuit.opcodes, initial_witness);
+    assert!(acvm.solve().is_err(), "a 9 bit value should not satisfy an 8 bit NOT");
+}
+
+#[test]
+fn range_fallback_uses_the_largest_supported_range_constraint() {
+    // A backend which can only range constrain values of up to 16 bits, e.g. through a lookup table.
+    let circuit = compile(
+        Circuit {
+            current_witness_index: 1,
+            opcodes: vec![Opcode::BlackBoxFuncCall(BlackBoxFuncCall::RANGE {
+                input: FunctionInput { witness: Witness(1), num_bits: 40 },
+            })],
+            ..Circuit::default()
+        },
+        &BackendCapabilities::new(Language::PLONKCSat { width: 3 }).with_max_range_bits(16),
+        &CircuitSimplifier::new(1),
+    )
+    .expect("RANGE should be replaced by its fallback")
+    .0;
+
+    let range_sizes: Vec<u32> = circuit
+        .opcodes
+        .iter()
+        .filter_map(|opcode| match opcode {
+            Opcode::BlackBoxFuncCall(BlackBoxFuncCall::RANGE { input }) => Some(input.num_bits),
+            _ => None,
+        })
+        .collect();
+    // Three chunks, the most significant of which is range constrained a second time once shifted.
+    assert_eq!(range_sizes, vec![16; 4]);
+
+    let solve = |value: u128| {
+        let initial_witness = WitnessMap::from(std::collections::BTreeMap::from([(
+            Witness(1),
+            FieldElement::from(value),
+        )]));
+        let mut acvm = ACVM::new(MockBackend::default(), circuit.opcodes.clone(), initial_witness);
+        acvm.solve()
+    };
+    for value in [0, 0xffff, 0x12_3456_789a, (1 << 40) - 1] {
+        assert_eq!(solve(value), Ok(PartialWitnessGeneratorStatus::Solved));
+    }
+    for value in [1 << 40, 1 << 47, 1 << 48] {
+        assert!(solve(value).is_err(), "{value:#x} does not fit within 40 bits");
+    }
+}
+
+#[test]
+fn fallback_report_lists_every_substitution() {
+    let circuit = Circuit {
+        current_witness_index: 3,
+        opcodes: vec![
+            Opcode::BlackBoxFuncCall(BlackBoxFuncCall::AND {
+                lhs: FunctionInput { witness: Witness(1), num_bits: 8 },
+                rhs: FunctionInput { witness: Witness(2), num_bits: 8 },
+                output: Witness(3),
+            }),
+            Opcode::BlackBoxFuncCall(BlackBoxFuncCall::RANGE {
+                input: FunctionInput { witness: Witness(3), num_bits: 8 },
+            }),
+        ],
+        ..Circuit::default()
+    };
+
+    // Only the call to AND is replaced, along with the range constraints its fallback relies on.
+    let capabilities =
+        BackendCapabilities::new(Language::PLONKCSat { width: 3 }).without(BlackBoxFunc::AND);
+    let (_, _, report) = compile(circuit.clone(), &capabilities, &CircuitSimplifier::new(3))
+        .expect("AND should be replaced by its fallback");
+    assert_eq!(report.substitutions.len(), 1);
+    let substitution = &report.substitutions[0];
+    assert_eq!((substitution.opcode_index, substitution.black_box_func), (0, BlackBoxFunc::AND));
+    assert!(substitution.num_opcodes > 0 && substitution.num_witnesses > 0);
+
+    let capabilities = BackendCapabilities::arithmetic_only(Language::PLONKCSat { width: 3 });
+    let (_, _, report) = compile(circuit, &capabilities, &CircuitSimplifier::new(3))
+        .expect("AND and RANGE should be replaced by their fallbacks");
+    let substituted: Vec<_> = report
+        .substitutions
+        .iter()
+        .map(|substitution| (substitution.opcode_index, substitution.black_box_func))
+        .collect();
+    assert_eq!(substituted, vec![(0, BlackBoxFunc::AND), (1, BlackBoxFunc::RANGE)]);
+    assert_eq!(report.num_opcodes_of(BlackBoxFunc::AND), report.substitutions[0].num_opcodes);
+}
+
+#[test]
+fn memory_blocks_are_lowered_for_backends_without_memory() {
+    let memory_op = |operation: Expression, index: Expression, value: Witness| Opcode::MemoryOp {
+        block_id: BlockId(0),
+        op: MemOp { operation, index, value: Expression::from(value) },
+    };
+    let write = |index, value| memory_op(Expression::one(), index, value);
+    let read = |index, value| memory_op(Expression::zero(), index, value);
+    let constant = |value: u128| Expression::from_field(FieldElement::from(value));
+    // Witnesses 1 to 3 initialize the block, which is then read at and written to the dynamic index 4.
+    // The operations on the block are gathered across the opcodes interleaved with them.
+    let circuit = Circuit {
+        current_witness_index: 8,
+        opcodes: vec![
+            Opcode::MemoryInit {
+                block_id: BlockId(0),
+                init: vec![Witness(1), Witness(2), Witness(3)],
+            },
+            read(Expression::from(Witness(4)), Witness(5)),
+            Opcode::AssertZero(&Expression::from(Witness(6)) - &constant(60)),
+            write(Expression::from(Witness(4)), Witness(6)),
+            read(constant(1), Witness(7)),
+            read(constant(0), Witness(8)),
+        ],
+        ..Circuit::default()
+    };
+    let initial_witness: std::collections::BTreeMap<_, _> =
+        [(1, 10_u128), (2, 20), (3, 30), (4, 1), (6, 60)]
+            .into_iter()
+            .map(|(witness, value)| (Witness(witness), FieldElement::from(value)))
+            .collect();
+
+    let capabilities = BackendCapabilities::new(Language::PLONKCSat { width: 3 }).without_memory();
+    let (compiled, _, _) = compile(circuit, &capabilities, &CircuitSimplifier::new(7)).unwrap();
+    assert!(compiled.opcodes.iter().all(|opcode| capabilities.supports_opcode(opcode)));
+
+    let mut acvm =
+        ACVM::new(MockBackend::default(), compiled.opcodes.clone(), initial_witness.clone().into());
+    assert_eq!(acvm.solve(), Ok(PartialWitnessGeneratorStatus::Solved));
+    let values: Vec<u128> =
+        [5, 7, 8].iter().map(|witness| acvm.witness_map()[&Witness(*witness)].to_u128()).collect();
+    assert_eq!(values, vec![20, 60, 10]);
+
+    // Reading a stale value at the dynamic index violates the consistency of the sorted trace.
+    let mut initial_witness = initial_witness;
+    for (witness, value) in [(5, 30_u128), (7, 60), (8, 10)] {
+        initial_witness.insert(Witness(witness), FieldElement::from(value));
+    }
+    let opcodes = compiled
+        .opcodes
+        .into_iter()
+        .filter(|opcode| !matches!(opcode, Opcode::Brillig(_)))
+        .collect();
+    let mut acvm = ACVM::new(MockBackend::default(), opcodes, initial_witness.into());
+    assert!(acvm.solve().is_err(), "inconsistent reads should be rejected");
+}
+
+/// Number of byte inputs to an `EcdsaSecp256k1` call: the public key coordinates, the signature and the hashed message.
+const ECDSA_SECP256K1_INPUT_BYTES: u32 = 32 + 32 + 64 + 32;
+
+/// Returns the opcodes of a circuit verifying a single ECDSA signature over the input bytes,
+/// with the black box call replaced by its fallback if `use_fallback` is set.
+fn ecdsa_secp256k1_opcodes(use_fallback: bool) -> Vec<Opcode> {
+    let inputs: Vec<FunctionInput> = (1..=ECDSA_SECP256K1_INPUT_BYTES)
+        .map(|index| FunctionInput { witness: Witness(index), num_bits: 8 })
+        .collect();
+    let output = Witness(ECDSA_SECP256K1_INPUT_BYTES + 1);
+
+    let call = BlackBoxFuncCall::EcdsaSecp256k1 {
+        public_key_x: inputs[..32].to_vec(),
+        public_key_y: inputs[32..64].to_vec(),
+        signature: inputs[64..128].to_vec(),
+        hashed_message: inputs[128..].to_vec(),
+        output,
+    };
+    let circuit = Circuit {
+        current_witness_index: output.witness_index(),
+        opcodes: vec![Opcode::BlackBoxFuncCall(call)],
+        ..Circuit::default()
+    };
+    if !use_fallback {
+        return circuit.opcodes;
+    }
+
+    // The fallback is not reduced to width 3 here, as the intermediate opcodes this creates
+    // are not ordered by their dependencies and solving them takes a pass per point doubling.
+    compile(
+        circuit,
+        &BackendCapabilities::new(Language::R1CS).without(BlackBoxFunc::EcdsaSecp256k1),
+        &CircuitSimplifier::new(ECDSA_SECP256K1_INPUT_BYTES),
+    )
+    .expect("ecdsa_secp256k1 should be replaced by its fallback")
+    .0
+    .opcodes
+}
+
+/// Solves `opcodes` with the inputs of their ECDSA verification assigned to the given bytes,
+/// returning the resulting output.
+fn solve_ecdsa_secp256k1(
+    opcodes: &[Opcode],
+    hashed_message: &[u8],
+    public_key_x: &[u8],
+    public_key_y: &[u8],
+    signature: &[u8],
+) -> Result<bool, OpcodeResolutionError> {
+    let initial_witness: WitnessMap = [public_key_x, public_key_y, signature, hashed_message]
+        .into_iter()
+        .flatten()
+        .zip(1..)
+        .map(|(&byte, index)| (Witness(index), FieldElement::from(byte as u128)))
+        .collect::<std::collections::BTreeMap<_, _>>()
+        .into();
+
+    let mut acvm = ACVM::new(MockBackend::default(), opcodes.to_vec(), initial_witness);
+    let solver_status = acvm.solve()?;
+    assert_eq!(solver_status, PartialWitnessGeneratorStatus::Solved, "should be fully solved");
+    Ok(acvm.witness_map()[&Witness(ECDSA_SECP256K1_INPUT_BYTES + 1)].is_one())
+}
+
+#[test]
+fn ecdsa_secp256k1_fallback_matches_native_implementation() {
+    let hashed_message =
+        hex_digest("3a73f4123a5cd2121f21cd7e8d358835476949d035d9c2da6806b4633ac8c1e2");
+    let public_key_x =
+        hex_digest("a0434d9e47f3c86235477c7b1ae6ae5d3442d49b1943c2b752a68e2a47e247c7");
+    let public_key_y =
+        hex_digest("893aba425419bc27a3b6c7e693a24c696f794c2ed877a1593cbee53b037368d7");
+    let signature = hex_digest(
+        "e5081c80ab427dc370346f4a0e31aa2bad8d9798c38061db9ae55a4e8df454fd\
+         28119894344e71b78770cc931d61f480ecbb0b89d6eb69690161e49a715fcd55",
+    );
+
+    let mut tampered_message = hashed_message.clone();
+    tampered_message[0] ^= 1;
+    let mut zero_s = signature.clone();
+    zero_s[32..].fill(0);
+    // Replacing `s` with `n - s` gives a signature which only differs by the high `s` it is rejected for.
+    let mut high_s = signature.clone();
+    high_s[32..].copy_from_slice(&hex_digest(
+        "d7ee676bcbb18e48788f336ce29e0b7dcdf3d15cd85d36d2be7079f25ed673ec",
+    ));
+    // Public keys which are not on the curve cannot be used.
+    let mut invalid_public_key_y = public_key_y.clone();
+    invalid_public_key_y[31] ^= 1;
+
+    for use_fallback in [false, true] {
+        let opcodes = ecdsa_secp256k1_opcodes(use_fallback);
+        let cases = [
+            (&hashed_message, &signature, true),
+            (&tampered_message, &signature, false),
+            (&hashed_message, &zero_s, false),
+            (&hashed_message, &high_s, false),
+        ];
+        for (message, signature, is_valid) in cases {
+            let output =
+                solve_ecdsa_secp256k1(&opcodes, message, &public_key_x, &public_key_y, signature);
+            assert_eq!(output, Ok(is_valid), "mismatch with use_fallback = {use_fallback}");
+        }
+
+        let output = solve_ecdsa_secp256k1(
+            &opcodes,
+            &hashed_message,
+            &public_key_x,
+            &invalid_public_key_y,
+            &signature,
+        );
+        assert!(output.is_err(), "invalid public key accepted with use_fallback = {use_fallback}");
+    }
+}
+
+#[test]
+fn big_field_arithmetic_matches_integer_arithmetic() {
+    let moduli = [
+        // 2^61 - 1
+        BigUint::from(u64::MAX >> 3),
+        // 2^255 - 19
+        (BigUint::from(1_u32) << 255_u32) - 19_u32,
+        // BLS12-381 base field modulus
+        BigUint::parse_bytes(
+            b"1a0111ea397fe69a4b1ba7b6434bacd764774b84f38512bf6730d2a0f6b0f6241eabfffeb153ffffb9feffffffffaaab",
+            16,
+        )
+        .unwrap(),
+    ];
+    for modulus in moduli {
+        let num_bytes = (modulus.bits() as usize + 7) / 8;
+        // Operands need not be reduced, so the first one is the largest integer fitting within its bytes.
+        let a = (BigUint::from(1_u32) << (8 * num_bytes)) - 1_u32;
+        let b = &modulus - 2_u32;
+        let expected = [
+            (&a + &b) % &modulus,
+            (&a % &modulus + &modulus - &b) % &modulus,
+            (&a * &b) % &modulus,
+        ];
+
+        let a_bytes: Vec<Witness> = (1..=num_bytes as u32).map(Witness).collect();
+        let b_bytes: Vec<Witness> =
+            (num_bytes as u32 + 1..=2 * num_bytes as u32).map(Witness).collect();
+        let mut initial_witness = std::collections::BTreeMap::new();
+        for (witnesses, value) in [(&a_bytes, &a), (&b_bytes, &b)] {
+            let mut bytes = value.to_bytes_be();
+            bytes.splice(0..0, vec![0; num_bytes - bytes.len()]);
+            for (witness, byte) in witnesses.iter().zip(bytes) {
+                initial_witness.insert(*witness, FieldElement::from(byte as u128));
+            }
+        }
+
+        let field = BigField::new(modulus.clone());
+        let mut builder = CircuitBuilder::new(2 * num_bytes as u32 + 1);
+        let a = BigFieldElement::from_be_bytes(&a_bytes, &mut builder);
+        let b = BigFieldElement::from_be_bytes(&b_bytes, &mut builder);
+        let results = [
+            field.add(&a, &b, &mut builder),
+            field.sub(&a, &b, &mut builder),
+            field.mul(&a, &b, &mut builder),
+        ];
+        let results = results.map(|result| field.reduce(&result, &mut builder));
+        let (_, opcodes) = builder.finalize();
+
+        let mut acvm = ACVM::new(MockBackend::default(), opcodes, initial_witness.into());
+        let solver_status = acvm.solve().expect("circuit should be satisfied");
+        assert_eq!(solver_status, PartialWitnessGeneratorStatus::Solved, "should be fully solved");
+
+        let witness_map = acvm.witness_map();
+        for (result, expected) in results.iter().zip(&expected) {
+            let value = result.limbs().iter().rev().fold(BigUint::default(), |value, limb| {
+                let limb = witness_map[&limb.to_witness().expect("limbs of results are witnesses")];
+                (value << LIMB_BITS) + BigUint::from_bytes_be(&limb.to_be_bytes())
+            });
+            assert_eq!(&value, expected, "mismatch modulo {modulus}");
+        }
+    }
+}
+
+#[test]
+fn radix_decompositions_lay_out_digits_in_either_order() {
+    let value = Expression::from(Witness(1));
+    let digits: Vec<Witness> = (2..=5).map(Witness).collect();
+    let initial_witness = WitnessMap::from(std::collections::BTreeMap::from([(
+        Witness(1),
+        FieldElement::from(1234_u128),
+    )]));
+    let solve = |opcodes: Vec<Opcode>, initial_witness: WitnessMap| {
+        let mut acvm = ACVM::new(MockBackend::default(), opcodes, initial_witness);
+        acvm.solve().map(|_| {
+            digits.iter().map(|digit| acvm.witness_map()[digit].to_u128()).collect::<Vec<_>>()
+        })
+    };
+
+    let build = |decompose: &dyn Fn(&mut CircuitBuilder)| {
+        let mut builder = CircuitBuilder::new(6);
+        decompose(&mut builder);
+        builder.finalize().1
+    };
+
+    let opcodes = build(&|builder| {
+        radix_decomposition(value.clone(), &digits, 10, Endianness::Little, builder)
+    });
+    assert_eq!(solve(opcodes, initial_witness.clone()), Ok(vec![4, 3, 2, 1]));
+    let opcodes =
+        build(&|builder| radix_decomposition(value.clone(), &digits, 10, Endianness::Big, builder));
+    assert_eq!(solve(opcodes, initial_witness.clone()), Ok(vec![1, 2, 3, 4]));
+    let opcodes =
+        build(&|builder| byte_decomposition(value.clone(), &digits, Endianness::Big, builder));
+    assert_eq!(solve(opcodes, initial_witness.clone()), Ok(vec![0, 0, 0x04, 0xd2]));
+    let opcodes = build(&|builder| {
+        radix_decomposition(value.clone(), &digits[..3], 10, Endianness::Little, builder)
+    });
+    assert!(solve(opcodes, initial_witness.clone()).is_err(), "1234 does not fit within 3 digits");
+
+    // Without the directive, digits which are not less than the radix can still recompose into the value.
+    let opcodes = build(&|builder| {
+        radix_decomposition(value.clone(), &digits, 10, Endianness::Little, builder)
+    });
+    let opcodes =
+        opcodes.into_iter().filter(|opcode| !matches!(opcode, Opcode::Directive(_))).collect();
+    let mut initial_witness =
+        initial_witness.into_iter().collect::<std::collections::BTreeMap<_, _>>();
+    for (digit, value) in digits.iter().zip([14_u128, 2, 2, 1]) {
+        initial_witness.insert(*digit, FieldElement::from(value));
+    }
+    assert!(solve(opcodes, initial_witness.into()).is_err(), "digit 14 is not less than the radix");
+}
+
+#[test]
+fn witness_to_bytes_matches_fallback_byte_decomposition() {
+    let value = -FieldElement::one();
+    let cases =
+        [(0_u128, 1), (0xff, 8), (0x1ff, 9), (0x1234, 16), (0xdeadbeef, 32), (0xdeadbeef, 64)]
+            .into_iter()
+            .map(|(value, num_bits)| (FieldElement::from(value), num_bits))
+            .chain([(value, FieldElement::max_num_bits())]);
+
+    for (value, num_bits) in cases {
+        for endianness in [Endianness::Little, Endianness::Big] {
+            let initial_witness =
+                WitnessMap::from(std::collections::BTreeMap::from([(Witness(1), value)]));
+            let bytes = acvm::pwg::witness_to_bytes(
+                &initial_witness,
+                Witness(1),
+                num_bits,
+                endianness,
+                BlackBoxFunc::SHA256,
+            )
+            .unwrap();
+
+            let mut builder = CircuitBuilder::new(2);
+            let byte_witnesses = builder.new_witnesses(bytes.len());
+            byte_decomposition(Witness(1).into(), &byte_witnesses, endianness, &mut builder);
+            let mut acvm =
+                ACVM::new(MockBackend::default(), builder.finalize().1, initial_witness.clone());
+            assert_eq!(acvm.solve(), Ok(PartialWitnessGeneratorStatus::Solved));
+            let expected: Vec<u8> = byte_witnesses
+                .iter()
+                .map(|byte| acvm.witness_map()[byte].to_u128() as u8)
+                .collect();
+            assert_eq!(bytes, expected, "{} in {num_bits} bits", value.to_hex());
+        }
+    }
+
+    let initial_witness = WitnessMap::from(std::collections::BTreeMap::from([(
+        Witness(1),
+        FieldElement::from(256_u128),
+    )]));
+    assert_eq!(
+        acvm::pwg::witness_to_bytes(
+            &initial_witness,
+            Witness(1),
+            8,
+            Endianness::Big,
+            BlackBoxFunc::SHA256
+        ),
+        Err(OpcodeResolutionError::BlackBoxFunctionFailed(
+            BlackBoxFunc::SHA256,
+            format!("input {} does not fit within 8 bits", FieldElement::from(256_u128).to_hex())
+        ))
+    );
+}
+
+#[test]
+fn comparison_gadgets_order_unsigned_integers() {
+    for (a, b) in [(0_u128, 0_u128), (3, 5), (5, 3), (255, 0), (0, 255), (7, 7)] {
+        let (lhs, rhs) = (Expression::from(Witness(1)), Expression::from(Witness(2)));
+        let mut builder = CircuitBuilder::new(3);
+        let is_less = less_than(lhs.clone(), rhs.clone(), 8, &mut builder);
+        let max = max(lhs.clone(), rhs.clone(), 8, &mut builder);
+        let min = min(lhs, rhs, 8, &mut builder);
+        let (_, opcodes) = builder.finalize();
+
+        let initial_witness = WitnessMap::from(std::collections::BTreeMap::from([
+            (Witness(1), FieldElement::from(a)),
+            (Witness(2), FieldElement::from(b)),
+        ]));
+        let mut acvm = ACVM::new(MockBackend::default(), opcodes, initial_witness);
+        assert_eq!(acvm.solve(), Ok(PartialWitnessGeneratorStatus::Solved));
+        let witness_map = acvm.witness_map();
+        assert_eq!(witness_map[&is_less].to_u128(), (a < b) as u128, "{a} < {b}");
+        assert_eq!(witness_map[&max].to_u128(), a.max(b), "max({a}, {b})");
+        assert_eq!(witness_map[&min].to_u128(), a.min(b), "min({a}, {b})");
+    }
+}
+
+#[test]
+fn sort_gadget_constrains_outputs_to_sorted_permutation_of_inputs() {
+    let values = [5_u128, 3, 9, 3, 0, 200, 1];
+    // Both odd and even numbers of inputs lay out the permutation network differently.
+    for num_inputs in [1, 2, 6, 7] {
+        let inputs: Vec<Witness> = (1..=num_inputs).map(Witness).collect();
+        let initial_witness: std::collections::BTreeMap<_, _> = inputs
+            .iter()
+            .zip(values)
+            .map(|(input, value)| (*input, FieldElement::from(value)))
+            .collect();
+        let mut builder = CircuitBuilder::new(num_inputs + 1);
+        let outputs = sort(inputs.iter().copied().map(Expression::from).collect(), 8, &mut builder);
+        let (_, opcodes) = builder.finalize();
+
+        let mut acvm =
+            ACVM::new(MockBackend::default(), opcodes.clone(), initial_witness.clone().into());
+        assert_eq!(acvm.solve(), Ok(PartialWitnessGeneratorStatus::Solved));
+        let sorted: Vec<u128> =
+            outputs.iter().map(|output| acvm.witness_map()[output].to_u128()).collect();
+        let mut expected = values[..num_inputs as usize].to_vec();
+        expected.sort();
+        assert_eq!(sorted, expected);
+
+        // Leaving every switch open routes the inputs through unchanged, which are not in order.
+        if num_inputs > 2 {
+            let mut initial_witness = initial_witness;
+            let opcodes = opcodes
+                .into_iter()
+                .filter(|opcode| match opcode {
+                    Opcode::Directive(Directive::PermutationSort { bits, .. }) => {
+                        initial_witness.extend(bits.iter().map(|bit| (*bit, FieldElement::zero())));
+                        false
+                    }
+                    _ => true,
+                })
+                .collect();
+            let mut acvm = ACVM::new(MockBackend::default(), opcodes, initial_witness.into());
+            assert!(acvm.solve().is_err(), "unsorted outputs should be rejected");
+        }
+    }
+}
+
+#[test]
+fn fixed_point_gadgets_round_and_reject_overflows() {
+    // Numbers with 4 fractional bits, i.e. held as multiples of 1/16.
+    let format = FixedPointFormat::new(8, 4);
+    let solve =
+        |a: u128,
+         b: u128,
+         gadget: &dyn Fn(Expression, Expression, &mut CircuitBuilder) -> Witness| {
+            let mut builder = CircuitBuilder::new(3);
+            let result = gadget(Witness(1).into(), Witness(2).into(), &mut builder);
+            let (_, opcodes) = builder.finalize();
+            let initial_witness = WitnessMap::from(std::collections::BTreeMap::from([
+                (Witness(1), FieldElement::from(a)),
+                (Witness(2), FieldElement::from(b)),
+            ]));
+            let mut acvm = ACVM::new(MockBackend::default(), opcodes, initial_witness);
+            acvm.solve().map(|_| acvm.witness_map()[&result].to_u128())
+        };
+    let mul = |rounding| {
+        move |a, b, builder: &mut CircuitBuilder| fixed_point::mul(a, b, format, rounding, builder)
+    };
+
+    assert_eq!(format.from_integer(3), FieldElement::from(48_u128));
+    // 2.5 + 1.25 = 3.75
+    assert_eq!(solve(40, 20, &|a, b, builder| fixed_point::add(a, b, format, builder)), Ok(60));
+    // 2.5 - 1.25 = 1.25
+    assert_eq!(solve(40, 20, &|a, b, builder| fixed_point::sub(a, b, format, builder)), Ok(20));
+    // 1.0625 * 1.5 = 1.59375, halfway between 1.5625 and 1.625.
+    assert_eq!(solve(17, 24, &mul(Rounding::Down)), Ok(25));
+    assert_eq!(solve(17, 24, &mul(Rounding::Up)), Ok(26));
+    assert_eq!(solve(17, 24, &mul(Rounding::NearestTiesUp)), Ok(26));
+    // 1.0625 * 1.0625 = 1.12890625, closer to 1.125 than to 1.1875.
+    assert_eq!(solve(17, 17, &mul(Rounding::Down)), Ok(18));
+    assert_eq!(solve(17, 17, &mul(Rounding::Up)), Ok(19));
+    assert_eq!(solve(17, 17, &mul(Rounding::NearestTiesUp)), Ok(18));
+    // Exact products are not rounded.
+    assert_eq!(solve(40, 20, &mul(Rounding::Up)), Ok(50));
+
+    for (a, b) in [(40_u128, 20_u128), (20, 40), (40, 40)] {
+        let is_less = solve(a, b, &|a, b, builder| fixed_point::less_than(a, b, format, builder));
+        assert_eq!(is_less, Ok((a < b) as u128), "{a} < {b}");
+        let is_less_or_equal =
+            solve(a, b, &|a, b, builder| fixed_point::less_than_or_equal(a, b, format, builder));
+        assert_eq!(is_less_or_equal, Ok((a <= b) as u128), "{a} <= {b}");
+    }
+
+    // 255 + 1 and 16 * 16 do not fit within 8 integer bits, and 1.25 - 2.5 is negative.
+    let max = (1 << 12) - 16;
+    assert!(solve(max, 16, &|a, b, builder| fixed_point::add(a, b, format, builder)).is_err());
+    assert!(solve(256, 256, &mul(Rounding::Down)).is_err());
+    assert!(solve(20, 40, &|a, b, builder| fixed_point::sub(a, b, format, builder)).is_err());
+}
+
+/// Solves the opcodes built by `build` over the witnesses `Witness(1)` onwards, which hold `inputs`.
+///
+/// Returns the values of the witnesses returned by `build`.
+fn solve_gadgets(
+    inputs: &[u128],
+    build: impl FnOnce(&[Witness], &mut CircuitBuilder) -> Vec<Witness>,
+) -> Vec<u128> {
+    let input_witnesses: Vec<Witness> = (1..=inputs.len() as u32).map(Witness).collect();
+    let mut builder = CircuitBuilder::new(inputs.len() as u32 + 1);
+    let outputs = build(&input_witnesses, &mut builder);
+    let (_, opcodes) = builder.finalize();
+
+    let initial_witness: std::collections::BTreeMap<_, _> = input_witnesses
+        .into_iter()
+        .zip(inputs)
+        .map(|(witness, value)| (witness, FieldElement::from(*value)))
+        .collect();
+    let mut acvm = ACVM::new(MockBackend::default(), opcodes, initial_witness.into());
+    assert_eq!(acvm.solve(), Ok(PartialWitnessGeneratorStatus::Solved), "inputs {inputs:?}");
+    outputs.iter().map(|output| acvm.witness_map()[output].to_u128()).collect()
+}
+
+/// Checks the gadgets of the unsigned integer type `$uint` of `$num_bits` bits on every pair of `$values`
+/// against `u128` arithmetic reduced modulo `2^$num_bits`.
+macro_rules! assert_uint_gadgets_match_native {
+    ($uint:ty, $num_bits:expr, $values:expr) => {{
+        let num_bits: u32 = $num_bits;
+        let mask = u128::MAX >> (128 - num_bits);
+        for a in $values {
+            for b in $values {
+                let outputs = solve_gadgets(&[a, b], |inputs, builder| {
+                    let (lhs, rhs) = (<$uint>::new(inputs[0]), <$uint>::new(inputs[1]));
+                    vec![
+                        lhs.rol(5, builder).inner,
+                        lhs.ror(5, builder).inner,
+                        lhs.shl(3, builder).inner,
+                        lhs.shr(3, builder).inner,
+                        lhs.add(rhs, builder).inner,
+                        lhs.sub(rhs, builder).inner,
+                        lhs.mul(rhs, builder).inner,
+                    ]
+                });
+                let expected = [
+                    ((a << 5) | (a >> (num_bits - 5))) & mask,
+                    ((a >> 5) | (a << (num_bits - 5))) & mask,
+                    (a << 3) & mask,
+                    a >> 3,
+                    a.wrapping_add(b) & mask,
+                    a.wrapping_sub(b) & mask,
+                    a.wrapping_mul(b) & mask,
+                ];
+                assert_eq!(outputs, expected, "{} bits, a = {a:#x}, b = {b:#x}", num_bits);
+            }
+        }
+    }};
+}
+
+#[test]
+fn uint_gadgets_match_native_arithmetic() {
+    assert_uint_gadgets_match_native!(UInt8, 8, [0_u128, 1, 0x7f, 0x80, 0xa5, 0xff]);
+    assert_uint_gadgets_match_native!(UInt16, 16, [0_u128, 1, 0x8000, 0xbeef, 0xffff]);
+    assert_uint_gadgets_match_native!(
+        UInt32,
+        32,
+        [0_u128, 1, 0x8000_0000, 0xdead_beef, 0xffff_ffff]
+    );
+    assert_uint_gadgets_match_native!(
+        UInt64,
+        64,
+        [0_u128, 1, 1 << 63, 0x0123_4567_89ab_cdef, u64::MAX as u128]
+    );
+    // Widths which are not a whole number of bytes.
+    assert_uint_gadgets_match_native!(UIntN<12>, 12, [0_u128, 1, 0x800, 0xabc, 0xfff]);
+    assert_uint_gadgets_match_native!(UIntN<61>, 61, [0_u128, 1, 1 << 60, (1 << 61) - 1]);
+}
+
+#[test]
+fn uint128_gadgets_match_native_arithmetic() {
+    // Products of values with their high limb set overflow the field unless split into halves.
+    let values = [
+        0_u128,
+        1,
+        u64::MAX as u128,
+        1 << 64,
+        0x0123_4567_89ab_cdef_fedc_ba98_7654_3210,
+        u128::MAX - 1,
+        u128::MAX,
+    ];
+    assert_uint_gadgets_match_native!(UInt128, 128, values);
+
+    for value in values {
+        let outputs = solve_gadgets(&[value], |inputs, builder| {
+            let (low, high) = UInt128::new(inputs[0]).to_u64_limbs(builder);
+            let recomposed = UInt128::from_u64_limbs(low, high, builder);
+            vec![low.inner, high.inner, recomposed.inner]
+        });
+        assert_eq!(outputs, [value as u64 as u128, value >> 64, value], "limbs of {value:#x}");
+    }
+
+    // The limbs are recomposed as `high * 2^64 + low`.
+    let outputs = solve_gadgets(&[0xdead_beef, 0xcafe], |inputs, builder| {
+        let limbs = (UInt64::new(inputs[0]), UInt64::new(inputs[1]));
+        vec![UInt128::from_u64_limbs(limbs.0, limbs.1, builder).inner]
+    });
+    assert_eq!(outputs, [(0xcafe << 64) | 0xdead_beef]);
+}
+
+/// Checks the gadgets of the signed integer type `$int`, with native type `$type` and unsigned counterpart
+/// `$unsigned`, on every pair of `$values` against native two's complement arithmetic.
+macro_rules! assert_int_gadgets_match_native {
+    ($int:ident, $type:ty, $unsigned:ty, $values:expr) => {{
+        let num_bits = <$type>::BITS;
+        let to_u128 = |value: $type| value as $unsigned as u128;
+        for a in $values {
+            for b in $values {
+                let outputs = solve_gadgets(&[to_u128(a), to_u128(b)], |inputs, builder| {
+                    let (lhs, rhs) = ($int::new(inputs[0]), $int::new(inputs[1]));
+                    vec![
+                        $int::load_constant(a, builder).inner,
+                        lhs.is_negative(builder),
+                        lhs.less_than(rhs, builder),
+                        lhs.add(rhs, builder).inner,
+                        lhs.sub(rhs, builder).inner,
+                        lhs.neg(builder).inner,
+                        lhs.shl(1, builder).inner,
+                        lhs.shr(1, builder).inner,
+                        lhs.shr(num_bits - 1, builder).inner,
+                    ]
+                });
+                let expected = [
+                    to_u128(a),
+                    (a < 0) as u128,
+                    (a < b) as u128,
+                    to_u128(a.wrapping_add(b)),
+                    to_u128(a.wrapping_sub(b)),
+                    to_u128(a.wrapping_neg()),
+                    to_u128(a.wrapping_shl(1)),
+                    to_u128(a >> 1),
+                    to_u128(a >> (num_bits - 1)),
+                ];
+                assert_eq!(outputs, expected, "{}, a = {a}, b = {b}", stringify!($int));
+            }
+        }
+    }};
+}
+
+#[test]
+fn int_gadgets_match_native_twos_complement_arithmetic() {
+    assert_int_gadgets_match_native!(Int8, i8, u8, [i8::MIN, i8::MIN + 1, -1, 0, 1, i8::MAX]);
+    assert_int_gadgets_match_native!(Int32, i32, u32, [i32::MIN, -1, 0, 1, i32::MAX]);
+    assert_int_gadgets_match_native!(Int64, i64, u64, [i64::MIN, -1, 0, 1, i64::MAX]);
+}
+
+fn hex_digest(hex: &str) -> Vec<u8> {
+    (0..hex.len()).step_by(2).map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap()).collect()
+}
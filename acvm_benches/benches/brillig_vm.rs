@@ -0,0 +1,35 @@
+//! Benchmarks `VM::process_opcodes` outside of the ACVM, on the Brillig loop of this crate.
+//!
+//! Run with `cargo bench -p acvm_benches --bench brillig_vm`.
+
+use acir::brillig_vm::{Registers, VMStatus, Value, VM};
+use acvm_benches::brillig_loop;
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+
+fn bench_process_opcodes(c: &mut Criterion) {
+    let mut group = c.benchmark_group("brillig_vm");
+    let bytecode = brillig_loop();
+    for iterations in [1 << 8, 1 << 12, 1 << 16] {
+        group.bench_with_input(
+            BenchmarkId::new("process_opcodes", iterations),
+            &iterations,
+            |b, &iterations| {
+                b.iter_batched(
+                    || {
+                        let registers = Registers::load(vec![Value::from(iterations as u128)]);
+                        VM::new(registers, Vec::new(), bytecode.clone(), Vec::new())
+                    },
+                    |mut vm| {
+                        assert_eq!(vm.process_opcodes(), VMStatus::Finished);
+                        vm
+                    },
+                    BatchSize::SmallInput,
+                )
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_process_opcodes);
+criterion_main!(benches);
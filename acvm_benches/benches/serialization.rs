@@ -0,0 +1,42 @@
+//! Benchmarks `Circuit::write` and `Circuit::read` on Brillig bytecode, and reports how much smaller its
+//! compact encoding is than its list of opcodes.
+//!
+//! Run with `cargo bench -p acvm_benches --bench serialization`.
+
+use acir::{brillig_vm::encode_bytecode, circuit::Circuit};
+use acvm_benches::{brillig_heavy, brillig_loop};
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+fn report_sizes() {
+    let bytecode = brillig_loop();
+    let opcodes = rmp_serde::to_vec(&bytecode).unwrap();
+    let compact = encode_bytecode(&bytecode);
+    println!(
+        "brillig_loop: {} bytes as a list of opcodes, {} bytes compactly ({:.1}x smaller)",
+        opcodes.len(),
+        compact.len(),
+        opcodes.len() as f64 / compact.len() as f64
+    );
+}
+
+fn bench_serialization(c: &mut Criterion) {
+    report_sizes();
+
+    let circuit = brillig_heavy(1 << 8).circuit;
+    let mut bytes = Vec::new();
+    circuit.write(&mut bytes).unwrap();
+
+    let mut group = c.benchmark_group("serialization");
+    group.bench_function("write", |b| {
+        b.iter(|| {
+            let mut bytes = Vec::new();
+            black_box(&circuit).write(&mut bytes).unwrap();
+            bytes
+        })
+    });
+    group.bench_function("read", |b| b.iter(|| Circuit::read(black_box(&*bytes)).unwrap()));
+    group.finish();
+}
+
+criterion_group!(benches, bench_serialization);
+criterion_main!(benches);
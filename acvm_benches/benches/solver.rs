@@ -0,0 +1,85 @@
+//! Benchmarks `ACVM::solve` over the circuits of this crate.
+//!
+//! Run with `cargo bench -p acvm_benches --bench solver`.
+
+use acvm::{
+    pwg::{IntegerExpressions, ACVM},
+    testing::MockBackend,
+};
+use acvm_benches::{brillig_heavy, hash_heavy, memory_heavy, range_heavy, BenchCircuit};
+use criterion::{
+    black_box, criterion_group, criterion_main, measurement::WallTime, BatchSize, BenchmarkGroup,
+    BenchmarkId, Criterion,
+};
+
+fn new_acvm(circuit: &BenchCircuit) -> ACVM<MockBackend> {
+    ACVM::new(
+        MockBackend::default(),
+        circuit.circuit.opcodes.clone(),
+        circuit.initial_witness.clone(),
+    )
+}
+
+fn bench_solve(
+    group: &mut BenchmarkGroup<WallTime>,
+    id: BenchmarkId,
+    circuit: &BenchCircuit,
+    build: impl Fn(&BenchCircuit) -> ACVM<MockBackend>,
+) {
+    group.bench_with_input(id, circuit, |b, circuit| {
+        // The ACVM is built outside of the measurement so that only solving is timed.
+        b.iter_batched(
+            || build(circuit),
+            |mut acvm| black_box(acvm.solve().unwrap()),
+            BatchSize::SmallInput,
+        )
+    });
+}
+
+fn bench_circuit(c: &mut Criterion, group_name: &str, circuits: Vec<(u32, BenchCircuit)>) {
+    let mut group = c.benchmark_group(group_name);
+    for (size, circuit) in circuits {
+        bench_solve(&mut group, BenchmarkId::new("solve", size), &circuit, new_acvm);
+    }
+    group.finish();
+}
+
+fn bench_hash_heavy(c: &mut Criterion) {
+    let circuits = [16, 128].map(|num_hashes| (num_hashes, hash_heavy(num_hashes)));
+    bench_circuit(c, "hash_heavy", circuits.into());
+}
+
+fn bench_memory_heavy(c: &mut Criterion) {
+    let circuits = [1 << 8, 1 << 12].map(|len| (len, memory_heavy(len + 1, len)));
+    bench_circuit(c, "memory_heavy", circuits.into());
+}
+
+fn bench_brillig_heavy(c: &mut Criterion) {
+    let circuits = [1 << 8, 1 << 12].map(|iterations| (iterations, brillig_heavy(iterations)));
+    bench_circuit(c, "brillig_heavy", circuits.into());
+}
+
+fn bench_range_heavy(c: &mut Criterion) {
+    let mut group = c.benchmark_group("range_heavy");
+    for num_values in [1 << 8, 1 << 12] {
+        let circuit = range_heavy(num_values);
+        bench_solve(&mut group, BenchmarkId::new("solve", num_values), &circuit, new_acvm);
+
+        // The integer forms are built once for the circuit, as they would be when solving it repeatedly.
+        let integer_expressions = IntegerExpressions::new(&circuit.circuit.opcodes);
+        let id = BenchmarkId::new("solve_with_integer_expressions", num_values);
+        bench_solve(&mut group, id, &circuit, |circuit| {
+            new_acvm(circuit).with_integer_expressions(integer_expressions.clone())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_hash_heavy,
+    bench_memory_heavy,
+    bench_brillig_heavy,
+    bench_range_heavy
+);
+criterion_main!(benches);
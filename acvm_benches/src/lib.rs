@@ -0,0 +1,263 @@
+//! Representative circuits for benchmarking the ACVM.
+//!
+//! Each circuit stresses a different part of the solver: black box hash functions, memory blocks,
+//! Brillig execution and range constrained arithmetic. They are public so that optimizations can be measured against the same
+//! workloads in other crates, and are run by the Criterion benchmarks of this crate:
+//!
+//! ```text
+//! cargo bench -p acvm_benches
+//! ```
+#![warn(unused_crate_dependencies, unused_extern_crates)]
+#![warn(unreachable_pub)]
+
+use acir::{
+    brillig_vm::{self, BinaryFieldOp, BinaryIntOp, RegisterIndex, Value},
+    circuit::{
+        brillig::{Brillig, BrilligInputs, BrilligOutputs},
+        opcodes::{BlackBoxFuncCall, BlockId, FunctionInput, MemOp},
+        Circuit, Opcode,
+    },
+    native_types::{Expression, Witness, WitnessMap},
+    FieldElement,
+};
+use acvm::{
+    pwg::{PartialWitnessGeneratorStatus, ACVM},
+    testing::MockBackend,
+};
+use std::collections::BTreeMap;
+
+// Only used by the benchmarks.
+#[cfg(test)]
+use criterion as _;
+#[cfg(test)]
+use rmp_serde as _;
+
+/// A circuit along with a witness from which it can be solved.
+#[derive(Clone, Debug)]
+pub struct BenchCircuit {
+    pub circuit: Circuit,
+    pub initial_witness: WitnessMap,
+}
+
+impl BenchCircuit {
+    /// Solves the circuit with the [`MockBackend`], returning the solved witness.
+    ///
+    /// # Panics
+    ///
+    /// If the circuit cannot be solved without foreign calls.
+    pub fn solve(&self) -> WitnessMap {
+        let mut acvm = ACVM::new(
+            MockBackend::default(),
+            self.circuit.opcodes.clone(),
+            self.initial_witness.clone(),
+        );
+        let status = acvm.solve().expect("bench circuits should be solvable");
+        assert_eq!(status, PartialWitnessGeneratorStatus::Solved);
+        acvm.witness_map().clone()
+    }
+}
+
+/// A chain of `num_hashes` hashes, each hashing the output of the previous one.
+///
+/// The hashes alternate between SHA256, Blake2s and Keccak256, starting from a 64 byte input.
+pub fn hash_heavy(num_hashes: u32) -> BenchCircuit {
+    let bytes = |witnesses: std::ops::Range<u32>| -> Vec<FunctionInput> {
+        witnesses.map(|witness| FunctionInput { witness: Witness(witness), num_bits: 8 }).collect()
+    };
+
+    let mut inputs = bytes(1..65);
+    let mut next_witness = 65;
+    let mut opcodes = Vec::with_capacity(num_hashes as usize);
+    for i in 0..num_hashes {
+        let outputs: Vec<Witness> = (next_witness..next_witness + 32).map(Witness).collect();
+        let hash = match i % 3 {
+            0 => BlackBoxFuncCall::SHA256 { inputs, outputs },
+            1 => BlackBoxFuncCall::Blake2s { inputs, outputs },
+            _ => BlackBoxFuncCall::Keccak256 { inputs, outputs },
+        };
+        opcodes.push(Opcode::BlackBoxFuncCall(hash));
+        inputs = bytes(next_witness..next_witness + 32);
+        next_witness += 32;
+    }
+
+    let initial_witness = (1..65)
+        .map(|witness| (Witness(witness), FieldElement::from(witness as u128)))
+        .collect::<BTreeMap<_, _>>()
+        .into();
+    BenchCircuit {
+        circuit: Circuit { current_witness_index: next_witness - 1, opcodes, ..Circuit::default() },
+        initial_witness,
+    }
+}
+
+/// A RAM block of `len` cells holding a permutation of its indices, followed by `num_reads` reads
+/// chasing the permutation: each read is at the index returned by the previous one.
+///
+/// `len` must not be a multiple of 7.
+pub fn memory_heavy(len: u32, num_reads: u32) -> BenchCircuit {
+    assert!(len % 7 != 0, "the block should hold a permutation of its indices");
+    let constant = |value: u32| Expression::from_field(FieldElement::from(value as u128));
+
+    // The value of cell `i` is held by the witness `i + 1`.
+    let init = Opcode::MemoryInit { block_id: BlockId(0), init: (1..=len).map(Witness).collect() };
+    let reads = (0..num_reads).map(|read| {
+        let index = if read == 0 { constant(0) } else { Expression::from(Witness(len + read)) };
+        Opcode::MemoryOp {
+            block_id: BlockId(0),
+            op: MemOp {
+                operation: Expression::zero(),
+                index,
+                value: Expression::from(Witness(len + read + 1)),
+            },
+        }
+    });
+    let opcodes = std::iter::once(init).chain(reads).collect();
+
+    let initial_witness = (0..len)
+        .map(|index| (Witness(index + 1), FieldElement::from(((7 * index + 3) % len) as u128)))
+        .collect::<BTreeMap<_, _>>()
+        .into();
+    BenchCircuit {
+        circuit: Circuit { current_witness_index: len + num_reads, opcodes, ..Circuit::default() },
+        initial_witness,
+    }
+}
+
+/// `num_values` values of 64 bits, each range constrained along with its 8 bytes and constrained to equal
+/// their little endian composition by an arithmetic opcode.
+pub fn range_heavy(num_values: u32) -> BenchCircuit {
+    let range = |witness, num_bits| {
+        Opcode::BlackBoxFuncCall(BlackBoxFuncCall::RANGE {
+            input: FunctionInput { witness: Witness(witness), num_bits },
+        })
+    };
+
+    // The value `i` is held by the witness `9 * i + 1` and its bytes by the 8 witnesses which follow.
+    let mut opcodes = Vec::with_capacity(num_values as usize * 10);
+    let mut initial_witness = BTreeMap::new();
+    for i in 0..num_values {
+        let value_witness = 9 * i + 1;
+        let value = u64::MAX - 0x0101_0101 * i as u64;
+        initial_witness.insert(Witness(value_witness), FieldElement::from(value as u128));
+
+        let mut composition = Expression::from(Witness(value_witness));
+        for (byte, byte_witness) in (value_witness + 1..value_witness + 9).enumerate() {
+            let byte_value = (value >> (8 * byte)) & 0xff;
+            initial_witness.insert(Witness(byte_witness), FieldElement::from(byte_value as u128));
+            composition
+                .linear_combinations
+                .push((-FieldElement::from(1_u128 << (8 * byte)), Witness(byte_witness)));
+            opcodes.push(range(byte_witness, 8));
+        }
+        opcodes.push(range(value_witness, 64));
+        opcodes.push(Opcode::AssertZero(composition));
+    }
+
+    BenchCircuit {
+        circuit: Circuit { current_witness_index: 9 * num_values, opcodes, ..Circuit::default() },
+        initial_witness: initial_witness.into(),
+    }
+}
+
+/// Brillig bytecode computing the sum of the squares of the integers below the value of the
+/// first register, which is overwritten with the result.
+pub fn brillig_loop() -> Vec<brillig_vm::Opcode> {
+    let [n, i, acc, one, condition, square] = [0, 1, 2, 3, 4, 5].map(RegisterIndex::from);
+    vec![
+        brillig_vm::Opcode::Const { destination: i, value: Value::from(0_u128) },
+        brillig_vm::Opcode::Const { destination: acc, value: Value::from(0_u128) },
+        brillig_vm::Opcode::Const { destination: one, value: Value::from(1_u128) },
+        // Loop header
+        brillig_vm::Opcode::BinaryIntOp {
+            destination: condition,
+            op: BinaryIntOp::LessThan,
+            bit_size: 64,
+            lhs: i,
+            rhs: n,
+        },
+        brillig_vm::Opcode::JumpIfNot { condition, location: 9 },
+        brillig_vm::Opcode::BinaryFieldOp {
+            destination: square,
+            op: BinaryFieldOp::Mul,
+            lhs: i,
+            rhs: i,
+        },
+        brillig_vm::Opcode::BinaryFieldOp {
+            destination: acc,
+            op: BinaryFieldOp::Add,
+            lhs: acc,
+            rhs: square,
+        },
+        brillig_vm::Opcode::BinaryIntOp {
+            destination: i,
+            op: BinaryIntOp::Add,
+            bit_size: 64,
+            lhs: i,
+            rhs: one,
+        },
+        brillig_vm::Opcode::Jump { location: 3 },
+        // Exit
+        brillig_vm::Opcode::Mov { destination: n, source: acc },
+        brillig_vm::Opcode::Stop,
+    ]
+}
+
+/// A Brillig opcode running [`brillig_loop`] for `iterations` iterations, whose result is
+/// constrained by an arithmetic opcode.
+pub fn brillig_heavy(iterations: u32) -> BenchCircuit {
+    let (input, output) = (Witness(1), Witness(2));
+    let brillig = Brillig {
+        inputs: vec![BrilligInputs::Single(Expression::from(input))],
+        outputs: vec![BrilligOutputs::Simple(output)],
+        foreign_call_results: Vec::new(),
+        bytecode: brillig_loop(),
+        predicate: None,
+    };
+    let expected = sum_of_squares(iterations);
+    let mut check = Expression::from(output);
+    check.q_c = -expected;
+
+    BenchCircuit {
+        circuit: Circuit {
+            current_witness_index: 2,
+            opcodes: vec![Opcode::Brillig(brillig), Opcode::AssertZero(check)],
+            ..Circuit::default()
+        },
+        initial_witness: BTreeMap::from([(input, FieldElement::from(iterations as u128))]).into(),
+    }
+}
+
+/// Returns the result of [`brillig_loop`] after `iterations` iterations.
+pub fn sum_of_squares(iterations: u32) -> FieldElement {
+    let n = iterations as u128;
+    FieldElement::from(n * n.saturating_sub(1) * (2 * n).saturating_sub(1) / 6)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{brillig_heavy, hash_heavy, memory_heavy, range_heavy, sum_of_squares};
+    use acir::{native_types::Witness, FieldElement};
+    use acvm::pwg::IntegerExpressions;
+
+    #[test]
+    fn bench_circuits_are_solvable() {
+        let witness = hash_heavy(4).solve();
+        assert_eq!(witness.len(), 64 + 4 * 32);
+
+        // Cell 0 holds 3, which holds 24, which holds 7.
+        let witness = memory_heavy(41, 3).solve();
+        assert_eq!(witness[&Witness(42)], FieldElement::from(3_u128));
+        assert_eq!(witness[&Witness(43)], FieldElement::from(24_u128));
+        assert_eq!(witness[&Witness(44)], FieldElement::from(7_u128));
+
+        let witness = brillig_heavy(10).solve();
+        assert_eq!(witness[&Witness(2)], sum_of_squares(10));
+        assert_eq!(sum_of_squares(10), FieldElement::from(285_u128));
+
+        let circuit = range_heavy(4);
+        // Each composition of a value from its bytes is checked with integer arithmetic.
+        assert_eq!(IntegerExpressions::new(&circuit.circuit.opcodes).len(), 4);
+        let witness = circuit.solve();
+        assert_eq!(witness.len(), 4 * 9);
+    }
+}
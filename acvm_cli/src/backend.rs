@@ -0,0 +1,35 @@
+use acvm::{acir::BlackBoxFunc, pwg::OpcodeResolutionError, FieldElement, PartialWitnessGenerator};
+
+/// The CLI is not linked to a proving system, so the black box functions which the ACVM
+/// cannot solve natively are reported as unsupported.
+#[derive(Debug, Default)]
+pub(crate) struct CliBackend;
+
+impl PartialWitnessGenerator for CliBackend {
+    fn schnorr_verify(
+        &self,
+        _public_key_x: &FieldElement,
+        _public_key_y: &FieldElement,
+        _signature_s: &FieldElement,
+        _signature_e: &FieldElement,
+        _message: &[u8],
+    ) -> Result<bool, OpcodeResolutionError> {
+        Err(OpcodeResolutionError::UnsupportedBlackBoxFunc(BlackBoxFunc::SchnorrVerify))
+    }
+
+    fn pedersen(
+        &self,
+        _inputs: &[FieldElement],
+        _domain_separator: u32,
+    ) -> Result<(FieldElement, FieldElement), OpcodeResolutionError> {
+        // `Pedersen` opcodes are solved by the ACVM through the `native-pedersen` feature.
+        Err(OpcodeResolutionError::UnsupportedBlackBoxFunc(BlackBoxFunc::Pedersen))
+    }
+
+    fn fixed_base_scalar_mul(
+        &self,
+        _input: &FieldElement,
+    ) -> Result<(FieldElement, FieldElement), OpcodeResolutionError> {
+        Err(OpcodeResolutionError::UnsupportedBlackBoxFunc(BlackBoxFunc::FixedBaseScalarMul))
+    }
+}
@@ -0,0 +1,28 @@
+use std::path::PathBuf;
+
+use acvm::{
+    compiler::DeprecatedOpcodeError, foreign_calls::ForeignCallError, pwg::OpcodeResolutionError,
+};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub(crate) enum CliError {
+    #[error("failed to read {0}: {1}")]
+    ReadFile(PathBuf, std::io::Error),
+    #[error("failed to write {0}: {1}")]
+    WriteFile(PathBuf, std::io::Error),
+    #[error("invalid inputs file {0}: {1}")]
+    InvalidInputs(PathBuf, String),
+    #[error("invalid witness index `{0}`")]
+    InvalidWitnessIndex(String),
+    #[error("invalid value for witness {0}: `{1}`")]
+    InvalidWitnessValue(u32, String),
+    #[error("failed to serialize the solved witness: {0}")]
+    SerializeWitness(String),
+    #[error("circuit uses a deprecated opcode: {0}")]
+    Deprecated(#[from] DeprecatedOpcodeError),
+    #[error("failed to solve circuit: {0}")]
+    Solve(#[from] OpcodeResolutionError),
+    #[error(transparent)]
+    ForeignCall(#[from] ForeignCallError),
+}
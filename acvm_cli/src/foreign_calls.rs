@@ -0,0 +1,186 @@
+use std::{
+    collections::{BTreeMap, VecDeque},
+    path::Path,
+};
+
+use acvm::{
+    acir::brillig_vm::{ForeignCallInput, ForeignCallOutput, ForeignCallResult, Value},
+    foreign_calls::{ForeignCallError, ForeignCallExecutor},
+    pwg::ForeignCallWaitInfo,
+    FieldElement,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{errors::CliError, inputs::to_hex};
+
+/// A foreign call output, encoded as hex strings.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub(crate) enum JsonForeignCallOutput {
+    Single(String),
+    Array(Vec<String>),
+}
+
+impl From<&ForeignCallOutput> for JsonForeignCallOutput {
+    fn from(output: &ForeignCallOutput) -> Self {
+        match output {
+            ForeignCallOutput::Single(value) => Self::Single(to_hex(&value.to_field())),
+            ForeignCallOutput::Array(values) => {
+                Self::Array(values.iter().map(|value| to_hex(&value.to_field())).collect())
+            }
+        }
+    }
+}
+
+impl JsonForeignCallOutput {
+    fn decode(self) -> Result<ForeignCallOutput, ForeignCallError> {
+        let decode_value = |hex: String| {
+            FieldElement::try_from_str(&hex).map(Value::from).ok_or_else(|| {
+                ForeignCallError::InvalidEncoding(format!("invalid output value `{hex}`"))
+            })
+        };
+        match self {
+            Self::Single(value) => decode_value(value).map(ForeignCallOutput::Single),
+            Self::Array(values) => values
+                .into_iter()
+                .map(decode_value)
+                .collect::<Result<_, _>>()
+                .map(ForeignCallOutput::Array),
+        }
+    }
+}
+
+/// Returns the inputs of `foreign_call`, encoded as hex strings.
+pub(crate) fn encode_inputs(foreign_call: &ForeignCallWaitInfo) -> Vec<Vec<String>> {
+    foreign_call
+        .inputs
+        .iter()
+        .map(|input| input.values().iter().map(|value| to_hex(&value.to_field())).collect())
+        .collect()
+}
+
+fn decode_outputs(
+    outputs: Vec<JsonForeignCallOutput>,
+) -> Result<ForeignCallResult, ForeignCallError> {
+    let values =
+        outputs.into_iter().map(JsonForeignCallOutput::decode).collect::<Result<_, _>>()?;
+    Ok(ForeignCallResult { values })
+}
+
+/// Answers foreign calls with responses recorded in advance, in the order they are made.
+///
+/// Calls to functions without recorded responses have no outputs, which is enough for logging oracles.
+#[derive(Debug, Default)]
+pub(crate) struct MockForeignCallExecutor {
+    responses: BTreeMap<String, VecDeque<Vec<JsonForeignCallOutput>>>,
+}
+
+impl MockForeignCallExecutor {
+    /// Reads the responses from a JSON file mapping each function to the list of its responses, e.g.
+    ///
+    /// ```json
+    /// { "get_price": [["0x05"], ["0x06"]], "get_path": [[["0x01", "0x02"]]] }
+    /// ```
+    pub(crate) fn from_file(path: &Path) -> Result<Self, CliError> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|err| CliError::ReadFile(path.to_path_buf(), err))?;
+        let responses = serde_json::from_str(&contents)
+            .map_err(|err| CliError::InvalidInputs(path.to_path_buf(), err.to_string()))?;
+        Ok(Self { responses })
+    }
+}
+
+impl ForeignCallExecutor for MockForeignCallExecutor {
+    fn execute(
+        &mut self,
+        foreign_call: &ForeignCallWaitInfo,
+    ) -> Result<ForeignCallResult, ForeignCallError> {
+        match self.responses.get_mut(&foreign_call.function) {
+            Some(responses) => {
+                let outputs = responses.pop_front().ok_or_else(|| ForeignCallError::Failed {
+                    function: foreign_call.function.clone(),
+                    reason: "no responses left".to_owned(),
+                })?;
+                decode_outputs(outputs)
+            }
+            None => Ok(ForeignCallResult { values: Vec::new() }),
+        }
+    }
+}
+
+/// Answers foreign calls with their own inputs, values read from registers being returned as such and values read from
+/// memory as arrays.
+#[derive(Debug, Default)]
+pub(crate) struct EchoForeignCallExecutor;
+
+impl ForeignCallExecutor for EchoForeignCallExecutor {
+    fn execute(
+        &mut self,
+        foreign_call: &ForeignCallWaitInfo,
+    ) -> Result<ForeignCallResult, ForeignCallError> {
+        let values = foreign_call
+            .inputs
+            .iter()
+            .map(|input| match input {
+                ForeignCallInput::Single(value) => ForeignCallOutput::Single(*value),
+                input => ForeignCallOutput::Array(input.values().to_vec()),
+            })
+            .collect();
+        Ok(ForeignCallResult { values })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{EchoForeignCallExecutor, MockForeignCallExecutor};
+    use acvm::{
+        acir::brillig_vm::{ForeignCallInput, ForeignCallOutput, Value},
+        foreign_calls::ForeignCallExecutor,
+        pwg::ForeignCallWaitInfo,
+    };
+
+    fn foreign_call(function: &str, inputs: Vec<ForeignCallInput>) -> ForeignCallWaitInfo {
+        ForeignCallWaitInfo { function: function.to_owned(), inputs }
+    }
+
+    #[test]
+    fn echoes_inputs() {
+        let inputs = vec![
+            ForeignCallInput::Single(Value::from(1_u128)),
+            ForeignCallInput::Array { pointer: 0, values: vec![Value::from(2_u128)] },
+            ForeignCallInput::Vector { pointer: 1, values: vec![Value::from(3_u128)] },
+        ];
+
+        let result = EchoForeignCallExecutor.execute(&foreign_call("echo", inputs)).unwrap();
+
+        // Arrays holding a single value are still returned as arrays.
+        assert_eq!(
+            result.values,
+            vec![
+                ForeignCallOutput::Single(Value::from(1_u128)),
+                ForeignCallOutput::Array(vec![Value::from(2_u128)]),
+                ForeignCallOutput::Array(vec![Value::from(3_u128)]),
+            ]
+        );
+    }
+
+    #[test]
+    fn replays_mocked_responses_in_order() {
+        let mut executor = MockForeignCallExecutor {
+            responses: serde_json::from_str(r#"{ "price": [["0x05"], [["0x06", "7"]]] }"#).unwrap(),
+        };
+        let price = foreign_call("price", Vec::new());
+
+        assert_eq!(
+            executor.execute(&price).unwrap().values,
+            vec![ForeignCallOutput::Single(Value::from(5_u128))]
+        );
+        assert_eq!(
+            executor.execute(&price).unwrap().values,
+            vec![ForeignCallOutput::Array(vec![Value::from(6_u128), Value::from(7_u128)])]
+        );
+        assert!(executor.execute(&price).is_err());
+        // Calls which were not mocked have no outputs.
+        assert!(executor.execute(&foreign_call("print", Vec::new())).unwrap().values.is_empty());
+    }
+}
@@ -0,0 +1,123 @@
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+use acvm::{
+    acir::native_types::{Witness, WitnessMap},
+    FieldElement,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::errors::CliError;
+
+/// The human-readable formats of witness maps, chosen from the extension of their file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Toml,
+    Json,
+}
+
+impl Format {
+    fn from_path(path: &Path) -> Option<Format> {
+        match path.extension()?.to_str()? {
+            "toml" => Some(Format::Toml),
+            "json" => Some(Format::Json),
+            _ => None,
+        }
+    }
+}
+
+/// The value of a witness, either as an integer or as a decimal or `0x`-prefixed hex string.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+enum InputValue {
+    Integer(u64),
+    String(String),
+}
+
+/// Reads the initial witness from a TOML or JSON file mapping witness indices to their values, e.g.
+///
+/// ```toml
+/// 1 = 5
+/// 2 = "0x0a"
+/// ```
+pub(crate) fn read_inputs(path: &Path) -> Result<WitnessMap, CliError> {
+    let contents =
+        std::fs::read_to_string(path).map_err(|err| CliError::ReadFile(path.to_path_buf(), err))?;
+    let invalid_inputs = |message: String| CliError::InvalidInputs(path.to_path_buf(), message);
+    let values: BTreeMap<String, InputValue> = match Format::from_path(path) {
+        Some(Format::Toml) => {
+            toml::from_str(&contents).map_err(|err| invalid_inputs(err.to_string()))?
+        }
+        Some(Format::Json) => {
+            serde_json::from_str(&contents).map_err(|err| invalid_inputs(err.to_string()))?
+        }
+        None => return Err(invalid_inputs("expected a `.toml` or `.json` file".into())),
+    };
+    parse_witness_map(values)
+}
+
+fn parse_witness_map(values: BTreeMap<String, InputValue>) -> Result<WitnessMap, CliError> {
+    let mut witness_map = WitnessMap::new();
+    for (index, value) in values {
+        let witness_index =
+            index.parse::<u32>().map_err(|_| CliError::InvalidWitnessIndex(index.clone()))?;
+        let value = match value {
+            InputValue::Integer(value) => FieldElement::from(value as u128),
+            InputValue::String(value) => FieldElement::try_from_str(&value)
+                .ok_or(CliError::InvalidWitnessValue(witness_index, value))?,
+        };
+        witness_map.insert(Witness(witness_index), value);
+    }
+    Ok(witness_map)
+}
+
+/// Writes the solved witness to `path`, as hex strings if it is a TOML or JSON file,
+/// and in the compressed binary format of [`WitnessMap`] otherwise.
+pub(crate) fn write_witness(path: &Path, witness_map: WitnessMap) -> Result<(), CliError> {
+    let write_error = |err| CliError::WriteFile(PathBuf::from(path), err);
+    let bytes = match Format::from_path(path) {
+        Some(format) => {
+            let values: BTreeMap<String, String> = witness_map
+                .into_iter()
+                .map(|(witness, value)| (witness.witness_index().to_string(), to_hex(&value)))
+                .collect();
+            let contents = match format {
+                Format::Toml => toml::to_string(&values).map_err(|err| err.to_string()),
+                Format::Json => {
+                    serde_json::to_string_pretty(&values).map_err(|err| err.to_string())
+                }
+            }
+            .map_err(CliError::SerializeWitness)?;
+            contents.into_bytes()
+        }
+        None => Vec::<u8>::try_from(witness_map)
+            .map_err(|err| CliError::SerializeWitness(err.to_string()))?,
+    };
+    std::fs::write(path, bytes).map_err(write_error)
+}
+
+/// Returns the `0x`-prefixed hex encoding of `value`.
+pub(crate) fn to_hex(value: &FieldElement) -> String {
+    format!("0x{}", value.to_hex())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_witness_map, InputValue};
+    use acvm::{acir::native_types::Witness, FieldElement};
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn parses_integer_decimal_and_hex_values() {
+        let values: BTreeMap<String, InputValue> =
+            toml::from_str("1 = 5\n2 = \"10\"\n3 = \"0x0f\"").unwrap();
+
+        let witness_map = parse_witness_map(values).unwrap();
+
+        assert_eq!(witness_map[&Witness(1)], FieldElement::from(5_u128));
+        assert_eq!(witness_map[&Witness(2)], FieldElement::from(10_u128));
+        assert_eq!(witness_map[&Witness(3)], FieldElement::from(15_u128));
+    }
+}
@@ -0,0 +1,291 @@
+//! `acvm-cli` executes a serialized ACIR circuit on a set of inputs, independently of any front-end.
+//!
+//! The solved witness and a JSON report of the execution are written to the given files,
+//! which makes it possible to debug a circuit artifact and its foreign calls in isolation.
+#![warn(unused_crate_dependencies)]
+#![warn(unreachable_pub)]
+
+mod backend;
+mod errors;
+mod foreign_calls;
+mod inputs;
+mod report;
+
+use std::{path::PathBuf, time::Instant};
+
+use acvm::{
+    acir::{circuit::Circuit, native_types::WitnessMap},
+    compiler::check_deprecated_opcodes,
+    foreign_calls::{ForeignCallExecutor, JsonRpcForeignCallExecutor},
+    pwg::{PartialWitnessGeneratorStatus, ACVM},
+};
+use clap::{Parser, ValueEnum};
+
+use backend::CliBackend;
+use errors::CliError;
+use foreign_calls::{encode_inputs, EchoForeignCallExecutor, MockForeignCallExecutor};
+use report::{ForeignCallRecord, SolveReport};
+
+/// Executes a serialized ACIR circuit and writes its solved witness.
+#[derive(Debug, Parser)]
+#[command(name = "acvm-cli", version)]
+struct Args {
+    /// The serialized circuit to execute.
+    circuit: PathBuf,
+    /// A TOML or JSON file mapping witness indices to their initial values.
+    #[arg(short, long)]
+    inputs: PathBuf,
+    /// Where to write the solved witness, as TOML or JSON depending on its extension
+    /// and in the compressed binary format otherwise.
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+    /// Where to write the JSON report of the execution.
+    #[arg(short, long)]
+    report: Option<PathBuf>,
+    /// Where to write the timeline of the solver, in the Chrome trace event format read by
+    /// `chrome://tracing` and the Perfetto UI.
+    #[arg(long)]
+    trace: Option<PathBuf>,
+    /// How foreign calls are resolved.
+    #[arg(long, value_enum, default_value_t = ForeignCallMode::Mock)]
+    foreign_calls: ForeignCallMode,
+    /// A JSON file mapping functions to their responses, for `mock` foreign calls.
+    #[arg(long)]
+    oracle_responses: Option<PathBuf>,
+    /// The URL of the JSON-RPC server resolving `http` foreign calls.
+    #[arg(long, required_if_eq("foreign_calls", "http"))]
+    oracle_url: Option<String>,
+    /// Rejects circuits using deprecated opcodes, such as directives.
+    #[arg(long)]
+    strict: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum ForeignCallMode {
+    /// Replays the responses of `--oracle-responses`, calls without responses having no outputs.
+    Mock,
+    /// Returns the inputs of each call as its outputs.
+    Echo,
+    /// Forwards each call to the JSON-RPC server at `--oracle-url`, following the oracle resolver protocol.
+    Http,
+}
+
+fn main() {
+    if let Err(err) = run(Args::parse()) {
+        eprintln!("error: {err}");
+        std::process::exit(1);
+    }
+}
+
+fn run(args: Args) -> Result<(), CliError> {
+    let bytes = std::fs::read(&args.circuit)
+        .map_err(|err| CliError::ReadFile(args.circuit.clone(), err))?;
+    let circuit =
+        Circuit::read(&*bytes).map_err(|err| CliError::ReadFile(args.circuit.clone(), err))?;
+    if args.strict {
+        check_deprecated_opcodes(&circuit.opcodes)?;
+    }
+    let initial_witness = inputs::read_inputs(&args.inputs)?;
+
+    let mut executor: Box<dyn ForeignCallExecutor> = match args.foreign_calls {
+        ForeignCallMode::Mock => match &args.oracle_responses {
+            Some(path) => Box::new(MockForeignCallExecutor::from_file(path)?),
+            None => Box::<MockForeignCallExecutor>::default(),
+        },
+        ForeignCallMode::Echo => Box::new(EchoForeignCallExecutor),
+        ForeignCallMode::Http => Box::new(JsonRpcForeignCallExecutor::new(
+            args.oracle_url.clone().expect("clap requires a URL for http foreign calls"),
+        )),
+    };
+
+    let mut report = SolveReport::default();
+    let traced = args.trace.is_some();
+    let result = execute(&circuit, initial_witness, executor.as_mut(), traced, &mut report);
+
+    if let Some(path) = &args.report {
+        let contents = serde_json::to_string_pretty(&report).expect("reports are serializable");
+        std::fs::write(path, contents).map_err(|err| CliError::WriteFile(path.clone(), err))?;
+    }
+    if let (Some(path), Some(trace)) = (&args.trace, &report.trace) {
+        let contents = trace.to_chrome_trace().to_string();
+        std::fs::write(path, contents).map_err(|err| CliError::WriteFile(path.clone(), err))?;
+    }
+    let solved_witness = result?;
+    eprintln!(
+        "solved {} opcodes with {} foreign calls in {}ms",
+        report.num_opcodes,
+        report.foreign_calls.len(),
+        report.duration_ms
+    );
+    match &args.output {
+        Some(path) => inputs::write_witness(path, solved_witness),
+        None => Ok(()),
+    }
+}
+
+/// Solves `circuit` from `initial_witness`, resolving its foreign calls with `executor`.
+///
+/// The [phases][Circuit::phases] of the circuit are activated in order, each once the opcodes preceding it are solved.
+///
+/// `report` records the execution, whether it succeeds or not, along with the timeline of the solver if `traced`.
+fn execute(
+    circuit: &Circuit,
+    initial_witness: WitnessMap,
+    executor: &mut dyn ForeignCallExecutor,
+    traced: bool,
+    report: &mut SolveReport,
+) -> Result<WitnessMap, CliError> {
+    let start = Instant::now();
+    report.num_opcodes = circuit.opcodes.len();
+    report.num_input_witnesses = initial_witness.len();
+    let mut acvm = ACVM::new(CliBackend, circuit.opcodes.clone(), initial_witness)
+        .with_phases(&circuit.phases);
+    if traced {
+        acvm = acvm.with_solve_trace();
+    }
+
+    let result = loop {
+        match acvm.solve() {
+            Ok(PartialWitnessGeneratorStatus::Solved) => {
+                let Some(phase) = acvm.inactive_phases().first().map(|phase| phase.to_string())
+                else {
+                    break Ok(());
+                };
+                acvm.activate_phase(&phase).expect("the phase is inactive");
+                report.phases.push(phase);
+            }
+            Ok(PartialWitnessGeneratorStatus::RequiresForeignCall) => {
+                let foreign_call =
+                    acvm.get_pending_foreign_call().expect("the ACVM requires a foreign call");
+                let foreign_call_result = match executor.execute(foreign_call) {
+                    Ok(foreign_call_result) => foreign_call_result,
+                    Err(err) => break Err(err.into()),
+                };
+                report.foreign_calls.push(ForeignCallRecord {
+                    function: foreign_call.function.clone(),
+                    inputs: encode_inputs(foreign_call),
+                    outputs: foreign_call_result.values.iter().map(Into::into).collect(),
+                });
+                acvm.resolve_pending_foreign_call(foreign_call_result);
+            }
+            Ok(PartialWitnessGeneratorStatus::RequiresBlackBoxCall) => {
+                unreachable!("the CLI backend does not defer black box functions")
+            }
+            Err(err) => {
+                report.opcode_location = err.opcode_location().map(|location| location.to_string());
+                break Err(err.into());
+            }
+        }
+    };
+
+    report.solved = result.is_ok();
+    report.error = result.as_ref().err().map(ToString::to_string);
+    report.num_assigned_witnesses = acvm.witness_map().len();
+    report.duration_ms = start.elapsed().as_millis();
+    report.trace = acvm.solve_trace().cloned();
+    result.map(|()| acvm.witness_map().clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{execute, foreign_calls::EchoForeignCallExecutor, report::SolveReport};
+    use acvm::{
+        acir::{
+            brillig_vm::{self, RegisterIndex, RegisterOrMemory},
+            circuit::{
+                brillig::{Brillig, BrilligInputs, BrilligOutputs},
+                phases::Phase,
+                Circuit, Opcode,
+            },
+            native_types::{Expression, Witness, WitnessMap},
+        },
+        pwg::{TraceEventKind, TraceOutcome},
+        FieldElement,
+    };
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn records_foreign_calls_and_failures() {
+        // An oracle returns its input, the square of which is then constrained to be equal to 9.
+        let mut square_minus_nine = Expression::from_field(-FieldElement::from(9_u128));
+        square_minus_nine.push_multiplication_term(FieldElement::one(), Witness(2), Witness(2));
+        let circuit = Circuit {
+            current_witness_index: 2,
+            opcodes: vec![
+                Opcode::Brillig(Brillig {
+                    inputs: vec![BrilligInputs::Single(Witness(1).into())],
+                    outputs: vec![BrilligOutputs::Simple(Witness(2))],
+                    foreign_call_results: vec![],
+                    bytecode: vec![brillig_vm::Opcode::ForeignCall {
+                        function: "echo".into(),
+                        destinations: vec![RegisterOrMemory::RegisterIndex(RegisterIndex::from(0))],
+                        inputs: vec![RegisterOrMemory::RegisterIndex(RegisterIndex::from(0))],
+                    }],
+                    predicate: None,
+                }),
+                Opcode::AssertZero(square_minus_nine),
+            ],
+            ..Circuit::default()
+        };
+        let initial_witness = |value: u128| {
+            WitnessMap::from(BTreeMap::from([(Witness(1), FieldElement::from(value))]))
+        };
+
+        let mut report = SolveReport::default();
+        let solved_witness =
+            execute(&circuit, initial_witness(3), &mut EchoForeignCallExecutor, false, &mut report)
+                .unwrap();
+        assert_eq!(solved_witness[&Witness(2)], FieldElement::from(3_u128));
+        assert!(report.solved);
+        assert_eq!(report.foreign_calls.len(), 1);
+        assert_eq!(report.foreign_calls[0].function, "echo");
+        assert_eq!(report.num_assigned_witnesses, 2);
+        assert!(report.trace.is_none());
+
+        let mut report = SolveReport::default();
+        let result =
+            execute(&circuit, initial_witness(4), &mut EchoForeignCallExecutor, true, &mut report);
+        assert!(result.is_err());
+        assert!(!report.solved);
+        assert_eq!(report.opcode_location.as_deref(), Some("1"));
+        let trace = report.trace.unwrap();
+        let failure = &trace.events().last().unwrap().kind;
+        assert!(matches!(failure, TraceEventKind::Opcode { outcome: TraceOutcome::Failed, .. }));
+    }
+
+    #[test]
+    fn activates_phases_in_order() {
+        let echo = |function: &str, input: u32, output: u32| {
+            Opcode::Brillig(Brillig {
+                inputs: vec![BrilligInputs::Single(Witness(input).into())],
+                outputs: vec![BrilligOutputs::Simple(Witness(output))],
+                foreign_call_results: vec![],
+                bytecode: vec![brillig_vm::Opcode::ForeignCall {
+                    function: function.into(),
+                    destinations: vec![RegisterOrMemory::RegisterIndex(RegisterIndex::from(0))],
+                    inputs: vec![RegisterOrMemory::RegisterIndex(RegisterIndex::from(0))],
+                }],
+                predicate: None,
+            })
+        };
+        // The challenge is only obtained once the commitment, which comes after it, is made.
+        let circuit = Circuit {
+            current_witness_index: 3,
+            opcodes: vec![echo("challenge", 2, 3), echo("commit", 1, 2)],
+            phases: vec![Phase::new("challenge", [0])],
+            ..Circuit::default()
+        };
+        let initial_witness =
+            WitnessMap::from(BTreeMap::from([(Witness(1), FieldElement::from(5_u128))]));
+
+        let mut report = SolveReport::default();
+        let solved_witness =
+            execute(&circuit, initial_witness, &mut EchoForeignCallExecutor, false, &mut report)
+                .unwrap();
+        assert_eq!(solved_witness[&Witness(3)], FieldElement::from(5_u128));
+        let functions: Vec<_> =
+            report.foreign_calls.iter().map(|call| call.function.as_str()).collect();
+        assert_eq!(functions, ["commit", "challenge"]);
+        assert_eq!(report.phases, ["challenge"]);
+    }
+}
@@ -0,0 +1,35 @@
+use acvm::pwg::SolveTrace;
+use serde::Serialize;
+
+use crate::foreign_calls::JsonForeignCallOutput;
+
+/// A summary of the execution of a circuit, written as JSON.
+#[derive(Debug, Default, Serialize)]
+pub(crate) struct SolveReport {
+    /// Whether all opcodes of the circuit have been solved.
+    pub(crate) solved: bool,
+    pub(crate) num_opcodes: usize,
+    /// The number of witnesses assigned by the inputs.
+    pub(crate) num_input_witnesses: usize,
+    /// The number of witnesses assigned once execution stopped, including the inputs.
+    pub(crate) num_assigned_witnesses: usize,
+    /// The foreign calls resolved during execution, in order.
+    pub(crate) foreign_calls: Vec<ForeignCallRecord>,
+    /// The phases of the circuit activated during execution, in order.
+    pub(crate) phases: Vec<String>,
+    /// The error which stopped execution, if any.
+    pub(crate) error: Option<String>,
+    /// The location of the opcode which failed, if known.
+    pub(crate) opcode_location: Option<String>,
+    pub(crate) duration_ms: u128,
+    /// The timeline of the solver, if it was traced.
+    #[serde(skip)]
+    pub(crate) trace: Option<SolveTrace>,
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct ForeignCallRecord {
+    pub(crate) function: String,
+    pub(crate) inputs: Vec<Vec<String>>,
+    pub(crate) outputs: Vec<JsonForeignCallOutput>,
+}
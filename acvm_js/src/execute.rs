@@ -0,0 +1,73 @@
+use acvm::{
+    acir::{
+        circuit::{brillig::Brillig, Circuit, Opcode},
+        native_types::WitnessMap,
+    },
+    pwg::{PartialWitnessGeneratorStatus, ACVM},
+};
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    backend::SimulatedBackend, foreign_call::resolve_brillig, ForeignCallHandler, JsWitnessMap,
+};
+
+/// Executes an ACIR circuit to generate the solved witness from the initial witness.
+///
+/// @param {Uint8Array} circuit - A serialized representation of an ACIR circuit
+/// @param {WitnessMap} initial_witness - The initial witness map defining all of the inputs to `circuit`.
+/// @param {ForeignCallHandler} foreign_call_handler - A callback to process any foreign calls from the circuit.
+/// @returns {WitnessMap} A witness map containing the circuit's return values.
+#[wasm_bindgen(js_name = executeCircuit, skip_jsdoc)]
+pub async fn execute_circuit(
+    circuit: Vec<u8>,
+    initial_witness: JsWitnessMap,
+    foreign_call_handler: ForeignCallHandler,
+) -> Result<JsWitnessMap, JsValue> {
+    let circuit = Circuit::read(&*circuit).map_err(|err| JsError::new(&err.to_string()))?;
+    execute_opcodes(circuit.opcodes, initial_witness, &foreign_call_handler).await
+}
+
+/// Executes a single Brillig opcode, assigning its outputs in the witness map.
+///
+/// @param {Uint8Array} brillig - A MessagePack serialized representation of a Brillig opcode.
+/// @param {WitnessMap} initial_witness - The witness map assigning the inputs of `brillig`.
+/// @param {ForeignCallHandler} foreign_call_handler - A callback to process the foreign calls of the Brillig bytecode.
+/// @returns {WitnessMap} The witness map extended with the outputs of `brillig`.
+#[wasm_bindgen(js_name = executeBrillig, skip_jsdoc)]
+pub async fn execute_brillig(
+    brillig: Vec<u8>,
+    initial_witness: JsWitnessMap,
+    foreign_call_handler: ForeignCallHandler,
+) -> Result<JsWitnessMap, JsValue> {
+    let brillig: Brillig =
+        rmp_serde::from_slice(&brillig).map_err(|err| JsError::new(&err.to_string()))?;
+    execute_opcodes(vec![Opcode::Brillig(brillig)], initial_witness, &foreign_call_handler).await
+}
+
+/// Solves `opcodes`, pausing the ACVM to resolve its foreign calls through `foreign_call_handler`.
+async fn execute_opcodes(
+    opcodes: Vec<Opcode>,
+    initial_witness: JsWitnessMap,
+    foreign_call_handler: &ForeignCallHandler,
+) -> Result<JsWitnessMap, JsValue> {
+    let initial_witness = WitnessMap::try_from(initial_witness)?;
+    let mut acvm = ACVM::new(SimulatedBackend, opcodes, initial_witness);
+
+    loop {
+        let status = acvm.solve().map_err(|err| JsError::new(&err.to_string()))?;
+        match status {
+            PartialWitnessGeneratorStatus::Solved => break,
+            PartialWitnessGeneratorStatus::RequiresForeignCall => {
+                let foreign_call =
+                    acvm.get_pending_foreign_call().expect("the ACVM requires a foreign call");
+                let result = resolve_brillig(foreign_call_handler, foreign_call).await?;
+                acvm.resolve_pending_foreign_call(result);
+            }
+            PartialWitnessGeneratorStatus::RequiresBlackBoxCall => {
+                unreachable!("the simulated backend does not defer black box functions")
+            }
+        }
+    }
+
+    Ok(acvm.witness_map().clone().into())
+}
@@ -0,0 +1,84 @@
+use acvm::{
+    acir::brillig_vm::{ForeignCallOutput, ForeignCallResult, Value},
+    pwg::ForeignCallWaitInfo,
+    FieldElement,
+};
+use js_sys::{Array, JsString, Promise};
+use wasm_bindgen::{prelude::*, JsCast};
+use wasm_bindgen_futures::JsFuture;
+
+use crate::js_witness_map::field_element_to_hex;
+
+#[wasm_bindgen(typescript_custom_section)]
+const FOREIGN_CALL_HANDLER: &'static str = r#"
+// A foreign call input is either a single hex-encoded field element or an array of them.
+export type ForeignCallInput = string[]
+// A foreign call output is either a single hex-encoded field element or an array of them.
+export type ForeignCallOutput = string | string[]
+
+/**
+* A callback which performs a foreign call and returns the response.
+* @callback ForeignCallHandler
+* @param {string} name - The identifier for the type of foreign call being performed.
+* @param {ForeignCallInput[]} inputs - An array of hex encoded inputs to the foreign call.
+* @returns {Promise<ForeignCallOutput[]>} outputs - An array of hex encoded outputs containing the results of the foreign call.
+*/
+export type ForeignCallHandler = (name: string, inputs: ForeignCallInput[]) => Promise<ForeignCallOutput[]>;
+"#;
+
+#[wasm_bindgen]
+extern "C" {
+    /// The Javascript callback resolving the foreign calls of the executed circuit.
+    #[wasm_bindgen(extends = js_sys::Function, typescript_type = "ForeignCallHandler")]
+    #[derive(Clone, Debug, PartialEq)]
+    pub type ForeignCallHandler;
+}
+
+/// Calls `foreign_call_handler` with the inputs of `foreign_call` and waits for its outputs.
+pub(crate) async fn resolve_brillig(
+    foreign_call_handler: &ForeignCallHandler,
+    foreign_call: &ForeignCallWaitInfo,
+) -> Result<ForeignCallResult, JsValue> {
+    let name = JsString::from(foreign_call.function.as_str());
+    let inputs: Array = foreign_call
+        .inputs
+        .iter()
+        .map(|input| {
+            input
+                .values()
+                .iter()
+                .map(|value| field_element_to_hex(&value.to_field()))
+                .collect::<Array>()
+        })
+        .collect();
+
+    let promise: Promise =
+        foreign_call_handler.call2(&JsValue::NULL, &name, &inputs)?.dyn_into()?;
+    let outputs: Array = JsFuture::from(promise).await?.dyn_into().map_err(|_| {
+        JsError::new(&format!(
+            "foreign call handler must return an array of outputs for `{}`",
+            foreign_call.function
+        ))
+    })?;
+
+    let values = outputs.iter().map(decode_foreign_call_output).collect::<Result<_, _>>()?;
+    Ok(ForeignCallResult { values })
+}
+
+/// Decodes either a single hex string or an array of hex strings.
+fn decode_foreign_call_output(output: JsValue) -> Result<ForeignCallOutput, JsValue> {
+    if Array::is_array(&output) {
+        let values = Array::from(&output).iter().map(decode_value).collect::<Result<_, _>>()?;
+        Ok(ForeignCallOutput::Array(values))
+    } else {
+        decode_value(output).map(ForeignCallOutput::Single)
+    }
+}
+
+fn decode_value(value: JsValue) -> Result<Value, JsValue> {
+    value
+        .as_string()
+        .and_then(|hex| FieldElement::from_hex(&hex))
+        .map(Value::from)
+        .ok_or_else(|| JsError::new("foreign call outputs must be hex strings").into())
+}
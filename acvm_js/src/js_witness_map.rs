@@ -0,0 +1,71 @@
+use acvm::{
+    acir::native_types::{Witness, WitnessMap},
+    FieldElement,
+};
+use js_sys::{JsString, Map};
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen(typescript_custom_section)]
+const WITNESS_MAP: &'static str = r#"
+// Map from witness index to hex string value of witness.
+export type WitnessMap = Map<number, string>;
+"#;
+
+#[wasm_bindgen]
+extern "C" {
+    /// A Javascript `Map` from witness indices to hex-encoded field elements.
+    #[wasm_bindgen(extends = Map, js_name = "WitnessMap", typescript_type = "WitnessMap")]
+    #[derive(Clone, Debug, PartialEq)]
+    pub type JsWitnessMap;
+
+    #[wasm_bindgen(constructor, js_class = "Map")]
+    fn new() -> JsWitnessMap;
+}
+
+impl Default for JsWitnessMap {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TryFrom<JsWitnessMap> for WitnessMap {
+    type Error = JsValue;
+
+    fn try_from(js_map: JsWitnessMap) -> Result<Self, Self::Error> {
+        let mut witness_map = WitnessMap::new();
+        let mut error = None;
+        js_map.for_each(&mut |value, key| {
+            if error.is_some() {
+                return;
+            }
+            match (key.as_f64(), value.as_string()) {
+                (Some(index), Some(value)) => match FieldElement::from_hex(&value) {
+                    Some(value) => {
+                        witness_map.insert(Witness(index as u32), value);
+                    }
+                    None => error = Some(format!("invalid value for witness {index}: {value}")),
+                },
+                _ => error = Some("witness map must map witness indices to hex strings".into()),
+            }
+        });
+        match error {
+            Some(error) => Err(JsError::new(&error).into()),
+            None => Ok(witness_map),
+        }
+    }
+}
+
+impl From<WitnessMap> for JsWitnessMap {
+    fn from(witness_map: WitnessMap) -> Self {
+        let js_map = JsWitnessMap::new();
+        for (witness, value) in witness_map {
+            js_map.set(&JsValue::from(witness.witness_index()), &field_element_to_hex(&value));
+        }
+        js_map
+    }
+}
+
+/// Returns the hex encoding of `value`, prefixed by `0x`.
+pub(crate) fn field_element_to_hex(value: &FieldElement) -> JsString {
+    format!("0x{}", value.to_hex()).into()
+}
@@ -0,0 +1,15 @@
+//! WebAssembly bindings to the ACVM, allowing circuits to be executed from Javascript.
+//!
+//! Witness maps are exchanged as `Map<number, string>` from witness indices to hex-encoded field elements,
+//! and foreign calls are resolved by an asynchronous Javascript callback.
+#![warn(unused_crate_dependencies)]
+#![warn(unreachable_pub)]
+
+mod backend;
+mod execute;
+mod foreign_call;
+mod js_witness_map;
+
+pub use execute::{execute_brillig, execute_circuit};
+pub use foreign_call::ForeignCallHandler;
+pub use js_witness_map::JsWitnessMap;
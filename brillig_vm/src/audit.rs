@@ -0,0 +1,343 @@
+//! Tracking of the values derived from secrets during the execution of Brillig bytecode.
+//!
+//! Hints computing on secrets can leak them through behavior observable by the host executing the bytecode,
+//! even though the values they compute are correct. A [`SecretAudit`] marks the registers and memory cells
+//! holding secrets, propagates these marks through the opcodes executed by the VM, and records a [`SecretLeak`]
+//! whenever a secret decides which opcodes are executed, where memory is accessed, or what is passed to the host.
+//!
+//! Only explicit data flows are tracked: a value assigned differently on either side of a branch on a secret is
+//! not itself marked, although the branch is reported.
+use std::collections::BTreeSet;
+
+use crate::{
+    ForeignCallOutput, ForeignCallResult, Opcode, RegisterIndex, RegisterOrMemory, Registers,
+};
+
+/// How a secret may be observed from outside of the VM.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LeakKind {
+    /// A jump conditioned on a secret, making the opcodes executed depend on it.
+    Branch,
+    /// A memory access at an address derived from a secret.
+    MemoryAccess,
+    /// A secret passed as the input of a foreign call, revealing it to the host.
+    ForeignCallInput { function: String },
+    /// A foreign call input whose length is derived from a secret.
+    ForeignCallLength { function: String },
+}
+
+impl std::fmt::Display for LeakKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LeakKind::Branch => write!(f, "jump conditioned on a secret"),
+            LeakKind::MemoryAccess => {
+                write!(f, "memory access at an address derived from a secret")
+            }
+            LeakKind::ForeignCallInput { function } => {
+                write!(f, "secret passed to foreign call `{function}`")
+            }
+            LeakKind::ForeignCallLength { function } => {
+                write!(f, "input of foreign call `{function}` has a length derived from a secret")
+            }
+        }
+    }
+}
+
+/// A leak of a secret by the opcode at `program_counter`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SecretLeak {
+    pub program_counter: usize,
+    pub kind: LeakKind,
+}
+
+/// The registers and memory cells of a VM holding values derived from secrets, along with the leaks found so far.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SecretAudit {
+    registers: BTreeSet<usize>,
+    memory: BTreeSet<usize>,
+    /// The leaks found, each reported once per opcode.
+    leaks: Vec<SecretLeak>,
+}
+
+impl SecretAudit {
+    /// Marks the given registers and memory addresses as holding secrets.
+    pub fn new(
+        registers: impl IntoIterator<Item = RegisterIndex>,
+        memory: impl IntoIterator<Item = usize>,
+    ) -> Self {
+        SecretAudit {
+            registers: registers.into_iter().map(RegisterIndex::to_usize).collect(),
+            memory: memory.into_iter().collect(),
+            leaks: Vec::new(),
+        }
+    }
+
+    /// Returns whether `register` holds a value derived from a secret.
+    pub fn is_secret_register(&self, register: RegisterIndex) -> bool {
+        self.registers.contains(&register.to_usize())
+    }
+
+    /// Returns whether the memory cell at `address` holds a value derived from a secret.
+    pub fn is_secret_memory(&self, address: usize) -> bool {
+        self.memory.contains(&address)
+    }
+
+    /// Returns the leaks found so far, in the order they were found.
+    pub fn leaks(&self) -> &[SecretLeak] {
+        &self.leaks
+    }
+
+    /// Removes and returns the leaks found so far.
+    pub fn take_leaks(&mut self) -> Vec<SecretLeak> {
+        std::mem::take(&mut self.leaks)
+    }
+
+    /// Records the leaks of `opcode` and propagates secrets through it, before it is executed on `registers`.
+    ///
+    /// Foreign calls are only audited once their `foreign_call_result` is known, as they are executed.
+    pub(crate) fn observe(
+        &mut self,
+        program_counter: usize,
+        opcode: &Opcode,
+        registers: &Registers,
+        foreign_call_result: Option<&ForeignCallResult>,
+    ) {
+        let address = |register: RegisterIndex| registers.get(register).try_to_usize().ok();
+        match opcode {
+            Opcode::BinaryFieldOp { destination, lhs, rhs, .. }
+            | Opcode::BinaryIntOp { destination, lhs, rhs, .. } => {
+                let secret = self.is_secret_register(*lhs) || self.is_secret_register(*rhs);
+                self.mark_register(*destination, secret);
+            }
+            Opcode::JumpIf { condition, .. } | Opcode::JumpIfNot { condition, .. } => {
+                if self.is_secret_register(*condition) {
+                    self.leak(program_counter, LeakKind::Branch);
+                }
+            }
+            Opcode::Mov { destination, source } => {
+                let secret = self.is_secret_register(*source);
+                self.mark_register(*destination, secret);
+            }
+            Opcode::Const { destination, .. } => self.mark_register(*destination, false),
+            Opcode::Load { destination, source_pointer } => {
+                let secret_pointer = self.check_pointer(program_counter, *source_pointer);
+                let secret = secret_pointer
+                    || address(*source_pointer)
+                        .map_or(false, |source| self.is_secret_memory(source));
+                self.mark_register(*destination, secret);
+            }
+            Opcode::Store { destination_pointer, source } => {
+                let secret_pointer = self.check_pointer(program_counter, *destination_pointer);
+                if let Some(destination) = address(*destination_pointer) {
+                    let secret = secret_pointer || self.is_secret_register(*source);
+                    self.mark_memory(destination..destination + 1, secret);
+                }
+            }
+            Opcode::ForeignCall { function, destinations, inputs } => {
+                let result = match foreign_call_result {
+                    Some(result) => result,
+                    None => return,
+                };
+                let mut secret_inputs = false;
+                for input in inputs {
+                    let secret = match *input {
+                        RegisterOrMemory::RegisterIndex(register) => {
+                            self.is_secret_register(register)
+                        }
+                        RegisterOrMemory::HeapArray(pointer, size) => {
+                            self.check_pointer(program_counter, pointer)
+                                | self.is_secret_slice(address(pointer), Some(size))
+                        }
+                        RegisterOrMemory::HeapVector(pointer, size) => {
+                            let secret_size = self.is_secret_register(size);
+                            if secret_size {
+                                let kind =
+                                    LeakKind::ForeignCallLength { function: function.clone() };
+                                self.leak(program_counter, kind);
+                            }
+                            secret_size
+                                | self.check_pointer(program_counter, pointer)
+                                | self.is_secret_slice(address(pointer), address(size))
+                        }
+                    };
+                    secret_inputs |= secret;
+                }
+                if secret_inputs {
+                    let kind = LeakKind::ForeignCallInput { function: function.clone() };
+                    self.leak(program_counter, kind);
+                }
+
+                // The outputs of a foreign call taking secrets may be derived from them.
+                for (destination, output) in destinations.iter().zip(&result.values) {
+                    let len = match output {
+                        ForeignCallOutput::Single(_) => 1,
+                        ForeignCallOutput::Array(values) => values.len(),
+                    };
+                    match *destination {
+                        RegisterOrMemory::RegisterIndex(register) => {
+                            self.mark_register(register, secret_inputs);
+                        }
+                        RegisterOrMemory::HeapArray(pointer, _) => {
+                            if let Some(pointer) = address(pointer) {
+                                self.mark_memory(pointer..pointer + len, secret_inputs);
+                            }
+                        }
+                        RegisterOrMemory::HeapVector(pointer, size) => {
+                            self.mark_register(size, secret_inputs);
+                            if let Some(pointer) = address(pointer) {
+                                self.mark_memory(pointer..pointer + len, secret_inputs);
+                            }
+                        }
+                    }
+                }
+            }
+            Opcode::FieldIntegerDiv { quotient, remainder, lhs, rhs } => {
+                let secret = self.is_secret_register(*lhs) || self.is_secret_register(*rhs);
+                self.mark_register(*quotient, secret);
+                self.mark_register(*remainder, secret);
+            }
+            Opcode::FieldSqrt { root, is_residue, source } => {
+                let secret = self.is_secret_register(*source);
+                self.mark_register(*root, secret);
+                self.mark_register(*is_residue, secret);
+            }
+            Opcode::ToRadix { source, destination_pointer, limbs, .. } => {
+                let secret_pointer = self.check_pointer(program_counter, *destination_pointer);
+                if let Some(destination) = address(*destination_pointer) {
+                    let secret = secret_pointer || self.is_secret_register(*source);
+                    self.mark_memory(destination..destination.saturating_add(*limbs), secret);
+                }
+            }
+            Opcode::Jump { .. }
+            | Opcode::Call { .. }
+            | Opcode::Return
+            | Opcode::Trap
+            | Opcode::Stop => (),
+        }
+    }
+
+    /// Records a memory access leak if `pointer` holds a secret, returning whether it does.
+    fn check_pointer(&mut self, program_counter: usize, pointer: RegisterIndex) -> bool {
+        let secret = self.is_secret_register(pointer);
+        if secret {
+            self.leak(program_counter, LeakKind::MemoryAccess);
+        }
+        secret
+    }
+
+    /// Returns whether any of the `len` memory cells starting at `pointer` holds a secret, if both are known.
+    fn is_secret_slice(&self, pointer: Option<usize>, len: Option<usize>) -> bool {
+        match (pointer, len) {
+            (Some(pointer), Some(len)) => {
+                self.memory.range(pointer..pointer.saturating_add(len)).next().is_some()
+            }
+            _ => false,
+        }
+    }
+
+    fn mark_register(&mut self, register: RegisterIndex, secret: bool) {
+        if secret {
+            self.registers.insert(register.to_usize());
+        } else {
+            self.registers.remove(&register.to_usize());
+        }
+    }
+
+    fn mark_memory(&mut self, addresses: std::ops::Range<usize>, secret: bool) {
+        for address in addresses {
+            if secret {
+                self.memory.insert(address);
+            } else {
+                self.memory.remove(&address);
+            }
+        }
+    }
+
+    fn leak(&mut self, program_counter: usize, kind: LeakKind) {
+        let leak = SecretLeak { program_counter, kind };
+        if !self.leaks.contains(&leak) {
+            self.leaks.push(leak);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{LeakKind, SecretAudit, SecretLeak};
+    use crate::{
+        BinaryIntOp, ForeignCallResult, Opcode, RegisterIndex, RegisterOrMemory, Registers,
+        VMStatus, Value, VM,
+    };
+
+    #[test]
+    fn reports_branches_and_memory_accesses_on_secrets() {
+        let r = RegisterIndex::from;
+        // Register 0 holds a secret index into the array at memory address 0, register 1 a public value.
+        let bytecode = vec![
+            Opcode::BinaryIntOp {
+                destination: r(2),
+                op: BinaryIntOp::Add,
+                bit_size: 32,
+                lhs: r(0),
+                rhs: r(1),
+            },
+            Opcode::Load { destination: r(3), source_pointer: r(0) },
+            Opcode::JumpIf { condition: r(1), location: 3 },
+            Opcode::JumpIfNot { condition: r(2), location: 4 },
+            Opcode::Const { destination: r(2), value: Value::from(0u128) },
+            Opcode::JumpIf { condition: r(2), location: 6 },
+            Opcode::Stop,
+        ];
+        let registers = Registers::load(vec![Value::from(1u128), Value::from(0u128)]);
+        let memory = vec![Value::from(7u128), Value::from(8u128)];
+        let mut vm = VM::new(registers, memory, bytecode, Vec::new());
+        vm.audit_secrets(SecretAudit::new([r(0)], []));
+        assert_eq!(vm.process_opcodes(), VMStatus::Finished);
+
+        let audit = vm.secret_audit().unwrap();
+        assert!(audit.is_secret_register(r(3)), "loads at secret addresses are secret");
+        assert!(!audit.is_secret_register(r(2)), "constants overwrite secrets");
+        assert_eq!(
+            audit.leaks(),
+            [
+                SecretLeak { program_counter: 1, kind: LeakKind::MemoryAccess },
+                SecretLeak { program_counter: 3, kind: LeakKind::Branch },
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_secrets_passed_to_foreign_calls() {
+        let r = RegisterIndex::from;
+        // Register 0 points to an array of secret length held by register 1.
+        let bytecode = vec![
+            Opcode::ForeignCall {
+                function: "print".into(),
+                destinations: vec![RegisterOrMemory::RegisterIndex(r(2))],
+                inputs: vec![RegisterOrMemory::HeapVector(r(0), r(1))],
+            },
+            Opcode::Stop,
+        ];
+        let registers = Registers::load(vec![Value::from(0u128), Value::from(2u128)]);
+        let memory = vec![Value::from(7u128), Value::from(8u128)];
+        let mut vm = VM::new(registers, memory, bytecode, Vec::new());
+        vm.audit_secrets(SecretAudit::new([r(1)], []));
+        assert!(matches!(vm.process_opcodes(), VMStatus::ForeignCallWait { .. }));
+        assert!(vm.take_secret_leaks().is_empty(), "calls are audited once resolved");
+
+        vm.resolve_foreign_call(ForeignCallResult::from(Value::from(0u128)));
+        assert_eq!(vm.process_opcodes(), VMStatus::Finished);
+        let function = "print".to_string();
+        assert_eq!(
+            vm.take_secret_leaks(),
+            [
+                SecretLeak {
+                    program_counter: 0,
+                    kind: LeakKind::ForeignCallLength { function: function.clone() }
+                },
+                SecretLeak { program_counter: 0, kind: LeakKind::ForeignCallInput { function } },
+            ]
+        );
+        assert!(vm.secret_audit().unwrap().is_secret_register(r(2)));
+    }
+}
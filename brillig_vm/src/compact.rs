@@ -0,0 +1,470 @@
+//! A compact binary encoding of Brillig bytecode.
+//!
+//! Serializing bytecode field by field spells out the name of each opcode and operation, and every
+//! constant in full. This encoding instead writes each opcode as a one byte tag followed by its
+//! operands, where:
+//! - register indices, bit sizes and lengths are written as LEB128 varints,
+//! - labels are written as the zigzag varint of their offset from the opcode, as jumps are mostly local,
+//! - constants are written as their big-endian bytes without leading zeroes, prefixed by their length.
+//!
+//! The bytecode is prefixed by its number of opcodes.
+use acir_field::FieldElement;
+
+use crate::{BinaryFieldOp, BinaryIntOp, Label, Opcode, RegisterIndex, RegisterOrMemory, Value};
+
+const BINARY_FIELD_OPS: [BinaryFieldOp; 6] = [
+    BinaryFieldOp::Add,
+    BinaryFieldOp::Sub,
+    BinaryFieldOp::Mul,
+    BinaryFieldOp::Div,
+    BinaryFieldOp::Equals,
+    BinaryFieldOp::Pow,
+];
+
+const BINARY_INT_OPS: [BinaryIntOp; 16] = [
+    BinaryIntOp::Add,
+    BinaryIntOp::Sub,
+    BinaryIntOp::Mul,
+    BinaryIntOp::SignedDiv,
+    BinaryIntOp::UnsignedDiv,
+    BinaryIntOp::Equals,
+    BinaryIntOp::LessThan,
+    BinaryIntOp::LessThanEquals,
+    BinaryIntOp::And,
+    BinaryIntOp::Or,
+    BinaryIntOp::Xor,
+    BinaryIntOp::Shl,
+    BinaryIntOp::Shr,
+    BinaryIntOp::AddChecked,
+    BinaryIntOp::SubChecked,
+    BinaryIntOp::MulChecked,
+];
+
+/// Bytes which cannot be decoded into Brillig bytecode.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BytecodeDecodeError {
+    /// The bytes end in the middle of the bytecode.
+    UnexpectedEnd,
+    /// A tag does not name any of the kind of item expected, e.g. an opcode.
+    InvalidTag { kind: &'static str, tag: u8 },
+    /// A varint does not fit into a `usize`, or a label is out of range.
+    InvalidNumber,
+    /// The name of a foreign call is not valid UTF-8.
+    InvalidFunctionName,
+    /// Bytes remain once all opcodes have been decoded.
+    TrailingBytes,
+}
+
+impl std::fmt::Display for BytecodeDecodeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BytecodeDecodeError::UnexpectedEnd => write!(f, "unexpected end of bytecode"),
+            BytecodeDecodeError::InvalidTag { kind, tag } => write!(f, "invalid {kind} tag {tag}"),
+            BytecodeDecodeError::InvalidNumber => write!(f, "number out of range"),
+            BytecodeDecodeError::InvalidFunctionName => {
+                write!(f, "foreign call name is not valid UTF-8")
+            }
+            BytecodeDecodeError::TrailingBytes => write!(f, "trailing bytes after bytecode"),
+        }
+    }
+}
+
+impl std::error::Error for BytecodeDecodeError {}
+
+/// Encodes `bytecode` compactly, to be decoded by [`decode_bytecode`].
+pub fn encode_bytecode(bytecode: &[Opcode]) -> Vec<u8> {
+    let mut encoder = Encoder { bytes: Vec::new() };
+    encoder.usize(bytecode.len());
+    for (pc, opcode) in bytecode.iter().enumerate() {
+        encoder.opcode(pc, opcode);
+    }
+    encoder.bytes
+}
+
+/// Decodes bytecode encoded by [`encode_bytecode`].
+pub fn decode_bytecode(bytes: &[u8]) -> Result<Vec<Opcode>, BytecodeDecodeError> {
+    let mut decoder = Decoder { bytes };
+    let len = decoder.usize()?;
+    // Each opcode takes at least a byte, which bounds the allocation for malformed lengths.
+    let mut bytecode = Vec::with_capacity(len.min(bytes.len()));
+    for pc in 0..len {
+        bytecode.push(decoder.opcode(pc)?);
+    }
+    if !decoder.bytes.is_empty() {
+        return Err(BytecodeDecodeError::TrailingBytes);
+    }
+    Ok(bytecode)
+}
+
+struct Encoder {
+    bytes: Vec<u8>,
+}
+
+impl Encoder {
+    fn u64(&mut self, mut value: u64) {
+        loop {
+            let byte = (value & 0x7f) as u8;
+            value >>= 7;
+            if value == 0 {
+                self.bytes.push(byte);
+                return;
+            }
+            self.bytes.push(byte | 0x80);
+        }
+    }
+
+    fn usize(&mut self, value: usize) {
+        self.u64(value as u64);
+    }
+
+    fn register(&mut self, register: RegisterIndex) {
+        self.usize(register.to_usize());
+    }
+
+    fn label(&mut self, pc: usize, label: Label) {
+        let offset = label as i64 - pc as i64;
+        self.u64(((offset << 1) ^ (offset >> 63)) as u64);
+    }
+
+    fn value(&mut self, value: Value) {
+        let bytes = value.to_field().to_be_bytes();
+        let start = bytes.iter().position(|byte| *byte != 0).unwrap_or(bytes.len());
+        self.usize(bytes.len() - start);
+        self.bytes.extend_from_slice(&bytes[start..]);
+    }
+
+    fn register_or_memory(&mut self, value: &RegisterOrMemory) {
+        match value {
+            RegisterOrMemory::RegisterIndex(register) => {
+                self.bytes.push(0);
+                self.register(*register);
+            }
+            RegisterOrMemory::HeapArray(pointer, size) => {
+                self.bytes.push(1);
+                self.register(*pointer);
+                self.usize(*size);
+            }
+            RegisterOrMemory::HeapVector(pointer, size) => {
+                self.bytes.push(2);
+                self.register(*pointer);
+                self.register(*size);
+            }
+        }
+    }
+
+    fn opcode(&mut self, pc: usize, opcode: &Opcode) {
+        match opcode {
+            Opcode::BinaryFieldOp { destination, op, lhs, rhs } => {
+                self.bytes.push(0);
+                let op = BINARY_FIELD_OPS.iter().position(|other| other == op);
+                self.bytes.push(op.expect("all operations are listed") as u8);
+                self.register(*destination);
+                self.register(*lhs);
+                self.register(*rhs);
+            }
+            Opcode::BinaryIntOp { destination, op, bit_size, lhs, rhs } => {
+                self.bytes.push(1);
+                let op = BINARY_INT_OPS.iter().position(|other| other == op);
+                self.bytes.push(op.expect("all operations are listed") as u8);
+                self.u64(u64::from(*bit_size));
+                self.register(*destination);
+                self.register(*lhs);
+                self.register(*rhs);
+            }
+            Opcode::JumpIfNot { condition, location } => {
+                self.bytes.push(2);
+                self.register(*condition);
+                self.label(pc, *location);
+            }
+            Opcode::JumpIf { condition, location } => {
+                self.bytes.push(3);
+                self.register(*condition);
+                self.label(pc, *location);
+            }
+            Opcode::Jump { location } => {
+                self.bytes.push(4);
+                self.label(pc, *location);
+            }
+            Opcode::Call { location } => {
+                self.bytes.push(5);
+                self.label(pc, *location);
+            }
+            Opcode::Const { destination, value } => {
+                self.bytes.push(6);
+                self.register(*destination);
+                self.value(*value);
+            }
+            Opcode::Return => self.bytes.push(7),
+            Opcode::ForeignCall { function, destinations, inputs } => {
+                self.bytes.push(8);
+                self.usize(function.len());
+                self.bytes.extend_from_slice(function.as_bytes());
+                for values in [destinations, inputs] {
+                    self.usize(values.len());
+                    for value in values {
+                        self.register_or_memory(value);
+                    }
+                }
+            }
+            Opcode::Mov { destination, source } => {
+                self.bytes.push(9);
+                self.register(*destination);
+                self.register(*source);
+            }
+            Opcode::Load { destination, source_pointer } => {
+                self.bytes.push(10);
+                self.register(*destination);
+                self.register(*source_pointer);
+            }
+            Opcode::Store { destination_pointer, source } => {
+                self.bytes.push(11);
+                self.register(*destination_pointer);
+                self.register(*source);
+            }
+            Opcode::Trap => self.bytes.push(12),
+            Opcode::Stop => self.bytes.push(13),
+            Opcode::FieldIntegerDiv { quotient, remainder, lhs, rhs } => {
+                self.bytes.push(14);
+                self.register(*quotient);
+                self.register(*remainder);
+                self.register(*lhs);
+                self.register(*rhs);
+            }
+            Opcode::ToRadix { source, destination_pointer, radix, limbs } => {
+                self.bytes.push(15);
+                self.register(*source);
+                self.register(*destination_pointer);
+                self.u64(u64::from(*radix));
+                self.usize(*limbs);
+            }
+            Opcode::FieldSqrt { root, is_residue, source } => {
+                self.bytes.push(16);
+                self.register(*root);
+                self.register(*is_residue);
+                self.register(*source);
+            }
+        }
+    }
+}
+
+struct Decoder<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Decoder<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], BytecodeDecodeError> {
+        if self.bytes.len() < len {
+            return Err(BytecodeDecodeError::UnexpectedEnd);
+        }
+        let (taken, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Ok(taken)
+    }
+
+    fn byte(&mut self) -> Result<u8, BytecodeDecodeError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u64(&mut self) -> Result<u64, BytecodeDecodeError> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = self.byte()?;
+            let bits = u64::from(byte & 0x7f);
+            if shift == 63 && bits > 1 {
+                return Err(BytecodeDecodeError::InvalidNumber);
+            }
+            value |= bits << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(BytecodeDecodeError::InvalidNumber)
+    }
+
+    fn usize(&mut self) -> Result<usize, BytecodeDecodeError> {
+        usize::try_from(self.u64()?).map_err(|_| BytecodeDecodeError::InvalidNumber)
+    }
+
+    fn register(&mut self) -> Result<RegisterIndex, BytecodeDecodeError> {
+        self.usize().map(RegisterIndex::from)
+    }
+
+    fn label(&mut self, pc: usize) -> Result<Label, BytecodeDecodeError> {
+        let zigzag = self.u64()?;
+        let offset = (zigzag >> 1) as i64 ^ -((zigzag & 1) as i64);
+        i64::try_from(pc)
+            .ok()
+            .and_then(|pc| pc.checked_add(offset))
+            .and_then(|label| Label::try_from(label).ok())
+            .ok_or(BytecodeDecodeError::InvalidNumber)
+    }
+
+    fn value(&mut self) -> Result<Value, BytecodeDecodeError> {
+        let len = self.usize()?;
+        Ok(FieldElement::from_be_bytes_reduce(self.take(len)?).into())
+    }
+
+    fn register_or_memory(&mut self) -> Result<RegisterOrMemory, BytecodeDecodeError> {
+        match self.byte()? {
+            0 => Ok(RegisterOrMemory::RegisterIndex(self.register()?)),
+            1 => Ok(RegisterOrMemory::HeapArray(self.register()?, self.usize()?)),
+            2 => Ok(RegisterOrMemory::HeapVector(self.register()?, self.register()?)),
+            tag => Err(BytecodeDecodeError::InvalidTag { kind: "register or memory", tag }),
+        }
+    }
+
+    fn register_or_memory_list(&mut self) -> Result<Vec<RegisterOrMemory>, BytecodeDecodeError> {
+        let len = self.usize()?;
+        (0..len).map(|_| self.register_or_memory()).collect()
+    }
+
+    fn opcode(&mut self, pc: usize) -> Result<Opcode, BytecodeDecodeError> {
+        let opcode = match self.byte()? {
+            0 => {
+                let tag = self.byte()?;
+                let op = *BINARY_FIELD_OPS
+                    .get(tag as usize)
+                    .ok_or(BytecodeDecodeError::InvalidTag { kind: "binary field op", tag })?;
+                Opcode::BinaryFieldOp {
+                    op,
+                    destination: self.register()?,
+                    lhs: self.register()?,
+                    rhs: self.register()?,
+                }
+            }
+            1 => {
+                let tag = self.byte()?;
+                let op = *BINARY_INT_OPS
+                    .get(tag as usize)
+                    .ok_or(BytecodeDecodeError::InvalidTag { kind: "binary int op", tag })?;
+                let bit_size =
+                    u32::try_from(self.u64()?).map_err(|_| BytecodeDecodeError::InvalidNumber)?;
+                Opcode::BinaryIntOp {
+                    op,
+                    bit_size,
+                    destination: self.register()?,
+                    lhs: self.register()?,
+                    rhs: self.register()?,
+                }
+            }
+            2 => Opcode::JumpIfNot { condition: self.register()?, location: self.label(pc)? },
+            3 => Opcode::JumpIf { condition: self.register()?, location: self.label(pc)? },
+            4 => Opcode::Jump { location: self.label(pc)? },
+            5 => Opcode::Call { location: self.label(pc)? },
+            6 => Opcode::Const { destination: self.register()?, value: self.value()? },
+            7 => Opcode::Return,
+            8 => {
+                let len = self.usize()?;
+                let function = String::from_utf8(self.take(len)?.to_vec())
+                    .map_err(|_| BytecodeDecodeError::InvalidFunctionName)?;
+                Opcode::ForeignCall {
+                    function,
+                    destinations: self.register_or_memory_list()?,
+                    inputs: self.register_or_memory_list()?,
+                }
+            }
+            9 => Opcode::Mov { destination: self.register()?, source: self.register()? },
+            10 => Opcode::Load { destination: self.register()?, source_pointer: self.register()? },
+            11 => Opcode::Store { destination_pointer: self.register()?, source: self.register()? },
+            12 => Opcode::Trap,
+            13 => Opcode::Stop,
+            14 => Opcode::FieldIntegerDiv {
+                quotient: self.register()?,
+                remainder: self.register()?,
+                lhs: self.register()?,
+                rhs: self.register()?,
+            },
+            15 => Opcode::ToRadix {
+                source: self.register()?,
+                destination_pointer: self.register()?,
+                radix: u32::try_from(self.u64()?)
+                    .map_err(|_| BytecodeDecodeError::InvalidNumber)?,
+                limbs: self.usize()?,
+            },
+            16 => Opcode::FieldSqrt {
+                root: self.register()?,
+                is_residue: self.register()?,
+                source: self.register()?,
+            },
+            tag => return Err(BytecodeDecodeError::InvalidTag { kind: "opcode", tag }),
+        };
+        Ok(opcode)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use acir_field::FieldElement;
+
+    use super::{decode_bytecode, encode_bytecode, BytecodeDecodeError, BINARY_INT_OPS};
+    use crate::{BinaryFieldOp, Opcode, RegisterIndex, RegisterOrMemory, Value};
+
+    fn every_opcode() -> Vec<Opcode> {
+        let r = RegisterIndex::from;
+        let mut bytecode = vec![
+            Opcode::BinaryFieldOp {
+                destination: r(0),
+                op: BinaryFieldOp::Div,
+                lhs: r(1),
+                rhs: r(2),
+            },
+            Opcode::JumpIfNot { condition: r(3), location: 0 },
+            Opcode::JumpIf { condition: r(300), location: 1000 },
+            Opcode::Jump { location: 3 },
+            Opcode::Call { location: 12 },
+            Opcode::Const { destination: r(4), value: Value::from(0u128) },
+            Opcode::Const { destination: r(5), value: Value::from(-FieldElement::one()) },
+            Opcode::Return,
+            Opcode::ForeignCall {
+                function: "oracle".into(),
+                destinations: vec![
+                    RegisterOrMemory::RegisterIndex(r(6)),
+                    RegisterOrMemory::HeapArray(r(7), 16),
+                ],
+                inputs: vec![RegisterOrMemory::HeapVector(r(8), r(9))],
+            },
+            Opcode::Mov { destination: r(10), source: r(11) },
+            Opcode::Load { destination: r(12), source_pointer: r(13) },
+            Opcode::Store { destination_pointer: r(14), source: r(15) },
+            Opcode::Trap,
+            Opcode::Stop,
+            Opcode::FieldIntegerDiv { quotient: r(16), remainder: r(17), lhs: r(18), rhs: r(19) },
+            Opcode::ToRadix { source: r(20), destination_pointer: r(21), radix: 256, limbs: 32 },
+            Opcode::FieldSqrt { root: r(22), is_residue: r(23), source: r(24) },
+        ];
+        bytecode.extend(BINARY_INT_OPS.iter().map(|&op| Opcode::BinaryIntOp {
+            destination: r(1),
+            op,
+            bit_size: 64,
+            lhs: r(2),
+            rhs: r(3),
+        }));
+        bytecode
+    }
+
+    #[test]
+    fn decodes_encoded_bytecode() {
+        let bytecode = every_opcode();
+        assert_eq!(decode_bytecode(&encode_bytecode(&bytecode)), Ok(bytecode));
+    }
+
+    #[test]
+    fn rejects_malformed_bytecode() {
+        let bytes = encode_bytecode(&every_opcode());
+        assert_eq!(
+            decode_bytecode(&bytes[..bytes.len() - 1]),
+            Err(BytecodeDecodeError::UnexpectedEnd)
+        );
+
+        let mut trailing = bytes.clone();
+        trailing.push(13);
+        assert_eq!(decode_bytecode(&trailing), Err(BytecodeDecodeError::TrailingBytes));
+
+        assert_eq!(
+            decode_bytecode(&[1, 17]),
+            Err(BytecodeDecodeError::InvalidTag { kind: "opcode", tag: 17 })
+        );
+        // A jump to before the start of the bytecode.
+        assert_eq!(decode_bytecode(&[1, 4, 1]), Err(BytecodeDecodeError::InvalidNumber));
+    }
+}
@@ -9,18 +9,32 @@
 //! [acir]: https://crates.io/crates/acir
 //! [acvm]: https://crates.io/crates/acvm
 
+mod audit;
+mod compact;
 mod memory;
 mod opcodes;
 mod registers;
+pub mod routines;
+mod validation;
 mod value;
 
-pub use memory::Memory;
-pub use opcodes::{BinaryFieldOp, BinaryIntOp, RegisterOrMemory};
-pub use opcodes::{Label, Opcode};
+use acir_field::FieldElement;
+pub use audit::{LeakKind, SecretAudit, SecretLeak};
+pub use compact::{decode_bytecode, encode_bytecode, BytecodeDecodeError};
+pub use memory::{Memory, MemoryError, MAX_MEMORY_SIZE};
+use num_bigint::BigUint;
+use num_traits::Zero;
+pub use opcodes::{to_signed, to_unsigned, Label, Opcode};
+pub use opcodes::{BinaryFieldOp, BinaryIntOp, BinaryIntOpError, RegisterOrMemory};
 pub use registers::{RegisterIndex, Registers};
 use serde::{Deserialize, Serialize};
+pub use validation::{validate_bytecode, ValidationError};
 pub use value::Typ;
-pub use value::Value;
+pub use value::{Value, ValueError};
+
+// Only used by the integration tests.
+#[cfg(test)]
+use proptest as _;
 
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub enum VMStatus {
@@ -38,12 +52,58 @@ pub enum VMStatus {
     ForeignCallWait {
         /// Interpreted by simulator context
         function: String,
-        /// Input values
-        /// Each input is a list of values as an input can be either a single value or a memory pointer
-        inputs: Vec<Vec<Value>>,
+        /// Input values, along with where they were read from
+        inputs: Vec<ForeignCallInput>,
+    },
+    /// The opcode preceding the program counter wrote to [watched][VM::watch_register] locations.
+    ///
+    /// The VM resumes from the program counter once it is processed again.
+    Paused {
+        /// The watched locations written to, in the order they were watched
+        watchpoints: Vec<Watchpoint>,
     },
 }
 
+/// A location of the VM whose writes pause its execution with [VMStatus::Paused].
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Watchpoint {
+    Register(RegisterIndex),
+    Memory(usize),
+}
+
+/// Single input of a [foreign call][Opcode::ForeignCall], shaped by the [RegisterOrMemory] it was read from.
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize, Clone)]
+pub enum ForeignCallInput {
+    /// A value read from a register.
+    Single(Value),
+    /// A fixed-size array read from memory, starting at `pointer`.
+    Array { pointer: usize, values: Vec<Value> },
+    /// A vector read from memory, starting at `pointer`, whose length was held by a register.
+    Vector { pointer: usize, values: Vec<Value> },
+}
+
+impl ForeignCallInput {
+    /// Returns the values of the input, regardless of its shape.
+    pub fn values(&self) -> &[Value] {
+        match self {
+            ForeignCallInput::Single(value) => std::slice::from_ref(value),
+            ForeignCallInput::Array { values, .. } | ForeignCallInput::Vector { values, .. } => {
+                values
+            }
+        }
+    }
+
+    /// Returns the memory address the input was read from, or `None` if it was read from a register.
+    pub fn pointer(&self) -> Option<usize> {
+        match self {
+            ForeignCallInput::Single(_) => None,
+            ForeignCallInput::Array { pointer, .. } | ForeignCallInput::Vector { pointer, .. } => {
+                Some(*pointer)
+            }
+        }
+    }
+}
+
 /// Single output of a [foreign call][Opcode::ForeignCall].
 #[derive(Debug, PartialEq, Eq, Serialize, Deserialize, Clone)]
 pub enum ForeignCallOutput {
@@ -91,8 +151,12 @@ pub struct VM {
     status: VMStatus,
     /// Memory of the VM
     memory: Memory,
-    /// Call stack
-    call_stack: Vec<Value>,
+    /// The program counter of each call which has not yet returned, outermost first.
+    call_stack: Vec<usize>,
+    /// Tracks the values derived from secrets, if enabled.
+    secret_audit: Option<Box<SecretAudit>>,
+    /// Locations whose writes pause execution
+    watchpoints: Vec<Watchpoint>,
 }
 
 impl VM {
@@ -112,7 +176,24 @@ impl VM {
             status: VMStatus::InProgress,
             memory: memory.into(),
             call_stack: Vec::new(),
+            secret_audit: None,
+            watchpoints: Vec::new(),
+        }
+    }
+
+    /// Constructs a new VM instance like [`VM::new`], once `bytecode` has been [validated][validate_bytecode]
+    /// and `inputs` checked to fit into the registers of the VM.
+    pub fn try_new(
+        inputs: Registers,
+        memory: Vec<Value>,
+        bytecode: Vec<Opcode>,
+        foreign_call_results: Vec<ForeignCallResult>,
+    ) -> Result<VM, ValidationError> {
+        if inputs.inner.len() > registers::MAX_REGISTERS {
+            return Err(ValidationError::TooManyRegisters { count: inputs.inner.len() });
         }
+        validate_bytecode(&bytecode)?;
+        Ok(Self::new(inputs, memory, bytecode, foreign_call_results))
     }
 
     /// Updates the current status of the VM.
@@ -129,7 +210,11 @@ impl VM {
 
     /// Sets the status of the VM to `ForeignCallWait`.
     /// Indicating that the VM is now waiting for a foreign call to be resolved.
-    fn wait_for_foreign_call(&mut self, function: String, inputs: Vec<Vec<Value>>) -> VMStatus {
+    fn wait_for_foreign_call(
+        &mut self,
+        function: String,
+        inputs: Vec<ForeignCallInput>,
+    ) -> VMStatus {
         self.status(VMStatus::ForeignCallWait { function, inputs })
     }
 
@@ -141,11 +226,20 @@ impl VM {
         self.status.clone()
     }
 
+    /// Provides the result of the foreign call the VM is waiting on, allowing it to resume from that call.
+    pub fn resolve_foreign_call(&mut self, foreign_call_result: ForeignCallResult) {
+        self.foreign_call_results.push(foreign_call_result);
+        self.status(VMStatus::InProgress);
+    }
+
     /// Loop over the bytecode and update the program counter
     pub fn process_opcodes(&mut self) -> VMStatus {
         while !matches!(
             self.process_opcode(),
-            VMStatus::Finished | VMStatus::Failure { .. } | VMStatus::ForeignCallWait { .. }
+            VMStatus::Finished
+                | VMStatus::Failure { .. }
+                | VMStatus::ForeignCallWait { .. }
+                | VMStatus::Paused { .. }
         ) {}
         self.status.clone()
     }
@@ -159,16 +253,149 @@ impl VM {
         self.memory.values()
     }
 
+    /// Prevents the bytecode from writing to the memory addresses of `segment`, such as those of its
+    /// constants or calldata. Writing to them fails the VM.
+    pub fn protect_memory(&mut self, segment: std::ops::Range<usize>) {
+        self.memory.protect(segment);
+    }
+
+    /// Audits the execution of the bytecode for leaks of the secrets marked by `audit`, which are
+    /// returned by [`VM::secret_audit`].
+    pub fn audit_secrets(&mut self, audit: SecretAudit) {
+        self.secret_audit = Some(Box::new(audit));
+    }
+
+    /// Returns the state of the [secret audit][VM::audit_secrets], if enabled.
+    pub fn secret_audit(&self) -> Option<&SecretAudit> {
+        self.secret_audit.as_deref()
+    }
+
+    /// Removes and returns the leaks found by the [secret audit][VM::audit_secrets] so far.
+    pub fn take_secret_leaks(&mut self) -> Vec<SecretLeak> {
+        self.secret_audit.as_mut().map(|audit| audit.take_leaks()).unwrap_or_default()
+    }
+
+    /// Pauses execution with [VMStatus::Paused] after each opcode writing to `register`.
+    pub fn watch_register(&mut self, register: RegisterIndex) {
+        self.watch(Watchpoint::Register(register));
+    }
+
+    /// Pauses execution with [VMStatus::Paused] after each opcode writing to the memory address `address`.
+    pub fn watch_memory(&mut self, address: usize) {
+        self.watch(Watchpoint::Memory(address));
+    }
+
+    fn watch(&mut self, watchpoint: Watchpoint) {
+        if !self.watchpoints.contains(&watchpoint) {
+            self.watchpoints.push(watchpoint);
+        }
+    }
+
     /// Process a single opcode and modify the program counter.
     pub fn process_opcode(&mut self) -> VMStatus {
+        if let VMStatus::Paused { .. } = self.status {
+            // The opcode which paused the VM may have been the last one.
+            if self.program_counter >= self.bytecode.len() {
+                return self.finish();
+            }
+            self.status(VMStatus::InProgress);
+        }
+        let watchpoints = self.watched_writes();
+        let status = self.execute_opcode();
+        if watchpoints.is_empty() || !matches!(status, VMStatus::InProgress | VMStatus::Finished) {
+            return status;
+        }
+        self.status(VMStatus::Paused { watchpoints })
+    }
+
+    /// Returns the watched locations which the opcode at the program counter writes to, should it succeed.
+    fn watched_writes(&self) -> Vec<Watchpoint> {
+        if self.watchpoints.is_empty() {
+            return Vec::new();
+        }
+        let mut registers = Vec::new();
+        let mut memory = Vec::new();
+        match &self.bytecode[self.program_counter] {
+            Opcode::BinaryFieldOp { destination, .. }
+            | Opcode::BinaryIntOp { destination, .. }
+            | Opcode::Mov { destination, .. }
+            | Opcode::Load { destination, .. }
+            | Opcode::Const { destination, .. } => registers.push(*destination),
+            Opcode::Store { destination_pointer, .. } => {
+                if let Ok(address) = self.read_usize(*destination_pointer) {
+                    memory.push(address..address + 1);
+                }
+            }
+            Opcode::FieldIntegerDiv { quotient, remainder, .. } => {
+                registers.extend([*quotient, *remainder]);
+            }
+            Opcode::FieldSqrt { root, is_residue, .. } => registers.extend([*root, *is_residue]),
+            Opcode::ToRadix { destination_pointer, limbs, .. } => {
+                if let Ok(address) = self.read_usize(*destination_pointer) {
+                    memory.push(address..address.saturating_add(*limbs));
+                }
+            }
+            Opcode::ForeignCall { destinations, .. } => {
+                // Nothing is written until the result of the call is known.
+                let values = self
+                    .foreign_call_results
+                    .get(self.foreign_call_counter)
+                    .map_or(&[][..], |result| &result.values[..]);
+                for (destination, output) in destinations.iter().zip(values) {
+                    match destination {
+                        RegisterOrMemory::RegisterIndex(register) => registers.push(*register),
+                        RegisterOrMemory::HeapArray(pointer, size) => {
+                            if let Ok(address) = self.read_usize(*pointer) {
+                                memory.push(address..address + size);
+                            }
+                        }
+                        RegisterOrMemory::HeapVector(pointer, size) => {
+                            registers.push(*size);
+                            if let (Ok(address), ForeignCallOutput::Array(values)) =
+                                (self.read_usize(*pointer), output)
+                            {
+                                memory.push(address..address + values.len());
+                            }
+                        }
+                    }
+                }
+            }
+            Opcode::Jump { .. }
+            | Opcode::JumpIf { .. }
+            | Opcode::JumpIfNot { .. }
+            | Opcode::Call { .. }
+            | Opcode::Return
+            | Opcode::Trap
+            | Opcode::Stop => {}
+        }
+        self.watchpoints
+            .iter()
+            .copied()
+            .filter(|watchpoint| match watchpoint {
+                Watchpoint::Register(register) => registers.contains(register),
+                Watchpoint::Memory(address) => memory.iter().any(|range| range.contains(address)),
+            })
+            .collect()
+    }
+
+    /// Executes the opcode at the program counter and modify the program counter.
+    fn execute_opcode(&mut self) -> VMStatus {
         let opcode = &self.bytecode[self.program_counter];
+        if let Some(audit) = &mut self.secret_audit {
+            let foreign_call_result = self.foreign_call_results.get(self.foreign_call_counter);
+            audit.observe(self.program_counter, opcode, &self.registers, foreign_call_result);
+        }
         match opcode {
             Opcode::BinaryFieldOp { op, lhs, rhs, destination: result } => {
                 self.process_binary_field_op(*op, *lhs, *rhs, *result);
                 self.increment_program_counter()
             }
             Opcode::BinaryIntOp { op, bit_size, lhs, rhs, destination: result } => {
-                self.process_binary_int_op(*op, *bit_size, *lhs, *rhs, *result);
+                if let Err(message) =
+                    self.process_binary_int_op(*op, *bit_size, *lhs, *rhs, *result)
+                {
+                    return self.fail(message);
+                }
                 self.increment_program_counter()
             }
             Opcode::Jump { location: destination } => self.set_program_counter(*destination),
@@ -189,8 +416,8 @@ impl VM {
                 self.increment_program_counter()
             }
             Opcode::Return => {
-                if let Some(register) = self.call_stack.pop() {
-                    self.set_program_counter(register.to_usize())
+                if let Some(call_location) = self.call_stack.pop() {
+                    self.set_program_counter(call_location + 1)
                 } else {
                     self.fail("return opcode hit, but callstack already empty".to_string())
                 }
@@ -206,15 +433,21 @@ impl VM {
                     // but has the necessary results to proceed with execution.
                     let resolved_inputs = inputs
                         .iter()
-                        .map(|input| self.get_register_value_or_memory_values(*input))
-                        .collect::<Vec<_>>();
-                    return self.wait_for_foreign_call(function.clone(), resolved_inputs);
+                        .map(|input| self.get_foreign_call_input(*input))
+                        .collect::<Result<Vec<_>, _>>();
+                    return match resolved_inputs {
+                        Ok(resolved_inputs) => {
+                            self.wait_for_foreign_call(function.clone(), resolved_inputs)
+                        }
+                        Err(message) => self.fail(message),
+                    };
                 }
 
                 let ForeignCallResult { values } =
                     &self.foreign_call_results[self.foreign_call_counter];
 
                 let mut invalid_foreign_call_result = false;
+                let mut write_failure = None;
                 for (destination, output) in destinations.iter().zip(values) {
                     match destination {
                         RegisterOrMemory::RegisterIndex(value_index) => match output {
@@ -233,9 +466,21 @@ impl VM {
                                         break;
                                     }
                                     // Convert the destination pointer to a usize
-                                    let destination = self.registers.get(*pointer_index).to_usize();
+                                    let destination = match self.read_usize(*pointer_index) {
+                                        Ok(destination) => destination,
+                                        Err(message) => {
+                                            write_failure = Some(message);
+                                            break;
+                                        }
+                                    };
                                     // Write to our destination memory
-                                    self.memory.write_slice(destination, values);
+                                    if let Err(err) = self.memory.write_slice(destination, values) {
+                                        write_failure = Some(format!(
+                                            "foreign call `{function}` failed: {err} at opcode {}",
+                                            self.program_counter
+                                        ));
+                                        break;
+                                    }
                                 }
                                 _ => {
                                     unreachable!("Function result size does not match brillig bytecode size")
@@ -248,9 +493,21 @@ impl VM {
                                     // Set our size in the size register
                                     self.registers.set(*size_index, Value::from(values.len()));
                                     // Convert the destination pointer to a usize
-                                    let destination = self.registers.get(*pointer_index).to_usize();
+                                    let destination = match self.read_usize(*pointer_index) {
+                                        Ok(destination) => destination,
+                                        Err(message) => {
+                                            write_failure = Some(message);
+                                            break;
+                                        }
+                                    };
                                     // Write to our destination memory
-                                    self.memory.write_slice(destination, values);
+                                    if let Err(err) = self.memory.write_slice(destination, values) {
+                                        write_failure = Some(format!(
+                                            "foreign call `{function}` failed: {err} at opcode {}",
+                                            self.program_counter
+                                        ));
+                                        break;
+                                    }
                                 }
                                 _ => {
                                     unreachable!("Function result size does not match brillig bytecode size")
@@ -262,10 +519,14 @@ impl VM {
 
                 // These checks must come after resolving the foreign call outputs as `fail` uses a mutable reference
                 if destinations.len() != values.len() {
-                    self.fail(format!("{} output values were provided as a foreign call result for {} destination slots", values.len(), destinations.len()));
+                    return self.fail(format!("{} output values were provided as a foreign call result for {} destination slots", values.len(), destinations.len()));
                 }
                 if invalid_foreign_call_result {
-                    self.fail("Function result size does not match brillig bytecode".to_owned());
+                    return self
+                        .fail("Function result size does not match brillig bytecode".to_owned());
+                }
+                if let Some(message) = write_failure {
+                    return self.fail(message);
                 }
 
                 self.foreign_call_counter += 1;
@@ -280,22 +541,63 @@ impl VM {
             Opcode::Stop => self.finish(),
             Opcode::Load { destination: destination_register, source_pointer } => {
                 // Convert our source_pointer to a usize
-                let source = self.registers.get(*source_pointer);
+                let source = match self.read_usize(*source_pointer) {
+                    Ok(source) => source,
+                    Err(message) => return self.fail(message),
+                };
                 // Use our usize source index to lookup the value in memory
-                let value = &self.memory.read(source.to_usize());
-                self.registers.set(*destination_register, *value);
+                let value = match self.memory.read(source) {
+                    Ok(value) => value,
+                    Err(err) => {
+                        return self.fail(format!("{err} at opcode {}", self.program_counter))
+                    }
+                };
+                self.registers.set(*destination_register, value);
                 self.increment_program_counter()
             }
             Opcode::Store { destination_pointer, source: source_register } => {
                 // Convert our destination_pointer to a usize
-                let destination = self.registers.get(*destination_pointer).to_usize();
+                let destination = match self.read_usize(*destination_pointer) {
+                    Ok(destination) => destination,
+                    Err(message) => return self.fail(message),
+                };
                 // Use our usize destination index to set the value in memory
-                self.memory.write(destination, self.registers.get(*source_register));
+                if let Err(err) =
+                    self.memory.write(destination, self.registers.get(*source_register))
+                {
+                    return self.fail(format!("{err} at opcode {}", self.program_counter));
+                }
+                self.increment_program_counter()
+            }
+            Opcode::FieldIntegerDiv { quotient, remainder, lhs, rhs } => {
+                if let Err(message) =
+                    self.process_field_integer_div(*lhs, *rhs, *quotient, *remainder)
+                {
+                    return self.fail(message);
+                }
+                self.increment_program_counter()
+            }
+            Opcode::ToRadix { source, destination_pointer, radix, limbs } => {
+                if let Err(message) =
+                    self.process_to_radix(*source, *destination_pointer, *radix, *limbs)
+                {
+                    return self.fail(message);
+                }
+                self.increment_program_counter()
+            }
+            Opcode::FieldSqrt { root, is_residue, source } => {
+                let (root_value, is_residue_value) =
+                    match self.registers.get(*source).to_field().sqrt() {
+                        Some(root_value) => (root_value, FieldElement::one()),
+                        None => (FieldElement::zero(), FieldElement::zero()),
+                    };
+                self.registers.set(*root, root_value.into());
+                self.registers.set(*is_residue, is_residue_value.into());
                 self.increment_program_counter()
             }
             Opcode::Call { location } => {
-                // Push a return location
-                self.call_stack.push(Value::from(self.program_counter + 1));
+                // Returning resumes from the opcode following the call.
+                self.call_stack.push(self.program_counter);
                 self.set_program_counter(*location)
             }
             Opcode::Const { destination, value } => {
@@ -306,10 +608,15 @@ impl VM {
     }
 
     /// Returns the current value of the program counter.
-    pub fn program_counter(self) -> usize {
+    pub fn program_counter(&self) -> usize {
         self.program_counter
     }
 
+    /// Returns the program counter of each [call][Opcode::Call] which has not yet returned, outermost first.
+    pub fn call_stack(&self) -> Vec<usize> {
+        self.call_stack.clone()
+    }
+
     /// Increments the program counter by 1.
     fn increment_program_counter(&mut self) -> VMStatus {
         self.set_program_counter(self.program_counter + 1)
@@ -327,21 +634,41 @@ impl VM {
         self.status.clone()
     }
 
-    fn get_register_value_or_memory_values(&self, input: RegisterOrMemory) -> Vec<Value> {
-        match input {
+    /// Reads the value of `register` as a memory address or size, returning the failure message
+    /// if it does not fit into a `usize`.
+    fn read_usize(&self, register: RegisterIndex) -> Result<usize, String> {
+        self.registers.get(register).try_to_usize().map_err(|err| {
+            format!(
+                "{err}, read from register {} at opcode {}",
+                register.to_usize(),
+                self.program_counter
+            )
+        })
+    }
+
+    fn get_foreign_call_input(&self, input: RegisterOrMemory) -> Result<ForeignCallInput, String> {
+        let read_slice = |pointer, size| {
+            self.memory
+                .read_slice(pointer, size)
+                .map(<[Value]>::to_vec)
+                .map_err(|err| format!("{err} at opcode {}", self.program_counter))
+        };
+        Ok(match input {
             RegisterOrMemory::RegisterIndex(value_index) => {
-                vec![self.registers.get(value_index)]
+                ForeignCallInput::Single(self.registers.get(value_index))
             }
             RegisterOrMemory::HeapArray(pointer_index, size) => {
-                let start = self.registers.get(pointer_index);
-                self.memory.read_slice(start.to_usize(), size).to_vec()
+                let pointer = self.read_usize(pointer_index)?;
+                let values = read_slice(pointer, size)?;
+                ForeignCallInput::Array { pointer, values }
             }
             RegisterOrMemory::HeapVector(pointer_index, size_index) => {
-                let start = self.registers.get(pointer_index);
-                let size = self.registers.get(size_index);
-                self.memory.read_slice(start.to_usize(), size.to_usize()).to_vec()
+                let pointer = self.read_usize(pointer_index)?;
+                let size = self.read_usize(size_index)?;
+                let values = read_slice(pointer, size)?;
+                ForeignCallInput::Vector { pointer, values }
             }
-        }
+        })
     }
 
     /// Process a binary operation.
@@ -361,8 +688,69 @@ impl VM {
         self.registers.set(result, result_value.into())
     }
 
+    /// Process the euclidean division of the integer values of `lhs` and `rhs`.
+    /// This method will not modify the program counter.
+    /// Returns the failure message if `rhs` is zero.
+    fn process_field_integer_div(
+        &mut self,
+        lhs: RegisterIndex,
+        rhs: RegisterIndex,
+        quotient: RegisterIndex,
+        remainder: RegisterIndex,
+    ) -> Result<(), String> {
+        let lhs_value = to_big_uint(self.registers.get(lhs));
+        let rhs_value = to_big_uint(self.registers.get(rhs));
+        if rhs_value.is_zero() {
+            return Err(format!(
+                "integer division by zero, read from register {} at opcode {}",
+                rhs.to_usize(),
+                self.program_counter
+            ));
+        }
+
+        self.registers.set(quotient, from_big_uint(&(&lhs_value / &rhs_value)));
+        self.registers.set(remainder, from_big_uint(&(&lhs_value % &rhs_value)));
+        Ok(())
+    }
+
+    /// Process the decomposition of `source` into `limbs` digits of the given `radix`.
+    /// This method will not modify the program counter.
+    /// Returns the failure message if the radix is invalid, the value does not fit into `limbs` digits
+    /// or the digits are written to read-only memory.
+    fn process_to_radix(
+        &mut self,
+        source: RegisterIndex,
+        destination_pointer: RegisterIndex,
+        radix: u32,
+        limbs: usize,
+    ) -> Result<(), String> {
+        if radix < 2 {
+            return Err(format!("invalid radix {radix} at opcode {}", self.program_counter));
+        }
+        let destination = self.read_usize(destination_pointer)?;
+
+        let mut value = to_big_uint(self.registers.get(source));
+        let mut digits = Vec::with_capacity(limbs);
+        for _ in 0..limbs {
+            digits.push(from_big_uint(&(&value % radix)));
+            value /= radix;
+        }
+        if !value.is_zero() {
+            return Err(format!(
+                "value of register {} does not fit into {limbs} digits of radix {radix} at opcode {}",
+                source.to_usize(),
+                self.program_counter
+            ));
+        }
+
+        self.memory
+            .write_slice(destination, &digits)
+            .map_err(|err| format!("{err} at opcode {}", self.program_counter))
+    }
+
     /// Process a binary operation.
     /// This method will not modify the program counter.
+    /// Returns the failure message if the operation cannot be [evaluated][BinaryIntOp::checked_evaluate_int].
     fn process_binary_int_op(
         &mut self,
         op: BinaryIntOp,
@@ -370,15 +758,38 @@ impl VM {
         lhs: RegisterIndex,
         rhs: RegisterIndex,
         result: RegisterIndex,
-    ) {
-        let lhs_value = self.registers.get(lhs);
-        let rhs_value = self.registers.get(rhs);
-
-        let result_value = op.evaluate_int(lhs_value.to_u128(), rhs_value.to_u128(), bit_size);
+    ) -> Result<(), String> {
+        let operand = |register: RegisterIndex| {
+            self.registers
+                .get(register)
+                .try_to_u128()
+                .map_err(|err| format!("{op:?} operand {err} at opcode {}", self.program_counter))
+        };
+        let lhs_value = operand(lhs)?;
+        let rhs_value = operand(rhs)?;
+
+        let result_value =
+            op.checked_evaluate_int(lhs_value, rhs_value, bit_size).map_err(|err| {
+                format!(
+                    "{op:?} of {lhs_value} and {rhs_value} {err} at opcode {}",
+                    self.program_counter
+                )
+            })?;
         self.registers.set(result, result_value.into());
+        Ok(())
     }
 }
 
+/// Returns the integer value of `value`.
+fn to_big_uint(value: Value) -> BigUint {
+    BigUint::from_bytes_be(&value.to_field().to_be_bytes())
+}
+
+/// Returns the field element of the integer `value`, which must be less than the field modulus.
+fn from_big_uint(value: &BigUint) -> Value {
+    FieldElement::from_be_bytes_reduce(&value.to_bytes_be()).into()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -418,6 +829,77 @@ mod tests {
         assert_eq!(output_value, Value::from(3u128))
     }
 
+    #[test]
+    fn checked_int_op_overflow_fails() {
+        let input_registers = Registers::load(vec![Value::from(250u128), Value::from(6u128)]);
+        let opcode = |op| Opcode::BinaryIntOp {
+            op,
+            bit_size: 8,
+            lhs: RegisterIndex::from(0),
+            rhs: RegisterIndex::from(1),
+            destination: RegisterIndex::from(0),
+        };
+
+        let mut vm =
+            VM::new(input_registers.clone(), vec![], vec![opcode(BinaryIntOp::AddChecked)], vec![]);
+        assert_eq!(
+            vm.process_opcodes(),
+            VMStatus::Failure {
+                message: "AddChecked of 250 and 6 overflows 8 bits integers at opcode 0"
+                    .to_string()
+            }
+        );
+
+        // The same addition without overflow checks wraps around.
+        let mut vm = VM::new(input_registers, vec![], vec![opcode(BinaryIntOp::Add)], vec![]);
+        assert_eq!(vm.process_opcodes(), VMStatus::Finished);
+        assert_eq!(vm.registers.get(RegisterIndex::from(0)), Value::from(0u128));
+    }
+
+    #[test]
+    fn int_division_by_zero_fails() {
+        let input_registers = Registers::load(vec![Value::from(7u128), Value::from(0u128)]);
+        for op in [BinaryIntOp::UnsignedDiv, BinaryIntOp::SignedDiv] {
+            let opcode = Opcode::BinaryIntOp {
+                op,
+                bit_size: 8,
+                lhs: RegisterIndex::from(0),
+                rhs: RegisterIndex::from(1),
+                destination: RegisterIndex::from(2),
+            };
+            let mut vm = VM::new(input_registers.clone(), vec![], vec![opcode], vec![]);
+            assert_eq!(
+                vm.process_opcodes(),
+                VMStatus::Failure {
+                    message: format!("{op:?} of 7 and 0 divides by zero at opcode 0")
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn int_op_with_invalid_bit_size_fails() {
+        let input_registers = Registers::load(vec![Value::from(7u128), Value::from(2u128)]);
+        for bit_size in [0, 129] {
+            let opcode = Opcode::BinaryIntOp {
+                op: BinaryIntOp::SignedDiv,
+                bit_size,
+                lhs: RegisterIndex::from(0),
+                rhs: RegisterIndex::from(1),
+                destination: RegisterIndex::from(2),
+            };
+            let mut vm = VM::new(input_registers.clone(), vec![], vec![opcode], vec![]);
+            assert_eq!(
+                vm.process_opcodes(),
+                VMStatus::Failure {
+                    message: format!(
+                        "SignedDiv of 7 and 2 uses unsupported {bit_size} bits integers at opcode 0"
+                    )
+                }
+            );
+        }
+    }
+
     #[test]
     fn jmpif_opcode() {
         let mut registers = vec![];
@@ -538,6 +1020,126 @@ mod tests {
         assert_eq!(source_value, Value::from(1u128));
     }
 
+    #[test]
+    fn field_integer_div_opcode() {
+        let r = RegisterIndex::from;
+        let opcode =
+            Opcode::FieldIntegerDiv { quotient: r(2), remainder: r(3), lhs: r(0), rhs: r(1) };
+        // The integer value of -1 is p - 1, which is even.
+        let minus_one = Value::from(-acir_field::FieldElement::one());
+
+        let input_registers = Registers::load(vec![minus_one, Value::from(2u128)]);
+        let mut vm = VM::new(input_registers, vec![], vec![opcode.clone()], vec![]);
+        assert_eq!(vm.process_opcodes(), VMStatus::Finished);
+        // (p - 1) / 2 is -1/2 in the field.
+        let half = -acir_field::FieldElement::from(2u128).inverse();
+        assert_eq!(vm.registers.get(r(2)), Value::from(half));
+        assert_eq!(vm.registers.get(r(3)), Value::from(0u128));
+
+        let input_registers = Registers::load(vec![Value::from(17u128), Value::from(0u128)]);
+        let mut vm = VM::new(input_registers, vec![], vec![opcode], vec![]);
+        assert_eq!(
+            vm.process_opcodes(),
+            VMStatus::Failure {
+                message: "integer division by zero, read from register 1 at opcode 0".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn field_pow_opcode() {
+        let r = RegisterIndex::from;
+        let opcode = Opcode::BinaryFieldOp {
+            destination: r(2),
+            op: BinaryFieldOp::Pow,
+            lhs: r(0),
+            rhs: r(1),
+        };
+        let input_registers = Registers::load(vec![Value::from(3u128), Value::from(5u128)]);
+
+        let mut vm = VM::new(input_registers, vec![], vec![opcode], vec![]);
+        assert_eq!(vm.process_opcodes(), VMStatus::Finished);
+        assert_eq!(vm.registers.get(r(2)), Value::from(243u128));
+    }
+
+    #[test]
+    fn field_sqrt_opcode() {
+        let r = RegisterIndex::from;
+        let opcode = Opcode::FieldSqrt { root: r(1), is_residue: r(2), source: r(0) };
+
+        let input_registers = Registers::load(vec![Value::from(9u128)]);
+        let mut vm = VM::new(input_registers, vec![], vec![opcode.clone()], vec![]);
+        assert_eq!(vm.process_opcodes(), VMStatus::Finished);
+        let root = vm.registers.get(r(1)).to_field();
+        assert_eq!(root * root, acir_field::FieldElement::from(9u128));
+        assert_eq!(vm.registers.get(r(2)), Value::from(1u128));
+
+        // 5 is a quadratic non-residue.
+        let input_registers = Registers::load(vec![Value::from(5u128)]);
+        let mut vm = VM::new(input_registers, vec![], vec![opcode], vec![]);
+        assert_eq!(vm.process_opcodes(), VMStatus::Finished);
+        assert_eq!(vm.registers.get(r(1)), Value::from(0u128));
+        assert_eq!(vm.registers.get(r(2)), Value::from(0u128));
+    }
+
+    #[test]
+    fn to_radix_opcode() {
+        let r = RegisterIndex::from;
+        let opcode =
+            |limbs| Opcode::ToRadix { source: r(0), destination_pointer: r(1), radix: 10, limbs };
+        let input_registers = Registers::load(vec![Value::from(1234u128), Value::from(1u128)]);
+
+        let mut vm =
+            VM::new(input_registers.clone(), vec![Value::from(0u128); 6], vec![opcode(5)], vec![]);
+        assert_eq!(vm.process_opcodes(), VMStatus::Finished);
+        let digits: Vec<_> = [0u128, 4, 3, 2, 1, 0].into_iter().map(Value::from).collect();
+        assert_eq!(vm.get_memory(), &digits);
+
+        let mut vm = VM::new(input_registers, vec![], vec![opcode(3)], vec![]);
+        assert_eq!(
+            vm.process_opcodes(),
+            VMStatus::Failure {
+                message: "value of register 0 does not fit into 3 digits of radix 10 at opcode 0"
+                    .to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn try_new_validates_bytecode_and_registers() {
+        let load = Opcode::Load {
+            destination: RegisterIndex::from(1),
+            source_pointer: RegisterIndex::from(0),
+        };
+        let registers = Registers::load(vec![Value::from(4u128)]);
+
+        let result =
+            VM::try_new(registers.clone(), vec![], vec![Opcode::Jump { location: 2 }], vec![]);
+        assert_eq!(
+            result.err(),
+            Some(ValidationError::LabelOutOfRange { opcode_index: 0, label: 2 })
+        );
+        let too_many_registers =
+            Registers::load(vec![Value::from(0u128); registers::MAX_REGISTERS + 1]);
+        let result = VM::try_new(too_many_registers, vec![], vec![load.clone()], vec![]);
+        assert_eq!(
+            result.err(),
+            Some(ValidationError::TooManyRegisters { count: registers::MAX_REGISTERS + 1 })
+        );
+
+        // Loading past the end of the memory fails the VM rather than panicking.
+        let mut vm = VM::try_new(registers, vec![Value::from(0u128); 4], vec![load], vec![])
+            .expect("bytecode is valid");
+        assert_eq!(
+            vm.process_opcodes(),
+            VMStatus::Failure {
+                message:
+                    "access to 1 values at address 4 out of bounds of memory of size 4 at opcode 0"
+                        .to_string()
+            }
+        );
+    }
+
     #[test]
     fn cmp_binary_ops() {
         let bit_size = 32;
@@ -849,6 +1451,147 @@ mod tests {
         }
     }
 
+    #[test]
+    fn writes_to_read_only_memory_fail() {
+        let (r_pointer, r_value) = (RegisterIndex::from(0), RegisterIndex::from(1));
+        let calldata = vec![Value::from(1u128), Value::from(2u128)];
+
+        let store_program = vec![
+            Opcode::Const { destination: r_pointer, value: Value::from(1u128) },
+            Opcode::Const { destination: r_value, value: Value::from(3u128) },
+            Opcode::Store { destination_pointer: r_pointer, source: r_value },
+        ];
+        let mut vm = VM::new(empty_registers(), calldata.clone(), store_program, vec![]);
+        vm.protect_memory(0..2);
+        assert_eq!(
+            vm.process_opcodes(),
+            VMStatus::Failure {
+                message: "write to read-only memory at address 1 at opcode 2".to_string()
+            }
+        );
+        assert_eq!(vm.get_memory(), &calldata);
+
+        let foreign_call_program = vec![
+            Opcode::Const { destination: r_pointer, value: Value::from(1u128) },
+            Opcode::ForeignCall {
+                function: "clobber".into(),
+                destinations: vec![RegisterOrMemory::HeapArray(r_pointer, 2)],
+                inputs: vec![],
+            },
+        ];
+        let mut vm = VM::new(
+            empty_registers(),
+            calldata.clone(),
+            foreign_call_program,
+            vec![vec![Value::from(3u128), Value::from(4u128)].into()],
+        );
+        vm.protect_memory(0..2);
+        assert_eq!(
+            vm.process_opcodes(),
+            VMStatus::Failure {
+                message: "foreign call `clobber` failed: write to read-only memory at address 1 at opcode 1"
+                    .to_string()
+            }
+        );
+        assert_eq!(vm.get_memory(), &calldata);
+    }
+
+    #[test]
+    fn writes_past_the_maximum_memory_size_fail() {
+        let (r_pointer, r_value) = (RegisterIndex::from(0), RegisterIndex::from(1));
+        let pointer = usize::MAX - 1;
+
+        let store_program = vec![
+            Opcode::Const { destination: r_pointer, value: Value::from(pointer as u128) },
+            Opcode::Const { destination: r_value, value: Value::from(3u128) },
+            Opcode::Store { destination_pointer: r_pointer, source: r_value },
+        ];
+        let mut vm = VM::new(empty_registers(), vec![], store_program, vec![]);
+        assert_eq!(
+            vm.process_opcodes(),
+            VMStatus::Failure {
+                message: format!(
+                    "write of 1 values at address {pointer} exceeds the maximum memory size of {MAX_MEMORY_SIZE} at opcode 2"
+                )
+            }
+        );
+        assert!(vm.get_memory().is_empty());
+    }
+
+    #[test]
+    fn pointers_which_do_not_fit_into_usize_fail() {
+        let r_pointer = RegisterIndex::from(0);
+        let pointer = Value::from(-acir_field::FieldElement::one());
+
+        let load_program = vec![
+            Opcode::Const { destination: r_pointer, value: pointer },
+            Opcode::Load { destination: r_pointer, source_pointer: r_pointer },
+        ];
+        let mut vm = VM::new(empty_registers(), vec![], load_program, vec![]);
+        assert_eq!(
+            vm.process_opcodes(),
+            VMStatus::Failure {
+                message: format!(
+                    "value {} does not fit into a usize, read from register 0 at opcode 1",
+                    -acir_field::FieldElement::one()
+                )
+            }
+        );
+
+        let foreign_call_program = vec![
+            Opcode::Const { destination: r_pointer, value: pointer },
+            Opcode::ForeignCall {
+                function: "print".into(),
+                destinations: vec![],
+                inputs: vec![RegisterOrMemory::HeapArray(r_pointer, 1)],
+            },
+        ];
+        let mut vm = VM::new(empty_registers(), vec![], foreign_call_program, vec![]);
+        assert!(matches!(vm.process_opcodes(), VMStatus::Failure { .. }));
+    }
+
+    #[test]
+    fn watchpoints_pause_after_writes_to_watched_locations() {
+        let (r_pointer, r_value, r_len) =
+            (RegisterIndex::from(0), RegisterIndex::from(1), RegisterIndex::from(2));
+        let program = vec![
+            Opcode::Const { destination: r_pointer, value: Value::from(2u128) },
+            Opcode::Const { destination: r_value, value: Value::from(7u128) },
+            Opcode::Store { destination_pointer: r_pointer, source: r_value },
+            Opcode::Mov { destination: r_pointer, source: r_value },
+            Opcode::Const { destination: r_pointer, value: Value::from(1u128) },
+            Opcode::ForeignCall {
+                function: "fill".into(),
+                destinations: vec![RegisterOrMemory::HeapVector(r_pointer, r_len)],
+                inputs: vec![],
+            },
+        ];
+        let mut vm = VM::new(
+            empty_registers(),
+            vec![Value::from(0u128); 4],
+            program,
+            vec![vec![Value::from(8u128), Value::from(9u128)].into()],
+        );
+        vm.watch_memory(2);
+        vm.watch_register(r_value);
+
+        let paused =
+            |watchpoints: &[Watchpoint]| VMStatus::Paused { watchpoints: watchpoints.to_vec() };
+        // Pauses after the write, with the program counter on the next opcode.
+        assert_eq!(vm.process_opcodes(), paused(&[Watchpoint::Register(r_value)]));
+        assert_eq!(vm.program_counter(), 2);
+        assert_eq!(vm.get_registers().get(r_value), Value::from(7u128));
+
+        assert_eq!(vm.process_opcodes(), paused(&[Watchpoint::Memory(2)]));
+        assert_eq!(vm.get_memory()[2], Value::from(7u128));
+
+        // Reading a watched register does not pause the VM, while the last opcode writes to memory address 2.
+        assert_eq!(vm.process_opcodes(), paused(&[Watchpoint::Memory(2)]));
+        assert_eq!(vm.program_counter(), 6);
+        assert_eq!(vm.get_memory()[2], Value::from(9u128));
+        assert_eq!(vm.process_opcodes(), VMStatus::Finished);
+    }
+
     #[test]
     fn foreign_call_opcode_register_result() {
         let r_input = RegisterIndex::from(0);
@@ -872,14 +1615,17 @@ mod tests {
             vm.status,
             VMStatus::ForeignCallWait {
                 function: "double".into(),
-                inputs: vec![vec![Value::from(5u128)]]
+                inputs: vec![ForeignCallInput::Single(Value::from(5u128))]
             }
         );
 
-        // Push result we're waiting for
-        vm.foreign_call_results.push(
+        // Provide the result we're waiting for
+        vm.resolve_foreign_call(
             Value::from(10u128).into(), // Result of doubling 5u128
         );
+        assert_eq!(vm.status, VMStatus::InProgress);
+        // The VM resumes from the foreign call rather than restarting
+        assert_eq!(vm.program_counter(), 1);
 
         // Resume VM
         brillig_execute(&mut vm);
@@ -927,7 +1673,7 @@ mod tests {
             vm.status,
             VMStatus::ForeignCallWait {
                 function: "matrix_2x2_transpose".into(),
-                inputs: vec![initial_matrix]
+                inputs: vec![ForeignCallInput::Array { pointer: 0, values: initial_matrix }]
             }
         );
 
@@ -941,7 +1687,7 @@ mod tests {
         assert_eq!(vm.status, VMStatus::Finished);
 
         // Check result in memory
-        let result_values = vm.memory.read_slice(0, 4).to_vec();
+        let result_values = vm.memory.read_slice(0, 4).unwrap().to_vec();
         assert_eq!(result_values, expected_result);
 
         // Ensure the foreign call counter has been incremented
@@ -994,7 +1740,7 @@ mod tests {
             vm.status,
             VMStatus::ForeignCallWait {
                 function: "string_double".into(),
-                inputs: vec![input_string.clone()]
+                inputs: vec![ForeignCallInput::Vector { pointer: 0, values: input_string.clone() }]
             }
         );
 
@@ -1010,7 +1756,8 @@ mod tests {
         assert_eq!(vm.status, VMStatus::Finished);
 
         // Check result in memory
-        let result_values = vm.memory.read_slice(input_string.len(), output_string.len()).to_vec();
+        let result_values =
+            vm.memory.read_slice(input_string.len(), output_string.len()).unwrap().to_vec();
         assert_eq!(result_values, output_string);
 
         // Ensure the foreign call counter has been incremented
@@ -1050,7 +1797,10 @@ mod tests {
             vm.status,
             VMStatus::ForeignCallWait {
                 function: "matrix_2x2_transpose".into(),
-                inputs: vec![initial_matrix.clone()]
+                inputs: vec![ForeignCallInput::Array {
+                    pointer: 0,
+                    values: initial_matrix.clone()
+                }]
             }
         );
 
@@ -1064,11 +1814,11 @@ mod tests {
         assert_eq!(vm.status, VMStatus::Finished);
 
         // Check initial memory still in place
-        let initial_values = vm.memory.read_slice(0, 4).to_vec();
+        let initial_values = vm.memory.read_slice(0, 4).unwrap().to_vec();
         assert_eq!(initial_values, initial_matrix);
 
         // Check result in memory
-        let result_values = vm.memory.read_slice(4, 4).to_vec();
+        let result_values = vm.memory.read_slice(4, 4).unwrap().to_vec();
         assert_eq!(result_values, expected_result);
 
         // Ensure the foreign call counter has been incremented
@@ -1126,7 +1876,10 @@ mod tests {
             vm.status,
             VMStatus::ForeignCallWait {
                 function: "matrix_2x2_transpose".into(),
-                inputs: vec![matrix_a, matrix_b]
+                inputs: vec![
+                    ForeignCallInput::Array { pointer: 0, values: matrix_a },
+                    ForeignCallInput::Array { pointer: 4, values: matrix_b },
+                ]
             }
         );
 
@@ -1140,7 +1893,7 @@ mod tests {
         assert_eq!(vm.status, VMStatus::Finished);
 
         // Check result in memory
-        let result_values = vm.memory.read_slice(0, 4).to_vec();
+        let result_values = vm.memory.read_slice(0, 4).unwrap().to_vec();
         assert_eq!(result_values, expected_result);
 
         // Ensure the foreign call counter has been incremented
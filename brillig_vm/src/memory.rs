@@ -1,41 +1,128 @@
+use std::ops::Range;
+
 use crate::Value;
 
+/// The number of values past which writes fail rather than grow the memory.
+///
+/// Bytecode can write to any address, so the growth is bounded to fail the VM rather than abort the
+/// process when allocating.
+pub const MAX_MEMORY_SIZE: usize = 1 << 24;
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Memory {
     // Memory is a vector of values.
     // We grow the memory when values past the end are set, extending with 0s.
     inner: Vec<Value>,
+    /// Segments of memory which can not be written to, such as constants or calldata.
+    read_only_segments: Vec<Range<usize>>,
 }
 
 impl From<Vec<Value>> for Memory {
     fn from(values: Vec<Value>) -> Self {
-        Memory { inner: values }
+        Memory { inner: values, read_only_segments: Vec::new() }
+    }
+}
+
+/// An invalid access to [`Memory`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MemoryError {
+    /// An access to `len` values from address `ptr`, past the end of the memory of `size` values
+    /// or of the address space.
+    OutOfBounds { ptr: usize, len: usize, size: usize },
+    /// A write to a read-only segment, at the first read-only address written to.
+    ReadOnly { ptr: usize },
+    /// A write of `len` values from address `ptr`, past [`MAX_MEMORY_SIZE`].
+    TooLarge { ptr: usize, len: usize },
+}
+
+impl std::fmt::Display for MemoryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MemoryError::OutOfBounds { ptr, len, size } => write!(
+                f,
+                "access to {len} values at address {ptr} out of bounds of memory of size {size}"
+            ),
+            MemoryError::ReadOnly { ptr } => {
+                write!(f, "write to read-only memory at address {ptr}")
+            }
+            MemoryError::TooLarge { ptr, len } => write!(
+                f,
+                "write of {len} values at address {ptr} exceeds the maximum memory size of {MAX_MEMORY_SIZE}"
+            ),
+        }
     }
 }
 
+impl std::error::Error for MemoryError {}
+
 impl Memory {
-    /// Gets the value at pointer
-    pub fn read(&self, ptr: usize) -> Value {
-        self.inner[ptr]
+    /// Gets the value at pointer `ptr`
+    pub fn read(&self, ptr: usize) -> Result<Value, MemoryError> {
+        self.read_slice(ptr, 1).map(|values| values[0])
     }
 
-    pub fn read_slice(&self, ptr: usize, len: usize) -> &[Value] {
-        &self.inner[ptr..ptr + len]
+    /// Gets the `len` values after pointer `ptr`
+    pub fn read_slice(&self, ptr: usize, len: usize) -> Result<&[Value], MemoryError> {
+        let range = self.range(ptr, len)?;
+        self.inner.get(range).ok_or(MemoryError::OutOfBounds { ptr, len, size: self.inner.len() })
     }
 
     /// Sets the value at pointer `ptr` to `value`
-    pub fn write(&mut self, ptr: usize, value: Value) {
-        self.write_slice(ptr, &[value]);
+    pub fn write(&mut self, ptr: usize, value: Value) -> Result<(), MemoryError> {
+        self.write_slice(ptr, &[value])
     }
 
     /// Sets the values after pointer `ptr` to `values`
-    pub fn write_slice(&mut self, ptr: usize, values: &[Value]) {
+    ///
+    /// Nothing is written if any of these values would be written to a read-only segment, or past
+    /// [`MAX_MEMORY_SIZE`] when the memory needs to grow.
+    pub fn write_slice(&mut self, ptr: usize, values: &[Value]) -> Result<(), MemoryError> {
+        let written = self.range(ptr, values.len())?;
+        if let Some(segment) = self
+            .read_only_segments
+            .iter()
+            .find(|segment| segment.start < written.end && written.start < segment.end)
+        {
+            return Err(MemoryError::ReadOnly { ptr: std::cmp::max(segment.start, written.start) });
+        }
+        if written.end > std::cmp::max(self.inner.len(), MAX_MEMORY_SIZE) {
+            return Err(MemoryError::TooLarge { ptr, len: values.len() });
+        }
+
         // Calculate new memory size
-        let new_size = std::cmp::max(self.inner.len(), ptr + values.len());
+        let new_size = std::cmp::max(self.inner.len(), written.end);
         // Expand memory to new size with default values if needed
         self.inner.resize(new_size, Value::from(0_usize));
 
-        self.inner[ptr..ptr + values.len()].copy_from_slice(values);
+        self.inner[written].copy_from_slice(values);
+        Ok(())
+    }
+
+    /// Returns the range of the `len` addresses from `ptr`, failing if it overflows the address space.
+    fn range(&self, ptr: usize, len: usize) -> Result<Range<usize>, MemoryError> {
+        let end = ptr.checked_add(len).ok_or(MemoryError::OutOfBounds {
+            ptr,
+            len,
+            size: self.inner.len(),
+        })?;
+        Ok(ptr..end)
+    }
+
+    /// Prevents any further write to the addresses of `segment`.
+    pub fn protect(&mut self, segment: Range<usize>) {
+        if !segment.is_empty() {
+            self.read_only_segments.push(segment);
+        }
+    }
+
+    /// Returns whether the address `ptr` is within a read-only segment.
+    pub fn is_read_only(&self, ptr: usize) -> bool {
+        self.read_only_segments.iter().any(|segment| segment.contains(&ptr))
+    }
+
+    /// Returns the read-only segments of the memory
+    pub fn read_only_segments(&self) -> &[Range<usize>] {
+        &self.read_only_segments
     }
 
     /// Returns the values of the memory
@@ -43,3 +130,60 @@ impl Memory {
         &self.inner
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{Memory, MemoryError, MAX_MEMORY_SIZE};
+    use crate::Value;
+
+    #[test]
+    fn read_only_segments_are_not_written() {
+        let mut memory = Memory::from(vec![Value::from(1_usize); 4]);
+        memory.protect(1..3);
+        assert!(!memory.is_read_only(0) && memory.is_read_only(2) && !memory.is_read_only(3));
+
+        assert_eq!(memory.write(2, Value::from(0_usize)), Err(MemoryError::ReadOnly { ptr: 2 }));
+        assert_eq!(
+            memory.write_slice(0, &[Value::from(0_usize); 2]),
+            Err(MemoryError::ReadOnly { ptr: 1 })
+        );
+        assert_eq!(memory.values(), &vec![Value::from(1_usize); 4]);
+
+        memory.write(0, Value::from(0_usize)).unwrap();
+        memory.write_slice(3, &[Value::from(0_usize); 2]).unwrap();
+        let expected = [0_usize, 1, 1, 0, 0].map(Value::from);
+        assert_eq!(memory.read_slice(0, 5), Ok(&expected[..]));
+    }
+
+    #[test]
+    fn out_of_bounds_accesses_fail() {
+        let mut memory = Memory::from(vec![Value::from(1_usize); 4]);
+        assert_eq!(memory.read(3), Ok(Value::from(1_usize)));
+        assert_eq!(memory.read(4), Err(MemoryError::OutOfBounds { ptr: 4, len: 1, size: 4 }));
+        assert_eq!(
+            memory.read_slice(2, 3),
+            Err(MemoryError::OutOfBounds { ptr: 2, len: 3, size: 4 })
+        );
+        assert_eq!(
+            memory.write_slice(usize::MAX, &[Value::from(0_usize); 2]),
+            Err(MemoryError::OutOfBounds { ptr: usize::MAX, len: 2, size: 4 })
+        );
+    }
+
+    #[test]
+    fn writes_past_the_maximum_size_fail() {
+        let mut memory = Memory::from(vec![Value::from(1_usize); 4]);
+        assert_eq!(
+            memory.write(usize::MAX - 1, Value::from(0_usize)),
+            Err(MemoryError::TooLarge { ptr: usize::MAX - 1, len: 1 })
+        );
+        assert_eq!(
+            memory.write_slice(MAX_MEMORY_SIZE - 1, &[Value::from(0_usize); 2]),
+            Err(MemoryError::TooLarge { ptr: MAX_MEMORY_SIZE - 1, len: 2 })
+        );
+        assert_eq!(memory.values().len(), 4);
+
+        memory.write(MAX_MEMORY_SIZE - 1, Value::from(0_usize)).unwrap();
+        assert_eq!(memory.values().len(), MAX_MEMORY_SIZE);
+    }
+}
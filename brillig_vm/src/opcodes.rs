@@ -97,6 +97,32 @@ pub enum Opcode {
         destination_pointer: RegisterIndex,
         source: RegisterIndex,
     },
+    /// Performs the euclidean division of the integer values of the fields in registers `lhs` and `rhs`
+    /// and stores the quotient in the `quotient` register and the remainder in the `remainder` register.
+    /// Fails if the value of `rhs` is zero.
+    FieldIntegerDiv {
+        quotient: RegisterIndex,
+        remainder: RegisterIndex,
+        lhs: RegisterIndex,
+        rhs: RegisterIndex,
+    },
+    /// Decomposes the integer value of the field in register `source` into `limbs` digits of the
+    /// given `radix` in little endian form, stored in memory starting at the address held by `destination_pointer`.
+    /// Fails if the value does not fit into `limbs` digits.
+    ToRadix {
+        source: RegisterIndex,
+        destination_pointer: RegisterIndex,
+        radix: u32,
+        limbs: usize,
+    },
+    /// Computes a square root of the field in register `source`, storing it in the `root` register
+    /// and one in the `is_residue` register.
+    /// If the value of `source` is a quadratic non-residue, which has no square root, stores zero in both registers.
+    FieldSqrt {
+        root: RegisterIndex,
+        is_residue: RegisterIndex,
+        source: RegisterIndex,
+    },
     /// Used to denote execution failure
     Trap,
     /// Stop execution
@@ -118,6 +144,9 @@ impl Opcode {
             Opcode::Mov { .. } => "mov",
             Opcode::Load { .. } => "load",
             Opcode::Store { .. } => "store",
+            Opcode::FieldIntegerDiv { .. } => "field_integer_div",
+            Opcode::ToRadix { .. } => "to_radix",
+            Opcode::FieldSqrt { .. } => "field_sqrt",
             Opcode::Trap => "trap",
             Opcode::Stop => "stop",
         }
@@ -133,6 +162,8 @@ pub enum BinaryFieldOp {
     Div,
     /// (==) equal
     Equals,
+    /// Exponentiation of `lhs` by the integer value of `rhs`
+    Pow,
 }
 
 /// Binary fixed-length integer expressions
@@ -159,6 +190,12 @@ pub enum BinaryIntOp {
     Shl,
     /// (>>) Shift right
     Shr,
+    /// (+) Addition failing the VM, rather than wrapping around, on overflow of unsigned integers
+    AddChecked,
+    /// (-) Subtraction failing the VM, rather than wrapping around, on underflow of unsigned integers
+    SubChecked,
+    /// (*) Multiplication failing the VM, rather than wrapping around, on overflow of unsigned integers
+    MulChecked,
 }
 
 impl BinaryFieldOp {
@@ -171,59 +208,132 @@ impl BinaryFieldOp {
             BinaryFieldOp::Mul => a * b,
             BinaryFieldOp::Div => a / b,
             BinaryFieldOp::Equals => (a == b).into(),
+            BinaryFieldOp::Pow => a.pow(&b),
         }
     }
 }
 
 impl BinaryIntOp {
     /// Evaluate a binary operation on two unsigned integers (u128) with a given bit size and return the result as a u128.
+    ///
+    /// Operations wrap around on overflow, as the constraints on `bit_size` integers do, including the
+    /// checked operations. Use [`BinaryIntOp::checked_evaluate_int`] to detect their overflows.
+    /// Shifting by `bit_size` bits or more returns zero.
+    ///
+    /// # Panics
+    ///
+    /// If the operation is a division and `b` is zero, or a signed division and `bit_size` is not
+    /// between 1 and 128.
     pub fn evaluate_int(&self, a: u128, b: u128, bit_size: u32) -> u128 {
-        let bit_modulo = 1_u128 << bit_size;
+        let mask = bit_mask(bit_size);
+        let shift = u32::try_from(b).ok();
         match self {
-            // Perform addition, subtraction, and multiplication, applying a modulo operation to keep the result within the bit size.
-            BinaryIntOp::Add => a.wrapping_add(b) % bit_modulo,
-            BinaryIntOp::Sub => a.wrapping_sub(b) % bit_modulo,
-            BinaryIntOp::Mul => a.wrapping_mul(b) % bit_modulo,
-            // Perform unsigned division using the modulo operation on a and b.
-            BinaryIntOp::UnsignedDiv => (a % bit_modulo) / (b % bit_modulo),
+            // Perform addition, subtraction, and multiplication, masking the result to keep it within the bit size.
+            BinaryIntOp::Add | BinaryIntOp::AddChecked => a.wrapping_add(b) & mask,
+            BinaryIntOp::Sub | BinaryIntOp::SubChecked => a.wrapping_sub(b) & mask,
+            BinaryIntOp::Mul | BinaryIntOp::MulChecked => a.wrapping_mul(b) & mask,
+            // Perform unsigned division on the masked a and b.
+            BinaryIntOp::UnsignedDiv => (a & mask) / (b & mask),
             // Perform signed division by first converting a and b to signed integers and then back to unsigned after the operation.
             BinaryIntOp::SignedDiv => {
-                to_unsigned(to_signed(a, bit_size) / to_signed(b, bit_size), bit_size)
+                to_unsigned(to_signed(a, bit_size).wrapping_div(to_signed(b, bit_size)), bit_size)
             }
             // Perform a == operation, returning 0 or 1
-            BinaryIntOp::Equals => ((a % bit_modulo) == (b % bit_modulo)).into(),
+            BinaryIntOp::Equals => ((a & mask) == (b & mask)).into(),
             // Perform a < operation, returning 0 or 1
-            BinaryIntOp::LessThan => ((a % bit_modulo) < (b % bit_modulo)).into(),
+            BinaryIntOp::LessThan => ((a & mask) < (b & mask)).into(),
             // Perform a <= operation, returning 0 or 1
-            BinaryIntOp::LessThanEquals => ((a % bit_modulo) <= (b % bit_modulo)).into(),
-            // Perform bitwise AND, OR, XOR, left shift, and right shift operations, applying a modulo operation to keep the result within the bit size.
-            BinaryIntOp::And => (a & b) % bit_modulo,
-            BinaryIntOp::Or => (a | b) % bit_modulo,
-            BinaryIntOp::Xor => (a ^ b) % bit_modulo,
-            BinaryIntOp::Shl => (a << b) % bit_modulo,
-            BinaryIntOp::Shr => (a >> b) % bit_modulo,
+            BinaryIntOp::LessThanEquals => ((a & mask) <= (b & mask)).into(),
+            // Perform bitwise AND, OR, XOR, left shift, and right shift operations, masking the result to keep it within the bit size.
+            BinaryIntOp::And => (a & b) & mask,
+            BinaryIntOp::Or => (a | b) & mask,
+            BinaryIntOp::Xor => (a ^ b) & mask,
+            BinaryIntOp::Shl => shift.and_then(|shift| a.checked_shl(shift)).unwrap_or(0) & mask,
+            BinaryIntOp::Shr => shift.and_then(|shift| (a & mask).checked_shr(shift)).unwrap_or(0),
+        }
+    }
+
+    /// Evaluate a binary operation like [`BinaryIntOp::evaluate_int`], but fail rather than panic or
+    /// wrap around: if a checked operation overflows the unsigned `bit_size` bits integers, if a
+    /// division is by zero, or if `bit_size` is not between 1 and 128.
+    pub fn checked_evaluate_int(
+        &self,
+        a: u128,
+        b: u128,
+        bit_size: u32,
+    ) -> Result<u128, BinaryIntOpError> {
+        if !(1..=u128::BITS).contains(&bit_size) {
+            return Err(BinaryIntOpError::InvalidBitSize(bit_size));
         }
+        let mask = bit_mask(bit_size);
+        let (masked_a, masked_b) = (a & mask, b & mask);
+        let result = match self {
+            BinaryIntOp::AddChecked => masked_a.checked_add(masked_b),
+            BinaryIntOp::SubChecked => masked_a.checked_sub(masked_b),
+            BinaryIntOp::MulChecked => masked_a.checked_mul(masked_b),
+            BinaryIntOp::UnsignedDiv | BinaryIntOp::SignedDiv if masked_b == 0 => {
+                return Err(BinaryIntOpError::DivisionByZero)
+            }
+            _ => return Ok(self.evaluate_int(a, b, bit_size)),
+        };
+        result.filter(|&result| result <= mask).ok_or(BinaryIntOpError::Overflow { bit_size })
     }
 }
 
-fn to_signed(a: u128, bit_size: u32) -> i128 {
-    assert!(bit_size < 128);
-    let pow_2 = 2_u128.pow(bit_size - 1);
-    if a < pow_2 {
-        a as i128
-    } else {
-        (a.wrapping_sub(2 * pow_2)) as i128
+/// The reason a [`BinaryIntOp`] cannot be [evaluated][BinaryIntOp::checked_evaluate_int].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BinaryIntOpError {
+    /// A checked operation overflows the `bit_size` bits integers.
+    Overflow { bit_size: u32 },
+    /// A division is by zero.
+    DivisionByZero,
+    /// The bit size is not between 1 and 128.
+    InvalidBitSize(u32),
+}
+
+impl std::fmt::Display for BinaryIntOpError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BinaryIntOpError::Overflow { bit_size } => {
+                write!(f, "overflows {bit_size} bits integers")
+            }
+            BinaryIntOpError::DivisionByZero => write!(f, "divides by zero"),
+            BinaryIntOpError::InvalidBitSize(bit_size) => {
+                write!(f, "uses unsupported {bit_size} bits integers")
+            }
+        }
     }
 }
 
-fn to_unsigned(a: i128, bit_size: u32) -> u128 {
-    if a >= 0 {
-        a as u128
+impl std::error::Error for BinaryIntOpError {}
+
+/// Returns the mask keeping the `bit_size` least significant bits of an integer.
+fn bit_mask(bit_size: u32) -> u128 {
+    if bit_size >= u128::BITS {
+        u128::MAX
     } else {
-        (a + 2_i128.pow(bit_size)) as u128
+        (1 << bit_size) - 1
     }
 }
 
+/// Interprets the `bit_size` least significant bits of `a` as a two's complement signed integer.
+///
+/// # Panics
+///
+/// If `bit_size` is not between 1 and 128.
+pub fn to_signed(a: u128, bit_size: u32) -> i128 {
+    assert!((1..=u128::BITS).contains(&bit_size), "invalid bit size {bit_size}");
+    // Moves the sign bit to the most significant bit, so that shifting back extends it.
+    let unused_bits = u128::BITS - bit_size;
+    ((a << unused_bits) as i128) >> unused_bits
+}
+
+/// Returns the `bit_size` bits two's complement representation of `a`, wrapping around if `a`
+/// does not fit in `bit_size` bits.
+pub fn to_unsigned(a: i128, bit_size: u32) -> u128 {
+    (a as u128) & bit_mask(bit_size)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -313,6 +423,19 @@ mod tests {
         evaluate_int_ops(test_ops, BinaryIntOp::UnsignedDiv, bit_size);
     }
 
+    #[test]
+    fn field_pow_test() {
+        let (two, three) = (FieldElement::from(2_u128), FieldElement::from(3_u128));
+        assert_eq!(BinaryFieldOp::Pow.evaluate_field(two, three), FieldElement::from(8_u128));
+        assert_eq!(
+            BinaryFieldOp::Pow.evaluate_field(three, FieldElement::zero()),
+            FieldElement::one()
+        );
+        // The inverse of `a` is `a^(p - 2)`.
+        let p_minus_two = FieldElement::zero() - two;
+        assert_eq!(BinaryFieldOp::Pow.evaluate_field(three, p_minus_two), three.inverse());
+    }
+
     #[test]
     fn to_signed_roundtrip() {
         let bit_size = 32;
@@ -332,4 +455,40 @@ mod tests {
 
         evaluate_int_ops(test_ops, BinaryIntOp::SignedDiv, bit_size);
     }
+
+    #[test]
+    fn checked_ops_test() {
+        let bit_size = 8;
+
+        let overflow = Err(BinaryIntOpError::Overflow { bit_size });
+        assert_eq!(BinaryIntOp::AddChecked.checked_evaluate_int(200, 55, bit_size), Ok(255));
+        assert_eq!(BinaryIntOp::AddChecked.checked_evaluate_int(200, 56, bit_size), overflow);
+        assert_eq!(BinaryIntOp::SubChecked.checked_evaluate_int(3, 3, bit_size), Ok(0));
+        assert_eq!(BinaryIntOp::SubChecked.checked_evaluate_int(3, 4, bit_size), overflow);
+        assert_eq!(BinaryIntOp::MulChecked.checked_evaluate_int(15, 17, bit_size), Ok(255));
+        assert_eq!(BinaryIntOp::MulChecked.checked_evaluate_int(16, 16, bit_size), overflow);
+        assert_eq!(
+            BinaryIntOp::MulChecked.checked_evaluate_int(u128::MAX, 2, 128),
+            Err(BinaryIntOpError::Overflow { bit_size: 128 })
+        );
+
+        // Unchecked operations and the checked ones evaluated without checks wrap around.
+        assert_eq!(BinaryIntOp::Add.checked_evaluate_int(200, 56, bit_size), Ok(0));
+        assert_eq!(BinaryIntOp::AddChecked.evaluate_int(200, 56, bit_size), 0);
+    }
+
+    #[test]
+    fn checked_ops_reject_division_by_zero_and_invalid_bit_sizes() {
+        for op in [BinaryIntOp::UnsignedDiv, BinaryIntOp::SignedDiv] {
+            assert_eq!(op.checked_evaluate_int(7, 0, 8), Err(BinaryIntOpError::DivisionByZero));
+            // The divisor is zero once truncated to the bit size.
+            assert_eq!(op.checked_evaluate_int(7, 256, 8), Err(BinaryIntOpError::DivisionByZero));
+        }
+        for bit_size in [0, 129] {
+            assert_eq!(
+                BinaryIntOp::SignedDiv.checked_evaluate_int(7, 2, bit_size),
+                Err(BinaryIntOpError::InvalidBitSize(bit_size))
+            );
+        }
+    }
 }
@@ -0,0 +1,357 @@
+//! Pre-assembled Brillig subroutines which compilers can link into their bytecode rather than
+//! generating equivalent opcode sequences for each program.
+//!
+//! Every routine follows the same calling convention. It is linked through [`Routine::link`] at a
+//! `location` of the bytecode, using the registers from a `register_base` onwards, both chosen by the
+//! caller. Register `i` of the documentation of a routine is then register `register_base + i`. The
+//! caller places the arguments in the first registers, [calls][Opcode::Call] the `location` and finds
+//! the result, if any, in register 0 once the routine [returns][Opcode::Return]. All of the
+//! [`Routine::registers`] registers of a routine may be overwritten.
+//!
+//! Pointers and lengths are read as 64 bits integers.
+
+use crate::{BinaryFieldOp, BinaryIntOp, Label, Opcode, RegisterIndex, RegisterOrMemory, Value};
+
+/// A pre-assembled Brillig subroutine, see the [module documentation][self] for its calling convention.
+#[derive(Debug, Clone, Copy)]
+pub struct Routine {
+    /// The name of the routine.
+    pub name: &'static str,
+    /// The number of registers used by the routine, from the register base it is linked with.
+    pub registers: usize,
+    assemble: fn() -> Vec<Opcode>,
+}
+
+impl Routine {
+    /// Returns the bytecode of the routine, linked at location 0 with register base 0.
+    pub fn bytecode(&self) -> Vec<Opcode> {
+        (self.assemble)()
+    }
+
+    /// Returns the bytecode of the routine to be placed at `location` of the bytecode, using the
+    /// registers from `register_base` onwards.
+    pub fn link(&self, register_base: usize, location: Label) -> Vec<Opcode> {
+        self.bytecode()
+            .into_iter()
+            .map(|opcode| relocate(opcode, register_base, location))
+            .collect()
+    }
+}
+
+/// Copies `r2` values from the memory at address `r0` to the memory at address `r1`.
+///
+/// Values are copied in increasing order of addresses, so the source and destination may only
+/// overlap if the destination comes first.
+pub const MEMCPY: Routine = Routine { name: "memcpy", registers: 6, assemble: memcpy };
+
+/// Compares the `r2` values in memory at addresses `r0` and `r1`, returning one if they are all equal
+/// and zero otherwise.
+pub const MEMCMP: Routine = Routine { name: "memcmp", registers: 7, assemble: memcmp };
+
+/// Adds the big integers of `r3` little endian limbs of 64 bits in memory at addresses `r0` and `r1`,
+/// writing the limbs of their sum to the memory at address `r2` and returning the carry out of the last limb.
+pub const BIGINT_ADD: Routine = Routine { name: "bigint_add", registers: 11, assemble: bigint_add };
+
+/// Decomposes the integer value of `r0` into `r3` digits of radix `r2` in little endian form, written to
+/// the memory at address `r1`, returning the value which remains once these digits are removed.
+///
+/// The value fits into the digits if and only if the returned value is zero. Unlike [`Opcode::ToRadix`],
+/// the radix and number of digits are only known at runtime.
+pub const TO_RADIX: Routine = Routine { name: "to_radix", registers: 7, assemble: to_radix };
+
+/// Sorts the `r1` values in memory at address `r0` in increasing order, in place, comparing them as
+/// integers of at most 128 bits.
+pub const SORT: Routine = Routine { name: "sort", registers: 11, assemble: sort };
+
+/// All of the routines of the library.
+pub const ROUTINES: [Routine; 5] = [MEMCPY, MEMCMP, BIGINT_ADD, TO_RADIX, SORT];
+
+fn r(index: usize) -> RegisterIndex {
+    RegisterIndex::from(index)
+}
+
+fn constant(destination: usize, value: impl Into<Value>) -> Opcode {
+    Opcode::Const { destination: r(destination), value: value.into() }
+}
+
+fn int_op(op: BinaryIntOp, bit_size: u32, destination: usize, lhs: usize, rhs: usize) -> Opcode {
+    Opcode::BinaryIntOp { destination: r(destination), op, bit_size, lhs: r(lhs), rhs: r(rhs) }
+}
+
+/// Pointer arithmetic on 64 bits integers.
+fn ptr_op(op: BinaryIntOp, destination: usize, lhs: usize, rhs: usize) -> Opcode {
+    int_op(op, 64, destination, lhs, rhs)
+}
+
+fn memcpy() -> Vec<Opcode> {
+    vec![
+        constant(3, 1u128),
+        constant(4, 0u128),
+        // 2: loop while the length is not zero
+        ptr_op(BinaryIntOp::Equals, 5, 2, 4),
+        Opcode::JumpIf { condition: r(5), location: 10 },
+        Opcode::Load { destination: r(5), source_pointer: r(0) },
+        Opcode::Store { destination_pointer: r(1), source: r(5) },
+        ptr_op(BinaryIntOp::Add, 0, 0, 3),
+        ptr_op(BinaryIntOp::Add, 1, 1, 3),
+        ptr_op(BinaryIntOp::Sub, 2, 2, 3),
+        Opcode::Jump { location: 2 },
+        // 10:
+        Opcode::Return,
+    ]
+}
+
+fn memcmp() -> Vec<Opcode> {
+    vec![
+        constant(3, 1u128),
+        constant(4, 0u128),
+        // 2: loop while the length is not zero
+        ptr_op(BinaryIntOp::Equals, 5, 2, 4),
+        Opcode::JumpIf { condition: r(5), location: 12 },
+        Opcode::Load { destination: r(5), source_pointer: r(0) },
+        Opcode::Load { destination: r(6), source_pointer: r(1) },
+        Opcode::BinaryFieldOp {
+            destination: r(5),
+            op: BinaryFieldOp::Equals,
+            lhs: r(5),
+            rhs: r(6),
+        },
+        Opcode::JumpIfNot { condition: r(5), location: 14 },
+        ptr_op(BinaryIntOp::Add, 0, 0, 3),
+        ptr_op(BinaryIntOp::Add, 1, 1, 3),
+        ptr_op(BinaryIntOp::Sub, 2, 2, 3),
+        Opcode::Jump { location: 2 },
+        // 12: all values are equal
+        Opcode::Mov { destination: r(0), source: r(3) },
+        Opcode::Return,
+        // 14: a value differs
+        Opcode::Mov { destination: r(0), source: r(4) },
+        Opcode::Return,
+    ]
+}
+
+fn bigint_add() -> Vec<Opcode> {
+    vec![
+        constant(4, 1u128),
+        constant(5, 0u128),
+        // The carry
+        constant(6, 0u128),
+        constant(7, u128::from(u64::MAX)),
+        constant(8, 64u128),
+        // 5: loop while there are limbs left
+        ptr_op(BinaryIntOp::Equals, 9, 3, 5),
+        Opcode::JumpIf { condition: r(9), location: 19 },
+        Opcode::Load { destination: r(9), source_pointer: r(0) },
+        Opcode::Load { destination: r(10), source_pointer: r(1) },
+        int_op(BinaryIntOp::Add, 128, 9, 9, 10),
+        int_op(BinaryIntOp::Add, 128, 9, 9, 6),
+        int_op(BinaryIntOp::Shr, 128, 6, 9, 8),
+        int_op(BinaryIntOp::And, 128, 9, 9, 7),
+        Opcode::Store { destination_pointer: r(2), source: r(9) },
+        ptr_op(BinaryIntOp::Add, 0, 0, 4),
+        ptr_op(BinaryIntOp::Add, 1, 1, 4),
+        ptr_op(BinaryIntOp::Add, 2, 2, 4),
+        ptr_op(BinaryIntOp::Sub, 3, 3, 4),
+        Opcode::Jump { location: 5 },
+        // 19:
+        Opcode::Mov { destination: r(0), source: r(6) },
+        Opcode::Return,
+    ]
+}
+
+fn to_radix() -> Vec<Opcode> {
+    vec![
+        constant(4, 1u128),
+        constant(5, 0u128),
+        // 2: loop while there are digits left
+        ptr_op(BinaryIntOp::Equals, 6, 3, 5),
+        Opcode::JumpIf { condition: r(6), location: 9 },
+        Opcode::FieldIntegerDiv { quotient: r(0), remainder: r(6), lhs: r(0), rhs: r(2) },
+        Opcode::Store { destination_pointer: r(1), source: r(6) },
+        ptr_op(BinaryIntOp::Add, 1, 1, 4),
+        ptr_op(BinaryIntOp::Sub, 3, 3, 4),
+        Opcode::Jump { location: 2 },
+        // 9:
+        Opcode::Return,
+    ]
+}
+
+fn sort() -> Vec<Opcode> {
+    // An insertion sort, moving each value `i` down to its place among the sorted values before it.
+    vec![
+        constant(2, 1u128),
+        constant(10, 0u128),
+        Opcode::Mov { destination: r(3), source: r(2) },
+        // 3: loop while `i < len`
+        ptr_op(BinaryIntOp::LessThan, 9, 3, 1),
+        Opcode::JumpIfNot { condition: r(9), location: 20 },
+        Opcode::Mov { destination: r(4), source: r(3) },
+        // 6: loop while `j > 0` and the value `j` is less than the value `j - 1`, swapping them
+        ptr_op(BinaryIntOp::Equals, 9, 4, 10),
+        Opcode::JumpIf { condition: r(9), location: 18 },
+        ptr_op(BinaryIntOp::Add, 5, 0, 4),
+        ptr_op(BinaryIntOp::Sub, 6, 5, 2),
+        Opcode::Load { destination: r(7), source_pointer: r(6) },
+        Opcode::Load { destination: r(8), source_pointer: r(5) },
+        int_op(BinaryIntOp::LessThan, 128, 9, 8, 7),
+        Opcode::JumpIfNot { condition: r(9), location: 18 },
+        Opcode::Store { destination_pointer: r(6), source: r(8) },
+        Opcode::Store { destination_pointer: r(5), source: r(7) },
+        ptr_op(BinaryIntOp::Sub, 4, 4, 2),
+        Opcode::Jump { location: 6 },
+        // 18:
+        ptr_op(BinaryIntOp::Add, 3, 3, 2),
+        Opcode::Jump { location: 3 },
+        // 20:
+        Opcode::Return,
+    ]
+}
+
+/// Shifts the registers of `opcode` by `register_base` and its labels by `location`.
+fn relocate(opcode: Opcode, register_base: usize, location: Label) -> Opcode {
+    let r = |register: RegisterIndex| RegisterIndex::from(register.to_usize() + register_base);
+    let r_or_m = |value: RegisterOrMemory| match value {
+        RegisterOrMemory::RegisterIndex(register) => RegisterOrMemory::RegisterIndex(r(register)),
+        RegisterOrMemory::HeapArray(pointer, size) => RegisterOrMemory::HeapArray(r(pointer), size),
+        RegisterOrMemory::HeapVector(pointer, size) => {
+            RegisterOrMemory::HeapVector(r(pointer), r(size))
+        }
+    };
+    match opcode {
+        Opcode::BinaryFieldOp { destination, op, lhs, rhs } => {
+            Opcode::BinaryFieldOp { destination: r(destination), op, lhs: r(lhs), rhs: r(rhs) }
+        }
+        Opcode::BinaryIntOp { destination, op, bit_size, lhs, rhs } => Opcode::BinaryIntOp {
+            destination: r(destination),
+            op,
+            bit_size,
+            lhs: r(lhs),
+            rhs: r(rhs),
+        },
+        Opcode::JumpIfNot { condition, location: label } => {
+            Opcode::JumpIfNot { condition: r(condition), location: label + location }
+        }
+        Opcode::JumpIf { condition, location: label } => {
+            Opcode::JumpIf { condition: r(condition), location: label + location }
+        }
+        Opcode::Jump { location: label } => Opcode::Jump { location: label + location },
+        Opcode::Call { location: label } => Opcode::Call { location: label + location },
+        Opcode::Const { destination, value } => {
+            Opcode::Const { destination: r(destination), value }
+        }
+        Opcode::ForeignCall { function, destinations, inputs } => Opcode::ForeignCall {
+            function,
+            destinations: destinations.into_iter().map(r_or_m).collect(),
+            inputs: inputs.into_iter().map(r_or_m).collect(),
+        },
+        Opcode::Mov { destination, source } => {
+            Opcode::Mov { destination: r(destination), source: r(source) }
+        }
+        Opcode::Load { destination, source_pointer } => {
+            Opcode::Load { destination: r(destination), source_pointer: r(source_pointer) }
+        }
+        Opcode::Store { destination_pointer, source } => {
+            Opcode::Store { destination_pointer: r(destination_pointer), source: r(source) }
+        }
+        Opcode::FieldIntegerDiv { quotient, remainder, lhs, rhs } => Opcode::FieldIntegerDiv {
+            quotient: r(quotient),
+            remainder: r(remainder),
+            lhs: r(lhs),
+            rhs: r(rhs),
+        },
+        Opcode::ToRadix { source, destination_pointer, radix, limbs } => Opcode::ToRadix {
+            source: r(source),
+            destination_pointer: r(destination_pointer),
+            radix,
+            limbs,
+        },
+        Opcode::FieldSqrt { root, is_residue, source } => {
+            Opcode::FieldSqrt { root: r(root), is_residue: r(is_residue), source: r(source) }
+        }
+        Opcode::Return | Opcode::Trap | Opcode::Stop => opcode,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Routine, BIGINT_ADD, MEMCMP, MEMCPY, ROUTINES, SORT, TO_RADIX};
+    use crate::{validate_bytecode, Opcode, RegisterIndex, Registers, VMStatus, Value, VM};
+
+    /// The registers below the register base of the routines, which they must not overwrite.
+    const REGISTER_BASE: usize = 2;
+
+    /// Calls `routine` linked after a call to it with `arguments`, returning its result and the final memory.
+    fn call(routine: Routine, arguments: &[u128], memory: &[u128]) -> (Value, Vec<Value>) {
+        let mut bytecode = vec![Opcode::Call { location: 2 }, Opcode::Stop];
+        bytecode.extend(routine.link(REGISTER_BASE, 2));
+        assert_eq!(validate_bytecode(&bytecode), Ok(()));
+
+        let mut registers = vec![Value::from(7u128); REGISTER_BASE];
+        registers.extend(arguments.iter().map(|&argument| Value::from(argument)));
+        let memory = memory.iter().map(|&value| Value::from(value)).collect();
+        let mut vm = VM::new(Registers::load(registers), memory, bytecode, vec![]);
+        assert_eq!(vm.process_opcodes(), VMStatus::Finished, "{} failed", routine.name);
+
+        let registers = vm.get_registers();
+        for register in 0..REGISTER_BASE {
+            assert_eq!(registers.get(RegisterIndex::from(register)), Value::from(7u128));
+        }
+        for register in REGISTER_BASE + routine.registers..REGISTER_BASE + 32 {
+            assert_eq!(registers.get(RegisterIndex::from(register)), Value::from(0u128));
+        }
+        (registers.get(RegisterIndex::from(REGISTER_BASE)), vm.get_memory().clone())
+    }
+
+    fn values(values: &[u128]) -> Vec<Value> {
+        values.iter().map(|&value| Value::from(value)).collect()
+    }
+
+    #[test]
+    fn memcpy_copies_values() {
+        let (_, memory) = call(MEMCPY, &[0, 3, 3], &[1, 2, 3, 0, 0, 0]);
+        assert_eq!(memory, values(&[1, 2, 3, 1, 2, 3]));
+    }
+
+    #[test]
+    fn memcmp_compares_values() {
+        let (equal, _) = call(MEMCMP, &[0, 3, 3], &[1, 2, 3, 1, 2, 3]);
+        assert_eq!(equal, Value::from(1u128));
+        let (equal, _) = call(MEMCMP, &[0, 3, 3], &[1, 2, 3, 1, 5, 3]);
+        assert_eq!(equal, Value::from(0u128));
+        let (equal, _) = call(MEMCMP, &[0, 3, 0], &[]);
+        assert_eq!(equal, Value::from(1u128));
+    }
+
+    #[test]
+    fn bigint_add_propagates_carries() {
+        let max = u128::from(u64::MAX);
+        let (carry, memory) = call(BIGINT_ADD, &[0, 2, 4, 2], &[max, 5, 1, max, 0, 0]);
+        assert_eq!(memory[4..], values(&[0, 5]));
+        assert_eq!(carry, Value::from(1u128));
+    }
+
+    #[test]
+    fn to_radix_decomposes_values() {
+        let (remainder, memory) = call(TO_RADIX, &[1234, 0, 10, 5], &[]);
+        assert_eq!(memory, values(&[4, 3, 2, 1, 0]));
+        assert_eq!(remainder, Value::from(0u128));
+
+        let (remainder, _) = call(TO_RADIX, &[1234, 0, 10, 3], &[]);
+        assert_eq!(remainder, Value::from(1u128));
+    }
+
+    #[test]
+    fn sort_orders_values() {
+        let (_, memory) = call(SORT, &[1, 5], &[9, 4, 1, 3, 1, 0, 9]);
+        assert_eq!(memory, values(&[9, 0, 1, 1, 3, 4, 9]));
+        let (_, memory) = call(SORT, &[0, 0], &[2, 1]);
+        assert_eq!(memory, values(&[2, 1]));
+    }
+
+    #[test]
+    fn routines_are_valid() {
+        for routine in ROUTINES {
+            assert_eq!(validate_bytecode(&routine.bytecode()), Ok(()), "{}", routine.name);
+        }
+    }
+}
@@ -0,0 +1,134 @@
+//! Static validation of Brillig bytecode, catching inconsistencies which would otherwise only be found,
+//! if at all, by panicking in the middle of its execution.
+use crate::{registers::MAX_REGISTERS, Label, Opcode, RegisterIndex, RegisterOrMemory};
+
+/// An inconsistency found in the bytecode or the initial state of a [VM][crate::VM] before executing it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ValidationError {
+    /// More registers are loaded than the VM supports.
+    TooManyRegisters { count: usize },
+    /// The opcode at `opcode_index` uses a register past the registers of the VM.
+    RegisterOutOfRange { opcode_index: usize, register: RegisterIndex },
+    /// The opcode at `opcode_index` jumps or calls past the end of the bytecode.
+    LabelOutOfRange { opcode_index: usize, label: Label },
+    /// The `ToRadix` opcode at `opcode_index` decomposes values into digits of a radix below two.
+    InvalidRadix { opcode_index: usize, radix: u32 },
+}
+
+impl std::fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ValidationError::TooManyRegisters { count } => {
+                write!(f, "{count} registers are loaded, more than the maximum of {MAX_REGISTERS}")
+            }
+            ValidationError::RegisterOutOfRange { opcode_index, register } => write!(
+                f,
+                "opcode {opcode_index} uses register {}, past the maximum of {MAX_REGISTERS} registers",
+                register.to_usize()
+            ),
+            ValidationError::LabelOutOfRange { opcode_index, label } => {
+                write!(f, "opcode {opcode_index} jumps to {label}, past the end of the bytecode")
+            }
+            ValidationError::InvalidRadix { opcode_index, radix } => {
+                write!(f, "opcode {opcode_index} decomposes values into digits of invalid radix {radix}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+/// Checks that every register used by `bytecode` is within the registers of the VM, that every jump
+/// and call lands within the bytecode or right at its end, and that every radix is at least two.
+pub fn validate_bytecode(bytecode: &[Opcode]) -> Result<(), ValidationError> {
+    for (opcode_index, opcode) in bytecode.iter().enumerate() {
+        if let Some(&register) =
+            registers(opcode).iter().find(|register| register.to_usize() >= MAX_REGISTERS)
+        {
+            return Err(ValidationError::RegisterOutOfRange { opcode_index, register });
+        }
+        match *opcode {
+            Opcode::Jump { location }
+            | Opcode::JumpIf { location, .. }
+            | Opcode::JumpIfNot { location, .. }
+            | Opcode::Call { location }
+                if location > bytecode.len() =>
+            {
+                return Err(ValidationError::LabelOutOfRange { opcode_index, label: location });
+            }
+            Opcode::ToRadix { radix, .. } if radix < 2 => {
+                return Err(ValidationError::InvalidRadix { opcode_index, radix });
+            }
+            _ => (),
+        }
+    }
+    Ok(())
+}
+
+/// Returns the registers read or written by `opcode`.
+fn registers(opcode: &Opcode) -> Vec<RegisterIndex> {
+    match opcode {
+        Opcode::BinaryFieldOp { destination, lhs, rhs, .. }
+        | Opcode::BinaryIntOp { destination, lhs, rhs, .. } => vec![*destination, *lhs, *rhs],
+        Opcode::JumpIf { condition, .. } | Opcode::JumpIfNot { condition, .. } => vec![*condition],
+        Opcode::Const { destination, .. } => vec![*destination],
+        Opcode::Mov { destination, source } => vec![*destination, *source],
+        Opcode::Load { destination, source_pointer } => vec![*destination, *source_pointer],
+        Opcode::Store { destination_pointer, source } => vec![*destination_pointer, *source],
+        Opcode::FieldIntegerDiv { quotient, remainder, lhs, rhs } => {
+            vec![*quotient, *remainder, *lhs, *rhs]
+        }
+        Opcode::ToRadix { source, destination_pointer, .. } => vec![*source, *destination_pointer],
+        Opcode::FieldSqrt { root, is_residue, source } => vec![*root, *is_residue, *source],
+        Opcode::ForeignCall { destinations, inputs, .. } => destinations
+            .iter()
+            .chain(inputs)
+            .flat_map(|value| match *value {
+                RegisterOrMemory::RegisterIndex(register) => vec![register],
+                RegisterOrMemory::HeapArray(pointer, _) => vec![pointer],
+                RegisterOrMemory::HeapVector(pointer, size) => vec![pointer, size],
+            })
+            .collect(),
+        Opcode::Jump { .. }
+        | Opcode::Call { .. }
+        | Opcode::Return
+        | Opcode::Trap
+        | Opcode::Stop => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{validate_bytecode, ValidationError};
+    use crate::{Opcode, RegisterIndex, Value};
+
+    #[test]
+    fn rejects_inconsistent_bytecode() {
+        let r = RegisterIndex::from;
+        let valid = vec![
+            Opcode::Const { destination: r(0), value: Value::from(1u128) },
+            Opcode::JumpIf { condition: r(0), location: 3 },
+            Opcode::Trap,
+        ];
+        assert_eq!(validate_bytecode(&valid), Ok(()));
+
+        let jump = vec![Opcode::Jump { location: 2 }];
+        assert_eq!(
+            validate_bytecode(&jump),
+            Err(ValidationError::LabelOutOfRange { opcode_index: 0, label: 2 })
+        );
+
+        let mov = vec![Opcode::Trap, Opcode::Mov { destination: r(1 << 16), source: r(0) }];
+        assert_eq!(
+            validate_bytecode(&mov),
+            Err(ValidationError::RegisterOutOfRange { opcode_index: 1, register: r(1 << 16) })
+        );
+
+        let to_radix =
+            vec![Opcode::ToRadix { source: r(0), destination_pointer: r(1), radix: 1, limbs: 8 }];
+        assert_eq!(
+            validate_bytecode(&to_radix),
+            Err(ValidationError::InvalidRadix { opcode_index: 0, radix: 1 })
+        );
+    }
+}
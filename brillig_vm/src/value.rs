@@ -27,8 +27,9 @@ impl Value {
         self.inner
     }
 
-    /// Converts `Value` into a `u128`.
-    // TODO: Check what happens if `Value` cannot fit into a u128
+    /// Converts `Value` into a `u128`, keeping only its 128 least significant bits.
+    ///
+    /// Use [`Value::try_to_u128`] unless the value is known to fit.
     pub fn to_u128(&self) -> u128 {
         self.to_field().to_u128()
     }
@@ -36,12 +37,83 @@ impl Value {
     /// Converts `Value` into a u64 and then casts it into a usize.
     /// Panics: If `Value` cannot fit into a u64 or `Value` does
     //// not fit into a usize.
+    ///
+    /// Use [`Value::try_to_usize`] unless the value is known to fit.
     pub fn to_usize(&self) -> usize {
         usize::try_from(self.inner.try_to_u64().expect("register does not fit into u64"))
             .expect("register does not fit into usize")
     }
+
+    /// Converts `Value` into a `u128`, failing if it does not fit.
+    pub fn try_to_u128(&self) -> Result<u128, ValueError> {
+        self.inner.try_into_u128().ok_or(ValueError::DoesNotFit { value: *self, target: "u128" })
+    }
+
+    /// Converts `Value` into a `u64`, failing if it does not fit.
+    pub fn try_to_u64(&self) -> Result<u64, ValueError> {
+        self.inner.try_to_u64().ok_or(ValueError::DoesNotFit { value: *self, target: "u64" })
+    }
+
+    /// Converts `Value` into a `usize`, such as a memory address or size, failing if it does not fit.
+    pub fn try_to_usize(&self) -> Result<usize, ValueError> {
+        self.try_to_u64()
+            .ok()
+            .and_then(|value| usize::try_from(value).ok())
+            .ok_or(ValueError::DoesNotFit { value: *self, target: "usize" })
+    }
+
+    /// Converts `Value` into a `bool`, failing unless it is zero or one.
+    pub fn try_to_bool(&self) -> Result<bool, ValueError> {
+        if self.inner.is_zero() {
+            Ok(false)
+        } else if self.inner.is_one() {
+            Ok(true)
+        } else {
+            Err(ValueError::DoesNotFit { value: *self, target: "bool" })
+        }
+    }
+
+    /// Creates a `Value` of type `typ`, failing if `value` is not a valid value of this type.
+    ///
+    /// Integers are represented by their first `bit_size` bits, with signed integers in two's complement.
+    pub fn try_new(value: FieldElement, typ: Typ) -> Result<Value, ValueError> {
+        let fits = match typ {
+            Typ::Field => true,
+            Typ::Unsigned { bit_size } | Typ::Signed { bit_size } => value.num_bits() <= bit_size,
+        };
+        let value = Value { inner: value };
+        if fits {
+            Ok(value)
+        } else {
+            Err(ValueError::InvalidForType { value, typ })
+        }
+    }
 }
 
+/// A [`Value`] which cannot be converted into the type expected of it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValueError {
+    /// The value does not fit into the named primitive type.
+    DoesNotFit { value: Value, target: &'static str },
+    /// The value is not a valid value of a VM type.
+    InvalidForType { value: Value, typ: Typ },
+}
+
+impl std::fmt::Display for ValueError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ValueError::DoesNotFit { value, target } => {
+                write!(f, "value {} does not fit into a {target}", value.inner)
+            }
+            ValueError::InvalidForType { value, typ } => {
+                write!(f, "value {} is not a valid {typ:?} value", value.inner)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ValueError {}
+
 impl From<usize> for Value {
     fn from(value: usize) -> Self {
         Value { inner: FieldElement::from(value as u128) }
@@ -101,3 +173,37 @@ impl Neg for Value {
         Value { inner: -self.inner }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{Typ, Value, ValueError};
+    use acir_field::FieldElement;
+
+    #[test]
+    fn checked_conversions_fail_on_values_which_do_not_fit() {
+        let large = Value::from(u64::MAX as u128 + 1);
+        assert_eq!(large.try_to_u128(), Ok(u64::MAX as u128 + 1));
+        assert_eq!(large.try_to_u64(), Err(ValueError::DoesNotFit { value: large, target: "u64" }));
+        assert!(Value::from(-FieldElement::one()).try_to_u128().is_err());
+        assert_eq!(Value::from(7u128).try_to_usize(), Ok(7));
+
+        assert_eq!(Value::from(true).try_to_bool(), Ok(true));
+        assert_eq!(Value::from(0u128).try_to_bool(), Ok(false));
+        assert!(Value::from(2u128).try_to_bool().is_err());
+    }
+
+    #[test]
+    fn typed_values_fit_into_their_bit_size() {
+        let value = FieldElement::from(255u128);
+        assert!(Value::try_new(value, Typ::Unsigned { bit_size: 8 }).is_ok());
+        assert!(Value::try_new(value, Typ::Signed { bit_size: 8 }).is_ok());
+        assert_eq!(
+            Value::try_new(value, Typ::Unsigned { bit_size: 7 }),
+            Err(ValueError::InvalidForType {
+                value: Value::from(value),
+                typ: Typ::Unsigned { bit_size: 7 }
+            })
+        );
+        assert!(Value::try_new(-FieldElement::one(), Typ::Field).is_ok());
+    }
+}
@@ -0,0 +1,233 @@
+//! Property tests checking `BinaryIntOp::evaluate_int` and `BinaryIntOp::checked_evaluate_int` against reference
+//! implementations.
+//!
+//! The VM must agree with the constraints generated for the same integer operations, so it wraps
+//! around on overflow for every bit size. It is compared against Rust's own integer types for the
+//! native bit sizes, and against a reference which never relies on `u128` wrap around for the others.
+use brillig_vm::{to_signed, to_unsigned, BinaryIntOp, BinaryIntOpError};
+use proptest::prelude::*;
+
+const OPS: [BinaryIntOp; 16] = [
+    BinaryIntOp::Add,
+    BinaryIntOp::Sub,
+    BinaryIntOp::Mul,
+    BinaryIntOp::SignedDiv,
+    BinaryIntOp::UnsignedDiv,
+    BinaryIntOp::Equals,
+    BinaryIntOp::LessThan,
+    BinaryIntOp::LessThanEquals,
+    BinaryIntOp::And,
+    BinaryIntOp::Or,
+    BinaryIntOp::Xor,
+    BinaryIntOp::Shl,
+    BinaryIntOp::Shr,
+    BinaryIntOp::AddChecked,
+    BinaryIntOp::SubChecked,
+    BinaryIntOp::MulChecked,
+];
+
+fn is_division(op: BinaryIntOp) -> bool {
+    matches!(op, BinaryIntOp::SignedDiv | BinaryIntOp::UnsignedDiv)
+}
+
+/// The largest `bit_size` bits integer.
+fn max_value(bit_size: u32) -> u128 {
+    u128::MAX >> (u128::BITS - bit_size)
+}
+
+/// Returns `2^bit_size - x`, the opposite of `x` modulo `2^bit_size`.
+fn negate(x: u128, bit_size: u32) -> u128 {
+    if x == 0 {
+        0
+    } else {
+        max_value(bit_size) - x + 1
+    }
+}
+
+fn add(a: u128, b: u128, bit_size: u32) -> u128 {
+    let sum = match a.checked_add(b) {
+        Some(sum) => sum,
+        // Only 128 bits integers can overflow, in which case the sum is `a - (2^128 - b)`.
+        None => a - negate(b, bit_size),
+    };
+    if sum > max_value(bit_size) {
+        sum - max_value(bit_size) - 1
+    } else {
+        sum
+    }
+}
+
+fn mul(a: u128, b: u128, bit_size: u32) -> u128 {
+    (0..bit_size).rev().fold(0, |product, bit| {
+        let product = add(product, product, bit_size);
+        if (b >> bit) & 1 == 1 {
+            add(product, a, bit_size)
+        } else {
+            product
+        }
+    })
+}
+
+/// Reference semantics of `op` on `bit_size` bits integers `a` and `b`.
+fn reference(op: BinaryIntOp, a: u128, b: u128, bit_size: u32) -> u128 {
+    let is_negative = |x: u128| (x >> (bit_size - 1)) & 1 == 1;
+    let magnitude = |x: u128| if is_negative(x) { negate(x, bit_size) } else { x };
+    match op {
+        BinaryIntOp::Add | BinaryIntOp::AddChecked => add(a, b, bit_size),
+        BinaryIntOp::Sub | BinaryIntOp::SubChecked => add(a, negate(b, bit_size), bit_size),
+        BinaryIntOp::Mul | BinaryIntOp::MulChecked => mul(a, b, bit_size),
+        BinaryIntOp::UnsignedDiv => a / b,
+        BinaryIntOp::SignedDiv => {
+            let quotient = magnitude(a) / magnitude(b);
+            if is_negative(a) == is_negative(b) {
+                // Dividing the smallest integer by -1 overflows to itself, which is `2^(bit_size - 1)`.
+                quotient
+            } else {
+                negate(quotient, bit_size)
+            }
+        }
+        BinaryIntOp::Equals => (a == b).into(),
+        BinaryIntOp::LessThan => (a < b).into(),
+        BinaryIntOp::LessThanEquals => (a <= b).into(),
+        BinaryIntOp::And => a & b,
+        BinaryIntOp::Or => a | b,
+        BinaryIntOp::Xor => a ^ b,
+        BinaryIntOp::Shl if b < bit_size as u128 => mul(a, 1 << b, bit_size),
+        BinaryIntOp::Shr if b < bit_size as u128 => a / (1 << b),
+        BinaryIntOp::Shl | BinaryIntOp::Shr => 0,
+    }
+}
+
+/// Evaluates `op` with the native unsigned and signed integer types of the same bit size.
+macro_rules! native {
+    ($op:expr, $a:expr, $b:expr, $unsigned:ty, $signed:ty) => {{
+        let (a, b) = ($a as $unsigned, $b as $unsigned);
+        let shift = u32::try_from($b).ok();
+        let result: $unsigned = match $op {
+            BinaryIntOp::Add | BinaryIntOp::AddChecked => a.wrapping_add(b),
+            BinaryIntOp::Sub | BinaryIntOp::SubChecked => a.wrapping_sub(b),
+            BinaryIntOp::Mul | BinaryIntOp::MulChecked => a.wrapping_mul(b),
+            BinaryIntOp::UnsignedDiv => a / b,
+            BinaryIntOp::SignedDiv => (a as $signed).wrapping_div(b as $signed) as $unsigned,
+            BinaryIntOp::Equals => (a == b).into(),
+            BinaryIntOp::LessThan => (a < b).into(),
+            BinaryIntOp::LessThanEquals => (a <= b).into(),
+            BinaryIntOp::And => a & b,
+            BinaryIntOp::Or => a | b,
+            BinaryIntOp::Xor => a ^ b,
+            BinaryIntOp::Shl => shift.and_then(|shift| a.checked_shl(shift)).unwrap_or(0),
+            BinaryIntOp::Shr => shift.and_then(|shift| a.checked_shr(shift)).unwrap_or(0),
+        };
+        result as u128
+    }};
+}
+
+fn native(op: BinaryIntOp, a: u128, b: u128, bit_size: u32) -> u128 {
+    match bit_size {
+        8 => native!(op, a, b, u8, i8),
+        16 => native!(op, a, b, u16, i16),
+        32 => native!(op, a, b, u32, i32),
+        64 => native!(op, a, b, u64, i64),
+        128 => native!(op, a, b, u128, i128),
+        _ => unreachable!("no native integer type of {bit_size} bits"),
+    }
+}
+
+/// Strategy generating an operation with two operands of `bit_size` bits.
+///
+/// The second operand is often small so that shifts are not always by more than `bit_size` bits.
+fn operands(bit_size: u32) -> impl Strategy<Value = (BinaryIntOp, u128, u128)> {
+    let max = max_value(bit_size);
+    (
+        proptest::sample::select(OPS.to_vec()),
+        0..=max,
+        prop_oneof![0..=max, 0..=(bit_size as u128 + 1).min(max)],
+    )
+}
+
+proptest! {
+    #[test]
+    fn matches_reference_for_all_bit_sizes(
+        (bit_size, (op, a, b)) in (1..=128_u32).prop_flat_map(|bit_size| (Just(bit_size), operands(bit_size)))
+    ) {
+        if is_division(op) && b == 0 {
+            prop_assert_eq!(op.checked_evaluate_int(a, b, bit_size), Err(BinaryIntOpError::DivisionByZero));
+        } else {
+            prop_assert_eq!(op.evaluate_int(a, b, bit_size), reference(op, a, b, bit_size));
+        }
+    }
+
+    #[test]
+    fn matches_native_integers(
+        (bit_size, (op, a, b)) in proptest::sample::select(vec![8, 16, 32, 64, 128_u32])
+            .prop_flat_map(|bit_size| (Just(bit_size), operands(bit_size)))
+    ) {
+        if is_division(op) && b == 0 {
+            prop_assert_eq!(op.checked_evaluate_int(a, b, bit_size), Err(BinaryIntOpError::DivisionByZero));
+        } else {
+            prop_assert_eq!(op.evaluate_int(a, b, bit_size), native(op, a, b, bit_size));
+        }
+    }
+
+    #[test]
+    fn checked_ops_fail_exactly_when_wrapping(
+        (bit_size, (op, a, b)) in (1..=128_u32).prop_flat_map(|bit_size| (Just(bit_size), operands(bit_size)))
+    ) {
+        let overflows = match op {
+            BinaryIntOp::AddChecked => a.checked_add(b).map_or(true, |sum| sum > max_value(bit_size)),
+            BinaryIntOp::SubChecked => a < b,
+            BinaryIntOp::MulChecked => a.checked_mul(b).map_or(true, |product| product > max_value(bit_size)),
+            _ => false,
+        };
+        let expected = if is_division(op) && b == 0 {
+            Err(BinaryIntOpError::DivisionByZero)
+        } else if overflows {
+            Err(BinaryIntOpError::Overflow { bit_size })
+        } else {
+            Ok(reference(op, a, b, bit_size))
+        };
+        prop_assert_eq!(op.checked_evaluate_int(a, b, bit_size), expected);
+    }
+
+    #[test]
+    fn unsupported_bit_sizes_fail(
+        op in proptest::sample::select(OPS.to_vec()),
+        bit_size in prop_oneof![Just(0), 129..=u32::MAX],
+        a: u128,
+        b: u128,
+    ) {
+        prop_assert_eq!(op.checked_evaluate_int(a, b, bit_size), Err(BinaryIntOpError::InvalidBitSize(bit_size)));
+    }
+
+    #[test]
+    fn signed_conversions_roundtrip(
+        (bit_size, a) in (1..=128_u32).prop_flat_map(|bit_size| (Just(bit_size), 0..=max_value(bit_size)))
+    ) {
+        let signed = to_signed(a, bit_size);
+        prop_assert!(signed < 0 || signed as u128 <= max_value(bit_size) >> 1);
+        prop_assert_eq!(to_unsigned(signed, bit_size), a);
+    }
+}
+
+#[test]
+fn references_agree_with_native_integers() {
+    // Sanity check of the reference itself on edge cases of each native bit size.
+    for bit_size in [8, 16, 32, 64, 128] {
+        let max = max_value(bit_size);
+        let values = [0, 1, 2, 3, max >> 1, (max >> 1) + 1, max - 1, max];
+        for op in OPS {
+            for a in values {
+                for b in values {
+                    if is_division(op) && b == 0 {
+                        continue;
+                    }
+                    assert_eq!(
+                        reference(op, a, b, bit_size),
+                        native(op, a, b, bit_size),
+                        "{op:?} {a} {b} on {bit_size} bits"
+                    );
+                }
+            }
+        }
+    }
+}
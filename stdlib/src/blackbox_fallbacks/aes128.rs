@@ -0,0 +1,322 @@
+//! AES-128 encryption fallback function.
+//!
+//! The cipher is implemented as a boolean circuit over the bits of its state, where
+//! the S-box computes inverses in `GF(2^8)` as `x^254` using four multiplications.
+use crate::{fallback::bit_decomposition, helpers::CircuitBuilder};
+use acir::{
+    acir_field::FieldElement,
+    circuit::Opcode,
+    native_types::{Expression, Witness},
+};
+
+const BLOCK_SIZE: usize = 16;
+const NUM_ROUNDS: usize = 10;
+
+const ROUND_CONSTANTS: [u8; NUM_ROUNDS] =
+    [0x01, 0x02, 0x04, 0x08, 0x10, 0x20, 0x40, 0x80, 0x1b, 0x36];
+
+/// A bit, represented by an expression which evaluates to either 0 or 1.
+type Bit = Expression;
+
+/// A byte, represented by its bits starting with the least significant.
+type Byte = [Bit; 8];
+
+/// Adds the opcodes constraining `outputs` to be the encryption of the bytes `inputs`
+/// using AES-128 in CBC mode, after padding them following PKCS#7, to the builder.
+pub fn aes128_encrypt(
+    inputs: Vec<Expression>,
+    iv: Vec<Expression>,
+    key: Vec<Expression>,
+    outputs: Vec<Witness>,
+    builder: &mut CircuitBuilder,
+) {
+    assert_eq!(iv.len(), BLOCK_SIZE, "aes128 takes a 16 byte initialization vector");
+    assert_eq!(key.len(), BLOCK_SIZE, "aes128 takes a 16 byte key");
+    let padding = BLOCK_SIZE - inputs.len() % BLOCK_SIZE;
+    assert_eq!(outputs.len(), inputs.len() + padding, "aes128 pads inputs to whole blocks");
+
+    // Decomposing the inputs into bits also constrains them to be bytes.
+    let mut plaintext = Vec::with_capacity(outputs.len());
+    for input in inputs {
+        plaintext.push(to_bits(input, builder));
+    }
+    plaintext.resize(outputs.len(), constant_byte(padding as u8));
+    let mut previous_block = Vec::with_capacity(BLOCK_SIZE);
+    for byte in iv {
+        previous_block.push(to_bits(byte, builder));
+    }
+    let mut key_bytes = Vec::with_capacity(BLOCK_SIZE);
+    for byte in key {
+        key_bytes.push(to_bits(byte, builder));
+    }
+
+    let round_keys = expand_key(key_bytes, builder);
+
+    for (block, output_block) in plaintext.chunks(BLOCK_SIZE).zip(outputs.chunks(BLOCK_SIZE)) {
+        let mut state = Vec::with_capacity(BLOCK_SIZE);
+        for (byte, previous_byte) in block.iter().zip(&previous_block) {
+            state.push(xor_bytes(byte, previous_byte, builder));
+        }
+        let ciphertext = encrypt_block(state, &round_keys, builder);
+
+        for (byte, output) in ciphertext.iter().zip(output_block) {
+            builder.push(from_bits(byte, *output));
+        }
+        previous_block = ciphertext;
+    }
+}
+
+/// Derives the key of each round, starting with the initial key addition.
+fn expand_key(key: Vec<Byte>, builder: &mut CircuitBuilder) -> Vec<Vec<Byte>> {
+    let mut round_keys = vec![key];
+
+    for round_constant in ROUND_CONSTANTS {
+        let previous = round_keys.last().expect("initial key is always present");
+        let mut word = Vec::with_capacity(4);
+        for byte in [&previous[13], &previous[14], &previous[15], &previous[12]] {
+            word.push(sbox(byte, builder));
+        }
+        word[0] = xor_constant(&word[0], round_constant);
+
+        let mut round_key = Vec::with_capacity(BLOCK_SIZE);
+        for (i, previous_byte) in previous.iter().enumerate() {
+            let byte = xor_bytes(previous_byte, &word[i % 4], builder);
+            word[i % 4] = byte.clone();
+            round_key.push(byte);
+        }
+        round_keys.push(round_key);
+    }
+
+    round_keys
+}
+
+/// Encrypts a single block, where `state` is stored column by column.
+fn encrypt_block(
+    mut state: Vec<Byte>,
+    round_keys: &[Vec<Byte>],
+    builder: &mut CircuitBuilder,
+) -> Vec<Byte> {
+    state = add_round_key(&state, &round_keys[0], builder);
+    for (round, round_key) in round_keys.iter().enumerate().skip(1) {
+        for byte in state.iter_mut() {
+            *byte = sbox(byte, builder);
+        }
+        state = shift_rows(state);
+        if round != NUM_ROUNDS {
+            state = mix_columns(&state, builder);
+        }
+        state = add_round_key(&state, round_key, builder);
+    }
+
+    state
+}
+
+fn add_round_key(state: &[Byte], round_key: &[Byte], builder: &mut CircuitBuilder) -> Vec<Byte> {
+    let mut result = Vec::with_capacity(BLOCK_SIZE);
+    for (byte, key_byte) in state.iter().zip(round_key) {
+        result.push(xor_bytes(byte, key_byte, builder));
+    }
+    result
+}
+
+fn shift_rows(state: Vec<Byte>) -> Vec<Byte> {
+    (0..BLOCK_SIZE)
+        .map(|i| {
+            let (column, row) = (i / 4, i % 4);
+            state[4 * ((column + row) % 4) + row].clone()
+        })
+        .collect()
+}
+
+fn mix_columns(state: &[Byte], builder: &mut CircuitBuilder) -> Vec<Byte> {
+    let mut result = Vec::with_capacity(BLOCK_SIZE);
+
+    // Each byte of a column becomes `xtime(a_i ^ a_{i + 1}) ^ (a_0 ^ a_1 ^ a_2 ^ a_3) ^ a_i`,
+    // which is equal to `2 * a_i ^ 3 * a_{i + 1} ^ a_{i + 2} ^ a_{i + 3}`.
+    for column in state.chunks(4) {
+        let mut total = column[0].clone();
+        for byte in &column[1..] {
+            total = xor_bytes(&total, byte, builder);
+        }
+        for i in 0..4 {
+            let sum = xor_bytes(&column[i], &column[(i + 1) % 4], builder);
+            let doubled = linear_map(&sum, xtime, builder);
+            let partial = xor_bytes(&doubled, &total, builder);
+            result.push(xor_bytes(&partial, &column[i], builder));
+        }
+    }
+
+    result
+}
+
+/// Applies the AES S-box to `byte`.
+fn sbox(byte: &Byte, builder: &mut CircuitBuilder) -> Byte {
+    let square = |byte: u8| gf_mul(byte, byte);
+
+    // Computes `x^254`, which is the inverse of `x` in `GF(2^8)` for non-zero `x` and maps zero to itself.
+    let x2 = linear_map(byte, square, builder);
+    let x3 = mul_bytes(&x2, byte, builder);
+    let x6 = linear_map(&x3, square, builder);
+    let x12 = linear_map(&x6, square, builder);
+    let x15 = mul_bytes(&x12, &x3, builder);
+    let mut x240 = x15;
+    for _ in 0..4 {
+        x240 = linear_map(&x240, square, builder);
+    }
+    let x252 = mul_bytes(&x240, &x12, builder);
+    let inverse = mul_bytes(&x252, &x2, builder);
+
+    let affine = |byte: u8| {
+        byte ^ byte.rotate_left(1) ^ byte.rotate_left(2) ^ byte.rotate_left(3) ^ byte.rotate_left(4)
+    };
+    let transformed = linear_map(&inverse, affine, builder);
+
+    xor_constant(&transformed, 0x63)
+}
+
+/// Multiplies `lhs` and `rhs` in the AES field `GF(2^8) = GF(2)[x] / (x^8 + x^4 + x^3 + x + 1)`.
+fn mul_bytes(lhs: &Byte, rhs: &Byte, builder: &mut CircuitBuilder) -> Byte {
+    // Multiply the bits as polynomials before reducing the result modulo the field polynomial.
+    let mut product: [Bit; 15] = std::array::from_fn(|_| Expression::zero());
+    for (i, lhs_bit) in lhs.iter().enumerate() {
+        for (j, rhs_bit) in rhs.iter().enumerate() {
+            let term = and(lhs_bit, rhs_bit, builder);
+            product[i + j] = xor(&product[i + j], &term, builder);
+        }
+    }
+
+    let mut result: Byte = std::array::from_fn(|i| product[i].clone());
+    for (degree, coefficient) in product.iter().enumerate().skip(8) {
+        let reduced = gf_pow_x(degree);
+        for (bit, result_bit) in result.iter_mut().enumerate() {
+            if reduced >> bit & 1 == 1 {
+                *result_bit = xor(result_bit, coefficient, builder);
+            }
+        }
+    }
+
+    result
+}
+
+/// Applies `map`, which must be linear over `GF(2)`, to the bits of `byte`.
+fn linear_map(byte: &Byte, map: impl Fn(u8) -> u8, builder: &mut CircuitBuilder) -> Byte {
+    let mut result: Byte = std::array::from_fn(|_| Expression::zero());
+    for (i, input_bit) in byte.iter().enumerate() {
+        let image = map(1 << i);
+        for (bit, result_bit) in result.iter_mut().enumerate() {
+            if image >> bit & 1 == 1 {
+                *result_bit = xor(result_bit, input_bit, builder);
+            }
+        }
+    }
+    result
+}
+
+fn xor_bytes(lhs: &Byte, rhs: &Byte, builder: &mut CircuitBuilder) -> Byte {
+    let mut result: Byte = std::array::from_fn(|_| Expression::zero());
+    for ((result_bit, lhs_bit), rhs_bit) in result.iter_mut().zip(lhs).zip(rhs) {
+        *result_bit = xor(lhs_bit, rhs_bit, builder);
+    }
+    result
+}
+
+fn xor_constant(byte: &Byte, constant: u8) -> Byte {
+    std::array::from_fn(|i| if constant >> i & 1 == 1 { not(&byte[i]) } else { byte[i].clone() })
+}
+
+fn constant_byte(constant: u8) -> Byte {
+    std::array::from_fn(|i| Expression::from_field(FieldElement::from((constant >> i & 1) as u128)))
+}
+
+/// Returns a bit constrained to be equal to `lhs ^ rhs`, computed as `lhs + rhs - 2 * lhs * rhs`.
+fn xor(lhs: &Bit, rhs: &Bit, builder: &mut CircuitBuilder) -> Bit {
+    match (lhs.to_const(), rhs.to_const()) {
+        (Some(constant), _) => xor_with_constant(rhs, constant),
+        (_, Some(constant)) => xor_with_constant(lhs, constant),
+        (None, None) => {
+            let product = (lhs * rhs).expect("bits are linear expressions");
+            let sum = lhs + rhs;
+            new_bit(sum.add_mul(-FieldElement::from(2_u128), &product), builder)
+        }
+    }
+}
+
+/// Returns a bit constrained to be equal to `lhs & rhs`, computed as `lhs * rhs`.
+fn and(lhs: &Bit, rhs: &Bit, builder: &mut CircuitBuilder) -> Bit {
+    match (lhs.to_const(), rhs.to_const()) {
+        (Some(constant), _) => and_with_constant(rhs, constant),
+        (_, Some(constant)) => and_with_constant(lhs, constant),
+        (None, None) => new_bit((lhs * rhs).expect("bits are linear expressions"), builder),
+    }
+}
+
+fn xor_with_constant(bit: &Bit, constant: FieldElement) -> Bit {
+    if constant.is_zero() {
+        bit.clone()
+    } else {
+        not(bit)
+    }
+}
+
+fn and_with_constant(bit: &Bit, constant: FieldElement) -> Bit {
+    if constant.is_zero() {
+        Expression::zero()
+    } else {
+        bit.clone()
+    }
+}
+
+fn not(bit: &Bit) -> Bit {
+    &Expression::one() - bit
+}
+
+/// Returns a new bit constrained to be equal to `value`, which must evaluate to either 0 or 1.
+fn new_bit(value: Expression, builder: &mut CircuitBuilder) -> Bit {
+    let bit = builder.new_witness();
+    let mut constraint = value;
+    constraint.push_addition_term(-FieldElement::one(), bit);
+    constraint.sort();
+    builder.push(Opcode::AssertZero(constraint));
+    Expression::from(bit)
+}
+
+/// Decomposes `byte` into bits, constraining it to fit within 8 bits.
+fn to_bits(byte: Expression, builder: &mut CircuitBuilder) -> Byte {
+    let bits = bit_decomposition(byte, 8, builder);
+    std::array::from_fn(|i| Expression::from(bits[i]))
+}
+
+/// Returns the opcode constraining `output` to be equal to the composition of the bits of `byte`.
+fn from_bits(byte: &Byte, output: Witness) -> Opcode {
+    let mut constraint = Expression::from(output);
+    let mut multiplier = FieldElement::one();
+    for bit in byte {
+        constraint = constraint.add_mul(-multiplier, bit);
+        multiplier = multiplier * FieldElement::from(2_u128);
+    }
+    constraint.sort();
+    Opcode::AssertZero(constraint)
+}
+
+/// Multiplies `lhs` and `rhs` in the AES field.
+fn gf_mul(mut lhs: u8, mut rhs: u8) -> u8 {
+    let mut product = 0;
+    while rhs != 0 {
+        if rhs & 1 == 1 {
+            product ^= lhs;
+        }
+        lhs = xtime(lhs);
+        rhs >>= 1;
+    }
+    product
+}
+
+/// Returns `x^degree` reduced modulo the AES field polynomial.
+fn gf_pow_x(degree: usize) -> u8 {
+    (0..degree).fold(1, |power, _| xtime(power))
+}
+
+/// Multiplies `byte` by `x` in the AES field.
+fn xtime(byte: u8) -> u8 {
+    (byte << 1) ^ if byte & 0x80 != 0 { 0x1b } else { 0 }
+}
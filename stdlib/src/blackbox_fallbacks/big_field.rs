@@ -0,0 +1,538 @@
+//! Arithmetic modulo integers other than the native field modulus, such as the moduli of the fields
+//! over which foreign curves are defined or of RSA groups.
+//!
+//! Elements are held as little endian limbs of [`LIMB_BITS`] bits. Each limb is range constrained, so that an element
+//! holds some integer which need not be the canonical representative of its class modulo the modulus.
+//! Elements produced by a [`BigField`] have as many limbs as its modulus.
+//!
+//! Results are hinted along with their quotient by the modulus using the [`BigQuotient`][Directive::BigQuotient]
+//! directive, after which the integer identity relating them is checked limb by limb.
+//! The carries between limbs are bounded so that this check never wraps around the native field modulus.
+//!
+//! As with the other gadgets, each function adds the opcodes constraining its result to the builder.
+use super::{
+    uint::{power_of_two, split},
+    utils::{is_zero, range_opcode, witness_from_expression},
+};
+use crate::{fallback::bit_decomposition, helpers::CircuitBuilder};
+use acir::{
+    acir_field::FieldElement,
+    circuit::{
+        directives::{Directive, QuotientDirective},
+        Opcode,
+    },
+    native_types::{Expression, Witness},
+};
+use num_bigint::BigUint;
+
+/// Number of bits held by each limb of an element.
+pub const LIMB_BITS: u32 = 64;
+
+/// An integer held as little endian limbs, each of which is either a constant
+/// or a witness fitting within [`LIMB_BITS`] bits.
+#[derive(Clone, Debug)]
+pub struct BigFieldElement {
+    limbs: Vec<Expression>,
+}
+
+impl BigFieldElement {
+    pub fn constant(value: &BigUint) -> Self {
+        let limbs = to_limbs(value, 1).iter().map(|limb| to_field(limb).into()).collect();
+        BigFieldElement { limbs }
+    }
+
+    /// Returns the element holding the integer whose big endian bytes are `bytes`,
+    /// which must already be constrained to fit within 8 bits.
+    pub fn from_be_bytes(bytes: &[Witness], builder: &mut CircuitBuilder) -> Self {
+        if bytes.is_empty() {
+            return BigFieldElement::constant(&BigUint::default());
+        }
+
+        let mut limbs = Vec::new();
+        for chunk in bytes.rchunks((LIMB_BITS / 8) as usize) {
+            let mut limb = Expression::default();
+            for (i, byte) in chunk.iter().rev().enumerate() {
+                limb.push_addition_term(power_of_two(8 * i as u32), *byte);
+            }
+            limb.sort();
+            limbs.push(witness_from_expression(limb, builder).into());
+        }
+
+        BigFieldElement { limbs }
+    }
+
+    /// Returns an element made of the witnesses `limbs`, range constraining each of them to [`LIMB_BITS`] bits.
+    pub fn from_limbs(limbs: Vec<Witness>, builder: &mut CircuitBuilder) -> Self {
+        assert!(!limbs.is_empty(), "elements have at least one limb");
+        builder.extend(limbs.iter().map(|limb| range_opcode(*limb, LIMB_BITS)));
+        BigFieldElement { limbs: limbs.into_iter().map(Expression::from).collect() }
+    }
+
+    pub fn limbs(&self) -> &[Expression] {
+        &self.limbs
+    }
+
+    /// Returns the limb of weight `2^(i * LIMB_BITS)`, which is zero past the most significant limb.
+    fn limb(&self, i: usize) -> Expression {
+        self.limbs.get(i).cloned().unwrap_or_default()
+    }
+
+    /// Returns the bits of the integer held by `self`, starting with the least significant.
+    pub fn to_le_bits(&self, builder: &mut CircuitBuilder) -> Vec<Witness> {
+        self.limbs
+            .iter()
+            .flat_map(|limb| bit_decomposition(limb.clone(), LIMB_BITS, builder))
+            .collect()
+    }
+
+    /// Returns a witness equal to one if the integer held by `self` is less than `bound` and zero otherwise.
+    pub fn is_less_than(&self, bound: &BigUint, builder: &mut CircuitBuilder) -> Witness {
+        assert!(bound.bits() >= 1, "bound must be positive");
+
+        // `self < bound` if and only if subtracting `self` from `bound - 1` does not borrow,
+        // which is the case if the most significant limb of the difference does not borrow.
+        let max_value = to_limbs(&(bound - 1_u32), self.limbs.len());
+        let mut no_borrow = None;
+        for (i, max_limb) in max_value.iter().enumerate() {
+            let limb = self.limb(i);
+            // Lies within `[0, 2^(LIMB_BITS + 1))`, with its top bit set if there is no borrow.
+            let mut difference =
+                Expression::from_field(to_field(max_limb) + power_of_two(LIMB_BITS));
+            difference = &difference - &limb;
+            if let Some(no_borrow) = no_borrow {
+                difference = &(&difference + no_borrow) - &Expression::one();
+            }
+            let (high, _) = split(difference, LIMB_BITS, 1, builder);
+            no_borrow = Some(high);
+        }
+
+        no_borrow.expect("elements have at least one limb")
+    }
+
+    /// Constrains the integer held by `self` to be less than `bound`.
+    pub fn assert_less_than(&self, bound: &BigUint, builder: &mut CircuitBuilder) {
+        let is_less_than = self.is_less_than(bound, builder);
+        builder.push(Opcode::AssertZero(&Expression::from(is_less_than) - &Expression::one()));
+    }
+
+    /// Returns a witness equal to one if `self` and `other` hold the same integer and zero otherwise.
+    pub fn is_equal(&self, other: &Self, builder: &mut CircuitBuilder) -> Witness {
+        let mut is_equal: Option<Witness> = None;
+        for i in 0..self.limbs.len().max(other.limbs.len()) {
+            let mut difference = &self.limb(i) - &other.limb(i);
+            difference.sort();
+            let limb_is_equal = is_zero(difference, builder);
+            is_equal = Some(match is_equal {
+                Some(is_equal) => {
+                    let mut product = Expression::default();
+                    product.push_multiplication_term(FieldElement::one(), is_equal, limb_is_equal);
+                    witness_from_expression(product, builder)
+                }
+                None => limb_is_equal,
+            });
+        }
+        is_equal.expect("elements have at least one limb")
+    }
+
+    /// Returns `if_true` if `condition` is one and `if_false` if it is zero.
+    pub fn select(
+        condition: Witness,
+        if_true: &Self,
+        if_false: &Self,
+        builder: &mut CircuitBuilder,
+    ) -> Self {
+        let num_limbs = if_true.limbs.len().max(if_false.limbs.len());
+        let mut limbs = Vec::with_capacity(num_limbs);
+        for i in 0..num_limbs {
+            let false_limb = if_false.limb(i);
+            let difference = &if_true.limb(i) - &false_limb;
+            let selected = &(&Expression::from(condition) * &difference)
+                .expect("limbs are linear expressions")
+                + &false_limb;
+            if selected.is_const() {
+                limbs.push(selected);
+                continue;
+            }
+            limbs.push(witness_from_expression(selected, builder).into());
+        }
+        BigFieldElement { limbs }
+    }
+}
+
+/// A sum of elements and of products of two of them, each multiplied by a small integer coefficient.
+#[derive(Clone, Debug, Default)]
+pub struct ProductSum {
+    products: Vec<(i64, BigFieldElement, BigFieldElement)>,
+    terms: Vec<(i64, BigFieldElement)>,
+}
+
+impl ProductSum {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn product(
+        mut self,
+        coefficient: i64,
+        lhs: &BigFieldElement,
+        rhs: &BigFieldElement,
+    ) -> Self {
+        self.products.push((coefficient, lhs.clone(), rhs.clone()));
+        self
+    }
+
+    pub fn term(mut self, coefficient: i64, element: &BigFieldElement) -> Self {
+        self.terms.push((coefficient, element.clone()));
+        self
+    }
+
+    /// Returns `self - other`.
+    fn sub(mut self, other: ProductSum) -> Self {
+        self.products.extend(other.products.into_iter().map(|(c, lhs, rhs)| (-c, lhs, rhs)));
+        self.terms.extend(other.terms.into_iter().map(|(c, element)| (-c, element)));
+        self
+    }
+
+    /// Returns the product of `self`, which must not contain any products, with `element`.
+    fn mul(&self, element: &BigFieldElement) -> Self {
+        assert!(self.products.is_empty(), "only sums of elements can be multiplied");
+        let products = self.terms.iter().map(|(c, term)| (*c, term.clone(), element.clone()));
+        ProductSum { products: products.collect(), terms: Vec::new() }
+    }
+
+    /// Returns the limbs of the sum, i.e. expressions such that `sum = \sum limbs[k] * 2^(k * LIMB_BITS)`.
+    fn limbs(&self) -> Vec<Expression> {
+        let products =
+            self.products.iter().map(|(_, lhs, rhs)| lhs.limbs.len() + rhs.limbs.len() - 1);
+        let terms = self.terms.iter().map(|(_, element)| element.limbs.len());
+        let mut limbs = vec![Expression::zero(); products.chain(terms).max().unwrap_or(1)];
+        for (coefficient, lhs, rhs) in &self.products {
+            let coefficient = FieldElement::from(*coefficient as i128);
+            for (i, lhs_limb) in lhs.limbs.iter().enumerate() {
+                for (j, rhs_limb) in rhs.limbs.iter().enumerate() {
+                    let product = (lhs_limb * rhs_limb).expect("limbs are linear expressions");
+                    limbs[i + j] = limbs[i + j].add_mul(coefficient, &product);
+                }
+            }
+        }
+        for (coefficient, element) in &self.terms {
+            let coefficient = FieldElement::from(*coefficient as i128);
+            for (i, limb) in element.limbs.iter().enumerate() {
+                limbs[i] = limbs[i].add_mul(coefficient, limb);
+            }
+        }
+        limbs
+    }
+
+    /// Returns bounds on the absolute values of the negative and positive parts of the sum.
+    fn bounds(&self) -> (BigUint, BigUint) {
+        let max_value = |element: &BigFieldElement| max_limb_value(element.limbs.len());
+        let (mut negative, mut positive) = (BigUint::default(), BigUint::default());
+        let products =
+            self.products.iter().map(|(c, lhs, rhs)| (*c, max_value(lhs) * max_value(rhs)));
+        for (coefficient, max_value) in
+            products.chain(self.terms.iter().map(|(c, element)| (*c, max_value(element))))
+        {
+            let bound = max_value * coefficient.unsigned_abs();
+            if coefficient < 0 {
+                negative += bound;
+            } else {
+                positive += bound;
+            }
+        }
+        (negative, positive)
+    }
+
+    /// Returns a bound on the absolute value of each limb of the sum.
+    fn limb_bound(&self) -> BigUint {
+        // Each limb of a product sums the products of at most as many pairs of limbs as the shortest factor has.
+        let max_limb = max_limb_value(1);
+        let products: u64 = self
+            .products
+            .iter()
+            .map(|(c, lhs, rhs)| c.unsigned_abs() * lhs.limbs.len().min(rhs.limbs.len()) as u64)
+            .sum();
+        let terms: u64 = self.terms.iter().map(|(c, _)| c.unsigned_abs()).sum();
+        &max_limb * &max_limb * products + max_limb * terms
+    }
+}
+
+/// The integers modulo some `modulus`, whose elements are made of as many limbs as are needed to hold it.
+///
+/// Only [`divide`][BigField::divide] and [`assert_not_zero`][BigField::assert_not_zero] rely on the modulus being prime.
+#[derive(Clone, Debug)]
+pub struct BigField {
+    modulus: BigUint,
+    num_limbs: usize,
+}
+
+impl BigField {
+    pub fn new(modulus: BigUint) -> Self {
+        assert!(modulus > BigUint::from(1_u32), "modulus must be greater than one");
+        let num_limbs = num_limbs(&modulus);
+        BigField { modulus, num_limbs }
+    }
+
+    pub fn modulus(&self) -> &BigUint {
+        &self.modulus
+    }
+
+    /// Returns an element congruent to `lhs + rhs`.
+    pub fn add(
+        &self,
+        lhs: &BigFieldElement,
+        rhs: &BigFieldElement,
+        builder: &mut CircuitBuilder,
+    ) -> BigFieldElement {
+        self.evaluate(&ProductSum::new().term(1, lhs).term(1, rhs), builder)
+    }
+
+    /// Returns an element congruent to `lhs - rhs`.
+    pub fn sub(
+        &self,
+        lhs: &BigFieldElement,
+        rhs: &BigFieldElement,
+        builder: &mut CircuitBuilder,
+    ) -> BigFieldElement {
+        self.evaluate(&ProductSum::new().term(1, lhs).term(-1, rhs), builder)
+    }
+
+    /// Returns an element congruent to `lhs * rhs`.
+    pub fn mul(
+        &self,
+        lhs: &BigFieldElement,
+        rhs: &BigFieldElement,
+        builder: &mut CircuitBuilder,
+    ) -> BigFieldElement {
+        self.evaluate(&ProductSum::new().product(1, lhs, rhs), builder)
+    }
+
+    /// Returns an element congruent to `sum`.
+    ///
+    /// Evaluating a whole sum at once is cheaper than evaluating each of its products and terms separately.
+    pub fn evaluate(&self, sum: &ProductSum, builder: &mut CircuitBuilder) -> BigFieldElement {
+        let result = builder.new_witnesses(self.num_limbs);
+        self.assert_division(sum, Some(result.clone()), builder);
+        BigFieldElement::from_limbs(result, builder)
+    }
+
+    /// Returns the canonical representative of `element`, which is less than the modulus.
+    pub fn reduce(
+        &self,
+        element: &BigFieldElement,
+        builder: &mut CircuitBuilder,
+    ) -> BigFieldElement {
+        let reduced = self.evaluate(&ProductSum::new().term(1, element), builder);
+        reduced.assert_less_than(&self.modulus, builder);
+        reduced
+    }
+
+    /// Constrains `sum` to be congruent to zero.
+    pub fn assert_zero(&self, sum: &ProductSum, builder: &mut CircuitBuilder) {
+        self.assert_division(sum, None, builder);
+    }
+
+    /// Constrains `sum`, which must not contain any products, not to be congruent to zero.
+    pub fn assert_not_zero(&self, sum: &ProductSum, builder: &mut CircuitBuilder) {
+        let inverse = self.invert_hint(sum, builder);
+        let inverse = BigFieldElement::from_limbs(inverse, builder);
+
+        let one = BigFieldElement::constant(&BigUint::from(1_u32));
+        self.assert_zero(&sum.mul(&inverse).term(-1, &one), builder);
+    }
+
+    /// Returns an element congruent to `numerator / denominator`, where `denominator` must not contain any products.
+    ///
+    /// The denominator must be known not to be congruent to zero, as the result is otherwise unconstrained
+    /// whenever the numerator is congruent to zero.
+    pub fn divide(
+        &self,
+        numerator: &ProductSum,
+        denominator: &ProductSum,
+        builder: &mut CircuitBuilder,
+    ) -> BigFieldElement {
+        // Hint the quotient, which is unconstrained until its product with the denominator is checked.
+        let inverse = self.invert_hint(denominator, builder);
+        let numerator_hint = self.hint(numerator, builder);
+        let hint_sum = ProductSum::new().product(
+            1,
+            &BigFieldElement { limbs: numerator_hint.into_iter().map(Expression::from).collect() },
+            &BigFieldElement { limbs: inverse.into_iter().map(Expression::from).collect() },
+        );
+        let result = self.hint(&hint_sum, builder);
+        let result = BigFieldElement::from_limbs(result, builder);
+
+        self.assert_zero(&denominator.mul(&result).sub(numerator.clone()), builder);
+        result
+    }
+
+    /// Returns the limbs of the non-negative integer obtained by adding to `sum` the least multiple of the modulus
+    /// which makes it non-negative, along with an upper bound on this integer.
+    fn non_negative_limbs(&self, sum: &ProductSum) -> (Vec<Expression>, BigUint) {
+        let (negative_bound, positive_bound) = sum.bounds();
+        let offset = (negative_bound + &self.modulus - 1_u32) / &self.modulus * &self.modulus;
+
+        let mut limbs = sum.limbs();
+        let offset_limbs = to_limbs(&offset, 0);
+        if offset_limbs.len() > limbs.len() {
+            limbs.resize(offset_limbs.len(), Expression::zero());
+        }
+        for (limb, offset_limb) in limbs.iter_mut().zip(offset_limbs) {
+            *limb = limb.clone() + to_field(&offset_limb);
+        }
+        (limbs, positive_bound + offset)
+    }
+
+    /// Returns witnesses which the solver sets to the limbs of the canonical representative of `sum`,
+    /// without constraining them in any way.
+    fn hint(&self, sum: &ProductSum, builder: &mut CircuitBuilder) -> Vec<Witness> {
+        let (limbs, bound) = self.non_negative_limbs(sum);
+        let quotient = builder.new_witnesses(num_limbs(&(bound / &self.modulus)));
+        let remainder = builder.new_witnesses(self.num_limbs);
+
+        let directive = Directive::BigQuotient {
+            a: limbs,
+            b: self.modulus_limbs(),
+            q: quotient,
+            r: remainder.clone(),
+            limb_bits: LIMB_BITS,
+        };
+        builder.push(Opcode::Directive(directive));
+        remainder
+    }
+
+    /// Returns witnesses which the solver sets to the limbs of the inverse of `sum`,
+    /// without constraining them in any way.
+    fn invert_hint(&self, sum: &ProductSum, builder: &mut CircuitBuilder) -> Vec<Witness> {
+        let inverse = builder.new_witnesses(self.num_limbs);
+
+        let directive = Directive::BigInvert {
+            a: sum.limbs(),
+            modulus: self.modulus_limbs(),
+            result: inverse.clone(),
+            limb_bits: LIMB_BITS,
+        };
+        builder.push(Opcode::Directive(directive));
+        inverse
+    }
+
+    /// Constrains `sum = quotient * modulus + remainder` for a hinted quotient.
+    ///
+    /// The remainder is taken to be zero if it is not given, in which case `sum` is constrained to be a multiple of the modulus.
+    fn assert_division(
+        &self,
+        sum: &ProductSum,
+        remainder: Option<Vec<Witness>>,
+        builder: &mut CircuitBuilder,
+    ) {
+        let (mut limbs, bound) = self.non_negative_limbs(sum);
+
+        let quotient = builder.new_witnesses(num_limbs(&(bound / &self.modulus)));
+        // The remainder hinted for a multiple of the modulus is left unconstrained.
+        let hinted_remainder = match &remainder {
+            Some(remainder) => remainder.clone(),
+            None => builder.new_witnesses(self.num_limbs),
+        };
+
+        builder.push(Opcode::Directive(Directive::BigQuotient {
+            a: limbs.clone(),
+            b: self.modulus_limbs(),
+            q: quotient.clone(),
+            r: hinted_remainder,
+            limb_bits: LIMB_BITS,
+        }));
+        builder.extend(quotient.iter().map(|limb| range_opcode(*limb, LIMB_BITS)));
+
+        let num_positions = quotient.len() + self.num_limbs - 1;
+        if num_positions > limbs.len() {
+            limbs.resize(num_positions, Expression::zero());
+        }
+        let modulus_limbs = to_limbs(&self.modulus, self.num_limbs);
+        for (i, quotient_limb) in quotient.iter().enumerate() {
+            for (j, modulus_limb) in modulus_limbs.iter().enumerate() {
+                limbs[i + j].push_addition_term(-to_field(modulus_limb), *quotient_limb);
+            }
+        }
+        for (limb, remainder_limb) in limbs.iter_mut().zip(remainder.iter().flatten()) {
+            limb.push_addition_term(-FieldElement::one(), *remainder_limb);
+        }
+
+        // Accounts for the limbs of the product of the quotient and the modulus, of the remainder and of the offset.
+        let max_limb = max_limb_value(1);
+        let quotient_products = quotient.len().min(self.num_limbs) as u64;
+        let limb_bound =
+            sum.limb_bound() + &max_limb * &max_limb * quotient_products + max_limb * 2_u32;
+        assert_integer_zero(limbs, &limb_bound, builder);
+    }
+
+    fn modulus_limbs(&self) -> Vec<Expression> {
+        to_limbs(&self.modulus, self.num_limbs).iter().map(|limb| to_field(limb).into()).collect()
+    }
+}
+
+/// Constrains the integer `\sum limbs[k] * 2^(k * LIMB_BITS)` to be zero,
+/// where the absolute value of each limb is at most `limb_bound`.
+///
+/// The limbs are summed from the least significant one, each partial sum being constrained
+/// to be a multiple of `2^LIMB_BITS` whose quotient is carried into the next limb.
+fn assert_integer_zero(limbs: Vec<Expression>, limb_bound: &BigUint, builder: &mut CircuitBuilder) {
+    // Carries are shifted by `2^carry_bits` so that they can be range constrained.
+    let carry_bits = (limb_bound.bits() as u32 + 1).saturating_sub(LIMB_BITS).max(1);
+    assert!(
+        carry_bits + LIMB_BITS + 2 < FieldElement::max_num_bits(),
+        "limbs are too large for their sum to be checked"
+    );
+    let carry_offset = power_of_two(carry_bits);
+    let limb_base = power_of_two(LIMB_BITS);
+
+    let mut carry = Expression::zero();
+    let num_limbs = limbs.len();
+    for (k, limb) in limbs.into_iter().enumerate() {
+        let mut partial_sum = &limb + &carry;
+        if k == num_limbs - 1 {
+            partial_sum.sort();
+            builder.push(Opcode::AssertZero(partial_sum));
+            break;
+        }
+
+        let shifted_carry = builder.new_witness();
+        let remainder = builder.new_witness();
+
+        builder.push(Opcode::Directive(Directive::Quotient(QuotientDirective {
+            a: partial_sum.clone() + carry_offset * limb_base,
+            b: Expression::from_field(limb_base),
+            q: shifted_carry,
+            r: remainder,
+            predicate: None,
+        })));
+        builder.push(range_opcode(shifted_carry, carry_bits + 1));
+
+        carry = Expression::from(shifted_carry) - carry_offset;
+        let mut constraint = &partial_sum - &(&carry * limb_base);
+        constraint.sort();
+        builder.push(Opcode::AssertZero(constraint));
+    }
+}
+
+/// Returns the little endian limbs of `value`, padded with zeroes up to `min_limbs` limbs.
+fn to_limbs(value: &BigUint, min_limbs: usize) -> Vec<BigUint> {
+    let mask = max_limb_value(1);
+    (0..num_limbs(value).max(min_limbs))
+        .map(|i| (value >> (i * LIMB_BITS as usize)) & &mask)
+        .collect()
+}
+
+/// Returns the number of limbs needed to hold `value`, which is at least one.
+fn num_limbs(value: &BigUint) -> usize {
+    let num_limbs = (value.bits() as usize + LIMB_BITS as usize - 1) / LIMB_BITS as usize;
+    num_limbs.max(1)
+}
+
+/// Returns the greatest integer which fits within `num_limbs` limbs.
+fn max_limb_value(num_limbs: usize) -> BigUint {
+    (BigUint::from(1_u32) << (num_limbs * LIMB_BITS as usize)) - 1_u32
+}
+
+fn to_field(value: &BigUint) -> FieldElement {
+    FieldElement::from_be_bytes_reduce(&value.to_bytes_be())
+}
@@ -0,0 +1,144 @@
+//! Blake2b fallback function.
+use super::{uint::UInt64, utils::hash_input_bytes};
+use crate::helpers::CircuitBuilder;
+use acir::native_types::{Expression, Witness};
+
+const BLOCK_BYTES: usize = 128;
+const DIGEST_BYTES: usize = 64;
+const NUM_ROUNDS: usize = 12;
+
+const IV: [u64; 8] = [
+    0x6a09e667f3bcc908,
+    0xbb67ae8584caa73b,
+    0x3c6ef372fe94f82b,
+    0xa54ff53a5f1d36f1,
+    0x510e527fade682d1,
+    0x9b05688c2b3e6c1f,
+    0x1f83d9abfb41bd6b,
+    0x5be0cd19137e2179,
+];
+
+/// Permutations of the message words used by each round, the last two rounds reusing the first two permutations.
+const SIGMA: [[usize; 16]; 10] = [
+    [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15],
+    [14, 10, 4, 8, 9, 15, 13, 6, 1, 12, 0, 2, 11, 7, 5, 3],
+    [11, 8, 12, 0, 5, 2, 15, 13, 10, 14, 3, 6, 7, 1, 9, 4],
+    [7, 9, 3, 1, 13, 12, 11, 14, 2, 6, 5, 10, 4, 0, 15, 8],
+    [9, 0, 5, 7, 2, 4, 10, 15, 14, 1, 11, 12, 6, 8, 3, 13],
+    [2, 12, 6, 10, 0, 11, 8, 3, 4, 13, 7, 5, 15, 14, 1, 9],
+    [12, 5, 1, 15, 14, 13, 4, 10, 0, 7, 6, 3, 9, 2, 8, 11],
+    [13, 11, 7, 14, 12, 1, 3, 9, 5, 0, 15, 4, 8, 6, 2, 10],
+    [6, 15, 14, 9, 11, 3, 0, 8, 12, 2, 13, 7, 1, 4, 10, 5],
+    [10, 2, 8, 4, 7, 6, 1, 5, 15, 11, 9, 14, 3, 12, 13, 0],
+];
+
+/// Adds the opcodes constraining `outputs` to be the unkeyed Blake2b digest of `inputs` to the builder.
+///
+/// Each input is paired with its bit size and contributes its `ceil(num_bits / 8)` bytes in little-endian order.
+pub fn blake2b(
+    inputs: Vec<(Expression, u32)>,
+    outputs: Vec<Witness>,
+    builder: &mut CircuitBuilder,
+) {
+    assert_eq!(outputs.len(), DIGEST_BYTES, "blake2b produces a 64 byte digest");
+    let bytes = hash_input_bytes(inputs, builder);
+    let message_len = bytes.len();
+    let message: Vec<Expression> = bytes.into_iter().map(Expression::from).collect();
+
+    // The parameter block of an unkeyed hash only sets the digest length and a fanout and depth of 1.
+    let mut state: Vec<UInt64> = IV
+        .into_iter()
+        .enumerate()
+        .map(|(i, word)| {
+            let word = if i == 0 { word ^ 0x01010000 ^ DIGEST_BYTES as u64 } else { word };
+            UInt64::load_constant(word, builder)
+        })
+        .collect();
+
+    // The empty message is hashed as a single block of zeroes.
+    let blocks: Vec<&[Expression]> =
+        if message.is_empty() { vec![&[]] } else { message.chunks(BLOCK_BYTES).collect() };
+    let num_blocks = blocks.len();
+    for (i, block) in blocks.into_iter().enumerate() {
+        let mut block_bytes = block.to_vec();
+        block_bytes.resize(BLOCK_BYTES, Expression::zero());
+
+        let block_words: Vec<UInt64> = block_bytes
+            .chunks(8)
+            .map(|word_bytes| UInt64::from_le_bytes(word_bytes, builder))
+            .collect();
+
+        // The counter holds the number of message bytes hashed so far, including those of this block.
+        let is_final_block = i == num_blocks - 1;
+        let counter = if is_final_block { message_len } else { (i + 1) * BLOCK_BYTES };
+        state = compress(&state, &block_words, counter as u128, is_final_block, builder);
+    }
+
+    for (word, digest_bytes) in state.into_iter().zip(outputs.chunks(8)) {
+        word.to_le_bytes(digest_bytes.to_vec(), builder);
+    }
+}
+
+/// Applies the Blake2b compression function `F` to the state `h` and the message block `m`.
+fn compress(
+    h: &[UInt64],
+    m: &[UInt64],
+    counter: u128,
+    is_final_block: bool,
+    builder: &mut CircuitBuilder,
+) -> Vec<UInt64> {
+    // The counter and final block flag are known at compile time so they are folded into the constants directly.
+    let mut v = h.to_vec();
+    for (i, mut word) in IV.into_iter().enumerate() {
+        match i {
+            4 => word ^= counter as u64,
+            5 => word ^= (counter >> 64) as u64,
+            6 if is_final_block => word = !word,
+            _ => (),
+        }
+        v.push(UInt64::load_constant(word, builder));
+    }
+
+    for round in 0..NUM_ROUNDS {
+        let s = &SIGMA[round % 10];
+        // Mix the columns and then the diagonals of the state.
+        for (i, [a, b, c, d]) in [
+            [0, 4, 8, 12],
+            [1, 5, 9, 13],
+            [2, 6, 10, 14],
+            [3, 7, 11, 15],
+            [0, 5, 10, 15],
+            [1, 6, 11, 12],
+            [2, 7, 8, 13],
+            [3, 4, 9, 14],
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            let (x, y) = (m[s[2 * i]], m[s[2 * i + 1]]);
+
+            v[a] = v[a].add(v[b], builder);
+            v[a] = v[a].add(x, builder);
+            v[d] = v[d].xor(v[a], builder);
+            v[d] = v[d].ror(32, builder);
+            v[c] = v[c].add(v[d], builder);
+            v[b] = v[b].xor(v[c], builder);
+            v[b] = v[b].ror(24, builder);
+            v[a] = v[a].add(v[b], builder);
+            v[a] = v[a].add(y, builder);
+            v[d] = v[d].xor(v[a], builder);
+            v[d] = v[d].ror(16, builder);
+            v[c] = v[c].add(v[d], builder);
+            v[b] = v[b].xor(v[c], builder);
+            v[b] = v[b].ror(63, builder);
+        }
+    }
+
+    let mut output = Vec::with_capacity(8);
+    for i in 0..8 {
+        let mixed = v[i].xor(v[i + 8], builder);
+        output.push(h[i].xor(mixed, builder));
+    }
+
+    output
+}
@@ -0,0 +1,180 @@
+//! Blake2s fallback function.
+use super::{uint::UInt32, utils::hash_input_bytes};
+use crate::helpers::CircuitBuilder;
+use acir::native_types::{Expression, Witness};
+
+const BLOCK_BYTES: usize = 64;
+const DIGEST_BYTES: usize = 32;
+const NUM_ROUNDS: usize = 10;
+
+const IV: [u32; 8] = [
+    0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+];
+
+/// Permutations of the message words used by each round.
+const SIGMA: [[usize; 16]; NUM_ROUNDS] = [
+    [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15],
+    [14, 10, 4, 8, 9, 15, 13, 6, 1, 12, 0, 2, 11, 7, 5, 3],
+    [11, 8, 12, 0, 5, 2, 15, 13, 10, 14, 3, 6, 7, 1, 9, 4],
+    [7, 9, 3, 1, 13, 12, 11, 14, 2, 6, 5, 10, 4, 0, 15, 8],
+    [9, 0, 5, 7, 2, 4, 10, 15, 14, 1, 11, 12, 6, 8, 3, 13],
+    [2, 12, 6, 10, 0, 11, 8, 3, 4, 13, 7, 5, 15, 14, 1, 9],
+    [12, 5, 1, 15, 14, 13, 4, 10, 0, 7, 6, 3, 9, 2, 8, 11],
+    [13, 11, 7, 14, 12, 1, 3, 9, 5, 0, 15, 4, 8, 6, 2, 10],
+    [6, 15, 14, 9, 11, 3, 0, 8, 12, 2, 13, 7, 1, 4, 10, 5],
+    [10, 2, 8, 4, 7, 6, 1, 5, 15, 11, 9, 14, 3, 12, 13, 0],
+];
+
+/// Adds the opcodes constraining `outputs` to be the unkeyed Blake2s digest of `inputs` to the builder.
+///
+/// Each input is paired with its bit size and contributes its `ceil(num_bits / 8)` bytes in little-endian order.
+pub fn blake2s(
+    inputs: Vec<(Expression, u32)>,
+    outputs: Vec<Witness>,
+    builder: &mut CircuitBuilder,
+) {
+    assert_eq!(outputs.len(), DIGEST_BYTES, "blake2s produces a 32 byte digest");
+    let bytes = hash_input_bytes(inputs, builder);
+    let message: Vec<Expression> = bytes.into_iter().map(Expression::from).collect();
+
+    let mut hasher = Blake2sHasher::new(builder);
+    hasher.update(&message, builder);
+    hasher.finalize(&outputs, builder);
+}
+
+/// Unkeyed Blake2s hasher absorbing a message through successive calls to [`Blake2sHasher::update`].
+///
+/// Message bytes are assumed to have been constrained to fit within 8 bits by the caller. As the final block
+/// is compressed differently, a complete block is only compressed once further bytes follow it. Cloning a hasher
+/// allows the state reached after a common prefix to be reused for several messages.
+#[derive(Clone, Debug)]
+pub struct Blake2sHasher {
+    state: Vec<UInt32>,
+    /// Bytes of the message which have not been compressed yet, of which there are at most a block.
+    buffer: Vec<Expression>,
+    /// Number of bytes of the message which have been compressed.
+    compressed_len: usize,
+}
+
+impl Blake2sHasher {
+    /// Returns a hasher for the empty message, adding the opcodes loading its initial state to the builder.
+    pub fn new(builder: &mut CircuitBuilder) -> Self {
+        // The parameter block of an unkeyed hash only sets the digest length and a fanout and depth of 1.
+        let state = IV
+            .into_iter()
+            .enumerate()
+            .map(|(i, word)| {
+                let word = if i == 0 { word ^ 0x01010000 ^ DIGEST_BYTES as u32 } else { word };
+                UInt32::load_constant(word, builder)
+            })
+            .collect();
+
+        Blake2sHasher { state, buffer: Vec::new(), compressed_len: 0 }
+    }
+
+    /// Appends `bytes` to the message, compressing every block which they complete and follow.
+    pub fn update(&mut self, bytes: &[Expression], builder: &mut CircuitBuilder) {
+        self.buffer.extend_from_slice(bytes);
+        while self.buffer.len() > BLOCK_BYTES {
+            let block: Vec<Expression> = self.buffer.drain(..BLOCK_BYTES).collect();
+            self.compressed_len += BLOCK_BYTES;
+            self.compress_block(block, false, builder);
+        }
+    }
+
+    /// Compresses the final block of the message and constrains `outputs` to be its 32 byte digest.
+    pub fn finalize(mut self, outputs: &[Witness], builder: &mut CircuitBuilder) {
+        assert_eq!(outputs.len(), DIGEST_BYTES, "blake2s produces a 32 byte digest");
+
+        // The final block is padded with zeroes, the empty message being hashed as a single block of zeroes.
+        let mut block = std::mem::take(&mut self.buffer);
+        self.compressed_len += block.len();
+        block.resize(BLOCK_BYTES, Expression::zero());
+        self.compress_block(block, true, builder);
+
+        for (word, digest_bytes) in self.state.iter().zip(outputs.chunks(4)) {
+            word.to_le_bytes(digest_bytes.to_vec(), builder);
+        }
+    }
+
+    fn compress_block(
+        &mut self,
+        block: Vec<Expression>,
+        is_final_block: bool,
+        builder: &mut CircuitBuilder,
+    ) {
+        let block_words: Vec<UInt32> =
+            block.chunks(4).map(|word_bytes| UInt32::from_le_bytes(word_bytes, builder)).collect();
+
+        // The counter holds the number of message bytes hashed so far, including those of this block.
+        self.state = compress(
+            &self.state,
+            &block_words,
+            self.compressed_len as u64,
+            is_final_block,
+            builder,
+        );
+    }
+}
+
+/// Applies the Blake2s compression function `F` to the state `h` and the message block `m`.
+fn compress(
+    h: &[UInt32],
+    m: &[UInt32],
+    counter: u64,
+    is_final_block: bool,
+    builder: &mut CircuitBuilder,
+) -> Vec<UInt32> {
+    // The counter and final block flag are known at compile time so they are folded into the constants directly.
+    let mut v = h.to_vec();
+    for (i, mut word) in IV.into_iter().enumerate() {
+        match i {
+            4 => word ^= counter as u32,
+            5 => word ^= (counter >> 32) as u32,
+            6 if is_final_block => word = !word,
+            _ => (),
+        }
+        v.push(UInt32::load_constant(word, builder));
+    }
+
+    for s in SIGMA {
+        // Mix the columns and then the diagonals of the state.
+        for (i, [a, b, c, d]) in [
+            [0, 4, 8, 12],
+            [1, 5, 9, 13],
+            [2, 6, 10, 14],
+            [3, 7, 11, 15],
+            [0, 5, 10, 15],
+            [1, 6, 11, 12],
+            [2, 7, 8, 13],
+            [3, 4, 9, 14],
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            let (x, y) = (m[s[2 * i]], m[s[2 * i + 1]]);
+
+            v[a] = v[a].add(v[b], builder);
+            v[a] = v[a].add(x, builder);
+            v[d] = v[d].xor(v[a], builder);
+            v[d] = v[d].ror(16, builder);
+            v[c] = v[c].add(v[d], builder);
+            v[b] = v[b].xor(v[c], builder);
+            v[b] = v[b].ror(12, builder);
+            v[a] = v[a].add(v[b], builder);
+            v[a] = v[a].add(y, builder);
+            v[d] = v[d].xor(v[a], builder);
+            v[d] = v[d].ror(8, builder);
+            v[c] = v[c].add(v[d], builder);
+            v[b] = v[b].xor(v[c], builder);
+            v[b] = v[b].ror(7, builder);
+        }
+    }
+
+    (0..8)
+        .map(|i| {
+            let mixed = v[i].xor(v[i + 8], builder);
+            h[i].xor(mixed, builder)
+        })
+        .collect()
+}
@@ -0,0 +1,177 @@
+//! Blake3 fallback function.
+use super::{uint::UInt32, utils::hash_input_bytes};
+use crate::helpers::CircuitBuilder;
+use acir::native_types::{Expression, Witness};
+
+const BLOCK_BYTES: usize = 64;
+const CHUNK_BYTES: usize = 1024;
+const NUM_ROUNDS: usize = 7;
+
+const IV: [u32; 8] = [
+    0x6A09E667, 0xBB67AE85, 0x3C6EF372, 0xA54FF53A, 0x510E527F, 0x9B05688C, 0x1F83D9AB, 0x5BE0CD19,
+];
+
+const MSG_PERMUTATION: [usize; 16] = [2, 6, 3, 10, 7, 0, 4, 13, 1, 11, 12, 5, 9, 14, 15, 8];
+
+const CHUNK_START: u32 = 1 << 0;
+const CHUNK_END: u32 = 1 << 1;
+const PARENT: u32 = 1 << 2;
+const ROOT: u32 = 1 << 3;
+
+/// Adds the opcodes constraining `outputs` to be the Blake3 digest of `inputs` to the builder.
+///
+/// Each input is paired with its bit size and contributes its `ceil(num_bits / 8)` bytes in little-endian order.
+pub fn blake3(inputs: Vec<(Expression, u32)>, outputs: Vec<Witness>, builder: &mut CircuitBuilder) {
+    assert_eq!(outputs.len(), 32, "blake3 produces a 32 byte digest");
+    let bytes = hash_input_bytes(inputs, builder);
+    let inputs: Vec<Expression> = bytes.into_iter().map(Expression::from).collect();
+
+    let key: Vec<UInt32> =
+        IV.into_iter().map(|word| UInt32::load_constant(word, builder)).collect();
+
+    // The empty message is hashed as a single empty chunk.
+    let chunks: Vec<&[Expression]> =
+        if inputs.is_empty() { vec![&[]] } else { inputs.chunks(CHUNK_BYTES).collect() };
+    let digest = hash_subtree(&key, &chunks, 0, true, builder);
+
+    for (word, digest_bytes) in digest.into_iter().zip(outputs.chunks(4)) {
+        word.to_le_bytes(digest_bytes.to_vec(), builder);
+    }
+}
+
+/// Returns the chaining value of the subtree made of `chunks`, the first of which is the chunk
+/// at index `chunk_counter` within the message.
+///
+/// The left subtree of each parent node holds the largest power of two number of chunks
+/// which leaves at least one chunk to the right subtree.
+fn hash_subtree(
+    key: &[UInt32],
+    chunks: &[&[Expression]],
+    chunk_counter: u64,
+    is_root: bool,
+    builder: &mut CircuitBuilder,
+) -> Vec<UInt32> {
+    if let [chunk] = chunks {
+        return hash_chunk(key, chunk, chunk_counter, is_root, builder);
+    }
+
+    let left_len = 1 << (usize::BITS - 1 - (chunks.len() - 1).leading_zeros());
+    let (left, right) = chunks.split_at(left_len);
+
+    let mut block = hash_subtree(key, left, chunk_counter, false, builder);
+    let right_cv = hash_subtree(key, right, chunk_counter + left_len as u64, false, builder);
+    block.extend(right_cv);
+
+    let flags = if is_root { PARENT | ROOT } else { PARENT };
+    compress(key, &block, 0, BLOCK_BYTES as u32, flags, builder)
+}
+
+/// Returns the chaining value of a chunk of at most 1024 bytes.
+fn hash_chunk(
+    key: &[UInt32],
+    chunk: &[Expression],
+    chunk_counter: u64,
+    is_root: bool,
+    builder: &mut CircuitBuilder,
+) -> Vec<UInt32> {
+    // The empty chunk is hashed as a single empty block.
+    let blocks: Vec<&[Expression]> =
+        if chunk.is_empty() { vec![&[]] } else { chunk.chunks(BLOCK_BYTES).collect() };
+    let num_blocks = blocks.len();
+
+    let mut chaining_value = key.to_vec();
+    for (i, block) in blocks.into_iter().enumerate() {
+        let mut block_bytes = block.to_vec();
+        block_bytes.resize(BLOCK_BYTES, Expression::zero());
+
+        let block_words: Vec<UInt32> = block_bytes
+            .chunks(4)
+            .map(|word_bytes| UInt32::from_le_bytes(word_bytes, builder))
+            .collect();
+
+        let mut flags = 0;
+        if i == 0 {
+            flags |= CHUNK_START;
+        }
+        if i == num_blocks - 1 {
+            flags |= CHUNK_END;
+            if is_root {
+                flags |= ROOT;
+            }
+        }
+
+        chaining_value = compress(
+            &chaining_value,
+            &block_words,
+            chunk_counter,
+            block.len() as u32,
+            flags,
+            builder,
+        );
+    }
+
+    chaining_value
+}
+
+/// Applies the Blake3 compression function, returning the first 8 words of its output.
+fn compress(
+    chaining_value: &[UInt32],
+    block_words: &[UInt32],
+    counter: u64,
+    block_len: u32,
+    flags: u32,
+    builder: &mut CircuitBuilder,
+) -> Vec<UInt32> {
+    let mut state = chaining_value.to_vec();
+    for word in
+        [IV[0], IV[1], IV[2], IV[3], counter as u32, (counter >> 32) as u32, block_len, flags]
+    {
+        state.push(UInt32::load_constant(word, builder));
+    }
+
+    let mut message = block_words.to_vec();
+    for round in 0..NUM_ROUNDS {
+        // Mix the columns and then the diagonals of the state.
+        for (i, [a, b, c, d]) in [
+            [0, 4, 8, 12],
+            [1, 5, 9, 13],
+            [2, 6, 10, 14],
+            [3, 7, 11, 15],
+            [0, 5, 10, 15],
+            [1, 6, 11, 12],
+            [2, 7, 8, 13],
+            [3, 4, 9, 14],
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            let (mx, my) = (message[2 * i], message[2 * i + 1]);
+
+            state[a] = state[a].add(state[b], builder);
+            state[a] = state[a].add(mx, builder);
+            state[d] = state[d].xor(state[a], builder);
+            state[d] = state[d].ror(16, builder);
+            state[c] = state[c].add(state[d], builder);
+            state[b] = state[b].xor(state[c], builder);
+            state[b] = state[b].ror(12, builder);
+            state[a] = state[a].add(state[b], builder);
+            state[a] = state[a].add(my, builder);
+            state[d] = state[d].xor(state[a], builder);
+            state[d] = state[d].ror(8, builder);
+            state[c] = state[c].add(state[d], builder);
+            state[b] = state[b].xor(state[c], builder);
+            state[b] = state[b].ror(7, builder);
+        }
+
+        if round < NUM_ROUNDS - 1 {
+            message = MSG_PERMUTATION.iter().map(|&i| message[i]).collect();
+        }
+    }
+
+    let mut output = Vec::with_capacity(8);
+    for i in 0..8 {
+        output.push(state[i].xor(state[i + 8], builder));
+    }
+
+    output
+}
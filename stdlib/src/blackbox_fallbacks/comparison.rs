@@ -0,0 +1,179 @@
+//! Comparison and sorting of unsigned integers of a given bit size.
+//!
+//! Operands are assumed to have been constrained to fit within their bit size, which is not checked here.
+//! Every gadget adds the opcodes constraining its result to the builder.
+use super::{
+    uint::{power_of_two, split},
+    utils::{range_opcode, to_witness, witness_from_expression},
+};
+use crate::helpers::CircuitBuilder;
+use acir::{
+    acir_field::FieldElement,
+    circuit::{directives::Directive, Opcode},
+    native_types::{Expression, Witness},
+};
+
+/// Returns a boolean witness which is 1 if `a < b`.
+pub fn less_than(
+    a: Expression,
+    b: Expression,
+    bit_size: u32,
+    builder: &mut CircuitBuilder,
+) -> Witness {
+    assert!(bit_size + 1 < FieldElement::max_num_bits(), "operands are too large to be compared");
+    // `a - b + 2^bit_size` overflows `bit_size` bits unless `a < b`.
+    let mut difference = &a - &b;
+    difference.q_c += power_of_two(bit_size);
+    let (is_greater_or_equal, _) = split(difference, bit_size, 1, builder);
+
+    let mut is_less = Expression::one();
+    is_less.push_addition_term(-FieldElement::one(), is_greater_or_equal);
+    witness_from_expression(is_less, builder)
+}
+
+/// Returns a witness equal to the greater of `a` and `b`.
+pub fn max(a: Expression, b: Expression, bit_size: u32, builder: &mut CircuitBuilder) -> Witness {
+    let is_less = less_than(a.clone(), b.clone(), bit_size, builder);
+    select(is_less, b, a, builder)
+}
+
+/// Returns a witness equal to the lesser of `a` and `b`.
+pub fn min(a: Expression, b: Expression, bit_size: u32, builder: &mut CircuitBuilder) -> Witness {
+    let is_less = less_than(a.clone(), b.clone(), bit_size, builder);
+    select(is_less, a, b, builder)
+}
+
+/// Returns witnesses holding `inputs` sorted in ascending order.
+///
+/// The outputs are routed from the inputs through a permutation network whose switches are set by
+/// a [`Directive::PermutationSort`], and adjacent outputs are then constrained to be in order.
+pub fn sort(inputs: Vec<Expression>, bit_size: u32, builder: &mut CircuitBuilder) -> Vec<Witness> {
+    let inputs: Vec<Witness> = inputs.into_iter().map(|input| to_witness(input, builder)).collect();
+
+    let switches = builder.new_witnesses(num_switches(inputs.len()));
+    builder.push(Opcode::Directive(Directive::PermutationSort {
+        inputs: inputs.iter().map(|input| vec![Expression::from(*input)]).collect(),
+        tuple: 1,
+        bits: switches.clone(),
+        sort_by: vec![0],
+    }));
+    for &switch in &switches {
+        // switch^2 - switch = 0
+        let mut constraint = Expression::default();
+        constraint.push_multiplication_term(FieldElement::one(), switch, switch);
+        constraint.push_addition_term(-FieldElement::one(), switch);
+        builder.push(Opcode::AssertZero(constraint));
+    }
+
+    let outputs = permutation_network(inputs, &switches, builder);
+
+    // As the outputs are a permutation of the inputs, they fit within `bit_size` bits
+    // and their successive differences fit as well only if they are in order.
+    for pair in outputs.windows(2) {
+        let difference = &Expression::from(pair[1]) - &Expression::from(pair[0]);
+        let difference = witness_from_expression(difference, builder);
+        builder.push(range_opcode(difference, bit_size));
+    }
+
+    outputs
+}
+
+/// Returns a witness equal to `if_true` if `condition` is 1 and to `if_false` if it is 0.
+fn select(
+    condition: Witness,
+    if_true: Expression,
+    if_false: Expression,
+    builder: &mut CircuitBuilder,
+) -> Witness {
+    // The difference is multiplied by the condition within a single opcode, which requires it to be linear.
+    let mut difference = &if_true - &if_false;
+    if !difference.is_linear() {
+        difference = witness_from_expression(difference, builder).into();
+    }
+
+    // result = if_false + condition * (if_true - if_false)
+    let selection = (&Expression::from(condition) * &difference).expect("operands have degree one");
+    witness_from_expression(&selection + &if_false, builder)
+}
+
+/// Returns the number of switches in a permutation network over `n` wires.
+pub(super) fn num_switches(n: usize) -> usize {
+    if n <= 1 {
+        return 0;
+    }
+    n / 2 + (n - 1) / 2 + num_switches(n / 2) + num_switches(n - n / 2)
+}
+
+/// Routes `inputs` through the permutation network configured by `switches`, returning its outputs.
+///
+/// The network is laid out as expected by the solver of [`Directive::PermutationSort`]: an outer layer of
+/// switches on the inputs, then an outer layer on the outputs, followed by the switches of the upper
+/// and lower sub-networks, which are defined recursively.
+pub(super) fn permutation_network(
+    inputs: Vec<Witness>,
+    switches: &[Witness],
+    builder: &mut CircuitBuilder,
+) -> Vec<Witness> {
+    let n = inputs.len();
+    if n <= 1 {
+        return inputs;
+    }
+
+    let mut upper_inputs = Vec::with_capacity(n / 2);
+    let mut lower_inputs = Vec::with_capacity(n - n / 2);
+    for (i, pair) in inputs.chunks_exact(2).enumerate() {
+        let (upper, lower) = switch(switches[i], pair[0], pair[1], builder);
+        upper_inputs.push(upper);
+        lower_inputs.push(lower);
+    }
+    if n % 2 == 1 {
+        lower_inputs.push(inputs[n - 1]);
+    }
+
+    let outer_switches = n / 2 + (n - 1) / 2;
+    let (upper_switches, lower_switches) = switches[outer_switches..].split_at(num_switches(n / 2));
+    let upper_outputs = permutation_network(upper_inputs, upper_switches, builder);
+    let lower_outputs = permutation_network(lower_inputs, lower_switches, builder);
+
+    let mut outputs = Vec::with_capacity(n);
+    for i in 0..(n - 1) / 2 {
+        let (first, second) =
+            switch(switches[n / 2 + i], upper_outputs[i], lower_outputs[i], builder);
+        outputs.push(first);
+        outputs.push(second);
+    }
+    if n % 2 == 0 {
+        outputs.push(upper_outputs[n / 2 - 1]);
+    }
+    outputs.push(lower_outputs[lower_outputs.len() - 1]);
+
+    outputs
+}
+
+/// Returns the outputs of a switch which swaps `x` and `y` if `control` is 1.
+fn switch(
+    control: Witness,
+    x: Witness,
+    y: Witness,
+    builder: &mut CircuitBuilder,
+) -> (Witness, Witness) {
+    let first = builder.new_witness();
+    let second = builder.new_witness();
+
+    // first = x + control * (y - x)
+    let mut first_constraint = Expression::from(x);
+    first_constraint.push_multiplication_term(FieldElement::one(), control, y);
+    first_constraint.push_multiplication_term(-FieldElement::one(), control, x);
+    first_constraint.push_addition_term(-FieldElement::one(), first);
+    first_constraint.sort();
+    builder.push(Opcode::AssertZero(first_constraint));
+
+    // second = x + y - first
+    let mut second_constraint = &Expression::from(x) + &Expression::from(y);
+    second_constraint.push_addition_term(-FieldElement::one(), first);
+    second_constraint.push_addition_term(-FieldElement::one(), second);
+    second_constraint.sort();
+    builder.push(Opcode::AssertZero(second_constraint));
+
+    (first, second)
+}
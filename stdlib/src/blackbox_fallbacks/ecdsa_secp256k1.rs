@@ -0,0 +1,250 @@
+//! ECDSA verification fallback over the secp256k1 curve.
+//!
+//! Both `u1 * G` and `u2 * Q` are computed at once with a double-and-add over the bits of the scalars,
+//! adding either `G`, `Q` or `G + Q` at each step. Affine formulas are used throughout, the accumulator starting
+//! from a fixed offset point which is subtracted at the end so that it never reaches the point at infinity.
+//!
+//! The incomplete addition formulas constrain the points they add to have distinct x coordinates, which only fails
+//! for an honest prover in negligibly rare cases, or when the public key is `±G`
+//! or the signature leads to the point at infinity. These are unsatisfiable, whereas the native
+//! implementation reports the signature to be invalid.
+use super::{
+    big_field::{BigField, BigFieldElement, ProductSum},
+    utils::{is_zero, range_opcode, to_witness, witness_from_expression},
+};
+use crate::helpers::CircuitBuilder;
+use acir::{
+    acir_field::FieldElement,
+    circuit::Opcode,
+    native_types::{Expression, Witness},
+};
+use num_bigint::BigUint;
+
+/// Modulus of the field over which the curve `y^2 = x^3 + 7` is defined.
+const FIELD_MODULUS: &str = "fffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc2f";
+/// Order of the group of points of the curve.
+const GROUP_ORDER: &str = "fffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364141";
+const GENERATOR_X: &str = "79be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798";
+const GENERATOR_Y: &str = "483ada7726a3c4655da4fbfc0e1108a8fd17b448a68554199c47d08ffb10d4b8";
+
+const NUM_SCALAR_BITS: usize = 256;
+
+/// Adds the opcodes constraining `output` to be one if `signature` is a valid ECDSA signature of `hashed_message`
+/// by the public key with coordinates `public_key_x` and `public_key_y`, and zero otherwise.
+///
+/// The coordinates and both components of the signature are given as 32 big-endian bytes, while only the leftmost
+/// 32 bytes of the message hash are used. The public key is constrained to be a point on the curve.
+/// As in the native implementation, signatures whose `s` component is greater than half the group order are invalid.
+pub fn ecdsa_secp256k1(
+    public_key_x: Vec<Expression>,
+    public_key_y: Vec<Expression>,
+    signature: Vec<Expression>,
+    hashed_message: Vec<Expression>,
+    output: Witness,
+    builder: &mut CircuitBuilder,
+) {
+    assert_eq!(public_key_x.len(), 32, "public key coordinates are made of 32 bytes");
+    assert_eq!(public_key_y.len(), 32, "public key coordinates are made of 32 bytes");
+    assert_eq!(signature.len(), 64, "signatures are made of 64 bytes");
+
+    let base_field = BigField::new(parse_hex(FIELD_MODULUS));
+    let scalar_field = BigField::new(parse_hex(GROUP_ORDER));
+    let group_order = scalar_field.modulus();
+
+    let mut to_element = |bytes: &[Expression]| {
+        let bytes = to_bytes(bytes, builder);
+        BigFieldElement::from_be_bytes(&bytes, builder)
+    };
+    let public_key = Point { x: to_element(&public_key_x), y: to_element(&public_key_y) };
+    let r = to_element(&signature[..32]);
+    let s = to_element(&signature[32..]);
+    let z = to_element(&hashed_message[..hashed_message.len().min(32)]);
+
+    // The public key must be a point on the curve, with canonical coordinates.
+    for coordinate in [&public_key.x, &public_key.y] {
+        coordinate.assert_less_than(base_field.modulus(), builder);
+    }
+    let x_squared =
+        base_field.evaluate(&ProductSum::new().product(1, &public_key.x, &public_key.x), builder);
+    let curve_equation = ProductSum::new()
+        .product(1, &public_key.y, &public_key.y)
+        .product(-1, &x_squared, &public_key.x)
+        .term(-7, &BigFieldElement::constant(&BigUint::from(1_u32)));
+    base_field.assert_zero(&curve_equation, builder);
+
+    // Both components of the signature must lie within `[1, n)`, with `s` at most `n / 2`.
+    let r_is_valid = is_within_range(&r, group_order, builder);
+    let s_is_valid = is_within_range(&s, &(group_order / 2_u32 + 1_u32), builder);
+    let mut both_valid = Expression::default();
+    both_valid.push_multiplication_term(FieldElement::one(), r_is_valid, s_is_valid);
+    let is_valid = witness_from_expression(both_valid, builder);
+
+    // Invalid components are replaced by one so that the rest of the circuit remains satisfiable.
+    let one = BigFieldElement::constant(&BigUint::from(1_u32));
+    let r = BigFieldElement::select(is_valid, &r, &one, builder);
+    let s = BigFieldElement::select(is_valid, &s, &one, builder);
+
+    // `s` is not congruent to zero, so it can be divided by.
+    let s = ProductSum::new().term(1, &s);
+    let u1 = scalar_field.divide(&ProductSum::new().term(1, &z), &s, builder);
+    let u2 = scalar_field.divide(&ProductSum::new().term(1, &r), &s, builder);
+    let u1_bits = u1.to_le_bits(builder);
+    let u2_bits = u2.to_le_bits(builder);
+
+    let generator = (parse_hex(GENERATOR_X), parse_hex(GENERATOR_Y));
+    let offset = offset_point(base_field.modulus());
+    let final_offset = (0..NUM_SCALAR_BITS)
+        .fold(offset.clone(), |point, _| native_double(base_field.modulus(), &point));
+
+    let generator_point = Point::constant(&generator);
+    let sum_point = add(&base_field, &generator_point, &public_key, builder);
+    let mut accumulator = Point::constant(&offset);
+    for (u1_bit, u2_bit) in u1_bits.into_iter().zip(u2_bits).rev() {
+        accumulator = double(&base_field, &accumulator, builder);
+
+        // Selects `G` if only `u1_bit` is set, `Q` if only `u2_bit` is, and `G + Q` if both are.
+        let mut both_bits = Expression::default();
+        both_bits.push_multiplication_term(FieldElement::one(), u1_bit, u2_bit);
+        let both_bits = witness_from_expression(both_bits, builder);
+        let addend = Point::select(u2_bit, &public_key, &generator_point, builder);
+        let addend = Point::select(both_bits, &sum_point, &addend, builder);
+        let sum = add(&base_field, &accumulator, &addend, builder);
+
+        let mut any_bit = Expression::from(u1_bit);
+        any_bit.push_addition_term(FieldElement::one(), u2_bit);
+        any_bit.push_addition_term(-FieldElement::one(), both_bits);
+        let any_bit = witness_from_expression(any_bit, builder);
+        accumulator = Point::select(any_bit, &sum, &accumulator, builder);
+    }
+
+    let negated_offset =
+        (final_offset.0, base_field.modulus() - final_offset.1 % base_field.modulus());
+    let result = add(&base_field, &accumulator, &Point::constant(&negated_offset), builder);
+
+    // The signature is valid if the x coordinate of the result is congruent to `r` modulo the group order.
+    let x = base_field.reduce(&result.x, builder);
+    let x = scalar_field.reduce(&x, builder);
+    let x_matches = x.is_equal(&r, builder);
+
+    let mut output_constraint = Expression::default();
+    output_constraint.push_multiplication_term(FieldElement::one(), is_valid, x_matches);
+    output_constraint.push_addition_term(-FieldElement::one(), output);
+    builder.push(Opcode::AssertZero(output_constraint));
+}
+
+/// Returns witnesses holding `bytes`, each constrained to fit within 8 bits.
+fn to_bytes(bytes: &[Expression], builder: &mut CircuitBuilder) -> Vec<Witness> {
+    bytes
+        .iter()
+        .map(|byte| {
+            let witness = to_witness(byte.clone(), builder);
+            builder.push(range_opcode(witness, 8));
+            witness
+        })
+        .collect()
+}
+
+/// Returns a witness equal to one if `value` lies within `[1, bound)` and zero otherwise.
+fn is_within_range(
+    value: &BigFieldElement,
+    bound: &BigUint,
+    builder: &mut CircuitBuilder,
+) -> Witness {
+    let is_less_than = value.is_less_than(bound, builder);
+    // Limbs fit within 64 bits, so their sum cannot wrap around and is only zero if they all are.
+    let limb_sum = value.limbs().iter().fold(Expression::default(), |sum, limb| &sum + limb);
+    let is_zero = is_zero(limb_sum, builder);
+
+    let mut is_within_range = Expression::from(is_less_than);
+    is_within_range.push_multiplication_term(-FieldElement::one(), is_less_than, is_zero);
+    witness_from_expression(is_within_range, builder)
+}
+
+/// A point of the curve other than the point at infinity.
+#[derive(Clone, Debug)]
+struct Point {
+    x: BigFieldElement,
+    y: BigFieldElement,
+}
+
+impl Point {
+    fn constant((x, y): &NativePoint) -> Self {
+        Point { x: BigFieldElement::constant(x), y: BigFieldElement::constant(y) }
+    }
+
+    /// Returns `if_true` if `condition` is one and `if_false` if it is zero.
+    fn select(
+        condition: Witness,
+        if_true: &Point,
+        if_false: &Point,
+        builder: &mut CircuitBuilder,
+    ) -> Point {
+        let x = BigFieldElement::select(condition, &if_true.x, &if_false.x, builder);
+        let y = BigFieldElement::select(condition, &if_true.y, &if_false.y, builder);
+        Point { x, y }
+    }
+}
+
+/// Returns `point + point`.
+///
+/// The curve has no point of order two, so the y coordinate of `point` is never zero.
+fn double(field: &BigField, point: &Point, builder: &mut CircuitBuilder) -> Point {
+    let slope = field.divide(
+        &ProductSum::new().product(3, &point.x, &point.x),
+        &ProductSum::new().term(2, &point.y),
+        builder,
+    );
+    let x =
+        field.evaluate(&ProductSum::new().product(1, &slope, &slope).term(-2, &point.x), builder);
+    let y = field.evaluate(
+        &ProductSum::new().product(1, &slope, &point.x).product(-1, &slope, &x).term(-1, &point.y),
+        builder,
+    );
+    Point { x, y }
+}
+
+/// Returns `lhs + rhs`, constraining both points to have distinct x coordinates.
+fn add(field: &BigField, lhs: &Point, rhs: &Point, builder: &mut CircuitBuilder) -> Point {
+    let x_difference = ProductSum::new().term(1, &rhs.x).term(-1, &lhs.x);
+    field.assert_not_zero(&x_difference, builder);
+
+    let slope =
+        field.divide(&ProductSum::new().term(1, &rhs.y).term(-1, &lhs.y), &x_difference, builder);
+    let x = field.evaluate(
+        &ProductSum::new().product(1, &slope, &slope).term(-1, &lhs.x).term(-1, &rhs.x),
+        builder,
+    );
+    let y = field.evaluate(
+        &ProductSum::new().product(1, &slope, &lhs.x).product(-1, &slope, &x).term(-1, &lhs.y),
+        builder,
+    );
+    Point { x, y }
+}
+
+/// Affine coordinates of a point of the curve, used to derive the constant points of the circuit.
+type NativePoint = (BigUint, BigUint);
+
+/// Returns the point with x coordinate one and an even y coordinate, from which the accumulator starts.
+fn offset_point(modulus: &BigUint) -> NativePoint {
+    let x = BigUint::from(1_u32);
+    let y_squared = (x.pow(3) + 7_u32) % modulus;
+    // The modulus is congruent to 3 modulo 4, so square roots are obtained by exponentiation.
+    let mut y = y_squared.modpow(&((modulus + 1_u32) / 4_u32), modulus);
+    assert_eq!((&y * &y) % modulus, y_squared, "x = 1 is the x coordinate of a point");
+    if y.bit(0) {
+        y = modulus - y;
+    }
+    (x, y)
+}
+
+fn native_double(modulus: &BigUint, (x, y): &NativePoint) -> NativePoint {
+    let inverse = |value: BigUint| value.modpow(&(modulus - 2_u32), modulus);
+    let slope = BigUint::from(3_u32) * x * x * inverse(BigUint::from(2_u32) * y) % modulus;
+    let new_x = (&slope * &slope + (modulus - x) * 2_u32) % modulus;
+    let new_y = (slope * (x + modulus - &new_x) + modulus - y) % modulus;
+    (new_x, new_y)
+}
+
+fn parse_hex(hex: &str) -> BigUint {
+    BigUint::parse_bytes(hex.as_bytes(), 16).expect("constants are valid hexadecimal")
+}
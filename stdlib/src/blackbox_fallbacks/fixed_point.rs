@@ -0,0 +1,132 @@
+//! Arithmetic over unsigned fixed-point numbers with a configurable number of fractional bits.
+//!
+//! A fixed-point number `x` is held in a single witness as the integer `x * 2^fractional_bits`. Operands are assumed
+//! to have been constrained to fit within the bit size of their format, which is not checked here. Every gadget adds
+//! the opcodes constraining its result to the builder, including to fit within the format, so that a result which
+//! overflows or underflows leaves the circuit unsatisfiable rather than wrapping around.
+use super::{
+    comparison,
+    uint::{power_of_two, split},
+    utils::{range_opcode, to_witness, witness_from_expression},
+};
+use crate::helpers::CircuitBuilder;
+use acir::{
+    acir_field::FieldElement,
+    native_types::{Expression, Witness},
+};
+
+/// The number of integer and fractional bits of fixed-point numbers.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FixedPointFormat {
+    pub integer_bits: u32,
+    pub fractional_bits: u32,
+}
+
+impl FixedPointFormat {
+    pub fn new(integer_bits: u32, fractional_bits: u32) -> Self {
+        // The product of two numbers, before being rescaled, must not wrap around the field.
+        assert!(
+            2 * (integer_bits + fractional_bits) + 1 < FieldElement::max_num_bits(),
+            "fixed-point numbers are too large to be multiplied"
+        );
+        FixedPointFormat { integer_bits, fractional_bits }
+    }
+
+    /// Returns the number of bits of the witnesses holding numbers of this format.
+    pub fn num_bits(&self) -> u32 {
+        self.integer_bits + self.fractional_bits
+    }
+
+    /// Returns the value of the witness holding `integer` as a number of this format.
+    pub fn from_integer(&self, integer: u128) -> FieldElement {
+        FieldElement::from(integer) * power_of_two(self.fractional_bits)
+    }
+}
+
+/// How the digits dropped when rescaling the result of a fixed-point operation are rounded.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Rounding {
+    /// Rounds towards zero, truncating the dropped digits.
+    Down,
+    /// Rounds away from zero unless the dropped digits are all zero.
+    Up,
+    /// Rounds to the nearest number, ties rounding away from zero.
+    NearestTiesUp,
+}
+
+/// Returns a witness equal to `a + b`, failing should the sum not fit within `format`.
+pub fn add(
+    a: Expression,
+    b: Expression,
+    format: FixedPointFormat,
+    builder: &mut CircuitBuilder,
+) -> Witness {
+    let sum = witness_from_expression(&a + &b, builder);
+    builder.push(range_opcode(sum, format.num_bits()));
+    sum
+}
+
+/// Returns a witness equal to `a - b`, failing should `b` be greater than `a`.
+pub fn sub(
+    a: Expression,
+    b: Expression,
+    format: FixedPointFormat,
+    builder: &mut CircuitBuilder,
+) -> Witness {
+    // A negative difference wraps around the field, and so does not fit within the format.
+    let difference = witness_from_expression(&a - &b, builder);
+    builder.push(range_opcode(difference, format.num_bits()));
+    difference
+}
+
+/// Returns a witness equal to `a * b` rounded following `rounding`, failing should the product not fit within `format`.
+pub fn mul(
+    a: Expression,
+    b: Expression,
+    format: FixedPointFormat,
+    rounding: Rounding,
+    builder: &mut CircuitBuilder,
+) -> Witness {
+    let a = to_witness(a, builder);
+    let b = to_witness(b, builder);
+    let mut product = Expression::default();
+    product.push_multiplication_term(FieldElement::one(), a, b);
+
+    // The product has twice as many fractional bits as its operands, of which the lower half are dropped.
+    if format.fractional_bits == 0 {
+        let product = witness_from_expression(product, builder);
+        builder.push(range_opcode(product, format.num_bits()));
+        return product;
+    }
+    product.q_c = match rounding {
+        Rounding::Down => FieldElement::zero(),
+        Rounding::Up => power_of_two(format.fractional_bits) - FieldElement::one(),
+        Rounding::NearestTiesUp => power_of_two(format.fractional_bits - 1),
+    };
+    let (rescaled, _) = split(product, format.fractional_bits, format.num_bits(), builder);
+    rescaled
+}
+
+/// Returns a boolean witness which is 1 if `a < b`.
+pub fn less_than(
+    a: Expression,
+    b: Expression,
+    format: FixedPointFormat,
+    builder: &mut CircuitBuilder,
+) -> Witness {
+    // Numbers of the same format compare as the integers holding them.
+    comparison::less_than(a, b, format.num_bits(), builder)
+}
+
+/// Returns a boolean witness which is 1 if `a <= b`.
+pub fn less_than_or_equal(
+    a: Expression,
+    b: Expression,
+    format: FixedPointFormat,
+    builder: &mut CircuitBuilder,
+) -> Witness {
+    let is_greater = less_than(b, a, format, builder);
+    let mut is_less_or_equal = Expression::one();
+    is_less_or_equal.push_addition_term(-FieldElement::one(), is_greater);
+    witness_from_expression(is_less_or_equal, builder)
+}
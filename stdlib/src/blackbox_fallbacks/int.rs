@@ -0,0 +1,119 @@
+//! Signed integers of a fixed bit size held in a single witness in two's complement.
+use super::{
+    comparison,
+    uint::{power_of_two, split, UInt32, UInt64, UInt8},
+    utils::witness_from_expression,
+};
+use crate::helpers::CircuitBuilder;
+use acir::native_types::{Expression, Witness};
+
+/// Defines a signed integer type of `$num_bits` bits held in a single witness in two's complement,
+/// along with its gadgets.
+///
+/// Signed integers share their representation with the unsigned integer type `$unsigned`, whose gadgets
+/// are reused wherever two's complement arithmetic matches unsigned arithmetic. Constants are loaded
+/// through `$unsigned_type`, the native type of `$unsigned`.
+macro_rules! impl_int {
+    ($name:ident, $type:ty, $unsigned:ident, $unsigned_type:ty, $num_bits:expr) => {
+        #[doc = concat!("A ", stringify!($num_bits), "-bit signed integer held in a single witness in two's complement.")]
+        ///
+        /// The witness is assumed to have been constrained to fit within the integer's bit size. Every
+        /// operation adds the opcodes constraining its result to the builder.
+        #[derive(Clone, Copy, Debug)]
+        pub struct $name {
+            pub inner: Witness,
+        }
+
+        impl $name {
+            const NUM_BITS: u32 = $num_bits;
+
+            pub fn new(witness: Witness) -> Self {
+                $name { inner: witness }
+            }
+
+            #[doc = concat!("Reinterprets the bits of a `", stringify!($unsigned), "` as a signed integer.")]
+            pub fn from_unsigned(value: $unsigned) -> Self {
+                $name::new(value.inner)
+            }
+
+            #[doc = concat!("Reinterprets the bits of this integer as a `", stringify!($unsigned), "`.")]
+            pub fn to_unsigned(self) -> $unsigned {
+                $unsigned::new(self.inner)
+            }
+
+            #[doc = concat!("Returns a `", stringify!($name), "` constrained to be equal to `constant`.")]
+            pub fn load_constant(constant: $type, builder: &mut CircuitBuilder) -> $name {
+                $name::from_unsigned($unsigned::load_constant(constant as $unsigned_type, builder))
+            }
+
+            /// Returns `self + rhs`, wrapping around on overflow.
+            pub fn add(self, rhs: $name, builder: &mut CircuitBuilder) -> $name {
+                $name::from_unsigned(self.to_unsigned().add(rhs.to_unsigned(), builder))
+            }
+
+            /// Returns `self - rhs`, wrapping around on overflow.
+            pub fn sub(self, rhs: $name, builder: &mut CircuitBuilder) -> $name {
+                $name::from_unsigned(self.to_unsigned().sub(rhs.to_unsigned(), builder))
+            }
+
+            /// Returns `-self`, wrapping around for the minimum value.
+            pub fn neg(self, builder: &mut CircuitBuilder) -> $name {
+                $name::load_constant(0, builder).sub(self, builder)
+            }
+
+            /// Returns a boolean witness which is 1 if `self` is negative.
+            pub fn is_negative(self, builder: &mut CircuitBuilder) -> Witness {
+                let (sign, _) = split(self.inner.into(), Self::NUM_BITS - 1, 1, builder);
+                sign
+            }
+
+            /// Returns a boolean witness which is 1 if `self < rhs`.
+            pub fn less_than(self, rhs: $name, builder: &mut CircuitBuilder) -> Witness {
+                let lhs_offset = self.offset(builder);
+                let rhs_offset = rhs.offset(builder);
+
+                // Offset integers compare as unsigned integers.
+                comparison::less_than(lhs_offset, rhs_offset, Self::NUM_BITS, builder)
+            }
+
+            /// Returns an expression equal to the value of `self` offset by `2^(NUM_BITS - 1)`,
+            /// which maps the signed range onto `[0, 2^NUM_BITS)` while preserving order.
+            fn offset(self, builder: &mut CircuitBuilder) -> Expression {
+                let sign = self.is_negative(builder);
+                // Flipping the sign bit adds `2^(NUM_BITS - 1)` to the value of non-negative integers
+                // and subtracts it from negative ones.
+                let mut offset = Expression::from(self.inner);
+                offset.push_addition_term(-power_of_two(Self::NUM_BITS), sign);
+                offset.q_c += power_of_two(Self::NUM_BITS - 1);
+                offset
+            }
+
+            /// Returns `self << shift`, wrapping around on overflow.
+            pub fn shl(self, shift: u32, builder: &mut CircuitBuilder) -> $name {
+                $name::from_unsigned(self.to_unsigned().shl(shift, builder))
+            }
+
+            /// Returns `self >> shift`, filling the vacated high bits with the sign bit.
+            pub fn shr(self, shift: u32, builder: &mut CircuitBuilder) -> $name {
+                if shift == 0 {
+                    return self;
+                }
+                let sign = self.is_negative(builder);
+                let shifted = self.to_unsigned().shr(shift, builder);
+
+                // Negative integers have their `shift` high bits set,
+                // i.e. `2^NUM_BITS - 2^(NUM_BITS - shift)` is added to them.
+                let mut result = Expression::from(shifted.inner);
+                result.push_addition_term(
+                    power_of_two(Self::NUM_BITS) - power_of_two(Self::NUM_BITS - shift),
+                    sign,
+                );
+                $name::new(witness_from_expression(result, builder))
+            }
+        }
+    };
+}
+
+impl_int!(Int8, i8, UInt8, u8, 8);
+impl_int!(Int32, i32, UInt32, u32, 32);
+impl_int!(Int64, i64, UInt64, u64, 64);
@@ -0,0 +1,285 @@
+//! Keccak256 fallback function.
+use super::{
+    uint::UInt64,
+    utils::{hash_input_bytes, range_opcode, to_witness},
+    variable_length::{select, MessageLength},
+};
+use crate::helpers::CircuitBuilder;
+use acir::{
+    acir_field::FieldElement,
+    circuit::Opcode,
+    native_types::{Expression, Witness},
+};
+
+/// Number of bytes absorbed into the state by each application of the permutation.
+const RATE_BYTES: usize = 136;
+const NUM_ROUNDS: usize = 24;
+
+const ROUND_CONSTANTS: [u64; NUM_ROUNDS] = [
+    0x0000000000000001,
+    0x0000000000008082,
+    0x800000000000808A,
+    0x8000000080008000,
+    0x000000000000808B,
+    0x0000000080000001,
+    0x8000000080008081,
+    0x8000000000008009,
+    0x000000000000008A,
+    0x0000000000000088,
+    0x0000000080008009,
+    0x000000008000000A,
+    0x000000008000808B,
+    0x800000000000008B,
+    0x8000000000008089,
+    0x8000000000008003,
+    0x8000000000008002,
+    0x8000000000000080,
+    0x000000000000800A,
+    0x800000008000000A,
+    0x8000000080008081,
+    0x8000000000008080,
+    0x0000000080000001,
+    0x8000000080008008,
+];
+
+/// Rotation offsets of the rho step, indexed by `x + 5 * y`.
+const ROTATIONS: [u32; 25] = [
+    0, 1, 62, 28, 27, //
+    36, 44, 6, 55, 20, //
+    3, 10, 43, 25, 39, //
+    41, 45, 15, 21, 8, //
+    18, 2, 61, 56, 14,
+];
+
+/// Adds the opcodes constraining `outputs` to be the Keccak256 digest of `inputs` to the builder.
+///
+/// Each input is paired with its bit size and contributes its `ceil(num_bits / 8)` bytes in little-endian order.
+pub fn keccak256(
+    inputs: Vec<(Expression, u32)>,
+    outputs: Vec<Witness>,
+    builder: &mut CircuitBuilder,
+) {
+    assert_eq!(outputs.len(), 32, "keccak256 produces a 32 byte digest");
+    let bytes = hash_input_bytes(inputs, builder);
+    let message: Vec<Expression> = bytes.into_iter().map(Expression::from).collect();
+
+    let mut hasher = Keccak256Hasher::new(builder);
+    hasher.update(&message, builder);
+    hasher.finalize(&outputs, builder);
+}
+
+/// Keccak256 hasher absorbing a message through successive calls to [`Keccak256Hasher::update`].
+///
+/// Message bytes are assumed to have been constrained to fit within 8 bits by the caller. Each block is
+/// absorbed as soon as it is complete, so cloning a hasher allows the state reached after a common prefix
+/// to be reused for several messages.
+#[derive(Clone, Debug)]
+pub struct Keccak256Hasher {
+    /// The state after absorbing the complete blocks of the message, if there are any.
+    state: Option<Vec<UInt64>>,
+    /// Bytes of the message which do not fill a block yet.
+    buffer: Vec<Expression>,
+}
+
+impl Keccak256Hasher {
+    /// Returns a hasher for the empty message.
+    ///
+    /// The initial state is zero and is never loaded, so nothing is added to the builder.
+    /// It is taken nonetheless for consistency with the other hashers.
+    pub fn new(_builder: &mut CircuitBuilder) -> Self {
+        Keccak256Hasher { state: None, buffer: Vec::new() }
+    }
+
+    /// Appends `bytes` to the message, absorbing every block they complete.
+    pub fn update(&mut self, bytes: &[Expression], builder: &mut CircuitBuilder) {
+        self.buffer.extend_from_slice(bytes);
+        while self.buffer.len() >= RATE_BYTES {
+            let block: Vec<Expression> = self.buffer.drain(..RATE_BYTES).collect();
+            self.state = Some(absorb_block(self.state.take(), &block, builder));
+        }
+    }
+
+    /// Pads the message and constrains `outputs` to be its 32 byte digest.
+    pub fn finalize(mut self, outputs: &[Witness], builder: &mut CircuitBuilder) {
+        assert_eq!(outputs.len(), 32, "keccak256 produces a 32 byte digest");
+        let final_block = pad(std::mem::take(&mut self.buffer));
+        let state = absorb_block(self.state, &final_block, builder);
+
+        // The digest is made of the first 4 lanes of the state.
+        for (lane, digest_bytes) in state.iter().zip(outputs.chunks(8)) {
+            lane.to_le_bytes(digest_bytes.to_vec(), builder);
+        }
+    }
+}
+
+/// Adds the opcodes constraining `outputs` to be the Keccak256 digest of the first `var_message_size` bytes
+/// of `inputs`, whose bytes are laid out as in [`keccak256`], to the builder.
+///
+/// Every block which may hold the end of the message is absorbed, the digest being read from the state
+/// after the block which actually does.
+pub fn keccak256_variable_length(
+    inputs: Vec<(Expression, u32)>,
+    var_message_size: Expression,
+    outputs: Vec<Witness>,
+    builder: &mut CircuitBuilder,
+) {
+    assert_eq!(outputs.len(), 32, "keccak256 produces a 32 byte digest");
+
+    let bytes = hash_input_bytes(inputs, builder);
+    let max_length = bytes.len();
+    let length = MessageLength::new(var_message_size, max_length, builder);
+
+    // The message ends in the block holding its first padding byte.
+    let num_blocks = max_length / RATE_BYTES + 1;
+    let is_final_block: Vec<Witness> = (0..num_blocks)
+        .map(|block| length.is_within(block * RATE_BYTES..(block + 1) * RATE_BYTES, builder))
+        .collect();
+
+    let mut padded_message = Vec::with_capacity(num_blocks * RATE_BYTES);
+    for index in 0..num_blocks * RATE_BYTES {
+        let mut byte = match bytes.get(index) {
+            Some(&byte) => length.mask(index, byte),
+            None => Expression::zero(),
+        };
+        byte = &byte + &length.equals(index);
+        if index % RATE_BYTES == RATE_BYTES - 1 {
+            byte = byte
+                .add_mul(FieldElement::from(0x80_u128), &is_final_block[index / RATE_BYTES].into());
+        }
+        padded_message.push(byte);
+    }
+
+    let states = absorb(padded_message, builder);
+
+    // The digest is made of the first 4 lanes of the state.
+    for (lane, digest_bytes) in outputs.chunks(8).enumerate() {
+        let candidates: Vec<Witness> = states.iter().map(|state| state[lane].inner).collect();
+        let selected = select(&is_final_block, &candidates, builder);
+        UInt64::new(selected).to_le_bytes(digest_bytes.to_vec(), builder);
+    }
+}
+
+/// Pads the message with the keccak `pad10*1` rule to a multiple of the rate.
+fn pad(mut message: Vec<Expression>) -> Vec<Expression> {
+    let message_len = message.len();
+    let padded_len = (message_len / RATE_BYTES + 1) * RATE_BYTES;
+    message.resize(padded_len, Expression::zero());
+    message[message_len] = Expression::one();
+    message[padded_len - 1] =
+        &message[padded_len - 1] + &Expression::from_field(FieldElement::from(0x80_u128));
+    message
+}
+
+/// Absorbs the padded message into the keccak state, returning the state after the permutation of each block.
+fn absorb(padded_message: Vec<Expression>, builder: &mut CircuitBuilder) -> Vec<Vec<UInt64>> {
+    let mut states: Vec<Vec<UInt64>> = Vec::new();
+    for block in padded_message.chunks(RATE_BYTES) {
+        let state = absorb_block(states.last().cloned(), block, builder);
+        states.push(state);
+    }
+    states
+}
+
+/// Absorbs a block of the padded message into `state`, returning the state after the permutation.
+///
+/// A missing state stands for the initial state of the sponge.
+fn absorb_block(
+    state: Option<Vec<UInt64>>,
+    block: &[Expression],
+    builder: &mut CircuitBuilder,
+) -> Vec<UInt64> {
+    let mut lanes: Vec<UInt64> =
+        block.chunks(8).map(|lane_bytes| UInt64::from_le_bytes(lane_bytes, builder)).collect();
+
+    let block_state = match state {
+        // The initial state is zero so the first block can be used as is.
+        None => {
+            for _ in lanes.len()..25 {
+                lanes.push(UInt64::load_constant(0, builder));
+            }
+            lanes
+        }
+        Some(mut state) => {
+            for (lane, block_lane) in state.iter_mut().zip(lanes) {
+                *lane = lane.xor(block_lane, builder);
+            }
+            state
+        }
+    };
+
+    permute(block_state, builder)
+}
+
+/// Adds the opcodes constraining `outputs` to be the keccak-f[1600] permutation of the state `inputs`
+/// to the builder.
+///
+/// Both states are made of 25 lanes of 64 bits, indexed by `x + 5 * y`.
+pub fn keccakf1600(inputs: Vec<Expression>, outputs: Vec<Witness>, builder: &mut CircuitBuilder) {
+    assert_eq!(inputs.len(), 25, "keccakf1600 operates on a state of 25 lanes");
+    assert_eq!(outputs.len(), 25, "keccakf1600 operates on a state of 25 lanes");
+
+    let mut state = Vec::with_capacity(25);
+    for input in inputs {
+        let witness = to_witness(input, builder);
+        // Lanes are only trusted to fit within 64 bits once they are range constrained.
+        builder.push(range_opcode(witness, 64));
+        state.push(UInt64::new(witness));
+    }
+
+    let state = permute(state, builder);
+
+    for (lane, output) in state.into_iter().zip(outputs) {
+        let mut constraint = Expression::from(lane.inner);
+        constraint.push_addition_term(-FieldElement::one(), output);
+        constraint.sort();
+        builder.push(Opcode::AssertZero(constraint));
+    }
+}
+
+/// Applies the keccak-f[1600] permutation to `state`, a list of 25 lanes indexed by `x + 5 * y`.
+fn permute(mut state: Vec<UInt64>, builder: &mut CircuitBuilder) -> Vec<UInt64> {
+    assert_eq!(state.len(), 25, "keccak state is made of 25 lanes");
+
+    for round_constant in ROUND_CONSTANTS {
+        // Theta
+        let mut column_parities = Vec::with_capacity(5);
+        for x in 0..5 {
+            let mut parity = state[x];
+            for y in 1..5 {
+                parity = parity.xor(state[x + 5 * y], builder);
+            }
+            column_parities.push(parity);
+        }
+        for x in 0..5 {
+            let rotated = column_parities[(x + 1) % 5].rol(1, builder);
+            let d = column_parities[(x + 4) % 5].xor(rotated, builder);
+            for y in 0..5 {
+                state[x + 5 * y] = state[x + 5 * y].xor(d, builder);
+            }
+        }
+
+        // Rho and pi
+        let mut permuted = state.clone();
+        for x in 0..5 {
+            for y in 0..5 {
+                let lane = state[x + 5 * y];
+                permuted[y + 5 * ((2 * x + 3 * y) % 5)] = lane.rol(ROTATIONS[x + 5 * y], builder);
+            }
+        }
+
+        // Chi
+        for y in 0..5 {
+            for x in 0..5 {
+                let not_next = permuted[(x + 1) % 5 + 5 * y].not(builder);
+                let and = not_next.and(permuted[(x + 2) % 5 + 5 * y], builder);
+                state[x + 5 * y] = permuted[x + 5 * y].xor(and, builder);
+            }
+        }
+
+        // Iota
+        let round_constant = UInt64::load_constant(round_constant, builder);
+        state[0] = state[0].xor(round_constant, builder);
+    }
+
+    state
+}
@@ -0,0 +1,221 @@
+//! Lowering of memory blocks into arithmetic and range constraints, for backends without native memory support.
+//!
+//! The operations of the trace are tagged with their position and sorted by index and then by position.
+//! Within the sorted trace, every index is first accessed by a write and every read returns the value of
+//! the operation preceding it, which is the latest operation on that index.
+use super::{
+    comparison::{num_switches, permutation_network},
+    utils::{is_zero, range_opcode, to_witness, witness_from_expression},
+};
+use crate::helpers::CircuitBuilder;
+use acir::{
+    acir_field::FieldElement,
+    brillig_vm::{self, BinaryFieldOp, BinaryIntOp, RegisterIndex, Value},
+    circuit::{
+        brillig::{Brillig, BrilligInputs, BrilligOutputs},
+        directives::Directive,
+        opcodes::MemoryBlock,
+        Opcode,
+    },
+    native_types::{Expression, Witness},
+};
+
+/// Replaces the memory operations of `block` with constraints checking the consistency of its trace.
+///
+/// Operations are reads when their `operation` is the constant zero, the value of which is assigned
+/// by a Brillig opcode replaying the preceding writes. The values of other operations must be solvable
+/// without the memory block.
+pub fn memory_block(block: &MemoryBlock, builder: &mut CircuitBuilder) {
+    let num_operations = block.trace.len();
+    if num_operations == 0 {
+        return;
+    }
+
+    // Each operation is the tuple `(index, position, operation, value)`.
+    let mut columns: [Vec<Witness>; 4] = Default::default();
+    for (position, op) in block.trace.iter().enumerate() {
+        let operation = to_witness(op.operation.clone(), builder);
+        if !op.operation.is_const() {
+            boolean_constraint(operation, builder);
+        }
+        let value = if op.operation.is_zero() {
+            read_value(&op.index, &columns, builder)
+        } else {
+            to_witness(op.value.clone(), builder)
+        };
+        if op.operation.is_zero() {
+            builder.push(Opcode::AssertZero(&op.value - &Expression::from(value)));
+        }
+        let position = Expression::from_field(FieldElement::from(position as u128));
+        columns[0].push(to_witness(op.index.clone(), builder));
+        columns[1].push(to_witness(position, builder));
+        columns[2].push(operation);
+        columns[3].push(value);
+    }
+
+    let switches = builder.new_witnesses(num_switches(num_operations));
+    builder.push(Opcode::Directive(Directive::PermutationSort {
+        inputs: (0..num_operations)
+            .map(|i| columns.iter().map(|column| Expression::from(column[i])).collect())
+            .collect(),
+        tuple: 4,
+        bits: switches.clone(),
+        sort_by: vec![0, 1],
+    }));
+    for &switch in &switches {
+        boolean_constraint(switch, builder);
+    }
+    // Every column is routed through the same network, so that the tuples are kept together.
+    let [indices, positions, operations, values] =
+        columns.map(|column| permutation_network(column, &switches, builder));
+
+    // Differences between successive indices or positions are smaller than both the block and the trace.
+    let max_value = std::cmp::max(block.len as usize, num_operations);
+    let num_bits = usize::BITS - max_value.leading_zeros();
+
+    // The sorted indices lie within the block.
+    builder.push(range_opcode(indices[0], num_bits));
+    // An empty block cannot be accessed, in which case the difference is negative and fails its range constraint.
+    let mut last_index =
+        Expression::from_field(FieldElement::from(block.len as u128) - FieldElement::one());
+    last_index.push_addition_term(-FieldElement::one(), indices[num_operations - 1]);
+    let last_index = witness_from_expression(last_index, builder);
+    builder.push(range_opcode(last_index, num_bits));
+
+    // The first operation of the sorted trace is a write.
+    let mut first_operation = Expression::from(operations[0]);
+    first_operation.q_c = -FieldElement::one();
+    builder.push(Opcode::AssertZero(first_operation));
+
+    for j in 0..num_operations - 1 {
+        let (prev, next) = (j, j + 1);
+        let same_index =
+            is_zero(&Expression::from(indices[next]) - &Expression::from(indices[prev]), builder);
+
+        // Ordering: `(1 - s) * (next_index - prev_index - 1) + s * (next_position - prev_position - 1)`
+        // fits within `num_bits`, where `s` is one if both operations share the same index.
+        let mut ordering = &Expression::from(indices[next]) - &Expression::from(indices[prev]);
+        ordering.q_c = -FieldElement::one();
+        ordering.push_multiplication_term(FieldElement::one(), same_index, positions[next]);
+        ordering.push_multiplication_term(-FieldElement::one(), same_index, positions[prev]);
+        ordering.push_multiplication_term(-FieldElement::one(), same_index, indices[next]);
+        ordering.push_multiplication_term(FieldElement::one(), same_index, indices[prev]);
+        ordering.sort();
+        let ordering = witness_from_expression(ordering, builder);
+        builder.push(range_opcode(ordering, num_bits));
+
+        // A new index is first accessed by a write: `(1 - s) * (1 - next_operation) = 0`.
+        let mut first_access = Expression::one();
+        first_access.push_addition_term(-FieldElement::one(), same_index);
+        first_access.push_addition_term(-FieldElement::one(), operations[next]);
+        first_access.push_multiplication_term(FieldElement::one(), same_index, operations[next]);
+        first_access.sort();
+        builder.push(Opcode::AssertZero(first_access));
+
+        // A read returns the previous value: `s * (1 - next_operation) * (next_value - prev_value) = 0`.
+        let mut is_read = Expression::from(same_index);
+        is_read.push_multiplication_term(-FieldElement::one(), same_index, operations[next]);
+        is_read.sort();
+        let is_read = witness_from_expression(is_read, builder);
+        let mut consistency = Expression::default();
+        consistency.push_multiplication_term(FieldElement::one(), is_read, values[next]);
+        consistency.push_multiplication_term(-FieldElement::one(), is_read, values[prev]);
+        consistency.sort();
+        builder.push(Opcode::AssertZero(consistency));
+    }
+}
+
+/// Constrains `witness` to be either zero or one.
+fn boolean_constraint(witness: Witness, builder: &mut CircuitBuilder) {
+    // witness^2 - witness = 0
+    let mut constraint = Expression::default();
+    constraint.push_multiplication_term(FieldElement::one(), witness, witness);
+    constraint.push_addition_term(-FieldElement::one(), witness);
+    builder.push(Opcode::AssertZero(constraint));
+}
+
+/// Returns a witness assigned to the value at `index` after the operations of `columns`.
+///
+/// The value is only computed by a Brillig opcode, the constraints on the sorted trace ensuring that it is correct.
+fn read_value(
+    index: &Expression,
+    columns: &[Vec<Witness>; 4],
+    builder: &mut CircuitBuilder,
+) -> Witness {
+    let value = builder.new_witness();
+    let operations = (0..columns[0].len())
+        .flat_map(|i| [columns[2][i], columns[0][i], columns[3][i]])
+        .map(Expression::from)
+        .collect::<Vec<_>>();
+    builder.push(Opcode::Brillig(Brillig {
+        inputs: vec![BrilligInputs::Single(index.clone()), BrilligInputs::Array(operations)],
+        outputs: vec![BrilligOutputs::Simple(value)],
+        foreign_call_results: vec![],
+        bytecode: read_value_bytecode(columns[0].len()),
+        predicate: None,
+    }));
+    value
+}
+
+/// Returns the bytecode replaying `num_operations` operations to compute the value at the index in register 0.
+///
+/// The operations are stored in memory from the pointer in register 1 as `(operation, index, value)` triples,
+/// and the value is returned in register 0.
+fn read_value_bytecode(num_operations: usize) -> Vec<brillig_vm::Opcode> {
+    const POINTER_BIT_SIZE: u32 = 32;
+    let register = RegisterIndex::from;
+    let (index, pointer, result, end, one, condition, operation, op_index, op_value) = (
+        register(0),
+        register(1),
+        register(2),
+        register(3),
+        register(4),
+        register(5),
+        register(6),
+        register(7),
+        register(8),
+    );
+    let field_op =
+        |op, lhs, rhs, destination| brillig_vm::Opcode::BinaryFieldOp { destination, op, lhs, rhs };
+    let int_op = |op, lhs, rhs, destination| brillig_vm::Opcode::BinaryIntOp {
+        destination,
+        op,
+        bit_size: POINTER_BIT_SIZE,
+        lhs,
+        rhs,
+    };
+    let load_next = |destination| {
+        [
+            brillig_vm::Opcode::Load { destination, source_pointer: pointer },
+            int_op(BinaryIntOp::Add, pointer, one, pointer),
+        ]
+    };
+
+    let loop_start = 4;
+    let loop_end = 18;
+    let mut bytecode = vec![
+        brillig_vm::Opcode::Const { destination: result, value: Value::from(0_u128) },
+        brillig_vm::Opcode::Const { destination: end, value: Value::from(3 * num_operations) },
+        int_op(BinaryIntOp::Add, pointer, end, end),
+        brillig_vm::Opcode::Const { destination: one, value: Value::from(1_u128) },
+        // loop_start:
+        int_op(BinaryIntOp::LessThan, pointer, end, condition),
+        brillig_vm::Opcode::JumpIfNot { condition, location: loop_end },
+    ];
+    bytecode.extend(load_next(operation));
+    bytecode.extend(load_next(op_index));
+    bytecode.extend(load_next(op_value));
+    bytecode.extend([
+        // result += operation * (op_index == index) * (op_value - result)
+        field_op(BinaryFieldOp::Equals, op_index, index, condition),
+        field_op(BinaryFieldOp::Mul, condition, operation, condition),
+        field_op(BinaryFieldOp::Sub, op_value, result, op_value),
+        field_op(BinaryFieldOp::Mul, op_value, condition, op_value),
+        field_op(BinaryFieldOp::Add, result, op_value, result),
+        brillig_vm::Opcode::Jump { location: loop_start },
+        // loop_end:
+        brillig_vm::Opcode::Mov { destination: index, source: result },
+    ]);
+    debug_assert_eq!(bytecode.len(), loop_end + 1);
+    bytecode
+}
@@ -0,0 +1,30 @@
+//! Fallback implementations of black box functions built out of arithmetic opcodes,
+//! directives and the `AND`, `XOR` and `RANGE` black box functions.
+mod aes128;
+pub mod big_field;
+mod blake2b;
+mod blake2s;
+mod blake3;
+pub mod comparison;
+mod ecdsa_secp256k1;
+pub mod fixed_point;
+pub mod int;
+mod keccak256;
+pub mod memory;
+mod poseidon2;
+pub mod radix;
+mod sha256;
+mod sha512;
+pub mod uint;
+mod utils;
+mod variable_length;
+
+pub use aes128::aes128_encrypt;
+pub use blake2b::blake2b;
+pub use blake2s::{blake2s, Blake2sHasher};
+pub use blake3::blake3;
+pub use ecdsa_secp256k1::ecdsa_secp256k1;
+pub use keccak256::{keccak256, keccak256_variable_length, keccakf1600, Keccak256Hasher};
+pub use poseidon2::{poseidon2_permutation, poseidon2_supports_width};
+pub use sha256::{sha256, sha256_compression, Sha256Hasher};
+pub use sha512::{sha512, sha512_variable_length};
@@ -0,0 +1,242 @@
+//! Poseidon2 permutation fallback function.
+//!
+//! Parameters follow the reference implementation of the Poseidon2 paper: an S-box of degree 5,
+//! 8 full rounds and 56 partial rounds. Rather than being hardcoded, the round constants are generated
+//! with the Grain LFSR for the field [`FieldElement`] is defined over, so they match the constants of the
+//! native solver over BN254.
+use std::collections::VecDeque;
+
+use super::utils::witness_from_expression;
+use crate::helpers::CircuitBuilder;
+use acir::{
+    acir_field::{FieldElement, FieldOptions, CHOSEN_FIELD},
+    circuit::Opcode,
+    native_types::{Expression, Witness},
+};
+use num_bigint::BigUint;
+
+const NUM_FULL_ROUNDS: usize = 8;
+const NUM_PARTIAL_ROUNDS: usize = 56;
+
+/// Diagonal of the internal matrix minus the identity for a state of width 4 over BN254.
+///
+/// Unlike the round constants, the reference implementation only keeps a randomly sampled diagonal
+/// once it passes a series of security checks, so it is not generated here.
+const INTERNAL_MATRIX_DIAGONAL_4_BN254: [&str; 4] = [
+    "0x10dc6e9c006ea38b04b1e03b4bd9490c0d03f98929ca1d7fb56821fd19d3b6e7",
+    "0x0c28145b6a44df3e0149b3d0a30b3bb599df9756d4dd9b84a86b38cfb45a740b",
+    "0x00544b8338791518b2c7645a50392798b21f75bb60e3596170067d00141cac15",
+    "0x222c01175718386f2e2e82eb122789e352e105a3b8fa852613bc534433ee428b",
+];
+
+/// Adds the opcodes constraining `outputs` to be the Poseidon2 permutation of the state `inputs` to the builder.
+///
+/// Panics if the state width is not supported, see [`poseidon2_supports_width`].
+pub fn poseidon2_permutation(
+    inputs: Vec<Expression>,
+    outputs: Vec<Witness>,
+    builder: &mut CircuitBuilder,
+) {
+    assert_eq!(inputs.len(), outputs.len(), "poseidon2 outputs a state of the same width");
+    let poseidon2 = Poseidon2::new(inputs.len()).unwrap_or_else(|| {
+        panic!("poseidon2 does not support a state width of {} over this field", inputs.len())
+    });
+
+    let state = poseidon2.permutation(inputs, builder);
+    for (element, output) in state.iter().zip(outputs) {
+        builder.push(Opcode::AssertZero(element - output));
+    }
+}
+
+/// Returns whether [`poseidon2_permutation`] supports states of `width` elements over the field in use.
+pub fn poseidon2_supports_width(width: usize) -> bool {
+    internal_matrix_diagonal(width).is_some()
+}
+
+struct Poseidon2 {
+    round_constants: Vec<Vec<FieldElement>>,
+    /// Diagonal of the internal matrix minus the identity.
+    internal_matrix_diagonal: Vec<FieldElement>,
+}
+
+impl Poseidon2 {
+    fn new(width: usize) -> Option<Self> {
+        let internal_matrix_diagonal = internal_matrix_diagonal(width)?;
+
+        // Full rounds add a constant to each element of the state while partial rounds only add one to the first.
+        let half_full_rounds = NUM_FULL_ROUNDS / 2;
+        let mut grain = GrainLfsr::new(width);
+        let round_constants = (0..NUM_FULL_ROUNDS + NUM_PARTIAL_ROUNDS)
+            .map(|round| {
+                let is_partial_round =
+                    (half_full_rounds..half_full_rounds + NUM_PARTIAL_ROUNDS).contains(&round);
+                let num_constants = if is_partial_round { 1 } else { width };
+                (0..num_constants).map(|_| grain.next_field_element()).collect()
+            })
+            .collect();
+
+        Some(Poseidon2 { round_constants, internal_matrix_diagonal })
+    }
+
+    fn permutation(
+        &self,
+        mut state: Vec<Expression>,
+        builder: &mut CircuitBuilder,
+    ) -> Vec<Expression> {
+        let half_full_rounds = NUM_FULL_ROUNDS / 2;
+        external_matrix_multiplication(&mut state);
+        for (round, round_constants) in self.round_constants.iter().enumerate() {
+            let is_partial_round =
+                (half_full_rounds..half_full_rounds + NUM_PARTIAL_ROUNDS).contains(&round);
+            for (element, round_constant) in state.iter_mut().zip(round_constants) {
+                let sbox_input = element.clone() + *round_constant;
+                *element = sbox(sbox_input, builder).into();
+            }
+
+            if is_partial_round {
+                self.internal_matrix_multiplication(&mut state);
+                // Each element would otherwise accumulate a term for the output of every partial round.
+                for element in state.iter_mut() {
+                    let value = std::mem::take(element);
+                    *element = witness_from_expression(value, builder).into();
+                }
+            } else {
+                external_matrix_multiplication(&mut state);
+            }
+        }
+
+        state
+    }
+
+    fn internal_matrix_multiplication(&self, state: &mut [Expression]) {
+        let sum = state.iter().fold(Expression::zero(), |sum, element| &sum + element);
+        for (element, diagonal) in state.iter_mut().zip(&self.internal_matrix_diagonal) {
+            *element = &(&*element * *diagonal) + &sum;
+        }
+    }
+}
+
+fn external_matrix_multiplication(state: &mut [Expression]) {
+    if let [x0, x1, x2, x3] = state {
+        // Multiplication by the matrix
+        // [5, 7, 1, 3]
+        // [4, 6, 1, 1]
+        // [1, 3, 5, 7]
+        // [1, 1, 4, 6]
+        let two = FieldElement::from(2_u128);
+        let four = FieldElement::from(4_u128);
+        let t0 = &*x0 + &*x1;
+        let t1 = &*x2 + &*x3;
+        let t2 = &(&*x1 * two) + &t1;
+        let t3 = &(&*x3 * two) + &t0;
+        let t4 = &(&t1 * four) + &t3;
+        let t5 = &(&t0 * four) + &t2;
+        *x0 = &t3 + &t5;
+        *x1 = t5;
+        *x2 = &t2 + &t4;
+        *x3 = t4;
+    } else {
+        // Multiplication by the circulant matrix circ(2, 1, 1).
+        let sum = state.iter().fold(Expression::zero(), |sum, element| &sum + element);
+        state.iter_mut().for_each(|element| *element = &*element + &sum);
+    }
+}
+
+/// Returns a witness constrained to `x^5`.
+fn sbox(x: Expression, builder: &mut CircuitBuilder) -> Witness {
+    // `x` is squared within a single opcode, which requires it to depend on a single witness.
+    let x =
+        if x.is_degree_one_univariate() { x } else { witness_from_expression(x, builder).into() };
+
+    let mut multiply = |lhs: &Expression, rhs: &Expression| {
+        let product = builder.new_witness();
+        let mut constraint = (lhs * rhs).expect("operands have degree one");
+        constraint.push_addition_term(-FieldElement::one(), product);
+        constraint.sort();
+        builder.push(Opcode::AssertZero(constraint));
+        product
+    };
+    let x2 = multiply(&x, &x).into();
+    let x4 = multiply(&x2, &x2).into();
+    multiply(&x4, &x)
+}
+
+/// Returns the diagonal of the internal matrix minus the identity for a state of `width` elements,
+/// or `None` if this width is not supported over the field in use.
+fn internal_matrix_diagonal(width: usize) -> Option<Vec<FieldElement>> {
+    match width {
+        3 => Some(vec![FieldElement::one(), FieldElement::one(), FieldElement::from(2_u128)]),
+        4 if matches!(CHOSEN_FIELD, FieldOptions::BN254) => Some(
+            INTERNAL_MATRIX_DIAGONAL_4_BN254
+                .iter()
+                .map(|hex| FieldElement::from_hex(hex).expect("constants should be valid"))
+                .collect(),
+        ),
+        _ => None,
+    }
+}
+
+/// The Grain LFSR used by the reference implementation to sample round constants.
+struct GrainLfsr {
+    bits: VecDeque<bool>,
+    field_bits: u32,
+    modulus: BigUint,
+}
+
+impl GrainLfsr {
+    fn new(width: usize) -> Self {
+        let field_bits = FieldElement::max_num_bits();
+
+        // The 80 bit state is seeded with the parameters of the permutation: a prime field, an `x^alpha` S-box,
+        // the size of the field, the width of the state and the number of full and partial rounds.
+        // The remaining bits are all set.
+        let parameters = [
+            (1, 2),
+            (0, 4),
+            (field_bits as usize, 12),
+            (width, 12),
+            (NUM_FULL_ROUNDS, 10),
+            (NUM_PARTIAL_ROUNDS, 10),
+        ];
+        let mut bits: VecDeque<bool> = parameters
+            .into_iter()
+            .flat_map(|(value, num_bits)| (0..num_bits).rev().map(move |i| (value >> i) & 1 == 1))
+            .collect();
+        bits.extend([true; 30]);
+
+        let mut grain = GrainLfsr { bits, field_bits, modulus: FieldElement::modulus() };
+        // The first 160 bits are discarded.
+        for _ in 0..160 {
+            grain.update();
+        }
+        grain
+    }
+
+    fn update(&mut self) -> bool {
+        let new_bit = [62, 51, 38, 23, 13, 0].into_iter().fold(false, |bit, i| bit ^ self.bits[i]);
+        self.bits.pop_front();
+        self.bits.push_back(new_bit);
+        new_bit
+    }
+
+    /// Bits are generated in pairs, the second bit being output only if the first one is set.
+    fn next_bit(&mut self) -> bool {
+        loop {
+            if self.update() {
+                return self.update();
+            }
+            self.update();
+        }
+    }
+
+    /// Samples integers of the bit size of the field until one of them lies below the modulus.
+    fn next_field_element(&mut self) -> FieldElement {
+        loop {
+            let value = (0..self.field_bits)
+                .fold(BigUint::default(), |value, _| (value << 1_u32) + u32::from(self.next_bit()));
+            if value < self.modulus {
+                return FieldElement::from_be_bytes_reduce(&value.to_bytes_be());
+            }
+        }
+    }
+}
@@ -0,0 +1,141 @@
+//! Decomposition of values into digits in some radix, and composition of digits back into values.
+//!
+//! Digits are written to witnesses provided by the caller, each of which is constrained to be less than the radix.
+//! A decomposition into `n` digits constrains the value to be less than `radix^n`, however it is only unique
+//! if `radix^n` does not exceed the field modulus, as the digits are otherwise free to compose into a value
+//! which wraps around it.
+use super::utils::{range_opcode, witness_from_expression};
+use crate::helpers::CircuitBuilder;
+use acir::{
+    acir_field::FieldElement,
+    circuit::{directives::Directive, Opcode},
+    native_types::{Expression, Witness},
+};
+
+/// Order in which the digits of a value are laid out.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Endianness {
+    /// The least significant digit comes first.
+    Little,
+    /// The most significant digit comes first.
+    Big,
+}
+
+/// Returns the number of bytes needed to hold `num_bits` bits.
+pub fn round_to_nearest_byte(num_bits: u32) -> u32 {
+    (num_bits + 7) / 8
+}
+
+/// Returns `num_bits` rounded up to the nearest multiple of 8.
+pub fn round_to_nearest_mul_8(num_bits: u32) -> u32 {
+    round_to_nearest_byte(num_bits) * 8
+}
+
+/// Returns the expression equal to the integer whose digits in base `radix` are `digits`.
+///
+/// As no directive is involved, the radix may be as large as the field allows, e.g. `2^64` to compose limbs.
+pub fn compose(digits: &[Expression], radix: FieldElement, endianness: Endianness) -> Expression {
+    let mut multiplier = FieldElement::one();
+    let mut composed = Expression::default();
+    for digit in le_ordered(digits, endianness) {
+        composed = &composed + &(digit * multiplier);
+        multiplier = multiplier * radix;
+    }
+    composed
+}
+
+/// Generates opcodes to decompose `value` into its digits in base `radix`, which are written to the witnesses `digits`.
+///
+/// The radix must be at least 2. Digits are range constrained, which requires a new witness
+/// for each of them if the radix is not a power of two.
+pub fn radix_decomposition(
+    value: Expression,
+    digits: &[Witness],
+    radix: u32,
+    endianness: Endianness,
+    builder: &mut CircuitBuilder,
+) {
+    assert!(radix >= 2, "radix must be at least 2");
+    builder.push(to_radix_directive(value.clone(), digits, radix, endianness));
+
+    // Digits fitting within `num_bits` bits are less than a radix which is not a power of two
+    // if they still fit once offset by the difference between the radix and `2^num_bits`.
+    let num_bits = u32::BITS - (radix - 1).leading_zeros();
+    let offset = FieldElement::from(((1_u64 << num_bits) - radix as u64) as u128);
+    for digit in digits {
+        builder.push(range_opcode(*digit, num_bits));
+        if !radix.is_power_of_two() {
+            let offset_digit = witness_from_expression(Expression::from(*digit) + offset, builder);
+            builder.push(range_opcode(offset_digit, num_bits));
+        }
+    }
+
+    builder.push(recomposition(value, digits, FieldElement::from(radix as u128), endianness));
+}
+
+/// Generates opcodes to decompose `value` into bytes, which are written to the witnesses `bytes`.
+///
+/// Each byte is range constrained and the bytes are constrained to recompose into `value`,
+/// which is therefore constrained to fit within `bytes.len()` bytes.
+pub fn byte_decomposition(
+    value: Expression,
+    bytes: &[Witness],
+    endianness: Endianness,
+    builder: &mut CircuitBuilder,
+) {
+    builder.push(to_radix_directive(value.clone(), bytes, 256, endianness));
+    builder.extend(bytes.iter().map(|byte| range_opcode(*byte, 8)));
+    builder.push(recomposition(value, bytes, FieldElement::from(256_u128), endianness));
+}
+
+/// Generates opcodes to decompose `value` into bits, which are written to the witnesses `bits`.
+///
+/// Each bit is constrained to be zero or one by an arithmetic opcode rather than a range constraint.
+pub fn bit_decomposition(
+    value: Expression,
+    bits: &[Witness],
+    endianness: Endianness,
+    builder: &mut CircuitBuilder,
+) {
+    builder.push(to_radix_directive(value.clone(), bits, 2, endianness));
+    for &bit in bits {
+        // bit^2 - bit = 0
+        let mut constraint = Expression::default();
+        constraint.push_multiplication_term(FieldElement::one(), bit, bit);
+        constraint.push_addition_term(-FieldElement::one(), bit);
+        builder.push(Opcode::AssertZero(constraint));
+    }
+    builder.push(recomposition(value, bits, FieldElement::from(2_u128), endianness));
+}
+
+/// Returns the directive which sets `digits` to the digits of `value` in base `radix`.
+fn to_radix_directive(
+    value: Expression,
+    digits: &[Witness],
+    radix: u32,
+    endianness: Endianness,
+) -> Opcode {
+    let b = le_ordered(digits, endianness).copied().collect();
+    Opcode::Directive(Directive::ToLeRadix { a: value, b, radix })
+}
+
+/// Returns the opcode constraining `digits` to recompose into `value`.
+fn recomposition(
+    value: Expression,
+    digits: &[Witness],
+    radix: FieldElement,
+    endianness: Endianness,
+) -> Opcode {
+    let digits: Vec<Expression> = digits.iter().copied().map(Expression::from).collect();
+    let mut constraint = &value - &compose(&digits, radix, endianness);
+    constraint.sort();
+    Opcode::AssertZero(constraint)
+}
+
+/// Returns an iterator over `digits` starting from the least significant one.
+fn le_ordered<T>(digits: &[T], endianness: Endianness) -> Box<dyn Iterator<Item = &T> + '_> {
+    match endianness {
+        Endianness::Little => Box::new(digits.iter()),
+        Endianness::Big => Box::new(digits.iter().rev()),
+    }
+}
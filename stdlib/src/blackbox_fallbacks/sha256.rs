@@ -0,0 +1,237 @@
+//! SHA256 fallback functions.
+use super::{
+    uint::UInt32,
+    utils::{hash_input_bytes, range_opcode, to_witness},
+};
+use crate::helpers::CircuitBuilder;
+use acir::{
+    acir_field::FieldElement,
+    circuit::Opcode,
+    native_types::{Expression, Witness},
+};
+
+const BLOCK_BYTES: usize = 64;
+/// Number of bytes taken by the length of the message at the end of the padding.
+const LENGTH_BYTES: usize = 8;
+const DIGEST_BYTES: usize = 32;
+const NUM_ROUNDS: usize = 64;
+
+const INITIAL_STATE: [u32; 8] = [
+    0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+];
+
+const ROUND_CONSTANTS: [u32; NUM_ROUNDS] = [
+    0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+    0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+    0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+    0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+    0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+    0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+    0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+    0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+];
+
+/// Adds the opcodes constraining `outputs` to be the SHA256 digest of `inputs` to the builder.
+///
+/// Each input is paired with its bit size and contributes its `ceil(num_bits / 8)` bytes in little-endian order.
+pub fn sha256(inputs: Vec<(Expression, u32)>, outputs: Vec<Witness>, builder: &mut CircuitBuilder) {
+    assert_eq!(outputs.len(), DIGEST_BYTES, "sha256 produces a 32 byte digest");
+    let bytes = hash_input_bytes(inputs, builder);
+    let message: Vec<Expression> = bytes.into_iter().map(Expression::from).collect();
+
+    let mut hasher = Sha256Hasher::new(builder);
+    hasher.update(&message, builder);
+    hasher.finalize(&outputs, builder);
+}
+
+/// SHA256 hasher absorbing a message through successive calls to [`Sha256Hasher::update`].
+///
+/// Message bytes are assumed to have been constrained to fit within 8 bits by the caller. Each block is
+/// compressed as soon as it is complete, so cloning a hasher allows the state reached after a common prefix
+/// to be reused for several messages.
+#[derive(Clone, Debug)]
+pub struct Sha256Hasher {
+    state: Vec<UInt32>,
+    /// Bytes of the message which do not fill a block yet.
+    buffer: Vec<Expression>,
+    message_len: usize,
+}
+
+impl Sha256Hasher {
+    /// Returns a hasher for the empty message, adding the opcodes loading its initial state to the builder.
+    pub fn new(builder: &mut CircuitBuilder) -> Self {
+        let state = INITIAL_STATE
+            .into_iter()
+            .map(|constant| UInt32::load_constant(constant, builder))
+            .collect();
+        Sha256Hasher { state, buffer: Vec::new(), message_len: 0 }
+    }
+
+    /// Appends `bytes` to the message, compressing every block they complete.
+    pub fn update(&mut self, bytes: &[Expression], builder: &mut CircuitBuilder) {
+        self.message_len += bytes.len();
+        self.absorb(bytes, builder);
+    }
+
+    /// Pads the message and constrains `outputs` to be its 32 byte digest.
+    pub fn finalize(mut self, outputs: &[Witness], builder: &mut CircuitBuilder) {
+        assert_eq!(outputs.len(), DIGEST_BYTES, "sha256 produces a 32 byte digest");
+
+        // A single set bit is followed by zeroes and the 64-bit big-endian length of the message in bits.
+        let message_bits = self.message_len as u64 * 8;
+        let mut padding = vec![Expression::from_field(FieldElement::from(0x80_u128))];
+        while (self.buffer.len() + padding.len()) % BLOCK_BYTES != BLOCK_BYTES - LENGTH_BYTES {
+            padding.push(Expression::zero());
+        }
+        padding.extend(
+            message_bits
+                .to_be_bytes()
+                .into_iter()
+                .map(|byte| Expression::from_field(FieldElement::from(byte as u128))),
+        );
+        self.absorb(&padding, builder);
+
+        // The digest is the big-endian encoding of the state.
+        for (word, digest_bytes) in self.state.iter().zip(outputs.chunks(4)) {
+            word.to_le_bytes(digest_bytes.iter().rev().copied().collect(), builder);
+        }
+    }
+
+    fn absorb(&mut self, bytes: &[Expression], builder: &mut CircuitBuilder) {
+        self.buffer.extend_from_slice(bytes);
+        while self.buffer.len() >= BLOCK_BYTES {
+            let block: Vec<Expression> = self.buffer.drain(..BLOCK_BYTES).collect();
+
+            // Words are read from the block in big-endian order.
+            let words = block
+                .chunks(4)
+                .map(|word_bytes| {
+                    let word_bytes: Vec<Expression> = word_bytes.iter().rev().cloned().collect();
+                    UInt32::from_le_bytes(&word_bytes, builder)
+                })
+                .collect();
+
+            let state = std::mem::take(&mut self.state);
+            self.state = compress_block(state, words, builder);
+        }
+    }
+}
+
+/// Adds the opcodes constraining `outputs` to be the result of applying the SHA256 compression
+/// function to the intermediate hash `hash_values` and the message block `inputs` to the builder.
+///
+/// The block is made of 16 words of 32 bits and the hash of 8 words of 32 bits.
+pub fn sha256_compression(
+    inputs: Vec<Expression>,
+    hash_values: Vec<Expression>,
+    outputs: Vec<Witness>,
+    builder: &mut CircuitBuilder,
+) {
+    assert_eq!(inputs.len(), 16, "sha256 compresses blocks of 16 words");
+    assert_eq!(hash_values.len(), 8, "sha256 state is made of 8 words");
+    assert_eq!(outputs.len(), 8, "sha256 state is made of 8 words");
+
+    let mut words = Vec::with_capacity(inputs.len() + hash_values.len());
+    for input in inputs.into_iter().chain(hash_values) {
+        let witness = to_witness(input, builder);
+        // Words are only trusted to fit within 32 bits once they are range constrained.
+        builder.push(range_opcode(witness, 32));
+        words.push(UInt32::new(witness));
+    }
+    let state = words.split_off(16);
+
+    let state = compress_block(state, words, builder);
+
+    for (word, output) in state.into_iter().zip(outputs) {
+        let mut constraint = Expression::from(word.inner);
+        constraint.push_addition_term(-FieldElement::one(), output);
+        constraint.sort();
+        builder.push(Opcode::AssertZero(constraint));
+    }
+}
+
+/// Applies the SHA256 compression function to `state` for the block made of the 16 words `block`.
+fn compress_block(
+    mut state: Vec<UInt32>,
+    block: Vec<UInt32>,
+    builder: &mut CircuitBuilder,
+) -> Vec<UInt32> {
+    // Message schedule
+    let mut schedule = block;
+    for t in 16..NUM_ROUNDS {
+        let (w2, w15) = (schedule[t - 2], schedule[t - 15]);
+        let s1 = {
+            let r17 = w2.ror(17, builder);
+            let r19 = w2.ror(19, builder);
+            let s10 = w2.shr(10, builder);
+            let x = r17.xor(r19, builder);
+            x.xor(s10, builder)
+        };
+        let s0 = {
+            let r7 = w15.ror(7, builder);
+            let r18 = w15.ror(18, builder);
+            let s3 = w15.shr(3, builder);
+            let x = r7.xor(r18, builder);
+            x.xor(s3, builder)
+        };
+        let sum = s1.add(schedule[t - 7], builder);
+        let sum = sum.add(s0, builder);
+        let word = sum.add(schedule[t - 16], builder);
+        schedule.push(word);
+    }
+
+    // Compression
+    let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h]: [UInt32; 8] =
+        state.clone().try_into().expect("sha256 state is made of 8 words");
+    for (round_constant, word) in ROUND_CONSTANTS.into_iter().zip(schedule) {
+        let big_sigma1 = {
+            let r6 = e.ror(6, builder);
+            let r11 = e.ror(11, builder);
+            let r25 = e.ror(25, builder);
+            let x = r6.xor(r11, builder);
+            x.xor(r25, builder)
+        };
+        let choice = {
+            let e_and_f = e.and(f, builder);
+            let not_e = e.not(builder);
+            let not_e_and_g = not_e.and(g, builder);
+            e_and_f.xor(not_e_and_g, builder)
+        };
+        let round_constant = UInt32::load_constant(round_constant, builder);
+        let temp1 = h.add(big_sigma1, builder);
+        let temp1 = temp1.add(choice, builder);
+        let temp1 = temp1.add(round_constant, builder);
+        let temp1 = temp1.add(word, builder);
+
+        let big_sigma0 = {
+            let r2 = a.ror(2, builder);
+            let r13 = a.ror(13, builder);
+            let r22 = a.ror(22, builder);
+            let x = r2.xor(r13, builder);
+            x.xor(r22, builder)
+        };
+        let majority = {
+            let a_and_b = a.and(b, builder);
+            let a_and_c = a.and(c, builder);
+            let b_and_c = b.and(c, builder);
+            let x = a_and_b.xor(a_and_c, builder);
+            x.xor(b_and_c, builder)
+        };
+        let temp2 = big_sigma0.add(majority, builder);
+
+        h = g;
+        g = f;
+        f = e;
+        e = d.add(temp1, builder);
+        d = c;
+        c = b;
+        b = a;
+        a = temp1.add(temp2, builder);
+    }
+
+    for (word, working_word) in state.iter_mut().zip([a, b, c, d, e, f, g, h]) {
+        *word = word.add(working_word, builder);
+    }
+
+    state
+}
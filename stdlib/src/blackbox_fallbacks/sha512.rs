@@ -0,0 +1,319 @@
+//! SHA512 fallback function.
+use super::{
+    radix::{byte_decomposition, Endianness},
+    uint::UInt64,
+    utils::hash_input_bytes,
+    variable_length::{select, MessageLength},
+};
+use crate::helpers::CircuitBuilder;
+use acir::{
+    acir_field::FieldElement,
+    native_types::{Expression, Witness},
+};
+
+/// Number of bytes of the message processed by each application of the compression function.
+const BLOCK_BYTES: usize = 128;
+/// Number of bytes taken by the length of the message at the end of the final block.
+const LENGTH_BYTES: usize = 16;
+const NUM_ROUNDS: usize = 80;
+
+const INITIAL_STATE: [u64; 8] = [
+    0x6a09e667f3bcc908,
+    0xbb67ae8584caa73b,
+    0x3c6ef372fe94f82b,
+    0xa54ff53a5f1d36f1,
+    0x510e527fade682d1,
+    0x9b05688c2b3e6c1f,
+    0x1f83d9abfb41bd6b,
+    0x5be0cd19137e2179,
+];
+
+const ROUND_CONSTANTS: [u64; NUM_ROUNDS] = [
+    0x428a2f98d728ae22,
+    0x7137449123ef65cd,
+    0xb5c0fbcfec4d3b2f,
+    0xe9b5dba58189dbbc,
+    0x3956c25bf348b538,
+    0x59f111f1b605d019,
+    0x923f82a4af194f9b,
+    0xab1c5ed5da6d8118,
+    0xd807aa98a3030242,
+    0x12835b0145706fbe,
+    0x243185be4ee4b28c,
+    0x550c7dc3d5ffb4e2,
+    0x72be5d74f27b896f,
+    0x80deb1fe3b1696b1,
+    0x9bdc06a725c71235,
+    0xc19bf174cf692694,
+    0xe49b69c19ef14ad2,
+    0xefbe4786384f25e3,
+    0x0fc19dc68b8cd5b5,
+    0x240ca1cc77ac9c65,
+    0x2de92c6f592b0275,
+    0x4a7484aa6ea6e483,
+    0x5cb0a9dcbd41fbd4,
+    0x76f988da831153b5,
+    0x983e5152ee66dfab,
+    0xa831c66d2db43210,
+    0xb00327c898fb213f,
+    0xbf597fc7beef0ee4,
+    0xc6e00bf33da88fc2,
+    0xd5a79147930aa725,
+    0x06ca6351e003826f,
+    0x142929670a0e6e70,
+    0x27b70a8546d22ffc,
+    0x2e1b21385c26c926,
+    0x4d2c6dfc5ac42aed,
+    0x53380d139d95b3df,
+    0x650a73548baf63de,
+    0x766a0abb3c77b2a8,
+    0x81c2c92e47edaee6,
+    0x92722c851482353b,
+    0xa2bfe8a14cf10364,
+    0xa81a664bbc423001,
+    0xc24b8b70d0f89791,
+    0xc76c51a30654be30,
+    0xd192e819d6ef5218,
+    0xd69906245565a910,
+    0xf40e35855771202a,
+    0x106aa07032bbd1b8,
+    0x19a4c116b8d2d0c8,
+    0x1e376c085141ab53,
+    0x2748774cdf8eeb99,
+    0x34b0bcb5e19b48a8,
+    0x391c0cb3c5c95a63,
+    0x4ed8aa4ae3418acb,
+    0x5b9cca4f7763e373,
+    0x682e6ff3d6b2b8a3,
+    0x748f82ee5defb2fc,
+    0x78a5636f43172f60,
+    0x84c87814a1f0ab72,
+    0x8cc702081a6439ec,
+    0x90befffa23631e28,
+    0xa4506cebde82bde9,
+    0xbef9a3f7b2c67915,
+    0xc67178f2e372532b,
+    0xca273eceea26619c,
+    0xd186b8c721c0c207,
+    0xeada7dd6cde0eb1e,
+    0xf57d4f7fee6ed178,
+    0x06f067aa72176fba,
+    0x0a637dc5a2c898a6,
+    0x113f9804bef90dae,
+    0x1b710b35131c471b,
+    0x28db77f523047d84,
+    0x32caab7b40c72493,
+    0x3c9ebe0a15c9bebc,
+    0x431d67c49c100d4c,
+    0x4cc5d4becb3e42b6,
+    0x597f299cfc657e2a,
+    0x5fcb6fab3ad6faec,
+    0x6c44198c4a475817,
+];
+
+/// Adds the opcodes constraining `outputs` to be the SHA512 digest of `inputs` to the builder.
+///
+/// Each input is paired with its bit size and contributes its `ceil(num_bits / 8)` bytes in little-endian order.
+pub fn sha512(inputs: Vec<(Expression, u32)>, outputs: Vec<Witness>, builder: &mut CircuitBuilder) {
+    assert_eq!(outputs.len(), 64, "sha512 produces a 64 byte digest");
+    let bytes = hash_input_bytes(inputs, builder);
+
+    let message = bytes.into_iter().map(Expression::from).collect();
+    let states = compress_message(pad(message), builder);
+
+    // The digest is the big-endian encoding of the state.
+    let state = states.last().expect("padded message contains at least one block");
+    for (word, digest_bytes) in state.iter().zip(outputs.chunks(8)) {
+        word.to_le_bytes(digest_bytes.iter().rev().copied().collect(), builder);
+    }
+}
+
+/// Adds the opcodes constraining `outputs` to be the SHA512 digest of the first `var_message_size` bytes
+/// of `inputs`, whose bytes are laid out as in [`sha512`], to the builder.
+///
+/// Every block which may hold the end of the padded message is compressed, the digest being read from the state
+/// after the block which actually does.
+pub fn sha512_variable_length(
+    inputs: Vec<(Expression, u32)>,
+    var_message_size: Expression,
+    outputs: Vec<Witness>,
+    builder: &mut CircuitBuilder,
+) {
+    assert_eq!(outputs.len(), 64, "sha512 produces a 64 byte digest");
+
+    let bytes = hash_input_bytes(inputs, builder);
+    let max_length = bytes.len();
+    let length = MessageLength::new(var_message_size.clone(), max_length, builder);
+
+    // The big-endian bytes of the length of the message in bits.
+    let length_bytes = builder.new_witnesses(LENGTH_BYTES);
+    byte_decomposition(
+        &var_message_size * FieldElement::from(8_u128),
+        &length_bytes,
+        Endianness::Big,
+        builder,
+    );
+
+    // The padded message ends in the block holding the last byte of the length,
+    // which follows the message and its first padding byte.
+    let num_blocks = (max_length + LENGTH_BYTES) / BLOCK_BYTES + 1;
+    let is_final_block: Vec<Witness> = (0..num_blocks)
+        .map(|block| {
+            let block_range = (block * BLOCK_BYTES).saturating_sub(LENGTH_BYTES)
+                ..(block + 1) * BLOCK_BYTES - LENGTH_BYTES;
+            length.is_within(block_range, builder)
+        })
+        .collect();
+
+    let mut padded_message = Vec::with_capacity(num_blocks * BLOCK_BYTES);
+    for index in 0..num_blocks * BLOCK_BYTES {
+        let mut byte = match bytes.get(index) {
+            Some(&byte) => length.mask(index, byte),
+            None => Expression::zero(),
+        };
+        byte = byte.add_mul(FieldElement::from(0x80_u128), &length.equals(index));
+        let offset = index % BLOCK_BYTES;
+        if offset >= BLOCK_BYTES - LENGTH_BYTES {
+            byte.push_multiplication_term(
+                FieldElement::one(),
+                is_final_block[index / BLOCK_BYTES],
+                length_bytes[offset - (BLOCK_BYTES - LENGTH_BYTES)],
+            );
+        }
+        padded_message.push(byte);
+    }
+
+    let states = compress_message(padded_message, builder);
+
+    // The digest is the big-endian encoding of the state.
+    for (word, digest_bytes) in outputs.chunks(8).enumerate() {
+        let candidates: Vec<Witness> = states.iter().map(|state| state[word].inner).collect();
+        let selected = select(&is_final_block, &candidates, builder);
+        UInt64::new(selected).to_le_bytes(digest_bytes.iter().rev().copied().collect(), builder);
+    }
+}
+
+/// Compresses each block of the padded message in turn, returning the state after each block.
+fn compress_message(
+    padded_message: Vec<Expression>,
+    builder: &mut CircuitBuilder,
+) -> Vec<Vec<UInt64>> {
+    let mut state: Vec<UInt64> = INITIAL_STATE
+        .into_iter()
+        .map(|constant| UInt64::load_constant(constant, builder))
+        .collect();
+
+    let mut states = Vec::with_capacity(padded_message.len() / BLOCK_BYTES);
+    for block in padded_message.chunks(BLOCK_BYTES) {
+        state = compress_block(state, block, builder);
+        states.push(state.clone());
+    }
+
+    states
+}
+
+/// Pads the message to a multiple of the block size, appending a single set bit followed by
+/// zeroes and the 128-bit big-endian length of the message in bits.
+fn pad(mut message: Vec<Expression>) -> Vec<Expression> {
+    let message_bits = message.len() as u128 * 8;
+    message.push(Expression::from_field(FieldElement::from(0x80_u128)));
+    while message.len() % BLOCK_BYTES != BLOCK_BYTES - LENGTH_BYTES {
+        message.push(Expression::zero());
+    }
+    message.extend(
+        message_bits
+            .to_be_bytes()
+            .into_iter()
+            .map(|byte| Expression::from_field(FieldElement::from(byte as u128))),
+    );
+    message
+}
+
+/// Applies the SHA512 compression function to `state` for the 128 byte `block`.
+fn compress_block(
+    mut state: Vec<UInt64>,
+    block: &[Expression],
+    builder: &mut CircuitBuilder,
+) -> Vec<UInt64> {
+    // Message schedule
+    let mut schedule = Vec::with_capacity(NUM_ROUNDS);
+    for word_bytes in block.chunks(8) {
+        let le_bytes: Vec<Expression> = word_bytes.iter().rev().cloned().collect();
+        schedule.push(UInt64::from_le_bytes(&le_bytes, builder));
+    }
+    for t in 16..NUM_ROUNDS {
+        let (w2, w15) = (schedule[t - 2], schedule[t - 15]);
+        let s1 = {
+            let r19 = w2.ror(19, builder);
+            let r61 = w2.ror(61, builder);
+            let s6 = w2.shr(6, builder);
+            let x = r19.xor(r61, builder);
+            x.xor(s6, builder)
+        };
+        let s0 = {
+            let r1 = w15.ror(1, builder);
+            let r8 = w15.ror(8, builder);
+            let s7 = w15.shr(7, builder);
+            let x = r1.xor(r8, builder);
+            x.xor(s7, builder)
+        };
+        let sum = s1.add(schedule[t - 7], builder);
+        let sum = sum.add(s0, builder);
+        schedule.push(sum.add(schedule[t - 16], builder));
+    }
+
+    // Compression
+    let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h]: [UInt64; 8] =
+        state.clone().try_into().expect("sha512 state is made of 8 words");
+    for (round_constant, word) in ROUND_CONSTANTS.into_iter().zip(schedule) {
+        let big_sigma1 = {
+            let r14 = e.ror(14, builder);
+            let r18 = e.ror(18, builder);
+            let r41 = e.ror(41, builder);
+            let x = r14.xor(r18, builder);
+            x.xor(r41, builder)
+        };
+        let choice = {
+            let e_and_f = e.and(f, builder);
+            let not_e = e.not(builder);
+            let not_e_and_g = not_e.and(g, builder);
+            e_and_f.xor(not_e_and_g, builder)
+        };
+        let round_constant = UInt64::load_constant(round_constant, builder);
+        let temp1 = h.add(big_sigma1, builder);
+        let temp1 = temp1.add(choice, builder);
+        let temp1 = temp1.add(round_constant, builder);
+        let temp1 = temp1.add(word, builder);
+
+        let big_sigma0 = {
+            let r28 = a.ror(28, builder);
+            let r34 = a.ror(34, builder);
+            let r39 = a.ror(39, builder);
+            let x = r28.xor(r34, builder);
+            x.xor(r39, builder)
+        };
+        let majority = {
+            let a_and_b = a.and(b, builder);
+            let a_and_c = a.and(c, builder);
+            let b_and_c = b.and(c, builder);
+            let x = a_and_b.xor(a_and_c, builder);
+            x.xor(b_and_c, builder)
+        };
+        let temp2 = big_sigma0.add(majority, builder);
+
+        h = g;
+        g = f;
+        f = e;
+        e = d.add(temp1, builder);
+        d = c;
+        c = b;
+        b = a;
+        a = temp1.add(temp2, builder);
+    }
+
+    for (word, working_word) in state.iter_mut().zip([a, b, c, d, e, f, g, h]) {
+        *word = word.add(working_word, builder);
+    }
+
+    state
+}
@@ -0,0 +1,271 @@
+//! Unsigned integers of a fixed bit size held in a single witness, along with the bitwise and
+//! modular arithmetic gadgets the hash function fallbacks are built out of.
+use super::{
+    radix::{byte_decomposition, compose, Endianness},
+    utils::{range_opcode, witness_from_expression},
+};
+use crate::helpers::CircuitBuilder;
+use acir::{
+    acir_field::FieldElement,
+    circuit::{
+        directives::{Directive, QuotientDirective},
+        opcodes::{BlackBoxFuncCall, FunctionInput},
+        Opcode,
+    },
+    native_types::{Expression, Witness},
+};
+
+/// Defines an unsigned integer type of `$num_bits` bits held in a single witness, along with
+/// the gadgets used to build hash functions out of it.
+///
+/// The `@gadgets` form only implements the gadgets, for integer types which are defined separately.
+macro_rules! impl_uint {
+    ($name:ident, $type:ty, $num_bits:expr) => {
+        #[doc = concat!("A ", stringify!($num_bits), "-bit unsigned integer held in a single witness.")]
+        ///
+        /// The witness is assumed to have been constrained to fit within the integer's bit size. Every
+        /// operation adds the opcodes constraining its result to the builder.
+        #[derive(Clone, Copy, Debug)]
+        pub struct $name {
+            pub inner: Witness,
+        }
+
+        impl $name {
+            pub fn new(witness: Witness) -> Self {
+                $name { inner: witness }
+            }
+        }
+
+        impl_uint!(@gadgets $name, $type, $num_bits);
+    };
+    (@gadgets $name:ty, $type:ty, $num_bits:expr $(, const $bits:ident)?) => {
+        impl$(<const $bits: u32>)? $name {
+            const NUM_BITS: u32 = $num_bits;
+            const NUM_BYTES: usize = ($num_bits / 8) as usize;
+
+            /// Returns an integer constrained to be equal to `constant`.
+            pub fn load_constant(constant: $type, builder: &mut CircuitBuilder) -> Self {
+                let constant = FieldElement::from(constant as u128);
+                assert!(constant.num_bits() <= Self::NUM_BITS, "constant does not fit within the integer");
+                Self::new(witness_from_expression(Expression::from_field(constant), builder))
+            }
+
+            /// Returns an integer constrained to be equal to the composition of its little-endian bytes.
+            ///
+            /// The bytes are assumed to have been range constrained by the caller.
+            pub fn from_le_bytes(bytes: &[Expression], builder: &mut CircuitBuilder) -> Self {
+                Self::assert_whole_bytes();
+                assert_eq!(bytes.len(), Self::NUM_BYTES, "unexpected number of bytes");
+                let composed = compose(bytes, FieldElement::from(256_u128), Endianness::Little);
+                Self::new(witness_from_expression(composed, builder))
+            }
+
+            /// Constrains the witnesses `bytes` to be the little-endian bytes of this integer.
+            pub fn to_le_bytes(self, bytes: Vec<Witness>, builder: &mut CircuitBuilder) {
+                Self::assert_whole_bytes();
+                assert_eq!(bytes.len(), Self::NUM_BYTES, "unexpected number of bytes");
+                byte_decomposition(self.inner.into(), &bytes, Endianness::Little, builder);
+            }
+
+            fn assert_whole_bytes() {
+                assert_eq!(Self::NUM_BITS % 8, 0, "integer is not made of whole bytes");
+            }
+
+            /// Returns `self ^ rhs`.
+            pub fn xor(self, rhs: Self, builder: &mut CircuitBuilder) -> Self {
+                self.logic_op(rhs, builder, |lhs, rhs, output| BlackBoxFuncCall::XOR {
+                    lhs,
+                    rhs,
+                    output,
+                })
+            }
+
+            /// Returns `self & rhs`.
+            pub fn and(self, rhs: Self, builder: &mut CircuitBuilder) -> Self {
+                self.logic_op(rhs, builder, |lhs, rhs, output| BlackBoxFuncCall::AND {
+                    lhs,
+                    rhs,
+                    output,
+                })
+            }
+
+            fn logic_op(
+                self,
+                rhs: Self,
+                builder: &mut CircuitBuilder,
+                op: impl FnOnce(FunctionInput, FunctionInput, Witness) -> BlackBoxFuncCall,
+            ) -> Self {
+                let output = builder.new_witness();
+                let call = op(
+                    FunctionInput { witness: self.inner, num_bits: Self::NUM_BITS },
+                    FunctionInput { witness: rhs.inner, num_bits: Self::NUM_BITS },
+                    output,
+                );
+                builder.push(Opcode::BlackBoxFuncCall(call));
+                Self::new(output)
+            }
+
+            /// Returns `!self`, i.e. `2^NUM_BITS - 1 - self`.
+            pub fn not(self, builder: &mut CircuitBuilder) -> Self {
+                let max = power_of_two(Self::NUM_BITS) - FieldElement::one();
+                Self::new(witness_from_expression(max - Expression::from(self.inner), builder))
+            }
+
+            /// Returns `self` rotated left by `rotation` bits.
+            pub fn rol(self, rotation: u32, builder: &mut CircuitBuilder) -> Self {
+                let rotation = rotation % Self::NUM_BITS;
+                if rotation == 0 {
+                    return self;
+                }
+                let low_bits = Self::NUM_BITS - rotation;
+
+                // Split `self` into `high * 2^low_bits + low`, the result is then `low * 2^rotation + high`.
+                let (high, low) = split(self.inner.into(), low_bits, rotation, builder);
+
+                let mut rotated = Expression::from(high);
+                rotated.push_addition_term(power_of_two(rotation), low);
+                Self::new(witness_from_expression(rotated, builder))
+            }
+
+            /// Returns `self` rotated right by `rotation` bits.
+            pub fn ror(self, rotation: u32, builder: &mut CircuitBuilder) -> Self {
+                self.rol(Self::NUM_BITS - rotation % Self::NUM_BITS, builder)
+            }
+
+            /// Returns `self << shift` modulo `2^NUM_BITS`.
+            pub fn shl(self, shift: u32, builder: &mut CircuitBuilder) -> Self {
+                assert!(shift < Self::NUM_BITS, "cannot shift by {shift} bits");
+                if shift == 0 {
+                    return self;
+                }
+                // Only the low `NUM_BITS - shift` bits of `self` remain once shifted.
+                let (_, low) = split(self.inner.into(), Self::NUM_BITS - shift, shift, builder);
+                Self::new(witness_from_expression(&Expression::from(low) * power_of_two(shift), builder))
+            }
+
+            /// Returns `self >> shift`.
+            pub fn shr(self, shift: u32, builder: &mut CircuitBuilder) -> Self {
+                assert!(shift < Self::NUM_BITS, "cannot shift by {shift} bits");
+                let (high, _) = split(self.inner.into(), shift, Self::NUM_BITS - shift, builder);
+                Self::new(high)
+            }
+
+            /// Returns `self + rhs` modulo `2^NUM_BITS`.
+            pub fn add(self, rhs: Self, builder: &mut CircuitBuilder) -> Self {
+                let sum = &Expression::from(self.inner) + &Expression::from(rhs.inner);
+                let (_, low) = split(sum, Self::NUM_BITS, 1, builder);
+                Self::new(low)
+            }
+
+            /// Returns `self - rhs` modulo `2^NUM_BITS`.
+            pub fn sub(self, rhs: Self, builder: &mut CircuitBuilder) -> Self {
+                // Offsetting the difference by `2^NUM_BITS` keeps it positive without changing its low bits.
+                let mut difference = &Expression::from(self.inner) - &Expression::from(rhs.inner);
+                difference.q_c += power_of_two(Self::NUM_BITS);
+                let (_, low) = split(difference, Self::NUM_BITS, 1, builder);
+                Self::new(low)
+            }
+
+            /// Returns `self * rhs` modulo `2^NUM_BITS`.
+            pub fn mul(self, rhs: Self, builder: &mut CircuitBuilder) -> Self {
+                let mut product = Expression::default();
+                let product_bits = if 2 * Self::NUM_BITS < FieldElement::max_num_bits() {
+                    product.push_multiplication_term(FieldElement::one(), self.inner, rhs.inner);
+                    2 * Self::NUM_BITS
+                } else {
+                    // The full product would overflow the field, so both operands are split into halves
+                    // and the product of their high halves, a multiple of `2^NUM_BITS`, is left out.
+                    let low_bits = (Self::NUM_BITS + 1) / 2;
+                    let high_bits = Self::NUM_BITS - low_bits;
+                    let (lhs_high, lhs_low) = split(self.inner.into(), low_bits, high_bits, builder);
+                    let (rhs_high, rhs_low) = split(rhs.inner.into(), low_bits, high_bits, builder);
+
+                    let shift = power_of_two(low_bits);
+                    product.push_multiplication_term(FieldElement::one(), lhs_low, rhs_low);
+                    product.push_multiplication_term(shift, lhs_low, rhs_high);
+                    product.push_multiplication_term(shift, lhs_high, rhs_low);
+                    Self::NUM_BITS + low_bits + 2
+                };
+
+                let (_, low) = split(product, Self::NUM_BITS, product_bits - Self::NUM_BITS, builder);
+                Self::new(low)
+            }
+        }
+    };
+}
+
+impl_uint!(UInt8, u8, 8);
+impl_uint!(UInt16, u16, 16);
+impl_uint!(UInt32, u32, 32);
+impl_uint!(UInt64, u64, 64);
+impl_uint!(UInt128, u128, 128);
+
+/// An unsigned integer of `BITS` bits held in a single witness, for widths without a dedicated type.
+///
+/// As with the other integer types, the witness is assumed to have been constrained to fit within `BITS` bits.
+#[derive(Clone, Copy, Debug)]
+pub struct UIntN<const BITS: u32> {
+    pub inner: Witness,
+}
+
+impl<const BITS: u32> UIntN<BITS> {
+    pub fn new(witness: Witness) -> Self {
+        // Constants are loaded from a `u128` and products must fit within the field once split into halves.
+        assert!(0 < BITS && BITS <= 128, "cannot define an integer of {BITS} bits");
+        UIntN { inner: witness }
+    }
+}
+
+impl_uint!(@gadgets UIntN<BITS>, u128, BITS, const BITS);
+
+// 128-bit arithmetic is often built on top of 64-bit limbs.
+impl UInt128 {
+    /// Returns the `UInt128` equal to `high * 2^64 + low`.
+    pub fn from_u64_limbs(low: UInt64, high: UInt64, builder: &mut CircuitBuilder) -> UInt128 {
+        let limbs = [Expression::from(low.inner), Expression::from(high.inner)];
+        let composed = compose(&limbs, power_of_two(UInt64::NUM_BITS), Endianness::Little);
+        UInt128::new(witness_from_expression(composed, builder))
+    }
+
+    /// Splits this integer into its low and high 64-bit limbs.
+    pub fn to_u64_limbs(self, builder: &mut CircuitBuilder) -> (UInt64, UInt64) {
+        let (high, low) = split(self.inner.into(), UInt64::NUM_BITS, UInt64::NUM_BITS, builder);
+        (UInt64::new(low), UInt64::new(high))
+    }
+}
+
+pub(super) fn power_of_two(exponent: u32) -> FieldElement {
+    FieldElement::from(2_u128).pow(&FieldElement::from(exponent as u128))
+}
+
+/// Splits `value` into `high * 2^low_bits + low` where `low` fits within `low_bits` bits
+/// and `high` fits within `high_bits` bits.
+///
+/// Returns the witnesses `high` and `low`.
+pub(super) fn split(
+    value: Expression,
+    low_bits: u32,
+    high_bits: u32,
+    builder: &mut CircuitBuilder,
+) -> (Witness, Witness) {
+    let high = builder.new_witness();
+    let low = builder.new_witness();
+
+    builder.push(Opcode::Directive(Directive::Quotient(QuotientDirective {
+        a: value.clone(),
+        b: Expression::from_field(power_of_two(low_bits)),
+        q: high,
+        r: low,
+        predicate: None,
+    })));
+    builder.push(range_opcode(high, high_bits));
+    builder.push(range_opcode(low, low_bits));
+
+    let mut split = value;
+    split.push_addition_term(-power_of_two(low_bits), high);
+    split.push_addition_term(-FieldElement::one(), low);
+    split.sort();
+    builder.push(Opcode::AssertZero(split));
+
+    (high, low)
+}
@@ -0,0 +1,83 @@
+use super::radix::{byte_decomposition, round_to_nearest_byte, Endianness};
+use crate::helpers::CircuitBuilder;
+use acir::{
+    acir_field::FieldElement,
+    circuit::{
+        directives::Directive,
+        opcodes::{BlackBoxFuncCall, FunctionInput},
+        Opcode,
+    },
+    native_types::{Expression, Witness},
+};
+
+/// Returns the opcode constraining `witness` to fit within `num_bits` bits.
+pub(crate) fn range_opcode(witness: Witness, num_bits: u32) -> Opcode {
+    Opcode::BlackBoxFuncCall(BlackBoxFuncCall::RANGE { input: FunctionInput { witness, num_bits } })
+}
+
+/// Returns a new witness constrained to be equal to `expr`.
+pub(crate) fn witness_from_expression(expr: Expression, builder: &mut CircuitBuilder) -> Witness {
+    let witness = builder.new_witness();
+    let mut constraint = expr;
+    constraint.push_addition_term(-FieldElement::one(), witness);
+    constraint.sort();
+    builder.push(Opcode::AssertZero(constraint));
+    witness
+}
+
+/// Returns `expr` if it is a single witness, or else a new witness constrained to be equal to it.
+pub(crate) fn to_witness(expr: Expression, builder: &mut CircuitBuilder) -> Witness {
+    match expr.to_witness() {
+        Some(witness) => witness,
+        None => witness_from_expression(expr, builder),
+    }
+}
+
+/// Returns a witness equal to one if `value` is zero and zero otherwise.
+pub(crate) fn is_zero(value: Expression, builder: &mut CircuitBuilder) -> Witness {
+    let value = to_witness(value, builder);
+    let inverse = builder.new_witness();
+    let is_zero = builder.new_witness();
+
+    // `value * inverse = 1 - is_zero` and `value * is_zero = 0`.
+    builder.push(Opcode::Directive(Directive::Invert { x: value, result: inverse }));
+    let mut inverse_constraint = Expression::default();
+    inverse_constraint.push_multiplication_term(FieldElement::one(), value, inverse);
+    inverse_constraint.push_addition_term(FieldElement::one(), is_zero);
+    inverse_constraint.q_c = -FieldElement::one();
+    builder.push(Opcode::AssertZero(inverse_constraint));
+    let mut zero_constraint = Expression::default();
+    zero_constraint.push_multiplication_term(FieldElement::one(), value, is_zero);
+    builder.push(Opcode::AssertZero(zero_constraint));
+
+    is_zero
+}
+
+/// Returns the bytes making up the message of a hash function, constraining each input to fit within its bit size.
+///
+/// Each input contributes the `ceil(num_bits / 8)` bytes of its value in little-endian order,
+/// matching the ACVM when it solves the hash natively.
+pub(crate) fn hash_input_bytes(
+    inputs: Vec<(Expression, u32)>,
+    builder: &mut CircuitBuilder,
+) -> Vec<Witness> {
+    let mut bytes = Vec::new();
+    for (input, num_bits) in inputs {
+        let num_bytes = round_to_nearest_byte(num_bits) as usize;
+        if num_bytes <= 1 {
+            let witness = to_witness(input, builder);
+            builder.push(range_opcode(witness, num_bits));
+            bytes.extend((num_bytes == 1).then_some(witness));
+            continue;
+        }
+
+        let input_bytes = builder.new_witnesses(num_bytes);
+        byte_decomposition(input, &input_bytes, Endianness::Little, builder);
+        // The most significant byte holds fewer than 8 bits if the bit size is not a multiple of 8.
+        if num_bits % 8 != 0 {
+            builder.push(range_opcode(input_bytes[num_bytes - 1], num_bits % 8));
+        }
+        bytes.extend(input_bytes);
+    }
+    bytes
+}
@@ -0,0 +1,85 @@
+//! Gadgets for hashing messages whose length is only known when solving the circuit.
+use super::utils::{is_zero, witness_from_expression};
+use crate::helpers::CircuitBuilder;
+use acir::{
+    acir_field::FieldElement,
+    circuit::Opcode,
+    native_types::{Expression, Witness},
+};
+use std::ops::Range;
+
+/// The length of a message of at most `max_length` bytes, represented by its one-hot encoding.
+pub(crate) struct MessageLength {
+    /// `equals[j]` is one if the length is `j` and zero otherwise.
+    equals: Vec<Witness>,
+    /// `exceeds[i]` is one if the length exceeds `i`, i.e. if byte `i` belongs to the message, and zero otherwise.
+    exceeds: Vec<Witness>,
+}
+
+impl MessageLength {
+    /// Returns the encoding of `length`, which is constrained to be at most `max_length`.
+    pub(crate) fn new(
+        length: Expression,
+        max_length: usize,
+        builder: &mut CircuitBuilder,
+    ) -> MessageLength {
+        let equals: Vec<Witness> = (0..=max_length)
+            .map(|j| {
+                is_zero(&length - &Expression::from_field(FieldElement::from(j as u128)), builder)
+            })
+            .collect();
+
+        // Exactly one of the possible lengths is selected, so the length is at most `max_length`.
+        let mut one_hot_constraint = Expression::from_field(-FieldElement::one());
+        for &is_equal in &equals {
+            one_hot_constraint.push_addition_term(FieldElement::one(), is_equal);
+        }
+        builder.push(Opcode::AssertZero(one_hot_constraint));
+
+        // `exceeds[i] = exceeds[i + 1] + equals[i + 1]`, starting from `exceeds[max_length - 1] = equals[max_length]`.
+        let mut exceeds = vec![Witness::default(); max_length];
+        let mut running_sum = Expression::default();
+        for i in (0..max_length).rev() {
+            running_sum.push_addition_term(FieldElement::one(), equals[i + 1]);
+            let witness = witness_from_expression(running_sum, builder);
+            exceeds[i] = witness;
+            running_sum = Expression::from(witness);
+        }
+
+        MessageLength { equals, exceeds }
+    }
+
+    /// Returns an expression equal to one if the length is `length` and zero otherwise.
+    pub(crate) fn equals(&self, length: usize) -> Expression {
+        self.equals.get(length).map_or_else(Expression::zero, |&witness| witness.into())
+    }
+
+    /// Returns an expression equal to `byte` if byte `index` belongs to the message and zero otherwise.
+    pub(crate) fn mask(&self, index: usize, byte: Witness) -> Expression {
+        let mut masked = Expression::default();
+        masked.push_multiplication_term(FieldElement::one(), self.exceeds[index], byte);
+        masked
+    }
+
+    /// Returns a witness equal to one if the length lies within `range` and zero otherwise.
+    pub(crate) fn is_within(&self, range: Range<usize>, builder: &mut CircuitBuilder) -> Witness {
+        let mut sum = Expression::default();
+        for is_equal in self.equals.iter().take(range.end).skip(range.start) {
+            sum.push_addition_term(FieldElement::one(), *is_equal);
+        }
+        witness_from_expression(sum, builder)
+    }
+}
+
+/// Returns a witness equal to `values[i]`, where `selectors[i]` is the only selector equal to one.
+pub(crate) fn select(
+    selectors: &[Witness],
+    values: &[Witness],
+    builder: &mut CircuitBuilder,
+) -> Witness {
+    let mut selected = Expression::default();
+    for (&selector, &value) in selectors.iter().zip(values) {
+        selected.push_multiplication_term(FieldElement::one(), selector, value);
+    }
+    witness_from_expression(selected, builder)
+}
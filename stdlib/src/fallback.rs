@@ -1,7 +1,13 @@
-use crate::helpers::VariableStore;
+use crate::{
+    blackbox_fallbacks::radix::{self, Endianness},
+    helpers::CircuitBuilder,
+};
 use acir::{
     acir_field::FieldElement,
-    circuit::{directives::Directive, Opcode},
+    circuit::{
+        opcodes::{BlackBoxFuncCall, FunctionInput},
+        Opcode,
+    },
     native_types::{Expression, Witness},
 };
 
@@ -13,68 +19,66 @@ pub fn split(
     num_witness: u32,
     new_gates: &mut Vec<Opcode>,
 ) -> Vec<Witness> {
-    let (extra_gates, bits, _) = bit_decomposition(gate, bit_size, num_witness);
+    let mut builder = CircuitBuilder::new(num_witness);
+    let bits = bit_decomposition(gate, bit_size, &mut builder);
+    let (_, extra_gates) = builder.finalize();
     new_gates.extend(extra_gates);
     bits
 }
 
 // Generates opcodes and directives to bit decompose the input `gate`
-// Returns the bits and the updated witness counter
-// TODO:Ideally, we return the updated witness counter, or we require the input
-// TODO to be a VariableStore. We are not doing this because we want migration to
-// TODO be less painful
+// Returns the bits
 pub(crate) fn bit_decomposition(
     gate: Expression,
     bit_size: u32,
-    mut num_witness: u32,
-) -> (Vec<Opcode>, Vec<Witness>, u32) {
-    let mut new_gates = Vec::new();
-    let mut variables = VariableStore::new(&mut num_witness);
-
+    builder: &mut CircuitBuilder,
+) -> Vec<Witness> {
     // First create a witness for each bit
-    let mut bit_vector = Vec::with_capacity(bit_size as usize);
-    for _ in 0..bit_size {
-        bit_vector.push(variables.new_variable())
-    }
-
-    // Next create a directive which computes those bits.
-    new_gates.push(Opcode::Directive(Directive::ToLeRadix {
-        a: gate.clone(),
-        b: bit_vector.clone(),
-        radix: 2,
-    }));
-
-    // Now apply constraints to the bits such that they are the bit decomposition
-    // of the input and each bit is actually a bit
-    let mut binary_exprs = Vec::new();
-    let mut bit_decomp_constraint = gate;
-    let mut two_pow: FieldElement = FieldElement::one();
-    let two = FieldElement::from(2_i128);
-    for &bit in &bit_vector {
-        // Bit constraint to ensure each bit is a zero or one; bit^2 - bit = 0
-        let mut expr = Expression::default();
-        expr.push_multiplication_term(FieldElement::one(), bit, bit);
-        expr.push_addition_term(-FieldElement::one(), bit);
-        binary_exprs.push(Opcode::Arithmetic(expr));
-
-        // Constraint to ensure that the bits are constrained to be a bit decomposition
-        // of the input
-        // ie \sum 2^i * x_i = input
-        bit_decomp_constraint.push_addition_term(-two_pow, bit);
-        two_pow = two * two_pow;
-    }
+    let bit_vector = builder.new_witnesses(bit_size as usize);
 
-    new_gates.extend(binary_exprs);
-    bit_decomp_constraint.sort(); // TODO: we have an issue open to check if this is needed. Ideally, we remove it.
-    new_gates.push(Opcode::Arithmetic(bit_decomp_constraint));
+    // Next decompose the input into those bits, constraining each of them to actually be a bit
+    radix::bit_decomposition(gate, &bit_vector, Endianness::Little, builder);
 
-    (new_gates, bit_vector, variables.finalize())
+    bit_vector
 }
 
 // Range constraint
-pub fn range(gate: Expression, bit_size: u32, num_witness: u32) -> (u32, Vec<Opcode>) {
-    let (new_gates, _, updated_witness_counter) = bit_decomposition(gate, bit_size, num_witness);
-    (updated_witness_counter, new_gates)
+pub fn range(gate: Expression, bit_size: u32, builder: &mut CircuitBuilder) {
+    bit_decomposition(gate, bit_size, builder);
+}
+
+// Range constraint which decomposes the input into chunks of `chunk_bits` bits rather than into bits,
+// each chunk being range constrained by a `RANGE` opcode of `chunk_bits` bits.
+// This suits backends which can only range constrain small values, e.g. through a lookup table.
+pub fn range_in_chunks(
+    gate: Expression,
+    bit_size: u32,
+    chunk_bits: u32,
+    builder: &mut CircuitBuilder,
+) {
+    assert!(
+        (1..u32::BITS).contains(&chunk_bits),
+        "chunks must fit within a radix of at most 32 bits"
+    );
+    let num_chunks = (bit_size + chunk_bits - 1) / chunk_bits;
+    let chunks = builder.new_witnesses(num_chunks as usize);
+    radix::radix_decomposition(gate, &chunks, 1 << chunk_bits, Endianness::Little, builder);
+
+    // When `bit_size` is not a multiple of `chunk_bits`, the most significant chunk must be further
+    // constrained to its remaining bits. It is shifted to the top of a chunk instead, so that it is
+    // range constrained to `chunk_bits` bits as well.
+    let top_chunk_bits = bit_size - (num_chunks - 1) * chunk_bits;
+    if let Some(&top_chunk) = chunks.last().filter(|_| top_chunk_bits < chunk_bits) {
+        let shifted_chunk = builder.new_witness();
+        let shift = FieldElement::from(1_u128 << (chunk_bits - top_chunk_bits));
+        let mut shift_expr = &Expression::from(top_chunk) * shift;
+        shift_expr.push_addition_term(-FieldElement::one(), shifted_chunk);
+        shift_expr.sort();
+        builder.push(Opcode::AssertZero(shift_expr));
+        builder.push(Opcode::BlackBoxFuncCall(BlackBoxFuncCall::RANGE {
+            input: FunctionInput { witness: shifted_chunk, num_bits: chunk_bits },
+        }));
+    }
 }
 
 pub fn and(
@@ -82,15 +86,12 @@ pub fn and(
     b: Expression,
     result: Witness,
     bit_size: u32,
-    num_witness: u32,
-) -> (u32, Vec<Opcode>) {
+    builder: &mut CircuitBuilder,
+) {
     // Decompose the operands into bits
     //
-    let (extra_gates_a, a_bits, updated_witness_counter) =
-        bit_decomposition(a, bit_size, num_witness);
-
-    let (extra_gates_b, b_bits, updated_witness_counter) =
-        bit_decomposition(b, bit_size, updated_witness_counter);
+    let a_bits = bit_decomposition(a, bit_size, builder);
+    let b_bits = bit_decomposition(b, bit_size, builder);
 
     assert_eq!(a_bits.len(), b_bits.len());
     assert_eq!(a_bits.len(), bit_size as usize);
@@ -110,13 +111,7 @@ pub fn and(
     and_expr.push_addition_term(-FieldElement::one(), result);
 
     and_expr.sort();
-
-    let mut new_gates = Vec::new();
-    new_gates.extend(extra_gates_a);
-    new_gates.extend(extra_gates_b);
-    new_gates.push(Opcode::Arithmetic(and_expr));
-
-    (updated_witness_counter, new_gates)
+    builder.push(Opcode::AssertZero(and_expr));
 }
 
 pub fn xor(
@@ -124,14 +119,12 @@ pub fn xor(
     b: Expression,
     result: Witness,
     bit_size: u32,
-    num_witness: u32,
-) -> (u32, Vec<Opcode>) {
+    builder: &mut CircuitBuilder,
+) {
     // Decompose the operands into bits
     //
-    let (extra_gates_a, a_bits, updated_witness_counter) =
-        bit_decomposition(a, bit_size, num_witness);
-    let (extra_gates_b, b_bits, updated_witness_counter) =
-        bit_decomposition(b, bit_size, updated_witness_counter);
+    let a_bits = bit_decomposition(a, bit_size, builder);
+    let b_bits = bit_decomposition(b, bit_size, builder);
 
     assert_eq!(a_bits.len(), b_bits.len());
     assert_eq!(a_bits.len(), bit_size as usize);
@@ -151,10 +144,69 @@ pub fn xor(
     xor_expr.push_addition_term(-FieldElement::one(), result);
 
     xor_expr.sort();
-    let mut new_gates = Vec::new();
-    new_gates.extend(extra_gates_a);
-    new_gates.extend(extra_gates_b);
-    new_gates.push(Opcode::Arithmetic(xor_expr));
+    builder.push(Opcode::AssertZero(xor_expr));
+}
+
+pub fn not(a: Expression, result: Witness, bit_size: u32, builder: &mut CircuitBuilder) {
+    // Range constrain the operand so that its complement fits within `bit_size` bits
+    range(a.clone(), bit_size, builder);
+
+    // Flipping every bit of `a` is the same as subtracting it from the all-ones value;
+    // ie result = (2^bit_size - 1) - a
+    let all_ones =
+        FieldElement::from(2_i128).pow(&FieldElement::from(bit_size as i128)) - FieldElement::one();
+    let mut not_expr = &a + &Expression::from(result);
+    not_expr.q_c -= all_ones;
+    not_expr.sort();
+    builder.push(Opcode::AssertZero(not_expr));
+}
+
+pub fn shl(
+    a: Expression,
+    result: Witness,
+    shift: u32,
+    bit_size: u32,
+    builder: &mut CircuitBuilder,
+) {
+    // Bits shifted past `bit_size` are discarded, so only the low bits of `a` contribute to the result
+    // ie result = \sum_{i + shift < bit_size} 2^(i + shift) * a_i
+    shift_bits(a, result, bit_size, builder, |index| {
+        (index + shift < bit_size).then_some(index + shift)
+    });
+}
+
+pub fn shr(
+    a: Expression,
+    result: Witness,
+    shift: u32,
+    bit_size: u32,
+    builder: &mut CircuitBuilder,
+) {
+    // The low `shift` bits of `a` are discarded
+    // ie result = \sum_{i >= shift} 2^(i - shift) * a_i
+    shift_bits(a, result, bit_size, builder, |index| index.checked_sub(shift));
+}
+
+// Decomposes `a` into bits and constrains `result` to be their recomposition, with the bit at index `i`
+// moved to the position given by `position(i)`, or dropped if there is none
+fn shift_bits(
+    a: Expression,
+    result: Witness,
+    bit_size: u32,
+    builder: &mut CircuitBuilder,
+    position: impl Fn(u32) -> Option<u32>,
+) {
+    let a_bits = bit_decomposition(a, bit_size, builder);
+
+    let two = FieldElement::from(2_i128);
+    let mut shift_expr = Expression::default();
+    for (index, bit) in (0..bit_size).zip(a_bits) {
+        if let Some(position) = position(index) {
+            shift_expr.push_addition_term(two.pow(&FieldElement::from(position as i128)), bit);
+        }
+    }
+    shift_expr.push_addition_term(-FieldElement::one(), result);
 
-    (updated_witness_counter, new_gates)
+    shift_expr.sort();
+    builder.push(Opcode::AssertZero(shift_expr));
 }
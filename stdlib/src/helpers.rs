@@ -1,4 +1,4 @@
-use acir::native_types::Witness;
+use acir::{circuit::Opcode, native_types::Witness};
 
 // Simple helper struct to keep track of the current witness index
 // and create variables
@@ -21,3 +21,59 @@ impl<'a> VariableStore<'a> {
         *self.witness_index
     }
 }
+
+/// Allocates the witnesses of a fallback and collects the opcodes constraining them.
+///
+/// Gadgets take the builder by mutable reference, so that they can be composed
+/// without threading the witness counter and opcodes through every call.
+#[derive(Debug, Default)]
+pub struct CircuitBuilder {
+    /// Index of the next witness to be allocated.
+    num_witness: u32,
+    opcodes: Vec<Opcode>,
+}
+
+impl CircuitBuilder {
+    /// Returns a builder allocating witnesses from index `num_witness` onwards.
+    pub fn new(num_witness: u32) -> Self {
+        CircuitBuilder { num_witness, opcodes: Vec::new() }
+    }
+
+    /// Allocates a new witness.
+    pub fn new_witness(&mut self) -> Witness {
+        let witness = Witness(self.num_witness);
+        self.num_witness += 1;
+        witness
+    }
+
+    /// Allocates `count` new witnesses.
+    pub fn new_witnesses(&mut self, count: usize) -> Vec<Witness> {
+        (0..count).map(|_| self.new_witness()).collect()
+    }
+
+    /// Appends `opcode` to the opcodes built so far.
+    pub fn push(&mut self, opcode: Opcode) {
+        self.opcodes.push(opcode);
+    }
+
+    /// Returns the index of the next witness to be allocated.
+    pub fn num_witness(&self) -> u32 {
+        self.num_witness
+    }
+
+    /// Returns the opcodes built so far.
+    pub fn opcodes(&self) -> &[Opcode] {
+        &self.opcodes
+    }
+
+    /// Consumes the builder, returning the index of the next witness to be allocated along with the opcodes.
+    pub fn finalize(self) -> (u32, Vec<Opcode>) {
+        (self.num_witness, self.opcodes)
+    }
+}
+
+impl Extend<Opcode> for CircuitBuilder {
+    fn extend<T: IntoIterator<Item = Opcode>>(&mut self, iter: T) {
+        self.opcodes.extend(iter);
+    }
+}
@@ -1,5 +1,6 @@
 #![warn(unused_crate_dependencies)]
 #![warn(unreachable_pub)]
 
+pub mod blackbox_fallbacks;
 pub mod fallback;
 pub mod helpers;